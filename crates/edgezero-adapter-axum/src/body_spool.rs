@@ -0,0 +1,125 @@
+//! Temporary-file spool for large request bodies.
+//!
+//! With `body-spill-bytes` set, the router writes buffered bodies past the
+//! threshold to a [`TempFileSpool`] instead of holding them in memory; see
+//! [`edgezero_core::body_spool`]. Each body gets its own file, removed once
+//! the request and every copy of its [`SpilledBody`] are dropped. File IO
+//! is blocking, as in the other dev-server stores.
+//!
+//! [`SpilledBody`]: edgezero_core::body_spool::SpilledBody
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Read, Write as _};
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use edgezero_core::body_spool::{BodySpool, SpoolFile};
+use edgezero_core::error::EdgeError;
+
+/// Distinguishes concurrent requests' spool files within this process.
+static SPOOL_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// A [`BodySpool`] writing each body to a file in a directory.
+#[derive(Clone, Debug)]
+pub struct TempFileSpool {
+    dir: PathBuf,
+}
+
+/// One spilled body's file, deleted on drop.
+struct TempSpoolFile {
+    file: File,
+    path: PathBuf,
+}
+
+impl TempFileSpool {
+    /// A spool writing to `dir`, which must exist.
+    #[must_use]
+    #[inline]
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl Default for TempFileSpool {
+    /// A spool in the system temporary directory.
+    #[inline]
+    fn default() -> Self {
+        Self::new(env::temp_dir())
+    }
+}
+
+impl BodySpool for TempFileSpool {
+    #[inline]
+    fn create(&self) -> Result<Box<dyn SpoolFile>, EdgeError> {
+        let seq = SPOOL_SEQ.fetch_add(1, Ordering::Relaxed);
+        let path = self
+            .dir
+            .join(format!("edgezero-body-{}-{seq}", process::id()));
+        let file = File::options()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|err| spool_error("create", &err))?;
+        Ok(Box::new(TempSpoolFile { file, path }))
+    }
+}
+
+impl Drop for TempSpoolFile {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_file(&self.path) {
+            log::warn!(
+                "failed to remove spooled body {}: {err}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+impl SpoolFile for TempSpoolFile {
+    fn append(&mut self, chunk: &[u8]) -> Result<(), EdgeError> {
+        self.file
+            .write_all(chunk)
+            .map_err(|err| spool_error("write", &err))
+    }
+
+    fn reader(&self) -> Result<Box<dyn Read + '_>, EdgeError> {
+        let file = File::open(&self.path).map_err(|err| spool_error("read", &err))?;
+        Ok(Box::new(file))
+    }
+}
+
+fn spool_error(action: &str, err: &io::Error) -> EdgeError {
+    EdgeError::internal(anyhow::anyhow!("failed to {action} spooled body: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_back(file: &dyn SpoolFile) -> String {
+        let mut read = String::new();
+        file.reader()
+            .expect("reader")
+            .read_to_string(&mut read)
+            .expect("read");
+        read
+    }
+
+    #[test]
+    fn spooled_bodies_read_back_whole_and_are_removed_on_drop() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let spool = TempFileSpool::new(dir.path());
+
+        let mut file = spool.create().expect("spool file");
+        file.append(b"hello, ").expect("append");
+        file.append(b"spool").expect("append");
+        assert_eq!(read_back(file.as_ref()), "hello, spool");
+        assert_eq!(read_back(file.as_ref()), "hello, spool");
+        assert_eq!(fs::read_dir(dir.path()).expect("list").count(), 1);
+
+        drop(file);
+        assert_eq!(fs::read_dir(dir.path()).expect("list").count(), 0);
+    }
+}
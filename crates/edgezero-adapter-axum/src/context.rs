@@ -20,6 +20,15 @@ impl AxumRequestContext {
     }
 }
 
+/// Both ends of an accepted TCP connection. The dev server records this as
+/// axum connect info so [`into_core_request`](crate::request::into_core_request)
+/// can fill in [`ConnectionInfo`](edgezero_core::connection::ConnectionInfo).
+#[derive(Clone, Copy, Debug)]
+pub struct TcpConnectInfo {
+    pub local_addr: Option<SocketAddr>,
+    pub peer_addr: SocketAddr,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
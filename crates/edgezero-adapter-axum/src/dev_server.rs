@@ -1,17 +1,32 @@
 use std::fs;
+use std::future::Future;
+use std::io::{self, IoSlice};
 #[cfg(test)]
 use std::iter;
 use std::net::{SocketAddr, TcpListener as StdTcpListener};
 use std::path::{Path, PathBuf};
+use std::pin::{Pin, pin};
 use std::str::FromStr as _;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::task::{Context as TaskContext, Poll};
+use std::time::Duration;
 
 use anyhow::Context as _;
 use axum::Router;
-use tokio::net::TcpListener as TokioTcpListener;
+use axum::extract::connect_info::ConnectInfo;
+use futures::future::{self, Either};
+use hyper::body::Incoming;
+use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
+use hyper_util::server::conn::auto::Builder;
+use hyper_util::server::graceful::GracefulConnection;
+use hyper_util::service::TowerToHyperService;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener as TokioTcpListener, TcpStream};
 use tokio::runtime::Builder as RuntimeBuilder;
-use tokio::signal;
-use tower::{Service as _, service_fn};
+use tokio::sync::watch;
+use tokio::{signal, time};
+use tower::{Service as _, ServiceExt as _, service_fn};
 
 use edgezero_core::addr;
 use edgezero_core::app::{Hooks, StoreMetadata, StoresMetadata};
@@ -28,10 +43,16 @@ use simple_logger::SimpleLogger;
 use std::collections::BTreeMap;
 
 use crate::config_store::AxumConfigStore;
+use crate::context::TcpConnectInfo;
+use crate::dotenv;
+use crate::in_flight::InFlight;
 use crate::key_value_store::PersistentKvStore;
 use crate::secret_store::EnvSecretStore;
 use crate::service::EdgeZeroAxumService;
 
+/// How long shutdown waits for in-flight requests by default.
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum KvInitRequirement {
     Optional,
@@ -42,7 +63,22 @@ enum KvInitRequirement {
 #[derive(Clone)]
 pub struct AxumDevServerConfig {
     pub addr: SocketAddr,
+    /// After Ctrl-C, how long to wait for in-flight requests to finish
+    /// before exiting anyway. See [`crate::in_flight`].
+    pub drain_timeout: Duration,
     pub enable_ctrl_c: bool,
+    /// Also accept HTTP/2 over cleartext (h2c, prior knowledge) on the same
+    /// port. Off by default, so only HTTP/1.1 is served.
+    pub http2: bool,
+    /// How often to ping HTTP/2 clients; a connection whose ping goes
+    /// unanswered is closed. `None` (the default) sends no pings.
+    pub http2_keep_alive_interval: Option<Duration>,
+    /// Close a connection (HTTP/1.1 or HTTP/2) that has read or written
+    /// nothing and had no request in progress for this long. `None` (the
+    /// default) keeps idle connections open.
+    pub idle_timeout: Option<Duration>,
+    /// Keep HTTP/1.1 connections open between requests. On by default.
+    pub keep_alive: bool,
 }
 
 impl Default for AxumDevServerConfig {
@@ -50,7 +86,12 @@ impl Default for AxumDevServerConfig {
     fn default() -> Self {
         Self {
             addr: SocketAddr::from((addr::DEFAULT_HOST, addr::DEFAULT_PORT)),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
             enable_ctrl_c: true,
+            http2: false,
+            http2_keep_alive_interval: None,
+            idle_timeout: None,
+            keep_alive: true,
         }
     }
 }
@@ -69,6 +110,24 @@ struct Stores {
     secrets: Option<SecretHandle>,
 }
 
+/// When a connection last read or wrote anything, and how many of its
+/// requests are still being handled. Drives
+/// [`AxumDevServerConfig::idle_timeout`].
+struct Activity {
+    busy: AtomicUsize,
+    last_active_ms: AtomicU64,
+    opened: time::Instant,
+}
+
+/// Marks one request on a connection as in progress until dropped.
+struct Busy(Arc<Activity>);
+
+/// A connection's socket, recording its reads and writes in [`Activity`].
+struct TrackedStream {
+    activity: Arc<Activity>,
+    stream: TcpStream,
+}
+
 /// Blocking dev server runner used by the `EdgeZero` CLI.
 pub struct AxumDevServer {
     config: AxumDevServerConfig,
@@ -116,7 +175,8 @@ impl AxumDevServer {
         let listener = TokioTcpListener::from_std(std_listener)
             .context("failed to adopt std listener into tokio")?;
 
-        serve_with_stores(router, listener, config.enable_ctrl_c, stores).await
+        let shutdown = config.enable_ctrl_c.then_some(ctrl_c());
+        serve_with_stores(router, listener, shutdown, &config, stores).await
     }
 
     #[cfg(test)]
@@ -126,7 +186,26 @@ impl AxumDevServer {
             config,
             stores,
         } = self;
-        serve_with_stores(router, listener, config.enable_ctrl_c, stores).await
+        let shutdown = config.enable_ctrl_c.then_some(ctrl_c());
+        serve_with_stores(router, listener, shutdown, &config, stores).await
+    }
+
+    /// Serve until `shutdown` resolves, then drain as on Ctrl-C.
+    #[cfg(test)]
+    async fn run_with_shutdown<F>(
+        self,
+        listener: TokioTcpListener,
+        shutdown: F,
+    ) -> anyhow::Result<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let AxumDevServer {
+            router,
+            config,
+            stores,
+        } = self;
+        serve_with_stores(router, listener, Some(shutdown), &config, stores).await
     }
 
     #[must_use]
@@ -192,6 +271,113 @@ impl AxumDevServer {
     }
 }
 
+impl Activity {
+    /// Resolve once no request has been in progress and nothing has been
+    /// read or written for `timeout`.
+    async fn idle_for(&self, timeout: Duration) {
+        loop {
+            let last_active = Duration::from_millis(self.last_active_ms.load(Ordering::Relaxed));
+            let Some(deadline) = self
+                .opened
+                .checked_add(last_active)
+                .and_then(|instant| instant.checked_add(timeout))
+            else {
+                return future::pending().await;
+            };
+            if time::Instant::now() < deadline {
+                time::sleep_until(deadline).await;
+            } else if self.busy.load(Ordering::Acquire) > 0 {
+                time::sleep(timeout).await;
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn new() -> Self {
+        Self {
+            busy: AtomicUsize::new(0),
+            last_active_ms: AtomicU64::new(0),
+            opened: time::Instant::now(),
+        }
+    }
+
+    fn touch(&self) {
+        let elapsed = u64::try_from(self.opened.elapsed().as_millis()).unwrap_or(u64::MAX);
+        self.last_active_ms.store(elapsed, Ordering::Relaxed);
+    }
+}
+
+impl Busy {
+    fn start(activity: Arc<Activity>) -> Self {
+        activity.busy.fetch_add(1, Ordering::AcqRel);
+        Self(activity)
+    }
+}
+
+impl Drop for Busy {
+    fn drop(&mut self) {
+        self.0.touch();
+        self.0.busy.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl AsyncRead for TrackedStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+        let polled = Pin::new(&mut this.stream).poll_read(cx, buf);
+        if buf.filled().len() > filled {
+            this.activity.touch();
+        }
+        polled
+    }
+}
+
+impl AsyncWrite for TrackedStream {
+    fn is_write_vectored(&self) -> bool {
+        self.stream.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().stream).poll_shutdown(cx)
+    }
+
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let polled = Pin::new(&mut this.stream).poll_write(cx, buf);
+        if matches!(polled, Poll::Ready(Ok(written)) if written > 0) {
+            this.activity.touch();
+        }
+        polled
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let polled = Pin::new(&mut this.stream).poll_write_vectored(cx, bufs);
+        if matches!(polled, Poll::Ready(Ok(written)) if written > 0) {
+            this.activity.touch();
+        }
+        polled
+    }
+}
+
 fn kv_init_requirement(stores: StoresMetadata) -> KvInitRequirement {
     if stores.kv.is_some() {
         KvInitRequirement::Required
@@ -268,15 +454,37 @@ fn kv_handle_from_path(kv_path: &Path) -> anyhow::Result<KvHandle> {
     }
     let kv_store = Arc::new(PersistentKvStore::new(kv_path).context("failed to create KV store")?);
     log::info!("KV store: {}", kv_path.display());
-    Ok(KvHandle::new(kv_store))
+    // Local data only, so name the keys each request touches in debug logs.
+    Ok(KvHandle::new(kv_store).with_key_logging(true))
+}
+
+async fn ctrl_c() {
+    let _ctrl_c = signal::ctrl_c().await;
+}
+
+/// Wait for shutdown to start, then for in-flight requests to finish.
+/// Returns `false` if some were still running after `drain_timeout`.
+async fn drain(in_flight: &InFlight, drain_timeout: Duration) -> bool {
+    in_flight.wait_draining().await;
+    log::info!(
+        "shutting down; draining {} in-flight request(s)",
+        in_flight.count()
+    );
+    time::timeout(drain_timeout, in_flight.wait_idle())
+        .await
+        .is_ok()
 }
 
-async fn serve_with_stores(
+async fn serve_with_stores<F>(
     router: RouterService,
     listener: TokioTcpListener,
-    enable_ctrl_c: bool,
+    shutdown: Option<F>,
+    config: &AxumDevServerConfig,
     stores: Stores,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
     let service = {
         let mut service = EdgeZeroAxumService::new(router);
         if let Some(registry) = stores.config_registry {
@@ -299,38 +507,185 @@ async fn serve_with_stores(
         }
         service
     };
+    let in_flight = service.in_flight();
     let axum_router = Router::new().fallback_service(service_fn(move |req| {
         let mut svc = service.clone();
         async move { svc.call(req).await }
     }));
-    let make_service = axum_router.into_make_service_with_connect_info::<SocketAddr>();
 
-    let shutdown = enable_ctrl_c.then_some(async {
-        let _ctrl_c = signal::ctrl_c().await;
-    });
-
-    let server = axum::serve(listener, make_service);
-    if let Some(shutdown_signal) = shutdown {
-        let graceful_server = server.with_graceful_shutdown(shutdown_signal);
-        graceful_server.await.context("axum server error")?;
-    } else {
-        server.await.context("axum server error")?;
+    let draining = in_flight.clone();
+    let shutdown_signal = async move {
+        match shutdown {
+            Some(signal) => signal.await,
+            None => future::pending().await,
+        }
+        draining.start_draining();
+    };
+    let mut serving = pin!(accept_connections(
+        listener,
+        axum_router,
+        connection_builder(config),
+        config.idle_timeout,
+        shutdown_signal,
+    ));
+    let drained = pin!(drain(&in_flight, config.drain_timeout));
+    match future::select(serving.as_mut(), drained).await {
+        Either::Left(((), _)) => {}
+        // Nothing is in flight; let hyper close the idle connections.
+        Either::Right((true, _)) => serving.await,
+        Either::Right((false, _)) => log::warn!(
+            "{} request(s) still in flight after {:?}; exiting anyway",
+            in_flight.count(),
+            config.drain_timeout
+        ),
     }
 
     Ok(())
 }
 
+/// The hyper connection builder for `config`.
+fn connection_builder(config: &AxumDevServerConfig) -> Builder<TokioExecutor> {
+    let mut builder = Builder::new(TokioExecutor::new());
+    builder.http1().keep_alive(config.keep_alive);
+    if !config.http2 {
+        return builder.http1_only();
+    }
+    builder
+        .http2()
+        .timer(TokioTimer::new())
+        .keep_alive_interval(config.http2_keep_alive_interval);
+    builder
+}
+
+/// Serve connections from `listener` until `shutdown` resolves, then stop
+/// accepting and wait for the open connections to close gracefully.
+async fn accept_connections<F>(
+    listener: TokioTcpListener,
+    app: Router,
+    builder: Builder<TokioExecutor>,
+    idle_timeout: Option<Duration>,
+    shutdown: F,
+) where
+    F: Future<Output = ()>,
+{
+    let (stop, stopped) = watch::channel(());
+    let mut shutdown_signal = pin!(shutdown);
+    loop {
+        let accepted = match future::select(pin!(listener.accept()), shutdown_signal.as_mut()).await
+        {
+            Either::Left((accepted, _)) => accepted,
+            Either::Right(((), _)) => break,
+        };
+        let (stream, peer_addr) = match accepted {
+            Ok(connection) => connection,
+            Err(err) => {
+                handle_accept_error(&err).await;
+                continue;
+            }
+        };
+        let info = TcpConnectInfo {
+            local_addr: stream.local_addr().ok(),
+            peer_addr,
+        };
+        let activity = Arc::new(Activity::new());
+        let busy = Arc::clone(&activity);
+        let service = app
+            .clone()
+            .map_request(move |mut req: http::Request<Incoming>| {
+                req.extensions_mut().insert(ConnectInfo(info));
+                req
+            })
+            .map_future(move |response| {
+                let request = Busy::start(Arc::clone(&busy));
+                async move {
+                    let _request = request;
+                    response.await
+                }
+            });
+        let tracked = TrackedStream {
+            activity: Arc::clone(&activity),
+            stream,
+        };
+        let connection = builder
+            .serve_connection(TokioIo::new(tracked), TowerToHyperService::new(service))
+            .into_owned();
+        let closing = stopped.clone();
+        tokio::spawn(async move {
+            let served = serve_connection(connection, &activity, idle_timeout, closing).await;
+            if let Err(err) = served {
+                log::debug!("connection from {peer_addr} failed: {err}");
+            }
+        });
+    }
+    drop(listener);
+    drop(stopped);
+    let _sent = stop.send(());
+    stop.closed().await;
+}
+
+/// Drive `connection` to completion, shutting it down gracefully (letting
+/// any request in progress finish) once the server stops or the connection
+/// has been idle for `idle_timeout`.
+async fn serve_connection<C>(
+    connection: C,
+    activity: &Activity,
+    idle_timeout: Option<Duration>,
+    mut stopped: watch::Receiver<()>,
+) -> C::Output
+where
+    C: GracefulConnection,
+{
+    let mut serving = pin!(connection);
+    let stop = pin!(async {
+        let closed = pin!(stopped.changed());
+        match idle_timeout {
+            Some(timeout) => {
+                future::select(closed, pin!(activity.idle_for(timeout))).await;
+            }
+            None => {
+                let _closed = closed.await;
+            }
+        }
+    });
+    if let Either::Left((result, _)) = future::select(serving.as_mut(), stop).await {
+        return result;
+    }
+    serving.as_mut().graceful_shutdown();
+    serving.await
+}
+
+/// Resets by a departing client are routine. Anything else (e.g. running out
+/// of file descriptors) is logged, and accepting pauses briefly so the loop
+/// does not spin.
+async fn handle_accept_error(err: &io::Error) {
+    if matches!(
+        err.kind(),
+        io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+    ) {
+        return;
+    }
+    log::error!("accept error: {err}");
+    time::sleep(Duration::from_secs(1)).await;
+}
+
 /// Entry point for an Axum dev-server application.
 ///
 /// Portable store config is baked into `A` by the `app!` macro; adapter-specific
 /// values (platform store names, bind host/port, logging level) are read at
 /// runtime from `EDGEZERO__*` environment variables. No `edgezero.toml` is
-/// required.
+/// required. A `.env` file is loaded first (see [`crate::dotenv`]), without
+/// overriding variables that are already set.
 ///
 /// # Errors
-/// Returns an error if the dev server fails to bind or any required store handle cannot be initialised.
+/// Returns an error if the `.env` file is invalid, the app's settings do not
+/// load, the app fails `App::validate` or a middleware setup, the dev server
+/// fails to bind, or any required store handle cannot be initialised.
 #[inline]
-pub fn run_app<A: Hooks>() -> anyhow::Result<()> {
+pub fn run_app<A: Hooks + 'static>() -> anyhow::Result<()> {
+    // Before anything else reads the environment or spawns a thread.
+    let dotenv = dotenv::load()?;
     let env = EnvConfig::from_env();
     let stores = A::stores();
     let kv_init_requirement = kv_init_requirement(stores);
@@ -343,14 +698,19 @@ pub fn run_app<A: Hooks>() -> anyhow::Result<()> {
     if !A::owns_logging() {
         let _logger_init = SimpleLogger::new().with_level(level).init();
     }
+    if let Some((path, applied)) = dotenv {
+        log::info!(
+            "[edgezero] loaded {applied} variable(s) from {}",
+            path.display()
+        );
+    }
 
     let resolution = resolve_addr(&env);
     for warning in &resolution.warnings {
         log::warn!("{warning}");
     }
     let addr = resolution.addr;
-    let app = A::build_app();
-    let router = app.router().clone();
+    let app = A::build_validated_app()?;
 
     log::info!("[edgezero] starting axum server on http://{addr}");
 
@@ -360,6 +720,7 @@ pub fn run_app<A: Hooks>() -> anyhow::Result<()> {
         .context("failed to build tokio runtime")?;
 
     runtime.block_on(async move {
+        let router = app.ready().await?.into_router();
         let std_listener = StdTcpListener::bind(addr)
             .with_context(|| format!("failed to bind dev server to {addr}"))?;
         std_listener
@@ -378,7 +739,11 @@ pub fn run_app<A: Hooks>() -> anyhow::Result<()> {
             secret_registry,
             ..Stores::default()
         };
-        serve_with_stores(router, listener, true, request_stores).await
+        let config = AxumDevServerConfig {
+            addr,
+            ..AxumDevServerConfig::default()
+        };
+        serve_with_stores(router, listener, Some(ctrl_c()), &config, request_stores).await
     })
 }
 
@@ -535,6 +900,15 @@ mod tests {
         assert!(config.enable_ctrl_c);
     }
 
+    #[test]
+    fn default_config_serves_http1_with_keep_alive() {
+        let config = AxumDevServerConfig::default();
+        assert!(!config.http2);
+        assert!(config.keep_alive);
+        assert_eq!(config.idle_timeout, None);
+        assert_eq!(config.http2_keep_alive_interval, None);
+    }
+
     #[test]
     fn config_can_be_cloned() {
         let config = AxumDevServerConfig::default();
@@ -548,7 +922,9 @@ mod tests {
         let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
         let config = AxumDevServerConfig {
             addr,
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
             enable_ctrl_c: false,
+            ..AxumDevServerConfig::default()
         };
         assert_eq!(config.addr.ip(), IpAddr::V4(Ipv4Addr::UNSPECIFIED));
         assert_eq!(config.addr.port(), 3000);
@@ -572,7 +948,9 @@ mod tests {
         let router = RouterService::builder().build();
         let config = AxumDevServerConfig {
             addr: SocketAddr::from(([127, 0, 0, 1], 9000)),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
             enable_ctrl_c: false,
+            ..AxumDevServerConfig::default()
         };
         let server = AxumDevServer::with_config(router, config);
         assert_eq!(server.config.addr.port(), 9000);
@@ -735,15 +1113,23 @@ mod tests {
 #[cfg(test)]
 mod integration_tests {
     use super::*;
+    use bytes::Bytes;
     use edgezero_core::action;
+    use edgezero_core::body::Body;
     use edgezero_core::context::RequestContext;
     use edgezero_core::error::EdgeError;
-    use edgezero_core::extractor::Secrets;
+    use edgezero_core::extractor::{ClientIp, Secrets};
+    use edgezero_core::http::response_builder;
+    use edgezero_core::response::ResponseExt as _;
     use edgezero_core::router::RouterService;
     use edgezero_core::secret_store::SecretHandle as CoreSecretHandle;
-    use std::time::{Duration, Instant};
+    use futures::future::join_all;
+    use futures::stream;
+    use std::iter;
+    use std::time::Instant;
+    use tokio::sync::{Notify, oneshot};
     use tokio::task::{JoinHandle, spawn_blocking};
-    use tokio::time::sleep;
+    use tokio::time::{sleep, timeout};
 
     struct TestServer {
         _temp_dir: tempfile::TempDir,
@@ -763,7 +1149,9 @@ mod integration_tests {
         let addr = listener.local_addr().expect("local addr");
         let config = AxumDevServerConfig {
             addr,
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
             enable_ctrl_c: false,
+            ..AxumDevServerConfig::default()
         };
         // Use a unique temp directory for each test server
         let temp_dir = tempfile::tempdir().expect("create temp dir");
@@ -823,6 +1211,308 @@ mod integration_tests {
         server.handle.abort();
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn handlers_see_connection_peer_and_local_addresses() {
+        async fn handler(ctx: RequestContext) -> Result<String, EdgeError> {
+            let info = ctx
+                .connection_info()
+                .ok_or_else(|| EdgeError::internal(anyhow::anyhow!("no connection info")))?;
+            let peer = info.peer_addr.map(|addr| addr.ip().to_string());
+            let local = info.local_addr.map(|addr| addr.to_string());
+            Ok(format!(
+                "{} {} {}",
+                peer.unwrap_or_default(),
+                local.unwrap_or_default(),
+                info.is_tls()
+            ))
+        }
+
+        let router = RouterService::builder().get("/conn", handler).build();
+        let server = start_test_server(router).await;
+
+        let client = reqwest::Client::new();
+        let url = format!("{}/conn", server.base_url);
+        let response = send_with_retry(&client, |http_client| http_client.get(url.as_str())).await;
+
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let local = server.base_url.trim_start_matches("http://");
+        assert_eq!(
+            response.text().await.unwrap(),
+            format!("127.0.0.1 {local} false")
+        );
+
+        server.handle.abort();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn client_ip_extractor_reads_the_peer_address() {
+        #[action]
+        async fn handler(ClientIp(ip): ClientIp) -> String {
+            ip.to_string()
+        }
+
+        let router = RouterService::builder().get("/ip", handler).build();
+        let server = start_test_server(router).await;
+
+        let client = reqwest::Client::new();
+        let url = format!("{}/ip", server.base_url);
+        let response = send_with_retry(&client, |http_client| http_client.get(url.as_str())).await;
+
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(response.text().await.unwrap(), "127.0.0.1");
+
+        server.handle.abort();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn stream_error_delivers_earlier_chunks_then_aborts_the_body() {
+        let router = RouterService::builder()
+            .get("/feed", |_ctx: RequestContext| async {
+                let chunks = stream::iter([
+                    Ok(Bytes::from_static(b"first")),
+                    Err(anyhow::anyhow!("feed source failed")),
+                ]);
+                Ok::<_, EdgeError>(Body::from_stream(chunks))
+            })
+            .get("/ok", |_ctx: RequestContext| async {
+                Ok::<_, EdgeError>("ok")
+            })
+            .build();
+        let server = start_test_server(router).await;
+
+        let client = reqwest::Client::new();
+        let url = format!("{}/feed", server.base_url);
+        let mut response =
+            send_with_retry(&client, |http_client| http_client.get(url.as_str())).await;
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let mut received = Vec::new();
+        let failure = loop {
+            match response.chunk().await {
+                Ok(Some(chunk)) => received.extend_from_slice(&chunk),
+                Ok(None) => panic!("a failed stream must not end like a complete body"),
+                Err(err) => break err,
+            }
+        };
+        assert!(failure.is_body() || failure.is_decode(), "{failure:?}");
+        assert_eq!(received, b"first");
+
+        let after = client
+            .get(format!("{}/ok", server.base_url))
+            .send()
+            .await
+            .expect("server still serving");
+        assert_eq!(after.text().await.unwrap(), "ok");
+
+        server.handle.abort();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn flushed_headers_arrive_before_a_delayed_first_chunk() {
+        let release = Arc::new(Notify::new());
+        let router = RouterService::builder()
+            .get("/live", {
+                let route_release = Arc::clone(&release);
+                move |_ctx: RequestContext| {
+                    let chunk_release = Arc::clone(&route_release);
+                    async move {
+                        let chunks = stream::once(async move {
+                            chunk_release.notified().await;
+                            Bytes::from_static(b"late")
+                        });
+                        let response = response_builder()
+                            .header("x-live", "yes")
+                            .body(Body::stream(chunks))
+                            .map_err(EdgeError::internal)?;
+                        Ok::<_, EdgeError>(response.flush_headers())
+                    }
+                }
+            })
+            .build();
+        let server = start_test_server(router).await;
+
+        let client = reqwest::Client::new();
+        let url = format!("{}/live", server.base_url);
+        let response = timeout(
+            Duration::from_secs(10),
+            send_with_retry(&client, |http_client| http_client.get(url.as_str())),
+        )
+        .await
+        .expect("headers sent while the first chunk is held back");
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(response.headers()["x-live"], "yes");
+
+        release.notify_one();
+        assert_eq!(response.text().await.expect("body"), "late");
+
+        server.handle.abort();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn shutdown_waits_for_in_flight_requests_to_finish() {
+        let started = Arc::new(Notify::new());
+        let release = Arc::new(Notify::new());
+        let router = RouterService::builder()
+            .get("/slow", {
+                let route_started = Arc::clone(&started);
+                let route_release = Arc::clone(&release);
+                move |_ctx: RequestContext| {
+                    let handler_started = Arc::clone(&route_started);
+                    let handler_release = Arc::clone(&route_release);
+                    async move {
+                        handler_started.notify_one();
+                        handler_release.notified().await;
+                        Ok::<_, EdgeError>("finished")
+                    }
+                }
+            })
+            .build();
+        let listener = TokioTcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind test server");
+        let addr = listener.local_addr().expect("local addr");
+        let server = AxumDevServer::with_config(
+            router,
+            AxumDevServerConfig {
+                addr,
+                drain_timeout: Duration::from_secs(10),
+                enable_ctrl_c: false,
+                ..AxumDevServerConfig::default()
+            },
+        );
+        let (stop, stopped) = oneshot::channel::<()>();
+        let server_task = tokio::spawn(server.run_with_shutdown(listener, async move {
+            stopped.await.unwrap_or_default();
+        }));
+
+        let request = tokio::spawn(async move {
+            let client = reqwest::Client::new();
+            let url = format!("http://{addr}/slow");
+            let response =
+                send_with_retry(&client, |http_client| http_client.get(url.as_str())).await;
+            (response.status(), response.text().await.expect("body"))
+        });
+        timeout(Duration::from_secs(5), started.notified())
+            .await
+            .expect("slow handler started");
+
+        stop.send(()).expect("signal shutdown");
+        sleep(Duration::from_millis(200)).await;
+        assert!(
+            !server_task.is_finished(),
+            "server exited with a request in flight"
+        );
+
+        release.notify_one();
+        let (status, body) = request.await.expect("request task");
+        assert_eq!(status, reqwest::StatusCode::OK);
+        assert_eq!(body, "finished");
+        timeout(Duration::from_secs(5), server_task)
+            .await
+            .expect("server exits once drained")
+            .expect("server task")
+            .expect("server result");
+    }
+
+    async fn get_over_h2(http2: bool) -> reqwest::Result<reqwest::Response> {
+        let router = RouterService::builder()
+            .get("/hello", |_ctx: RequestContext| async {
+                Ok::<_, EdgeError>("hello")
+            })
+            .build();
+        let listener = TokioTcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind test server");
+        let addr = listener.local_addr().expect("local addr");
+        let server = AxumDevServer::with_config(
+            router,
+            AxumDevServerConfig {
+                addr,
+                enable_ctrl_c: false,
+                http2,
+                ..AxumDevServerConfig::default()
+            },
+        );
+        let handle = tokio::spawn(async move {
+            let _result = server.run_with_listener(listener).await;
+        });
+
+        let client = reqwest::Client::builder()
+            .http2_prior_knowledge()
+            .build()
+            .expect("h2 client");
+        let response = client.get(format!("http://{addr}/hello")).send().await;
+        handle.abort();
+        response
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn http2_clients_connect_when_enabled() {
+        let response = get_over_h2(true).await.expect("h2 request");
+        assert_eq!(response.version(), reqwest::Version::HTTP_2);
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(response.text().await.expect("body"), "hello");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn http2_is_refused_by_default() {
+        get_over_h2(false)
+            .await
+            .expect_err("HTTP/1.1-only server answered an h2 request");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn idle_connections_close_after_idle_timeout() {
+        use std::io::{Read as _, Write as _};
+        use std::net::TcpStream as StdTcpStream;
+        use tokio::task;
+
+        let router = RouterService::builder()
+            .get("/hello", |_ctx: RequestContext| async {
+                Ok::<_, EdgeError>("hello")
+            })
+            .build();
+        let listener = TokioTcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind test server");
+        let addr = listener.local_addr().expect("local addr");
+        let server = AxumDevServer::with_config(
+            router,
+            AxumDevServerConfig {
+                addr,
+                enable_ctrl_c: false,
+                idle_timeout: Some(Duration::from_millis(200)),
+                ..AxumDevServerConfig::default()
+            },
+        );
+        let handle = tokio::spawn(async move {
+            let _result = server.run_with_listener(listener).await;
+        });
+
+        // A keep-alive client that never sends a second request: the server
+        // must close the connection rather than the read timing out.
+        let received = task::spawn_blocking(move || {
+            let mut stream = StdTcpStream::connect(addr).expect("connect");
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .expect("set read timeout");
+            stream
+                .write_all(b"GET /hello HTTP/1.1\r\nhost: localhost\r\n\r\n")
+                .expect("send request");
+            let mut received = Vec::new();
+            stream
+                .read_to_end(&mut received)
+                .expect("server closes the idle connection");
+            received
+        })
+        .await
+        .expect("client task");
+        handle.abort();
+
+        let response = String::from_utf8(received).expect("utf-8 response");
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(response.ends_with("hello"), "{response}");
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn server_returns_404_for_unknown_routes() {
         let router = RouterService::builder().build();
@@ -883,6 +1573,78 @@ mod integration_tests {
         server.handle.abort();
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn server_sends_streaming_response_trailers() {
+        use edgezero_core::body::Body;
+        use edgezero_core::http::{HeaderMap, Response, response_builder};
+        use futures::stream;
+        use std::io::{Read as _, Write as _};
+        use std::net::TcpStream;
+        use std::thread;
+
+        async fn handler(_ctx: RequestContext) -> Result<Response, EdgeError> {
+            let body = Body::stream_with_trailers(
+                stream::iter(vec![
+                    bytes::Bytes::from_static(b"stream"),
+                    bytes::Bytes::from_static(b"ed"),
+                ]),
+                async {
+                    let mut trailers = HeaderMap::new();
+                    trailers.insert("grpc-status", "0".parse().expect("header value"));
+                    trailers
+                },
+            );
+            response_builder()
+                .status(200)
+                .body(body)
+                .map_err(EdgeError::internal)
+        }
+
+        let router = RouterService::builder().get("/grpc", handler).build();
+        let server = start_test_server(router).await;
+        let addr = server.base_url.trim_start_matches("http://").to_owned();
+
+        // reqwest does not surface trailers, so speak HTTP/1.1 directly.
+        let raw = spawn_blocking(move || {
+            let start = Instant::now();
+            let mut stream = loop {
+                match TcpStream::connect(addr.as_str()) {
+                    Ok(stream) => break stream,
+                    Err(err) => {
+                        assert!(
+                            start.elapsed() < Duration::from_secs(2),
+                            "server did not accept before timeout: {err}"
+                        );
+                        thread::sleep(Duration::from_millis(10));
+                    }
+                }
+            };
+            let request = format!(
+                "GET /grpc HTTP/1.1\r\nHost: {addr}\r\nTE: trailers\r\nConnection: close\r\n\r\n"
+            );
+            stream.write_all(request.as_bytes()).expect("write request");
+            let mut response = String::new();
+            stream.read_to_string(&mut response).expect("read response");
+            response
+        })
+        .await
+        .expect("client task");
+
+        let lower = raw.to_ascii_lowercase();
+        assert!(lower.starts_with("http/1.1 200"), "{raw}");
+        assert!(lower.contains("transfer-encoding: chunked"), "{raw}");
+        assert!(lower.contains("trailer: grpc-status"), "{raw}");
+        let (_, body) = raw.split_once("\r\n\r\n").expect("header terminator");
+        assert!(body.contains("streamed"), "{raw}");
+        assert!(
+            body.to_ascii_lowercase()
+                .ends_with("0\r\ngrpc-status: 0\r\n\r\n"),
+            "trailer after the last chunk: {raw}"
+        );
+
+        server.handle.abort();
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn server_fails_to_bind_to_used_port() {
         // First bind to a port
@@ -893,7 +1655,9 @@ mod integration_tests {
         let router = RouterService::builder().build();
         let config = AxumDevServerConfig {
             addr,
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
             enable_ctrl_c: false,
+            ..AxumDevServerConfig::default()
         };
         let server = AxumDevServer::with_config(router, config);
 
@@ -1033,6 +1797,69 @@ mod integration_tests {
         server.handle.abort();
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn kv_store_serializes_concurrent_increments_without_deadlock() {
+        const CLIENTS: usize = 16;
+        const INCREMENTS: usize = 5;
+
+        async fn increment_handler(ctx: RequestContext) -> Result<String, EdgeError> {
+            let kv = ctx.kv_store_default().expect("kv configured");
+            Ok(kv.increment("hits", 1).await?.to_string())
+        }
+
+        async fn total_handler(ctx: RequestContext) -> Result<String, EdgeError> {
+            let kv = ctx.kv_store_default().expect("kv configured");
+            Ok(kv.get_or("hits", 0_i64).await?.to_string())
+        }
+
+        let router = RouterService::builder()
+            .post("/hit", increment_handler)
+            .get("/total", total_handler)
+            .build();
+        let server = start_test_server(router).await;
+        let client = reqwest::Client::new();
+
+        // Every client bumps the same counter, so all of the increments
+        // contend for one key.
+        let url = format!("{}/hit", server.base_url);
+        let clients = iter::repeat_with(|| {
+            let http_client = client.clone();
+            let hit_url = url.clone();
+            tokio::spawn(async move {
+                let mut seen = Vec::with_capacity(INCREMENTS);
+                for _ in 0..INCREMENTS {
+                    let resp =
+                        send_with_retry(&http_client, |inner| inner.post(hit_url.as_str())).await;
+                    assert_eq!(resp.status(), reqwest::StatusCode::OK);
+                    seen.push(resp.text().await.unwrap().parse::<usize>().expect("count"));
+                }
+                seen
+            })
+        })
+        .take(CLIENTS);
+        let finals = timeout(Duration::from_secs(30), join_all(clients))
+            .await
+            .expect("concurrent increments deadlocked");
+        // No increment was lost: each one saw a different count, and
+        // together they saw every count up to the total.
+        let mut seen: Vec<usize> = finals
+            .into_iter()
+            .flat_map(|counts| counts.expect("client task"))
+            .collect();
+        seen.sort_unstable();
+        assert_eq!(seen, (1..=CLIENTS * INCREMENTS).collect::<Vec<_>>());
+
+        let total_url = format!("{}/total", server.base_url);
+        let resp =
+            send_with_retry(&client, |http_client| http_client.get(total_url.as_str())).await;
+        assert_eq!(
+            resp.text().await.unwrap(),
+            (CLIENTS * INCREMENTS).to_string()
+        );
+
+        server.handle.abort();
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn kv_store_returns_not_found_gracefully() {
         async fn read_handler(ctx: RequestContext) -> Result<String, EdgeError> {
@@ -1120,7 +1947,9 @@ mod integration_tests {
         let addr = listener.local_addr().expect("local addr");
         let config = super::AxumDevServerConfig {
             addr,
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
             enable_ctrl_c: false,
+            ..super::AxumDevServerConfig::default()
         };
         let mut server = super::AxumDevServer::with_config(router, config);
         if let Some(handle) = secret_handle {
@@ -0,0 +1,276 @@
+//! `.env` loading for the axum dev server.
+//!
+//! [`run_app`](crate::dev_server::run_app) loads `.env` from the working
+//! directory (or the file named by [`DOTENV_PATH_VAR`]) before it reads any
+//! configuration, so `EDGEZERO__*` settings and environment-backed secrets
+//! can live in an untracked file during local development. Variables that
+//! are already set in the process environment always win.
+//!
+//! The format follows `dotenvy` for the common cases:
+//!
+//! ```text
+//! # comment
+//! export API_TOKEN=abc123        # trailing comment
+//! GREETING="hello\nworld"        # escapes are processed in double quotes
+//! PATTERN='literal $value \n'    # single quotes are taken verbatim
+//! ```
+//!
+//! Values must fit on one line, and `$VAR` references are not expanded.
+
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use anyhow::{Context as _, bail};
+
+/// Environment variable naming the `.env` file to load. When it is set the
+/// file must exist; the default `.env` is optional.
+pub const DOTENV_PATH_VAR: &str = "EDGEZERO_DOTENV";
+
+/// Load the dev-server `.env` file into the process environment, skipping
+/// variables that are already set. Returns the path read (if any) and the
+/// number of variables applied.
+///
+/// Must be called before the process starts any other thread: writing the
+/// environment while another thread reads it is undefined behaviour.
+///
+/// # Errors
+///
+/// Returns an error if a file named by [`DOTENV_PATH_VAR`] is missing, the
+/// file cannot be read, or it fails to parse.
+#[inline]
+pub fn load() -> anyhow::Result<Option<(PathBuf, usize)>> {
+    let (path, explicit) = env::var_os(DOTENV_PATH_VAR).map_or_else(
+        || (PathBuf::from(".env"), false),
+        |raw| (PathBuf::from(raw), true),
+    );
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound && !explicit => return Ok(None),
+        Err(err) => {
+            return Err(err).with_context(|| format!("failed to read {}", path.display()));
+        }
+    };
+    let entries = parse(&contents).with_context(|| format!("invalid {}", path.display()))?;
+    let unset = unset_entries(entries, |key| env::var_os(key).is_some());
+    let applied = unset.len();
+    for (key, value) in unset {
+        set_env(&key, &value);
+    }
+    Ok(Some((path, applied)))
+}
+
+/// Parse `.env` contents into `(key, value)` pairs in file order.
+///
+/// # Errors
+///
+/// Returns an error naming the line for a missing `=`, an invalid key, an
+/// unterminated quote, or trailing characters after a closing quote.
+#[inline]
+pub fn parse(contents: &str) -> anyhow::Result<Vec<(String, String)>> {
+    let mut entries = Vec::new();
+    for (index, raw_line) in contents.lines().enumerate() {
+        let line_number = index.saturating_add(1);
+        let trimmed = raw_line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let line = trimmed
+            .strip_prefix("export")
+            .filter(|rest| rest.starts_with(char::is_whitespace))
+            .map_or(trimmed, str::trim_start);
+        let Some((raw_key, raw_value)) = line.split_once('=') else {
+            bail!("line {line_number}: expected KEY=VALUE");
+        };
+        let key = raw_key.trim();
+        if !is_valid_key(key) {
+            bail!("line {line_number}: invalid variable name `{key}`");
+        }
+        let value = parse_value(raw_value.trim_start())
+            .with_context(|| format!("line {line_number}: invalid value for `{key}`"))?;
+        entries.push((key.to_owned(), value));
+    }
+    Ok(entries)
+}
+
+/// Keys follow shell variable naming, plus `.` which `dotenvy` also allows.
+fn is_valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '.')
+}
+
+/// The text between an opening quote and its closing `quote`. Only
+/// whitespace or a comment may follow the closing quote.
+fn parse_quoted(raw: &str, quote: char) -> anyhow::Result<&str> {
+    let mut escaped = false;
+    for (offset, ch) in raw.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if quote == '"' => escaped = true,
+            _ if ch == quote => {
+                let body = raw.get(..offset).unwrap_or_default();
+                let rest = raw
+                    .get(offset.saturating_add(1)..)
+                    .unwrap_or_default()
+                    .trim_start();
+                if !rest.is_empty() && !rest.starts_with('#') {
+                    bail!("unexpected characters after closing quote");
+                }
+                return Ok(body);
+            }
+            _ => {}
+        }
+    }
+    bail!("unterminated {quote} quote")
+}
+
+fn parse_value(raw: &str) -> anyhow::Result<String> {
+    if let Some(rest) = raw.strip_prefix('\'') {
+        return Ok(parse_quoted(rest, '\'')?.to_owned());
+    }
+    if let Some(rest) = raw.strip_prefix('"') {
+        return Ok(unescape(parse_quoted(rest, '"')?));
+    }
+    // Unquoted: a `#` preceded by whitespace starts a comment.
+    let end = raw
+        .char_indices()
+        .find(|&(offset, ch)| {
+            ch == '#'
+                && raw
+                    .get(..offset)
+                    .is_some_and(|before| before.ends_with(char::is_whitespace))
+        })
+        .map_or(raw.len(), |(offset, _)| offset);
+    Ok(raw.get(..end).unwrap_or_default().trim_end().to_owned())
+}
+
+#[expect(
+    unsafe_code,
+    reason = "std::env::set_var is unsafe in edition 2024; `load` documents that it runs before any other thread exists"
+)]
+fn set_env(key: &str, value: &str) {
+    // SAFETY: `load` is only called from `run_app` before the tokio runtime
+    // or any other thread is started, so nothing reads the environment
+    // concurrently.
+    let () = unsafe { env::set_var(key, value) };
+}
+
+/// Expand the escapes `dotenvy` supports inside double quotes. Unknown
+/// escapes are kept as written.
+fn unescape(body: &str) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            out.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some(escaped @ ('"' | '\\' | '$')) => out.push(escaped),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Drop entries whose key `is_set` already; the process environment wins
+/// over the file. Later duplicates in the file override earlier ones.
+fn unset_entries(
+    entries: Vec<(String, String)>,
+    is_set: impl Fn(&str) -> bool,
+) -> Vec<(String, String)> {
+    let mut pending: Vec<(String, String)> = Vec::new();
+    for (key, value) in entries {
+        if is_set(&key) {
+            continue;
+        }
+        if let Some(existing) = pending.iter_mut().find(|(name, _)| *name == key) {
+            existing.1 = value;
+        } else {
+            pending.push((key, value));
+        }
+    }
+    pending
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(entries: &[(&str, &str)]) -> Vec<(String, String)> {
+        entries
+            .iter()
+            .map(|(key, value)| ((*key).to_owned(), (*value).to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let parsed = parse(
+            "# leading comment\n\n  # indented comment\nPLAIN=value # trailing\nHASH=a#b\nEMPTY=\n",
+        )
+        .expect("parse");
+        assert_eq!(
+            parsed,
+            pairs(&[("PLAIN", "value"), ("HASH", "a#b"), ("EMPTY", "")])
+        );
+    }
+
+    #[test]
+    fn export_prefix_is_stripped() {
+        let parsed = parse("export TOKEN=abc\nexport\tOTHER = 'x'\nexported=1\n").expect("parse");
+        assert_eq!(
+            parsed,
+            pairs(&[("TOKEN", "abc"), ("OTHER", "x"), ("exported", "1")])
+        );
+    }
+
+    #[test]
+    fn existing_variables_are_not_overridden() {
+        let entries = pairs(&[("SET", "file"), ("NEW", "first"), ("NEW", "second")]);
+        let pending = unset_entries(entries, |key| key == "SET");
+        assert_eq!(pending, pairs(&[("NEW", "second")]));
+    }
+
+    #[test]
+    fn invalid_lines_report_their_line_number() {
+        for (contents, needle) in [
+            ("OK=1\nnot a pair\n", "line 2"),
+            ("1BAD=x\n", "line 1"),
+            ("OPEN=\"unterminated\n", "line 1"),
+            ("TRAILING='x' y\n", "line 1"),
+        ] {
+            let err = parse(contents).expect_err(contents);
+            assert!(format!("{err:#}").contains(needle), "{contents}: {err:#}");
+        }
+    }
+
+    #[test]
+    fn quoted_values_keep_spaces_and_handle_escapes() {
+        let parsed = parse(concat!(
+            "DOUBLE=\"  hello\\nworld \\\"q\\\" \\$HOME # not a comment\"  # comment\n",
+            "SINGLE='literal \\n $HOME # kept'\n",
+            "URL=\"https://example.com/#frag\"\n",
+        ))
+        .expect("parse");
+        assert_eq!(
+            parsed,
+            pairs(&[
+                ("DOUBLE", "  hello\nworld \"q\" $HOME # not a comment"),
+                ("SINGLE", "literal \\n $HOME # kept"),
+                ("URL", "https://example.com/#frag"),
+            ])
+        );
+    }
+}
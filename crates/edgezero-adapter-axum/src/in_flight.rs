@@ -0,0 +1,219 @@
+//! In-flight request tracking for graceful shutdown.
+//!
+//! [`EdgeZeroAxumService`](crate::service::EdgeZeroAxumService) counts each
+//! request from the moment it is accepted until its response body has been
+//! sent or dropped. On shutdown the dev server marks the gauge as draining,
+//! stops accepting connections, and waits for the count to reach zero
+//! before exiting (up to
+//! [`AxumDevServerConfig::drain_timeout`](crate::dev_server::AxumDevServerConfig::drain_timeout)).
+//!
+//! The service also inserts its [`InFlight`] into every request's
+//! extensions, so a readiness handler can report the count and fail once
+//! draining starts:
+//!
+//! ```rust,ignore
+//! async fn ready(ctx: RequestContext) -> Result<Response, EdgeError> {
+//!     let in_flight = ctx.request().extensions().get::<InFlight>();
+//!     let status = if in_flight.is_some_and(InFlight::is_draining) {
+//!         StatusCode::SERVICE_UNAVAILABLE
+//!     } else {
+//!         StatusCode::OK
+//!     };
+//!     let count = in_flight.map_or(0, InFlight::count);
+//!     response_builder()
+//!         .status(status)
+//!         .body(Body::from(format!("in_flight {count}\n")))
+//!         .map_err(EdgeError::internal)
+//! }
+//! ```
+
+use std::pin::{Pin, pin};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+
+use axum::body::Body as AxumBody;
+use bytes::Bytes;
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use tokio::sync::Notify;
+
+/// Shared gauge of requests currently being served. Clones share the count.
+#[derive(Clone, Debug, Default)]
+pub struct InFlight {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    /// Woken when draining starts and when the count drops to zero.
+    changed: Notify,
+    count: AtomicUsize,
+    draining: AtomicBool,
+}
+
+impl InFlight {
+    /// Requests currently being served.
+    #[must_use]
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.shared.count.load(Ordering::Acquire)
+    }
+
+    /// Whether shutdown has started; readiness checks should fail from here
+    /// on.
+    #[must_use]
+    #[inline]
+    pub fn is_draining(&self) -> bool {
+        self.shared.draining.load(Ordering::Acquire)
+    }
+
+    /// Mark the server as shutting down.
+    #[inline]
+    pub fn start_draining(&self) {
+        self.shared.draining.store(true, Ordering::Release);
+        self.shared.changed.notify_waiters();
+    }
+
+    /// Count a request until the returned guard is dropped.
+    #[must_use]
+    #[inline]
+    pub fn track(&self) -> InFlightGuard {
+        self.shared.count.fetch_add(1, Ordering::AcqRel);
+        InFlightGuard {
+            in_flight: self.clone(),
+        }
+    }
+
+    /// Resolve once [`Self::start_draining`] has been called.
+    #[inline]
+    pub async fn wait_draining(&self) {
+        self.wait_until(Self::is_draining).await;
+    }
+
+    /// Resolve once no request is in flight.
+    #[inline]
+    pub async fn wait_idle(&self) {
+        self.wait_until(|in_flight| in_flight.count() == 0).await;
+    }
+
+    async fn wait_until(&self, ready: impl Fn(&Self) -> bool) {
+        loop {
+            // Register for the wake-up before checking, so a change between
+            // the check and the await is not missed.
+            let mut notified = pin!(self.shared.changed.notified());
+            notified.as_mut().enable();
+            if ready(self) {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Keeps one request counted in its [`InFlight`] gauge while alive.
+#[derive(Debug)]
+pub struct InFlightGuard {
+    in_flight: InFlight,
+}
+
+impl Drop for InFlightGuard {
+    #[inline]
+    fn drop(&mut self) {
+        let shared = &self.in_flight.shared;
+        if shared.count.fetch_sub(1, Ordering::AcqRel) == 1 {
+            shared.changed.notify_waiters();
+        }
+    }
+}
+
+/// Response body that keeps its request counted until hyper has sent or
+/// dropped it, so streaming responses hold off shutdown too.
+struct TrackedBody {
+    _guard: InFlightGuard,
+    body: AxumBody,
+}
+
+impl HttpBody for TrackedBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    #[inline]
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, axum::Error>>> {
+        Pin::new(&mut self.body).poll_frame(cx)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> SizeHint {
+        self.body.size_hint()
+    }
+}
+
+/// Wrap `body` so `guard` is released only once the body is finished.
+pub(crate) fn track_body(body: AxumBody, guard: InFlightGuard) -> AxumBody {
+    AxumBody::new(TrackedBody {
+        _guard: guard,
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[tokio::test]
+    async fn guards_count_requests_and_wake_idle_waiters() {
+        let in_flight = InFlight::default();
+        assert_eq!(in_flight.count(), 0);
+        timeout(Duration::from_millis(50), in_flight.wait_idle())
+            .await
+            .expect("idle with nothing in flight");
+
+        let first = in_flight.track();
+        let second = in_flight.clone().track();
+        assert_eq!(in_flight.count(), 2);
+        drop(first);
+        assert!(
+            timeout(Duration::from_millis(50), in_flight.wait_idle())
+                .await
+                .is_err(),
+            "one request still in flight"
+        );
+
+        let waiter = tokio::spawn({
+            let idle = in_flight.clone();
+            async move { idle.wait_idle().await }
+        });
+        drop(second);
+        timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("woken when idle")
+            .expect("waiter task");
+        assert_eq!(in_flight.count(), 0);
+    }
+
+    #[tokio::test]
+    async fn draining_is_observable_and_wakes_waiters() {
+        let in_flight = InFlight::default();
+        assert!(!in_flight.is_draining());
+        let waiter = tokio::spawn({
+            let draining = in_flight.clone();
+            async move { draining.wait_draining().await }
+        });
+        in_flight.start_draining();
+        timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("woken when draining")
+            .expect("waiter task");
+        assert!(in_flight.is_draining());
+    }
+}
@@ -37,6 +37,24 @@
 //! Within a single process, the store is thread-safe and supports
 //! concurrent access via redb's transaction system.
 //!
+//! Reads run in parallel against a snapshot. Writes take redb's single
+//! write lock, so concurrent writers queue behind one another. Each commit
+//! is flushed to disk before the lock is released, so write throughput is
+//! bounded by the disk's sync latency: under heavy write load, requests
+//! wait for the lock rather than fail. This cannot
+//! deadlock. Every operation opens, uses and commits its transaction
+//! synchronously, without awaiting while it holds one, and the dev server
+//! runs each request on its own blocking thread, so the writer holding the
+//! lock always finishes and releases it.
+//!
+//! Each operation is atomic on its own, including
+//! [`KvHandle::increment`](edgezero_core::key_value_store::KvHandle::increment)
+//! and [`KvHandle::compare_and_swap`](edgezero_core::key_value_store::KvHandle::compare_and_swap),
+//! which read and write in one transaction. By contrast
+//! [`KvHandle::read_modify_write`](edgezero_core::key_value_store::KvHandle::read_modify_write)
+//! is a read followed by a separate write, so concurrent updates of the
+//! same key through it can lose writes; see its documentation.
+//!
 //! ## Performance Notes
 //!
 //! - All operations are ACID-compliant via redb's transaction system.
@@ -49,7 +67,7 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 use bytes::Bytes;
-use edgezero_core::key_value_store::{KvError, KvPage, KvStore};
+use edgezero_core::key_value_store::{KvError, KvOp, KvPage, KvStore, incremented};
 use redb::{Database, ReadableDatabase as _, ReadableTable as _, TableDefinition};
 use std::time::SystemTime;
 
@@ -127,6 +145,15 @@ impl PersistentKvStore {
             .map_err(|err| KvError::Internal(anyhow::anyhow!("failed to commit: {err}")))
     }
 
+    /// Expiry timestamp, in milliseconds since the UNIX epoch, for an entry
+    /// written now with `ttl`.
+    fn expires_at_millis(ttl: Duration) -> Result<u128, KvError> {
+        let expires_at = SystemTime::now()
+            .checked_add(ttl)
+            .ok_or_else(|| KvError::Internal(anyhow::anyhow!("ttl overflows system time")))?;
+        Ok(Self::system_time_to_millis(expires_at))
+    }
+
     /// Check if an entry is expired based on its expiration timestamp.
     ///
     /// If the system clock is before UNIX epoch (highly unlikely), treats entries
@@ -146,6 +173,22 @@ impl PersistentKvStore {
         }
     }
 
+    /// The live value of `key` and its expiry, read inside a write
+    /// transaction so an update based on it is atomic. Expired entries read
+    /// as absent.
+    fn live_entry(
+        table: &KvTable<'_>,
+        key: &str,
+    ) -> Result<Option<(Bytes, Option<u128>)>, KvError> {
+        let entry = table
+            .get(key)
+            .map_err(|err| KvError::Internal(anyhow::anyhow!("failed to get key: {err}")))?;
+        Ok(entry.and_then(|guard| {
+            let (value, expires_at) = guard.value();
+            (!Self::is_expired(expires_at)).then(|| (Bytes::copy_from_slice(value), expires_at))
+        }))
+    }
+
     /// Create a new persistent KV store at the given path.
     ///
     /// # Behavior
@@ -193,6 +236,58 @@ impl PersistentKvStore {
 
 #[async_trait(?Send)]
 impl KvStore for PersistentKvStore {
+    /// Apply every op in one redb write transaction: either the whole batch
+    /// is committed or, if any op fails, none of it is.
+    #[inline]
+    async fn apply_batch(&self, ops: Vec<KvOp>) -> Result<(), KvError> {
+        let write_txn = self.begin_write()?;
+        let mut table = Self::open_table(&write_txn)?;
+        for op in ops {
+            match op {
+                KvOp::Delete { key } => {
+                    table.remove(key.as_str()).map_err(|err| {
+                        KvError::Internal(anyhow::anyhow!("failed to remove: {err}"))
+                    })?;
+                }
+                KvOp::Put { key, ttl, value } => {
+                    let expires_at = ttl.map(Self::expires_at_millis).transpose()?;
+                    table
+                        .insert(key.as_str(), (value.as_ref(), expires_at))
+                        .map_err(|err| {
+                            KvError::Internal(anyhow::anyhow!("failed to insert: {err}"))
+                        })?;
+                }
+            }
+        }
+        // Returning early above drops the transaction uncommitted, which
+        // aborts it.
+        drop(table);
+        Self::commit(write_txn)
+    }
+
+    /// Read and write in one redb write transaction, which holds the
+    /// database's single write lock, so no other write can interleave.
+    #[inline]
+    async fn compare_and_swap(
+        &self,
+        key: &str,
+        expected: Option<Bytes>,
+        new: Bytes,
+    ) -> Result<bool, KvError> {
+        let write_txn = self.begin_write()?;
+        let mut table = Self::open_table(&write_txn)?;
+        let current = Self::live_entry(&table, key)?.map(|(value, _)| value);
+        if current != expected {
+            return Ok(false);
+        }
+        table
+            .insert(key, (new.as_ref(), None))
+            .map_err(|err| KvError::Internal(anyhow::anyhow!("failed to insert: {err}")))?;
+        drop(table);
+        Self::commit(write_txn)?;
+        Ok(true)
+    }
+
     #[inline]
     async fn delete(&self, key: &str) -> Result<(), KvError> {
         let write_txn = self.begin_write()?;
@@ -265,6 +360,23 @@ impl KvStore for PersistentKvStore {
         }
     }
 
+    /// Read and write in one redb write transaction, as
+    /// [`Self::compare_and_swap`] does. The key keeps its expiry.
+    #[inline]
+    async fn increment(&self, key: &str, delta: i64) -> Result<i64, KvError> {
+        let write_txn = self.begin_write()?;
+        let mut table = Self::open_table(&write_txn)?;
+        let current = Self::live_entry(&table, key)?;
+        let expires_at = current.as_ref().and_then(|&(_, expires_at)| expires_at);
+        let (next, bytes) = incremented(current.as_ref().map(|(value, _)| value.as_ref()), delta)?;
+        table
+            .insert(key, (bytes.as_ref(), expires_at))
+            .map_err(|err| KvError::Internal(anyhow::anyhow!("failed to insert: {err}")))?;
+        drop(table);
+        Self::commit(write_txn)?;
+        Ok(next)
+    }
+
     #[inline]
     async fn list_keys_page(
         &self,
@@ -399,10 +511,7 @@ impl KvStore for PersistentKvStore {
         value: Bytes,
         ttl: Duration,
     ) -> Result<(), KvError> {
-        let expires_at = SystemTime::now()
-            .checked_add(ttl)
-            .ok_or_else(|| KvError::Internal(anyhow::anyhow!("ttl overflows system time")))?;
-        let expires_at_millis = Self::system_time_to_millis(expires_at);
+        let expires_at_millis = Self::expires_at_millis(ttl)?;
 
         let write_txn = self.begin_write()?;
         let mut table = Self::open_table(&write_txn)?;
@@ -463,6 +572,67 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn batch_commits_all_ops_together() {
+        let (kv, _dir) = store();
+        kv.put("stale", &1_i32).await.unwrap();
+
+        kv.batch()
+            .put("order", &42_i32)
+            .put_bytes("raw", Bytes::from("bytes"))
+            .put_bytes_with_ttl("session", Bytes::from("s"), Duration::from_hours(1))
+            .delete("stale")
+            .commit()
+            .await
+            .unwrap();
+
+        assert_eq!(kv.get::<i32>("order").await.unwrap(), Some(42_i32));
+        assert_eq!(
+            kv.get_bytes("raw").await.unwrap(),
+            Some(Bytes::from("bytes"))
+        );
+        assert!(kv.exists("session").await.unwrap());
+        assert!(!kv.exists("stale").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn batch_failure_leaves_store_unchanged() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let kv_store = PersistentKvStore::new(temp_dir.path().join("batch.redb")).unwrap();
+        kv_store
+            .put_bytes("keep", Bytes::from("old"))
+            .await
+            .unwrap();
+
+        // The TTL overflows after the first two ops have been staged, so the
+        // transaction must be rolled back rather than partially committed.
+        let err = kv_store
+            .apply_batch(vec![
+                KvOp::Put {
+                    key: "keep".to_owned(),
+                    ttl: None,
+                    value: Bytes::from("new"),
+                },
+                KvOp::Delete {
+                    key: "keep".to_owned(),
+                },
+                KvOp::Put {
+                    key: "doomed".to_owned(),
+                    ttl: Some(Duration::MAX),
+                    value: Bytes::from("x"),
+                },
+            ])
+            .await
+            .expect_err("overflowing TTL must fail the batch");
+        assert!(err.to_string().contains("ttl overflows system time"));
+
+        assert_eq!(
+            kv_store.get_bytes("keep").await.unwrap(),
+            Some(Bytes::from("old"))
+        );
+        assert_eq!(kv_store.get_bytes("doomed").await.unwrap(), None);
+    }
+
     #[tokio::test]
     async fn cleanup_expired_keys_does_not_delete_fresh_overwrite() {
         let temp_dir = tempfile::tempdir().unwrap();
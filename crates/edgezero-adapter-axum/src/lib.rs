@@ -1,5 +1,7 @@
 //! Axum adapter for `EdgeZero` routers and applications.
 
+#[cfg(feature = "axum")]
+pub mod body_spool;
 #[cfg(feature = "axum")]
 pub mod config_store;
 #[cfg(feature = "axum")]
@@ -7,8 +9,14 @@ pub mod context;
 #[cfg(feature = "axum")]
 pub mod dev_server;
 #[cfg(feature = "axum")]
+pub mod dotenv;
+#[cfg(feature = "axum")]
+pub mod in_flight;
+#[cfg(feature = "axum")]
 pub mod key_value_store;
 #[cfg(feature = "axum")]
+pub mod object_store;
+#[cfg(feature = "axum")]
 pub mod proxy;
 #[cfg(feature = "axum")]
 pub mod request;
@@ -0,0 +1,247 @@
+//! Filesystem-backed object store for local development and testing.
+//!
+//! Each object is a file under the store's root directory; `/` in a key
+//! becomes a subdirectory, so `assets/app.css` lives at
+//! `<root>/assets/app.css`. Writes go to a temporary file next to the target
+//! and are renamed into place, so readers never see a partial object.
+//!
+//! Bodies are streamed in both directions. File IO is blocking, like the
+//! redb-backed [`crate::key_value_store::PersistentKvStore`]; this store is
+//! meant for the dev server, not production traffic.
+//!
+//! `ETag`s are derived from the file's size and modification time.
+
+use std::fs::{self, File, Metadata};
+use std::io::{self, Read as _, Write as _};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::UNIX_EPOCH;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use edgezero_core::object_store::{ObjectError, ObjectMeta, ObjectStore, ObjectStream};
+use futures::stream::{self, StreamExt as _};
+
+/// Bytes read from disk per streamed chunk.
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Distinguishes concurrent uploads' temporary files within this process.
+static UPLOAD_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// An object store rooted at a local directory.
+#[derive(Clone, Debug)]
+pub struct FsObjectStore {
+    root: PathBuf,
+}
+
+impl FsObjectStore {
+    /// Create a store rooted at `root`. The directory is created on the
+    /// first write.
+    #[inline]
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// File path for `key`. Keys are split on `/`; empty, `.` and `..`
+    /// segments are rejected so a key cannot escape the root.
+    fn path_for(&self, key: &str) -> Result<PathBuf, ObjectError> {
+        let mut path = self.root.clone();
+        for segment in key.split('/') {
+            if segment.is_empty()
+                || segment == "."
+                || segment == ".."
+                || segment.contains(['\\', '\0'])
+            {
+                return Err(ObjectError::Validation(format!(
+                    "key `{key}` is not a valid relative path"
+                )));
+            }
+            path.push(segment);
+        }
+        Ok(path)
+    }
+
+    /// The root directory objects are stored under.
+    #[must_use]
+    #[inline]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+#[async_trait(?Send)]
+impl ObjectStore for FsObjectStore {
+    #[inline]
+    async fn delete(&self, key: &str) -> Result<(), ObjectError> {
+        match fs::remove_file(self.path_for(key)?) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(io_error("delete", &err)),
+        }
+    }
+
+    #[inline]
+    async fn get(&self, key: &str) -> Result<Option<ObjectStream>, ObjectError> {
+        let path = self.path_for(key)?;
+        let Some(metadata) = file_metadata(&path)? else {
+            return Ok(None);
+        };
+        let file = File::open(&path).map_err(|err| io_error("open", &err))?;
+        // The state goes to `None` after an error so the stream ends rather
+        // than retrying a failing read forever.
+        let chunks = stream::unfold(Some(file), |state| async move {
+            let mut reader = state?;
+            let mut buf = vec![0_u8; READ_CHUNK_SIZE];
+            match reader.read(&mut buf) {
+                Ok(0) => None,
+                Ok(read) => {
+                    buf.truncate(read);
+                    Some((Ok(Bytes::from(buf)), Some(reader)))
+                }
+                Err(err) => Some((Err(err), None)),
+            }
+        });
+        Ok(Some(
+            ObjectStream::from_stream(chunks).with_size(metadata.len()),
+        ))
+    }
+
+    #[inline]
+    async fn head(&self, key: &str) -> Result<Option<ObjectMeta>, ObjectError> {
+        Ok(file_metadata(&self.path_for(key)?)?
+            .as_ref()
+            .map(object_meta))
+    }
+
+    #[inline]
+    async fn put(&self, key: &str, body: ObjectStream) -> Result<ObjectMeta, ObjectError> {
+        let path = self.path_for(key)?;
+        let parent = path.parent().unwrap_or(&self.root);
+        fs::create_dir_all(parent).map_err(|err| io_error("create directory", &err))?;
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        let temp = parent.join(format!(
+            ".{file_name}.upload-{}-{}",
+            process::id(),
+            UPLOAD_SEQ.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let written = match write_stream(&temp, body).await {
+            Ok(()) => fs::rename(&temp, &path).map_err(|err| io_error("rename", &err)),
+            Err(err) => Err(err),
+        };
+        if written.is_err() {
+            // Best effort: the upload already failed.
+            let _ignored = fs::remove_file(&temp);
+        }
+        written?;
+
+        let metadata = fs::metadata(&path).map_err(|err| io_error("stat", &err))?;
+        Ok(object_meta(&metadata))
+    }
+}
+
+/// Metadata of the regular file at `path`, or `None` if there is none.
+fn file_metadata(path: &Path) -> Result<Option<Metadata>, ObjectError> {
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() => Ok(Some(metadata)),
+        Ok(_) => Ok(None),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(io_error("stat", &err)),
+    }
+}
+
+fn io_error(operation: &str, err: &io::Error) -> ObjectError {
+    ObjectError::Internal(anyhow::anyhow!("object {operation} failed: {err}"))
+}
+
+fn object_meta(metadata: &Metadata) -> ObjectMeta {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_nanos());
+    ObjectMeta {
+        etag: format!("\"{:x}-{modified:x}\"", metadata.len()),
+        size: metadata.len(),
+    }
+}
+
+/// Stream `body` into a new file at `path`, checking the declared size.
+async fn write_stream(path: &Path, body: ObjectStream) -> Result<(), ObjectError> {
+    let declared = body.size();
+    let mut file = File::create(path).map_err(|err| io_error("create", &err))?;
+    let mut chunks = body.into_stream();
+    let mut total = 0_u64;
+    while let Some(chunk) = chunks.next().await {
+        let bytes = chunk?;
+        file.write_all(&bytes)
+            .map_err(|err| io_error("write", &err))?;
+        total = total.saturating_add(u64::try_from(bytes.len()).unwrap_or(u64::MAX));
+    }
+    if let Some(expected) = declared
+        && expected != total
+    {
+        return Err(ObjectError::Internal(anyhow::anyhow!(
+            "object body was {total} bytes, declared {expected}"
+        )));
+    }
+    file.sync_all().map_err(|err| io_error("sync", &err))
+}
+
+#[cfg(test)]
+mod tests {
+    // Run the shared contract tests against FsObjectStore. The TempDir is
+    // leaked so it outlives the store, as in the KV contract tests.
+    edgezero_core::object_store_contract_tests!(fs_object_store_contract, {
+        let dir = Box::leak(Box::new(tempfile::tempdir().unwrap()));
+        FsObjectStore::new(dir.path())
+    });
+
+    use super::*;
+    use futures::executor::block_on;
+
+    #[test]
+    fn keys_cannot_escape_the_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsObjectStore::new(dir.path());
+        for key in ["../outside", "a//b", "/abs", "a/./b", "trailing/"] {
+            let err = block_on(store.head(key)).expect_err(key);
+            assert!(matches!(err, ObjectError::Validation(_)), "{key}: {err}");
+        }
+    }
+
+    #[test]
+    fn nested_keys_map_to_subdirectories() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsObjectStore::new(dir.path());
+        block_on(store.put("assets/app.css", ObjectStream::from_bytes("body{}"))).unwrap();
+        assert_eq!(
+            fs::read(dir.path().join("assets").join("app.css")).unwrap(),
+            b"body{}"
+        );
+        // A directory is not an object.
+        assert!(block_on(store.head("assets")).unwrap().is_none());
+    }
+
+    #[test]
+    fn size_mismatch_fails_and_keeps_the_previous_object() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsObjectStore::new(dir.path());
+        block_on(store.put("k", ObjectStream::from_bytes("old"))).unwrap();
+
+        let short = ObjectStream::from_bytes("new").with_size(10);
+        block_on(store.put("k", short)).expect_err("size mismatch");
+
+        let object = block_on(store.get("k")).unwrap().unwrap();
+        assert_eq!(
+            block_on(object.into_bytes()).unwrap(),
+            Bytes::from_static(b"old")
+        );
+        let leftovers = fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(leftovers, 1, "temporary upload file was removed");
+    }
+}
@@ -1,13 +1,24 @@
 use std::time::Duration;
 
+use anyhow::Error as AnyError;
 use async_trait::async_trait;
+use bytes::Bytes;
 use edgezero_core::body::Body;
 use edgezero_core::error::EdgeError;
 use edgezero_core::http::{HeaderName, HeaderValue, Method, StatusCode};
 use edgezero_core::proxy::{ProxyClient, ProxyRequest, ProxyResponse};
-use futures_util::StreamExt as _;
+use futures::channel::mpsc;
+use futures::future::join;
+use futures_util::SinkExt as _;
+use futures_util::stream::{LocalBoxStream, StreamExt as _};
 use reqwest::{Client, header};
 
+/// Upper bound on any proxied request, including its body.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Request body chunks read ahead of what reqwest has written upstream.
+const STREAM_BUFFER: usize = 4;
+
 pub struct AxumProxyClient {
     client: Client,
 }
@@ -25,7 +36,7 @@ impl AxumProxyClient {
     /// fails — typically because the TLS backend cannot be initialised on this target.
     #[inline]
     pub fn try_new() -> Result<Self, reqwest::Error> {
-        let client = Client::builder().timeout(Duration::from_secs(30)).build()?;
+        let client = Client::builder().timeout(DEFAULT_TIMEOUT).build()?;
         Ok(Self { client })
     }
 }
@@ -34,9 +45,18 @@ impl AxumProxyClient {
 impl ProxyClient for AxumProxyClient {
     #[inline]
     async fn send(&self, request: ProxyRequest) -> Result<ProxyResponse, EdgeError> {
+        // Capped by any request deadline; see `ProxyRequest::timeout`.
+        let timeout = request.timeout();
         let (method, uri, headers, body, _extensions) = request.into_parts();
         let reqwest_method = reqwest_method(&method)?;
         let mut builder = self.client.request(reqwest_method, uri.to_string());
+        if let Some(limit) = timeout {
+            if limit.is_zero() {
+                return Err(EdgeError::service_unavailable("request deadline exceeded"));
+            }
+            // A per-request timeout replaces the client's, so keep the lower.
+            builder = builder.timeout(limit.min(DEFAULT_TIMEOUT));
+        }
 
         for (name, value) in &headers {
             let header_name = header::HeaderName::from_bytes(name.as_str().as_bytes())
@@ -46,19 +66,18 @@ impl ProxyClient for AxumProxyClient {
             builder = builder.header(header_name, header_value);
         }
 
-        builder = match body {
-            Body::Once(bytes) => builder.body(bytes.to_vec()),
-            Body::Stream(mut stream) => {
-                let mut buf = Vec::new();
-                while let Some(result) = stream.next().await {
-                    let chunk = result.map_err(EdgeError::internal)?;
-                    buf.extend_from_slice(&chunk);
-                }
-                builder.body(buf)
+        // reqwest sends no request trailers; the trailers are dropped.
+        let response = match body {
+            Body::Once(bytes) => builder.body(bytes).send().await,
+            Body::Stream(chunks) | Body::StreamWithTrailers(chunks, _) => {
+                // reqwest needs a `Send` body, so the chunks are handed over
+                // through a channel while the request is in flight.
+                let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+                let upload = builder.body(reqwest::Body::wrap_stream(receiver)).send();
+                join(upload, forward_chunks(chunks, sender)).await.0
             }
-        };
-
-        let response = builder.send().await.map_err(EdgeError::internal)?;
+        }
+        .map_err(upstream_error)?;
         let status =
             StatusCode::from_u16(response.status().as_u16()).map_err(EdgeError::internal)?;
         let mut proxy_response = ProxyResponse::new(status, Body::empty());
@@ -70,20 +89,49 @@ impl ProxyClient for AxumProxyClient {
                 HeaderValue::from_bytes(value.as_bytes()).map_err(EdgeError::internal)?;
             proxy_response
                 .headers_mut()
-                .insert(header_name, header_value);
+                .append(header_name, header_value);
         }
 
-        let bytes = response.bytes().await.map_err(EdgeError::internal)?;
-        *proxy_response.body_mut() = Body::from(bytes.to_vec());
+        // Streamed as it arrives; an upstream that breaks off mid-body ends
+        // the stream with an error.
+        *proxy_response.body_mut() = Body::from_stream(response.bytes_stream());
 
         Ok(proxy_response)
     }
 }
 
+/// Feed `chunks` to `sender` until the stream ends, fails, or the request
+/// stops reading.
+async fn forward_chunks(
+    mut chunks: LocalBoxStream<'static, Result<Bytes, AnyError>>,
+    mut sender: mpsc::Sender<Result<Bytes, AnyError>>,
+) {
+    while let Some(chunk) = chunks.next().await {
+        let failed = chunk.is_err();
+        if sender.send(chunk).await.is_err() || failed {
+            break;
+        }
+    }
+}
+
 fn reqwest_method(method: &Method) -> Result<reqwest::Method, EdgeError> {
     reqwest::Method::from_bytes(method.as_str().as_bytes()).map_err(EdgeError::internal)
 }
 
+/// Map a failed send to the status a gateway answers with: `504 Gateway
+/// Timeout` when the upstream took too long, `500` for a request reqwest
+/// could not build, and `502 Bad Gateway` when the upstream could not be
+/// reached or broke off.
+fn upstream_error(err: reqwest::Error) -> EdgeError {
+    if err.is_timeout() {
+        EdgeError::gateway_timeout(format!("upstream timed out: {err}"))
+    } else if err.is_builder() {
+        EdgeError::internal(err)
+    } else {
+        EdgeError::bad_gateway(format!("upstream request failed: {err}"))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -130,8 +178,17 @@ mod integration_tests {
     use axum::http::{HeaderMap as AxumHeaderMap, StatusCode as AxumStatusCode};
     use axum::routing::{delete, get, patch, post, put};
     use edgezero_core::http::Uri;
+    use std::mem;
     use tokio::net::TcpListener;
 
+    /// Collect the streamed response body.
+    async fn read_body(mut response: ProxyResponse) -> Bytes {
+        mem::take(response.body_mut())
+            .into_bytes_bounded(usize::MAX)
+            .await
+            .expect("response body")
+    }
+
     async fn start_test_server(router: Router) -> String {
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
         let addr = listener.local_addr().unwrap();
@@ -153,10 +210,7 @@ mod integration_tests {
         let response = client.send(request).await.expect("response");
         assert_eq!(response.status(), StatusCode::OK);
 
-        match response.body() {
-            Body::Once(bytes) => assert_eq!(bytes.as_ref(), b"hello from server"),
-            Body::Stream(_) => panic!("expected buffered body"),
-        }
+        assert_eq!(read_body(response).await, "hello from server");
     }
 
     #[tokio::test]
@@ -172,10 +226,7 @@ mod integration_tests {
         let response = client.send(request).await.expect("response");
         assert_eq!(response.status(), StatusCode::OK);
 
-        match response.body() {
-            Body::Once(bytes) => assert_eq!(bytes.as_ref(), b"request body data"),
-            Body::Stream(_) => panic!("expected buffered body"),
-        }
+        assert_eq!(read_body(response).await, "request body data");
     }
 
     #[tokio::test]
@@ -202,10 +253,7 @@ mod integration_tests {
         let response = client.send(request).await.expect("response");
         assert_eq!(response.status(), StatusCode::OK);
 
-        match response.body() {
-            Body::Once(bytes) => assert_eq!(bytes.as_ref(), b"custom-value"),
-            Body::Stream(_) => panic!("expected buffered body"),
-        }
+        assert_eq!(read_body(response).await, "custom-value");
     }
 
     #[tokio::test]
@@ -282,10 +330,7 @@ mod integration_tests {
             let request = ProxyRequest::new(method, uri);
             let response = client.send(request).await.expect("response");
             assert_eq!(response.status(), StatusCode::OK);
-            match response.body() {
-                Body::Once(bytes) => assert_eq!(bytes.as_ref(), expected_body.as_bytes()),
-                Body::Stream(_) => panic!("expected buffered body"),
-            }
+            assert_eq!(read_body(response).await, expected_body.as_bytes());
         }
     }
 
@@ -296,10 +341,43 @@ mod integration_tests {
         let uri: Uri = "http://127.0.0.1:1".parse().unwrap();
         let request = ProxyRequest::new(Method::GET, uri);
 
-        client
+        let err = client
             .send(request)
             .await
             .expect_err("expected connection refused");
+        assert_eq!(err.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[tokio::test]
+    async fn proxy_client_timeout_is_capped_by_request_deadline() {
+        use edgezero_core::deadline::Deadline;
+        use std::time::Instant;
+        use tokio::time::sleep;
+
+        let app = Router::new().route(
+            "/slow",
+            get(|| async {
+                sleep(Duration::from_secs(5)).await;
+                "too late"
+            }),
+        );
+        let base_url = start_test_server(app).await;
+
+        let client = AxumProxyClient::try_new().expect("reqwest client init");
+        let uri: Uri = format!("{base_url}/slow").parse().unwrap();
+        let mut request = ProxyRequest::new(Method::GET, uri);
+        request.set_timeout(Duration::from_secs(10));
+        request
+            .extensions_mut()
+            .insert(Deadline::after(Duration::from_millis(200)));
+
+        let started = Instant::now();
+        let err = client
+            .send(request)
+            .await
+            .expect_err("deadline should abort the upstream call");
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(err.status(), StatusCode::GATEWAY_TIMEOUT);
     }
 
     #[tokio::test]
@@ -326,9 +404,169 @@ mod integration_tests {
         let response = client.send(request).await.expect("response");
         assert_eq!(response.status(), StatusCode::OK);
 
-        match response.body() {
-            Body::Once(bytes) => assert_eq!(bytes.as_ref(), b"chunk1chunk2chunk3"),
-            Body::Stream(_) => panic!("expected buffered body"),
+        assert_eq!(read_body(response).await, "chunk1chunk2chunk3");
+    }
+
+    #[tokio::test]
+    async fn proxy_client_streams_large_bodies_without_buffering() {
+        use axum::body::Body as AxumBody;
+        use futures::stream;
+        use std::iter;
+        use std::sync::{Arc, Mutex};
+        use tokio::sync::oneshot;
+        use tokio::time::timeout;
+
+        const CHUNK: usize = 64 * 1024;
+        const CHUNKS: usize = 64;
+
+        // The client holds back everything after the first chunk until the
+        // server has received it, so a client that buffered the body before
+        // sending would never finish.
+        let (signal, first_received) = oneshot::channel::<()>();
+        let first_seen = Arc::new(Mutex::new(Some(signal)));
+        let app = Router::new().route(
+            "/count",
+            post(move |body: AxumBody| async move {
+                let mut frames = body.into_data_stream();
+                let mut total = 0_usize;
+                while let Some(frame) = frames.next().await {
+                    total += frame.expect("request chunk").len();
+                    if let Some(seen) = first_seen.lock().unwrap().take() {
+                        seen.send(()).unwrap();
+                    }
+                }
+                total.to_string()
+            }),
+        );
+        let base_url = start_test_server(app).await;
+
+        let first = stream::iter([Bytes::from(vec![b'a'; CHUNK])]);
+        let rest = stream::once(async move { first_received.await.expect("first chunk seen") })
+            .flat_map(|()| {
+                stream::iter(iter::repeat_n(Bytes::from(vec![b'b'; CHUNK]), CHUNKS - 1))
+            });
+        let uri: Uri = format!("{base_url}/count").parse().unwrap();
+        let mut request = ProxyRequest::new(Method::POST, uri);
+        *request.body_mut() = Body::stream(first.chain(rest));
+
+        let client = AxumProxyClient::try_new().expect("reqwest client init");
+        let response = timeout(Duration::from_secs(10), client.send(request))
+            .await
+            .expect("body streamed before the upstream saw the first chunk")
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let expected = (CHUNK * CHUNKS).to_string();
+        assert_eq!(read_body(response).await, expected.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn proxy_client_round_trips_through_an_echo_server() {
+        use axum::body::to_bytes;
+        use axum::extract::Request as AxumRequest;
+        use axum::http::header::SET_COOKIE;
+        use axum::response::{AppendHeaders, IntoResponse as _, Response as AxumResponse};
+        use futures::stream;
+
+        /// Echoes the method, a request header and the body, with a status
+        /// and repeated headers the client has to keep as they are.
+        async fn echo(request: AxumRequest) -> AxumResponse {
+            let method = request.method().to_string();
+            let tag = request
+                .headers()
+                .get("x-tag")
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or("none")
+                .to_owned();
+            let body = to_bytes(request.into_body(), usize::MAX)
+                .await
+                .expect("request body");
+            let mut echoed = format!("{method} {tag} ").into_bytes();
+            echoed.extend_from_slice(&body);
+            (
+                AxumStatusCode::CREATED,
+                AppendHeaders([(SET_COOKIE, "a=1"), (SET_COOKIE, "b=2")]),
+                echoed,
+            )
+                .into_response()
+        }
+
+        let app = Router::new().route("/echo", get(echo).post(echo));
+        let base_url = start_test_server(app).await;
+        let client = AxumProxyClient::try_new().expect("reqwest client init");
+        let uri: Uri = format!("{base_url}/echo").parse().unwrap();
+
+        let mut get_request = ProxyRequest::new(Method::GET, uri.clone());
+        get_request
+            .headers_mut()
+            .insert("x-tag", HeaderValue::from_static("fetch"));
+        let post_body = Body::stream(stream::iter([
+            Bytes::from_static(b"streamed "),
+            Bytes::from_static(b"upload"),
+        ]));
+        let mut post_request = ProxyRequest::new(Method::POST, uri);
+        post_request
+            .headers_mut()
+            .insert("x-tag", HeaderValue::from_static("upload"));
+        *post_request.body_mut() = post_body;
+
+        for (request, expected) in [
+            (get_request, &b"GET fetch "[..]),
+            (post_request, &b"POST upload streamed upload"[..]),
+        ] {
+            let response = client.send(request).await.expect("response");
+            assert_eq!(response.status(), StatusCode::CREATED);
+            let cookies: Vec<_> = response.headers().get_all("set-cookie").iter().collect();
+            assert_eq!(cookies, ["a=1", "b=2"]);
+            assert!(matches!(response.body(), Body::Stream(_)));
+            assert_eq!(read_body(response).await, expected);
         }
     }
+
+    #[tokio::test]
+    async fn proxy_client_streams_the_response_as_it_arrives() {
+        use axum::body::Body as AxumBody;
+        use futures::stream;
+        use std::io;
+        use std::sync::{Arc, Mutex};
+        use tokio::sync::oneshot;
+        use tokio::time::timeout;
+
+        // The upstream holds back the rest of its body until the client has
+        // read the first chunk, so a client that buffered the response
+        // would never return.
+        let (release, released) = oneshot::channel::<()>();
+        let gate = Arc::new(Mutex::new(Some(released)));
+        let app = Router::new().route(
+            "/feed",
+            get(move || {
+                let opened = gate.lock().unwrap().take().expect("one request");
+                async move {
+                    let first = stream::iter([Ok::<_, io::Error>(Bytes::from("first;"))]);
+                    let rest = stream::once(async move {
+                        opened.await.expect("released");
+                        Ok(Bytes::from("rest"))
+                    });
+                    AxumBody::from_stream(first.chain(rest))
+                }
+            }),
+        );
+        let base_url = start_test_server(app).await;
+
+        let client = AxumProxyClient::try_new().expect("reqwest client init");
+        let uri: Uri = format!("{base_url}/feed").parse().unwrap();
+        let mut response = timeout(
+            Duration::from_secs(10),
+            client.send(ProxyRequest::new(Method::GET, uri)),
+        )
+        .await
+        .expect("headers arrive before the body ends")
+        .expect("response");
+        let Body::Stream(chunks) = response.body_mut() else {
+            panic!("expected a streamed body");
+        };
+        let first = chunks.next().await.expect("a chunk").expect("chunk");
+        assert_eq!(first, "first;");
+        release.send(()).expect("upstream waiting");
+        assert_eq!(read_body(response).await, "rest");
+    }
 }
@@ -1,49 +1,71 @@
 use std::net::SocketAddr;
+use std::time::Duration;
 
-use axum::body::{Body as AxumBody, to_bytes};
+use axum::body::Body as AxumBody;
 use axum::extract::connect_info::ConnectInfo;
 use axum::http::Request;
 use edgezero_core::body::Body;
-use edgezero_core::http::HeaderValue;
+use edgezero_core::body_limit::BodyLimit;
+use edgezero_core::body_spool::BodySpoolHandle;
+use edgezero_core::connection::ConnectionInfo;
+use edgezero_core::context::AdapterName;
+use edgezero_core::deadline::{Sleep, SleepHandle};
 use edgezero_core::http::Request as CoreRequest;
-use edgezero_core::http::header::CONTENT_TYPE;
 use edgezero_core::proxy::ProxyHandle;
+use futures::future::LocalBoxFuture;
+use tokio::time;
 
-use crate::context::AxumRequestContext;
+use crate::body_spool::TempFileSpool;
+use crate::context::{AxumRequestContext, TcpConnectInfo};
 use crate::proxy::AxumProxyClient;
 
+/// Request body cap the dev server applies unless the app sets
+/// `max-body-bytes` under `[app]`. See [`edgezero_core::body_limit`].
+pub const DEFAULT_MAX_BODY_BYTES: usize = 32 * 1024 * 1024;
+
+/// The timer route timeouts race handlers against.
+struct TokioSleep;
+
+impl Sleep for TokioSleep {
+    fn sleep(&self, duration: Duration) -> LocalBoxFuture<'static, ()> {
+        Box::pin(time::sleep(duration))
+    }
+}
+
 /// Convert an Axum/Hyper request into an `EdgeZero` core request while preserving streaming bodies
-/// and exposing connection metadata through `AxumRequestContext`.
+/// and exposing connection metadata through `AxumRequestContext` and
+/// [`ConnectionInfo`].
+///
+/// Bodies are passed on as streams, JSON included: the router buffers the
+/// ones the extractors read, spilling large ones to a [`TempFileSpool`]
+/// when `body-spill-bytes` is set.
 ///
 /// # Errors
-/// Returns an error if a buffered (`application/json`) body cannot be read into memory.
+/// Returns an error if the proxy client cannot be built.
 #[inline]
 pub async fn into_core_request(request: Request<AxumBody>) -> Result<CoreRequest, String> {
     let (parts, axum_body) = request.into_parts();
 
-    let body = match parts.headers.get(CONTENT_TYPE) {
-        Some(value) if is_json_content_type(value) => {
-            let bytes = to_bytes(axum_body, usize::MAX)
-                .await
-                .map_err(|err| format!("Failed to convert body into bytes: {err}"))?;
-            Body::from_bytes(bytes)
-        }
-        _ => {
-            let stream = axum_body.into_data_stream();
-            Body::from_stream(stream)
-        }
-    };
+    let body = Body::from_stream(axum_body.into_data_stream());
 
     let mut core_request = CoreRequest::from_parts(parts, body);
 
-    if let Some(remote_addr) = core_request
-        .extensions()
-        .get::<ConnectInfo<SocketAddr>>()
-        .map(|ConnectInfo(addr)| *addr)
+    let extensions = core_request.extensions_mut();
+    let addrs = if let Some(ConnectInfo(info)) = extensions.remove::<ConnectInfo<TcpConnectInfo>>()
     {
-        core_request
-            .extensions_mut()
-            .remove::<ConnectInfo<SocketAddr>>();
+        Some((info.peer_addr, info.local_addr))
+    } else {
+        extensions
+            .remove::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| (addr, None))
+    };
+    if let Some((remote_addr, local_addr)) = addrs {
+        // The dev server speaks plain HTTP, so there is no TLS metadata.
+        core_request.extensions_mut().insert(ConnectionInfo {
+            local_addr,
+            peer_addr: Some(remote_addr),
+            ..ConnectionInfo::default()
+        });
         AxumRequestContext::insert(
             &mut core_request,
             AxumRequestContext {
@@ -57,42 +79,52 @@ pub async fn into_core_request(request: Request<AxumBody>) -> Result<CoreRequest
     core_request
         .extensions_mut()
         .insert(ProxyHandle::with_client(proxy_client));
+    core_request
+        .extensions_mut()
+        .insert(BodyLimit::new(DEFAULT_MAX_BODY_BYTES));
+    core_request
+        .extensions_mut()
+        .insert(BodySpoolHandle::with_spool(TempFileSpool::default()));
+    core_request
+        .extensions_mut()
+        .insert(SleepHandle::with_sleep(TokioSleep));
+    core_request.extensions_mut().insert(AdapterName("axum"));
 
     Ok(core_request)
 }
 
-fn is_json_content_type(value: &HeaderValue) -> bool {
-    let Ok(raw) = value.to_str() else {
-        return false;
-    };
-
-    let media_type = raw.split(';').next().map_or("", str::trim);
-    if media_type.eq_ignore_ascii_case("application/json") {
-        return true;
-    }
-
-    let Some((ty, raw_subtype)) = media_type.split_once('/') else {
-        return false;
-    };
-
-    if !ty.eq_ignore_ascii_case("application") {
-        return false;
-    }
-
-    let subtype = raw_subtype.trim();
-    let Some(suffix_start) = subtype.len().checked_sub(5) else {
-        return false;
-    };
-    subtype
-        .get(suffix_start..)
-        .is_some_and(|suffix| suffix.eq_ignore_ascii_case("+json"))
-}
-
 #[cfg(test)]
 mod tests {
+    // Run the shared conversion contract against into_core_request and
+    // into_axum_response.
+    edgezero_core::conversion_contract_tests!(
+        axum_conversion_contract,
+        request: |method: Method, uri: &str, headers: HeaderMap, body: bytes::Bytes| {
+            let mut request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(AxumBody::from(body))
+                .expect("request");
+            *request.headers_mut() = headers;
+            request
+        },
+        into_core: into_core_request,
+        from_core: into_axum_response,
+        read_response: |response: Response<AxumBody>| async move {
+            let (parts, body) = response.into_parts();
+            let bytes = to_bytes(body, usize::MAX).await.expect("response body");
+            (parts.status, parts.headers, bytes)
+        },
+    );
+
     use super::*;
+    use crate::response::into_axum_response;
+    use axum::body::to_bytes;
+    use axum::http::Response;
     use edgezero_core::body::Body;
-    use edgezero_core::http::Method;
+    use edgezero_core::context::RequestContext;
+    use edgezero_core::http::{HeaderMap, Method};
+    use edgezero_core::params::PathParams;
 
     #[tokio::test]
     async fn converts_request_and_records_connect_info() {
@@ -114,11 +146,17 @@ mod tests {
         assert_eq!(core_request.headers()["x-test"], "1");
         match core_request.body() {
             Body::Stream(_) => {} // streaming bodies stay streaming
-            Body::Once(_) => panic!("body should remain streaming"),
+            Body::Once(_) | Body::StreamWithTrailers(..) => panic!("body should remain streaming"),
         }
 
         let context = AxumRequestContext::get(&core_request).expect("context");
         assert_eq!(context.remote_addr, Some("127.0.0.1:4000".parse().unwrap()));
+        let info = core_request
+            .extensions()
+            .get::<ConnectionInfo>()
+            .expect("connection info");
+        assert_eq!(info.peer_addr, Some("127.0.0.1:4000".parse().unwrap()));
+        assert!(info.local_addr.is_none());
         assert!(
             core_request
                 .extensions()
@@ -139,10 +177,25 @@ mod tests {
             .await
             .expect("request conversion");
         assert!(AxumRequestContext::get(&core_request).is_none());
+        assert!(core_request.extensions().get::<ConnectionInfo>().is_none());
+    }
+
+    #[tokio::test]
+    async fn records_the_adapter_name() {
+        let request = Request::builder()
+            .uri("/demo")
+            .body(AxumBody::empty())
+            .expect("request");
+
+        let core_request = into_core_request(request)
+            .await
+            .expect("request conversion");
+        let ctx = RequestContext::new(core_request, PathParams::default());
+        assert_eq!(ctx.adapter_name(), Some("axum"));
     }
 
     #[tokio::test]
-    async fn json_content_type_buffers_body() {
+    async fn json_bodies_stream_for_the_router_to_buffer() {
         let json_payload = r#"{"name":"test"}"#;
         let request = Request::builder()
             .method(Method::POST)
@@ -155,13 +208,9 @@ mod tests {
             .await
             .expect("request conversion");
         assert_eq!(core_request.method(), &Method::POST);
-
-        match core_request.body() {
-            Body::Once(bytes) => {
-                assert_eq!(bytes.as_ref(), json_payload.as_bytes());
-            }
-            Body::Stream(_) => panic!("JSON body should be buffered, not streaming"),
-        }
+        assert!(core_request.body().is_stream());
+        assert!(core_request.extensions().get::<BodySpoolHandle>().is_some());
+        assert!(core_request.extensions().get::<SleepHandle>().is_some());
     }
 
     #[tokio::test]
@@ -179,27 +228,4 @@ mod tests {
 
         assert!(matches!(core_request.body(), Body::Stream(_)));
     }
-
-    #[test]
-    fn json_content_type_detection() {
-        assert!(is_json_content_type(&HeaderValue::from_static(
-            "application/json"
-        )));
-        assert!(is_json_content_type(&HeaderValue::from_static(
-            "application/json; charset=utf-8"
-        )));
-        assert!(is_json_content_type(&HeaderValue::from_static(
-            "application/vnd.api+json"
-        )));
-        assert!(is_json_content_type(&HeaderValue::from_static(
-            "APPLICATION/VND.CUSTOM+JSON; CHARSET=UTF-8"
-        )));
-
-        assert!(!is_json_content_type(&HeaderValue::from_static(
-            "text/json"
-        )));
-        assert!(!is_json_content_type(&HeaderValue::from_static(
-            "application/json+xml"
-        )));
-    }
 }
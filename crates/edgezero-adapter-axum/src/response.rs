@@ -1,62 +1,191 @@
+use std::convert::Infallible;
+use std::io;
+
 use axum::body::Body as AxumBody;
-use axum::http::header::CONTENT_TYPE;
-use axum::http::{HeaderValue, Response, StatusCode};
+use axum::http::header::{CONTENT_LENGTH, TRAILER};
+use axum::http::{HeaderMap, HeaderName, HeaderValue, Response, StatusCode};
+use bytes::Bytes;
+use futures::channel::mpsc;
 use futures::executor::block_on;
-use futures_util::{StreamExt as _, pin_mut};
+use futures_util::future::{self, FutureExt as _, LocalBoxFuture};
+use futures_util::stream::{self, LocalBoxStream};
+use futures_util::{SinkExt as _, StreamExt as _, pin_mut};
+use http_body::Frame;
+use http_body_util::StreamBody;
+use tokio::task::yield_now;
 use tracing::error;
 
 use edgezero_core::body::Body;
 use edgezero_core::http::Response as CoreResponse;
+use edgezero_core::response::ResponseExt as _;
+
+/// Frames a flushed body reads ahead of what hyper has written.
+const STREAM_BUFFER: usize = 4;
 
 /// Convert an `EdgeZero` response into one consumable by Axum/Hyper.
 ///
 /// Streaming responses are collected into an in-memory buffer. While this sacrifices
 /// incremental flushing, it keeps the adapter compatible with the non-`Send` streaming type used by
-/// `edgezero_core::Body` and works well for local development.
+/// `edgezero_core::Body` and works well for local development. Responses marked
+/// with `flush_headers` go through [`into_flushed_response`] instead.
+///
+/// Trailers from [`Body::stream_with_trailers`] are sent after the body, and
+/// their names are declared in a `Trailer` header unless the handler set one.
+/// Over HTTP/1.1, hyper only writes them when the request carried
+/// `TE: trailers`.
 ///
+/// A stream that fails part-way is treated as it would be on an edge
+/// platform, where the status line has already gone out: the chunks before
+/// the error are sent with the handler's status, the error is logged, and the
+/// body then ends with an error so hyper aborts the connection instead of
+/// marking the body complete. No trailers are sent after a failed stream.
 #[inline]
 pub fn into_axum_response(response: CoreResponse) -> Response<AxumBody> {
-    let (parts, core_body) = response.into_parts();
+    let (mut parts, core_body) = response.into_parts();
+    set_content_length(parts.status, &mut parts.headers, core_body.size_hint());
     let body = match core_body {
         Body::Once(bytes) => AxumBody::from(bytes),
-        Body::Stream(stream) => {
-            let result = block_on(async {
-                let mut buf = Vec::new();
-                pin_mut!(stream);
-                while let Some(chunk) = stream.next().await {
-                    let bytes = chunk?;
-                    buf.extend_from_slice(&bytes);
-                }
-                Ok::<Vec<u8>, anyhow::Error>(buf)
-            });
-            match result {
-                Ok(buf) => AxumBody::from(buf),
-                Err(err) => {
-                    error!("streaming response error: {err}");
-                    return error_response_500("streaming response error");
+        Body::Stream(stream) => match block_on(collect(stream)) {
+            (buf, None) => AxumBody::from(buf),
+            (buf, Some(err)) => aborted_body(buf, &err),
+        },
+        Body::StreamWithTrailers(stream, pending) => {
+            let buf = match block_on(collect(stream)) {
+                (buf, None) => buf,
+                (buf, Some(err)) => {
+                    return Response::from_parts(parts, aborted_body(buf, &err));
                 }
-            }
+            };
+            let trailers = block_on(pending);
+            declare_trailers(&mut parts.headers, &trailers);
+            let frames = [Frame::data(Bytes::from(buf)), Frame::trailers(trailers)];
+            AxumBody::new(StreamBody::new(stream::iter(
+                frames.map(Ok::<_, Infallible>),
+            )))
         }
     };
 
     Response::from_parts(parts, body)
 }
 
-/// Build a minimal 500 response without any builder steps that could fail.
-/// Used as a fallback on the request path so we never panic on synthesis.
-fn error_response_500(message: &'static str) -> Response<AxumBody> {
-    let mut response = Response::new(AxumBody::from(message));
-    *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
-    response.headers_mut().insert(
-        CONTENT_TYPE,
-        HeaderValue::from_static("text/plain; charset=utf-8"),
-    );
-    response
+/// Convert a response, sending its head before the body when the handler
+/// asked for that with [`ResponseExt::flush_headers`].
+///
+/// The returned pump feeds a flushed streaming body and must be driven to
+/// completion on the calling thread after the head has been handed to hyper,
+/// since `edgezero_core::Body` streams are not `Send`. For any other
+/// response the body is converted by [`into_axum_response`] and the pump
+/// does nothing.
+///
+/// A flushed body is sent chunk by chunk and ends with an error when the
+/// stream fails, as with [`into_axum_response`]. Trailers are only sent when
+/// the handler declared them in a `Trailer` header, because the head goes
+/// out before they exist.
+///
+/// [`ResponseExt::flush_headers`]: edgezero_core::response::ResponseExt::flush_headers
+#[inline]
+pub fn into_flushed_response(
+    response: CoreResponse,
+) -> (Response<AxumBody>, LocalBoxFuture<'static, ()>) {
+    if !response.flushes_headers() || !response.body().is_stream() {
+        return (
+            into_axum_response(response),
+            future::ready(()).boxed_local(),
+        );
+    }
+    let (parts, core_body) = response.into_parts();
+    let (chunks, pending) = match core_body {
+        Body::StreamWithTrailers(stream, pending) => (stream, Some(pending)),
+        Body::Stream(stream) => (stream, None),
+        Body::Once(bytes) => (stream::once(future::ok(bytes)).boxed_local(), None),
+    };
+    let trailers = stream::iter(pending)
+        .then(|trailers| trailers)
+        .filter_map(|trailers| {
+            future::ready((!trailers.is_empty()).then(|| Ok(Frame::trailers(trailers))))
+        });
+    let mut frames = chunks
+        .map(|chunk| {
+            chunk.map(Frame::data).map_err(|err| {
+                error!("streaming response error: {err}");
+                io::Error::other(err.to_string())
+            })
+        })
+        .chain(trailers);
+    let (mut sender, receiver) = mpsc::channel(STREAM_BUFFER);
+    let pump = async move {
+        while let Some(frame) = frames.next().await {
+            let failed = frame.is_err();
+            if sender.send(frame).await.is_err() || failed {
+                break;
+            }
+        }
+    };
+    let body = AxumBody::new(StreamBody::new(receiver));
+    (Response::from_parts(parts, body), pump.boxed_local())
+}
+
+/// A body of the chunks read before `err`, ending with the error.
+fn aborted_body(buf: Vec<u8>, err: &anyhow::Error) -> AxumBody {
+    error!("streaming response error after {} bytes: {err}", buf.len());
+    let message = err.to_string();
+    // Yield before failing so hyper flushes the head and the data frame;
+    // an error polled straight after them drops them unsent.
+    let failure = async move {
+        yield_now().await;
+        Err(io::Error::other(message))
+    };
+    let frames = stream::iter([Ok(Frame::data(Bytes::from(buf)))]).chain(stream::once(failure));
+    AxumBody::new(StreamBody::new(frames))
+}
+
+/// The stream's bytes up to its end or first error, and that error.
+async fn collect(
+    stream: LocalBoxStream<'static, Result<Bytes, anyhow::Error>>,
+) -> (Vec<u8>, Option<anyhow::Error>) {
+    let mut buf = Vec::new();
+    pin_mut!(stream);
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(bytes) => buf.extend_from_slice(&bytes),
+            Err(err) => return (buf, Some(err)),
+        }
+    }
+    (buf, None)
+}
+
+/// Add a `Trailer` header naming every trailer field, which hyper requires
+/// before it will send them. A header the handler set itself is kept.
+fn declare_trailers(headers: &mut HeaderMap, trailers: &HeaderMap) {
+    if headers.contains_key(TRAILER) || trailers.is_empty() {
+        return;
+    }
+    let names: Vec<&str> = trailers.keys().map(HeaderName::as_str).collect();
+    if let Ok(value) = HeaderValue::from_str(&names.join(", ")) {
+        headers.insert(TRAILER, value);
+    }
+}
+
+/// Set `Content-Length` from an exact body size hint (a buffered body), so
+/// it is known without buffering. A header the handler set is kept, and
+/// statuses that carry no body get none.
+fn set_content_length(status: StatusCode, headers: &mut HeaderMap, hint: (usize, Option<usize>)) {
+    let (lower, upper) = hint;
+    if upper != Some(lower)
+        || headers.contains_key(CONTENT_LENGTH)
+        || status.is_informational()
+        || status == StatusCode::NO_CONTENT
+        || status == StatusCode::NOT_MODIFIED
+    {
+        return;
+    }
+    headers.insert(CONTENT_LENGTH, HeaderValue::from(lower));
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use axum::body::to_bytes;
     use edgezero_core::body::Body;
     use edgezero_core::http::{StatusCode, response_builder};
     use futures::stream;
@@ -98,4 +227,91 @@ mod tests {
 
         assert_eq!(collected, b"hello");
     }
+
+    #[test]
+    fn once_bodies_get_content_length_from_size_hint() {
+        let response = response_builder()
+            .status(StatusCode::OK)
+            .body(Body::from("hello"))
+            .expect("response");
+        let axum_response = into_axum_response(response);
+        assert_eq!(
+            axum_response.headers().get(CONTENT_LENGTH),
+            Some(&HeaderValue::from_static("5"))
+        );
+
+        let no_content = response_builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())
+            .expect("response");
+        assert!(
+            into_axum_response(no_content)
+                .headers()
+                .get(CONTENT_LENGTH)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn stream_bodies_are_not_given_content_length() {
+        let chunks = stream::iter(vec![bytes::Bytes::from_static(b"hello")]);
+        let response = response_builder()
+            .status(StatusCode::OK)
+            .body(Body::stream(chunks))
+            .expect("response");
+        let axum_response = into_axum_response(response);
+        assert!(axum_response.headers().get(CONTENT_LENGTH).is_none());
+    }
+
+    #[test]
+    fn flushed_responses_are_fed_by_the_pump() {
+        let chunks = stream::iter(vec![bytes::Bytes::from_static(b"live")]);
+        let response = response_builder()
+            .status(StatusCode::OK)
+            .body(Body::stream(chunks))
+            .expect("response")
+            .flush_headers();
+        let (axum_response, pump) = into_flushed_response(response);
+        assert_eq!(axum_response.status(), StatusCode::OK);
+
+        let mut body_stream = axum_response.into_body().into_data_stream();
+        block_on(pump);
+        let first = block_on(body_stream.next()).expect("chunk");
+        assert_eq!(first.expect("data").as_ref(), b"live");
+        assert!(block_on(body_stream.next()).is_none());
+    }
+
+    #[test]
+    fn unflushed_responses_have_nothing_to_pump() {
+        let chunks = stream::iter(vec![bytes::Bytes::from_static(b"buffered")]);
+        let response = response_builder()
+            .status(StatusCode::OK)
+            .body(Body::stream(chunks))
+            .expect("response");
+        let (axum_response, pump) = into_flushed_response(response);
+        block_on(pump);
+        let collected = block_on(to_bytes(axum_response.into_body(), usize::MAX));
+        assert_eq!(collected.expect("body").as_ref(), b"buffered");
+    }
+
+    #[test]
+    fn stream_error_keeps_the_status_and_aborts_after_the_sent_chunks() {
+        let chunks = stream::iter(vec![
+            Ok::<_, anyhow::Error>(bytes::Bytes::from_static(b"first")),
+            Err(anyhow::anyhow!("upstream went away")),
+            Ok(bytes::Bytes::from_static(b"never sent")),
+        ]);
+        let response = response_builder()
+            .status(StatusCode::OK)
+            .body(Body::from_stream(chunks))
+            .expect("response");
+        let axum_response = into_axum_response(response);
+        assert_eq!(axum_response.status(), StatusCode::OK);
+
+        let mut body_stream = axum_response.into_body().into_data_stream();
+        let first = block_on(body_stream.next()).expect("first chunk");
+        assert_eq!(first.expect("data").as_ref(), b"first");
+        let failure = block_on(body_stream.next()).expect("error frame");
+        failure.expect_err("stream aborted");
+    }
 }
@@ -13,17 +13,25 @@ use edgezero_core::secret_store::SecretHandle;
 use edgezero_core::store_registry::{
     BoundSecretStore, ConfigRegistry, ConfigStoreBinding, KvRegistry, SecretRegistry,
 };
+use futures_util::future::{self, FutureExt as _, LocalBoxFuture};
+use tokio::sync::oneshot;
 use tokio::{runtime::Handle, task};
 use tower::Service;
 
+use crate::in_flight::{InFlight, track_body};
 use crate::request::into_core_request;
-use crate::response::into_axum_response;
+use crate::response::into_flushed_response;
 
 /// Tower service that adapts `EdgeZero` router requests to Axum/Hyper compatible responses.
+///
+/// Every request is counted in the service's [`InFlight`] gauge until its
+/// response body is finished; the gauge is also inserted into the request
+/// extensions for readiness handlers.
 #[derive(Clone)]
 pub struct EdgeZeroAxumService {
     config_registry: Option<ConfigRegistry>,
     config_store_handle: Option<ConfigStoreHandle>,
+    in_flight: InFlight,
     kv_handle: Option<KvHandle>,
     kv_registry: Option<KvRegistry>,
     router: RouterService,
@@ -32,12 +40,20 @@ pub struct EdgeZeroAxumService {
 }
 
 impl EdgeZeroAxumService {
+    /// The gauge of requests this service (and its clones) are serving.
+    #[must_use]
+    #[inline]
+    pub fn in_flight(&self) -> InFlight {
+        self.in_flight.clone()
+    }
+
     #[must_use]
     #[inline]
     pub fn new(router: RouterService) -> Self {
         Self {
             config_registry: None,
             config_store_handle: None,
+            in_flight: InFlight::default(),
             kv_handle: None,
             kv_registry: None,
             router,
@@ -131,6 +147,8 @@ impl Service<Request<AxumBody>> for EdgeZeroAxumService {
     #[inline]
     fn call(&mut self, req: Request<AxumBody>) -> Self::Future {
         let router = self.router.clone();
+        let in_flight = self.in_flight.clone();
+        let guard = in_flight.track();
         // Hard-cutoff: legacy bare `KvHandle` /
         // `ConfigStoreHandle` / `SecretHandle` entries are NO
         // LONGER inserted into request extensions. The legacy
@@ -170,39 +188,37 @@ impl Service<Request<AxumBody>> for EdgeZeroAxumService {
             })
         });
         Box::pin(async move {
-            let mut core_request = match into_core_request(req).await {
-                Ok(converted) => converted,
-                Err(err) => {
-                    let mut err_response = Response::new(AxumBody::from(err.clone()));
-                    *err_response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
-
-                    return Ok(err_response);
-                }
-            };
-
-            if let Some(registry) = config_registry {
-                core_request.extensions_mut().insert(registry);
-            }
-            if let Some(registry) = kv_registry {
-                core_request.extensions_mut().insert(registry);
-            }
-            if let Some(registry) = secret_registry {
-                core_request.extensions_mut().insert(registry);
-            }
-
-            let core_response = task::block_in_place(move || {
-                Handle::current().block_on(router.oneshot(core_request))
+            let handle = Handle::current();
+            // The router runs on a blocking thread, which also drives the
+            // body of a response that flushes its headers early: the head is
+            // sent back here while the thread keeps feeding the body.
+            let (head_sender, head_receiver) = oneshot::channel();
+            let worker = task::spawn_blocking(move || {
+                handle.block_on(async move {
+                    let (response, pump) = dispatch(
+                        req,
+                        router,
+                        in_flight,
+                        config_registry,
+                        kv_registry,
+                        secret_registry,
+                    )
+                    .await;
+                    if head_sender.send(response).is_ok() {
+                        pump.await;
+                    }
+                });
             });
-            let response = match core_response {
-                Ok(response) => into_axum_response(response),
-                Err(err) => {
-                    let body = AxumBody::from(format!("internal error: {err}"));
-                    let mut fallback = Response::new(body);
-                    *fallback.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
-                    fallback
-                }
+            let response = if let Ok(head) = head_receiver.await {
+                head
+            } else {
+                let reason = worker
+                    .await
+                    .err()
+                    .map_or_else(String::new, |err| format!(": {err}"));
+                internal_error(format!("internal error: no response{reason}"))
             };
-            Ok(response)
+            Ok(response.map(|body| track_body(body, guard)))
         })
     }
 
@@ -212,21 +228,151 @@ impl Service<Request<AxumBody>> for EdgeZeroAxumService {
     }
 }
 
+/// Run `req` through `router`, returning the response and the pump that
+/// feeds its body when it flushes headers early (see
+/// [`into_flushed_response`]).
+async fn dispatch(
+    req: Request<AxumBody>,
+    router: RouterService,
+    in_flight: InFlight,
+    config_registry: Option<ConfigRegistry>,
+    kv_registry: Option<KvRegistry>,
+    secret_registry: Option<SecretRegistry>,
+) -> (Response<AxumBody>, LocalBoxFuture<'static, ()>) {
+    let mut core_request = match into_core_request(req).await {
+        Ok(converted) => converted,
+        Err(err) => return (internal_error(err), future::ready(()).boxed_local()),
+    };
+
+    core_request.extensions_mut().insert(in_flight);
+    if let Some(registry) = config_registry {
+        core_request.extensions_mut().insert(registry);
+    }
+    if let Some(registry) = kv_registry {
+        core_request.extensions_mut().insert(registry);
+    }
+    if let Some(registry) = secret_registry {
+        core_request.extensions_mut().insert(registry);
+    }
+
+    match router.oneshot(core_request).await {
+        Ok(response) => into_flushed_response(response),
+        Err(err) => (
+            internal_error(format!("internal error: {err}")),
+            future::ready(()).boxed_local(),
+        ),
+    }
+}
+
+fn internal_error<B: Into<AxumBody>>(body: B) -> Response<AxumBody> {
+    let mut response = Response::new(body.into());
+    *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+    response
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::body_spool::TempFileSpool;
+    use crate::request::DEFAULT_MAX_BODY_BYTES;
+    use axum::Router;
     use axum::body::to_bytes;
+    use axum::routing::get;
     use edgezero_core::body::Body;
+    use edgezero_core::body_limit::BodyLimit;
+    use edgezero_core::body_spool::{
+        BodyBuffering, BodySpool, BodySpoolHandle, SpilledBody, SpoolFile,
+    };
     use edgezero_core::config_store::{ConfigStore, ConfigStoreError, ConfigStoreHandle};
     use edgezero_core::context::RequestContext;
     use edgezero_core::error::EdgeError;
-    use edgezero_core::http::{StatusCode, response_builder};
+    use edgezero_core::extractor::{FromRequest as _, Json};
+    use edgezero_core::http::{Method, StatusCode, Uri, response_builder};
     use edgezero_core::key_value_store::KvStore;
+    use edgezero_core::middleware::{Middleware, Next};
+    use edgezero_core::proxy::{Proxy, ProxyRequest};
+    use std::io::{self, Read};
     use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+    use tokio::net::TcpListener;
+    use tokio::time;
     use tower::ServiceExt as _;
 
     struct FixedConfigStore(String);
 
+    /// Swaps in a [`ReadRecordingSpool`] for the adapter's spool.
+    struct RecordSpilledReads(Arc<AtomicUsize>);
+
+    /// A spilled body's reader, recording the largest single read.
+    struct ReadRecorder<R> {
+        largest_read: Arc<AtomicUsize>,
+        reader: R,
+    }
+
+    /// [`TempFileSpool`] files whose readers record their largest read.
+    struct ReadRecordingFile {
+        file: Box<dyn SpoolFile>,
+        largest_read: Arc<AtomicUsize>,
+    }
+
+    struct ReadRecordingSpool {
+        largest_read: Arc<AtomicUsize>,
+        spool: TempFileSpool,
+    }
+
+    impl BodySpool for ReadRecordingSpool {
+        fn create(&self) -> Result<Box<dyn SpoolFile>, EdgeError> {
+            Ok(Box::new(ReadRecordingFile {
+                file: self.spool.create()?,
+                largest_read: Arc::clone(&self.largest_read),
+            }))
+        }
+    }
+
+    impl SpoolFile for ReadRecordingFile {
+        fn append(&mut self, chunk: &[u8]) -> Result<(), EdgeError> {
+            self.file.append(chunk)
+        }
+
+        fn reader(&self) -> Result<Box<dyn Read + '_>, EdgeError> {
+            Ok(Box::new(ReadRecorder {
+                largest_read: Arc::clone(&self.largest_read),
+                reader: self.file.reader()?,
+            }))
+        }
+    }
+
+    #[expect(
+        clippy::missing_trait_methods,
+        reason = "the provided methods all read through `read`, so every read is recorded"
+    )]
+    impl<R: Read> Read for ReadRecorder<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let read = self.reader.read(buf)?;
+            self.largest_read.fetch_max(read, Ordering::SeqCst);
+            Ok(read)
+        }
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl Middleware for RecordSpilledReads {
+        async fn handle(
+            &self,
+            mut ctx: RequestContext,
+            next: Next<'_>,
+        ) -> Result<Response<Body>, EdgeError> {
+            let spool = ReadRecordingSpool {
+                largest_read: Arc::clone(&self.0),
+                spool: TempFileSpool::default(),
+            };
+            ctx.request_mut()
+                .extensions_mut()
+                .insert(BodySpoolHandle::with_spool(spool));
+            next.run(ctx).await
+        }
+    }
+
     #[async_trait::async_trait(?Send)]
     impl ConfigStore for FixedConfigStore {
         async fn get(&self, _key: &str) -> Result<Option<String>, ConfigStoreError> {
@@ -252,6 +398,158 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn counts_request_until_response_body_is_dropped() {
+        let router = RouterService::builder()
+            .get("/", |ctx: RequestContext| async move {
+                let count = ctx
+                    .request()
+                    .extensions()
+                    .get::<InFlight>()
+                    .map_or(0, InFlight::count);
+                Ok::<_, EdgeError>(count.to_string())
+            })
+            .build();
+        let mut service = EdgeZeroAxumService::new(router);
+        let in_flight = service.in_flight();
+
+        let request = Request::builder().uri("/").body(AxumBody::empty()).unwrap();
+        let response = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(in_flight.count(), 1, "body not yet sent");
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&*body, b"1");
+        assert_eq!(in_flight.count(), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn head_requests_keep_the_get_content_length() {
+        let router = RouterService::builder()
+            .get("/page", |_ctx: RequestContext| async move {
+                Ok::<_, EdgeError>("twelve bytes")
+            })
+            .build();
+        let mut service = EdgeZeroAxumService::new(router);
+
+        let request = Request::builder()
+            .method(Method::HEAD)
+            .uri("/page")
+            .body(AxumBody::empty())
+            .unwrap();
+        let response = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-length"], "12");
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(body.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn route_timeouts_answer_before_a_slow_handler_finishes() {
+        let handler_time = Duration::from_secs(10);
+        let router = RouterService::builder()
+            .route_with_timeout(
+                "/slow",
+                Method::GET,
+                move |_ctx: RequestContext| async move {
+                    time::sleep(handler_time).await;
+                    Ok::<_, EdgeError>("finished")
+                },
+                Duration::from_millis(50),
+            )
+            .build();
+        let mut service = EdgeZeroAxumService::new(router);
+
+        let started = Instant::now();
+        let request = Request::builder()
+            .uri("/slow")
+            .body(AxumBody::empty())
+            .unwrap();
+        let response = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert!(started.elapsed() < handler_time, "{:?}", started.elapsed());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn applies_the_adapter_default_body_limit() {
+        let router = RouterService::builder()
+            .post("/upload", |ctx: RequestContext| async move {
+                let limit = ctx.request().extensions().get::<BodyLimit>().copied();
+                Ok::<_, EdgeError>(format!("{:?}", limit.map(BodyLimit::max_bytes)))
+            })
+            .build();
+        let service = EdgeZeroAxumService::new(router);
+
+        let (status, body) = upload(&service, DEFAULT_MAX_BODY_BYTES).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, format!("Some({DEFAULT_MAX_BODY_BYTES})"));
+        let (over, _) = upload(&service, DEFAULT_MAX_BODY_BYTES + 1).await;
+        assert_eq!(over, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn manifest_body_limit_overrides_the_adapter_default() {
+        let handler = |_ctx: RequestContext| async move { Ok::<_, EdgeError>("stored") };
+        let smaller = EdgeZeroAxumService::new(
+            RouterService::builder()
+                .post("/upload", handler)
+                .with_body_limit(BodyLimit::new(4))
+                .build(),
+        );
+        assert_eq!(upload(&smaller, 5).await.0, StatusCode::PAYLOAD_TOO_LARGE);
+
+        let larger = EdgeZeroAxumService::new(
+            RouterService::builder()
+                .post("/upload", handler)
+                .with_body_limit(BodyLimit::new(DEFAULT_MAX_BODY_BYTES * 2))
+                .build(),
+        );
+        assert_eq!(
+            upload(&larger, DEFAULT_MAX_BODY_BYTES + 1).await.0,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn uploads_past_the_spill_threshold_are_spilled_and_still_extracted() {
+        let largest_read = Arc::new(AtomicUsize::new(0));
+        let router = RouterService::builder()
+            .with_body_buffering(BodyBuffering::Spill { threshold: 64 })
+            .middleware(RecordSpilledReads(Arc::clone(&largest_read)))
+            .post("/upload", |ctx: RequestContext| async move {
+                let Json(items) = Json::<Vec<u32>>::from_request(&ctx).await?;
+                let spilled = ctx.spilled_body().map(SpilledBody::len);
+                Ok::<_, EdgeError>(format!("{spilled:?} {}", items.len()))
+            })
+            .build();
+        let service = EdgeZeroAxumService::new(router);
+        let post_json = |payload: Vec<u8>| {
+            let mut svc = service.clone();
+            async move {
+                let request = Request::builder()
+                    .method("POST")
+                    .uri("/upload")
+                    .header("content-type", "application/json")
+                    .body(AxumBody::from(payload))
+                    .unwrap();
+                let response = svc.ready().await.unwrap().call(request).await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                String::from_utf8(body.to_vec()).unwrap()
+            }
+        };
+
+        let large = serde_json::to_vec(&(0..20_000_u32).collect::<Vec<_>>()).unwrap();
+        let expected = format!("Some({}) 20000", large.len());
+        assert_eq!(post_json(large).await, expected);
+        // The extractor deserialized from the spool file a buffer at a time
+        // rather than reading the whole body back into memory.
+        let largest = largest_read.load(Ordering::SeqCst);
+        assert!(
+            largest > 0 && largest <= 8 * 1024,
+            "largest read: {largest}"
+        );
+        assert_eq!(post_json(b"[1,2]".to_vec()).await, "None 2");
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn with_config_store_handle_injects_into_request() {
         // Hard-cutoff: legacy `ctx.config_handle()` is
@@ -671,6 +969,45 @@ mod tests {
         assert_eq!(body_at(&service, "/lookup/missing").await, "present=false");
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn handlers_make_outbound_calls_through_the_proxy_extractor() {
+        let upstream = Router::new().route("/weather", get(|| async { "sunny upstream" }));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_uri: Uri = format!("http://{}/weather", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+        tokio::spawn(async move { axum::serve(listener, upstream).await.unwrap() });
+
+        let router = RouterService::builder()
+            .get("/weather", move |ctx: RequestContext| {
+                let uri = upstream_uri.clone();
+                async move {
+                    let Proxy(proxy) = Proxy::from_request(&ctx).await?;
+                    proxy.forward(ProxyRequest::new(Method::GET, uri)).await
+                }
+            })
+            .build();
+        let service = EdgeZeroAxumService::new(router);
+
+        assert_eq!(body_at(&service, "/weather").await, "sunny upstream");
+    }
+
+    /// POST to `/upload` declaring a `Content-Length` of `length`; the handlers
+    /// never read the body, so only the declared length matters.
+    async fn upload(service: &EdgeZeroAxumService, length: usize) -> (StatusCode, String) {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/upload")
+            .header("content-length", length.to_string())
+            .body(AxumBody::from("x"))
+            .unwrap();
+        let mut svc = service.clone();
+        let response = svc.ready().await.unwrap().call(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
     /// Send a GET request through `service` and return the response body as a UTF-8 string.
     /// Lifted out of the registry-aware tests so each can stay flat (clippy
     /// `items_after_statements` rejects nested `async fn` definitions).
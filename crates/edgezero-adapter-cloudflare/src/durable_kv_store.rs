@@ -0,0 +1,529 @@
+//! Cloudflare Durable Object-backed KV adapter.
+//!
+//! Workers KV is eventually consistent: a write at one location can take
+//! up to a minute to become visible elsewhere. When a handler needs
+//! read-after-write consistency (counters, locks, idempotency keys), route
+//! the namespace through a single Durable Object instead. Every operation
+//! is forwarded to the object's transactional storage, so all callers
+//! observe writes in order.
+//!
+//! # Latency trade-off
+//!
+//! A Durable Object lives in one location. Requests from far-away edges pay
+//! a round trip to that location on every read, where Workers KV would
+//! serve a cached copy locally. Prefer [`crate::key_value_store`] for
+//! read-heavy data that tolerates staleness, and this store only where
+//! consistency matters.
+//!
+//! # Wiring
+//!
+//! The client ([`DurableObjectKvStore`]) speaks a small HTTP protocol to the
+//! object. The object side forwards its `fetch` to [`serve_kv_request`]:
+//!
+//! ```rust,ignore
+//! #[durable_object]
+//! pub struct KvObject {
+//!     state: State,
+//! }
+//!
+//! impl DurableObject for KvObject {
+//!     fn new(state: State, _env: Env) -> Self {
+//!         Self { state }
+//!     }
+//!
+//!     async fn fetch(&self, req: Request) -> worker::Result<Response> {
+//!         serve_kv_request(&self.state.storage(), req).await
+//!     }
+//! }
+//! ```
+//!
+//! With `run_app`, a `[stores.kv]` id whose `wrangler.toml` binding is a
+//! Durable Object namespace rather than a KV namespace opens this store,
+//! one object per binding, so an app can keep Workers KV as its default
+//! store and select this one by id where it needs consistency:
+//!
+//! ```rust,ignore
+//! let limits = kv.named("rate-limits").ok_or_else(|| EdgeError::internal(...))?;
+//! let hits = limits.increment(&client_key, 1).await?;
+//! ```
+//!
+//! # Atomic updates
+//!
+//! [`KvStore::compare_and_swap`] and [`KvStore::increment`] are each one
+//! request to the object, which reads and writes its storage without
+//! awaiting anything else in between. The runtime's input gate delivers no
+//! other request to the object while those storage calls are in flight, so
+//! no other write can interleave and concurrent increments are never lost.
+//!
+//! Durable Object storage has no per-key expiry, so TTL writes return
+//! [`KvError::Unsupported`].
+
+#[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
+use async_trait::async_trait;
+#[cfg(any(test, all(feature = "cloudflare", target_arch = "wasm32")))]
+use bytes::Bytes;
+use edgezero_core::key_value_store::{KvError, KvPage};
+#[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
+use edgezero_core::key_value_store::{KvOp, KvStore, apply_sequentially, incremented};
+#[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
+use std::time::Duration;
+#[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
+use worker::js_sys::Uint8Array;
+#[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
+use worker::{Env, ListOptions, Method, Request, RequestInit, Response, Storage, Stub, Url};
+
+/// Base URL for requests sent to the object. The host is ignored by the
+/// Durable Object runtime; only the path and query carry meaning.
+#[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
+const PROTOCOL_BASE: &str = "https://edgezero-kv.internal/";
+
+/// KV store backed by a single Cloudflare Durable Object.
+///
+/// Strongly consistent, at the cost of a round trip to the object's home
+/// location on every operation. See the module docs for the object side.
+#[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
+pub struct DurableObjectKvStore {
+    stub: Stub,
+}
+
+#[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
+impl DurableObjectKvStore {
+    /// Open the Durable Object named `object_name` in the namespace bound as
+    /// `binding`.
+    ///
+    /// The `binding` must match a `[[durable_objects.bindings]]` entry in
+    /// `wrangler.toml`. All stores opened with the same `object_name` share
+    /// one object and therefore one consistent keyspace.
+    ///
+    /// # Errors
+    /// Returns [`KvError::Internal`] if the binding is missing or the object
+    /// stub cannot be created.
+    #[inline]
+    pub fn from_env(env: &Env, binding: &str, object_name: &str) -> Result<Self, KvError> {
+        let namespace = env.durable_object(binding).map_err(|err| {
+            KvError::Internal(anyhow::anyhow!(
+                "failed to open durable object binding: {err}"
+            ))
+        })?;
+        let stub = namespace.get_by_name(object_name).map_err(|err| {
+            KvError::Internal(anyhow::anyhow!(
+                "failed to create durable object stub: {err}"
+            ))
+        })?;
+        Ok(Self { stub })
+    }
+
+    async fn send(
+        &self,
+        operation: &str,
+        method: Method,
+        params: &[(&str, &str)],
+        body: Option<Bytes>,
+    ) -> Result<Response, KvError> {
+        let mut url = Url::parse(PROTOCOL_BASE)
+            .and_then(|base| base.join(operation))
+            .map_err(|err| KvError::Internal(anyhow::anyhow!("{operation} url failed: {err}")))?;
+        url.query_pairs_mut().extend_pairs(params);
+
+        let mut init = RequestInit::new();
+        init.with_method(method);
+        if let Some(bytes) = body {
+            init.with_body(Some(Uint8Array::from(bytes.as_ref()).into()));
+        }
+        let request = Request::new_with_init(url.as_str(), &init).map_err(|err| {
+            KvError::Internal(anyhow::anyhow!("{operation} request failed: {err}"))
+        })?;
+
+        let mut response = self
+            .stub
+            .fetch_with_request(request)
+            .await
+            .map_err(|err| KvError::Internal(anyhow::anyhow!("{operation} failed: {err}")))?;
+        let status = response.status_code();
+        if status == 404 || (200..300).contains(&status) {
+            return Ok(response);
+        }
+        let detail = response.text().await.unwrap_or_default();
+        Err(status_error(operation, status, &detail))
+    }
+}
+
+#[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
+#[async_trait(?Send)]
+impl KvStore for DurableObjectKvStore {
+    /// The Durable Object request protocol carries one key per call, so
+    /// batches are applied one op at a time and are not atomic.
+    #[inline]
+    async fn apply_batch(&self, ops: Vec<KvOp>) -> Result<(), KvError> {
+        apply_sequentially(self, ops).await
+    }
+
+    #[inline]
+    async fn compare_and_swap(
+        &self,
+        key: &str,
+        expected: Option<Bytes>,
+        new: Bytes,
+    ) -> Result<bool, KvError> {
+        let (expected_len, body) = encode_cas_body(expected.as_deref(), &new);
+        let mut params = vec![("key", key)];
+        if let Some(len) = expected_len.as_deref() {
+            params.push(("expected_len", len));
+        }
+        let mut response = self.send("cas", Method::Post, &params, Some(body)).await?;
+        let reply: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|err| KvError::Internal(anyhow::anyhow!("cas body failed: {err}")))?;
+        reply
+            .get("swapped")
+            .and_then(serde_json::Value::as_bool)
+            .ok_or_else(|| KvError::Internal(anyhow::anyhow!("cas response missing swapped")))
+    }
+
+    #[inline]
+    async fn delete(&self, key: &str) -> Result<(), KvError> {
+        self.send("delete", Method::Delete, &[("key", key)], None)
+            .await
+            .map(drop)
+    }
+
+    #[inline]
+    async fn exists(&self, key: &str) -> Result<bool, KvError> {
+        Ok(self.get_bytes(key).await?.is_some())
+    }
+
+    #[inline]
+    async fn get_bytes(&self, key: &str) -> Result<Option<Bytes>, KvError> {
+        let mut response = self.send("get", Method::Get, &[("key", key)], None).await?;
+        if response.status_code() == 404 {
+            return Ok(None);
+        }
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|err| KvError::Internal(anyhow::anyhow!("get body failed: {err}")))?;
+        Ok(Some(Bytes::from(bytes)))
+    }
+
+    #[inline]
+    async fn increment(&self, key: &str, delta: i64) -> Result<i64, KvError> {
+        let delta_str = delta.to_string();
+        let mut response = self
+            .send(
+                "increment",
+                Method::Post,
+                &[("key", key), ("delta", delta_str.as_str())],
+                None,
+            )
+            .await?;
+        let reply: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|err| KvError::Internal(anyhow::anyhow!("increment body failed: {err}")))?;
+        reply
+            .get("value")
+            .and_then(serde_json::Value::as_i64)
+            .ok_or_else(|| KvError::Internal(anyhow::anyhow!("increment response missing value")))
+    }
+
+    #[inline]
+    async fn list_keys_page(
+        &self,
+        prefix: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<KvPage, KvError> {
+        let limit_str = limit.to_string();
+        let mut params = vec![("prefix", prefix), ("limit", limit_str.as_str())];
+        if let Some(cursor_str) = cursor.filter(|value| !value.is_empty()) {
+            params.push(("cursor", cursor_str));
+        }
+        let mut response = self.send("list", Method::Get, &params, None).await?;
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|err| KvError::Internal(anyhow::anyhow!("list body failed: {err}")))?;
+        parse_list_page(&body)
+    }
+
+    #[inline]
+    async fn put_bytes(&self, key: &str, value: Bytes) -> Result<(), KvError> {
+        self.send("put", Method::Put, &[("key", key)], Some(value))
+            .await
+            .map(drop)
+    }
+
+    #[inline]
+    async fn put_bytes_with_ttl(
+        &self,
+        _key: &str,
+        _value: Bytes,
+        _ttl: Duration,
+    ) -> Result<(), KvError> {
+        Err(KvError::Unsupported {
+            operation: "put_bytes_with_ttl".to_owned(),
+        })
+    }
+}
+
+/// Serve one [`DurableObjectKvStore`] request against the object's storage.
+///
+/// Call this from the Durable Object's `fetch`. Values are stored as byte
+/// vectors under the caller's key; listing pages through keys in
+/// lexicographic order, using the first key of the next page as the cursor.
+/// `cas` and `increment` read and write storage with nothing else awaited
+/// in between, which the input gate makes atomic (see the module docs).
+///
+/// # Errors
+/// Returns a `worker::Error` only when a response cannot be constructed;
+/// storage failures are reported to the client as `500` responses.
+#[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
+#[inline]
+pub async fn serve_kv_request(storage: &Storage, mut req: Request) -> worker::Result<Response> {
+    let url = req.url()?;
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(param_name, _)| param_name == name)
+            .map(|(_, value)| value.into_owned())
+    };
+
+    match (req.method(), url.path(), param("key")) {
+        (Method::Get, "/get", Some(key)) => match storage.get::<Vec<u8>>(&key).await {
+            Ok(Some(bytes)) => Response::from_bytes(bytes),
+            Ok(None) => Response::error("not found", 404),
+            Err(err) => Response::error(err.to_string(), 500),
+        },
+        (Method::Put, "/put", Some(key)) => {
+            let bytes = req.bytes().await?;
+            match storage.put(&key, bytes).await {
+                Ok(()) => Ok(Response::empty()?.with_status(204)),
+                Err(err) => Response::error(err.to_string(), 500),
+            }
+        }
+        (Method::Post, "/cas", Some(key)) => {
+            let body = req.bytes().await?;
+            let expected_len = match param("expected_len").map(|raw| raw.parse::<usize>()) {
+                Some(Ok(len)) => Some(len),
+                Some(Err(_)) => return Response::error("cas expected_len must be a length", 400),
+                None => None,
+            };
+            let Some((expected, new)) = split_cas_body(&body, expected_len) else {
+                return Response::error("cas body is shorter than expected_len", 400);
+            };
+            let current = match storage.get::<Vec<u8>>(&key).await {
+                Ok(current) => current,
+                Err(err) => return Response::error(err.to_string(), 500),
+            };
+            if current.as_deref() != expected {
+                return Response::from_json(&serde_json::json!({ "swapped": false }));
+            }
+            match storage.put(&key, new.to_vec()).await {
+                Ok(()) => Response::from_json(&serde_json::json!({ "swapped": true })),
+                Err(err) => Response::error(err.to_string(), 500),
+            }
+        }
+        (Method::Post, "/increment", Some(key)) => {
+            let Some(delta) = param("delta").and_then(|raw| raw.parse::<i64>().ok()) else {
+                return Response::error("increment delta must be an integer", 400);
+            };
+            let current = match storage.get::<Vec<u8>>(&key).await {
+                Ok(current) => current,
+                Err(err) => return Response::error(err.to_string(), 500),
+            };
+            let (next, bytes) = match incremented(current.as_deref(), delta) {
+                Ok(updated) => updated,
+                Err(err) => return Response::error(err.to_string(), 400),
+            };
+            match storage.put(&key, bytes.to_vec()).await {
+                Ok(()) => Response::from_json(&serde_json::json!({ "value": next })),
+                Err(err) => Response::error(err.to_string(), 500),
+            }
+        }
+        (Method::Delete, "/delete", Some(key)) => match storage.delete(&key).await {
+            Ok(_) => Ok(Response::empty()?.with_status(204)),
+            Err(err) => Response::error(err.to_string(), 500),
+        },
+        (Method::Get, "/list", _) => {
+            let prefix = param("prefix").unwrap_or_default();
+            let cursor = param("cursor");
+            let Some(limit) = param("limit").and_then(|raw| raw.parse::<usize>().ok()) else {
+                return Response::error("list limit must be a positive integer", 400);
+            };
+            list_storage_page(storage, &prefix, cursor.as_deref(), limit).await
+        }
+        _ => Response::error("unsupported kv operation", 400),
+    }
+}
+
+#[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
+async fn list_storage_page(
+    storage: &Storage,
+    prefix: &str,
+    cursor: Option<&str>,
+    limit: usize,
+) -> worker::Result<Response> {
+    // Fetch one extra key: if it exists, it is the next page's cursor.
+    let mut options = ListOptions::new()
+        .prefix(prefix)
+        .limit(limit.saturating_add(1));
+    if let Some(start) = cursor {
+        options = options.start(start);
+    }
+    let map = match storage.list_with_options(options).await {
+        Ok(map) => map,
+        Err(err) => return Response::error(err.to_string(), 500),
+    };
+    let mut keys: Vec<String> = map
+        .keys()
+        .into_iter()
+        .filter_map(|entry| entry.ok()?.as_string())
+        .collect();
+    let next_cursor = (keys.len() > limit)
+        .then(|| keys.split_off(limit))
+        .and_then(|rest| rest.into_iter().next());
+    Response::from_json(&serde_json::json!({ "keys": keys, "cursor": next_cursor }))
+}
+
+/// The `cas` request body, `expected` followed by `new`, and the
+/// `expected_len` parameter splitting it (absent when the key must not
+/// exist).
+#[cfg(any(test, all(feature = "cloudflare", target_arch = "wasm32")))]
+fn encode_cas_body(expected: Option<&[u8]>, new: &[u8]) -> (Option<String>, Bytes) {
+    let mut body = Vec::with_capacity(expected.map_or(0, <[u8]>::len).saturating_add(new.len()));
+    body.extend_from_slice(expected.unwrap_or_default());
+    body.extend_from_slice(new);
+    (
+        expected.map(|bytes| bytes.len().to_string()),
+        Bytes::from(body),
+    )
+}
+
+/// Split a `cas` request body back into the expected and new values; `None`
+/// if it is shorter than `expected_len`.
+#[cfg(any(test, all(feature = "cloudflare", target_arch = "wasm32")))]
+fn split_cas_body(body: &[u8], expected_len: Option<usize>) -> Option<(Option<&[u8]>, &[u8])> {
+    match expected_len {
+        Some(len) => {
+            let (expected, new) = body.split_at_checked(len)?;
+            Some((Some(expected), new))
+        }
+        None => Some((None, body)),
+    }
+}
+
+/// Map a non-success protocol status onto a portable [`KvError`].
+#[cfg(any(test, all(feature = "cloudflare", target_arch = "wasm32")))]
+fn status_error(operation: &str, status: u16, detail: &str) -> KvError {
+    match status {
+        400 => KvError::Validation(format!("{operation} rejected: {detail}")),
+        429 => KvError::LimitExceeded {
+            message: format!("{operation} rate limited: {detail}"),
+        },
+        503 => KvError::Unavailable,
+        _ => KvError::Internal(anyhow::anyhow!(
+            "{operation} failed with status {status}: {detail}"
+        )),
+    }
+}
+
+/// Decode a `list` response body into a [`KvPage`].
+#[cfg(any(test, all(feature = "cloudflare", target_arch = "wasm32")))]
+fn parse_list_page(body: &serde_json::Value) -> Result<KvPage, KvError> {
+    let keys = body
+        .get("keys")
+        .and_then(serde_json::Value::as_array)
+        .ok_or_else(|| KvError::Internal(anyhow::anyhow!("list response missing keys")))?
+        .iter()
+        .map(|key| {
+            key.as_str().map(str::to_owned).ok_or_else(|| {
+                KvError::Internal(anyhow::anyhow!("list response key is not a string"))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let cursor = body
+        .get("cursor")
+        .and_then(serde_json::Value::as_str)
+        .filter(|value| !value.is_empty())
+        .map(str::to_owned);
+    Ok(KvPage { keys, cursor })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cas_body_round_trips_expected_and_new_values() {
+        let (len, body) = encode_cas_body(Some(b"old"), b"new value");
+        assert_eq!(len.as_deref(), Some("3"));
+        let parsed_len = len.map(|raw| raw.parse().expect("length"));
+        assert_eq!(
+            split_cas_body(&body, parsed_len),
+            Some((Some(&b"old"[..]), &b"new value"[..]))
+        );
+
+        let (absent, create) = encode_cas_body(None, b"first");
+        assert_eq!(absent, None);
+        assert_eq!(split_cas_body(&create, None), Some((None, &b"first"[..])));
+        // An empty expected value differs from an absent key.
+        assert_eq!(
+            split_cas_body(b"first", Some(0)),
+            Some((Some(&b""[..]), &b"first"[..]))
+        );
+    }
+
+    #[test]
+    fn cas_body_shorter_than_expected_len_is_rejected() {
+        assert_eq!(split_cas_body(b"ab", Some(3)), None);
+    }
+
+    #[test]
+    fn bad_request_maps_to_validation() {
+        let err = status_error("put", 400, "bad key");
+        assert!(matches!(&err, KvError::Validation(msg) if msg.contains("bad key")));
+    }
+
+    #[test]
+    fn list_page_reads_keys_and_cursor() {
+        let page = parse_list_page(&serde_json::json!({ "keys": ["a", "b"], "cursor": "c" }))
+            .expect("page");
+        assert_eq!(page.keys, vec!["a".to_owned(), "b".to_owned()]);
+        assert_eq!(page.cursor.as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn list_page_treats_null_or_empty_cursor_as_last_page() {
+        let null_cursor =
+            parse_list_page(&serde_json::json!({ "keys": [], "cursor": null })).expect("page");
+        assert!(null_cursor.cursor.is_none());
+        let empty_cursor =
+            parse_list_page(&serde_json::json!({ "keys": ["a"], "cursor": "" })).expect("page");
+        assert!(empty_cursor.cursor.is_none());
+    }
+
+    #[test]
+    fn list_page_without_keys_is_internal_error() {
+        let err = parse_list_page(&serde_json::json!({ "cursor": null })).expect_err("missing");
+        assert!(matches!(err, KvError::Internal(_)));
+    }
+
+    #[test]
+    fn server_errors_map_to_internal() {
+        let err = status_error("get", 500, "boom");
+        assert!(matches!(err, KvError::Internal(_)));
+        assert!(err.to_string().contains("status 500"));
+    }
+
+    #[test]
+    fn throttling_and_unavailable_statuses_are_portable() {
+        assert!(matches!(
+            status_error("list", 429, "slow down"),
+            KvError::LimitExceeded { .. }
+        ));
+        assert!(matches!(
+            status_error("list", 503, ""),
+            KvError::Unavailable
+        ));
+    }
+}
@@ -12,7 +12,7 @@ use async_trait::async_trait;
 #[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
 use bytes::Bytes;
 #[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
-use edgezero_core::key_value_store::{KvError, KvPage, KvStore};
+use edgezero_core::key_value_store::{KvError, KvOp, KvPage, KvStore, apply_sequentially};
 #[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
 use std::time::Duration;
 #[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
@@ -47,7 +47,18 @@ impl CloudflareKvStore {
 
 #[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
 #[async_trait(?Send)]
+#[expect(
+    clippy::missing_trait_methods,
+    reason = "Workers KV is eventually consistent; atomic updates go through `DurableObjectKvStore`"
+)]
 impl KvStore for CloudflareKvStore {
+    /// Workers KV has no multi-key transactions, so batches are applied one op
+    /// at a time and are not atomic.
+    #[inline]
+    async fn apply_batch(&self, ops: Vec<KvOp>) -> Result<(), KvError> {
+        apply_sequentially(self, ops).await
+    }
+
     #[inline]
     async fn delete(&self, key: &str) -> Result<(), KvError> {
         self.store
@@ -9,9 +9,15 @@ pub mod cli;
 pub mod config_store;
 #[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
 pub mod context;
+// `durable_kv_store` compiles on host so its protocol decoding can be
+// tested; the Durable Object client is feature-gated internally.
+#[cfg(any(test, all(feature = "cloudflare", target_arch = "wasm32")))]
+pub mod durable_kv_store;
 #[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
 pub mod key_value_store;
 #[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
+pub mod object_store;
+#[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
 pub mod proxy;
 #[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
 pub mod request;
@@ -91,11 +97,12 @@ fn env_config_from_worker(env: &Env, stores: StoresMetadata) -> EnvConfig {
 /// variables on the worker `Env`. No `edgezero.toml` is required.
 ///
 /// # Errors
-/// Returns [`worker::Error`] if the inner dispatch fails or any required
-/// store binding cannot be opened.
+/// Returns [`worker::Error`] if the app fails `App::validate` or a middleware
+/// setup, the inner dispatch fails, or any required store binding cannot be
+/// opened.
 #[cfg(all(feature = "cloudflare", target_arch = "wasm32"))]
 #[inline]
-pub async fn run_app<A: Hooks>(
+pub async fn run_app<A: Hooks + 'static>(
     req: Request,
     env: Env,
     ctx: Context,
@@ -108,7 +115,11 @@ pub async fn run_app<A: Hooks>(
     }
     let stores = A::stores();
     let env_config = env_config_from_worker(&env, stores);
-    let app = A::build_app();
+    let app = A::build_validated_app()
+        .map_err(|err| WorkerError::RustError(err.to_string()))?
+        .ready()
+        .await
+        .map_err(|err| WorkerError::RustError(err.to_string()))?;
     request::dispatch_with_registries(
         &app,
         req,
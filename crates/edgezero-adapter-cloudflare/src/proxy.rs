@@ -8,9 +8,10 @@ use edgezero_core::proxy::{PROXY_HEADER, ProxyClient, ProxyRequest, ProxyRespons
 use futures_util::TryStreamExt as _;
 use futures_util::stream::{self, LocalBoxStream, StreamExt as _};
 use std::io;
+use std::time::Duration;
 use worker::{
-    Body as WorkerBody, Fetch, Headers, Method as CfMethod, Request as CfRequest, RequestInit,
-    Response as CfResponse, wasm_bindgen::JsValue,
+    AbortSignal, Body as WorkerBody, Fetch, Headers, Method as CfMethod, Request as CfRequest,
+    RequestInit, Response as CfResponse, wasm_bindgen::JsValue, web_sys,
 };
 
 type ChunkStream = LocalBoxStream<'static, Result<Vec<u8>, io::Error>>;
@@ -21,12 +22,21 @@ pub struct CloudflareProxyClient;
 impl ProxyClient for CloudflareProxyClient {
     #[inline]
     async fn send(&self, request: ProxyRequest) -> Result<ProxyResponse, EdgeError> {
+        // Capped by any request deadline; see `ProxyRequest::timeout`.
+        let abort = request.timeout().map(timeout_signal);
         let (method, uri, headers, body, _ext) = request.into_parts();
         let cf_request = build_cf_request(&method, &uri, &headers, body)?;
-        let mut cf_response = Fetch::Request(cf_request)
-            .send()
-            .await
-            .map_err(EdgeError::internal)?;
+        let fetch = Fetch::Request(cf_request);
+        let sent = match &abort {
+            Some(signal) => fetch.send_with_signal(signal).await,
+            None => fetch.send().await,
+        };
+        let mut cf_response = sent.map_err(|err| match &abort {
+            Some(signal) if signal.aborted() => {
+                EdgeError::gateway_timeout(format!("upstream timed out: {err}"))
+            }
+            Some(_) | None => EdgeError::internal(err),
+        })?;
 
         let mut proxy_response = convert_response(&mut cf_response)?;
         proxy_response
@@ -54,6 +64,8 @@ fn build_cf_request(
     Ok(request)
 }
 
+/// Attach `body` as a `ReadableStream`, so a streamed body is sent to the
+/// upstream chunk by chunk as it is produced instead of being collected first.
 fn attach_body(init: &mut RequestInit, body: Body) -> Result<(), EdgeError> {
     match body {
         Body::Once(bytes) => {
@@ -67,7 +79,7 @@ fn attach_body(init: &mut RequestInit, body: Body) -> Result<(), EdgeError> {
                 init.with_body(Some(JsValue::from(readable)));
             }
         }
-        Body::Stream(stream) => {
+        Body::Stream(stream) | Body::StreamWithTrailers(stream, _) => {
             let mapped = stream
                 .map(|res| match res {
                     Ok(bytes) => Ok::<Vec<u8>, JsValue>(bytes.to_vec()),
@@ -134,6 +146,13 @@ fn http_method_to_cf(method: &Method) -> CfMethod {
     }
 }
 
+/// A signal that aborts the fetch, including reading its body, once
+/// `timeout` has passed.
+fn timeout_signal(timeout: Duration) -> AbortSignal {
+    let millis = u32::try_from(timeout.as_millis()).unwrap_or(u32::MAX);
+    AbortSignal::from(web_sys::AbortSignal::timeout_with_u32(millis))
+}
+
 fn transform_stream(
     stream: ChunkStream,
     encoding: Option<&str>,
@@ -161,7 +180,7 @@ mod tests {
     fn collect_body(body: Body) -> Vec<u8> {
         match body {
             Body::Once(bytes) => bytes.to_vec(),
-            Body::Stream(mut stream) => block_on(async {
+            Body::Stream(mut stream) | Body::StreamWithTrailers(mut stream, _) => block_on(async {
                 let mut out = Vec::new();
                 while let Some(item) = stream.next().await {
                     let chunk = item.expect("chunk");
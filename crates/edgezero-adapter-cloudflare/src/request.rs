@@ -1,10 +1,16 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Display;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
 
 use edgezero_core::app::{App, StoreMetadata};
 use edgezero_core::body::Body;
+use edgezero_core::body_limit::BodyLimit;
 use edgezero_core::config_store::ConfigStoreHandle;
+use edgezero_core::connection::ConnectionInfo;
+use edgezero_core::context::AdapterName;
+use edgezero_core::deadline::{Sleep, SleepHandle};
 use edgezero_core::env_config::EnvConfig;
 use edgezero_core::error::EdgeError;
 use edgezero_core::http::{Method as CoreMethod, Request, Uri, request_builder};
@@ -14,17 +20,34 @@ use edgezero_core::secret_store::SecretHandle;
 use edgezero_core::store_registry::{
     BoundSecretStore, ConfigRegistry, ConfigStoreBinding, KvRegistry, SecretRegistry, StoreRegistry,
 };
+use futures::future::LocalBoxFuture;
 use worker::{
-    Context, Env, Error as WorkerError, Method, Request as CfRequest, Response as CfResponse,
+    Context, Delay, Env, Error as WorkerError, Method, Request as CfRequest, Response as CfResponse,
 };
 
 use crate::config_store::CloudflareConfigStore;
 use crate::context::CloudflareRequestContext;
+use crate::durable_kv_store::DurableObjectKvStore;
 use crate::key_value_store::CloudflareKvStore;
 use crate::proxy::CloudflareProxyClient;
 use crate::response::from_core_response;
 use crate::secret_store::CloudflareSecretStore;
 
+/// Request body cap applied unless the app sets `max-body-bytes` under
+/// `[app]`: Workers on the Free and Pro plans reject bodies over 100 MB. See
+/// [`edgezero_core::body_limit`].
+pub const DEFAULT_MAX_BODY_BYTES: usize = 100_000_000;
+
+/// The timer route timeouts race handlers against: the Workers
+/// `setTimeout`.
+struct WorkerSleep;
+
+impl Sleep for WorkerSleep {
+    fn sleep(&self, duration: Duration) -> LocalBoxFuture<'static, ()> {
+        Box::pin(Delay::from(duration))
+    }
+}
+
 /// Groups the optional per-request store handles injected at dispatch time.
 ///
 /// Use `..Default::default()` for fields you do not need:
@@ -260,6 +283,7 @@ pub async fn into_core_request(
         builder = builder.header(name.as_str(), value);
     }
 
+    let connection = connection_info(&req);
     let bytes = req.bytes().await.map_err(EdgeError::internal)?;
 
     let mut request = builder
@@ -267,12 +291,51 @@ pub async fn into_core_request(
         .map_err(EdgeError::internal)?;
 
     CloudflareRequestContext::insert(&mut request, env, ctx);
+    request.extensions_mut().insert(connection);
     request
         .extensions_mut()
         .insert(ProxyHandle::with_client(CloudflareProxyClient));
+    request
+        .extensions_mut()
+        .insert(BodyLimit::new(DEFAULT_MAX_BODY_BYTES));
+    request
+        .extensions_mut()
+        .insert(SleepHandle::with_sleep(WorkerSleep));
+    request.extensions_mut().insert(AdapterName("cloudflare"));
     Ok(request)
 }
 
+/// Connection metadata Cloudflare reports for `req`: the client IP from
+/// `CF-Connecting-IP` (without a port) and the TLS details from `request.cf`.
+/// Workers see neither the local address nor the ALPN protocol.
+fn connection_info(req: &CfRequest) -> ConnectionInfo {
+    let peer_addr = req
+        .headers()
+        .get("cf-connecting-ip")
+        .ok()
+        .flatten()
+        .and_then(|raw| raw.parse::<IpAddr>().ok())
+        .map(|ip| SocketAddr::new(ip, 0));
+    let Some(cf) = req.cf() else {
+        return ConnectionInfo {
+            peer_addr,
+            ..ConnectionInfo::default()
+        };
+    };
+    let client_cert_subject = cf
+        .tls_client_auth()
+        .filter(|auth| auth.cert_presented() == "1")
+        .map(|auth| auth.cert_subject_dn())
+        .filter(|subject| !subject.is_empty());
+    let tls_version = Some(cf.tls_version()).filter(|version| !version.is_empty());
+    ConnectionInfo {
+        client_cert_subject,
+        peer_addr,
+        tls_version,
+        ..ConnectionInfo::default()
+    }
+}
+
 pub(crate) async fn dispatch_with_handles(
     app: &App,
     req: CfRequest,
@@ -320,11 +383,26 @@ pub(crate) async fn dispatch_with_registries(
     .await
 }
 
+/// Open the store bound as `kv_binding`: a [`DurableObjectKvStore`] when the
+/// binding is a Durable Object namespace (one object per binding, named
+/// after it), Workers KV otherwise.
 pub(crate) fn resolve_kv_handle(
     env: &Env,
     kv_binding: &str,
     kv_required: bool,
 ) -> Result<Option<KvHandle>, WorkerError> {
+    if env.durable_object(kv_binding).is_ok() {
+        return match DurableObjectKvStore::from_env(env, kv_binding, kv_binding) {
+            Ok(store) => Ok(Some(KvHandle::new(Arc::new(store)))),
+            Err(err) if kv_required => Err(WorkerError::RustError(format!(
+                "KV binding '{kv_binding}' is explicitly configured but could not be opened: {err}"
+            ))),
+            Err(err) => {
+                warn_missing_kv_binding_once(kv_binding, &err);
+                Ok(None)
+            }
+        };
+    }
     match CloudflareKvStore::from_env(env, kv_binding) {
         Ok(store) => Ok(Some(KvHandle::new(Arc::new(store)))),
         Err(err) => {
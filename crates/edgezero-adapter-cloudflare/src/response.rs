@@ -20,7 +20,8 @@ pub fn from_core_response(response: Response) -> Result<CfResponse, EdgeError> {
             CfResponse::empty().map_err(EdgeError::internal)?
         }
         Body::Once(bytes) => CfResponse::from_bytes(bytes.to_vec()).map_err(EdgeError::internal)?,
-        Body::Stream(stream) => {
+        // Workers responses cannot carry trailers; only the stream is sent.
+        Body::Stream(stream) | Body::StreamWithTrailers(stream, _) => {
             let worker_stream = stream
                 .map(|res| match res {
                     Ok(bytes) => Ok::<Vec<u8>, WorkerError>(bytes.to_vec()),
@@ -26,6 +26,7 @@ mod tests {
     use edgezero_core::context::RequestContext;
     use edgezero_core::error::EdgeError;
     use edgezero_core::http::{Method, Response, StatusCode, response_builder};
+    use edgezero_core::params::PathParams;
     use edgezero_core::router::RouterService;
     use futures::stream;
     use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
@@ -247,6 +248,18 @@ mod tests {
         assert!(CloudflareRequestContext::get(&core_request).is_some());
     }
 
+    #[wasm_bindgen_test]
+    async fn into_core_request_records_the_adapter_name() {
+        let req = cf_request(CfMethod::Get, "/", None);
+        let (env, ctx) = test_env_ctx();
+
+        let core_request = into_core_request(req, env, ctx)
+            .await
+            .expect("core request");
+        let request_ctx = RequestContext::new(core_request, PathParams::default());
+        assert_eq!(request_ctx.adapter_name(), Some("cloudflare"));
+    }
+
     #[wasm_bindgen_test]
     async fn service_with_config_handle_injects_handle() {
         let app = build_test_app();
@@ -11,7 +11,7 @@ use async_trait::async_trait;
 #[cfg(feature = "fastly")]
 use bytes::Bytes;
 #[cfg(feature = "fastly")]
-use edgezero_core::key_value_store::{KvError, KvPage, KvStore};
+use edgezero_core::key_value_store::{KvError, KvOp, KvPage, KvStore, apply_sequentially};
 #[cfg(feature = "fastly")]
 use fastly::kv_store::{KVStore, KVStoreError};
 #[cfg(feature = "fastly")]
@@ -44,12 +44,23 @@ impl FastlyKvStore {
 
 #[cfg(feature = "fastly")]
 #[async_trait(?Send)]
+#[expect(
+    clippy::missing_trait_methods,
+    reason = "Fastly KV has no atomic check-and-write, so compare_and_swap and increment stay unsupported"
+)]
 impl KvStore for FastlyKvStore {
+    /// Fastly KV has no multi-key transactions, so batches are applied one op
+    /// at a time and are not atomic.
+    #[inline]
+    async fn apply_batch(&self, ops: Vec<KvOp>) -> Result<(), KvError> {
+        apply_sequentially(self, ops).await
+    }
+
     #[inline]
     async fn delete(&self, key: &str) -> Result<(), KvError> {
         self.store
             .delete(key)
-            .map_err(|err| KvError::Internal(anyhow::anyhow!("delete failed: {err}")))
+            .map_err(|err| map_kv_error("delete", &err))
     }
 
     #[inline]
@@ -65,7 +76,7 @@ impl KvStore for FastlyKvStore {
                 Ok(Some(Bytes::from(bytes)))
             }
             Err(KVStoreError::ItemNotFound) => Ok(None),
-            Err(err) => Err(KvError::Internal(anyhow::anyhow!("lookup failed: {err}"))),
+            Err(err) => Err(map_kv_error("lookup", &err)),
         }
     }
 
@@ -76,10 +87,7 @@ impl KvStore for FastlyKvStore {
         cursor: Option<&str>,
         limit: usize,
     ) -> Result<KvPage, KvError> {
-        let limit_u32 = u32::try_from(limit)
-            .map_err(|_e| KvError::Validation("list limit exceeds u32".to_owned()))?;
-
-        let mut request = self.store.build_list().limit(limit_u32);
+        let mut request = self.store.build_list().limit(list_limit(limit)?);
 
         if !prefix.is_empty() {
             request = request.prefix(prefix);
@@ -90,7 +98,7 @@ impl KvStore for FastlyKvStore {
 
         let page = request
             .execute()
-            .map_err(|err| KvError::Internal(anyhow::anyhow!("list failed: {err}")))?;
+            .map_err(|err| map_kv_error("list", &err))?;
         let next_cursor = page.next_cursor().filter(|token| !token.is_empty());
 
         Ok(KvPage {
@@ -103,7 +111,7 @@ impl KvStore for FastlyKvStore {
     async fn put_bytes(&self, key: &str, value: Bytes) -> Result<(), KvError> {
         self.store
             .insert(key, value.as_ref())
-            .map_err(|err| KvError::Internal(anyhow::anyhow!("insert failed: {err}")))
+            .map_err(|err| map_kv_error("insert", &err))
     }
 
     #[inline]
@@ -117,9 +125,91 @@ impl KvStore for FastlyKvStore {
             .build_insert()
             .time_to_live(ttl)
             .execute(key, value.as_ref())
-            .map_err(|err| KvError::Internal(anyhow::anyhow!("insert with ttl failed: {err}")))
+            .map_err(|err| map_kv_error("insert with ttl", &err))
     }
 }
 
-// TODO: integration tests require the Fastly compute environment.
-// Test `FastlyKvStore` as part of the Fastly adapter E2E test suite.
+/// Map a Fastly KV failure for `operation` onto the portable [`KvError`].
+///
+/// Caller-side faults (bad key, oversized payload) become
+/// [`KvError::Validation`], a missing store or rate limit becomes
+/// [`KvError::Unavailable`] / [`KvError::LimitExceeded`], and anything else
+/// is [`KvError::Internal`]. `ItemNotFound` is handled by the callers (a
+/// lookup miss is `Ok(None)`, not an error) and only reaches here from
+/// operations where it is a genuine failure.
+#[cfg(feature = "fastly")]
+fn map_kv_error(operation: &str, err: &KVStoreError) -> KvError {
+    #[expect(
+        clippy::wildcard_enum_match_arm,
+        reason = "external non-exhaustive enum; new variants must remain internal errors"
+    )]
+    match err {
+        KVStoreError::InvalidKey | KVStoreError::ItemPayloadTooLarge => {
+            KvError::Validation(format!("{operation} rejected: {err}"))
+        }
+        KVStoreError::StoreNotFound(_) => KvError::Unavailable,
+        KVStoreError::TooManyRequests => KvError::LimitExceeded {
+            message: format!("{operation} rate limited: {err}"),
+        },
+        _ => KvError::Internal(anyhow::anyhow!("{operation} failed: {err}")),
+    }
+}
+
+/// Convert the portable `usize` page size into Fastly's `u32` list limit.
+#[cfg(feature = "fastly")]
+fn list_limit(limit: usize) -> Result<u32, KvError> {
+    u32::try_from(limit).map_err(|_e| KvError::Validation("list limit exceeds u32".to_owned()))
+}
+
+// Store round-trips need a live KV Store and are covered by the Fastly E2E
+// suite; the error and limit mapping below is pure and runs under Viceroy.
+#[cfg(test)]
+#[cfg(feature = "fastly")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caller_faults_map_to_validation() {
+        for err in [KVStoreError::InvalidKey, KVStoreError::ItemPayloadTooLarge] {
+            let mapped = map_kv_error("insert", &err);
+            assert!(
+                matches!(&mapped, KvError::Validation(msg) if msg.starts_with("insert rejected")),
+                "unexpected mapping for {err:?}: {mapped:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn list_limit_rejects_values_above_u32() {
+        assert_eq!(list_limit(100).expect("fits"), 100);
+        let Some(too_big) = usize::try_from(u64::from(u32::MAX) + 1).ok() else {
+            // 32-bit targets cannot represent a limit above u32::MAX.
+            return;
+        };
+        assert!(matches!(list_limit(too_big), Err(KvError::Validation(_))));
+    }
+
+    #[test]
+    fn missing_store_maps_to_unavailable() {
+        let mapped = map_kv_error(
+            "lookup",
+            &KVStoreError::StoreNotFound("sessions".to_owned()),
+        );
+        assert!(matches!(mapped, KvError::Unavailable));
+    }
+
+    #[test]
+    fn rate_limit_maps_to_limit_exceeded() {
+        let mapped = map_kv_error("list", &KVStoreError::TooManyRequests);
+        assert!(matches!(mapped, KvError::LimitExceeded { .. }));
+    }
+
+    #[test]
+    fn unexpected_errors_map_to_internal_with_operation() {
+        let mapped = map_kv_error("delete", &KVStoreError::ItemPreconditionFailed);
+        let KvError::Internal(source) = mapped else {
+            panic!("expected internal error");
+        };
+        assert!(source.to_string().starts_with("delete failed"));
+    }
+}
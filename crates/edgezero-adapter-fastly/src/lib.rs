@@ -29,6 +29,8 @@ use edgezero_core::http::Extensions;
 #[cfg(feature = "fastly")]
 use edgezero_core::manifest::ResolvedLoggingConfig;
 #[cfg(feature = "fastly")]
+use futures::executor;
+#[cfg(feature = "fastly")]
 #[derive(Debug, Clone)]
 pub struct FastlyLogging {
     pub echo_stdout: bool,
@@ -109,10 +111,13 @@ fn logging_from_env(env: &EnvConfig) -> FastlyLogging {
 /// `EDGEZERO__*` environment variables. No `edgezero.toml` is required.
 ///
 /// # Errors
-/// Returns an error if logger setup fails or any required store cannot be opened.
+/// Returns an error if logger setup fails, the app fails `App::validate` or
+/// a middleware setup, or any required store cannot be opened.
 #[cfg(feature = "fastly")]
 #[inline]
-pub fn run_app<A: Hooks>(req: fastly::Request) -> Result<fastly::Response, fastly::Error> {
+pub fn run_app<A: Hooks + 'static>(
+    req: fastly::Request,
+) -> Result<fastly::Response, fastly::Error> {
     run_app_with_request_extensions::<A, _>(req, |_req, _extensions| {})
 }
 
@@ -123,7 +128,8 @@ pub fn run_app<A: Hooks>(req: fastly::Request) -> Result<fastly::Response, fastl
 /// extensions and are visible to middleware and the `State`/extractor layer.
 ///
 /// # Errors
-/// Returns an error if logger setup fails or any required store cannot be opened.
+/// Returns an error if logger setup fails, the app fails `App::validate` or
+/// a middleware setup, or any required store cannot be opened.
 #[cfg(feature = "fastly")]
 #[inline]
 pub fn run_app_with_request_extensions<A, F>(
@@ -131,7 +137,7 @@ pub fn run_app_with_request_extensions<A, F>(
     extend: F,
 ) -> Result<fastly::Response, fastly::Error>
 where
-    A: Hooks,
+    A: Hooks + 'static,
     F: FnOnce(&fastly::Request, &mut Extensions),
 {
     let stores = A::stores();
@@ -141,7 +147,7 @@ where
         let endpoint = logging.endpoint.as_deref().unwrap_or("stdout");
         init_logger(endpoint, logging.level, logging.echo_stdout)?;
     }
-    let app = A::build_app();
+    let app = executor::block_on(A::build_validated_app()?.ready())?;
     request::dispatch_with_registries(
         &app,
         req,
@@ -225,10 +231,11 @@ fn env_config_from_runtime_dictionary(stores: StoresMetadata) -> EnvConfig {
 /// `FastlyService` builder if you need KV alongside the config store.
 ///
 /// # Errors
-/// Returns an error if logger setup fails or the underlying handler returns an error.
+/// Returns an error if logger setup fails, the app fails `App::validate` or
+/// a middleware setup, or the underlying handler returns an error.
 #[cfg(feature = "fastly")]
 #[inline]
-pub fn run_app_with_config<A: Hooks>(
+pub fn run_app_with_config<A: Hooks + 'static>(
     logging: &FastlyLogging,
     req: fastly::Request,
     config_store_name: Option<&str>,
@@ -237,7 +244,7 @@ pub fn run_app_with_config<A: Hooks>(
         let endpoint = logging.endpoint.as_deref().unwrap_or("stdout");
         init_logger(endpoint, logging.level, logging.echo_stdout)?;
     }
-    let app = A::build_app();
+    let app = executor::block_on(A::build_validated_app()?.ready())?;
     let mut service = request::FastlyService::new(&app);
     if let Some(name) = config_store_name {
         service = service.with_config(name);
@@ -3,9 +3,11 @@ use async_trait::async_trait;
 use bytes::Bytes;
 use edgezero_core::body::Body;
 use edgezero_core::compression::{decode_brotli_stream, decode_gzip_stream};
+use edgezero_core::deadline::Deadline;
 use edgezero_core::error::EdgeError;
 use edgezero_core::http::{HeaderMap, HeaderValue, Method, Uri, header};
 use edgezero_core::proxy::{PROXY_HEADER, ProxyClient, ProxyRequest, ProxyResponse};
+use fastly::http::request::{SendError, SendErrorCause};
 use fastly::{
     Backend, Request as FastlyRequest, Response as FastlyResponse, error::anyhow,
     http::body::StreamingBody,
@@ -15,6 +17,9 @@ use std::io::{self, Write as _};
 use std::time::Duration;
 
 const BACKEND_PREFIX: &str = "edgezero-dynamic-";
+const BETWEEN_BYTES_TIMEOUT: Duration = Duration::from_secs(10);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+const FIRST_BYTE_TIMEOUT: Duration = Duration::from_secs(15);
 
 type ChunkStream = BoxStream<'static, Result<Vec<u8>, io::Error>>;
 
@@ -24,15 +29,28 @@ pub struct FastlyProxyClient;
 impl ProxyClient for FastlyProxyClient {
     #[inline]
     async fn send(&self, request: ProxyRequest) -> Result<ProxyResponse, EdgeError> {
+        // Backends only take whole-second timeouts, so a deadline that has
+        // already passed would still get a second; answer it here instead.
+        if request
+            .extensions()
+            .get::<Deadline>()
+            .is_some_and(Deadline::is_expired)
+        {
+            return Err(EdgeError::gateway_timeout(
+                "request deadline passed before the upstream request was sent",
+            ));
+        }
+        // Capped by any request deadline; see `ProxyRequest::timeout`.
+        let timeout = request.timeout().map(whole_seconds);
         let (method, uri, headers, body, _ext) = request.into_parts();
-        let backend_name = ensure_backend(&uri)?;
+        let backend_name = ensure_backend(&uri, timeout)?;
         let fastly_request = build_fastly_request(method, &uri, &headers);
         let (mut streaming_body, pending_request) = fastly_request
             .send_async_streaming(&backend_name)
             .map_err(EdgeError::internal)?;
         forward_request_body(body, &mut streaming_body).await?;
         streaming_body.finish().map_err(EdgeError::internal)?;
-        let mut fastly_response = pending_request.wait().map_err(EdgeError::internal)?;
+        let mut fastly_response = pending_request.wait().map_err(send_error)?;
 
         let mut proxy_response = convert_response(&mut fastly_response);
         proxy_response
@@ -105,7 +123,10 @@ fn convert_response(fastly_response: &mut FastlyResponse) -> ProxyResponse {
     proxy_response
 }
 
-fn ensure_backend(uri: &Uri) -> Result<String, EdgeError> {
+/// Register (or reuse) the dynamic backend for `uri`. Timeouts belong to the
+/// backend, so a request `timeout` below the defaults gets a backend of its
+/// own, named after it.
+fn ensure_backend(uri: &Uri, timeout: Option<Duration>) -> Result<String, EdgeError> {
     let host = uri
         .host()
         .ok_or_else(|| EdgeError::bad_request("proxy target must include host"))?;
@@ -121,15 +142,22 @@ fn ensure_backend(uri: &Uri) -> Result<String, EdgeError> {
 
     let host_with_port = format!("{host}:{target_port}");
 
-    // Human-readable name: backend_{scheme}_{host}_{port} with dots/colons sanitised
-    let name_base = format!("{scheme}_{host}_{target_port}");
+    // Human-readable name: backend_{scheme}_{host}_{port} with dots/colons
+    // sanitised, plus `_t{secs}` for a shortened timeout.
+    let limit = timeout.filter(|requested| *requested < FIRST_BYTE_TIMEOUT);
+    let name_base = match limit {
+        Some(shortened) => format!("{scheme}_{host}_{target_port}_t{}", shortened.as_secs()),
+        None => format!("{scheme}_{host}_{target_port}"),
+    };
     let backend_name = format!("{}{}", BACKEND_PREFIX, name_base.replace(['.', ':'], "_"));
 
     let mut builder = Backend::builder(&backend_name, &host_with_port)
         .override_host(host)
-        .connect_timeout(Duration::from_secs(1))
-        .first_byte_timeout(Duration::from_secs(15))
-        .between_bytes_timeout(Duration::from_secs(10));
+        .connect_timeout(limit.map_or(CONNECT_TIMEOUT, |shortened| shortened.min(CONNECT_TIMEOUT)))
+        .first_byte_timeout(limit.unwrap_or(FIRST_BYTE_TIMEOUT))
+        .between_bytes_timeout(limit.map_or(BETWEEN_BYTES_TIMEOUT, |shortened| {
+            shortened.min(BETWEEN_BYTES_TIMEOUT)
+        }));
 
     if is_https {
         builder = builder
@@ -180,12 +208,15 @@ async fn forward_request_body(
                     .map_err(EdgeError::internal)?;
             }
         }
-        Body::Stream(mut stream) => {
+        Body::Stream(mut stream) | Body::StreamWithTrailers(mut stream, _) => {
+            // Flush each chunk so the backend receives it as it arrives
+            // rather than when `StreamingBody`'s write buffer fills.
             while let Some(result) = stream.next().await {
                 let chunk = result.map_err(EdgeError::internal)?;
                 streaming_body
                     .write_all(&chunk)
                     .map_err(EdgeError::internal)?;
+                streaming_body.flush().map_err(EdgeError::internal)?;
             }
         }
     }
@@ -195,6 +226,22 @@ async fn forward_request_body(
     Ok(())
 }
 
+/// Map a failed send to an error, answering the backend timeouts with
+/// `504 Gateway Timeout`.
+fn send_error(err: SendError) -> EdgeError {
+    let timed_out = matches!(
+        err.root_cause(),
+        SendErrorCause::ConnectionTimeout
+            | SendErrorCause::DnsTimeout
+            | SendErrorCause::HttpResponseTimeout
+    );
+    if timed_out {
+        EdgeError::gateway_timeout(format!("upstream timed out: {err}"))
+    } else {
+        EdgeError::internal(err)
+    }
+}
+
 fn transform_stream(
     stream: ChunkStream,
     encoding: Option<&str>,
@@ -206,17 +253,27 @@ fn transform_stream(
     }
 }
 
+/// `timeout` rounded up to whole seconds, at least one, so the deadline-capped
+/// timeouts of different requests share a bounded set of backends.
+fn whole_seconds(timeout: Duration) -> Duration {
+    let secs = timeout
+        .as_secs()
+        .saturating_add(u64::from(timeout.subsec_nanos() > 0));
+    Duration::from_secs(secs.max(1))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use brotli::CompressorWriter;
+    use edgezero_core::http::StatusCode;
     use flate2::{Compression, write::GzEncoder};
     use futures::executor::block_on;
 
     fn collect_body(body: Body) -> Vec<u8> {
         match body {
             Body::Once(bytes) => bytes.to_vec(),
-            Body::Stream(mut stream) => block_on(async {
+            Body::Stream(mut stream) | Body::StreamWithTrailers(mut stream, _) => block_on(async {
                 let mut out = Vec::new();
                 while let Some(chunk) = stream.next().await {
                     out.extend_from_slice(&chunk.expect("chunk"));
@@ -226,6 +283,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn expired_deadline_is_answered_without_sending() {
+        let mut request = ProxyRequest::new(Method::GET, Uri::from_static("https://example.com/"));
+        request
+            .extensions_mut()
+            .insert(Deadline::after(Duration::ZERO));
+
+        let err = block_on(FastlyProxyClient.send(request)).expect_err("deadline passed");
+        assert_eq!(err.status(), StatusCode::GATEWAY_TIMEOUT);
+    }
+
     #[test]
     fn convert_response_preserves_multi_value_set_cookie() {
         let mut fastly_response = FastlyResponse::from_status(200);
@@ -273,4 +341,21 @@ mod tests {
             Body::from_stream(transform_stream(fastly_body_stream(gz_body), Some("gzip")));
         assert_eq!(collect_body(gzip_body), b"hello gzip");
     }
+
+    #[test]
+    fn timeouts_round_up_to_whole_seconds() {
+        assert_eq!(
+            whole_seconds(Duration::from_millis(1)),
+            Duration::from_secs(1)
+        );
+        assert_eq!(whole_seconds(Duration::ZERO), Duration::from_secs(1));
+        assert_eq!(
+            whole_seconds(Duration::from_millis(2_500)),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            whole_seconds(Duration::from_secs(4)),
+            Duration::from_secs(4)
+        );
+    }
 }
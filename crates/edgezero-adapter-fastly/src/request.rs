@@ -1,11 +1,15 @@
 use std::collections::{HashSet, VecDeque};
 use std::fmt::Display;
 use std::io::Read as _;
+use std::net::SocketAddr;
 use std::sync::{Arc, Mutex, OnceLock, PoisonError};
 
 use edgezero_core::app::{App, StoreMetadata};
 use edgezero_core::body::Body;
+use edgezero_core::body_limit::BodyLimit;
 use edgezero_core::config_store::ConfigStoreHandle;
+use edgezero_core::connection::ConnectionInfo;
+use edgezero_core::context::AdapterName;
 use edgezero_core::env_config::EnvConfig;
 use edgezero_core::error::EdgeError;
 use edgezero_core::http::{Extensions, Request, request_builder};
@@ -26,6 +30,11 @@ use crate::proxy::FastlyProxyClient;
 use crate::response::{from_core_response, parse_uri};
 use crate::secret_store::FastlySecretStore;
 
+/// Request body cap applied unless the app sets `max-body-bytes` under
+/// `[app]`. The whole body is read into memory, so keep this modest. See
+/// [`edgezero_core::body_limit`].
+pub const DEFAULT_MAX_BODY_BYTES: usize = 32 * 1024 * 1024;
+
 const WARNED_STORE_CACHE_LIMIT: usize = 64;
 
 #[derive(Default)]
@@ -489,13 +498,30 @@ pub fn into_core_request(mut req: FastlyRequest) -> Result<Request, EdgeError> {
         client_ip: req.get_client_ip_addr(),
     };
     FastlyRequestContext::insert(&mut request, context);
+    request.extensions_mut().insert(connection_info(&req));
     request
         .extensions_mut()
         .insert(ProxyHandle::with_client(FastlyProxyClient));
+    request
+        .extensions_mut()
+        .insert(BodyLimit::new(DEFAULT_MAX_BODY_BYTES));
+    request.extensions_mut().insert(AdapterName("fastly"));
 
     Ok(request)
 }
 
+/// Connection metadata Fastly reports for `req`. Fastly exposes addresses
+/// without ports, and neither the ALPN protocol nor a parsed client
+/// certificate subject.
+fn connection_info(req: &FastlyRequest) -> ConnectionInfo {
+    ConnectionInfo {
+        local_addr: req.get_server_ip_addr().map(|ip| SocketAddr::new(ip, 0)),
+        peer_addr: req.get_client_ip_addr().map(|ip| SocketAddr::new(ip, 0)),
+        tls_version: req.get_tls_protocol().ok().flatten().map(str::to_owned),
+        ..ConnectionInfo::default()
+    }
+}
+
 fn map_edge_error(err: &EdgeError) -> FastlyError {
     FastlyError::msg(err.to_string())
 }
@@ -15,7 +15,8 @@ pub fn from_core_response(response: Response) -> Result<FastlyResponse, EdgeErro
 
     match body {
         Body::Once(bytes) => fastly_response.set_body(bytes.to_vec()),
-        Body::Stream(mut stream) => {
+        // Compute has no response-trailer API, so trailers are dropped.
+        Body::Stream(mut stream) | Body::StreamWithTrailers(mut stream, _) => {
             let mut fastly_body = fastly::Body::new();
             while let Some(result) = executor::block_on(stream.next()) {
                 let chunk = result.map_err(EdgeError::internal)?;
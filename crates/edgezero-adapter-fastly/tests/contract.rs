@@ -12,6 +12,17 @@ mod secret_store_compile_check {
     const _: fn() = assert_provider_impl::<FastlySecretStore>;
 }
 
+// Compile-time check: FastlyKvStore implements KvStore, so `dispatch` can wrap
+// it in the `KvHandle`s that back the `Kv` extractor.
+mod kv_store_compile_check {
+    use edgezero_adapter_fastly::key_value_store::FastlyKvStore;
+    use edgezero_core::key_value_store::KvStore;
+
+    fn assert_kv_impl<T: KvStore>() {}
+
+    const _: fn() = assert_kv_impl::<FastlyKvStore>;
+}
+
 #[cfg(test)]
 mod tests {
     use bytes::Bytes;
@@ -20,10 +31,13 @@ mod tests {
     use edgezero_adapter_fastly::response::from_core_response;
     use edgezero_core::app::App;
     use edgezero_core::body::Body;
+    use edgezero_core::body_spool::BodyBuffering;
     use edgezero_core::config_store::{ConfigStore, ConfigStoreError, ConfigStoreHandle};
     use edgezero_core::context::RequestContext;
     use edgezero_core::error::EdgeError;
+    use edgezero_core::extractor::{FromRequest as _, Json};
     use edgezero_core::http::{Method, Response, StatusCode, response_builder};
+    use edgezero_core::params::PathParams;
     use edgezero_core::router::RouterService;
     use fastly::Request as FastlyRequest;
     use fastly::http::{Method as FastlyMethod, StatusCode as FastlyStatus};
@@ -141,6 +155,15 @@ mod tests {
         assert_eq!(context.client_ip, expected_ip);
     }
 
+    #[test]
+    fn into_core_request_records_the_adapter_name() {
+        let req = fastly_request(FastlyMethod::GET, "/", None);
+
+        let core_request = into_core_request(req).expect("core request");
+        let ctx = RequestContext::new(core_request, PathParams::default());
+        assert_eq!(ctx.adapter_name(), Some("fastly"));
+    }
+
     #[test]
     fn from_core_response_translates_status_headers_and_streaming_body() {
         let response = response_builder()
@@ -199,6 +222,33 @@ mod tests {
         assert_eq!(response.take_body_bytes(), b"echo");
     }
 
+    #[test]
+    fn dispatch_rejects_bodies_past_the_spill_threshold() {
+        let router = RouterService::builder()
+            .with_body_buffering(BodyBuffering::Spill { threshold: 8 })
+            .post("/mirror", |ctx: RequestContext| async move {
+                let Json(value) = Json::<serde_json::Value>::from_request(&ctx).await?;
+                Ok::<_, EdgeError>(value.to_string())
+            })
+            .build();
+        let app = App::new(router);
+        let json_request = |body: &[u8]| {
+            let mut req = fastly_request(FastlyMethod::POST, "/mirror", Some(body));
+            req.set_header("content-type", "application/json");
+            req
+        };
+
+        let within = FastlyService::new(&app)
+            .dispatch(json_request(b"[1,2,3]"))
+            .expect("fastly response");
+        assert_eq!(within.get_status(), FastlyStatus::OK);
+        // Fastly installs no spool, so a larger body is rejected.
+        let over = FastlyService::new(&app)
+            .dispatch(json_request(b"[1,2,3,4,5]"))
+            .expect("fastly response");
+        assert_eq!(over.get_status(), FastlyStatus::PAYLOAD_TOO_LARGE);
+    }
+
     #[test]
     fn service_with_config_handle_injects_handle() {
         let app = build_test_app();
@@ -46,6 +46,19 @@ pub(crate) fn parse_client_addr(raw: &str) -> Option<IpAddr> {
     raw.parse::<IpAddr>().ok()
 }
 
+/// Parse the client's socket address from a `host:port` string.
+///
+/// A bare IP is accepted with port `0`, matching the other adapters'
+/// "IP only" addresses in [`ConnectionInfo`].
+///
+/// [`ConnectionInfo`]: edgezero_core::connection::ConnectionInfo
+#[cfg(any(test, all(feature = "spin", target_arch = "wasm32")))]
+pub(crate) fn parse_peer_addr(raw: &str) -> Option<SocketAddr> {
+    raw.parse::<SocketAddr>()
+        .ok()
+        .or_else(|| raw.parse::<IpAddr>().ok().map(|ip| SocketAddr::new(ip, 0)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -115,4 +128,17 @@ mod tests {
         let ip = parse_client_addr("[::1]:3000").unwrap();
         assert_eq!(ip, IpAddr::from_str("::1").unwrap());
     }
+
+    #[test]
+    fn parse_peer_addr_keeps_the_port() {
+        let addr = parse_peer_addr("192.168.1.1:8080").unwrap();
+        assert_eq!(addr, SocketAddr::from_str("192.168.1.1:8080").unwrap());
+    }
+
+    #[test]
+    fn parse_peer_addr_defaults_a_bare_ip_to_port_zero() {
+        let addr = parse_peer_addr("::1").unwrap();
+        assert_eq!(addr, SocketAddr::from_str("[::1]:0").unwrap());
+        assert!(parse_peer_addr("not-an-ip").is_none());
+    }
 }
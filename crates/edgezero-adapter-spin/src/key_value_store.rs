@@ -21,7 +21,7 @@
 
 use async_trait::async_trait;
 use bytes::Bytes;
-use edgezero_core::key_value_store::{KvError, KvPage, KvStore};
+use edgezero_core::key_value_store::{KvError, KvOp, KvPage, KvStore, apply_sequentially};
 use spin_sdk::key_value::Store as SpinSdkStore;
 use std::time::Duration;
 
@@ -77,7 +77,18 @@ impl SpinKvStore {
 }
 
 #[async_trait(?Send)]
+#[expect(
+    clippy::missing_trait_methods,
+    reason = "Spin's key-value store has no atomic check-and-write, so compare_and_swap and increment stay unsupported"
+)]
 impl KvStore for SpinKvStore {
+    /// Spin KV has no multi-key transactions, so batches are applied one op
+    /// at a time and are not atomic.
+    #[inline]
+    async fn apply_batch(&self, ops: Vec<KvOp>) -> Result<(), KvError> {
+        apply_sequentially(self, ops).await
+    }
+
     #[inline]
     async fn delete(&self, key: &str) -> Result<(), KvError> {
         self.store
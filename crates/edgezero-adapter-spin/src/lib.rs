@@ -104,11 +104,12 @@ pub fn init_logger() -> Result<(), log::SetLoggerError> {
 /// signature because `SpinFullResponse: spin_sdk::http::IntoResponse`.
 ///
 /// # Errors
-/// Returns [`anyhow::Error`] when the inner dispatch fails — transport,
-/// router, store binding, or response translation errors propagate here.
+/// Returns [`anyhow::Error`] when the app fails `App::validate` or a
+/// middleware setup, or the inner dispatch fails — transport, router, store
+/// binding, or response translation errors propagate here.
 #[cfg(all(feature = "spin", target_arch = "wasm32"))]
 #[inline]
-pub async fn run_app<A: Hooks>(req: SpinRequest) -> anyhow::Result<SpinFullResponse> {
+pub async fn run_app<A: Hooks + 'static>(req: SpinRequest) -> anyhow::Result<SpinFullResponse> {
     // Best-effort: every Spin `#[http_service]` re-enters this function, so a
     // second `log::set_logger` call returns Err — drop the result instead of
     // `.expect()` to avoid panicking on every subsequent request. Skipped
@@ -118,7 +119,7 @@ pub async fn run_app<A: Hooks>(req: SpinRequest) -> anyhow::Result<SpinFullRespo
     }
     let env = EnvConfig::from_env();
     let stores = A::stores();
-    let app = A::build_app();
+    let app = A::build_validated_app()?.ready().await?;
     request::dispatch_with_registries(&app, req, stores.config, stores.kv, stores.secrets, &env)
         .await
 }
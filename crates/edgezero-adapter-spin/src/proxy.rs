@@ -8,6 +8,9 @@ use edgezero_core::http::{HeaderValue, header};
 use edgezero_core::proxy::{PROXY_HEADER, ProxyClient, ProxyRequest, ProxyResponse};
 use spin_sdk::http::body::IncomingBodyExt as _;
 use spin_sdk::http::{FullBody, Request as SpinRequest, send};
+use spin_sdk::wasip3::http::types::{ErrorCode, RequestOptions};
+use spin_sdk::wasip3::http_compat::RequestOptionsExtension;
+use std::time::Duration;
 
 /// A proxy client that uses Spin's outbound HTTP (`spin_sdk::http::send`)
 /// to forward requests to upstream services.
@@ -17,9 +20,14 @@ pub struct SpinProxyClient;
 impl ProxyClient for SpinProxyClient {
     #[inline]
     async fn send(&self, request: ProxyRequest) -> Result<ProxyResponse, EdgeError> {
+        // Capped by any request deadline; see `ProxyRequest::timeout`.
+        let options = request.timeout().map(timeout_options).transpose()?;
         let (method, uri, headers, body, _extensions) = request.into_parts();
 
         let mut builder = SpinRequest::builder().method(method).uri(uri.to_string());
+        if let Some(limits) = options {
+            builder = builder.extension(RequestOptionsExtension(limits));
+        }
 
         for (name, value) in &headers {
             builder = builder.header(name, value);
@@ -34,7 +42,18 @@ impl ProxyClient for SpinProxyClient {
             })?;
 
         let spin_response = send(spin_request).await.map_err(|err| {
-            EdgeError::internal(anyhow::anyhow!("Spin outbound HTTP error: {err}"))
+            let timed_out = matches!(
+                err,
+                ErrorCode::ConnectionReadTimeout
+                    | ErrorCode::ConnectionTimeout
+                    | ErrorCode::DnsTimeout
+                    | ErrorCode::HttpResponseTimeout
+            );
+            if timed_out {
+                EdgeError::gateway_timeout(format!("upstream timed out: {err}"))
+            } else {
+                EdgeError::internal(anyhow::anyhow!("Spin outbound HTTP error: {err}"))
+            }
         })?;
 
         let (response_parts, response_body) = spin_response.into_parts();
@@ -77,3 +96,24 @@ impl ProxyClient for SpinProxyClient {
         Ok(proxy_response)
     }
 }
+
+/// Outbound request options bounding the connect, first-byte and
+/// between-bytes phases by `timeout`.
+///
+/// # Errors
+/// Returns [`EdgeError::not_implemented`] when the host does not support
+/// outbound timeouts, rather than sending without one.
+fn timeout_options(timeout: Duration) -> Result<RequestOptions, EdgeError> {
+    let nanos = u64::try_from(timeout.as_nanos()).unwrap_or(u64::MAX);
+    let options = RequestOptions::new();
+    options
+        .set_connect_timeout(Some(nanos))
+        .and_then(|()| options.set_first_byte_timeout(Some(nanos)))
+        .and_then(|()| options.set_between_bytes_timeout(Some(nanos)))
+        .map_err(|err| {
+            EdgeError::not_implemented(format!(
+                "Spin outbound HTTP cannot apply the request timeout: {err:?}"
+            ))
+        })?;
+    Ok(options)
+}
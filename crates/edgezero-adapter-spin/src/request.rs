@@ -1,18 +1,23 @@
 use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Context as _;
 
 use crate::SpinFullResponse;
 use crate::config_store::SpinConfigStore;
-use crate::context::{SpinRequestContext, parse_client_addr};
+use crate::context::{SpinRequestContext, parse_client_addr, parse_peer_addr};
 use crate::key_value_store::{DEFAULT_MAX_LIST_KEYS, SpinKvStore};
 use crate::proxy::SpinProxyClient;
 use crate::response::from_core_response;
 use crate::secret_store::SpinSecretStore;
 use edgezero_core::app::{App, StoreMetadata};
 use edgezero_core::body::Body;
+use edgezero_core::body_limit::BodyLimit;
 use edgezero_core::config_store::ConfigStoreHandle;
+use edgezero_core::connection::ConnectionInfo;
+use edgezero_core::context::AdapterName;
+use edgezero_core::deadline::{Sleep, SleepHandle};
 use edgezero_core::env_config::EnvConfig;
 use edgezero_core::error::EdgeError;
 use edgezero_core::http::{Request, request_builder};
@@ -22,8 +27,25 @@ use edgezero_core::secret_store::SecretHandle;
 use edgezero_core::store_registry::{
     BoundSecretStore, ConfigRegistry, ConfigStoreBinding, KvRegistry, SecretRegistry, StoreRegistry,
 };
+use futures::future::LocalBoxFuture;
 use spin_sdk::http::Request as SpinRequest;
 use spin_sdk::http::body::IncomingBodyExt as _;
+use spin_sdk::time;
+
+/// Request body cap applied unless the app sets `max-body-bytes` under
+/// `[app]`. The whole body is read into memory, so keep this modest. See
+/// [`edgezero_core::body_limit`].
+pub const DEFAULT_MAX_BODY_BYTES: usize = 32 * 1024 * 1024;
+
+/// The timer route timeouts race handlers against: the WASI monotonic
+/// clock.
+struct SpinSleep;
+
+impl Sleep for SpinSleep {
+    fn sleep(&self, duration: Duration) -> LocalBoxFuture<'static, ()> {
+        Box::pin(time::sleep(duration))
+    }
+}
 
 /// Per-dispatch store wiring assembled before the request enters the router.
 /// The struct itself is `pub(crate)` because `dispatch_with_handles` takes it
@@ -42,7 +64,9 @@ pub(crate) struct Stores {
 /// Convert a Spin `Request` into an `EdgeZero` core `Request`.
 ///
 /// Reads the full body into a buffered `Body::Once`, inserts
-/// `SpinRequestContext` and a `ProxyHandle` into extensions.
+/// `SpinRequestContext`, a [`ConnectionInfo`] carrying the `spin-client-addr`
+/// peer address, a `ProxyHandle`, a `SleepHandle` and the default
+/// [`BodyLimit`] into extensions.
 ///
 /// # Errors
 /// Returns [`EdgeError::bad_request`] if the request body cannot be read or
@@ -51,11 +75,12 @@ pub(crate) struct Stores {
 pub async fn into_core_request(req: SpinRequest) -> Result<Request, EdgeError> {
     let (parts, body) = req.into_parts();
 
-    let client_addr = parts
+    let raw_client_addr = parts
         .headers
         .get("spin-client-addr")
-        .and_then(|value| value.to_str().ok())
-        .and_then(parse_client_addr);
+        .and_then(|value| value.to_str().ok());
+    let client_addr = raw_client_addr.and_then(parse_client_addr);
+    let peer_addr = raw_client_addr.and_then(parse_peer_addr);
     let full_url = parts
         .headers
         .get("spin-full-url")
@@ -67,10 +92,9 @@ pub async fn into_core_request(req: SpinRequest) -> Result<Request, EdgeError> {
         builder = builder.header(name, value);
     }
 
-    // Inbound body size is not capped at the adapter level. The Spin runtime
-    // enforces its own request body limit (configurable via `spin.toml`), which
-    // is consistent with how the Fastly and Cloudflare adapters delegate inbound
-    // size enforcement to their respective platform runtimes.
+    // The Spin runtime enforces its own request body limit (configurable via
+    // `spin.toml`); the router checks `DEFAULT_MAX_BODY_BYTES` (or the app's
+    // `max-body-bytes`) once the body is buffered.
     let body_bytes = body
         .bytes()
         .await
@@ -87,13 +111,25 @@ pub async fn into_core_request(req: SpinRequest) -> Result<Request, EdgeError> {
             full_url,
         },
     );
-    request
-        .extensions_mut()
-        .insert(ProxyHandle::with_client(SpinProxyClient));
+    request.extensions_mut().insert(ConnectionInfo {
+        peer_addr,
+        ..ConnectionInfo::default()
+    });
+    insert_runtime_extensions(&mut request);
 
     Ok(request)
 }
 
+/// The extensions every Spin request carries whatever it contains: the
+/// proxy client, the default body limit, the timer and the adapter name.
+fn insert_runtime_extensions(request: &mut Request) {
+    let extensions = request.extensions_mut();
+    extensions.insert(ProxyHandle::with_client(SpinProxyClient));
+    extensions.insert(BodyLimit::new(DEFAULT_MAX_BODY_BYTES));
+    extensions.insert(SleepHandle::with_sleep(SpinSleep));
+    extensions.insert(AdapterName("spin"));
+}
+
 /// Dispatch a Spin request through the `EdgeZero` router using the `"default"`
 /// KV store label.
 ///
@@ -377,7 +413,9 @@ fn resolve_secret_handle(secrets_enabled: bool) -> Option<SecretHandle> {
 mod synthesis_tests {
     use super::*;
     use edgezero_core::config_store::{ConfigStore, ConfigStoreError, ConfigStoreHandle};
+    use edgezero_core::context::RequestContext;
     use edgezero_core::key_value_store::{KvStore, NoopKvStore};
+    use edgezero_core::params::PathParams;
     use edgezero_core::secret_store::{NoopSecretStore, SecretHandle};
     use std::collections::BTreeMap;
     use std::sync::Arc;
@@ -403,6 +441,23 @@ mod synthesis_tests {
         SecretHandle::new(Arc::new(NoopSecretStore))
     }
 
+    #[test]
+    fn requests_carry_the_proxy_client_body_limit_and_adapter_name() {
+        let mut request = request_builder()
+            .uri("/")
+            .body(Body::empty())
+            .expect("request");
+        insert_runtime_extensions(&mut request);
+
+        let ctx = RequestContext::new(request, PathParams::default());
+        assert_eq!(ctx.adapter_name(), Some("spin"));
+        assert!(ctx.proxy_handle().is_some());
+        assert_eq!(
+            ctx.request().extensions().get::<BodyLimit>(),
+            Some(&BodyLimit::new(DEFAULT_MAX_BODY_BYTES))
+        );
+    }
+
     #[test]
     fn synthesis_wraps_bare_kv_handle_under_default_when_no_registry() {
         let stores = Stores {
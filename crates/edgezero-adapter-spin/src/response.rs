@@ -24,7 +24,8 @@ const MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
 pub(crate) async fn collect_body_bytes(body: Body) -> Result<Vec<u8>, EdgeError> {
     match body {
         Body::Once(bytes) => Ok(bytes.to_vec()),
-        Body::Stream(mut stream) => {
+        // The body is buffered, which leaves nowhere to put trailers.
+        Body::Stream(mut stream) | Body::StreamWithTrailers(mut stream, _) => {
             let mut collected = Vec::new();
             while let Some(chunk) = stream.next().await {
                 match chunk {
@@ -111,7 +111,9 @@ mod tests {
     use edgezero_core::context::RequestContext;
     use edgezero_core::error::EdgeError;
     use edgezero_core::http::{Response, StatusCode, request_builder, response_builder};
-    use edgezero_core::key_value_store::{KvError, KvHandle, KvPage, KvStore};
+    use edgezero_core::key_value_store::{
+        KvError, KvHandle, KvOp, KvPage, KvStore, apply_sequentially,
+    };
     use edgezero_core::router::RouterService;
     use edgezero_core::secret_store::{SecretError, SecretHandle, SecretStore};
     use edgezero_core::store_registry::{
@@ -146,7 +148,14 @@ mod tests {
     }
 
     #[async_trait::async_trait(?Send)]
+    #[expect(
+        clippy::missing_trait_methods,
+        reason = "a read-only fixture; atomic updates are not exercised"
+    )]
     impl KvStore for FixedKvStore {
+        async fn apply_batch(&self, ops: Vec<KvOp>) -> Result<(), KvError> {
+            apply_sequentially(self, ops).await
+        }
         async fn delete(&self, _key: &str) -> Result<(), KvError> {
             Ok(())
         }
@@ -1,3 +1,32 @@
+//! Process-wide registry of [`Adapter`] implementations the CLI dispatches to.
+//!
+//! Every adapter — built-in or out-of-tree — participates the same way:
+//! implement [`Adapter`] on a `'static` value and pass it to
+//! [`register_adapter`] before the CLI handles a command. `edgezero build`,
+//! `deploy`, `serve`, `auth`, `provision`, and `config` run the manifest's
+//! `[adapters.<name>.commands]` entry when one is set and otherwise resolve
+//! `<name>` here with [`get_adapter`]. Names are case-insensitive, and a
+//! later registration under the same name replaces the earlier one.
+//!
+//! A third-party adapter ships a `register()` function, which a downstream
+//! CLI binary (see `edgezero_cli`) calls at the top of `main`, alongside
+//! [`register_adapter_blueprint`] so `edgezero new` can scaffold it:
+//!
+//! ```rust,ignore
+//! static ACME: AcmeAdapter = AcmeAdapter;
+//! static ACME_BLUEPRINT: AdapterBlueprint = AdapterBlueprint { id: "acme", /* ... */ };
+//!
+//! pub fn register() {
+//!     edgezero_adapter::registry::register_adapter(&ACME);
+//!     edgezero_adapter::scaffold::register_adapter_blueprint(&ACME_BLUEPRINT);
+//! }
+//! ```
+//!
+//! The built-in adapters also run their `register()` from a `#[ctor]`, so
+//! linking the crate is enough; out-of-tree adapters may do the same.
+//!
+//! [`register_adapter_blueprint`]: crate::scaffold::register_adapter_blueprint
+
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::{LazyLock, PoisonError, RwLock};
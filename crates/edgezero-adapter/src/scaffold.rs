@@ -1,3 +1,10 @@
+//! Scaffolding blueprints `edgezero new` uses to generate adapter crates.
+//!
+//! Register an [`AdapterBlueprint`] with [`register_adapter_blueprint`]
+//! next to the adapter itself (see [`crate::registry`]); the scaffolder
+//! offers every registered blueprint. Ids are case-insensitive, and a later
+//! registration with the same id replaces the earlier one.
+
 use std::collections::HashMap;
 use std::sync::{LazyLock, PoisonError, RwLock};
 
@@ -78,6 +85,15 @@ pub struct TemplateRegistration {
     pub name: &'static str,
 }
 
+/// Looks up a blueprint by adapter id.
+#[inline]
+pub fn get_blueprint(id: &str) -> Option<&'static AdapterBlueprint> {
+    let registry = BLUEPRINT_REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner);
+    registry.get(&id.to_ascii_lowercase()).copied()
+}
+
 /// Registers the blueprint for an adapter. Latest registration wins.
 #[inline]
 pub fn register_adapter_blueprint(blueprint: &'static AdapterBlueprint) {
@@ -194,6 +210,15 @@ mod tests {
 
     static TEST_LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
 
+    #[test]
+    fn get_blueprint_is_case_insensitive() {
+        let _guard = TEST_LOCK.lock().expect("lock");
+        super::BLUEPRINT_REGISTRY.write().expect("lock").clear();
+        register_adapter_blueprint(&BLUEPRINT_BETA);
+        assert_eq!(get_blueprint("BETA").map(|bp| bp.id), Some("beta"));
+        assert!(get_blueprint("alpha").is_none());
+    }
+
     #[test]
     fn latest_blueprint_wins() {
         let _guard = TEST_LOCK.lock().expect("lock");
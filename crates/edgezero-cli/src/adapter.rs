@@ -256,11 +256,41 @@ fn shell_join(args: &[String]) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{ResolvedEnvironment, apply_environment};
+    use super::{Action, ResolvedEnvironment, apply_environment, execute};
     use crate::test_support::manifest_guard;
-    use edgezero_core::manifest::ResolvedEnvironmentBinding;
+    use edgezero_adapter::registry::{Adapter, AdapterAction, register_adapter};
+    use edgezero_core::manifest::{ManifestLoader, ResolvedEnvironmentBinding};
     use edgezero_core::test_env::EnvOverride;
     use std::process::Command;
+    use std::sync::Mutex;
+
+    static THIRD_PARTY: ThirdPartyAdapter = ThirdPartyAdapter {
+        calls: Mutex::new(Vec::new()),
+    };
+
+    /// Out-of-tree adapter stand-in: registered at runtime rather than
+    /// linked in through a CLI feature, and records every dispatch.
+    struct ThirdPartyAdapter {
+        calls: Mutex<Vec<(AdapterAction, Vec<String>)>>,
+    }
+
+    #[expect(
+        clippy::missing_trait_methods,
+        reason = "only `execute` is dispatched here; the validation methods keep the trait defaults"
+    )]
+    impl Adapter for ThirdPartyAdapter {
+        fn execute(&self, action: AdapterAction, args: &[String]) -> Result<(), String> {
+            self.calls
+                .lock()
+                .map_err(|err| err.to_string())?
+                .push((action, args.to_vec()));
+            Ok(())
+        }
+
+        fn name(&self) -> &'static str {
+            "acme-edge"
+        }
+    }
 
     #[test]
     fn apply_environment_sets_defaults_and_checks_secrets() {
@@ -368,6 +398,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn registered_third_party_adapter_handles_build() {
+        let _lock = manifest_guard().lock().expect("registry lock");
+        register_adapter(&THIRD_PARTY);
+        THIRD_PARTY.calls.lock().expect("calls lock").clear();
+
+        // Without a manifest, and with a manifest that declares the adapter
+        // but no `build` command, dispatch falls through to the registry.
+        execute("ACME-EDGE", Action::Build, None, &["--release".to_owned()])
+            .expect("build via registry");
+        let loader = ManifestLoader::load_from_str(
+            "[app]\nname = \"demo\"\n\n[adapters.acme-edge.adapter]\ncrate = \"crates/demo-acme\"\n",
+        );
+        execute("acme-edge", Action::Build, Some(&loader), &[]).expect("build via registry");
+
+        let calls = THIRD_PARTY.calls.lock().expect("calls lock");
+        assert_eq!(
+            *calls,
+            vec![
+                (AdapterAction::Build, vec!["--release".to_owned()]),
+                (AdapterAction::Build, Vec::new()),
+            ]
+        );
+    }
+
     #[test]
     fn shell_escape_quotes_and_spaces() {
         assert_eq!(super::shell_escape("plain"), "plain");
@@ -33,6 +33,9 @@ pub enum Command {
     Demo,
     /// Deploy to a target edge.
     Deploy(DeployArgs),
+    /// Add code to an existing app (`generate route …`).
+    #[command(subcommand)]
+    Generate(GenerateCmd),
     /// Create a new `EdgeZero` app skeleton (multi-crate workspace).
     New(NewArgs),
     /// Create the platform resources backing the declared
@@ -69,6 +72,14 @@ pub enum ConfigCmd {
     Validate(ConfigValidateArgs),
 }
 
+/// Subcommands under `edgezero generate …`.
+#[derive(Subcommand, Debug)]
+pub enum GenerateCmd {
+    /// Append an `#[action]` handler stub to the core crate and route it
+    /// with a `[[triggers.http]]` entry in `edgezero.toml`.
+    Route(GenerateRouteArgs),
+}
+
 /// Hidden catch-all argument sink for the bundled stub variants of
 /// `config push` and `config diff`.  Absorbs any flags the user types
 /// so clap does not error before we can print the pointer text (3.2.2).
@@ -150,6 +161,23 @@ pub struct DeployArgs {
     pub adapter_args: Vec<String>,
 }
 
+/// Arguments for the `generate route` command.
+#[derive(clap::Args, Debug)]
+#[non_exhaustive]
+pub struct GenerateRouteArgs {
+    /// Path to the manifest (default: `edgezero.toml`).
+    #[arg(long, default_value = "edgezero.toml")]
+    pub manifest: PathBuf,
+    /// HTTP method the route answers.
+    #[arg(long, default_value = "GET")]
+    pub method: String,
+    /// Handler function name (`snake_case`); also used as the trigger id.
+    pub name: String,
+    /// Route path, e.g. `/users/{id}`.
+    #[arg(long)]
+    pub path: String,
+}
+
 /// Arguments for the `new` command.
 #[derive(clap::Args, Debug, Default)]
 #[non_exhaustive]
@@ -203,6 +231,9 @@ pub struct ServeArgs {
     /// Target adapter name.
     #[arg(long = "adapter", required = true)]
     pub adapter: String,
+    /// Watch the project sources and restart the server when they change.
+    #[arg(long)]
+    pub watch: bool,
 }
 
 /// Output format for `config diff`.
@@ -0,0 +1,270 @@
+//! `edgezero serve --watch`: restart the local server when sources change.
+//!
+//! The watcher re-runs the current binary as `serve --adapter <name>` in a
+//! child process, watches the project directory (the manifest's directory,
+//! or the working directory without a manifest) with `notify`, and once a
+//! burst of changes has settled for [`DEBOUNCE`] stops the child and starts
+//! a fresh one. The adapter's serve command — `cargo run` for the built-in
+//! axum adapter — rebuilds the app as part of starting.
+//!
+//! On Unix the child runs in its own process group so a restart stops the
+//! whole tree (`sh`, `cargo`, the server) rather than just the direct child;
+//! Ctrl-C is caught here and stops the group before exiting.
+
+use std::env;
+#[cfg(unix)]
+use std::os::unix::process::CommandExt as _;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Child, Command};
+use std::sync::mpsc::{self, RecvTimeoutError};
+#[cfg(unix)]
+use std::thread;
+use std::time::{Duration, Instant};
+
+use notify::{Event, RecursiveMode, Watcher as _};
+
+use crate::args::ServeArgs;
+
+/// Quiet period after the last change before the server restarts.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Directory names whose contents never trigger a restart: build output
+/// (which the restart itself rewrites) and VCS metadata.
+const IGNORED_DIRS: [&str; 4] = [".git", ".spin", "node_modules", "target"];
+
+/// How often the loop wakes without events to check the debounce window.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long a stopped server gets to exit before it is killed.
+#[cfg(unix)]
+const STOP_GRACE: Duration = Duration::from_secs(5);
+
+/// Collapses a burst of file changes into a single restart.
+///
+/// Every change pushes the restart back; [`Self::take_ready`] fires once
+/// no change has arrived for the whole window.
+#[derive(Debug)]
+pub(crate) struct Debouncer {
+    last_change: Option<Instant>,
+    window: Duration,
+}
+
+impl Debouncer {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            last_change: None,
+            window,
+        }
+    }
+
+    /// Note a change observed at `at`.
+    pub(crate) fn record(&mut self, at: Instant) {
+        self.last_change = Some(at);
+    }
+
+    /// Whether a restart is due at `now`; clears the pending change when it
+    /// is.
+    pub(crate) fn take_ready(&mut self, now: Instant) -> bool {
+        let ready = self
+            .last_change
+            .is_some_and(|last| now.saturating_duration_since(last) >= self.window);
+        if ready {
+            self.last_change = None;
+        }
+        ready
+    }
+}
+
+/// A running `serve` child process.
+struct ServerProcess {
+    child: Child,
+    exited: bool,
+}
+
+impl ServerProcess {
+    /// Log once if the server exited on its own (e.g. a build error); the
+    /// next change starts it again.
+    fn report_exit(&mut self) {
+        if self.exited {
+            return;
+        }
+        if let Ok(Some(status)) = self.child.try_wait() {
+            self.exited = true;
+            log::warn!("[edgezero] server exited with {status}; waiting for changes");
+        }
+    }
+
+    fn spawn(adapter: &str) -> Result<Self, String> {
+        let exe = env::current_exe()
+            .map_err(|err| format!("failed to locate the edgezero binary: {err}"))?;
+        let mut command = Command::new(exe);
+        command.args(["serve", "--adapter", adapter]);
+        #[cfg(unix)]
+        command.process_group(0);
+        let child = command
+            .spawn()
+            .map_err(|err| format!("failed to start serve for adapter `{adapter}`: {err}"))?;
+        Ok(Self {
+            child,
+            exited: false,
+        })
+    }
+
+    /// Stop the server: `SIGTERM` to its process group on Unix, then a
+    /// hard kill if it is still running after [`STOP_GRACE`].
+    fn stop(mut self) {
+        if self.exited || matches!(self.child.try_wait(), Ok(Some(_))) {
+            return;
+        }
+        #[cfg(unix)]
+        {
+            let group = format!("-{}", self.child.id());
+            if let Err(err) = Command::new("kill").args(["-TERM", "--", &group]).status() {
+                log::warn!("[edgezero] failed to signal server: {err}");
+            }
+            let deadline = Instant::now().checked_add(STOP_GRACE);
+            while deadline.is_some_and(|limit| Instant::now() < limit) {
+                if matches!(self.child.try_wait(), Ok(Some(_))) {
+                    return;
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+        if let Err(err) = self.child.kill() {
+            log::warn!("[edgezero] failed to stop server: {err}");
+        }
+        if let Err(err) = self.child.wait() {
+            log::warn!("[edgezero] failed to reap server: {err}");
+        }
+    }
+}
+
+/// Messages from the file watcher and the Ctrl-C handler.
+enum WatchEvent {
+    Changed(notify::Result<Event>),
+    Interrupted,
+}
+
+/// Whether a change to `path` should restart the server. Paths under
+/// [`IGNORED_DIRS`] (relative to `root`) and editor swap/backup files are
+/// ignored.
+pub(crate) fn is_watched(path: &Path, root: &Path) -> bool {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let in_ignored_dir = relative.components().any(|component| {
+        matches!(component, Component::Normal(name)
+            if IGNORED_DIRS.iter().any(|ignored| name == *ignored))
+    });
+    if in_ignored_dir {
+        return false;
+    }
+    let file_name = relative
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    !(file_name.ends_with('~')
+        || file_name.ends_with(".swp")
+        || file_name.ends_with(".swx")
+        || file_name.starts_with(".#"))
+}
+
+/// Serve `args.adapter`, restarting on every settled burst of source
+/// changes under `root` until interrupted.
+pub(crate) fn run_watch(args: &ServeArgs, root: &Path) -> Result<(), String> {
+    let (sender, events) = mpsc::channel();
+    let change_sender = sender.clone();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        change_sender
+            .send(WatchEvent::Changed(event))
+            .unwrap_or_default();
+    })
+    .map_err(|err| format!("failed to start file watcher: {err}"))?;
+    watcher
+        .watch(root, RecursiveMode::Recursive)
+        .map_err(|err| format!("failed to watch {}: {err}", root.display()))?;
+    ctrlc::set_handler(move || sender.send(WatchEvent::Interrupted).unwrap_or_default())
+        .map_err(|err| format!("failed to install Ctrl-C handler: {err}"))?;
+
+    log::info!(
+        "[edgezero] watching {} for changes (Ctrl-C to stop)",
+        root.display()
+    );
+    let mut server = ServerProcess::spawn(&args.adapter)?;
+    let mut debouncer = Debouncer::new(DEBOUNCE);
+    loop {
+        match events.recv_timeout(POLL_INTERVAL) {
+            Ok(WatchEvent::Changed(Ok(event))) => {
+                if event.paths.iter().any(|path| is_watched(path, root)) {
+                    debouncer.record(Instant::now());
+                }
+            }
+            Ok(WatchEvent::Changed(Err(err))) => {
+                log::warn!("[edgezero] file watcher error: {err}");
+            }
+            Ok(WatchEvent::Interrupted) => {
+                server.stop();
+                return Ok(());
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                server.stop();
+                return Err("file watcher stopped unexpectedly".to_owned());
+            }
+        }
+        if debouncer.take_ready(Instant::now()) {
+            log::info!("[edgezero] change detected; restarting `{}`", args.adapter);
+            server.stop();
+            server = ServerProcess::spawn(&args.adapter)?;
+        }
+        server.report_exit();
+    }
+}
+
+/// Directory to watch: the manifest's directory, else the working
+/// directory.
+pub(crate) fn watch_root(manifest_root: Option<&Path>) -> Result<PathBuf, String> {
+    match manifest_root {
+        Some(root) if !root.as_os_str().is_empty() => Ok(root.to_path_buf()),
+        _ => env::current_dir()
+            .map_err(|err| format!("failed to resolve the working directory: {err}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_of_changes_restarts_once_after_quiet_period() {
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+        let mut debouncer = Debouncer::new(Duration::from_millis(300));
+
+        assert!(!debouncer.take_ready(at(0)), "nothing pending");
+        debouncer.record(at(0));
+        debouncer.record(at(100));
+        debouncer.record(at(250));
+        assert!(!debouncer.take_ready(at(400)), "last change too recent");
+        assert!(debouncer.take_ready(at(550)), "quiet for the full window");
+        assert!(!debouncer.take_ready(at(2_000)), "fires once per burst");
+
+        debouncer.record(at(3_000));
+        assert!(debouncer.take_ready(at(3_300)), "next burst fires again");
+    }
+
+    #[test]
+    fn build_output_and_editor_files_are_ignored() {
+        let root = Path::new("/work/app");
+        assert!(is_watched(
+            Path::new("/work/app/crates/core/src/lib.rs"),
+            root
+        ));
+        assert!(is_watched(Path::new("/work/app/edgezero.toml"), root));
+        assert!(!is_watched(Path::new("/work/app/target/debug/app"), root));
+        assert!(!is_watched(Path::new("/work/app/.git/index"), root));
+        assert!(!is_watched(Path::new("/work/app/src/.lib.rs.swp"), root));
+        assert!(!is_watched(Path::new("/work/app/src/lib.rs~"), root));
+        // A project that itself lives under a `target` directory still works.
+        let nested = Path::new("/target/app");
+        assert!(is_watched(Path::new("/target/app/src/main.rs"), nested));
+    }
+}
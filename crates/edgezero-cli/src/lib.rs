@@ -28,12 +28,16 @@ mod config;
 #[cfg(all(feature = "cli", feature = "demo-example"))]
 mod demo_server;
 #[cfg(feature = "cli")]
+mod dev_server;
+#[cfg(feature = "cli")]
 mod diff;
 #[cfg(feature = "cli")]
 mod generator;
 #[cfg(feature = "cli")]
 mod provision;
 #[cfg(feature = "cli")]
+mod route_generator;
+#[cfg(feature = "cli")]
 mod scaffold;
 #[cfg(all(test, feature = "cli"))]
 mod test_support;
@@ -55,7 +59,7 @@ pub use config::{
 pub use provision::run_provision;
 
 #[cfg(feature = "cli")]
-use args::{BuildArgs, DeployArgs, NewArgs, ServeArgs};
+use args::{BuildArgs, DeployArgs, GenerateRouteArgs, NewArgs, ServeArgs};
 #[cfg(feature = "cli")]
 use edgezero_core::manifest::ManifestLoader;
 #[cfg(feature = "cli")]
@@ -170,15 +174,27 @@ pub fn run_deploy(args: &DeployArgs) -> Result<(), String> {
 
 /// Run a local simulation for a target edge adapter.
 ///
+/// With `--watch`, the server runs as a child process that is restarted
+/// whenever the project sources change (see `dev_server`).
+///
 /// # Errors
 ///
 /// Returns an error if the manifest cannot be loaded, the adapter is not
-/// configured, or the adapter serve command fails.
+/// configured, the adapter serve command fails, or the file watcher cannot
+/// be started.
 #[cfg(feature = "cli")]
 #[inline]
 pub fn run_serve(args: &ServeArgs) -> Result<(), String> {
     let manifest = load_manifest_optional()?;
     ensure_adapter_defined(&args.adapter, manifest.as_ref())?;
+    if args.watch {
+        let root = dev_server::watch_root(
+            manifest
+                .as_ref()
+                .and_then(|loader| loader.manifest().root()),
+        )?;
+        return dev_server::run_watch(args, &root);
+    }
     adapter::execute(
         &args.adapter,
         adapter::Action::Serve,
@@ -198,6 +214,34 @@ pub fn run_new(args: &NewArgs) -> Result<(), String> {
     generator::generate_new(args).map_err(|err| err.to_string())
 }
 
+/// Add a handler stub and its route to the app described by
+/// `args.manifest`. Re-running with the same arguments changes nothing.
+///
+/// # Errors
+///
+/// Returns an error if the arguments are invalid, the manifest or core crate
+/// cannot be read, the name, id, or method and path clash with an existing
+/// handler or trigger, or the files cannot be written.
+#[cfg(feature = "cli")]
+#[inline]
+pub fn run_generate_route(args: &GenerateRouteArgs) -> Result<(), String> {
+    match route_generator::generate_route(args)? {
+        route_generator::RouteOutcome::Added { handlers } => log::info!(
+            "[edgezero] added handler `{}` to {} and routed {} {} in {}",
+            args.name,
+            handlers.display(),
+            args.method.to_ascii_uppercase(),
+            args.path,
+            args.manifest.display()
+        ),
+        route_generator::RouteOutcome::Unchanged => log::info!(
+            "[edgezero] route `{}` already exists; nothing to do",
+            args.name
+        ),
+    }
+    Ok(())
+}
+
 /// Run the bundled `app-demo` example locally on the axum dev server.
 ///
 /// Contributor-only: available only under the `demo-example` feature,
@@ -412,6 +456,7 @@ mod tests {
         let _env = EnvOverride::set("EDGEZERO_MANIFEST", &manifest_str);
         let args = ServeArgs {
             adapter: "fastly".to_owned(),
+            watch: false,
         };
         run_serve(&args).expect("serve command runs");
     }
@@ -3,7 +3,7 @@
 #[cfg(feature = "cli")]
 fn main() {
     use clap::Parser as _;
-    use edgezero_cli::args::{self, Args, Command, ConfigCmd};
+    use edgezero_cli::args::{self, Args, Command, ConfigCmd, GenerateCmd};
     use std::process;
 
     edgezero_cli::init_cli_logger();
@@ -31,6 +31,9 @@ fn main() {
         Command::Deploy(cmd_args) => edgezero_cli::run_deploy(&cmd_args),
         #[cfg(feature = "demo-example")]
         Command::Demo => edgezero_cli::run_demo(),
+        Command::Generate(GenerateCmd::Route(cmd_args)) => {
+            edgezero_cli::run_generate_route(&cmd_args)
+        }
         Command::New(cmd_args) => edgezero_cli::run_new(&cmd_args),
         Command::Provision(cmd_args) => edgezero_cli::run_provision(&cmd_args),
         Command::Serve(cmd_args) => edgezero_cli::run_serve(&cmd_args),
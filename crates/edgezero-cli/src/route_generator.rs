@@ -0,0 +1,648 @@
+//! `edgezero generate route`: add a handler and its route to an existing
+//! project.
+//!
+//! Routes in an `EdgeZero` app are registered by `edgezero_core::app!` from
+//! the manifest's `[[triggers.http]]` entries, so generating a route means
+//! two edits: an `#[action]` stub appended to the core crate's
+//! `src/handlers.rs` (ahead of its test module) and a trigger pointing at it
+//! appended after the last existing trigger in `edgezero.toml`.
+//!
+//! Both edits are computed and the new manifest validated before anything
+//! is written, and both files are staged next to their targets before
+//! either is replaced. Re-running with the same arguments is a no-op; a handler or
+//! trigger that already exists with a different shape, or another trigger
+//! already serving the same method and path, aborts without changes.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use edgezero_core::manifest::{Manifest, ManifestLoader};
+use toml::Value;
+
+use crate::args::GenerateRouteArgs;
+
+const ACTION_IMPORT: &str = "use edgezero_core::action;";
+
+/// Paths the handler stub uses, with the glob imports that also bring each
+/// into scope.
+const STUB_PATHS: [(&str, &str, &str); 2] = [
+    ("edgezero_core::action", "edgezero_core::*", ACTION_IMPORT),
+    (
+        "edgezero_core::response::Text",
+        "edgezero_core::response::*",
+        TEXT_IMPORT,
+    ),
+];
+
+const HTTP_METHODS: [&str; 7] = ["DELETE", "GET", "HEAD", "OPTIONS", "PATCH", "POST", "PUT"];
+
+/// Reserved words that cannot name a handler function.
+const RUST_KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "yield",
+];
+
+const TEXT_IMPORT: &str = "use edgezero_core::response::Text;";
+
+/// What [`generate_route`] did.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum RouteOutcome {
+    /// Handler and trigger were written.
+    Added { handlers: PathBuf },
+    /// Both already existed with the requested shape; nothing was written.
+    Unchanged,
+}
+
+/// Validated `generate route` arguments.
+struct RouteSpec {
+    handler_path: String,
+    method: String,
+    name: String,
+    path: String,
+}
+
+impl RouteSpec {
+    /// `Ok(true)` when a trigger with this id already routes exactly this
+    /// handler, method, and path.
+    fn check_triggers(&self, manifest: &Manifest) -> Result<bool, String> {
+        let mut present = false;
+        for trigger in &manifest.triggers.http {
+            let serves_method = trigger.methods().contains(&self.method.as_str());
+            if trigger.id.as_deref() == Some(self.name.as_str()) {
+                if trigger.handler.as_deref() == Some(self.handler_path.as_str())
+                    && trigger.path == self.path
+                    && serves_method
+                {
+                    present = true;
+                    continue;
+                }
+                return Err(format!(
+                    "a trigger with id `{}` already exists with a different handler, path, or method",
+                    self.name
+                ));
+            }
+            if trigger.path == self.path && serves_method {
+                return Err(format!(
+                    "{} {} is already routed to `{}`",
+                    self.method,
+                    self.path,
+                    trigger.handler.as_deref().unwrap_or("(no handler)")
+                ));
+            }
+        }
+        Ok(present)
+    }
+
+    fn handler_stub(&self) -> String {
+        format!(
+            "#[action]\npub async fn {name}() -> Text<&'static str> {{\n    Text::new(\"{name}\")\n}}\n",
+            name = self.name
+        )
+    }
+
+    fn new(args: &GenerateRouteArgs, core_module: &str) -> Result<Self, String> {
+        if !is_handler_name(&args.name) {
+            return Err(format!(
+                "`{}` is not a valid handler name; use a snake_case Rust identifier",
+                args.name
+            ));
+        }
+        let method = args.method.trim().to_ascii_uppercase();
+        if !HTTP_METHODS.contains(&method.as_str()) {
+            return Err(format!(
+                "unsupported method `{}` (expected one of {})",
+                args.method,
+                HTTP_METHODS.join(", ")
+            ));
+        }
+        if !args.path.starts_with('/') {
+            return Err(format!("route path `{}` must start with `/`", args.path));
+        }
+        Ok(Self {
+            handler_path: format!("{core_module}::handlers::{}", args.name),
+            method,
+            name: args.name.clone(),
+            path: args.path.clone(),
+        })
+    }
+
+    fn trigger_block(&self, manifest: &Manifest) -> String {
+        let quote = |value: &str| Value::String(value.to_owned()).to_string();
+        let mut block = format!(
+            "[[triggers.http]]\nid = {}\npath = {}\nmethods = [{}]\nhandler = {}\n",
+            quote(&self.name),
+            quote(&self.path),
+            quote(&self.method),
+            quote(&self.handler_path)
+        );
+        if !manifest.adapters.is_empty() {
+            let adapters: Vec<String> = manifest.adapters.keys().map(|name| quote(name)).collect();
+            block.push_str("adapters = [");
+            block.push_str(&adapters.join(", "));
+            block.push_str("]\n");
+        }
+        block
+    }
+}
+
+/// Add handler `args.name` and its `[[triggers.http]]` entry to the project
+/// whose manifest is `args.manifest`.
+pub(crate) fn generate_route(args: &GenerateRouteArgs) -> Result<RouteOutcome, String> {
+    let manifest_path = args.manifest.as_path();
+    let manifest_text = fs::read_to_string(manifest_path)
+        .map_err(|err| format!("failed to read {}: {err}", manifest_path.display()))?;
+    let loader = ManifestLoader::try_load_from_str(&manifest_text)
+        .map_err(|err| format!("failed to load {}: {err}", manifest_path.display()))?;
+    let manifest = loader.manifest();
+
+    let root = manifest_path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let entry = manifest
+        .app
+        .entry
+        .as_deref()
+        .ok_or("edgezero.toml has no `[app].entry`; cannot locate the core crate")?;
+    let core_dir = root.join(entry);
+    let spec = RouteSpec::new(args, &core_module(&core_dir)?)?;
+
+    let handlers_path = core_dir.join("src/handlers.rs");
+    let handlers_text = fs::read_to_string(&handlers_path)
+        .map_err(|err| format!("failed to read {}: {err}", handlers_path.display()))?;
+
+    let trigger_present = spec.check_triggers(manifest)?;
+    let handler_present = defines_fn(&handlers_text, &spec.name);
+    match (handler_present, trigger_present) {
+        (true, true) => return Ok(RouteOutcome::Unchanged),
+        (true, false) => {
+            return Err(format!(
+                "{} already defines `{}` but no route points at it; pick another name",
+                handlers_path.display(),
+                spec.name
+            ));
+        }
+        (false, true) => {
+            return Err(format!(
+                "edgezero.toml already routes `{}` but {} has no such handler",
+                spec.handler_path,
+                handlers_path.display()
+            ));
+        }
+        (false, false) => {}
+    }
+
+    let new_manifest = insert_trigger(&manifest_text, &spec.trigger_block(manifest));
+    ManifestLoader::try_load_from_str(&new_manifest)
+        .map_err(|err| format!("generated manifest is invalid: {err}"))?;
+    let new_handlers = insert_handler(&handlers_text, &spec.handler_stub());
+
+    write_both(
+        (&handlers_path, &new_handlers, &handlers_text),
+        (manifest_path, &new_manifest),
+    )?;
+    Ok(RouteOutcome::Added {
+        handlers: handlers_path,
+    })
+}
+
+/// The core crate's Rust module name, from its `Cargo.toml` package name.
+fn core_module(core_dir: &Path) -> Result<String, String> {
+    let cargo_path = core_dir.join("Cargo.toml");
+    let cargo = fs::read_to_string(&cargo_path)
+        .map_err(|err| format!("failed to read {}: {err}", cargo_path.display()))?;
+    let parsed: Value = toml::from_str(&cargo)
+        .map_err(|err| format!("failed to parse {}: {err}", cargo_path.display()))?;
+    parsed
+        .get("package")
+        .and_then(|package| package.get("name"))
+        .and_then(Value::as_str)
+        .map(|name| name.replace('-', "_"))
+        .ok_or_else(|| format!("{} has no `[package].name`", cargo_path.display()))
+}
+
+/// Whether `source` declares a function called `name`.
+fn defines_fn(source: &str, name: &str) -> bool {
+    let needle = format!("fn {name}");
+    source.match_indices(&needle).any(|(offset, _)| {
+        let after = source
+            .get(offset.saturating_add(needle.len())..)
+            .unwrap_or_default();
+        after.starts_with(['(', '<'])
+    })
+}
+
+/// Insert `stub` ahead of the `#[cfg(test)]` module (or at the end), adding
+/// the `action` and `Text` imports the stub needs, after the last top-level
+/// `use` item, when no `use` item brings them into scope.
+fn insert_handler(source: &str, stub: &str) -> String {
+    let mut lines: Vec<&str> = source.lines().collect();
+    let uses = use_items(&lines);
+    let imported: Vec<String> = uses
+        .iter()
+        .flat_map(|(_, tree)| {
+            let mut paths = Vec::new();
+            flatten_use_tree(tree, "", &mut paths);
+            paths
+        })
+        .collect();
+    let missing: Vec<&str> = STUB_PATHS
+        .into_iter()
+        .filter(|(path, glob, _)| !imported.iter().any(|seen| seen == path || seen == glob))
+        .map(|(_, _, import)| import)
+        .collect();
+    if !missing.is_empty() {
+        let after_imports = uses.last().map_or(0, |(end, _)| end.saturating_add(1));
+        lines.splice(after_imports..after_imports, missing);
+    }
+
+    let tests_at = lines
+        .iter()
+        .position(|line| *line == "#[cfg(test)]")
+        .unwrap_or(lines.len());
+    let (before, after) = lines.split_at(tests_at);
+    let mut out = before.join("\n").trim_end().to_owned();
+    out.push_str("\n\n");
+    out.push_str(stub);
+    if !after.is_empty() {
+        out.push('\n');
+        out.push_str(&after.join("\n"));
+        out.push('\n');
+    }
+    out
+}
+
+/// Push the paths `tree` imports onto `paths`, each prefixed by `prefix`.
+/// Renamed imports (`Text as Body`) bring in another name and are skipped.
+fn flatten_use_tree(tree: &str, prefix: &str, paths: &mut Vec<String>) {
+    let trimmed = tree.trim();
+    let Some((head, group)) = trimmed.split_once('{') else {
+        if !trimmed.is_empty() && !trimmed.contains(" as ") {
+            let path: String = trimmed.split_whitespace().collect();
+            paths.push(format!("{prefix}{path}"));
+        }
+        return;
+    };
+    let nested_prefix: String = format!("{prefix}{head}").split_whitespace().collect();
+    let inner = group.trim_end().strip_suffix('}').unwrap_or(group);
+    let mut depth = 0_usize;
+    let mut start = 0;
+    for (index, ch) in inner.char_indices() {
+        match ch {
+            '{' => depth = depth.saturating_add(1),
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                flatten_use_tree(
+                    inner.get(start..index).unwrap_or_default(),
+                    &nested_prefix,
+                    paths,
+                );
+                start = index.saturating_add(1);
+            }
+            _ => {}
+        }
+    }
+    flatten_use_tree(
+        inner.get(start..).unwrap_or_default(),
+        &nested_prefix,
+        paths,
+    );
+}
+
+/// Insert `block` after the last `[[triggers.http]]` entry, ahead of any
+/// comments introducing the next section; append when there is none.
+fn insert_trigger(manifest: &str, block: &str) -> String {
+    let lines: Vec<&str> = manifest.lines().collect();
+    let insert_at = lines
+        .iter()
+        .rposition(|line| line.trim() == "[[triggers.http]]")
+        .map_or(lines.len(), |last| {
+            let next_header = lines
+                .iter()
+                .skip(last.saturating_add(1))
+                .position(|line| line.trim_start().starts_with('['))
+                .map_or(lines.len(), |offset| {
+                    last.saturating_add(1).saturating_add(offset)
+                });
+            // Back up over blank lines and comments that belong to the
+            // next section.
+            lines
+                .get(..next_header)
+                .unwrap_or_default()
+                .iter()
+                .rposition(|line| {
+                    let trimmed = line.trim();
+                    !trimmed.is_empty() && !trimmed.starts_with('#')
+                })
+                .map_or(next_header, |index| index.saturating_add(1))
+        });
+    let (before, after) = lines.split_at(insert_at);
+    let mut out = before.join("\n").trim_end().to_owned();
+    out.push_str("\n\n");
+    out.push_str(block);
+    if !after.is_empty() {
+        out.push_str(&after.join("\n"));
+        out.push('\n');
+    }
+    out
+}
+
+/// Top-level `use` items in `lines`, as the index of each item's last line
+/// and its use tree. An item spans lines until its `;` outside braces.
+fn use_items(lines: &[&str]) -> Vec<(usize, String)> {
+    let mut items = Vec::new();
+    let mut pending: Option<String> = None;
+    for (index, line) in lines.iter().enumerate() {
+        let code = line.split("//").next().unwrap_or_default();
+        let text = match pending.take() {
+            Some(mut text) => {
+                text.push(' ');
+                text.push_str(code);
+                text
+            }
+            None => match code
+                .strip_prefix("use ")
+                .or_else(|| code.strip_prefix("pub use "))
+            {
+                Some(rest) => rest.to_owned(),
+                None => continue,
+            },
+        };
+        match use_tree(&text) {
+            Some(tree) => items.push((index, tree.to_owned())),
+            None => pending = Some(text),
+        }
+    }
+    items
+}
+
+/// The use tree in `text`, once it holds the item's closing `;`.
+fn use_tree(text: &str) -> Option<&str> {
+    let mut depth = 0_usize;
+    for (index, ch) in text.char_indices() {
+        match ch {
+            '{' => depth = depth.saturating_add(1),
+            '}' => depth = depth.saturating_sub(1),
+            ';' if depth == 0 => return text.get(..index),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Write the handlers file and the manifest, staging both next to their
+/// targets first so a failed write leaves neither changed. If the manifest
+/// cannot be moved into place, the handlers file is restored to `original`.
+fn write_both(
+    (handlers_path, handlers, original): (&Path, &str, &str),
+    (manifest_path, manifest): (&Path, &str),
+) -> Result<(), String> {
+    let handlers_staged = staged_path(handlers_path);
+    let manifest_staged = staged_path(manifest_path);
+    let staged = fs::write(&handlers_staged, handlers)
+        .map_err(|err| format!("failed to write {}: {err}", handlers_staged.display()))
+        .and_then(|()| {
+            fs::write(&manifest_staged, manifest)
+                .map_err(|err| format!("failed to write {}: {err}", manifest_staged.display()))
+        });
+    if let Err(err) = staged {
+        drop(fs::remove_file(&handlers_staged));
+        drop(fs::remove_file(&manifest_staged));
+        return Err(err);
+    }
+
+    if let Err(err) = fs::rename(&handlers_staged, handlers_path) {
+        drop(fs::remove_file(&handlers_staged));
+        drop(fs::remove_file(&manifest_staged));
+        return Err(format!(
+            "failed to write {}: {err}",
+            handlers_path.display()
+        ));
+    }
+    fs::rename(&manifest_staged, manifest_path).map_err(|err| {
+        drop(fs::remove_file(&manifest_staged));
+        drop(fs::write(handlers_path, original));
+        format!("failed to write {}: {err}", manifest_path.display())
+    })
+}
+
+/// `path` with `.edgezero-new` appended, for staging a write.
+fn staged_path(path: &Path) -> PathBuf {
+    let mut staged = path.as_os_str().to_owned();
+    staged.push(".edgezero-new");
+    PathBuf::from(staged)
+}
+
+fn is_handler_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_lowercase() || first == '_')
+        && chars.all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '_')
+        && name != "_"
+        && !RUST_KEYWORDS.contains(&name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const HANDLERS: &str = "use edgezero_core::action;\nuse edgezero_core::response::Text;\n\n#[action]\npub async fn root() -> Text<&'static str> {\n    Text::new(\"demo app\")\n}\n\n#[cfg(test)]\nmod tests {\n    use super::*;\n}\n";
+
+    const MANIFEST: &str = r#"[app]
+name = "demo-app"
+entry = "crates/demo-app-core"
+
+[[triggers.http]]
+id = "root"
+path = "/"
+methods = ["GET"]
+handler = "demo_app_core::handlers::root"
+adapters = ["axum"]
+
+# -- Stores --
+# [stores.kv]
+
+[adapters.axum.adapter]
+crate = "crates/demo-app-adapter-axum"
+"#;
+
+    fn project() -> TempDir {
+        let temp = TempDir::new().expect("temp dir");
+        let core = temp.path().join("crates/demo-app-core");
+        fs::create_dir_all(core.join("src")).expect("core dir");
+        fs::write(
+            core.join("Cargo.toml"),
+            "[package]\nname = \"demo-app-core\"\n",
+        )
+        .expect("core manifest");
+        fs::write(core.join("src/handlers.rs"), HANDLERS).expect("handlers");
+        fs::write(temp.path().join("edgezero.toml"), MANIFEST).expect("manifest");
+        temp
+    }
+
+    fn route(manifest: &Path, name: &str, method: &str, path: &str) -> GenerateRouteArgs {
+        GenerateRouteArgs {
+            manifest: manifest.to_path_buf(),
+            method: method.to_owned(),
+            name: name.to_owned(),
+            path: path.to_owned(),
+        }
+    }
+
+    #[test]
+    fn generates_handler_and_registers_route() {
+        let temp = project();
+        let manifest_path = temp.path().join("edgezero.toml");
+
+        let outcome = generate_route(&route(&manifest_path, "list_users", "get", "/users"))
+            .expect("generate");
+        let handlers_path = temp.path().join("crates/demo-app-core/src/handlers.rs");
+        assert_eq!(
+            outcome,
+            RouteOutcome::Added {
+                handlers: handlers_path.clone()
+            }
+        );
+
+        let handlers = fs::read_to_string(&handlers_path).expect("handlers");
+        let stub = "#[action]\npub async fn list_users() -> Text<&'static str> {";
+        let stub_at = handlers.find(stub).expect("stub appended");
+        assert!(
+            stub_at < handlers.find("#[cfg(test)]").expect("tests kept"),
+            "stub goes ahead of the test module: {handlers}"
+        );
+
+        let manifest_text = fs::read_to_string(&manifest_path).expect("manifest");
+        let trigger = "[[triggers.http]]\nid = \"list_users\"\npath = \"/users\"\nmethods = [\"GET\"]\nhandler = \"demo_app_core::handlers::list_users\"\nadapters = [\"axum\"]\n";
+        let trigger_at = manifest_text.find(trigger).expect("trigger added");
+        assert!(
+            trigger_at < manifest_text.find("# -- Stores --").expect("comment kept"),
+            "trigger goes with the other triggers: {manifest_text}"
+        );
+        let loader = ManifestLoader::try_load_from_str(&manifest_text).expect("valid manifest");
+        assert_eq!(loader.manifest().triggers.http.len(), 2);
+
+        // Re-running is a no-op.
+        let again =
+            generate_route(&route(&manifest_path, "list_users", "GET", "/users")).expect("rerun");
+        assert_eq!(again, RouteOutcome::Unchanged);
+        assert_eq!(
+            fs::read_to_string(&manifest_path).expect("manifest"),
+            manifest_text
+        );
+    }
+
+    #[test]
+    fn conflicts_abort_without_writing() {
+        let temp = project();
+        let manifest_path = temp.path().join("edgezero.toml");
+
+        for (args, expected) in [
+            (
+                route(&manifest_path, "root_again", "GET", "/"),
+                "already routed",
+            ),
+            (
+                route(&manifest_path, "root", "POST", "/root"),
+                "different handler",
+            ),
+            (
+                route(&manifest_path, "Bad-Name", "GET", "/bad"),
+                "not a valid handler name",
+            ),
+            (
+                route(&manifest_path, "fetch", "TRACE", "/fetch"),
+                "unsupported method",
+            ),
+            (
+                route(&manifest_path, "fetch", "GET", "fetch"),
+                "must start with `/`",
+            ),
+        ] {
+            let err = generate_route(&args).expect_err(expected);
+            assert!(err.contains(expected), "{expected}: {err}");
+        }
+
+        fs::write(
+            temp.path().join("crates/demo-app-core/src/handlers.rs"),
+            format!("{HANDLERS}\nfn helper() {{}}\n"),
+        )
+        .expect("handlers");
+        let err = generate_route(&route(&manifest_path, "helper", "GET", "/helper"))
+            .expect_err("existing fn");
+        assert!(err.contains("already defines `helper`"), "{err}");
+
+        assert_eq!(
+            fs::read_to_string(&manifest_path).expect("manifest"),
+            MANIFEST
+        );
+    }
+
+    #[test]
+    fn missing_imports_are_added() {
+        let updated = insert_handler("mod other;\n", "#[action]\npub async fn a() {}\n");
+        assert!(updated.starts_with(&format!("{ACTION_IMPORT}\n{TEXT_IMPORT}\nmod other;")));
+        assert!(updated.ends_with("pub async fn a() {}\n"), "{updated}");
+    }
+
+    #[test]
+    fn multi_line_use_groups_are_understood() {
+        let stub = "#[action]\npub async fn a() {}\n";
+
+        let grouped =
+            "use edgezero_core::{\n    action,\n    response::{Html, Text},\n};\n\nmod other;\n";
+        assert_eq!(
+            insert_handler(grouped, stub),
+            format!("{}\n\n{stub}", grouped.trim_end())
+        );
+
+        let partial = "use edgezero_core::{\n    action, // the macro\n    extractor::Json,\n};\nuse edgezero_core::response::Text as Body;\n\nmod other;\n";
+        let updated = insert_handler(partial, stub);
+        assert!(
+            updated.starts_with(&format!(
+                "use edgezero_core::{{\n    action, // the macro\n    extractor::Json,\n}};\nuse edgezero_core::response::Text as Body;\n{TEXT_IMPORT}\n\nmod other;"
+            )),
+            "{updated}"
+        );
+
+        let globbed = "use edgezero_core::response::*;\nuse edgezero_core::*;\n";
+        assert_eq!(
+            insert_handler(globbed, stub),
+            format!("{}\n\n{stub}", globbed.trim_end())
+        );
+    }
+
+    #[test]
+    fn writes_leave_no_staged_files() {
+        let temp = project();
+        let manifest_path = temp.path().join("edgezero.toml");
+        generate_route(&route(&manifest_path, "list_users", "GET", "/users")).expect("generate");
+
+        let manifest_blocked = temp.path().join("elsewhere/edgezero.toml");
+        let handlers_path = temp.path().join("crates/demo-app-core/src/handlers.rs");
+        let handlers = fs::read_to_string(&handlers_path).expect("handlers");
+        let err = write_both(
+            (&handlers_path, "changed", &handlers),
+            (&manifest_blocked, "changed"),
+        )
+        .expect_err("missing manifest directory");
+        assert!(err.contains("elsewhere"), "{err}");
+        assert_eq!(
+            fs::read_to_string(&handlers_path).expect("handlers"),
+            handlers
+        );
+
+        let src = temp.path().join("crates/demo-app-core/src");
+        let leftovers: Vec<_> = fs::read_dir(&src)
+            .expect("src")
+            .chain(fs::read_dir(temp.path()).expect("root"))
+            .map(|entry| entry.expect("entry").file_name())
+            .filter(|name| name.to_string_lossy().ends_with(".edgezero-new"))
+            .collect();
+        assert!(leftovers.is_empty(), "{leftovers:?}");
+    }
+}
@@ -1,4 +1,19 @@
-use crate::router::RouterService;
+use std::any::TypeId;
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+use crate::error::EdgeError;
+use crate::extractor::{JsonLimits, QueryLimit, Settings};
+use crate::forwarded::TrustedProxies;
+use crate::manifest::{ManifestLoader, ManifestTriggers};
+use crate::middleware::{ErrorHook, Middleware};
+use crate::proxy::{ProxyRequestInterceptor, ProxyResponseInterceptor};
+use crate::response::JsonFormat;
+use crate::router::{self, RouterBuildError, RouterService};
 
 /// Canonical adapter name for the Axum adapter.
 pub const AXUM_ADAPTER: &str = "axum";
@@ -10,12 +25,63 @@ pub const FASTLY_ADAPTER: &str = "fastly";
 /// Canonical adapter name for the Spin adapter.
 pub const SPIN_ADAPTER: &str = "spin";
 
+/// The [`App::validate`] result for each hooks type an app was built from by
+/// [`Hooks::build_validated_app`].
+static VALIDATED: Mutex<Vec<(TypeId, Result<(), AppValidationError>)>> = Mutex::new(Vec::new());
+
 /// Lightweight container around a `RouterService` that can be extended via hook implementations.
 pub struct App {
     name: String,
     router: RouterService,
 }
 
+/// A wiring mistake found by [`App::validate`].
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[non_exhaustive]
+pub enum AppValidationError {
+    /// The manifest's `[settings]` table does not deserialize into the
+    /// app's settings type.
+    #[error("{message}")]
+    InvalidSettings { message: String },
+    /// A manifest trigger has no route registered for one of its methods.
+    #[error("`{method} {path}` is declared in the manifest but has no route")]
+    MissingRoute { method: String, path: String },
+    /// The router has no routes, mounts, or fallback, so every request
+    /// gets a 404.
+    #[error("the app has no routes")]
+    NoRoutes,
+    /// A built-in endpoint (route listing, manifest, metrics, request
+    /// debug) takes requests for its path from another route that also
+    /// matches it. A catch-all route such as `/{*path}` is expected to
+    /// overlap the endpoints, so it is only logged as a warning.
+    #[error("`{method} {route}` also matches `{endpoint}`, which is served by a built-in endpoint")]
+    ShadowedRoute {
+        endpoint: String,
+        method: String,
+        route: String,
+    },
+    /// An `[app] middleware` name has no middleware registered under it in
+    /// the app's `MiddlewareRegistry`, or the router never applied it.
+    #[error("`[app] middleware` entry `{name}` is not registered")]
+    UnknownMiddleware { name: String },
+}
+
+/// The parts of the baked manifest [`App::validate`] checks.
+#[derive(Deserialize)]
+struct ManifestWiring {
+    #[serde(default)]
+    app: ManifestWiringApp,
+    #[serde(default)]
+    triggers: ManifestTriggers,
+}
+
+/// The `[app] middleware` entries of the baked manifest.
+#[derive(Default, Deserialize)]
+struct ManifestWiringApp {
+    #[serde(default)]
+    middleware: Vec<String>,
+}
+
 impl App {
     /// Default name used when none is provided.
     #[must_use]
@@ -45,6 +111,36 @@ impl App {
         Self::with_name(router, DEFAULT_APP_NAME)
     }
 
+    /// Call `hook` with every error a route's handler or middleware returns,
+    /// as [`RouterBuilder::on_error`] does before the router is built.
+    ///
+    /// [`RouterBuilder::on_error`]: crate::router::RouterBuilder::on_error
+    #[must_use]
+    #[inline]
+    pub fn on_error<E>(mut self, hook: E) -> Self
+    where
+        E: ErrorHook,
+    {
+        self.router = self.router.layer_error_hook(Arc::new(hook));
+        self
+    }
+
+    /// Make the router [`RouterService::ready`], running the setups of
+    /// middleware registered with [`Self::with_middleware_async`] or
+    /// [`RouterBuilder::middleware_async`]. Adapters' `run_app` calls it
+    /// after [`Hooks::build_validated_app`].
+    ///
+    /// # Errors
+    /// Returns [`RouterBuildError::Middleware`] for the first setup that
+    /// fails.
+    ///
+    /// [`RouterBuilder::middleware_async`]: crate::router::RouterBuilder::middleware_async
+    #[inline]
+    pub async fn ready(mut self) -> Result<Self, RouterBuildError> {
+        self.router = self.router.ready().await?;
+        Ok(self)
+    }
+
     /// Access the underlying router service.
     #[must_use]
     #[inline]
@@ -61,6 +157,134 @@ impl App {
         self.name = name.into();
     }
 
+    /// Check the app's wiring, which adapters' `run_app` does before serving:
+    /// every named middleware is registered, the router answers something,
+    /// every `[app] middleware` name in the manifest was applied from a
+    /// `MiddlewareRegistry`, every manifest trigger with a handler
+    /// has its routes, and no route is shadowed by a built-in endpoint such
+    /// as the route listing or [`RouterBuilder::enable_metrics_at`]. A
+    /// catch-all route that overlaps an endpoint is logged as a warning
+    /// instead.
+    ///
+    /// # Errors
+    /// Returns the first [`AppValidationError`] found.
+    ///
+    /// [`RouterBuilder::enable_metrics_at`]: crate::router::RouterBuilder::enable_metrics_at
+    #[inline]
+    pub fn validate(&self) -> Result<(), AppValidationError> {
+        if let Some(name) = self.router.unknown_middleware().first() {
+            return Err(AppValidationError::UnknownMiddleware { name: name.clone() });
+        }
+        if !self.router.serves_anything() {
+            return Err(AppValidationError::NoRoutes);
+        }
+        let baked = self
+            .router
+            .manifest_json()
+            .and_then(|json| serde_json::from_str::<ManifestWiring>(json).ok());
+        if let Some(manifest) = baked {
+            let applied = self.router.middleware_names();
+            if let Some(name) = manifest
+                .app
+                .middleware
+                .iter()
+                .map(|entry| entry.trim())
+                .find(|name| !name.contains("::") && !applied.iter().any(|known| known == name))
+            {
+                return Err(AppValidationError::UnknownMiddleware {
+                    name: name.to_owned(),
+                });
+            }
+            let routes = self.router.routes();
+            for trigger in manifest.triggers.http {
+                if trigger.handler.is_none() {
+                    continue;
+                }
+                for method in trigger.methods() {
+                    let path = router::root_if_empty(&trigger.path);
+                    let registered = routes
+                        .iter()
+                        .any(|route| route.method().as_str() == method && route.path() == path);
+                    if !registered {
+                        return Err(AppValidationError::MissingRoute {
+                            method: method.to_owned(),
+                            path: trigger.path.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        for (endpoint, route) in self.router.shadowed_routes() {
+            if !route.path().contains("{*") {
+                return Err(AppValidationError::ShadowedRoute {
+                    endpoint: endpoint.path().to_owned(),
+                    method: route.method().to_string(),
+                    route: route.path().to_owned(),
+                });
+            }
+            log::warn!(
+                "`{} {}` also matches `{}`, which is served by a built-in endpoint",
+                route.method(),
+                route.path(),
+                endpoint.path()
+            );
+        }
+        Ok(())
+    }
+
+    /// Lay out JSON written through [`JsonFormat`] for every route, as
+    /// [`RouterBuilder::with_json_format`] does before the router is built.
+    ///
+    /// [`RouterBuilder::with_json_format`]: crate::router::RouterBuilder::with_json_format
+    #[must_use]
+    #[inline]
+    pub fn with_json_format(self, format: JsonFormat) -> Self {
+        self.with_state(format)
+    }
+
+    /// Enforce `limits` in the JSON extractors for every route, as
+    /// [`RouterBuilder::with_json_limits`] does before the router is built.
+    ///
+    /// [`RouterBuilder::with_json_limits`]: crate::router::RouterBuilder::with_json_limits
+    #[must_use]
+    #[inline]
+    pub fn with_json_limits(self, limits: JsonLimits) -> Self {
+        self.with_state(limits)
+    }
+
+    /// Wrap every route of the application in `middleware`.
+    ///
+    /// App-level middleware runs before any middleware registered on the
+    /// router's builder; when called repeatedly, the last call is outermost.
+    #[must_use]
+    #[inline]
+    pub fn with_middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: Middleware,
+    {
+        self.router = self.router.layer_middleware(Arc::new(middleware));
+        self
+    }
+
+    /// [`Self::with_middleware`] for middleware whose construction needs
+    /// async, fallible setup, like [`RouterBuilder::middleware_async`].
+    /// `setup` runs in [`Self::ready`].
+    ///
+    /// [`RouterBuilder::middleware_async`]: crate::router::RouterBuilder::middleware_async
+    #[must_use]
+    #[inline]
+    pub fn with_middleware_async<F, Fut, M>(mut self, setup: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<M, EdgeError>> + 'static,
+        M: Middleware,
+    {
+        self.router = self
+            .router
+            .layer_middleware_async(router::middleware_setup(setup));
+        self
+    }
+
     /// Construct a new application with the provided router and name.
     #[inline]
     pub fn with_name<S>(router: RouterService, name: S) -> Self
@@ -72,6 +296,88 @@ impl App {
             name: name.into(),
         }
     }
+
+    /// Run `interceptor` on every outbound proxy request, as
+    /// [`RouterBuilder::with_proxy_request_interceptor`] does before the
+    /// router is built.
+    ///
+    /// [`RouterBuilder::with_proxy_request_interceptor`]: crate::router::RouterBuilder::with_proxy_request_interceptor
+    #[must_use]
+    #[inline]
+    pub fn with_proxy_request_interceptor<I>(mut self, interceptor: I) -> Self
+    where
+        I: ProxyRequestInterceptor + 'static,
+    {
+        self.router = self
+            .router
+            .with_proxy_request_interceptor(Arc::new(interceptor));
+        self
+    }
+
+    /// Run `interceptor` on every proxy response received, as
+    /// [`RouterBuilder::with_proxy_response_interceptor`] does before the
+    /// router is built.
+    ///
+    /// [`RouterBuilder::with_proxy_response_interceptor`]: crate::router::RouterBuilder::with_proxy_response_interceptor
+    #[must_use]
+    #[inline]
+    pub fn with_proxy_response_interceptor<I>(mut self, interceptor: I) -> Self
+    where
+        I: ProxyResponseInterceptor + 'static,
+    {
+        self.router = self
+            .router
+            .with_proxy_response_interceptor(Arc::new(interceptor));
+        self
+    }
+
+    /// Cap the query strings parsed by the query extractors for every route,
+    /// as [`RouterBuilder::with_query_limit`] does before the router is built.
+    ///
+    /// [`RouterBuilder::with_query_limit`]: crate::router::RouterBuilder::with_query_limit
+    #[must_use]
+    #[inline]
+    pub fn with_query_limit(self, limit: QueryLimit) -> Self {
+        self.with_state(limit)
+    }
+
+    /// Register typed settings on the underlying router, as
+    /// [`RouterBuilder::with_settings`] does before the router is built.
+    ///
+    /// [`RouterBuilder::with_settings`]: crate::router::RouterBuilder::with_settings
+    #[must_use]
+    #[inline]
+    pub fn with_settings<T>(mut self, settings: T) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        self.router = self.router.with_state(Settings(Arc::new(settings)));
+        self
+    }
+
+    /// Register app-wide state on the underlying router, as
+    /// [`RouterBuilder::with_state`] does before the router is built.
+    ///
+    /// [`RouterBuilder::with_state`]: crate::router::RouterBuilder::with_state
+    #[must_use]
+    #[inline]
+    pub fn with_state<T>(mut self, value: T) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.router = self.router.with_state(value);
+        self
+    }
+
+    /// Trust forwarded headers from `proxies` for every route, as
+    /// [`RouterBuilder::with_trusted_proxies`] does before the router is built.
+    ///
+    /// [`RouterBuilder::with_trusted_proxies`]: crate::router::RouterBuilder::with_trusted_proxies
+    #[must_use]
+    #[inline]
+    pub fn with_trusted_proxies(self, proxies: TrustedProxies) -> Self {
+        self.with_state(proxies)
+    }
 }
 
 /// Compile-time metadata for one logical store kind, baked by the `app!` macro.
@@ -115,11 +421,44 @@ pub trait Hooks {
         app
     }
 
+    /// Build the app, register its settings with [`Self::install_settings`],
+    /// and check it with [`App::validate`], as adapters' `run_app` does for
+    /// every request. Every app built from the same hooks has the same
+    /// wiring, so only the first is checked and its result is reused for the
+    /// rest.
+    ///
+    /// # Errors
+    /// Returns [`AppValidationError::InvalidSettings`] if the settings do not
+    /// load, or the [`AppValidationError`] the first app built failed with.
+    #[inline]
+    fn build_validated_app() -> Result<App, AppValidationError>
+    where
+        Self: Sized + 'static,
+    {
+        let app = Self::install_settings(Self::build_app())?;
+        validate_once::<Self>(&app)?;
+        Ok(app)
+    }
+
     /// Allow implementations to mutate the freshly constructed application before use.
     /// The default implementation performs no changes.
     #[inline]
     fn configure(_app: &mut App) {}
 
+    /// Register the app's typed settings for the
+    /// [`Settings`](crate::extractor::Settings) extractor. Apps declaring
+    /// `settings = <Type>` in the `app!` macro load them from the manifest's
+    /// `[settings]` table with [`settings_from_manifest`]. The default
+    /// registers nothing.
+    ///
+    /// # Errors
+    /// Returns [`AppValidationError::InvalidSettings`] if the settings do not
+    /// load.
+    #[inline]
+    fn install_settings(app: App) -> Result<App, AppValidationError> {
+        Ok(app)
+    }
+
     /// Display name for the application. Defaults to `"EdgeZero App"`.
     #[must_use]
     #[inline]
@@ -150,14 +489,50 @@ pub trait Hooks {
     }
 }
 
+/// Deserialize the `[settings]` table of the manifest in `manifest` into `T`,
+/// ready for [`App::with_state`]. Used by the `install_settings` the `app!`
+/// macro generates for `settings = <Type>`.
+///
+/// # Errors
+/// Returns [`AppValidationError::InvalidSettings`] naming the offending field
+/// if the manifest does not parse or `[settings]` does not match `T`.
+#[inline]
+pub fn settings_from_manifest<T>(manifest: &str) -> Result<Settings<T>, AppValidationError>
+where
+    T: DeserializeOwned + Send + Sync + 'static,
+{
+    ManifestLoader::try_load_from_str(manifest)
+        .and_then(|loader| loader.settings::<T>())
+        .map(|settings| Settings(Arc::new(settings)))
+        .map_err(|err| AppValidationError::InvalidSettings {
+            message: err.to_string(),
+        })
+}
+
+/// [`App::validate`] for the first app built from hooks `H`, remembered and
+/// returned for the apps built from `H` after it.
+fn validate_once<H: 'static>(app: &App) -> Result<(), AppValidationError> {
+    let hooks = TypeId::of::<H>();
+    let mut checked = VALIDATED.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some((_, result)) = checked.iter().find(|(seen, _)| *seen == hooks) {
+        return result.clone();
+    }
+    let result = app.validate();
+    checked.push((hooks, result.clone()));
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::body::Body;
     use crate::context::RequestContext;
     use crate::error::EdgeError;
-    use crate::http::{Method, StatusCode, request_builder};
+    use crate::http::{Method, Response, StatusCode, request_builder};
+    use crate::introspection;
+    use crate::middleware::{MiddlewareRegistry, Next, RequestLogger};
     use futures::executor::block_on;
+    use std::sync::Mutex;
     use tower_service::Service as _;
 
     struct DefaultHooks;
@@ -214,10 +589,39 @@ mod tests {
         }
     }
 
+    struct RecordingMiddleware {
+        log: Arc<Mutex<Vec<String>>>,
+        name: &'static str,
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl Middleware for RecordingMiddleware {
+        async fn handle(&self, ctx: RequestContext, next: Next<'_>) -> Result<Response, EdgeError> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("{}:{}", self.name, ctx.request().uri().path()));
+            next.run(ctx).await
+        }
+    }
+
     fn empty_router() -> RouterService {
         RouterService::builder().build()
     }
 
+    fn get(app: &App, path: &str) -> Response {
+        let request = request_builder()
+            .method(Method::GET)
+            .uri(path)
+            .body(Body::empty())
+            .expect("request");
+        block_on(app.router().oneshot(request)).expect("response")
+    }
+
+    async fn ok_handler(_ctx: RequestContext) -> Result<String, EdgeError> {
+        Ok("ok".to_owned())
+    }
+
     #[test]
     fn build_app_invokes_hooks_for_routes_and_configuration() {
         let app = TestHooks::build_app();
@@ -261,4 +665,341 @@ mod tests {
         let router = app.into_router();
         assert!(router.routes().is_empty());
     }
+
+    #[test]
+    fn validate_accepts_a_wired_app() {
+        let router = RouterService::builder()
+            .with_manifest_json(
+                r#"{"triggers":{"http":[{"path":"/hello","methods":["GET"],"handler":"app::hello"}]}}"#,
+            )
+            .get("/hello", ok_handler)
+            .get("/_app/routes", introspection::routes)
+            .build();
+        assert_eq!(App::new(router).validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_checks_manifest_middleware_names_were_applied() {
+        let manifest = r#"{"app":{"middleware":["logger","crate::Auth"]},"triggers":{"http":[]}}"#;
+        let registry = MiddlewareRegistry::new().register("logger", || RequestLogger);
+
+        let unapplied = RouterService::builder()
+            .with_manifest_json(manifest)
+            .get("/hello", ok_handler)
+            .build();
+        assert_eq!(
+            App::new(unapplied).validate(),
+            Err(AppValidationError::UnknownMiddleware {
+                name: "logger".to_owned()
+            })
+        );
+
+        let applied = RouterService::builder()
+            .with_manifest_json(manifest)
+            .named_middleware(["logger"], &registry)
+            .expect("registered")
+            .get("/hello", ok_handler)
+            .build();
+        assert_eq!(App::new(applied).validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_reports_an_app_without_routes() {
+        assert_eq!(
+            App::new(empty_router()).validate(),
+            Err(AppValidationError::NoRoutes)
+        );
+    }
+
+    #[test]
+    fn validate_reports_manifest_triggers_without_routes() {
+        let router = RouterService::builder()
+            .with_manifest_json(
+                r#"{"triggers":{"http":[{"path":"/echo","methods":["GET","POST"],"handler":"app::echo"}]}}"#,
+            )
+            .get("/echo", ok_handler)
+            .build();
+        let err = App::new(router).validate().expect_err("POST is missing");
+        assert_eq!(
+            err,
+            AppValidationError::MissingRoute {
+                method: "POST".to_owned(),
+                path: "/echo".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn validate_reports_a_route_shadowed_by_the_route_listing() {
+        let router = RouterService::builder()
+            .get("/_app/{page}", ok_handler)
+            .get("/_app/routes", introspection::routes)
+            .build();
+        let err = App::new(router).validate().expect_err("collision");
+        assert_eq!(
+            err,
+            AppValidationError::ShadowedRoute {
+                endpoint: "/_app/routes".to_owned(),
+                method: "GET".to_owned(),
+                route: "/_app/{page}".to_owned(),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "`GET /_app/{page}` also matches `/_app/routes`, which is served by a built-in endpoint"
+        );
+    }
+
+    #[test]
+    fn validate_only_warns_about_catch_alls_overlapping_the_route_listing() {
+        let router = RouterService::builder()
+            .get("/{*path}", ok_handler)
+            .get("/_app/routes", introspection::routes)
+            .build();
+        assert_eq!(router.shadowed_routes().len(), 1);
+        assert_eq!(App::new(router).validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_treats_an_empty_trigger_path_as_the_root() {
+        let router = RouterService::builder()
+            .with_manifest_json(
+                r#"{"triggers":{"http":[{"path":"","methods":["GET"],"handler":"app::home"}]}}"#,
+            )
+            .get("", ok_handler)
+            .build();
+        assert_eq!(App::new(router).validate(), Ok(()));
+    }
+
+    #[test]
+    fn build_validated_app_checks_each_hooks_type_once() {
+        struct EmptyHooks;
+
+        #[expect(
+            clippy::missing_trait_methods,
+            reason = "test stub — only `routes` is overridden; every other Hooks method intentionally uses its trait default"
+        )]
+        impl Hooks for EmptyHooks {
+            fn routes() -> RouterService {
+                RouterService::builder().build()
+            }
+        }
+
+        for _ in 0_u8..2 {
+            TestHooks::build_validated_app().expect("wired");
+            assert_eq!(
+                EmptyHooks::build_validated_app().map(drop),
+                Err(AppValidationError::NoRoutes)
+            );
+        }
+        let hooks = [TypeId::of::<TestHooks>(), TypeId::of::<EmptyHooks>()];
+        let checked = VALIDATED
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(seen, _)| hooks.contains(seen))
+            .count();
+        assert_eq!(checked, 2);
+    }
+
+    #[test]
+    fn build_validated_app_reports_settings_that_do_not_load() {
+        #[derive(serde::Deserialize)]
+        struct AppSettings {
+            #[expect(dead_code, reason = "only deserialized")]
+            retries: u32,
+        }
+
+        struct BadSettingsHooks;
+
+        #[expect(
+            clippy::missing_trait_methods,
+            reason = "test stub — only `routes` and `install_settings` are overridden"
+        )]
+        impl Hooks for BadSettingsHooks {
+            fn install_settings(app: App) -> Result<App, AppValidationError> {
+                let manifest = "[app]\nname = \"demo\"\n\n[settings]\nretries = \"many\"\n";
+                let settings = settings_from_manifest::<AppSettings>(manifest)?;
+                Ok(app.with_state(settings))
+            }
+
+            fn routes() -> RouterService {
+                RouterService::builder().get("/", ok_handler).build()
+            }
+        }
+
+        let err = BadSettingsHooks::build_validated_app()
+            .map(drop)
+            .expect_err("retries must be an integer");
+        let AppValidationError::InvalidSettings { message } = err else {
+            panic!("expected InvalidSettings, got {err:?}");
+        };
+        assert!(message.contains("[settings]"), "{message}");
+        assert!(message.contains("retries"), "{message}");
+    }
+
+    #[test]
+    fn with_middleware_runs_for_every_route() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let router = RouterService::builder()
+            .get("/a", ok_handler)
+            .post("/b", ok_handler)
+            .get("/c/{id}", ok_handler)
+            .build();
+        let app = App::new(router).with_middleware(RecordingMiddleware {
+            log: Arc::clone(&log),
+            name: "app",
+        });
+
+        assert_eq!(get(&app, "/a").status(), StatusCode::OK);
+        assert_eq!(get(&app, "/c/7").status(), StatusCode::OK);
+        let request = request_builder()
+            .method(Method::POST)
+            .uri("/b")
+            .body(Body::empty())
+            .expect("request");
+        let response = block_on(app.router().oneshot(request)).expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "app:/a".to_owned(),
+                "app:/c/7".to_owned(),
+                "app:/b".to_owned()
+            ]
+        );
+    }
+
+    #[test]
+    fn with_middleware_async_is_set_up_by_ready_in_its_place() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let router_log = Arc::clone(&log);
+        let router = RouterService::builder()
+            .middleware_async(move || {
+                let recorder = RecordingMiddleware {
+                    log: Arc::clone(&router_log),
+                    name: "router-async",
+                };
+                async move { Ok(recorder) }
+            })
+            .get("/", ok_handler)
+            .build();
+        let app_log = Arc::clone(&log);
+        let app = App::new(router)
+            .with_middleware(RecordingMiddleware {
+                log: Arc::clone(&log),
+                name: "inner",
+            })
+            .with_middleware_async(move || {
+                let recorder = RecordingMiddleware {
+                    log: Arc::clone(&app_log),
+                    name: "outer-async",
+                };
+                async move { Ok(recorder) }
+            });
+
+        let ready = block_on(app.ready()).expect("setup succeeds");
+        assert_eq!(get(&ready, "/").status(), StatusCode::OK);
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "outer-async:/".to_owned(),
+                "inner:/".to_owned(),
+                "router-async:/".to_owned()
+            ]
+        );
+
+        let failing = App::new(RouterService::builder().get("/", ok_handler).build())
+            .with_middleware_async(|| async {
+                Err::<RecordingMiddleware, _>(EdgeError::service_unavailable("key server down"))
+            });
+        let err = block_on(failing.ready())
+            .map(drop)
+            .expect_err("setup fails");
+        assert_eq!(
+            EdgeError::from(err).status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    #[test]
+    fn with_middleware_wraps_router_middleware() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let router = RouterService::builder()
+            .middleware(RecordingMiddleware {
+                log: Arc::clone(&log),
+                name: "router",
+            })
+            .get("/", ok_handler)
+            .build();
+        let app = App::new(router)
+            .with_middleware(RecordingMiddleware {
+                log: Arc::clone(&log),
+                name: "inner",
+            })
+            .with_middleware(RecordingMiddleware {
+                log: Arc::clone(&log),
+                name: "outer",
+            });
+
+        assert_eq!(get(&app, "/").status(), StatusCode::OK);
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "outer:/".to_owned(),
+                "inner:/".to_owned(),
+                "router:/".to_owned()
+            ]
+        );
+    }
+
+    #[test]
+    fn with_state_exposes_value_to_handlers() {
+        use crate::extractor::{FromRequest as _, State};
+
+        #[derive(Clone)]
+        struct Greeting(&'static str);
+
+        async fn handler(ctx: RequestContext) -> Result<String, EdgeError> {
+            let State(greeting) = State::<Greeting>::from_request(&ctx).await?;
+            Ok(greeting.0.to_owned())
+        }
+
+        let router = RouterService::builder().get("/hello", handler).build();
+        let app = App::with_name(router, "stateful").with_state(Greeting("hi"));
+
+        assert_eq!(app.name(), "stateful");
+        let response = get(&app, "/hello");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body().as_bytes().expect("buffered"), b"hi");
+    }
+
+    #[test]
+    fn with_state_leaves_shared_router_clones_untouched() {
+        #[derive(Clone)]
+        struct Marker;
+
+        async fn handler(ctx: RequestContext) -> Result<String, EdgeError> {
+            Ok(ctx
+                .request()
+                .extensions()
+                .get::<Marker>()
+                .is_some()
+                .to_string())
+        }
+
+        let router = RouterService::builder().get("/", handler).build();
+        let original = App::new(router.clone());
+        let layered = App::new(router).with_state(Marker);
+
+        assert_eq!(
+            get(&original, "/").body().as_bytes().expect("buffered"),
+            b"false"
+        );
+        assert_eq!(
+            get(&layered, "/").body().as_bytes().expect("buffered"),
+            b"true"
+        );
+    }
 }
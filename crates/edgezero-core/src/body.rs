@@ -2,11 +2,19 @@ use std::fmt;
 use std::io;
 
 use bytes::Bytes;
-use futures_util::stream::{LocalBoxStream, Stream, StreamExt};
+use futures_util::future::{Future, FutureExt as _, LocalBoxFuture};
+use futures_util::stream::{self, LocalBoxStream, Stream, StreamExt};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 
+use crate::body_limit;
 use crate::error::EdgeError;
+use crate::framing::{FrameError, LengthDelimitedCodec};
+use crate::http::HeaderMap;
+
+/// Trailer headers for [`Body::StreamWithTrailers`], resolved after the last
+/// chunk has been sent.
+pub type TrailersFuture = LocalBoxFuture<'static, HeaderMap>;
 
 /// Lightweight HTTP body that can either contain a single `Bytes` buffer or a streaming source of
 /// chunks. The streaming variant is implemented with `LocalBoxStream` so it remains compatible with
@@ -14,6 +22,11 @@ use crate::error::EdgeError;
 pub enum Body {
     Once(Bytes),
     Stream(LocalBoxStream<'static, Result<Bytes, anyhow::Error>>),
+    /// A stream followed by HTTP trailers; see [`Body::stream_with_trailers`].
+    StreamWithTrailers(
+        LocalBoxStream<'static, Result<Bytes, anyhow::Error>>,
+        TrailersFuture,
+    ),
 }
 
 impl Body {
@@ -24,7 +37,7 @@ impl Body {
     pub fn as_bytes(&self) -> Option<&[u8]> {
         match self {
             Body::Once(bytes) => Some(bytes.as_ref()),
-            Body::Stream(_) => None,
+            Body::Stream(_) | Body::StreamWithTrailers(..) => None,
         }
     }
 
@@ -34,6 +47,23 @@ impl Body {
         Self::from_bytes(Bytes::new())
     }
 
+    /// Split the body into length-prefixed frames as described by `codec`.
+    ///
+    /// Works for both buffered and streaming variants; frames split across
+    /// stream chunks are reassembled. Framing failures, and a body that ends
+    /// mid-frame, are yielded as [`FrameError`]s.
+    #[inline]
+    pub fn framed(
+        self,
+        codec: LengthDelimitedCodec,
+    ) -> LocalBoxStream<'static, Result<Bytes, FrameError>> {
+        let chunks = match self {
+            Body::Once(bytes) => stream::once(async move { Ok(bytes) }).boxed_local(),
+            Body::Stream(stream) | Body::StreamWithTrailers(stream, _) => stream,
+        };
+        codec.decode_stream(chunks)
+    }
+
     #[inline]
     pub fn from_bytes<B>(bytes: B) -> Self
     where
@@ -62,16 +92,16 @@ impl Body {
     pub fn into_bytes(self) -> Option<Bytes> {
         match self {
             Body::Once(bytes) => Some(bytes),
-            Body::Stream(_) => None,
+            Body::Stream(_) | Body::StreamWithTrailers(..) => None,
         }
     }
 
     /// Drain the body into a single `Bytes` buffer, enforcing `max_size`.
     ///
-    /// Works for both buffered and streaming variants.
+    /// Works for both buffered and streaming variants; trailers are dropped.
     ///
     /// # Errors
-    /// Returns [`EdgeError::bad_request`] if the body exceeds `max_size` bytes; or [`EdgeError::internal`] if the upstream stream errors.
+    /// Returns [`EdgeError::bad_request`] if the body exceeds `max_size` bytes; [`EdgeError::payload_too_large`] if the stream was cut off by a [`BodyLimit`](crate::body_limit::BodyLimit); or [`EdgeError::internal`] if the upstream stream errors.
     #[inline]
     pub async fn into_bytes_bounded(self, max_size: usize) -> Result<Bytes, EdgeError> {
         match self {
@@ -81,10 +111,10 @@ impl Body {
                 }
                 Ok(bytes)
             }
-            Body::Stream(mut stream) => {
+            Body::Stream(mut stream) | Body::StreamWithTrailers(mut stream, _) => {
                 let mut buf = Vec::new();
                 while let Some(result) = StreamExt::next(&mut stream).await {
-                    let chunk = result.map_err(EdgeError::internal)?;
+                    let chunk = result.map_err(body_limit::stream_error)?;
                     buf.extend_from_slice(&chunk);
                     if buf.len() > max_size {
                         return Err(EdgeError::bad_request("request body too large"));
@@ -95,17 +125,19 @@ impl Body {
         }
     }
 
+    /// The chunk stream of a streaming body, or `None` for a buffered body.
+    /// Trailers, if any, are dropped.
     #[inline]
     pub fn into_stream(self) -> Option<LocalBoxStream<'static, Result<Bytes, anyhow::Error>>> {
         match self {
             Body::Once(_) => None,
-            Body::Stream(stream) => Some(stream),
+            Body::Stream(stream) | Body::StreamWithTrailers(stream, _) => Some(stream),
         }
     }
 
     #[inline]
     pub fn is_stream(&self) -> bool {
-        matches!(self, Body::Stream(_))
+        matches!(self, Body::Stream(_) | Body::StreamWithTrailers(..))
     }
 
     /// # Errors
@@ -118,6 +150,33 @@ impl Body {
         serde_json::to_vec(value).map(Self::from_bytes)
     }
 
+    /// A body computed by `compute` when it is first polled, so work for a
+    /// response that is never sent (such as the body of a `HEAD` response,
+    /// which the router drops) is skipped. Behaves as a one-chunk stream; an
+    /// error from `compute` ends the stream with that error.
+    #[inline]
+    pub fn lazy<F, Fut>(compute: F) -> Self
+    where
+        F: FnOnce() -> Fut + 'static,
+        Fut: Future<Output = Result<Bytes, EdgeError>> + 'static,
+    {
+        Self::Stream(
+            stream::once(async move { compute().await.map_err(anyhow::Error::from) }).boxed_local(),
+        )
+    }
+
+    /// Bounds on the body length, as `(lower, upper)`: exact for a buffered
+    /// body, `(0, None)` for a stream. Lets adapters set `Content-Length`
+    /// without buffering.
+    #[must_use]
+    #[inline]
+    pub fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Body::Once(bytes) => (bytes.len(), Some(bytes.len())),
+            Body::Stream(_) | Body::StreamWithTrailers(..) => (0, None),
+        }
+    }
+
     #[inline]
     pub fn stream<S>(stream: S) -> Self
     where
@@ -126,6 +185,25 @@ impl Body {
         Self::Stream(stream.map(Ok::<Bytes, anyhow::Error>).boxed_local())
     }
 
+    /// Stream `stream` and then send the headers `trailers` resolves to as
+    /// HTTP trailers (e.g. `grpc-status` for gRPC-web).
+    ///
+    /// The future is polled only after the stream ends, so it can report
+    /// on the whole body. Trailers are best effort: adapters whose platform
+    /// cannot send them (Fastly, Cloudflare, Spin, and outbound proxy
+    /// bodies) send the stream and drop the trailers.
+    #[inline]
+    pub fn stream_with_trailers<S, F>(stream: S, trailers: F) -> Self
+    where
+        S: Stream<Item = Bytes> + 'static,
+        F: Future<Output = HeaderMap> + 'static,
+    {
+        Self::StreamWithTrailers(
+            stream.map(Ok::<Bytes, anyhow::Error>).boxed_local(),
+            trailers.boxed_local(),
+        )
+    }
+
     #[inline]
     pub fn text<S>(text: S) -> Self
     where
@@ -143,9 +221,9 @@ impl Body {
     {
         match self {
             Body::Once(bytes) => serde_json::from_slice(bytes.as_ref()),
-            Body::Stream(_) => Err(serde_json::Error::io(io::Error::other(
-                "streaming body cannot be materialised as JSON",
-            ))),
+            Body::Stream(_) | Body::StreamWithTrailers(..) => Err(serde_json::Error::io(
+                io::Error::other("streaming body cannot be materialised as JSON"),
+            )),
         }
     }
 }
@@ -166,6 +244,7 @@ impl fmt::Debug for Body {
                 .field("len", &bytes.len())
                 .finish(),
             Body::Stream(_) => f.debug_tuple("Body::Stream").finish(),
+            Body::StreamWithTrailers(..) => f.debug_tuple("Body::StreamWithTrailers").finish(),
         }
     }
 }
@@ -321,6 +400,47 @@ mod tests {
         assert!(!body.is_stream());
     }
 
+    #[test]
+    fn size_hint_is_exact_for_buffered_bodies_and_open_for_streams() {
+        assert_eq!(Body::from("hello").size_hint(), (5, Some(5)));
+        assert_eq!(Body::empty().size_hint(), (0, Some(0)));
+
+        let chunks = stream::iter(vec![Bytes::from_static(b"hello")]);
+        assert_eq!(Body::stream(chunks).size_hint(), (0, None));
+        let trailing = Body::stream_with_trailers(stream::empty(), async { HeaderMap::new() });
+        assert_eq!(trailing.size_hint(), (0, None));
+    }
+
+    #[test]
+    fn stream_with_trailers_yields_chunks_then_trailers() {
+        let body = Body::stream_with_trailers(
+            stream::iter(vec![Bytes::from_static(b"a"), Bytes::from_static(b"b")]),
+            async {
+                let mut trailers = HeaderMap::new();
+                trailers.insert("grpc-status", "0".parse().expect("header value"));
+                trailers
+            },
+        );
+        assert!(body.is_stream());
+        assert!(format!("{body:?}").contains("Body::StreamWithTrailers"));
+        let Body::StreamWithTrailers(chunks, pending) = body else {
+            panic!("expected a trailered stream");
+        };
+        let (collected, trailers) = block_on(async {
+            let data: Vec<Bytes> = chunks.map(|chunk| chunk.expect("chunk")).collect().await;
+            (data.concat(), pending.await)
+        });
+        assert_eq!(collected, b"ab");
+        assert_eq!(trailers.get("grpc-status").expect("trailer"), "0");
+
+        let dropped =
+            Body::stream_with_trailers(stream::iter(vec![Bytes::from_static(b"data")]), async {
+                HeaderMap::new()
+            });
+        let bytes = block_on(dropped.into_bytes_bounded(100)).expect("bytes");
+        assert_eq!(bytes, Bytes::from_static(b"data"));
+    }
+
     #[test]
     fn to_json_fails_for_streaming_body() {
         let body = Body::stream(stream::iter(vec![
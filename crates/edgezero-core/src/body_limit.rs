@@ -0,0 +1,190 @@
+//! Request body size limits.
+//!
+//! Each adapter exposes a `DEFAULT_MAX_BODY_BYTES` constant for its platform
+//! and inserts it into every request as a [`BodyLimit`]. An app overrides it
+//! with `max-body-bytes` under `[app]` in `edgezero.toml`, which the `app!`
+//! macro passes to [`RouterBuilder::with_body_limit`]. The router enforces
+//! whichever applies while it sets up the request context, before routing:
+//!
+//! - a `Content-Length` or buffered body over the limit is rejected with
+//!   `413 Payload Too Large`;
+//! - a streamed body ends with an error once it passes the limit, which
+//!   [`Body::into_bytes_bounded`] reports as a 413. The JSON, form and CBOR
+//!   extractors buffer streamed bodies this way when they first read them,
+//!   so a chunked body without `Content-Length` is still capped as its bytes
+//!   arrive. Without any limit installed they stop at
+//!   [`BodyLimit::default`].
+//!
+//! The limit in effect stays in the request's extensions, so handlers can
+//! read it with `ctx.request().extensions().get::<BodyLimit>()`.
+//!
+//! [`RouterBuilder::with_body_limit`]: crate::router::RouterBuilder::with_body_limit
+
+use std::mem;
+
+use anyhow::Error as AnyError;
+use futures::future::ready;
+use futures_util::stream::{LocalBoxStream, StreamExt as _};
+use thiserror::Error;
+
+use crate::body::Body;
+use crate::error::EdgeError;
+use crate::http::Request;
+use crate::http::header::CONTENT_LENGTH;
+
+type ChunkStream = LocalBoxStream<'static, Result<bytes::Bytes, AnyError>>;
+
+/// Largest request body accepted, in bytes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BodyLimit {
+    max_bytes: usize,
+}
+
+/// The error a limited body stream ends with.
+#[derive(Debug, Error)]
+#[error("request body exceeds {max_bytes} bytes")]
+pub(crate) struct BodyTooLarge {
+    max_bytes: usize,
+}
+
+impl BodyLimit {
+    /// Reject `request` if it declares or buffers a body over the limit, and
+    /// cap a streamed body. Records the limit in the request's extensions.
+    pub(crate) fn enforce(self, request: &mut Request) -> Result<(), EdgeError> {
+        let too_large = || too_large(self.max_bytes);
+        let declared = request
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok());
+        let max_declared = u64::try_from(self.max_bytes).unwrap_or(u64::MAX);
+        if declared.is_some_and(|length| length > max_declared) {
+            return Err(too_large());
+        }
+        let body = match mem::take(request.body_mut()) {
+            Body::Once(bytes) if bytes.len() > self.max_bytes => return Err(too_large()),
+            Body::Once(bytes) => Body::Once(bytes),
+            Body::Stream(chunks) => Body::Stream(self.limit_stream(chunks)),
+            Body::StreamWithTrailers(chunks, trailers) => {
+                Body::StreamWithTrailers(self.limit_stream(chunks), trailers)
+            }
+        };
+        *request.body_mut() = body;
+        request.extensions_mut().insert(self);
+        Ok(())
+    }
+
+    /// `chunks`, ending with [`BodyTooLarge`] once more than the limit has
+    /// been read. Nothing is yielded after the first error.
+    pub(crate) fn limit_stream(self, chunks: ChunkStream) -> ChunkStream {
+        let max_bytes = self.max_bytes;
+        chunks
+            .scan(Some(0_usize), move |state, chunk| {
+                let Some(seen) = *state else {
+                    return ready(None);
+                };
+                let item = chunk.and_then(|bytes| {
+                    let total = seen.saturating_add(bytes.len());
+                    *state = Some(total);
+                    if total > max_bytes {
+                        Err(AnyError::new(BodyTooLarge { max_bytes }))
+                    } else {
+                        Ok(bytes)
+                    }
+                });
+                if item.is_err() {
+                    *state = None;
+                }
+                ready(Some(item))
+            })
+            .boxed_local()
+    }
+
+    /// The limit in bytes.
+    #[must_use]
+    #[inline]
+    pub const fn max_bytes(self) -> usize {
+        self.max_bytes
+    }
+
+    /// A limit of `max_bytes`.
+    #[must_use]
+    #[inline]
+    pub const fn new(max_bytes: usize) -> Self {
+        Self { max_bytes }
+    }
+}
+
+impl Default for BodyLimit {
+    /// 32 MiB, the limit the extractors apply when no adapter or app
+    /// installed one.
+    #[inline]
+    fn default() -> Self {
+        Self::new(32 * 1024 * 1024)
+    }
+}
+
+/// Map a body stream error to an [`EdgeError`]: `413` when the stream was cut
+/// off by a [`BodyLimit`], `500` otherwise.
+pub(crate) fn stream_error(err: AnyError) -> EdgeError {
+    match err.downcast::<BodyTooLarge>() {
+        Ok(too_large) => EdgeError::payload_too_large(too_large.to_string()),
+        Err(other) => EdgeError::internal(other),
+    }
+}
+
+/// The `413 Payload Too Large` for a body over `max_bytes`.
+pub(crate) fn too_large(max_bytes: usize) -> EdgeError {
+    EdgeError::payload_too_large(BodyTooLarge { max_bytes }.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{StatusCode, request_builder};
+    use bytes::Bytes;
+    use futures::executor::block_on;
+    use futures::stream;
+
+    fn request(body: Body) -> Request {
+        request_builder().uri("/").body(body).expect("request")
+    }
+
+    #[test]
+    fn declared_length_over_the_limit_is_rejected() {
+        let mut req = request_builder()
+            .uri("/")
+            .header(CONTENT_LENGTH, "11")
+            .body(Body::empty())
+            .expect("request");
+        let err = BodyLimit::new(10).enforce(&mut req).expect_err("too large");
+        assert_eq!(err.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn buffered_body_within_the_limit_passes_and_records_it() {
+        let mut req = request(Body::from("0123456789"));
+        BodyLimit::new(10).enforce(&mut req).expect("within limit");
+        assert_eq!(
+            req.extensions().get::<BodyLimit>(),
+            Some(&BodyLimit::new(10))
+        );
+
+        let mut over = request(Body::from("0123456789!"));
+        let err = BodyLimit::new(10)
+            .enforce(&mut over)
+            .expect_err("too large");
+        assert_eq!(err.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn streamed_body_is_cut_off_with_a_413() {
+        let chunks = stream::iter([Bytes::from_static(b"012345"), Bytes::from_static(b"6789!")]);
+        let mut req = request(Body::stream(chunks));
+        BodyLimit::new(10).enforce(&mut req).expect("not known yet");
+
+        let err = block_on(req.into_body().into_bytes_bounded(usize::MAX))
+            .expect_err("stream passes the limit");
+        assert_eq!(err.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
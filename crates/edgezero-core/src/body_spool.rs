@@ -0,0 +1,538 @@
+//! Spilling large request bodies out of memory.
+//!
+//! The JSON, form and CBOR extractors buffer a streamed body the first time
+//! they read it (see [`RequestContext::buffer_body`]). By default the whole
+//! body is held in memory, up to the
+//! [`BodyLimit`](crate::body_limit::BodyLimit). With
+//! [`BodyBuffering::Spill`], set by `body-spill-bytes` under `[app]` in
+//! `edgezero.toml` or [`RouterBuilder::with_body_buffering`], a body past
+//! the threshold is written to the [`BodySpool`] the adapter installed
+//! instead:
+//!
+//! - the Axum dev server spools to temporary files, which are deleted when
+//!   the request is done;
+//! - the edge adapters install no spool, so the body is rejected with
+//!   `413 Payload Too Large`.
+//!
+//! A spilled body leaves the request's own body empty. The JSON, form and
+//! CBOR extractors deserialize it straight from a [`SpilledBody::reader`],
+//! so it is never read back into memory whole. Handlers can reach it with
+//! [`RequestContext::spilled_body`].
+//!
+//! [`RequestContext::buffer_body`]: crate::context::RequestContext::buffer_body
+//! [`RequestContext::spilled_body`]: crate::context::RequestContext::spilled_body
+//! [`RouterBuilder::with_body_buffering`]: crate::router::RouterBuilder::with_body_buffering
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use bytes::{Bytes, BytesMut};
+use futures::future::ready;
+use futures_util::stream::{self, StreamExt as _};
+use serde::de::value::{Error as ValueError, MapDeserializer, StringDeserializer};
+use serde::de::{DeserializeOwned, Deserializer, Error as _, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::body::Body;
+use crate::body_limit;
+use crate::error::EdgeError;
+
+/// How the router buffers bodies for the extractors.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BodyBuffering {
+    /// Hold the whole body in memory.
+    #[default]
+    Memory,
+    /// Write a body over `threshold` bytes to the adapter's [`BodySpool`],
+    /// or reject it when there is none.
+    Spill {
+        /// Largest body kept in memory, in bytes.
+        threshold: usize,
+    },
+}
+
+/// Storage for bodies too large to keep in memory.
+pub trait BodySpool: Send + Sync {
+    /// Storage for one new body.
+    ///
+    /// # Errors
+    /// Returns [`EdgeError`] if the storage cannot be created.
+    fn create(&self) -> Result<Box<dyn SpoolFile>, EdgeError>;
+}
+
+/// The storage behind one spilled body: appended to while the body arrives,
+/// then read back by the extractors.
+pub trait SpoolFile: Send + Sync {
+    /// Append `chunk` to the body.
+    ///
+    /// # Errors
+    /// Returns [`EdgeError`] if the write fails.
+    fn append(&mut self, chunk: &[u8]) -> Result<(), EdgeError>;
+
+    /// A reader over the body from its start. Each call starts a new read.
+    ///
+    /// # Errors
+    /// Returns [`EdgeError`] if the storage cannot be opened for reading.
+    fn reader(&self) -> Result<Box<dyn Read + '_>, EdgeError>;
+}
+
+/// A request body read to its end for the extractors.
+pub(crate) enum Buffered {
+    /// Held in memory.
+    Memory(Bytes),
+    /// Written to the adapter's [`BodySpool`].
+    Spilled(SpilledBody),
+}
+
+/// The `&`-separated pairs of a form body, read one at a time. The first
+/// read error ends the pairs and is kept in `error`.
+struct FormPairs<'error, R> {
+    error: &'error mut Option<io::Error>,
+    segments: io::Split<R>,
+}
+
+/// One decoded form value, parsed into whatever type the field asks for,
+/// as `serde_urlencoded` does.
+struct FormValue(String);
+
+/// The [`BodySpool`] an adapter installs in request extensions.
+#[derive(Clone)]
+pub struct BodySpoolHandle {
+    spool: Arc<dyn BodySpool>,
+}
+
+/// A request body written to the adapter's [`BodySpool`].
+#[derive(Clone)]
+pub struct SpilledBody {
+    file: Arc<dyn SpoolFile>,
+    len: usize,
+}
+
+impl BodySpoolHandle {
+    #[inline]
+    pub fn with_spool<S>(spool: S) -> Self
+    where
+        S: BodySpool + 'static,
+    {
+        Self {
+            spool: Arc::new(spool),
+        }
+    }
+}
+
+impl fmt::Debug for BodySpoolHandle {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodySpoolHandle").finish_non_exhaustive()
+    }
+}
+
+impl SpilledBody {
+    /// Whether the body is empty. Never true for a spilled request body,
+    /// since only bodies over a threshold are.
+    #[must_use]
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The body's length in bytes.
+    #[must_use]
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// A buffered reader over the body from its start, for deserializing
+    /// it without reading it into memory first.
+    ///
+    /// # Errors
+    /// Returns [`EdgeError`] if the spool cannot be read.
+    #[inline]
+    pub fn reader(&self) -> Result<impl BufRead + '_, EdgeError> {
+        self.file.reader().map(BufReader::new)
+    }
+}
+
+impl fmt::Debug for SpilledBody {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpilledBody")
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+#[expect(
+    clippy::missing_trait_methods,
+    reason = "the pairs are only walked once, by `MapDeserializer`; the provided adapters suffice"
+)]
+impl<R: BufRead> Iterator for FormPairs<'_, R> {
+    type Item = (String, FormValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let segment = match self.segments.next()? {
+                Ok(segment) => segment,
+                Err(err) => {
+                    *self.error = Some(err);
+                    return None;
+                }
+            };
+            if let Some((name, value)) = form_urlencoded::parse(&segment).next() {
+                return Some((name.into_owned(), FormValue(value.into_owned())));
+            }
+        }
+    }
+}
+
+#[expect(
+    clippy::missing_trait_methods,
+    reason = "`is_human_readable` keeps its default; a form value is text"
+)]
+impl<'de> Deserializer<'de> for FormValue {
+    type Error = ValueError;
+
+    forward_to_deserialize_any! {
+        byte_buf bytes char i128 identifier ignored_any map seq str string struct
+        tuple tuple_struct u128 unit unit_struct
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_string(self.0)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_bool(self.parse()?)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ValueError> {
+        let variant: StringDeserializer<ValueError> = self.0.into_deserializer();
+        visitor.visit_enum(variant)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_f32(self.parse()?)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_f64(self.parse()?)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_i16(self.parse()?)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_i32(self.parse()?)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_i64(self.parse()?)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_i8(self.parse()?)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ValueError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_u16(self.parse()?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_u32(self.parse()?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_u64(self.parse()?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_u8(self.parse()?)
+    }
+}
+
+impl FormValue {
+    fn parse<T: FromStr>(&self) -> Result<T, ValueError>
+    where
+        T::Err: fmt::Display,
+    {
+        self.0.parse().map_err(ValueError::custom)
+    }
+}
+
+impl IntoDeserializer<'_, ValueError> for FormValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Read `body` to its end, rejecting it with `413 Payload Too Large` once
+/// it passes `max_bytes`. With [`BodyBuffering::Spill`], a body past the
+/// threshold goes to `spool`, and is rejected with `413` when there is none.
+pub(crate) async fn buffer(
+    body: Body,
+    buffering: BodyBuffering,
+    max_bytes: usize,
+    spool: Option<BodySpoolHandle>,
+) -> Result<Buffered, EdgeError> {
+    let threshold = match buffering {
+        BodyBuffering::Memory => usize::MAX,
+        BodyBuffering::Spill { threshold } => threshold,
+    };
+    let mut chunks = match body {
+        Body::Once(bytes) => stream::once(ready(Ok(bytes))).boxed_local(),
+        Body::Stream(chunks) | Body::StreamWithTrailers(chunks, _) => chunks,
+    };
+    let mut buffer = BytesMut::new();
+    let head = loop {
+        let Some(chunk) = chunks.next().await else {
+            return Ok(Buffered::Memory(buffer.freeze()));
+        };
+        buffer.extend_from_slice(&chunk.map_err(body_limit::stream_error)?);
+        if buffer.len() > max_bytes {
+            return Err(body_limit::too_large(max_bytes));
+        }
+        if buffer.len() > threshold {
+            break buffer.freeze();
+        }
+    };
+    let handle = spool.ok_or_else(|| {
+        EdgeError::payload_too_large(format!(
+            "request body exceeds the {threshold}-byte buffering threshold"
+        ))
+    })?;
+    let mut file = handle.spool.create()?;
+    file.append(&head)?;
+    let mut len = head.len();
+    while let Some(chunk) = chunks.next().await {
+        let bytes = chunk.map_err(body_limit::stream_error)?;
+        len = len.saturating_add(bytes.len());
+        if len > max_bytes {
+            return Err(body_limit::too_large(max_bytes));
+        }
+        file.append(&bytes)?;
+    }
+    Ok(Buffered::Spilled(SpilledBody {
+        file: Arc::from(file),
+        len,
+    }))
+}
+
+/// Deserialize a form-urlencoded `T` from `reader` one `&`-separated pair at
+/// a time, so only the pair being decoded is held in memory.
+pub(crate) fn form_from_reader<T, R>(reader: R) -> Result<T, EdgeError>
+where
+    T: DeserializeOwned,
+    R: BufRead,
+{
+    let mut error = None;
+    let pairs = FormPairs {
+        error: &mut error,
+        segments: reader.split(b'&'),
+    };
+    let parsed = T::deserialize(MapDeserializer::new(pairs));
+    if let Some(err) = error {
+        return Err(read_error(&err));
+    }
+    parsed.map_err(|err| EdgeError::bad_request(format!("invalid form payload: {err}")))
+}
+
+/// The `500 Internal Server Error` for a spilled body that cannot be read.
+pub(crate) fn read_error(err: &io::Error) -> EdgeError {
+    EdgeError::internal(anyhow::anyhow!("failed to read spilled body: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::RequestContext;
+    use crate::extractor::{FromRequest as _, Json};
+    use crate::http::header::CONTENT_TYPE;
+    use crate::http::{Method, StatusCode, request_builder};
+    use crate::router::RouterService;
+    use crate::test_client::TestClient;
+    use futures::executor::block_on;
+    use futures::stream;
+    use serde::Deserialize;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Keeps spilled bodies in memory, counting how many it created.
+    #[derive(Default)]
+    struct MemorySpool {
+        created: Arc<AtomicUsize>,
+    }
+
+    #[derive(Default)]
+    struct MemoryFile(Vec<u8>);
+
+    impl BodySpool for MemorySpool {
+        fn create(&self) -> Result<Box<dyn SpoolFile>, EdgeError> {
+            self.created.fetch_add(1, Ordering::SeqCst);
+            Ok(Box::new(MemoryFile::default()))
+        }
+    }
+
+    impl SpoolFile for MemoryFile {
+        fn append(&mut self, chunk: &[u8]) -> Result<(), EdgeError> {
+            self.0.extend_from_slice(chunk);
+            Ok(())
+        }
+
+        fn reader(&self) -> Result<Box<dyn Read + '_>, EdgeError> {
+            Ok(Box::new(self.0.as_slice()))
+        }
+    }
+
+    fn chunked(parts: &[&'static str]) -> Body {
+        let chunks: Vec<Bytes> = parts
+            .iter()
+            .map(|part| Bytes::from_static(part.as_bytes()))
+            .collect();
+        Body::stream(stream::iter(chunks))
+    }
+
+    fn spill(
+        body: Body,
+        threshold: usize,
+        spool: Option<MemorySpool>,
+    ) -> Result<Buffered, EdgeError> {
+        block_on(buffer(
+            body,
+            BodyBuffering::Spill { threshold },
+            usize::MAX,
+            spool.map(BodySpoolHandle::with_spool),
+        ))
+    }
+
+    #[test]
+    fn bodies_within_the_threshold_stay_in_memory() {
+        let spool = MemorySpool::default();
+        let created = Arc::clone(&spool.created);
+
+        let buffered = spill(chunked(&["0123", "4567"]), 8, Some(spool)).expect("buffered");
+        assert!(matches!(buffered, Buffered::Memory(bytes) if bytes == "01234567"));
+        assert_eq!(created.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn bodies_past_the_threshold_spill_whole() {
+        for body in [chunked(&["0123", "4567", "89"]), Body::from("0123456789")] {
+            let Buffered::Spilled(spilled) =
+                spill(body, 6, Some(MemorySpool::default())).expect("spilled")
+            else {
+                panic!("expected a spilled body");
+            };
+            let mut read = String::new();
+            spilled
+                .reader()
+                .expect("reader")
+                .read_to_string(&mut read)
+                .expect("read");
+            assert_eq!(spilled.len(), 10);
+            assert_eq!(read, "0123456789");
+        }
+    }
+
+    #[test]
+    fn bodies_past_max_bytes_are_rejected_while_spilling() {
+        let err = block_on(buffer(
+            chunked(&["0123", "4567", "89"]),
+            BodyBuffering::Spill { threshold: 4 },
+            8,
+            Some(BodySpoolHandle::with_spool(MemorySpool::default())),
+        ))
+        .map(drop)
+        .expect_err("over max_bytes");
+        assert_eq!(err.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn extractors_read_spilled_bodies_back() {
+        async fn handler(ctx: RequestContext) -> Result<String, EdgeError> {
+            let Json(value) = Json::<serde_json::Value>::from_request(&ctx).await?;
+            let spilled = ctx.spilled_body().map(SpilledBody::len);
+            Ok(format!("{spilled:?} {}", value["name"]))
+        }
+
+        let router = RouterService::builder()
+            .with_body_buffering(BodyBuffering::Spill { threshold: 8 })
+            .post("/echo", handler)
+            .build();
+        let request = request_builder()
+            .method(Method::POST)
+            .uri("/echo")
+            .header(CONTENT_TYPE, "application/json")
+            .extension(BodySpoolHandle::with_spool(MemorySpool::default()))
+            .body(chunked(&["{\"name\":", "\"edge\"}"]))
+            .expect("request");
+        let response = block_on(TestClient::new(router).send(request));
+        assert_eq!(response.text(), "Some(15) \"edge\"");
+    }
+
+    #[test]
+    fn spilled_form_bodies_are_parsed_a_pair_at_a_time() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "lowercase")]
+        enum Plan {
+            Free,
+            Pro,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Signup {
+            age: u8,
+            name: String,
+            newsletter: Option<bool>,
+            plan: Plan,
+        }
+
+        let signup: Signup =
+            form_from_reader(&b"name=Ada+Lovelace&&age=36&plan=pro&newsletter=true"[..])
+                .expect("form");
+        assert_eq!(
+            signup,
+            Signup {
+                age: 36,
+                name: "Ada Lovelace".to_owned(),
+                newsletter: Some(true),
+                plan: Plan::Pro,
+            }
+        );
+        assert_ne!(signup.plan, Plan::Free);
+
+        let err = form_from_reader::<Signup, _>(&b"name=x&age=old&plan=pro"[..])
+            .map(drop)
+            .expect_err("age is not a number");
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn without_a_spool_bodies_past_the_threshold_are_rejected() {
+        let err = spill(chunked(&["0123", "4567"]), 6, None)
+            .map(drop)
+            .expect_err("rejected");
+        assert_eq!(err.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
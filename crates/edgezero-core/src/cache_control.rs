@@ -0,0 +1,261 @@
+//! `Cache-Control` response headers.
+//!
+//! [`CacheControl`] builds the header from its common directives, so
+//! handlers do not have to spell them out by hand. Apply it to a
+//! [`Response`] directly, or wrap any responder with
+//! [`Responder::with_cache_control`]:
+//!
+//! ```rust,ignore
+//! #[action]
+//! async fn catalog() -> impl Responder {
+//!     let cache = CacheControl::new()
+//!         .public()
+//!         .max_age(Duration::from_secs(60))
+//!         .stale_while_revalidate(Duration::from_secs(300));
+//!     Json(load_catalog()).with_cache_control(cache)
+//! }
+//! ```
+//!
+//! Durations are written in whole seconds, rounded down.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::error::EdgeError;
+use crate::http::header::CACHE_CONTROL;
+use crate::http::{HeaderValue, Response};
+use crate::responder::Responder;
+use crate::response::IntoResponse;
+
+/// Who may store the response.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Visibility {
+    Private,
+    Public,
+}
+
+/// A `Cache-Control` header value.
+///
+/// Directives are written in a fixed order: `public` or `private`,
+/// `no-store`, `max-age`, `s-maxage`, then `stale-while-revalidate`. A
+/// value with no directives writes no header.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheControl {
+    max_age: Option<Duration>,
+    no_store: bool,
+    shared_max_age: Option<Duration>,
+    stale_while_revalidate: Option<Duration>,
+    visibility: Option<Visibility>,
+}
+
+/// A responder whose response gets a [`CacheControl`] header, returned by
+/// [`Responder::with_cache_control`].
+#[derive(Debug)]
+pub struct WithCacheControl<R> {
+    cache_control: CacheControl,
+    inner: R,
+}
+
+impl CacheControl {
+    /// Set the header on `response`, replacing any `Cache-Control` it has.
+    #[inline]
+    pub fn apply(&self, response: &mut Response) {
+        if let Some(value) = self.header_value() {
+            response.headers_mut().insert(CACHE_CONTROL, value);
+        }
+    }
+
+    /// The header value, or `None` when no directive is set.
+    #[must_use]
+    #[inline]
+    pub fn header_value(&self) -> Option<HeaderValue> {
+        let value = self.to_string();
+        if value.is_empty() {
+            return None;
+        }
+        HeaderValue::try_from(value).ok()
+    }
+
+    /// `max-age`: how long any cache may serve the response as fresh.
+    #[must_use]
+    #[inline]
+    pub const fn max_age(mut self, age: Duration) -> Self {
+        self.max_age = Some(age);
+        self
+    }
+
+    /// No directives.
+    #[must_use]
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `no-store`: no cache may keep a copy of the response.
+    #[must_use]
+    #[inline]
+    pub const fn no_store(mut self) -> Self {
+        self.no_store = true;
+        self
+    }
+
+    /// `private`: only the client's own cache may store the response.
+    /// Replaces [`Self::public`].
+    #[must_use]
+    #[inline]
+    pub const fn private(mut self) -> Self {
+        self.visibility = Some(Visibility::Private);
+        self
+    }
+
+    /// `public`: shared caches may store the response, even one they
+    /// would otherwise not, such as an answer to an authorized request.
+    /// Replaces [`Self::private`].
+    #[must_use]
+    #[inline]
+    pub const fn public(mut self) -> Self {
+        self.visibility = Some(Visibility::Public);
+        self
+    }
+
+    /// `s-maxage`: how long shared caches, such as a CDN, may serve the
+    /// response as fresh, overriding [`Self::max_age`] for them.
+    #[must_use]
+    #[inline]
+    pub const fn s_maxage(mut self, age: Duration) -> Self {
+        self.shared_max_age = Some(age);
+        self
+    }
+
+    /// `stale-while-revalidate`: how long past freshness a cache may serve
+    /// the response while it fetches a new one in the background.
+    #[must_use]
+    #[inline]
+    pub const fn stale_while_revalidate(mut self, window: Duration) -> Self {
+        self.stale_while_revalidate = Some(window);
+        self
+    }
+}
+
+impl fmt::Display for CacheControl {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let visibility = self.visibility.map(|visibility| match visibility {
+            Visibility::Private => "private".to_owned(),
+            Visibility::Public => "public".to_owned(),
+        });
+        let seconds = |name: &str, duration: Option<Duration>| {
+            duration.map(|value| format!("{name}={}", value.as_secs()))
+        };
+        let directives: Vec<String> = [
+            visibility,
+            self.no_store.then(|| "no-store".to_owned()),
+            seconds("max-age", self.max_age),
+            seconds("s-maxage", self.shared_max_age),
+            seconds("stale-while-revalidate", self.stale_while_revalidate),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        f.write_str(&directives.join(", "))
+    }
+}
+
+impl<R> IntoResponse for WithCacheControl<R>
+where
+    R: Responder,
+{
+    #[inline]
+    fn into_response(self) -> Result<Response, EdgeError> {
+        let mut response = self.inner.respond()?;
+        self.cache_control.apply(&mut response);
+        Ok(response)
+    }
+}
+
+impl<R> WithCacheControl<R> {
+    pub(crate) const fn new(inner: R, cache_control: CacheControl) -> Self {
+        Self {
+            cache_control,
+            inner,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::RequestContext;
+    use crate::http::StatusCode;
+    use crate::router::RouterService;
+    use crate::test_client::TestClient;
+    use futures::executor::block_on;
+
+    const MINUTE: Duration = Duration::from_mins(1);
+
+    #[test]
+    fn directives_serialize_in_a_fixed_order() {
+        let cases = [
+            (CacheControl::new().no_store(), "no-store"),
+            (
+                CacheControl::new().private().max_age(MINUTE),
+                "private, max-age=60",
+            ),
+            (
+                CacheControl::new()
+                    .stale_while_revalidate(MINUTE * 5)
+                    .s_maxage(MINUTE * 10)
+                    .max_age(MINUTE)
+                    .public(),
+                "public, max-age=60, s-maxage=600, stale-while-revalidate=300",
+            ),
+            (
+                CacheControl::new()
+                    .public()
+                    .private()
+                    .max_age(Duration::from_millis(1500)),
+                "private, max-age=1",
+            ),
+        ];
+        for (cache_control, expected) in cases {
+            assert_eq!(cache_control.to_string(), expected);
+            assert_eq!(
+                cache_control.header_value(),
+                Some(HeaderValue::from_static(expected))
+            );
+        }
+    }
+
+    #[test]
+    fn apply_replaces_the_header_unless_empty() {
+        let mut response = "body".into_response().expect("response");
+        response
+            .headers_mut()
+            .insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+
+        CacheControl::new().apply(&mut response);
+        assert_eq!(response.headers()[CACHE_CONTROL], "no-cache");
+
+        CacheControl::new().private().apply(&mut response);
+        assert_eq!(response.headers()[CACHE_CONTROL], "private");
+        assert_eq!(response.headers().get_all(CACHE_CONTROL).iter().count(), 1);
+    }
+
+    #[test]
+    fn responders_carry_the_header_and_errors_pass_through() {
+        async fn cached(_ctx: RequestContext) -> Result<WithCacheControl<&'static str>, EdgeError> {
+            Ok("catalog".with_cache_control(CacheControl::new().public().max_age(MINUTE)))
+        }
+
+        let router = RouterService::builder().get("/catalog", cached).build();
+        let response = block_on(TestClient::new(router).get("/catalog"));
+        assert_eq!(response.text(), "catalog");
+        assert_eq!(response.header("cache-control"), Some("public, max-age=60"));
+
+        let failed = Err::<&str, _>(EdgeError::not_found("gone"))
+            .with_cache_control(CacheControl::new().no_store())
+            .into_response()
+            .expect_err("the error is kept");
+        assert_eq!(failed.status(), StatusCode::NOT_FOUND);
+    }
+}
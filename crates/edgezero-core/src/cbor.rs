@@ -0,0 +1,176 @@
+//! CBOR (RFC 8949) request and response bodies.
+//!
+//! Enabled by the `cbor` feature. [`Cbor<T>`] is both an extractor and a
+//! responder, like the JSON types but for compact binary payloads such as
+//! those sent by IoT devices:
+//!
+//! ```rust,ignore
+//! async fn ingest(Cbor(reading): Cbor<Reading>) -> Result<Cbor<Ack>, EdgeError> {
+//!     Ok(Cbor(store(reading).await?))
+//! }
+//! ```
+//!
+//! The extractor decodes the body whatever its `Content-Type`, as the JSON
+//! extractors do, and answers `400 Bad Request` when the payload does not
+//! decode into `T`. Responses are sent as `application/cbor`.
+
+use std::ops::{Deref, DerefMut};
+
+use async_trait::async_trait;
+use ciborium::de::Error as CborError;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::body::Body;
+use crate::body_spool;
+use crate::context::RequestContext;
+use crate::error::EdgeError;
+use crate::extractor::FromRequest;
+use crate::http::header::CONTENT_TYPE;
+use crate::http::{HeaderValue, Response, StatusCode};
+use crate::response::{IntoResponse, response_with_body};
+
+/// Media type of CBOR bodies.
+pub const CBOR_MEDIA_TYPE: &str = "application/cbor";
+
+/// A CBOR-encoded body: decoded into `T` as an extractor, encoded from `T`
+/// as a responder.
+pub struct Cbor<T>(pub T);
+
+#[async_trait(?Send)]
+impl<T> FromRequest for Cbor<T>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    #[inline]
+    async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+        if !ctx.has_body() {
+            return Err(EdgeError::missing_body("a CBOR body is required"));
+        }
+        ctx.buffer_body().await?;
+        ctx.read_buffered(
+            |body| {
+                let bytes = body.as_bytes().ok_or_else(|| {
+                    EdgeError::bad_request("streaming body cannot be materialised as CBOR")
+                })?;
+                ciborium::from_reader(bytes)
+                    .map(Cbor)
+                    .map_err(|err| EdgeError::bad_request(format!("invalid CBOR payload: {err}")))
+            },
+            |spilled| match ciborium::from_reader(spilled.reader()?) {
+                Ok(value) => Ok(Cbor(value)),
+                Err(CborError::Io(err)) => Err(body_spool::read_error(&err)),
+                Err(err) => Err(EdgeError::bad_request(format!(
+                    "invalid CBOR payload: {err}"
+                ))),
+            },
+        )
+    }
+}
+
+impl<T> Deref for Cbor<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Cbor<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> IntoResponse for Cbor<T>
+where
+    T: Serialize,
+{
+    #[inline]
+    fn into_response(self) -> Result<Response, EdgeError> {
+        let mut encoded = Vec::new();
+        ciborium::into_writer(&self.0, &mut encoded).map_err(EdgeError::internal)?;
+        let mut response = response_with_body(StatusCode::OK, Body::from(encoded))?;
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(CBOR_MEDIA_TYPE));
+        Ok(response)
+    }
+}
+
+impl<T> Cbor<T> {
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{Method, Request, request_builder};
+    use crate::router::RouterService;
+    use crate::test_client::TestClient;
+    use futures::executor::block_on;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Reading {
+        celsius: f32,
+        sensor: String,
+    }
+
+    async fn echo(ctx: RequestContext) -> Result<Cbor<Reading>, EdgeError> {
+        let Cbor(reading) = Cbor::<Reading>::from_request(&ctx).await?;
+        Ok(Cbor(reading))
+    }
+
+    fn encode<T: Serialize>(value: &T) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        ciborium::into_writer(value, &mut encoded).expect("encode");
+        encoded
+    }
+
+    fn post(content_type: &str, body: Vec<u8>) -> Request {
+        request_builder()
+            .method(Method::POST)
+            .uri("/readings")
+            .header(CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+            .expect("request")
+    }
+
+    #[test]
+    fn readings_round_trip_through_a_router() {
+        let client = TestClient::new(RouterService::builder().post("/readings", echo).build());
+        let reading = Reading {
+            celsius: 21.5,
+            sensor: "greenhouse-3".into(),
+        };
+
+        let response = block_on(client.send(post(CBOR_MEDIA_TYPE, encode(&reading))));
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.header("content-type"), Some(CBOR_MEDIA_TYPE));
+        let decoded: Reading = ciborium::from_reader(response.bytes().as_ref()).expect("decode");
+        assert_eq!(decoded, reading);
+
+        let senml = block_on(client.send(post("application/senml+cbor", encode(&reading))));
+        assert_eq!(senml.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn malformed_payloads_are_rejected() {
+        let client = TestClient::new(RouterService::builder().post("/readings", echo).build());
+
+        let truncated = block_on(client.send(post(CBOR_MEDIA_TYPE, vec![0xa2, 0x67])));
+        assert_eq!(truncated.status(), StatusCode::BAD_REQUEST);
+
+        let wrong_shape = block_on(client.send(post(CBOR_MEDIA_TYPE, encode(&[1_u8, 2, 3]))));
+        assert_eq!(wrong_shape.status(), StatusCode::BAD_REQUEST);
+
+        let json = block_on(client.send(post("application/json", b"{}".to_vec())));
+        assert_eq!(json.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,121 @@
+//! Wall-clock time for handlers.
+//!
+//! `std::time::SystemTime::now` panics on `wasm32-unknown-unknown`, so
+//! handlers should read the time through [`RequestContext::now`] instead. It
+//! returns a `web_time::SystemTime`, which is `std::time::SystemTime` on
+//! native targets and backed by `Date.now()` in the browser-like runtimes.
+//!
+//! The time comes from the [`Clock`] registered with
+//! [`RouterBuilder::with_clock`], or [`SystemClock`] when none is. Tests
+//! register a [`FixedClock`] to make timestamps predictable:
+//!
+//! ```rust,ignore
+//! let router = RouterService::builder()
+//!     .with_clock(FixedClock::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000)))
+//!     .get("/now", now_handler)
+//!     .build();
+//! ```
+//!
+//! [`RequestContext::now`]: crate::context::RequestContext::now
+//! [`RouterBuilder::with_clock`]: crate::router::RouterBuilder::with_clock
+
+use std::sync::Arc;
+
+use web_time::SystemTime;
+
+/// A source of the current time.
+pub trait Clock: Send + Sync {
+    /// The current time.
+    fn now(&self) -> SystemTime;
+}
+
+/// A clock that always reports the same time.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FixedClock {
+    time: SystemTime,
+}
+
+/// The clock registered for a router, stored in each request's extensions.
+#[derive(Clone)]
+pub(crate) struct SharedClock(Arc<dyn Clock>);
+
+/// The platform's wall clock.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SystemClock;
+
+impl Clock for FixedClock {
+    #[inline]
+    fn now(&self) -> SystemTime {
+        self.time
+    }
+}
+
+impl FixedClock {
+    /// A clock stopped at `time`.
+    #[must_use]
+    #[inline]
+    pub const fn new(time: SystemTime) -> Self {
+        Self { time }
+    }
+}
+
+impl Clock for SharedClock {
+    #[inline]
+    fn now(&self) -> SystemTime {
+        self.0.now()
+    }
+}
+
+impl SharedClock {
+    pub(crate) fn new<C: Clock + 'static>(clock: C) -> Self {
+        Self(Arc::new(clock))
+    }
+}
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::RequestContext;
+    use crate::error::EdgeError;
+    use crate::router::RouterService;
+    use crate::test_client::TestClient;
+    use futures::executor::block_on;
+    use std::time::Duration;
+    use web_time::UNIX_EPOCH;
+
+    async fn seconds(ctx: RequestContext) -> Result<String, EdgeError> {
+        let elapsed = ctx
+            .now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(EdgeError::internal)?;
+        Ok(elapsed.as_secs().to_string())
+    }
+
+    #[test]
+    fn handlers_read_the_registered_clock() {
+        let fixed = FixedClock::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+        let router = RouterService::builder()
+            .with_clock(fixed)
+            .get("/now", seconds)
+            .build();
+        let response = block_on(TestClient::new(router).get("/now"));
+        assert_eq!(response.text(), "1700000000");
+    }
+
+    #[test]
+    fn system_clock_is_the_default() {
+        let before = SystemTime::now();
+        let router = RouterService::builder().get("/now", seconds).build();
+        let response = block_on(TestClient::new(router).get("/now"));
+        let reported: u64 = response.text().parse().expect("seconds");
+        let floor = before.duration_since(UNIX_EPOCH).expect("after epoch");
+        assert!(reported >= floor.as_secs());
+    }
+}
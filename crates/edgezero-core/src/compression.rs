@@ -1,14 +1,297 @@
+//! Content-coding support: stream decoders and encoders for gzip, brotli,
+//! and deflate, the [`DecompressRequest`] and [`CompressResponse`]
+//! middleware that apply them to request and response bodies, and the
+//! `Accept-Encoding` handling behind [`ProxyResponse::transcode_for`].
+//!
+//! [`ProxyResponse::transcode_for`]: crate::proxy::ProxyResponse::transcode_for
+
 use std::io;
+use std::mem;
 
-use async_compression::futures::bufread::{BrotliDecoder, GzipDecoder};
+use anyhow::Error as AnyError;
+use async_compression::futures::bufread::{
+    BrotliDecoder, BrotliEncoder, GzipDecoder, GzipEncoder, ZlibDecoder, ZlibEncoder,
+};
 use async_stream::try_stream;
+use async_trait::async_trait;
 use bytes::Bytes;
 use futures::TryStream;
-use futures::io::{AsyncReadExt as _, BufReader};
+use futures::io::{AsyncRead, AsyncReadExt as _, BufReader};
 use futures::stream::Stream;
 use futures_util::TryStreamExt as _;
+use futures_util::future::ready;
+use futures_util::stream::{self, LocalBoxStream, StreamExt as _};
+
+use crate::body::Body;
+use crate::body_limit::{self, BodyLimit};
+use crate::context::RequestContext;
+use crate::error::EdgeError;
+use crate::http::header::{
+    ACCEPT_ENCODING, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, ETAG,
+};
+use crate::http::{HeaderMap, HeaderValue, Response, StatusCode};
+use crate::middleware::{Middleware, Next};
+use crate::response::append_vary;
 
 const BUFFER_SIZE: usize = 8 * 1024;
+/// Default cap on the decoded size of a request body.
+pub const DEFAULT_MAX_DECODED_SIZE: usize = 16 * 1024 * 1024;
+
+type ChunkStream = LocalBoxStream<'static, Result<Bytes, io::Error>>;
+
+/// A client's `Accept-Encoding` header.
+pub(crate) struct AcceptEncoding<'header> {
+    header: &'header str,
+}
+
+/// Middleware that compresses response bodies in the coding the client's
+/// `Accept-Encoding` rates highest, preferring brotli, then gzip, then
+/// deflate.
+///
+/// Bodies are encoded chunk by chunk as they are sent, buffered or
+/// streamed alike: each chunk a handler streams is compressed and flushed
+/// as soon as the next one is not ready, so server-sent events and other
+/// slow streams still arrive promptly. The response loses its
+/// `Content-Length`, so it is sent chunked, gains `Vary: Accept-Encoding`,
+/// and a strong `ETag` becomes weak. Trailers are kept.
+///
+/// Only text, JSON, JavaScript, XML and SVG responses are compressed.
+/// Responses that are empty, already encoded, marked
+/// `Cache-Control: no-transform`, or have a status without a body pass
+/// through untouched.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CompressResponse;
+
+/// Content codings this module can decode and encode.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Coding {
+    Brotli,
+    Deflate,
+    Gzip,
+}
+
+impl<'header> AcceptEncoding<'header> {
+    /// Whether the client takes `coding`: it is listed, or covered by `*`,
+    /// with a non-zero quality. `identity` is taken unless excluded.
+    pub(crate) fn accepts(&self, coding: &str) -> bool {
+        self.quality(coding) > 0
+    }
+
+    pub(crate) fn new(header: &'header str) -> Self {
+        Self { header }
+    }
+
+    /// The coding of this module the client rates highest, preferring
+    /// brotli, then gzip, then deflate between equal ratings.
+    pub(crate) fn preferred(&self) -> Option<Coding> {
+        [Coding::Brotli, Coding::Gzip, Coding::Deflate]
+            .into_iter()
+            .map(|coding| (coding, self.quality(coding.name())))
+            .filter(|&(_, quality)| quality > 0)
+            .fold(None, |best, (coding, quality)| match best {
+                Some((_, best_quality)) if best_quality >= quality => best,
+                Some(_) | None => Some((coding, quality)),
+            })
+            .map(|(coding, _)| coding)
+    }
+
+    /// The quality, in thousandths, the header gives `coding`. Entries
+    /// with a malformed quality are ignored.
+    fn quality(&self, coding: &str) -> u16 {
+        let mut wildcard = None;
+        for entry in self.header.split(',') {
+            let mut params = entry.split(';');
+            let name = params.next().unwrap_or_default().trim();
+            let parsed = params
+                .filter_map(|param| param.trim().split_once('='))
+                .find(|(key, _)| key.trim().eq_ignore_ascii_case("q"))
+                .map_or(Some(1000), |(_, value)| parse_quality(value.trim()));
+            let Some(quality) = parsed else {
+                continue;
+            };
+            if name.eq_ignore_ascii_case(coding)
+                || (coding == "gzip" && name.eq_ignore_ascii_case("x-gzip"))
+            {
+                return quality;
+            }
+            if name == "*" {
+                wildcard = Some(quality);
+            }
+        }
+        wildcard.unwrap_or(if coding == "identity" { 1000 } else { 0 })
+    }
+}
+
+impl Coding {
+    fn decode(self, chunks: LocalBoxStream<'static, Result<Vec<u8>, io::Error>>) -> ChunkStream {
+        match self {
+            Coding::Brotli => decode_brotli_stream(chunks).boxed_local(),
+            Coding::Deflate => decode_deflate_stream(chunks).boxed_local(),
+            Coding::Gzip => decode_gzip_stream(chunks).boxed_local(),
+        }
+    }
+
+    fn encode(self, chunks: ChunkStream) -> ChunkStream {
+        match self {
+            Coding::Brotli => encode_brotli_stream(chunks).boxed_local(),
+            Coding::Deflate => encode_deflate_stream(chunks).boxed_local(),
+            Coding::Gzip => encode_gzip_stream(chunks).boxed_local(),
+        }
+    }
+
+    /// The coding named by a `Content-Encoding` value, if this module
+    /// handles it. A list of several codings is not handled.
+    pub(crate) fn from_header(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "br" => Some(Coding::Brotli),
+            "deflate" => Some(Coding::Deflate),
+            "gzip" | "x-gzip" => Some(Coding::Gzip),
+            _ => None,
+        }
+    }
+
+    /// The coding's `Content-Encoding` token.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Coding::Brotli => "br",
+            Coding::Deflate => "deflate",
+            Coding::Gzip => "gzip",
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Middleware for CompressResponse {
+    #[inline]
+    async fn handle(&self, ctx: RequestContext, next: Next<'_>) -> Result<Response, EdgeError> {
+        let preferred = ctx
+            .request()
+            .headers()
+            .get(ACCEPT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|header| AcceptEncoding::new(header).preferred());
+        let mut response = next.run(ctx).await?;
+        if !is_compressible(&response) {
+            return Ok(response);
+        }
+        append_vary(response.headers_mut(), "Accept-Encoding");
+        let Some(coding) = preferred else {
+            return Ok(response);
+        };
+
+        let body = encode_body(mem::take(response.body_mut()), coding);
+        *response.body_mut() = body;
+        let headers = response.headers_mut();
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static(coding.name()));
+        headers.remove(CONTENT_LENGTH);
+        weaken_etag(headers);
+        Ok(response)
+    }
+}
+
+/// Middleware that decodes gzip, brotli, and deflate request bodies before
+/// handlers and extractors see them, then drops the `Content-Encoding` and
+/// `Content-Length` headers.
+///
+/// Buffered bodies are decoded up front and stay buffered, so
+/// [`RequestContext::json`] and the body extractors work unchanged; a body
+/// that is not valid for its coding is rejected with `400 Bad Request`, and
+/// one that decodes to more than the configured limit with
+/// `413 Payload Too Large`. Streaming bodies are decoded chunk by chunk as
+/// the handler reads them, and end with an error that reads as a `413` once
+/// their decoded bytes pass the limit, so a small compressed body cannot
+/// expand without bound. Bodies in other codings pass through untouched.
+pub struct DecompressRequest {
+    max_decoded_size: usize,
+}
+
+impl DecompressRequest {
+    /// Reject bodies that decode to more than `max_decoded_size` bytes.
+    #[must_use]
+    #[inline]
+    pub fn new(max_decoded_size: usize) -> Self {
+        Self { max_decoded_size }
+    }
+}
+
+impl Default for DecompressRequest {
+    #[inline]
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_DECODED_SIZE)
+    }
+}
+
+#[async_trait(?Send)]
+impl Middleware for DecompressRequest {
+    #[inline]
+    async fn handle(&self, mut ctx: RequestContext, next: Next<'_>) -> Result<Response, EdgeError> {
+        let Some(coding) = ctx
+            .request()
+            .headers()
+            .get(CONTENT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Coding::from_header)
+        else {
+            return next.run(ctx).await;
+        };
+
+        let decoded = match ctx.take_body() {
+            Body::Once(bytes) => {
+                let chunks = stream::once(async move { Ok(bytes.to_vec()) }).boxed_local();
+                let mut decoded = coding.decode(chunks);
+                let mut buf = Vec::new();
+                while let Some(result) = decoded.next().await {
+                    let chunk = result.map_err(|err| {
+                        EdgeError::bad_request(format!(
+                            "invalid {} request body: {err}",
+                            coding.name()
+                        ))
+                    })?;
+                    buf.extend_from_slice(&chunk);
+                    if buf.len() > self.max_decoded_size {
+                        return Err(body_limit::too_large(self.max_decoded_size));
+                    }
+                }
+                Body::from_bytes(buf)
+            }
+            Body::Stream(stream) | Body::StreamWithTrailers(stream, _) => {
+                let chunks = stream
+                    .map(|chunk| chunk.map(|bytes| bytes.to_vec()).map_err(io::Error::other))
+                    .boxed_local();
+                let decoded = coding
+                    .decode(chunks)
+                    .map(|chunk| chunk.map_err(AnyError::from))
+                    .boxed_local();
+                Body::Stream(BodyLimit::new(self.max_decoded_size).limit_stream(decoded))
+            }
+        };
+
+        let headers = ctx.request_mut().headers_mut();
+        headers.remove(CONTENT_ENCODING);
+        headers.remove(CONTENT_LENGTH);
+        *ctx.request_mut().body_mut() = decoded;
+        next.run(ctx).await
+    }
+}
+
+/// Decode a stream of brotli-compressed chunks into plain bytes.
+#[inline]
+pub fn decode_brotli_stream<S>(stream: S) -> impl Stream<Item = Result<Bytes, io::Error>>
+where
+    S: TryStream<Ok = Vec<u8>, Error = io::Error> + Unpin,
+{
+    read_chunks(BrotliDecoder::new(BufReader::new(stream.into_async_read())))
+}
+
+/// Decode a stream of deflate-compressed chunks (the zlib format HTTP calls
+/// `deflate`) into plain bytes.
+#[inline]
+pub fn decode_deflate_stream<S>(stream: S) -> impl Stream<Item = Result<Bytes, io::Error>>
+where
+    S: TryStream<Ok = Vec<u8>, Error = io::Error> + Unpin,
+{
+    read_chunks(ZlibDecoder::new(BufReader::new(stream.into_async_read())))
+}
 
 /// Decode a stream of gzip-compressed chunks into plain bytes.
 #[inline]
@@ -16,45 +299,122 @@ pub fn decode_gzip_stream<S>(stream: S) -> impl Stream<Item = Result<Bytes, io::
 where
     S: TryStream<Ok = Vec<u8>, Error = io::Error> + Unpin,
 {
-    try_stream! {
-        let reader = BufReader::new(stream.into_async_read());
-        let mut decoder = GzipDecoder::new(reader);
-        let mut buffer = vec![0_u8; BUFFER_SIZE];
+    read_chunks(GzipDecoder::new(BufReader::new(stream.into_async_read())))
+}
 
-        loop {
-            let read = decoder.read(&mut buffer).await?;
-            if read == 0 {
-                break;
-            }
-            let chunk = buffer.get(..read).ok_or_else(|| {
-                io::Error::other(format!(
-                    "decoder reported {read}-byte read into a {BUFFER_SIZE}-byte buffer"
-                ))
-            })?;
-            yield Bytes::copy_from_slice(chunk);
+/// Encode a stream of plain chunks as brotli.
+#[inline]
+pub fn encode_brotli_stream<S>(stream: S) -> impl Stream<Item = Result<Bytes, io::Error>>
+where
+    S: TryStream<Error = io::Error> + Unpin,
+    S::Ok: AsRef<[u8]>,
+{
+    read_chunks(BrotliEncoder::new(BufReader::new(stream.into_async_read())))
+}
+
+/// Encode a stream of plain chunks as deflate (the zlib format HTTP calls
+/// `deflate`).
+#[inline]
+pub fn encode_deflate_stream<S>(stream: S) -> impl Stream<Item = Result<Bytes, io::Error>>
+where
+    S: TryStream<Error = io::Error> + Unpin,
+    S::Ok: AsRef<[u8]>,
+{
+    read_chunks(ZlibEncoder::new(BufReader::new(stream.into_async_read())))
+}
+
+/// Encode a stream of plain chunks as gzip.
+#[inline]
+pub fn encode_gzip_stream<S>(stream: S) -> impl Stream<Item = Result<Bytes, io::Error>>
+where
+    S: TryStream<Error = io::Error> + Unpin,
+    S::Ok: AsRef<[u8]>,
+{
+    read_chunks(GzipEncoder::new(BufReader::new(stream.into_async_read())))
+}
+
+/// `body` encoded as `coding` chunk by chunk as it is read, keeping any
+/// trailers.
+fn encode_body(body: Body, coding: Coding) -> Body {
+    let encode = |chunks: LocalBoxStream<'static, Result<Bytes, AnyError>>| {
+        coding
+            .encode(chunks.map_err(io::Error::other).boxed_local())
+            .map_err(AnyError::from)
+            .boxed_local()
+    };
+    match body {
+        Body::Once(bytes) => Body::Stream(encode(stream::once(ready(Ok(bytes))).boxed_local())),
+        Body::Stream(chunks) => Body::Stream(encode(chunks)),
+        Body::StreamWithTrailers(chunks, trailers) => {
+            Body::StreamWithTrailers(encode(chunks), trailers)
         }
     }
 }
 
-/// Decode a stream of brotli-compressed chunks into plain bytes.
-#[inline]
-pub fn decode_brotli_stream<S>(stream: S) -> impl Stream<Item = Result<Bytes, io::Error>>
+/// Whether [`CompressResponse`] should encode `response`.
+fn is_compressible(response: &Response) -> bool {
+    let status = response.status();
+    let headers = response.headers();
+    let no_body = status.is_informational()
+        || status == StatusCode::NO_CONTENT
+        || status == StatusCode::NOT_MODIFIED
+        || matches!(response.body(), Body::Once(bytes) if bytes.is_empty());
+    let no_transform = headers
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|directive| directive.trim().eq_ignore_ascii_case("no-transform"));
+    let textual = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|media_type| media_type.trim().to_ascii_lowercase())
+        .is_some_and(|media_type| {
+            media_type.starts_with("text/")
+                || media_type.ends_with("+json")
+                || media_type.ends_with("+xml")
+                || matches!(
+                    media_type.as_str(),
+                    "application/json"
+                        | "application/javascript"
+                        | "application/x-ndjson"
+                        | "application/xml"
+                        | "image/svg+xml"
+                )
+        });
+    !no_body && !no_transform && textual && !headers.contains_key(CONTENT_ENCODING)
+}
+
+/// `"0"`, `"0.5"`, `"1.000"`, ... as thousandths.
+fn parse_quality(value: &str) -> Option<u16> {
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if fraction.len() > 3 || !fraction.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let thousandths: u16 = format!("{fraction:0<3}").parse().ok()?;
+    match whole {
+        "0" => Some(thousandths),
+        "1" if thousandths == 0 => Some(1000),
+        _ => None,
+    }
+}
+
+fn read_chunks<R>(mut reader: R) -> impl Stream<Item = Result<Bytes, io::Error>>
 where
-    S: TryStream<Ok = Vec<u8>, Error = io::Error> + Unpin,
+    R: AsyncRead + Unpin,
 {
     try_stream! {
-        let reader = BufReader::new(stream.into_async_read());
-        let mut decoder = BrotliDecoder::new(reader);
         let mut buffer = vec![0_u8; BUFFER_SIZE];
 
         loop {
-            let read = decoder.read(&mut buffer).await?;
+            let read = reader.read(&mut buffer).await?;
             if read == 0 {
                 break;
             }
             let chunk = buffer.get(..read).ok_or_else(|| {
                 io::Error::other(format!(
-                    "decoder reported {read}-byte read into a {BUFFER_SIZE}-byte buffer"
+                    "coder reported {read}-byte read into a {BUFFER_SIZE}-byte buffer"
                 ))
             })?;
             yield Bytes::copy_from_slice(chunk);
@@ -62,14 +422,112 @@ where
     }
 }
 
+/// `body`, in coding `from`, decoded and re-encoded as `to` (left decoded
+/// for `None`) chunk by chunk as it is read. Trailers are dropped.
+pub(crate) fn transcode(body: Body, from: Coding, to: Option<Coding>) -> Body {
+    let chunks = match body {
+        Body::Once(bytes) => stream::once(ready(Ok(bytes.to_vec()))).boxed_local(),
+        Body::Stream(chunks) | Body::StreamWithTrailers(chunks, _) => chunks
+            .map(|chunk| chunk.map(|bytes| bytes.to_vec()).map_err(io::Error::other))
+            .boxed_local(),
+    };
+    let decoded = from.decode(chunks);
+    Body::from_stream(match to {
+        Some(coding) => coding.encode(decoded),
+        None => decoded,
+    })
+}
+
+/// Mark a strong `ETag` weak, since the encoded bytes differ from the
+/// representation it was computed for.
+pub(crate) fn weaken_etag(headers: &mut HeaderMap) {
+    if let Some(weak) = headers
+        .get(ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .filter(|etag| etag.starts_with('"'))
+        .and_then(|etag| HeaderValue::try_from(format!("W/{etag}")).ok())
+    {
+        headers.insert(ETAG, weak);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::http::header::VARY;
+    use crate::http::{Method, Request, StatusCode, request_builder};
+    use crate::response::response_with_body;
+    use crate::router::RouterService;
     use brotli::CompressorWriter;
-    use flate2::{Compression, write::GzEncoder};
+    use flate2::Compression;
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use futures::channel::mpsc;
     use futures::executor::block_on;
-    use futures_util::stream;
+    use serde_json::Value;
     use std::io::Write as _;
+    use std::iter;
+    use std::sync::{Arc, Mutex};
+
+    async fn echo_json(ctx: RequestContext) -> Result<Response, EdgeError> {
+        let payload: Value = ctx.json()?;
+        let encoding = ctx
+            .request()
+            .headers()
+            .get(CONTENT_ENCODING)
+            .map_or("none", |value| value.to_str().unwrap_or("invalid"))
+            .to_owned();
+        response_with_body(
+            StatusCode::OK,
+            Body::from(format!("{encoding} {}", payload["name"])),
+        )
+    }
+
+    fn compressed_get(path: &str, accept: &str) -> Request {
+        request_builder()
+            .uri(path)
+            .header(ACCEPT_ENCODING, accept)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    async fn png(_ctx: RequestContext) -> Result<Response, EdgeError> {
+        let mut response = response_with_body(StatusCode::OK, Body::from(vec![0x89_u8, b'P']))?;
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("image/png"));
+        Ok(response)
+    }
+
+    async fn text(_ctx: RequestContext) -> Result<Response, EdgeError> {
+        let mut response = response_with_body(StatusCode::OK, Body::from("plain text"))?;
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+        Ok(response)
+    }
+
+    fn decompressing_router(max_decoded_size: usize) -> RouterService {
+        RouterService::builder()
+            .middleware(DecompressRequest::new(max_decoded_size))
+            .post("/echo", echo_json)
+            .build()
+    }
+
+    fn encoded_post(encoding: &str, body: Vec<u8>) -> Request {
+        request_builder()
+            .method(Method::POST)
+            .uri("/echo")
+            .header("content-type", "application/json")
+            .header(CONTENT_ENCODING, encoding)
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
 
     #[test]
     fn decode_gzip_stream_yields_plain_bytes() {
@@ -129,4 +587,189 @@ mod tests {
         });
         assert!(result.is_err(), "invalid brotli must decode to an error");
     }
+
+    #[test]
+    fn accept_encoding_ranks_codings_by_quality() {
+        let accept = AcceptEncoding::new("gzip;q=0.8, deflate, br;q=0, *;q=0.1");
+        assert!(accept.accepts("gzip"));
+        assert!(!accept.accepts("br"));
+        assert!(accept.accepts("zstd"));
+        assert_eq!(accept.preferred(), Some(Coding::Deflate));
+
+        assert_eq!(
+            AcceptEncoding::new("deflate, gzip, br").preferred(),
+            Some(Coding::Brotli)
+        );
+        assert_eq!(
+            AcceptEncoding::new("x-gzip;q=1.0, br;q=2").preferred(),
+            Some(Coding::Gzip)
+        );
+
+        let identity_only = AcceptEncoding::new("");
+        assert!(identity_only.accepts("identity"));
+        assert_eq!(identity_only.preferred(), None);
+        assert!(!AcceptEncoding::new("*;q=0").accepts("identity"));
+    }
+
+    #[test]
+    fn encode_gzip_stream_round_trips_through_the_decoder() {
+        let chunks = stream::iter([
+            Ok::<_, io::Error>(Bytes::from_static(b"hello ")),
+            Ok(Bytes::from_static(b"gzip")),
+        ]);
+        let encoded = block_on(encode_gzip_stream(chunks).try_collect::<Vec<Bytes>>()).unwrap();
+        let stream = stream::iter(encoded.into_iter().map(|chunk| Ok(chunk.to_vec())));
+        let decoded = block_on(decode_gzip_stream(stream).try_collect::<Vec<Bytes>>()).unwrap();
+        assert_eq!(decoded.concat(), b"hello gzip");
+    }
+
+    #[test]
+    fn compress_response_encodes_streams_as_chunks_arrive() {
+        let (sender, receiver) = mpsc::unbounded::<Result<Bytes, io::Error>>();
+        let source = Arc::new(Mutex::new(Some(receiver)));
+        let router = RouterService::builder()
+            .middleware(CompressResponse)
+            .get("/events", move |_ctx: RequestContext| {
+                let taken = source.lock().unwrap().take();
+                async move {
+                    let chunks = taken.ok_or_else(|| EdgeError::bad_request("already sent"))?;
+                    let mut response =
+                        response_with_body(StatusCode::OK, Body::from_stream(chunks))?;
+                    response
+                        .headers_mut()
+                        .insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+                    Ok::<_, EdgeError>(response)
+                }
+            })
+            .build();
+
+        let response = block_on(router.oneshot(compressed_get("/events", "gzip"))).unwrap();
+        assert_eq!(response.headers()[CONTENT_ENCODING], "gzip");
+        assert_eq!(response.headers()[VARY], "Accept-Encoding");
+        assert!(!response.headers().contains_key(CONTENT_LENGTH));
+        let mut encoded = response.into_body().into_stream().unwrap();
+
+        sender
+            .unbounded_send(Ok(Bytes::from_static(b"data: one\n\n")))
+            .unwrap();
+        let first = block_on(encoded.next()).unwrap().unwrap();
+        assert!(
+            !first.is_empty(),
+            "the first chunk is flushed before the next"
+        );
+        sender
+            .unbounded_send(Ok(Bytes::from_static(b"data: two\n\n")))
+            .unwrap();
+        drop(sender);
+        let rest = block_on(encoded.try_collect::<Vec<Bytes>>()).unwrap();
+
+        let compressed = iter::once(first)
+            .chain(rest)
+            .map(|chunk| Ok::<_, io::Error>(chunk.to_vec()));
+        let decoded =
+            block_on(decode_gzip_stream(stream::iter(compressed)).try_collect::<Vec<Bytes>>())
+                .unwrap();
+        assert_eq!(decoded.concat(), b"data: one\n\ndata: two\n\n");
+    }
+
+    #[test]
+    fn compress_response_skips_unaccepted_and_binary_responses() {
+        let router = RouterService::builder()
+            .middleware(CompressResponse)
+            .get("/text", text)
+            .get("/logo.png", png)
+            .build();
+
+        let identity = block_on(router.oneshot(compressed_get("/text", "identity"))).unwrap();
+        assert!(!identity.headers().contains_key(CONTENT_ENCODING));
+        assert_eq!(identity.headers()[VARY], "Accept-Encoding");
+        assert_eq!(identity.body().as_bytes().unwrap(), b"plain text");
+
+        let image = block_on(router.oneshot(compressed_get("/logo.png", "br, gzip"))).unwrap();
+        assert!(!image.headers().contains_key(CONTENT_ENCODING));
+        assert!(!image.headers().contains_key(VARY));
+
+        let brotli = block_on(router.oneshot(compressed_get("/text", "gzip, br"))).unwrap();
+        assert_eq!(brotli.headers()[CONTENT_ENCODING], "br");
+        let encoded = brotli.into_body().into_stream().unwrap();
+        let chunks = block_on(encoded.try_collect::<Vec<Bytes>>()).unwrap();
+        let decoded = block_on(
+            decode_brotli_stream(stream::iter(
+                chunks.into_iter().map(|chunk| Ok(chunk.to_vec())),
+            ))
+            .try_collect::<Vec<Bytes>>(),
+        )
+        .unwrap();
+        assert_eq!(decoded.concat(), b"plain text");
+    }
+
+    #[test]
+    fn decompress_request_decodes_gzip_json_for_extractors() {
+        let router = decompressing_router(DEFAULT_MAX_DECODED_SIZE);
+        let request = encoded_post("gzip", gzip(br#"{"name":"edge"}"#));
+        let response = block_on(router.oneshot(request)).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body().as_bytes().unwrap(), br#"none "edge""#);
+    }
+
+    #[test]
+    fn decompress_request_decodes_deflate_bodies() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(br#"{"name":"zlib"}"#).unwrap();
+        let request = encoded_post("Deflate", encoder.finish().unwrap());
+        let response =
+            block_on(decompressing_router(DEFAULT_MAX_DECODED_SIZE).oneshot(request)).unwrap();
+        assert_eq!(response.body().as_bytes().unwrap(), br#"none "zlib""#);
+    }
+
+    #[test]
+    fn decompress_request_rejects_invalid_and_oversized_bodies() {
+        let router = decompressing_router(8);
+        let invalid = encoded_post("gzip", b"not gzip at all".to_vec());
+        let invalid_response = block_on(router.oneshot(invalid)).unwrap();
+        assert_eq!(invalid_response.status(), StatusCode::BAD_REQUEST);
+
+        let oversized = encoded_post("gzip", gzip(br#"{"name":"too long"}"#));
+        let oversized_response = block_on(router.oneshot(oversized)).unwrap();
+        assert_eq!(oversized_response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn decompress_request_caps_streamed_bodies_as_they_decode() {
+        async fn length(ctx: RequestContext) -> Result<String, EdgeError> {
+            let bytes = ctx
+                .into_request()
+                .into_body()
+                .into_bytes_bounded(usize::MAX)
+                .await?;
+            Ok(bytes.len().to_string())
+        }
+
+        let router = RouterService::builder()
+            .middleware(DecompressRequest::new(1024))
+            .post("/length", length)
+            .build();
+        let streamed = |plain: &[u8]| {
+            request_builder()
+                .method(Method::POST)
+                .uri("/length")
+                .header(CONTENT_ENCODING, "gzip")
+                .body(Body::stream(stream::iter([Bytes::from(gzip(plain))])))
+                .unwrap()
+        };
+
+        let within = block_on(router.oneshot(streamed(&[b'a'; 1024]))).unwrap();
+        assert_eq!(within.body().as_bytes().unwrap(), b"1024");
+        // A megabyte of zeros compresses to about a kilobyte.
+        let bomb = block_on(router.oneshot(streamed(&vec![0_u8; 1024 * 1024]))).unwrap();
+        assert_eq!(bomb.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn decompress_request_leaves_unknown_codings_alone() {
+        let request = encoded_post("identity", br#"{"name":"plain"}"#.to_vec());
+        let response =
+            block_on(decompressing_router(DEFAULT_MAX_DECODED_SIZE).oneshot(request)).unwrap();
+        assert_eq!(response.body().as_bytes().unwrap(), br#"identity "plain""#);
+    }
 }
@@ -0,0 +1,327 @@
+//! Conditional write requests (`If-Match` / `If-Unmodified-Since`).
+//!
+//! The [`IfMatch`] extractor parses the request's preconditions; the
+//! handler then checks them against the resource's current `ETag` before
+//! applying an update, turning a lost-update race into `412 Precondition
+//! Failed`:
+//!
+//! ```rust,ignore
+//! #[action]
+//! async fn put_doc(condition: IfMatch, Json(doc): Json<Doc>) -> Result<Response, EdgeError> {
+//!     let current = load_doc().await?;
+//!     condition.check(current.as_ref().map(|doc| doc.etag.as_str()))?;
+//!     save_doc(doc).await
+//! }
+//! ```
+//!
+//! Evaluation follows RFC 9110 section 13.2.2: `If-Match` uses strong
+//! comparison, so weak (`W/`) tags never match, and `If-Unmodified-Since`
+//! is only consulted when `If-Match` is absent. An unparseable
+//! `If-Unmodified-Since` date is ignored.
+
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+
+use crate::context::RequestContext;
+use crate::error::EdgeError;
+use crate::extractor::FromRequest;
+use crate::http::HeaderMap;
+use crate::http::header::{IF_MATCH, IF_UNMODIFIED_SINCE};
+
+/// One entity tag from an `If-Match` list.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EntityTag {
+    /// The opaque tag, without quotes.
+    pub tag: String,
+    /// Whether the tag carried the `W/` weak prefix.
+    pub weak: bool,
+}
+
+impl EntityTag {
+    /// Parse `"tag"` or `W/"tag"`. Returns `None` for anything else.
+    #[must_use]
+    #[inline]
+    pub fn parse(value: &str) -> Option<Self> {
+        let trimmed = value.trim();
+        let (weak, quoted) = trimmed
+            .strip_prefix("W/")
+            .map_or((false, trimmed), |rest| (true, rest));
+        let tag = quoted.strip_prefix('"')?.strip_suffix('"')?;
+        if tag.contains('"') {
+            return None;
+        }
+        Some(Self {
+            tag: tag.to_owned(),
+            weak,
+        })
+    }
+
+    /// Strong comparison against a current `ETag`, which may be given
+    /// quoted (`"v1"`) or bare (`v1`). Weak tags never match.
+    #[must_use]
+    #[inline]
+    pub fn strong_eq(&self, current: &str) -> bool {
+        match Self::parse(current) {
+            Some(other) => !self.weak && !other.weak && self.tag == other.tag,
+            None => !self.weak && self.tag == current,
+        }
+    }
+}
+
+/// Parsed `If-Match` header value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum MatchCondition {
+    /// `If-Match: *` — the resource must exist.
+    Any,
+    /// A list of entity tags, at least one of which must match.
+    Tags(Vec<EntityTag>),
+}
+
+/// Preconditions of a conditional write, extracted from `If-Match` and
+/// `If-Unmodified-Since`.
+///
+/// Extraction fails with `400 Bad Request` only for a malformed
+/// `If-Match`. A request without either header extracts successfully and
+/// every check proceeds.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IfMatch {
+    condition: Option<MatchCondition>,
+    unmodified_since: Option<SystemTime>,
+}
+
+impl IfMatch {
+    /// Check `If-Match` against the resource's current `ETag` (`None` when
+    /// the resource does not exist). `If-Unmodified-Since` is not evaluated
+    /// here; use [`Self::check_with_last_modified`] for that.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EdgeError::PreconditionFailed`] when `If-Match` is present
+    /// and does not match.
+    #[inline]
+    pub fn check(&self, current_etag: Option<&str>) -> Result<(), EdgeError> {
+        let Some(condition) = &self.condition else {
+            return Ok(());
+        };
+        let matched = match (condition, current_etag) {
+            (_, None) => false,
+            (MatchCondition::Any, Some(_)) => true,
+            (MatchCondition::Tags(tags), Some(current)) => {
+                tags.iter().any(|tag| tag.strong_eq(current))
+            }
+        };
+        if matched {
+            Ok(())
+        } else {
+            Err(EdgeError::precondition_failed(
+                "If-Match does not match the current ETag",
+            ))
+        }
+    }
+
+    /// Like [`Self::check`], additionally evaluating `If-Unmodified-Since`
+    /// against `last_modified` when the request has no `If-Match`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EdgeError::PreconditionFailed`] when either precondition
+    /// does not hold.
+    #[inline]
+    pub fn check_with_last_modified(
+        &self,
+        current_etag: Option<&str>,
+        last_modified: Option<SystemTime>,
+    ) -> Result<(), EdgeError> {
+        if self.condition.is_some() {
+            return self.check(current_etag);
+        }
+        match (self.unmodified_since, last_modified) {
+            (Some(since), Some(modified)) if !unmodified_since_holds(since, modified) => Err(
+                EdgeError::precondition_failed("resource modified since If-Unmodified-Since"),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// The parsed `If-Match` condition, if the header was present.
+    #[must_use]
+    #[inline]
+    pub fn condition(&self) -> Option<&MatchCondition> {
+        self.condition.as_ref()
+    }
+
+    /// Parse preconditions from request headers.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EdgeError::BadRequest`] for a malformed `If-Match`.
+    #[inline]
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self, EdgeError> {
+        let condition = headers
+            .get_all(IF_MATCH)
+            .iter()
+            .map(|value| {
+                value
+                    .to_str()
+                    .map_err(|_err| EdgeError::bad_request("If-Match is not valid ASCII"))
+            })
+            .try_fold(None, |acc, value| merge_if_match(acc, value?))?;
+        let unmodified_since = headers
+            .get(IF_UNMODIFIED_SINCE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| httpdate::parse_http_date(value).ok());
+        Ok(Self {
+            condition,
+            unmodified_since,
+        })
+    }
+
+    /// The parsed `If-Unmodified-Since` date, if present and valid.
+    #[must_use]
+    #[inline]
+    pub fn unmodified_since(&self) -> Option<SystemTime> {
+        self.unmodified_since
+    }
+}
+
+#[async_trait(?Send)]
+impl FromRequest for IfMatch {
+    #[inline]
+    async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+        Self::from_headers(ctx.request().headers())
+    }
+}
+
+/// Fold one `If-Match` field line into the condition parsed so far.
+fn merge_if_match(
+    acc: Option<MatchCondition>,
+    value: &str,
+) -> Result<Option<MatchCondition>, EdgeError> {
+    if value.trim() == "*" {
+        return Ok(Some(MatchCondition::Any));
+    }
+    let mut tags = match acc {
+        Some(MatchCondition::Any) => return Ok(Some(MatchCondition::Any)),
+        Some(MatchCondition::Tags(tags)) => tags,
+        None => Vec::new(),
+    };
+    for item in value.split(',').filter(|item| !item.trim().is_empty()) {
+        let tag = EntityTag::parse(item)
+            .ok_or_else(|| EdgeError::bad_request(format!("invalid entity tag: {item}")))?;
+        tags.push(tag);
+    }
+    Ok(Some(MatchCondition::Tags(tags)))
+}
+
+/// HTTP dates have one-second resolution, so compare whole seconds.
+fn unmodified_since_holds(since: SystemTime, last_modified: SystemTime) -> bool {
+    let seconds = |time: SystemTime| {
+        time.duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs())
+    };
+    seconds(last_modified) <= seconds(since)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::Body;
+    use crate::http::{Method, StatusCode, request_builder};
+    use crate::params::PathParams;
+    use futures::executor::block_on;
+    use std::time::Duration;
+
+    fn extract(headers: &[(&str, &str)]) -> Result<IfMatch, EdgeError> {
+        block_on(IfMatch::from_request(&request_context(headers)))
+    }
+
+    fn request_context(headers: &[(&str, &str)]) -> RequestContext {
+        let mut builder = request_builder().method(Method::PUT).uri("/doc");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        let request = builder.body(Body::empty()).expect("request");
+        RequestContext::new(request, PathParams::default())
+    }
+
+    #[test]
+    fn absent_headers_always_proceed() {
+        let condition = extract(&[]).expect("extract");
+        assert_eq!(condition.condition(), None);
+        condition.check(Some("\"v1\"")).expect("proceed");
+        condition.check(None).expect("proceed");
+    }
+
+    #[test]
+    fn malformed_if_match_is_bad_request() {
+        let err = extract(&[("if-match", "v1")]).expect_err("unquoted tag");
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn matching_etag_proceeds() {
+        let condition = extract(&[("if-match", "\"v0\", \"v1\"")]).expect("extract");
+        condition.check(Some("\"v1\"")).expect("proceed");
+        condition.check(Some("v1")).expect("proceed");
+    }
+
+    #[test]
+    fn mismatched_etag_is_precondition_failed() {
+        let condition = extract(&[("if-match", "\"v1\"")]).expect("extract");
+        let err = condition.check(Some("\"v2\"")).expect_err("mismatch");
+        assert_eq!(err.status(), StatusCode::PRECONDITION_FAILED);
+        condition.check(None).expect_err("precondition failed");
+    }
+
+    #[test]
+    fn unmodified_since_is_evaluated_without_if_match() {
+        let since = SystemTime::UNIX_EPOCH + Duration::from_secs(784_111_777);
+        let condition =
+            extract(&[("if-unmodified-since", "Sun, 06 Nov 1994 08:49:37 GMT")]).expect("extract");
+        assert_eq!(condition.unmodified_since(), Some(since));
+
+        condition
+            .check_with_last_modified(None, Some(since))
+            .expect("proceed");
+        let later = since + Duration::from_secs(1);
+        let err = condition
+            .check_with_last_modified(None, Some(later))
+            .expect_err("modified");
+        assert_eq!(err.status(), StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[test]
+    fn if_match_takes_precedence_over_unmodified_since() {
+        let condition = extract(&[
+            ("if-match", "\"v1\""),
+            ("if-unmodified-since", "Sun, 06 Nov 1994 08:49:37 GMT"),
+        ])
+        .expect("extract");
+        condition
+            .check_with_last_modified(Some("\"v1\""), Some(SystemTime::now()))
+            .expect("proceed");
+    }
+
+    #[test]
+    fn weak_tags_never_match() {
+        let condition = extract(&[("if-match", "W/\"v1\"")]).expect("extract");
+        condition
+            .check(Some("\"v1\""))
+            .expect_err("precondition failed");
+        let strong = extract(&[("if-match", "\"v1\"")]).expect("extract");
+        strong
+            .check(Some("W/\"v1\""))
+            .expect_err("precondition failed");
+    }
+
+    #[test]
+    fn wildcard_matches_any_existing_resource() {
+        let condition = extract(&[("if-match", "*")]).expect("extract");
+        assert_eq!(condition.condition(), Some(&MatchCondition::Any));
+        condition.check(Some("\"anything\"")).expect("proceed");
+        let err = condition.check(None).expect_err("missing resource");
+        assert_eq!(err.status(), StatusCode::PRECONDITION_FAILED);
+    }
+}
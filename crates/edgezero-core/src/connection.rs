@@ -0,0 +1,49 @@
+//! Connection and TLS metadata for the current request.
+//!
+//! Adapters record what their platform reports about the client connection
+//! as a [`ConnectionInfo`] in the request extensions; handlers read it
+//! through [`RequestContext::connection_info`]. Every field is optional
+//! because no platform reports all of them:
+//!
+//! | Field | Axum | Fastly | Cloudflare | Spin |
+//! |-------|------|--------|------------|------|
+//! | `peer_addr` | yes | IP only | IP only | yes |
+//! | `local_addr` | yes | IP only | — | — |
+//! | `tls_version` | — | yes | yes | — |
+//! | `alpn` | — | — | — | — |
+//! | `client_cert_subject` | — | — | yes | — |
+//!
+//! "IP only" addresses carry port `0`: the platform exposes the address but
+//! not the port. The [`ClientIp`] extractor reads the IP of `peer_addr`.
+//!
+//! [`ClientIp`]: crate::extractor::ClientIp
+//!
+//! [`RequestContext::connection_info`]: crate::context::RequestContext::connection_info
+
+use std::net::SocketAddr;
+
+/// What the adapter knows about the connection a request arrived on.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ConnectionInfo {
+    /// Protocol negotiated through TLS ALPN, such as `h2` or `http/1.1`.
+    pub alpn: Option<String>,
+    /// Subject distinguished name of the client certificate, when the client
+    /// presented one.
+    pub client_cert_subject: Option<String>,
+    /// Address the connection was accepted on.
+    pub local_addr: Option<SocketAddr>,
+    /// Address of the connecting client.
+    pub peer_addr: Option<SocketAddr>,
+    /// Negotiated TLS protocol version, such as `TLSv1.3`. `None` for
+    /// plain-text connections.
+    pub tls_version: Option<String>,
+}
+
+impl ConnectionInfo {
+    /// Whether the connection was made over TLS.
+    #[must_use]
+    #[inline]
+    pub fn is_tls(&self) -> bool {
+        self.tls_version.is_some()
+    }
+}
@@ -1,26 +1,143 @@
+use std::cell::{OnceCell, RefCell};
+use std::mem;
+use std::rc::Rc;
+use std::task::Poll;
+
 use crate::body::Body;
+use crate::body_limit::BodyLimit;
+use crate::body_spool::{self, BodyBuffering, BodySpoolHandle, Buffered, SpilledBody};
+use crate::clock::{Clock, SharedClock};
+use crate::connection::ConnectionInfo;
+use crate::deadline::Deadline;
 use crate::error::EdgeError;
-use crate::http::Request;
+use crate::extractor::{JsonLimits, QueryLimit};
+use crate::forwarded::append_forwarded_for;
+use crate::http::header::{CONTENT_LENGTH, HOST, TRANSFER_ENCODING};
+use crate::http::{Method, Request, Response, Uri};
 use crate::params::PathParams;
-use crate::proxy::ProxyHandle;
+use crate::proxy::{ProxyHandle, ProxyInterceptors, ProxyRequest, strip_hop_by_hop_headers};
+use crate::request_id::{RequestIdGenerator, SharedRequestIdGenerator, gen_request_id};
+use crate::response::ResponseParts;
+use crate::router::{MatchedRoute, MountPrefix, strip_mount_prefix};
 use crate::store_registry::{
     BoundConfigStore, BoundKvStore, BoundSecretStore, ConfigRegistry, ConfigStoreBinding,
     KvRegistry, SecretRegistry, StoreRegistry,
 };
+use bytes::Bytes;
+use futures::future::ready;
+use futures_util::stream::{self, LocalBoxStream, StreamExt as _};
 use serde::de::DeserializeOwned;
+use web_time::SystemTime;
+
+type ChunkStream = LocalBoxStream<'static, Result<Bytes, anyhow::Error>>;
+
+/// The adapter serving the request, such as `"fastly"`, `"cloudflare"`,
+/// `"spin"` or `"axum"`. Each adapter inserts it into the request
+/// extensions; read it with [`RequestContext::adapter_name`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AdapterName(pub &'static str);
+
+/// A streamed request body the extractors may buffer through a shared
+/// [`RequestContext`]; see [`RequestContext::buffer_body`].
+///
+/// While it is attached, the request's body is a stream that reads from
+/// `source`, so a handler that never extracts the body still streams it.
+/// Buffering takes the stream out of `source` and leaves a replay of the
+/// buffered bytes in its place. Any `&mut` access to the request first
+/// settles the body back into the request (see
+/// [`RequestContext::settle_body`]), so the source can never go stale.
+#[derive(Default)]
+struct LazyBody {
+    buffered: OnceCell<Buffered>,
+    source: Option<Rc<RefCell<Option<ChunkStream>>>>,
+}
 
 /// Request context exposed to handlers and middleware.
 pub struct RequestContext {
+    lazy_body: LazyBody,
     path_params: PathParams,
     request: Request,
 }
 
 impl RequestContext {
+    /// The adapter serving the request, for platform-specific decisions
+    /// without `cfg` gates. `None` when the router is driven directly, as
+    /// in tests.
+    #[must_use]
+    #[inline]
+    pub fn adapter_name(&self) -> Option<&str> {
+        self.request
+            .extensions()
+            .get::<AdapterName>()
+            .map(|name| name.0)
+    }
+
     #[inline]
     pub fn body(&self) -> &Body {
         self.request.body()
     }
 
+    /// Read a streamed body to its end so [`Self::json`], [`Self::form`] and
+    /// the `Cbor` extractor can parse it. The body extractors call this
+    /// themselves; a handler that calls `ctx.json()` directly on a streamed
+    /// body awaits it first.
+    ///
+    /// Nothing is read until this is called, so routes that never extract
+    /// the body keep streaming it. The body is capped by the request's
+    /// [`BodyLimit`], or [`BodyLimit::default`] when none is installed, and
+    /// past the [`BodyBuffering::Spill`] threshold it goes to the adapter's
+    /// spool instead; see [`crate::body_spool`]. A buffered body is kept, so
+    /// later extractors and a handler that takes the body still see it.
+    ///
+    /// # Errors
+    /// Returns [`EdgeError::payload_too_large`] past the body limit or, with
+    /// no spool installed, the spill threshold; [`EdgeError::internal`] if
+    /// the body stream fails or is already being read.
+    #[inline]
+    pub async fn buffer_body(&self) -> Result<(), EdgeError> {
+        if self.lazy_body.buffered.get().is_some() {
+            return Ok(());
+        }
+        let extensions = self.request.extensions();
+        let buffering = extensions
+            .get::<BodyBuffering>()
+            .copied()
+            .unwrap_or_default();
+        let max_bytes = extensions
+            .get::<BodyLimit>()
+            .copied()
+            .unwrap_or_default()
+            .max_bytes();
+        let body = match (&self.lazy_body.source, self.request.body()) {
+            (Some(source), _) => {
+                let chunks = source.borrow_mut().take().ok_or_else(|| {
+                    EdgeError::internal(anyhow::anyhow!("the request body is already being read"))
+                })?;
+                Body::Stream(chunks)
+            }
+            // A buffered body only needs copying when it has to be spilled.
+            (None, Body::Once(bytes)) => match buffering {
+                BodyBuffering::Spill { threshold } if bytes.len() > threshold => {
+                    Body::Once(bytes.clone())
+                }
+                BodyBuffering::Spill { .. } | BodyBuffering::Memory => return Ok(()),
+            },
+            // Detached by `&mut` access in the same middleware; the stream can
+            // only be read once the context is handed on.
+            (None, Body::Stream(_) | Body::StreamWithTrailers(..)) => return Ok(()),
+        };
+        let spool = extensions.get::<BodySpoolHandle>().cloned();
+        let buffered = body_spool::buffer(body, buffering, max_bytes, spool).await?;
+        if let (Some(source), Buffered::Memory(bytes)) = (&self.lazy_body.source, &buffered) {
+            let replay = stream::once(ready(Ok(bytes.clone()))).boxed_local();
+            *source.borrow_mut() = Some(replay);
+        }
+        // Only a concurrent `buffer_body` could have filled the cell, and it
+        // found the source empty and failed above.
+        let _previous = self.lazy_body.buffered.set(buffered);
+        Ok(())
+    }
+
     /// Resolve the [`BoundConfigStore`] for `id`. Strict lookup: when a
     /// [`ConfigRegistry`] is wired, an unregistered id yields `None`. When
     /// no registry is wired this returns `None` — adapter dispatchers
@@ -69,6 +186,54 @@ impl RequestContext {
             .and_then(|registry| registry.default_ref())
     }
 
+    /// Connection and TLS metadata recorded by the adapter, if it reports
+    /// any.
+    #[must_use]
+    #[inline]
+    pub fn connection_info(&self) -> Option<&ConnectionInfo> {
+        self.request.extensions().get::<ConnectionInfo>()
+    }
+
+    /// Deadline established by
+    /// [`DeadlineMiddleware`](crate::deadline::DeadlineMiddleware), if it ran.
+    #[must_use]
+    #[inline]
+    pub fn deadline(&self) -> Option<Deadline> {
+        self.extension::<Deadline>()
+    }
+
+    /// Attach a streamed request body as a [`LazyBody`], so the extractors
+    /// can buffer it through `&self`. Buffered bodies, and bodies that are
+    /// already attached, are left alone.
+    pub(crate) fn defer_body(&mut self) {
+        if self.lazy_body.source.is_some() {
+            return;
+        }
+        let (raw, trailers) = match mem::take(self.request.body_mut()) {
+            Body::Stream(chunks) => (chunks, None),
+            Body::StreamWithTrailers(chunks, trailers) => (chunks, Some(trailers)),
+            once @ Body::Once(_) => {
+                *self.request.body_mut() = once;
+                return;
+            }
+        };
+        let source = Rc::new(RefCell::new(Some(raw)));
+        let reader = Rc::clone(&source);
+        let shared = stream::poll_fn(move |cx| match reader.borrow_mut().as_mut() {
+            Some(chunks) => chunks.poll_next_unpin(cx),
+            None => Poll::Ready(None),
+        })
+        .boxed_local();
+        *self.request.body_mut() = match trailers {
+            Some(future) => Body::StreamWithTrailers(shared, future),
+            None => Body::Stream(shared),
+        };
+        self.lazy_body = LazyBody {
+            buffered: OnceCell::new(),
+            source: Some(source),
+        };
+    }
+
     /// Clone a request extension of type `T`, if present. Used by the
     /// introspection extractors (`ManifestJson` / `RouteTable`) to read the
     /// payload the router injected for their route.
@@ -81,38 +246,119 @@ impl RequestContext {
         self.request.extensions().get::<T>().cloned()
     }
 
+    /// The `Content-Type` header is not checked.
+    ///
     /// # Errors
-    /// Returns [`EdgeError::bad_request`] if the body cannot be deserialized as form-urlencoded data into `T`, or the body is streaming.
+    /// Returns [`EdgeError::missing_body`] if the request has no body (see [`Self::has_body`]), or [`EdgeError::bad_request`] if the body cannot be deserialized as form-urlencoded data into `T` or the body is streaming.
     #[inline]
     pub fn form<T>(&self) -> Result<T, EdgeError>
     where
         T: DeserializeOwned,
     {
+        if !self.has_body() {
+            return Err(EdgeError::missing_body("a form body is required"));
+        }
+        self.read_buffered(
+            |body| match body {
+                Body::Once(bytes) => serde_urlencoded::from_bytes(bytes.as_ref())
+                    .map_err(|err| EdgeError::bad_request(format!("invalid form payload: {err}"))),
+                Body::Stream(_) | Body::StreamWithTrailers(..) => Err(EdgeError::bad_request(
+                    "streaming bodies are not supported for form extraction",
+                )),
+            },
+            |spilled| body_spool::form_from_reader(spilled.reader()?),
+        )
+    }
+
+    /// A new request id from the [`RequestIdGenerator`] registered with
+    /// [`RouterBuilder::with_request_id_generator`], or from
+    /// [`gen_request_id`] when none is.
+    ///
+    /// [`RouterBuilder::with_request_id_generator`]: crate::router::RouterBuilder::with_request_id_generator
+    #[must_use]
+    #[inline]
+    pub fn generate_request_id(&self) -> String {
+        self.request
+            .extensions()
+            .get::<SharedRequestIdGenerator>()
+            .map_or_else(gen_request_id, RequestIdGenerator::generate)
+    }
+
+    /// Whether the request carries a body, even an empty one.
+    ///
+    /// Follows HTTP framing: a `Transfer-Encoding` header or a non-zero
+    /// `Content-Length` means a body. `Content-Length: 0` is an explicitly
+    /// empty body, except on methods with no defined body semantics (`GET`,
+    /// `HEAD`, `DELETE`, `OPTIONS`, `TRACE`, `CONNECT`), where clients send
+    /// it routinely and it means no body. Without framing headers, a
+    /// non-empty buffered body counts, and a stream counts on `POST`, `PUT`,
+    /// and `PATCH` (HTTP/2 does not require `Content-Length`).
+    #[must_use]
+    #[inline]
+    pub fn has_body(&self) -> bool {
+        let headers = self.request.headers();
+        if headers.contains_key(TRANSFER_ENCODING) || self.spilled_body().is_some() {
+            return true;
+        }
+        let method = self.request.method();
+        let content_length = headers
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|raw| raw.trim().parse::<u64>().ok());
+        if let Some(length) = content_length {
+            return length > 0 || !ignores_empty_body(method);
+        }
         match self.request.body() {
-            Body::Once(bytes) => serde_urlencoded::from_bytes(bytes.as_ref())
-                .map_err(|err| EdgeError::bad_request(format!("invalid form payload: {err}"))),
-            Body::Stream(_) => Err(EdgeError::bad_request(
-                "streaming bodies are not supported for form extraction",
-            )),
+            Body::Once(bytes) => !bytes.is_empty(),
+            Body::Stream(_) | Body::StreamWithTrailers(..) => {
+                matches!(*method, Method::PATCH | Method::POST | Method::PUT)
+            }
         }
     }
 
     #[inline]
-    pub fn into_request(self) -> Request {
+    pub fn into_request(mut self) -> Request {
+        self.settle_body();
         self.request
     }
 
+    /// The `Content-Type` header is not checked.
+    ///
     /// # Errors
-    /// Returns [`EdgeError::bad_request`] if the body is not valid JSON for `T`.
+    /// Returns [`EdgeError::missing_body`] if the request has no body (see [`Self::has_body`]), or [`EdgeError::bad_request`] if the body is not valid JSON for `T` or exceeds registered [`JsonLimits`].
     #[inline]
     pub fn json<T>(&self) -> Result<T, EdgeError>
     where
         T: DeserializeOwned,
     {
-        self.request
-            .body()
-            .to_json()
-            .map_err(|err| EdgeError::bad_request(format!("invalid JSON payload: {err}")))
+        if !self.has_body() {
+            return Err(EdgeError::missing_body("a JSON body is required"));
+        }
+        // Limits registered via `with_json_limits` are checked before parsing.
+        let limits = self.request.extensions().get::<JsonLimits>();
+        self.read_buffered(
+            |body| {
+                if let Some(checked) = limits
+                    && let Some(bytes) = body.as_bytes()
+                {
+                    checked.check(bytes)?;
+                }
+                body.to_json()
+                    .map_err(|err| EdgeError::bad_request(format!("invalid JSON payload: {err}")))
+            },
+            |spilled| {
+                if let Some(checked) = limits {
+                    checked.check_reader(spilled.len(), spilled.reader()?)?;
+                }
+                serde_json::from_reader(spilled.reader()?).map_err(|err| {
+                    if err.is_io() {
+                        EdgeError::internal(err)
+                    } else {
+                        EdgeError::bad_request(format!("invalid JSON payload: {err}"))
+                    }
+                })
+            },
+        )
     }
 
     /// Resolve the [`BoundKvStore`] for `id`. Strict lookup: when a
@@ -139,12 +385,41 @@ impl RequestContext {
             .and_then(StoreRegistry::default)
     }
 
+    /// Template of the route that matched this request (e.g.
+    /// `/users/{id}`), or `None` outside router dispatch. Low-cardinality,
+    /// so suitable as a metrics or log label where the raw path is not.
+    #[must_use]
+    #[inline]
+    pub fn matched_route(&self) -> Option<&str> {
+        self.request
+            .extensions()
+            .get::<MatchedRoute>()
+            .map(MatchedRoute::as_str)
+    }
+
     #[inline]
     pub fn new(request: Request, params: PathParams) -> Self {
-        Self {
+        let mut ctx = Self {
+            lazy_body: LazyBody::default(),
             path_params: params,
             request,
-        }
+        };
+        ctx.defer_body();
+        ctx
+    }
+
+    /// The current time from the router's [`Clock`], or the system clock
+    /// when none is registered. Safe on every target, unlike
+    /// `std::time::SystemTime::now`; see [`crate::clock`].
+    ///
+    /// [`Clock`]: crate::clock::Clock
+    #[must_use]
+    #[inline]
+    pub fn now(&self) -> SystemTime {
+        self.request
+            .extensions()
+            .get::<SharedClock>()
+            .map_or_else(SystemTime::now, Clock::now)
     }
 
     /// # Errors
@@ -164,23 +439,93 @@ impl RequestContext {
         &self.path_params
     }
 
+    /// The [`ProxyHandle`] the adapter installed for outbound requests, if
+    /// any, with the router's app-wide interceptors run after its own.
+    /// Handlers can take a [`Proxy`](crate::proxy::Proxy) instead.
     #[inline]
     pub fn proxy_handle(&self) -> Option<ProxyHandle> {
-        self.request.extensions().get::<ProxyHandle>().cloned()
+        let extensions = self.request.extensions();
+        let handle = extensions.get::<ProxyHandle>()?.clone();
+        Some(match extensions.get::<ProxyInterceptors>() {
+            Some(interceptors) => handle.layered(interceptors),
+            None => handle,
+        })
+    }
+
+    /// Forward this request to `uri` through the context's [`ProxyHandle`]
+    /// and return the upstream's response, its body still streaming.
+    ///
+    /// Hop-by-hop headers are dropped in both directions, and the
+    /// connecting peer's address is appended to `X-Forwarded-For`. The
+    /// client's `Host` is dropped too, so the upstream gets the one for
+    /// `uri`. The request keeps its method, other headers, body and
+    /// [`Deadline`].
+    ///
+    /// # Errors
+    /// Returns [`EdgeError::internal`] when the adapter registered no
+    /// [`ProxyHandle`], and whatever error the proxy client returns.
+    #[inline]
+    pub async fn proxy_to(self, uri: Uri) -> Result<Response, EdgeError> {
+        let handle = self
+            .proxy_handle()
+            .ok_or_else(|| EdgeError::internal(anyhow::anyhow!("no proxy client is configured")))?;
+        let peer = self
+            .connection_info()
+            .and_then(|info| info.peer_addr)
+            .map(|addr| addr.ip());
+        let mut request = ProxyRequest::from_request(self.into_request(), uri);
+        let headers = request.headers_mut();
+        strip_hop_by_hop_headers(headers);
+        // The proxy client derives `Host` from `uri`.
+        headers.remove(HOST);
+        if let Some(ip) = peer {
+            append_forwarded_for(headers, ip);
+        }
+        let mut response = handle.forward(request).await?;
+        strip_hop_by_hop_headers(response.headers_mut());
+        Ok(response)
     }
 
     /// # Errors
-    /// Returns [`EdgeError::bad_request`] if the query string cannot be deserialized into `T`.
+    /// Returns [`EdgeError::uri_too_long`] if the query string exceeds the registered (or default) [`QueryLimit`], or [`EdgeError::bad_request`] if it cannot be deserialized into `T`.
     #[inline]
     pub fn query<T>(&self) -> Result<T, EdgeError>
     where
         T: DeserializeOwned,
     {
         let query = self.request.uri().query().unwrap_or("");
+        self.request
+            .extensions()
+            .get::<QueryLimit>()
+            .copied()
+            .unwrap_or_default()
+            .check(query)?;
         serde_urlencoded::from_str(query)
             .map_err(|err| EdgeError::bad_request(format!("invalid query string: {err}")))
     }
 
+    /// Read the body the extractors see: `from_body` gets the bytes
+    /// [`Self::buffer_body`] read, or the request body when it was never
+    /// buffered, and `from_spilled` gets a body written to the spool, to
+    /// deserialize from its reader.
+    pub(crate) fn read_buffered<T, FromBody, FromSpilled>(
+        &self,
+        from_body: FromBody,
+        from_spilled: FromSpilled,
+    ) -> Result<T, EdgeError>
+    where
+        FromBody: FnOnce(&Body) -> Result<T, EdgeError>,
+        FromSpilled: FnOnce(&SpilledBody) -> Result<T, EdgeError>,
+    {
+        if let Some(Buffered::Memory(bytes)) = self.lazy_body.buffered.get() {
+            return from_body(&Body::Once(bytes.clone()));
+        }
+        match self.spilled_body() {
+            Some(spilled) => from_spilled(spilled),
+            None => from_body(self.request.body()),
+        }
+    }
+
     #[inline]
     pub fn request(&self) -> &Request {
         &self.request
@@ -188,9 +533,38 @@ impl RequestContext {
 
     #[inline]
     pub fn request_mut(&mut self) -> &mut Request {
+        self.settle_body();
         &mut self.request
     }
 
+    /// The [`ResponseParts`] the router applies to this request's response.
+    ///
+    /// Outside a router, or in middleware before the handler is reached,
+    /// this is a fresh value whose changes are discarded.
+    #[must_use]
+    #[inline]
+    pub fn response_parts(&self) -> ResponseParts {
+        self.extension::<ResponseParts>().unwrap_or_default()
+    }
+
+    /// The request path relative to the [`crate::router::RouterBuilder::mount`]
+    /// point it was dispatched through, e.g. `/users/7` for `/api/v1/users/7`
+    /// under `/api/v1`. The mount point itself is `/`. Outside a mounted
+    /// router this is the full request path.
+    #[must_use]
+    #[inline]
+    pub fn route_path(&self) -> &str {
+        let path = self.request.uri().path();
+        let Some(prefix) = self.request.extensions().get::<MountPrefix>() else {
+            return path;
+        };
+        match strip_mount_prefix(path, prefix.as_str()) {
+            Some("") => "/",
+            Some(rest) => rest,
+            None => path,
+        }
+    }
+
     /// Resolve the [`BoundSecretStore`] for `id`. Strict lookup: when a
     /// [`SecretRegistry`] is wired, an unregistered id yields `None`.
     /// When no registry is wired this returns `None` — adapter
@@ -214,12 +588,96 @@ impl RequestContext {
             .get::<SecretRegistry>()
             .and_then(StoreRegistry::default)
     }
+
+    /// Put the body back into the request before it is handed out mutably: a
+    /// buffered body as [`Body::Once`], a spilled one as an empty body with
+    /// its [`SpilledBody`] in the extensions, and an unread stream as itself.
+    /// [`Next::run`](crate::middleware::Next::run) attaches it again.
+    fn settle_body(&mut self) {
+        let lazy_body = mem::take(&mut self.lazy_body);
+        match lazy_body.buffered.into_inner() {
+            Some(Buffered::Memory(bytes)) => *self.request.body_mut() = Body::Once(bytes),
+            Some(Buffered::Spilled(spilled)) => {
+                *self.request.body_mut() = Body::empty();
+                self.request.extensions_mut().insert(spilled);
+            }
+            None => {
+                let Some(chunks) = lazy_body
+                    .source
+                    .and_then(|source| source.borrow_mut().take())
+                else {
+                    return;
+                };
+                let body = match mem::take(self.request.body_mut()) {
+                    Body::StreamWithTrailers(_, trailers) => {
+                        Body::StreamWithTrailers(chunks, trailers)
+                    }
+                    Body::Once(_) | Body::Stream(_) => Body::Stream(chunks),
+                };
+                *self.request.body_mut() = body;
+            }
+        }
+    }
+
+    /// The body the extractors spilled to the adapter's spool, if they did;
+    /// see [`crate::body_spool`].
+    #[must_use]
+    #[inline]
+    pub fn spilled_body(&self) -> Option<&SpilledBody> {
+        match self.lazy_body.buffered.get() {
+            Some(Buffered::Spilled(spilled)) => Some(spilled),
+            Some(Buffered::Memory(_)) | None => self.request.extensions().get::<SpilledBody>(),
+        }
+    }
+
+    /// Take ownership of the request body, leaving [`Body::empty`] in its
+    /// place, so a later `take_body` or body extractor sees no body rather
+    /// than reading the same stream twice.
+    #[must_use]
+    #[inline]
+    pub fn take_body(&mut self) -> Body {
+        self.settle_body();
+        mem::take(self.request.body_mut())
+    }
+
+    /// A copy of this context with an empty body, for code that needs the
+    /// request after the original has been handed to the handler.
+    pub(crate) fn without_body(&self) -> Self {
+        let mut request = Request::new(Body::empty());
+        *request.method_mut() = self.request.method().clone();
+        *request.uri_mut() = self.request.uri().clone();
+        *request.version_mut() = self.request.version();
+        *request.headers_mut() = self.request.headers().clone();
+        *request.extensions_mut() = self.request.extensions().clone();
+        Self {
+            lazy_body: LazyBody::default(),
+            path_params: self.path_params.clone(),
+            request,
+        }
+    }
+}
+
+/// Methods whose requests have no defined body semantics, so a
+/// `Content-Length: 0` on them means "no body" rather than "empty body".
+fn ignores_empty_body(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::CONNECT
+            | Method::DELETE
+            | Method::GET
+            | Method::HEAD
+            | Method::OPTIONS
+            | Method::TRACE
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::http::{HeaderValue, Method, StatusCode, Uri, request_builder};
+    use crate::extractor::{FromRequest as _, Json};
+    use crate::forwarded::X_FORWARDED_FOR;
+    use crate::http::header::CONTENT_TYPE;
+    use crate::http::{HeaderName, HeaderValue, Method, StatusCode, Uri, request_builder};
     use crate::params::PathParams;
     use crate::proxy::{ProxyClient, ProxyHandle, ProxyRequest, ProxyResponse};
     use async_trait::async_trait;
@@ -231,6 +689,10 @@ mod tests {
 
     struct DummyClient;
 
+    /// Answers with the headers and body it received, streamed, plus
+    /// hop-by-hop headers of its own.
+    struct UpstreamClient;
+
     #[derive(Debug, PartialEq, Deserialize, Serialize)]
     struct PathData {
         id: String,
@@ -243,6 +705,24 @@ mod tests {
         }
     }
 
+    #[async_trait(?Send)]
+    impl ProxyClient for UpstreamClient {
+        async fn send(&self, request: ProxyRequest) -> Result<ProxyResponse, EdgeError> {
+            let (method, uri, headers, body, _) = request.into_parts();
+            assert_eq!(method, Method::PUT);
+            assert_eq!(uri, Uri::from_static("https://origin.example/items/7"));
+            let mut response = ProxyResponse::new(StatusCode::ACCEPTED, body);
+            for (name, value) in &headers {
+                let echoed = HeaderName::try_from(format!("x-sent-{name}")).expect("name");
+                response.headers_mut().append(echoed, value.clone());
+            }
+            response
+                .headers_mut()
+                .insert("keep-alive", HeaderValue::from_static("timeout=5"));
+            Ok(response)
+        }
+    }
+
     fn ctx(path: &str, body: Body, params: PathParams) -> RequestContext {
         let request = request_builder()
             .method(Method::GET)
@@ -252,6 +732,16 @@ mod tests {
         RequestContext::new(request, params)
     }
 
+    fn ctx_with_content_type(content_type: &str, body: &'static str) -> RequestContext {
+        let request = request_builder()
+            .method(Method::POST)
+            .uri("/submit")
+            .header(CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+            .expect("request");
+        RequestContext::new(request, PathParams::default())
+    }
+
     fn params(map: &[(&str, &str)]) -> PathParams {
         let inner = map
             .iter()
@@ -286,8 +776,12 @@ mod tests {
     #[test]
     fn form_streaming_body_not_supported() {
         let stream = stream::iter(vec![Ok::<Bytes, anyhow::Error>(Bytes::from("name=demo"))]);
-        let body = Body::from_stream(stream);
-        let ctx = ctx("/submit", body, PathParams::default());
+        let request = request_builder()
+            .method(Method::POST)
+            .uri("/submit")
+            .body(Body::from_stream(stream))
+            .expect("request");
+        let ctx = RequestContext::new(request, PathParams::default());
         let err = ctx.form::<serde_json::Value>().expect_err("expected error");
         assert_eq!(err.status(), StatusCode::BAD_REQUEST);
         assert!(
@@ -296,6 +790,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn form_accepts_content_type_with_charset() {
+        let ctx = ctx_with_content_type(
+            "Application/X-WWW-Form-Urlencoded; charset=utf-8",
+            "name=demo",
+        );
+        let parsed: serde_json::Value = ctx.form().expect("form data");
+        assert_eq!(parsed["name"], "demo");
+    }
+
     #[test]
     fn form_value_deserialises_successfully() {
         let body = Body::from("name=demo");
@@ -321,6 +825,100 @@ mod tests {
         assert!(err.message().contains("invalid form payload"));
     }
 
+    #[test]
+    fn has_body_follows_framing_headers_and_method() {
+        let with = |method: Method, headers: &[(&str, &str)], body: Body| {
+            let mut builder = request_builder().method(method).uri("/submit");
+            for (name, value) in headers {
+                builder = builder.header(*name, *value);
+            }
+            RequestContext::new(builder.body(body).expect("request"), PathParams::default())
+                .has_body()
+        };
+        let chunks = || {
+            Body::from_stream(stream::iter(vec![Ok::<Bytes, anyhow::Error>(Bytes::from(
+                "x",
+            ))]))
+        };
+
+        assert!(!with(Method::GET, &[], Body::empty()));
+        assert!(!with(
+            Method::GET,
+            &[("content-length", "0")],
+            Body::empty()
+        ));
+        assert!(!with(Method::GET, &[], chunks()));
+        assert!(with(
+            Method::GET,
+            &[("content-length", "2")],
+            Body::from("{}")
+        ));
+        assert!(!with(Method::POST, &[], Body::empty()));
+        assert!(with(
+            Method::POST,
+            &[("content-length", "0")],
+            Body::empty()
+        ));
+        assert!(with(Method::POST, &[], Body::from("x")));
+        assert!(with(Method::POST, &[], chunks()));
+        assert!(with(
+            Method::DELETE,
+            &[("transfer-encoding", "chunked")],
+            chunks()
+        ));
+    }
+
+    #[test]
+    fn bodyless_get_is_missing_body_for_json_and_form() {
+        let ctx = ctx("/items", Body::empty(), PathParams::default());
+        for err in [
+            ctx.json::<serde_json::Value>().expect_err("json"),
+            ctx.form::<serde_json::Value>().expect_err("form"),
+        ] {
+            assert!(matches!(err, EdgeError::MissingBody { .. }), "{err:?}");
+            assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+        }
+        let extracted = block_on(Json::<serde_json::Value>::from_request(&ctx));
+        assert!(matches!(extracted, Err(EdgeError::MissingBody { .. })));
+    }
+
+    #[test]
+    fn explicitly_empty_post_is_a_malformed_body_not_a_missing_one() {
+        let request = request_builder()
+            .method(Method::POST)
+            .uri("/items")
+            .header(CONTENT_TYPE, "application/json")
+            .header("content-length", "0")
+            .body(Body::empty())
+            .expect("request");
+        let ctx = RequestContext::new(request, PathParams::default());
+        assert!(ctx.has_body());
+        let err = ctx.json::<serde_json::Value>().expect_err("empty JSON");
+        assert!(matches!(err, EdgeError::BadRequest { .. }), "{err:?}");
+        assert!(err.message().contains("invalid JSON payload"));
+
+        // An empty form is a valid (empty) set of fields.
+        let form_request = request_builder()
+            .method(Method::PATCH)
+            .uri("/items")
+            .header("content-length", "0")
+            .body(Body::empty())
+            .expect("request");
+        let form_ctx = RequestContext::new(form_request, PathParams::default());
+        let fields: HashMap<String, String> = form_ctx.form().expect("empty form");
+        assert!(fields.is_empty());
+    }
+
+    #[test]
+    fn malformed_post_is_bad_request() {
+        let ctx = ctx_with_content_type("application/json", "{not json");
+        let err = block_on(Json::<serde_json::Value>::from_request(&ctx))
+            .err()
+            .expect("malformed JSON");
+        assert!(matches!(err, EdgeError::BadRequest { .. }), "{err:?}");
+        assert!(err.message().contains("invalid JSON payload"));
+    }
+
     #[test]
     fn invalid_json_returns_bad_request() {
         let body = Body::from(&b"not json"[..]);
@@ -360,6 +958,16 @@ mod tests {
         assert!(err.message().contains("invalid query string"));
     }
 
+    #[test]
+    fn take_body_leaves_an_empty_body_behind() {
+        let mut ctx = ctx("/upload", Body::from("payload"), PathParams::default());
+        let first = ctx.take_body();
+        assert_eq!(first.as_bytes(), Some(b"payload".as_slice()));
+        let second = ctx.take_body();
+        assert_eq!(second.as_bytes(), Some(b"".as_slice()));
+        assert!(ctx.body().as_bytes().is_some_and(<[u8]>::is_empty));
+    }
+
     #[test]
     fn json_deserialises_from_body() {
         #[derive(Debug, Deserialize, Serialize, PartialEq)]
@@ -380,6 +988,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn json_parses_the_body_whatever_the_content_type() {
+        for content_type in [
+            "application/json",
+            "application/json; charset=utf-8",
+            "application/vnd.api+json",
+            "application/problem+json; charset=UTF-8",
+            "text/plain",
+        ] {
+            let ctx = ctx_with_content_type(content_type, r#"{"name":"demo"}"#);
+            let parsed: serde_json::Value = ctx.json().expect(content_type);
+            assert_eq!(parsed["name"], "demo");
+        }
+    }
+
     // `RequestContext::kv_handle()` was removed. The
     // present/absent behaviour is now covered by `kv_store_*`
     // tests against a wired `KvRegistry`.
@@ -416,6 +1039,58 @@ mod tests {
         assert!(ctx.proxy_handle().is_some());
     }
 
+    #[test]
+    fn proxy_to_forwards_the_request_and_streams_the_response() {
+        let chunks = stream::iter([
+            Ok::<_, anyhow::Error>(Bytes::from_static(b"item ")),
+            Ok(Bytes::from_static(b"seven")),
+        ]);
+        let mut request = request_builder()
+            .method(Method::PUT)
+            .uri("/items/7")
+            .header("x-item", "7")
+            .header(HOST, "edge.example")
+            .header("connection", "keep-alive, x-session")
+            .header("x-session", "secret")
+            .header("te", "trailers")
+            .header(X_FORWARDED_FOR, "198.51.100.7")
+            .body(Body::from_stream(chunks))
+            .expect("request");
+        request
+            .extensions_mut()
+            .insert(ProxyHandle::with_client(UpstreamClient));
+        request.extensions_mut().insert(ConnectionInfo {
+            peer_addr: Some("10.0.0.1:443".parse().expect("peer")),
+            ..ConnectionInfo::default()
+        });
+        let ctx = RequestContext::new(request, PathParams::default());
+
+        let response = block_on(ctx.proxy_to(Uri::from_static("https://origin.example/items/7")))
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let headers = response.headers();
+        assert_eq!(headers["x-sent-x-item"], "7");
+        assert_eq!(headers["x-sent-x-forwarded-for"], "198.51.100.7, 10.0.0.1");
+        for dropped in ["connection", "x-session", "te", "host"] {
+            assert!(
+                !headers.contains_key(format!("x-sent-{dropped}").as_str()),
+                "{dropped}"
+            );
+        }
+        assert!(!headers.contains_key("keep-alive"));
+        assert!(response.body().is_stream());
+        let body = block_on(response.into_body().into_bytes_bounded(usize::MAX)).expect("body");
+        assert_eq!(body.as_ref(), b"item seven");
+    }
+
+    #[test]
+    fn proxy_to_requires_a_proxy_handle() {
+        let ctx = ctx("/items/7", Body::empty(), PathParams::default());
+        let err = block_on(ctx.proxy_to(Uri::from_static("https://origin.example/")))
+            .expect_err("no handle");
+        assert_eq!(err.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
     #[test]
     fn query_defaults_to_empty_when_missing() {
         #[derive(Debug, Deserialize, PartialEq)]
@@ -438,6 +1113,42 @@ mod tests {
         assert_eq!(parsed, Query { page: 5 });
     }
 
+    #[test]
+    fn query_over_the_limit_is_rejected_before_parsing() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Query {
+            term: String,
+        }
+        let mut short = ctx("/search?term=short", Body::empty(), PathParams::default());
+        short
+            .request_mut()
+            .extensions_mut()
+            .insert(QueryLimit::new(11));
+        let parsed: Query = short.query().expect("within the limit");
+        assert_eq!(parsed.term, "short");
+
+        let mut long = ctx(
+            "/search?term=too-long",
+            Body::empty(),
+            PathParams::default(),
+        );
+        long.request_mut()
+            .extensions_mut()
+            .insert(QueryLimit::new(11));
+        let err = long.query::<Query>().expect_err("over the limit");
+        assert_eq!(err.status(), StatusCode::URI_TOO_LONG);
+
+        let huge = format!(
+            "/search?q={}",
+            "a".repeat(QueryLimit::default().max_length())
+        );
+        let unregistered = ctx(&huge, Body::empty(), PathParams::default());
+        let default_err = unregistered
+            .query::<Query>()
+            .expect_err("over the default limit");
+        assert_eq!(default_err.status(), StatusCode::URI_TOO_LONG);
+    }
+
     #[test]
     fn request_context_accessors_return_expected_values() {
         let mut ctx = ctx(
@@ -463,6 +1174,37 @@ mod tests {
         assert_eq!(request.uri().path(), "/items/123");
     }
 
+    #[test]
+    fn adapter_name_reads_the_adapter_extension() {
+        let mut ctx = ctx("/", Body::empty(), PathParams::default());
+        assert_eq!(ctx.adapter_name(), None);
+
+        ctx.request_mut()
+            .extensions_mut()
+            .insert(AdapterName("fastly"));
+        assert_eq!(ctx.adapter_name(), Some("fastly"));
+    }
+
+    #[test]
+    fn connection_info_reads_adapter_populated_extension() {
+        let mut ctx = ctx("/", Body::empty(), PathParams::default());
+        assert!(ctx.connection_info().is_none());
+
+        let info = ConnectionInfo {
+            alpn: Some("h2".to_owned()),
+            client_cert_subject: Some("CN=client.example".to_owned()),
+            local_addr: Some("10.0.0.1:443".parse().expect("local addr")),
+            peer_addr: Some("203.0.113.7:51234".parse().expect("peer addr")),
+            tls_version: Some("TLSv1.3".to_owned()),
+        };
+        ctx.request_mut().extensions_mut().insert(info.clone());
+
+        let found = ctx.connection_info().expect("connection info");
+        assert_eq!(found, &info);
+        assert!(found.is_tls());
+        assert!(!ConnectionInfo::default().is_tls());
+    }
+
     // `RequestContext::secret_handle()` was removed. The
     // present/absent behaviour is now covered by `secret_store_*`
     // tests against a wired `SecretRegistry`.
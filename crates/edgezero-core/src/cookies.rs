@@ -0,0 +1,423 @@
+//! HMAC-signed cookies and a cookie-backed [`Session`] extractor.
+//!
+//! Register a [`SignedCookies`] key as app state, then read verified values
+//! with [`SignedCookies::get`] or the [`Session`] extractor:
+//!
+//! ```rust,ignore
+//! let router = RouterService::builder()
+//!     .with_state(SignedCookies::new(secret_bytes))
+//!     .get("/me", me)
+//!     .post("/login", login)
+//!     .build();
+//!
+//! #[action]
+//! async fn me(Session(user): Session<User>) -> Result<String, EdgeError> {
+//!     Ok(user.map_or_else(|| "anonymous".to_owned(), |user| user.name))
+//! }
+//!
+//! #[action]
+//! async fn login(State(cookies): State<SignedCookies>) -> Result<Response, EdgeError> {
+//!     let cookie = cookies.session_cookie(&User { name: "ada".into() }, Duration::from_secs(3600))?;
+//!     response_builder()
+//!         .header(SET_COOKIE, cookie)
+//!         .body(Body::empty())
+//!         .map_err(EdgeError::internal)
+//! }
+//! ```
+//!
+//! A signed value is `<payload>.<mac>`, both base64url without padding;
+//! the MAC is HMAC-SHA256 over `<name>=<payload>`, so a value cannot be
+//! replayed under another cookie name. Tampered, unsigned, expired or
+//! undecodable cookies read as absent rather than as errors.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use hmac::{Hmac, Mac as _};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::context::RequestContext;
+use crate::error::EdgeError;
+use crate::extractor::FromRequest;
+use crate::http::header::COOKIE;
+use crate::http::{HeaderMap, HeaderValue};
+
+/// Cookie holding the signed [`Session`] payload.
+pub const SESSION_COOKIE: &str = "edgezero_session";
+
+/// Signs and verifies cookie values with an app-configured key.
+///
+/// Cheap to clone (the keyed HMAC is shared), so it can be registered with
+/// [`crate::router::RouterBuilder::with_state`]. Use a key of at least 32
+/// random bytes; rotating it invalidates every outstanding cookie.
+#[derive(Clone)]
+pub struct SignedCookies {
+    keyed: Arc<Hmac<Sha256>>,
+}
+
+impl SignedCookies {
+    /// Read cookie `name` from the request's `Cookie` headers and return its
+    /// value if the signature verifies.
+    #[must_use]
+    #[inline]
+    pub fn get(&self, headers: &HeaderMap, name: &str) -> Option<String> {
+        cookie_values(headers, name).find_map(|signed| self.verify(name, signed))
+    }
+
+    /// The HMAC over `<name>=<payload>`, ready to finalize or verify.
+    fn mac(&self, name: &str, payload: &str) -> Hmac<Sha256> {
+        let mut mac = Hmac::clone(&self.keyed);
+        mac.update(name.as_bytes());
+        mac.update(b"=");
+        mac.update(payload.as_bytes());
+        mac
+    }
+
+    /// Create a signer using `key` as the HMAC secret.
+    #[expect(
+        clippy::expect_used,
+        clippy::missing_panics_doc,
+        reason = "HMAC accepts keys of any length, so `new_from_slice` cannot fail"
+    )]
+    #[inline]
+    pub fn new<K>(key: K) -> Self
+    where
+        K: Into<Vec<u8>>,
+    {
+        let keyed = Hmac::new_from_slice(&key.into()).expect("HMAC takes keys of any length");
+        Self {
+            keyed: Arc::new(keyed),
+        }
+    }
+
+    /// Decode the [`SESSION_COOKIE`] into `T`, or `None` when it is missing,
+    /// tampered with, expired or not valid JSON for `T`.
+    #[must_use]
+    #[inline]
+    pub fn session<T>(&self, headers: &HeaderMap) -> Option<T>
+    where
+        T: DeserializeOwned,
+    {
+        let json = self.get(headers, SESSION_COOKIE)?;
+        let envelope: SessionEnvelope<T> = serde_json::from_str(&json).ok()?;
+        (envelope.expires_at > unix_now_secs()).then_some(envelope.data)
+    }
+
+    /// Build a `Set-Cookie` value storing `data` as the signed session,
+    /// valid for `max_age`.
+    ///
+    /// # Errors
+    /// Returns [`EdgeError::internal`] if `data` cannot be serialized.
+    #[inline]
+    pub fn session_cookie<T>(&self, data: &T, max_age: Duration) -> Result<HeaderValue, EdgeError>
+    where
+        T: Serialize,
+    {
+        let envelope = SessionEnvelope {
+            data,
+            expires_at: unix_now_secs().saturating_add(max_age.as_secs()),
+        };
+        let json = serde_json::to_string(&envelope).map_err(EdgeError::internal)?;
+        self.set_cookie(SESSION_COOKIE, &json, Some(max_age))
+    }
+
+    /// Build a `Set-Cookie` value carrying `value` signed under `name`, with
+    /// `Path=/; HttpOnly; Secure; SameSite=Lax` and an optional `Max-Age`.
+    ///
+    /// # Errors
+    /// Returns [`EdgeError::internal`] if `name` is not a valid cookie
+    /// name, a programming error rather than a bad request.
+    #[inline]
+    pub fn set_cookie(
+        &self,
+        name: &str,
+        value: &str,
+        max_age: Option<Duration>,
+    ) -> Result<HeaderValue, EdgeError> {
+        let valid_name = !name.is_empty()
+            && name
+                .bytes()
+                .all(|byte| byte.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?={}".contains(&byte));
+        if !valid_name {
+            return Err(EdgeError::internal(anyhow::anyhow!(
+                "invalid cookie name `{name}`"
+            )));
+        }
+        let max_age_attr =
+            max_age.map_or_else(String::new, |age| format!("; Max-Age={}", age.as_secs()));
+        let cookie = format!(
+            "{name}={}; Path=/; HttpOnly; Secure; SameSite=Lax{max_age_attr}",
+            self.sign(name, value)
+        );
+        HeaderValue::from_str(&cookie).map_err(EdgeError::internal)
+    }
+
+    /// Sign `value` for use as cookie `name`.
+    #[must_use]
+    #[inline]
+    pub fn sign(&self, name: &str, value: &str) -> String {
+        let payload = URL_SAFE_NO_PAD.encode(value);
+        let mac = URL_SAFE_NO_PAD.encode(self.mac(name, &payload).finalize().into_bytes());
+        format!("{payload}.{mac}")
+    }
+
+    /// Verify a value produced by [`Self::sign`] for cookie `name` and
+    /// return the original value.
+    #[must_use]
+    #[inline]
+    pub fn verify(&self, name: &str, signed: &str) -> Option<String> {
+        let (payload, mac) = signed.split_once('.')?;
+        let presented = URL_SAFE_NO_PAD.decode(mac).ok()?;
+        // Constant-time comparison.
+        self.mac(name, payload).verify_slice(&presented).ok()?;
+        String::from_utf8(URL_SAFE_NO_PAD.decode(payload).ok()?).ok()
+    }
+}
+
+impl fmt::Debug for SignedCookies {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignedCookies").finish_non_exhaustive()
+    }
+}
+
+/// Extractor for a session stored in the signed [`SESSION_COOKIE`].
+///
+/// Yields `Session(None)` when there is no valid, unexpired session.
+/// Requires [`SignedCookies`] to be registered as app state.
+pub struct Session<T>(pub Option<T>);
+
+#[async_trait(?Send)]
+impl<T> FromRequest for Session<T>
+where
+    T: DeserializeOwned,
+{
+    #[inline]
+    async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+        let cookies = ctx
+            .request()
+            .extensions()
+            .get::<SignedCookies>()
+            .ok_or_else(|| {
+                EdgeError::internal(anyhow::anyhow!(
+                    "no `SignedCookies` registered -- call RouterBuilder::with_state(SignedCookies::new(..)) before build()"
+                ))
+            })?;
+        Ok(Session(cookies.session(ctx.request().headers())))
+    }
+}
+
+impl<T> Session<T> {
+    #[inline]
+    pub fn into_inner(self) -> Option<T> {
+        self.0
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct SessionEnvelope<T> {
+    data: T,
+    expires_at: u64,
+}
+
+/// Every value of cookie `name` across the request's `Cookie` headers.
+fn cookie_values<'headers>(
+    headers: &'headers HeaderMap,
+    name: &'headers str,
+) -> impl Iterator<Item = &'headers str> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(move |pair| {
+            let (key, value) = pair.trim().split_once('=')?;
+            (key == name).then_some(value)
+        })
+}
+
+fn unix_now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::Body;
+    use crate::http::{Method, StatusCode, request_builder};
+    use crate::params::PathParams;
+    use futures::executor::block_on;
+    use std::fmt::Write as _;
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct User {
+        name: String,
+    }
+
+    fn cookies() -> SignedCookies {
+        SignedCookies::new(b"0123456789abcdef0123456789abcdef".to_vec())
+    }
+
+    /// Turn a `Set-Cookie` value into the matching request `Cookie` header.
+    fn request_with_set_cookie(set_cookie: &HeaderValue) -> RequestContext {
+        let pair = set_cookie
+            .to_str()
+            .unwrap()
+            .split(';')
+            .next()
+            .unwrap()
+            .to_owned();
+        request_with_cookie(&pair)
+    }
+
+    fn request_with_cookie(cookie: &str) -> RequestContext {
+        let mut request = request_builder()
+            .method(Method::GET)
+            .uri("/")
+            .header(COOKIE, cookie)
+            .body(Body::empty())
+            .expect("request");
+        request.extensions_mut().insert(cookies());
+        RequestContext::new(request, PathParams::default())
+    }
+
+    #[test]
+    fn hmac_matches_rfc_4231_test_case_2() {
+        // The RFC message has no `=`, so check the keyed HMAC `sign` uses.
+        let signer = SignedCookies::new(b"Jefe".to_vec());
+        let mut mac = Hmac::clone(&signer.keyed);
+        mac.update(b"what do ya want for nothing?");
+        let hex = mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .fold(String::new(), |mut acc, byte| {
+                write!(acc, "{byte:02x}").unwrap();
+                acc
+            });
+        assert_eq!(
+            hex,
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn session_round_trips_through_set_cookie() {
+        let set_cookie = cookies()
+            .session_cookie(
+                &User {
+                    name: "ada".to_owned(),
+                },
+                Duration::from_mins(1),
+            )
+            .expect("cookie");
+        let header = set_cookie.to_str().unwrap();
+        assert!(header.starts_with("edgezero_session="));
+        assert!(header.contains("HttpOnly"));
+        assert!(header.ends_with("Max-Age=60"));
+
+        let ctx = request_with_set_cookie(&set_cookie);
+        let Session(user) = block_on(Session::<User>::from_request(&ctx)).expect("session");
+        assert_eq!(
+            user,
+            Some(User {
+                name: "ada".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn session_without_signed_cookies_state_is_internal_error() {
+        let request = request_builder()
+            .method(Method::GET)
+            .uri("/")
+            .body(Body::empty())
+            .expect("request");
+        let ctx = RequestContext::new(request, PathParams::default());
+        let err = block_on(Session::<User>::from_request(&ctx))
+            .err()
+            .expect("missing state");
+        assert_eq!(err.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn signed_value_is_bound_to_cookie_name() {
+        let signer = cookies();
+        let token = signer.sign("theme", "dark");
+        assert_eq!(signer.verify("theme", &token).as_deref(), Some("dark"));
+        assert_eq!(signer.verify("role", &token), None);
+    }
+
+    #[test]
+    fn tampered_session_cookie_is_no_session() {
+        let set_cookie = cookies()
+            .session_cookie(
+                &User {
+                    name: "ada".to_owned(),
+                },
+                Duration::from_mins(1),
+            )
+            .expect("cookie");
+        let pair = set_cookie.to_str().unwrap().split(';').next().unwrap();
+        let (name, signed) = pair.split_once('=').unwrap();
+        let (_, mac) = signed.split_once('.').unwrap();
+        let forged_payload =
+            URL_SAFE_NO_PAD.encode(r#"{"data":{"name":"root"},"expires_at":99999999999}"#);
+
+        for cookie in [
+            format!("{name}={forged_payload}.{mac}"),
+            format!("{name}={forged_payload}"),
+            format!("{name}=not-even-base64!.???"),
+        ] {
+            let ctx = request_with_cookie(&cookie);
+            let Session(user) = block_on(Session::<User>::from_request(&ctx)).expect("session");
+            assert_eq!(user, None, "accepted {cookie}");
+        }
+    }
+
+    #[test]
+    fn expired_session_is_no_session() {
+        let set_cookie = cookies()
+            .session_cookie(
+                &User {
+                    name: "ada".to_owned(),
+                },
+                Duration::ZERO,
+            )
+            .expect("cookie");
+        let ctx = request_with_set_cookie(&set_cookie);
+        let Session(user) = block_on(Session::<User>::from_request(&ctx)).expect("session");
+        assert_eq!(user, None);
+    }
+
+    #[test]
+    fn get_reads_signed_cookie_among_others() {
+        let signer = cookies();
+        let cookie = format!("a=1; theme={}; b=2", signer.sign("theme", "dark"));
+        let ctx = request_with_cookie(&cookie);
+        assert_eq!(
+            signer.get(ctx.request().headers(), "theme").as_deref(),
+            Some("dark")
+        );
+        assert_eq!(signer.get(ctx.request().headers(), "a"), None);
+    }
+
+    #[test]
+    fn set_cookie_rejects_invalid_names() {
+        let err = cookies()
+            .set_cookie("bad name", "value", None)
+            .expect_err("invalid name");
+        assert_eq!(err.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let ok = cookies().set_cookie("good", "value", None).expect("valid");
+        assert!(!ok.to_str().unwrap().contains("Max-Age"));
+    }
+}
@@ -0,0 +1,605 @@
+//! Cross-Origin Resource Sharing (CORS) middleware.
+//!
+//! [`Cors`] answers preflight requests itself and adds the
+//! `Access-Control-*` headers to responses for allowed origins. Requests
+//! without an `Origin` header, and requests from origins the policy does not
+//! allow, get no `Access-Control-*` headers, so the browser blocks the
+//! response. Unless the policy allows any origin, every response carries
+//! `Vary: Origin`, so a cache never serves one origin's answer to another.
+//!
+//! ```rust,ignore
+//! let cors = Cors::builder()
+//!     .allow_origin("https://app.example.com")
+//!     .allow_origin("https://*.example.com")
+//!     .allow_methods([Method::GET, Method::POST])
+//!     .allow_headers(["content-type"])
+//!     .allow_credentials(true)
+//!     .build()?;
+//!
+//! let router = RouterService::builder()
+//!     .middleware(cors)
+//!     .get("/api/items", list_items)
+//!     .build();
+//! ```
+//!
+//! Origins are matched exactly, except that `*` may stand for one whole
+//! label of the host: `https://*.example.com` matches
+//! `https://eu.example.com` but neither `https://example.com` nor
+//! `https://a.b.example.com`. A pattern match always reflects the requesting
+//! origin in `Access-Control-Allow-Origin`. The lone origin `*` allows any
+//! origin and cannot be combined with credentials.
+//!
+//! Apps built with the `app!` macro can declare the policy in a `[cors]`
+//! section of `edgezero.toml` instead; see
+//! [`ManifestCors`](crate::manifest::ManifestCors).
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use thiserror::Error;
+
+use crate::body::Body;
+use crate::context::RequestContext;
+use crate::error::EdgeError;
+use crate::http::header::{
+    ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+    ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS, ACCESS_CONTROL_MAX_AGE,
+    ACCESS_CONTROL_REQUEST_METHOD, ORIGIN,
+};
+use crate::http::{HeaderMap, HeaderName, HeaderValue, Method, Response, StatusCode};
+use crate::middleware::{Middleware, Next};
+use crate::response::{append_vary, response_with_body};
+
+/// Why a [`CorsBuilder`] could not build a policy.
+#[derive(Debug, Eq, Error, PartialEq)]
+#[non_exhaustive]
+pub enum CorsError {
+    /// Credentialed requests cannot use the `*` origin; list the origins or
+    /// origin patterns instead.
+    #[error("CORS origin `*` cannot be combined with credentials")]
+    CredentialsWithWildcard,
+    /// An allowed or exposed header is not a valid header name (or `*`).
+    #[error("invalid CORS header `{header}`")]
+    InvalidHeader { header: String },
+    /// The origin is not `scheme://host[:port]`, or uses `*` other than as
+    /// a whole host label.
+    #[error("invalid CORS origin `{origin}`")]
+    InvalidOrigin { origin: String },
+}
+
+/// CORS policy, applied as [`Middleware`]. Build one with [`Cors::builder`].
+#[derive(Clone, Debug)]
+pub struct Cors {
+    allow_credentials: bool,
+    allow_headers: Option<HeaderValue>,
+    allow_methods: HeaderValue,
+    expose_headers: Option<HeaderValue>,
+    max_age: Option<HeaderValue>,
+    origins: AllowedOrigins,
+}
+
+impl Cors {
+    /// Value for `Access-Control-Allow-Origin` when `origin` is allowed.
+    fn allow_origin_value(&self, origin: &HeaderValue) -> Option<HeaderValue> {
+        match &self.origins {
+            AllowedOrigins::Any => Some(HeaderValue::from_static("*")),
+            AllowedOrigins::List(patterns) => {
+                let requested = origin.to_str().ok()?.to_ascii_lowercase();
+                patterns
+                    .iter()
+                    .any(|pattern| pattern.matches(&requested))
+                    .then(|| origin.clone())
+            }
+        }
+    }
+
+    /// Whether requests from `origin` are allowed.
+    pub(crate) fn allows(&self, origin: &HeaderValue) -> bool {
+        self.allow_origin_value(origin).is_some()
+    }
+
+    fn apply(&self, headers: &mut HeaderMap, allow_origin: HeaderValue) {
+        headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+        if self.allow_credentials {
+            headers.insert(
+                ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+        if matches!(self.origins, AllowedOrigins::List(_)) {
+            append_vary(headers, "Origin");
+        }
+    }
+
+    /// Start a policy that allows no origins.
+    #[must_use]
+    #[inline]
+    pub fn builder() -> CorsBuilder {
+        CorsBuilder::default()
+    }
+
+    fn preflight(&self, allow_origin: HeaderValue) -> Result<Response, EdgeError> {
+        let mut response = response_with_body(StatusCode::NO_CONTENT, Body::empty())?;
+        let headers = response.headers_mut();
+        self.apply(headers, allow_origin);
+        headers.insert(ACCESS_CONTROL_ALLOW_METHODS, self.allow_methods.clone());
+        if let Some(allow_headers) = &self.allow_headers {
+            headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, allow_headers.clone());
+        }
+        if let Some(max_age) = &self.max_age {
+            headers.insert(ACCESS_CONTROL_MAX_AGE, max_age.clone());
+        }
+        Ok(response)
+    }
+}
+
+#[async_trait(?Send)]
+impl Middleware for Cors {
+    #[inline]
+    async fn handle(&self, ctx: RequestContext, next: Next<'_>) -> Result<Response, EdgeError> {
+        let request = ctx.request();
+        let Some(allow_origin) = request
+            .headers()
+            .get(ORIGIN)
+            .and_then(|origin| self.allow_origin_value(origin))
+        else {
+            if matches!(self.origins, AllowedOrigins::Any) {
+                return next.run(ctx).await;
+            }
+            // The answer still depends on the origin: an allowed one would
+            // have been given the CORS headers.
+            let mut response = next.run_rendered(ctx).await?;
+            append_vary(response.headers_mut(), "Origin");
+            return Ok(response);
+        };
+        if request.method() == Method::OPTIONS
+            && request
+                .headers()
+                .contains_key(ACCESS_CONTROL_REQUEST_METHOD)
+        {
+            return self.preflight(allow_origin);
+        }
+
+        // Render errors here so the browser can read error responses too.
+        let mut response = next.run_rendered(ctx).await?;
+        self.apply(response.headers_mut(), allow_origin);
+        if let Some(expose_headers) = &self.expose_headers {
+            response
+                .headers_mut()
+                .insert(ACCESS_CONTROL_EXPOSE_HEADERS, expose_headers.clone());
+        }
+        Ok(response)
+    }
+}
+
+/// Builder for [`Cors`].
+#[derive(Debug, Default)]
+pub struct CorsBuilder {
+    allow_credentials: bool,
+    allow_headers: Vec<String>,
+    allow_methods: Vec<Method>,
+    expose_headers: Vec<String>,
+    max_age: Option<Duration>,
+    origins: Vec<String>,
+}
+
+impl CorsBuilder {
+    /// Allow any origin. Same as `allow_origin("*")`.
+    #[must_use]
+    #[inline]
+    pub fn allow_any_origin(self) -> Self {
+        self.allow_origin("*")
+    }
+
+    /// Send `Access-Control-Allow-Credentials: true`, letting browsers
+    /// include cookies and credentials.
+    #[must_use]
+    #[inline]
+    pub fn allow_credentials(mut self, allow: bool) -> Self {
+        self.allow_credentials = allow;
+        self
+    }
+
+    /// Request headers preflight requests may ask for.
+    #[must_use]
+    #[inline]
+    pub fn allow_headers<I, S>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allow_headers
+            .extend(headers.into_iter().map(Into::into));
+        self
+    }
+
+    /// Methods preflight requests may ask for. Defaults to `GET`, `HEAD`,
+    /// and `POST`.
+    #[must_use]
+    #[inline]
+    pub fn allow_methods<I>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = Method>,
+    {
+        self.allow_methods.extend(methods);
+        self
+    }
+
+    /// Allow `origin`: an exact `scheme://host[:port]`, a pattern with `*`
+    /// as a whole host label such as `https://*.example.com`, or `*` for any
+    /// origin.
+    #[must_use]
+    #[inline]
+    pub fn allow_origin<S: Into<String>>(mut self, origin: S) -> Self {
+        self.origins.push(origin.into());
+        self
+    }
+
+    /// Build the policy.
+    ///
+    /// # Errors
+    /// Returns [`CorsError::InvalidOrigin`] for a malformed origin,
+    /// [`CorsError::InvalidHeader`] for an allowed or exposed header that is
+    /// not a header name, and [`CorsError::CredentialsWithWildcard`] when the
+    /// `*` origin is combined with [`Self::allow_credentials`].
+    #[inline]
+    pub fn build(self) -> Result<Cors, CorsError> {
+        let origins = if self.origins.iter().any(|origin| origin == "*") {
+            if self.allow_credentials {
+                return Err(CorsError::CredentialsWithWildcard);
+            }
+            AllowedOrigins::Any
+        } else {
+            AllowedOrigins::List(
+                self.origins
+                    .iter()
+                    .map(|origin| OriginPattern::parse(origin))
+                    .collect::<Result<_, _>>()?,
+            )
+        };
+
+        let methods = if self.allow_methods.is_empty() {
+            vec![Method::GET, Method::HEAD, Method::POST]
+        } else {
+            self.allow_methods
+        };
+        let method_names: Vec<&str> = methods.iter().map(Method::as_str).collect();
+
+        Ok(Cors {
+            allow_credentials: self.allow_credentials,
+            allow_headers: header_list(&self.allow_headers)?,
+            allow_methods: header_list(&method_names)?
+                .unwrap_or_else(|| HeaderValue::from_static("GET, HEAD, POST")),
+            expose_headers: header_list(&self.expose_headers)?,
+            max_age: self
+                .max_age
+                .map(|max_age| HeaderValue::from(max_age.as_secs())),
+            origins,
+        })
+    }
+
+    /// Response headers scripts may read, beyond the CORS-safelisted ones.
+    #[must_use]
+    #[inline]
+    pub fn expose_headers<I, S>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.expose_headers
+            .extend(headers.into_iter().map(Into::into));
+        self
+    }
+
+    /// How long browsers may cache a preflight answer.
+    #[must_use]
+    #[inline]
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+enum AllowedOrigins {
+    Any,
+    List(Vec<OriginPattern>),
+}
+
+/// An allowed origin, lowercased, with `None` host labels standing for `*`.
+#[derive(Clone, Debug)]
+struct OriginPattern {
+    host: Vec<Option<String>>,
+    port: Option<String>,
+    scheme: String,
+}
+
+impl OriginPattern {
+    fn matches(&self, origin: &str) -> bool {
+        let Some((scheme, host, port)) = split_origin(origin) else {
+            return false;
+        };
+        let labels: Vec<&str> = host.split('.').collect();
+        scheme == self.scheme
+            && port == self.port.as_deref()
+            && labels.len() == self.host.len()
+            && labels
+                .iter()
+                .zip(&self.host)
+                .all(|(label, pattern)| match pattern {
+                    Some(literal) => label == literal,
+                    None => !label.is_empty(),
+                })
+    }
+
+    fn parse(origin: &str) -> Result<Self, CorsError> {
+        let invalid = || CorsError::InvalidOrigin {
+            origin: origin.to_owned(),
+        };
+        let lowered = origin.to_ascii_lowercase();
+        let (scheme, host, port) = split_origin(&lowered).ok_or_else(invalid)?;
+        if scheme.contains('*') || port.is_some_and(|digits| digits.contains('*')) {
+            return Err(invalid());
+        }
+        let labels = host
+            .split('.')
+            .map(|label| match label {
+                "*" => Ok(None),
+                "" => Err(invalid()),
+                _ if label.contains('*') => Err(invalid()),
+                _ => Ok(Some(label.to_owned())),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            host: labels,
+            port: port.map(str::to_owned),
+            scheme: scheme.to_owned(),
+        })
+    }
+}
+
+/// Join header names (or methods) into one comma-separated header value.
+fn header_list<S: AsRef<str>>(values: &[S]) -> Result<Option<HeaderValue>, CorsError> {
+    if let Some(invalid) = values
+        .iter()
+        .map(AsRef::as_ref)
+        .find(|value| HeaderName::from_bytes(value.as_bytes()).is_err())
+    {
+        return Err(CorsError::InvalidHeader {
+            header: invalid.to_owned(),
+        });
+    }
+    if values.is_empty() {
+        return Ok(None);
+    }
+    let joined = values
+        .iter()
+        .map(AsRef::as_ref)
+        .collect::<Vec<_>>()
+        .join(", ");
+    HeaderValue::from_str(&joined)
+        .map(Some)
+        .map_err(|_err| CorsError::InvalidHeader { header: joined })
+}
+
+/// Split `scheme://host[:port]` into its parts. A bracketed IPv6 host keeps
+/// its colons; an empty host or port is rejected.
+fn split_origin(origin: &str) -> Option<(&str, &str, Option<&str>)> {
+    let (scheme, authority) = origin.split_once("://")?;
+    if scheme.is_empty() || authority.is_empty() || authority.contains('/') {
+        return None;
+    }
+    let port_colon = authority
+        .rfind(':')
+        .filter(|colon| authority.rfind(']').is_none_or(|bracket| colon > &bracket));
+    let Some(colon) = port_colon else {
+        return Some((scheme, authority, None));
+    };
+    let (host, port) = (
+        authority.get(..colon)?,
+        authority.get(colon.saturating_add(1)..)?,
+    );
+    if host.is_empty() || port.is_empty() {
+        return None;
+    }
+    Some((scheme, host, Some(port)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::header::VARY;
+    use crate::http::{Request, request_builder};
+    use crate::router::RouterService;
+    use futures::executor::block_on;
+
+    async fn ok_handler(_ctx: RequestContext) -> Result<Response, EdgeError> {
+        response_with_body(StatusCode::OK, Body::empty())
+    }
+
+    fn router(cors: Cors) -> RouterService {
+        RouterService::builder()
+            .middleware(cors)
+            .get("/items", ok_handler)
+            .build()
+    }
+
+    fn request(method: Method, origin: &str) -> Request {
+        request_builder()
+            .method(method)
+            .uri("/items")
+            .header(ORIGIN, origin)
+            .header(ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .body(Body::empty())
+            .expect("request")
+    }
+
+    fn wildcard_subdomains() -> Cors {
+        Cors::builder()
+            .allow_origin("https://*.Example.com")
+            .allow_credentials(true)
+            .max_age(Duration::from_mins(10))
+            .build()
+            .expect("valid policy")
+    }
+
+    #[test]
+    fn wildcard_pattern_reflects_matching_subdomain() {
+        let service = router(wildcard_subdomains());
+
+        let response =
+            block_on(service.oneshot(request(Method::GET, "https://eu.example.com"))).expect("ok");
+        assert_eq!(response.status(), StatusCode::OK);
+        let headers = response.headers();
+        assert_eq!(
+            headers[ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://eu.example.com"
+        );
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert_eq!(headers[VARY], "Origin");
+
+        let preflight =
+            block_on(service.oneshot(request(Method::OPTIONS, "https://eu.example.com")))
+                .expect("preflight");
+        assert_eq!(preflight.status(), StatusCode::NO_CONTENT);
+        assert_eq!(
+            preflight.headers()[ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://eu.example.com"
+        );
+        assert_eq!(
+            preflight.headers()[ACCESS_CONTROL_ALLOW_METHODS],
+            "GET, HEAD, POST"
+        );
+        assert_eq!(preflight.headers()[ACCESS_CONTROL_MAX_AGE], "600");
+    }
+
+    #[test]
+    fn wildcard_pattern_rejects_other_hosts() {
+        let service = router(wildcard_subdomains());
+        for origin in [
+            "https://example.com",
+            "https://a.b.example.com",
+            "https://eu.example.org",
+            "http://eu.example.com",
+            "https://eu.example.com:8443",
+            "https://evil-example.com",
+        ] {
+            let response =
+                block_on(service.oneshot(request(Method::GET, origin))).expect("response");
+            assert!(
+                !response.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN),
+                "{origin} should not be allowed"
+            );
+        }
+
+        let without_origin = request_builder()
+            .uri("/items")
+            .body(Body::empty())
+            .expect("request");
+        for response in [
+            block_on(service.oneshot(request(Method::GET, "https://example.com"))),
+            block_on(service.oneshot(without_origin)),
+        ] {
+            assert_eq!(response.expect("response").headers()[VARY], "Origin");
+        }
+
+        // Unanswered preflights fall through to the router's 405.
+        let preflight = block_on(service.oneshot(request(Method::OPTIONS, "https://example.com")))
+            .expect("response");
+        assert_eq!(preflight.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[test]
+    fn any_origin_sends_star_without_credentials() {
+        let service = router(Cors::builder().allow_any_origin().build().expect("policy"));
+        let response =
+            block_on(service.oneshot(request(Method::GET, "https://anywhere.test"))).expect("ok");
+        assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        assert!(
+            !response
+                .headers()
+                .contains_key(ACCESS_CONTROL_ALLOW_CREDENTIALS)
+        );
+        assert!(!response.headers().contains_key(VARY));
+    }
+
+    #[test]
+    fn build_rejects_wildcard_with_credentials() {
+        let err = Cors::builder()
+            .allow_origin("https://app.example.com")
+            .allow_any_origin()
+            .allow_credentials(true)
+            .build()
+            .expect_err("wildcard with credentials");
+        assert_eq!(err, CorsError::CredentialsWithWildcard);
+    }
+
+    #[test]
+    fn build_rejects_malformed_origins() {
+        for origin in [
+            "example.com",
+            "https://",
+            "https://app.example.com/path",
+            "https://app*.example.com",
+            "https://*.example.com:*",
+            "https://.example.com",
+            "https://app.example.com:",
+            "http://[::1]:",
+        ] {
+            let err = Cors::builder()
+                .allow_origin(origin)
+                .build()
+                .expect_err("malformed origin");
+            assert_eq!(
+                err,
+                CorsError::InvalidOrigin {
+                    origin: origin.to_owned()
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn build_rejects_invalid_header_names() {
+        let allowed = Cors::builder()
+            .allow_origin("https://app.example.com")
+            .allow_headers(["content-type", "x bad"])
+            .build()
+            .expect_err("space in header name");
+        assert_eq!(
+            allowed,
+            CorsError::InvalidHeader {
+                header: "x bad".to_owned()
+            }
+        );
+        let exposed = Cors::builder()
+            .allow_origin("https://app.example.com")
+            .expose_headers(["x-request-id\n"])
+            .build()
+            .expect_err("newline in header name");
+        assert_eq!(
+            exposed,
+            CorsError::InvalidHeader {
+                header: "x-request-id\n".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn ipv6_origins_keep_their_brackets() {
+        assert_eq!(split_origin("http://[::1]"), Some(("http", "[::1]", None)));
+        assert_eq!(
+            split_origin("http://[::1]:8080"),
+            Some(("http", "[::1]", Some("8080")))
+        );
+
+        let cors = Cors::builder()
+            .allow_origin("http://[::1]:8080")
+            .build()
+            .expect("ipv6 origin");
+        let allowed = HeaderValue::from_static("http://[::1]:8080");
+        assert_eq!(cors.allow_origin_value(&allowed), Some(allowed.clone()));
+        let other_port = HeaderValue::from_static("http://[::1]:9090");
+        assert_eq!(cors.allow_origin_value(&other_port), None);
+    }
+}
@@ -0,0 +1,393 @@
+//! Per-request deadlines shared by middleware and outbound proxy calls.
+//!
+//! [`DeadlineMiddleware`] fixes when the request must be answered: the
+//! caller's `X-Request-Deadline` budget (milliseconds remaining) when
+//! present, never later than the configured default budget. The resulting
+//! [`Deadline`] is stored in the request extensions, readable through
+//! [`RequestContext::deadline`], and carried onto proxy requests built with
+//! [`ProxyRequest::from_request`] or [`ProxyRequest::apply_deadline`], whose
+//! [`ProxyRequest::timeout`] is then capped by the remaining time.
+//!
+//! A route registered with [`RouterBuilder::route_with_timeout`] (or a
+//! manifest trigger with `timeout-ms`) gets its own deadline on top: it is
+//! merged into the request's [`Deadline`] before the handler runs. Core has
+//! no timer of its own, so the handler is raced against the [`Sleep`] the
+//! adapter installs in the request extensions (tokio on axum, the platform
+//! timer on Cloudflare and Spin) and answers `504 Gateway Timeout` as soon
+//! as it fires. Without one, as on Fastly, whose host calls block, a
+//! response produced after the timeout has passed is replaced by the 504.
+//!
+//! ```rust,ignore
+//! let router = RouterService::builder()
+//!     .middleware(DeadlineMiddleware::new(Duration::from_secs(5)))
+//!     .get("/api/{*rest}", forward)
+//!     .build();
+//! ```
+//!
+//! [`RouterBuilder::route_with_timeout`]: crate::router::RouterBuilder::route_with_timeout
+//! [`ProxyRequest::from_request`]: crate::proxy::ProxyRequest::from_request
+//! [`ProxyRequest::apply_deadline`]: crate::proxy::ProxyRequest::apply_deadline
+//! [`ProxyRequest::timeout`]: crate::proxy::ProxyRequest::timeout
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::future::{self, Either, LocalBoxFuture};
+use web_time::Instant;
+
+use crate::context::RequestContext;
+use crate::error::EdgeError;
+use crate::handler::{BoxHandler, DynHandler, IntrospectionNeeds};
+use crate::http::{HandlerFuture, HeaderMap, Response};
+use crate::middleware::{Middleware, Next};
+
+/// Header carrying the caller's remaining budget in milliseconds.
+pub const DEADLINE_HEADER: &str = "x-request-deadline";
+
+/// Instant by which the current request must be answered.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Deadline {
+    expires_at: Instant,
+}
+
+impl Deadline {
+    /// A deadline `budget` from now.
+    #[must_use]
+    #[inline]
+    pub fn after(budget: Duration) -> Self {
+        let now = Instant::now();
+        Self {
+            expires_at: now.checked_add(budget).unwrap_or(now),
+        }
+    }
+
+    /// `timeout`, shortened to the time remaining before the deadline.
+    #[must_use]
+    #[inline]
+    pub fn cap(&self, timeout: Duration) -> Duration {
+        timeout.min(self.remaining())
+    }
+
+    /// The earlier of two deadlines.
+    #[must_use]
+    #[inline]
+    pub fn earliest(self, other: Self) -> Self {
+        if other.expires_at < self.expires_at {
+            other
+        } else {
+            self
+        }
+    }
+
+    /// The caller's deadline from an `X-Request-Deadline` header, if present
+    /// and a whole number of milliseconds.
+    #[must_use]
+    #[inline]
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let millis = headers
+            .get(DEADLINE_HEADER)?
+            .to_str()
+            .ok()?
+            .trim()
+            .parse::<u64>()
+            .ok()?;
+        Some(Self::after(Duration::from_millis(millis)))
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn is_expired(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// Time left before the deadline, zero once it has passed.
+    #[must_use]
+    #[inline]
+    pub fn remaining(&self) -> Duration {
+        self.expires_at.saturating_duration_since(Instant::now())
+    }
+}
+
+/// A timer the adapter provides, since core has none.
+pub trait Sleep: Send + Sync {
+    /// A future completing once `duration` has passed.
+    fn sleep(&self, duration: Duration) -> LocalBoxFuture<'static, ()>;
+}
+
+/// The [`Sleep`] an adapter installs in request extensions.
+#[derive(Clone)]
+pub struct SleepHandle {
+    sleep: Arc<dyn Sleep>,
+}
+
+impl SleepHandle {
+    /// A future completing once `duration` has passed.
+    #[must_use]
+    #[inline]
+    pub fn sleep(&self, duration: Duration) -> LocalBoxFuture<'static, ()> {
+        self.sleep.sleep(duration)
+    }
+
+    #[inline]
+    pub fn with_sleep<S>(sleep: S) -> Self
+    where
+        S: Sleep + 'static,
+    {
+        Self {
+            sleep: Arc::new(sleep),
+        }
+    }
+}
+
+impl fmt::Debug for SleepHandle {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SleepHandle").finish_non_exhaustive()
+    }
+}
+
+/// Middleware that establishes a [`Deadline`] for every request and rejects
+/// requests whose deadline has already passed with `503 Service Unavailable`.
+pub struct DeadlineMiddleware {
+    default_budget: Duration,
+}
+
+impl DeadlineMiddleware {
+    /// Give each request at most `default_budget`; an incoming
+    /// `X-Request-Deadline` can shorten it but not extend it.
+    #[must_use]
+    #[inline]
+    pub fn new(default_budget: Duration) -> Self {
+        Self { default_budget }
+    }
+}
+
+#[async_trait(?Send)]
+impl Middleware for DeadlineMiddleware {
+    #[inline]
+    async fn handle(&self, mut ctx: RequestContext, next: Next<'_>) -> Result<Response, EdgeError> {
+        let mut deadline = Deadline::after(self.default_budget);
+        for tighter in [
+            Deadline::from_headers(ctx.request().headers()),
+            ctx.deadline(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            deadline = deadline.earliest(tighter);
+        }
+        if deadline.is_expired() {
+            return Err(EdgeError::service_unavailable("request deadline exceeded"));
+        }
+        ctx.request_mut().extensions_mut().insert(deadline);
+        next.run(ctx).await
+    }
+}
+
+/// Handler wrapper behind [`RouterBuilder::route_with_timeout`].
+///
+/// The timeout caps the request's deadline, so outbound proxy calls give up
+/// in time. The handler is raced against the adapter's [`SleepHandle`] and
+/// answers `504` when it fires; without one, a handler that finishes late
+/// answers with `504` instead.
+///
+/// [`RouterBuilder::route_with_timeout`]: crate::router::RouterBuilder::route_with_timeout
+pub(crate) struct RouteTimeout {
+    handler: BoxHandler,
+    timeout: Duration,
+}
+
+impl RouteTimeout {
+    pub(crate) fn new(handler: BoxHandler, timeout: Duration) -> Self {
+        Self { handler, timeout }
+    }
+}
+
+impl DynHandler for RouteTimeout {
+    #[inline]
+    fn call(&self, mut ctx: RequestContext) -> HandlerFuture {
+        let route_deadline = Deadline::after(self.timeout);
+        let deadline = ctx
+            .deadline()
+            .map_or(route_deadline, |existing| existing.earliest(route_deadline));
+        ctx.request_mut().extensions_mut().insert(deadline);
+        let timer = ctx.request().extensions().get::<SleepHandle>().cloned();
+        let timeout = self.timeout;
+        let timed_out = move || {
+            EdgeError::gateway_timeout(format!("route timed out after {}ms", timeout.as_millis()))
+        };
+        let response = self.handler.call(ctx);
+        Box::pin(async move {
+            if let Some(sleep) = timer {
+                return match future::select(response, sleep.sleep(timeout)).await {
+                    Either::Left((result, _)) => result,
+                    Either::Right(((), _)) => Err(timed_out()),
+                };
+            }
+            let result = response.await;
+            if route_deadline.is_expired() {
+                return Err(timed_out());
+            }
+            result
+        })
+    }
+
+    #[inline]
+    fn introspection_needs(&self) -> IntrospectionNeeds {
+        self.handler.introspection_needs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::Body;
+    use crate::http::{Method, Request, StatusCode, Uri, request_builder};
+    use crate::proxy::{ProxyClient, ProxyHandle, ProxyRequest, ProxyResponse};
+    use crate::router::RouterService;
+    use futures::channel::oneshot;
+    use futures::executor::block_on;
+    use std::sync::Mutex;
+    use std::thread;
+
+    /// Records the timeout each outbound request would be sent with.
+    struct RecordingClient(Arc<Mutex<Vec<Option<Duration>>>>);
+
+    #[async_trait(?Send)]
+    impl ProxyClient for RecordingClient {
+        async fn send(&self, request: ProxyRequest) -> Result<ProxyResponse, EdgeError> {
+            self.0.lock().unwrap().push(request.timeout());
+            Ok(ProxyResponse::new(StatusCode::OK, Body::empty()))
+        }
+    }
+
+    /// A [`Sleep`] backed by a thread per timer.
+    struct ThreadSleep;
+
+    impl Sleep for ThreadSleep {
+        fn sleep(&self, duration: Duration) -> LocalBoxFuture<'static, ()> {
+            let (fired, wait) = oneshot::channel();
+            thread::spawn(move || {
+                thread::sleep(duration);
+                // The race may have dropped the receiver already.
+                fired.send(()).unwrap_or_default();
+            });
+            Box::pin(async move {
+                wait.await.unwrap_or_default();
+            })
+        }
+    }
+
+    fn get(router: &RouterService, deadline_header: Option<&str>) -> Response {
+        block_on(router.oneshot(proxy_request(deadline_header))).expect("response")
+    }
+
+    fn proxy_request(deadline_header: Option<&str>) -> Request {
+        let mut builder = request_builder().method(Method::GET).uri("/proxy");
+        if let Some(value) = deadline_header {
+            builder = builder.header(DEADLINE_HEADER, value);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    fn proxy_router(recorded: &Arc<Mutex<Vec<Option<Duration>>>>) -> RouterService {
+        let client: Arc<dyn ProxyClient> = Arc::new(RecordingClient(Arc::clone(recorded)));
+        RouterService::builder()
+            .middleware(DeadlineMiddleware::new(Duration::from_secs(5)))
+            .get("/proxy", move |ctx: RequestContext| {
+                let proxy = ProxyHandle::new(Arc::clone(&client));
+                async move {
+                    let mut outbound =
+                        ProxyRequest::new(Method::GET, Uri::from_static("https://origin"));
+                    outbound.set_timeout(Duration::from_secs(30));
+                    outbound.apply_deadline(&ctx);
+                    proxy.forward(outbound).await
+                }
+            })
+            .build()
+    }
+
+    #[test]
+    fn cap_uses_the_smaller_of_timeout_and_remaining() {
+        let deadline = Deadline::after(Duration::from_millis(200));
+        assert!(deadline.cap(Duration::from_secs(10)) <= Duration::from_millis(200));
+        assert_eq!(
+            deadline.cap(Duration::from_millis(1)),
+            Duration::from_millis(1)
+        );
+    }
+
+    #[test]
+    fn expired_incoming_deadline_is_rejected() {
+        let recorded = Arc::new(Mutex::new(Vec::new()));
+        let response = get(&proxy_router(&recorded), Some("0"));
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(recorded.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn header_can_shorten_but_not_extend_the_default_budget() {
+        let mut headers = HeaderMap::new();
+        headers.insert(DEADLINE_HEADER, "250".parse().unwrap());
+        let incoming = Deadline::from_headers(&headers).expect("parsed");
+        assert!(incoming.remaining() <= Duration::from_millis(250));
+
+        let recorded = Arc::new(Mutex::new(Vec::new()));
+        get(&proxy_router(&recorded), Some("3600000"));
+        let timeout = recorded.lock().unwrap()[0].expect("timeout");
+        assert!(timeout <= Duration::from_secs(5), "{timeout:?}");
+    }
+
+    #[test]
+    fn malformed_header_falls_back_to_default_budget() {
+        let mut headers = HeaderMap::new();
+        headers.insert(DEADLINE_HEADER, "soon".parse().unwrap());
+        assert_eq!(Deadline::from_headers(&headers), None);
+    }
+
+    #[test]
+    fn outbound_proxy_timeout_is_capped_by_remaining_deadline() {
+        let recorded = Arc::new(Mutex::new(Vec::new()));
+        get(&proxy_router(&recorded), Some("100"));
+
+        let timeout = recorded.lock().unwrap()[0].expect("timeout");
+        assert!(timeout <= Duration::from_millis(100), "{timeout:?}");
+    }
+
+    #[test]
+    fn route_timeout_answers_before_a_slow_handler_finishes() {
+        let handler_time = Duration::from_secs(10);
+        let router = RouterService::builder()
+            .route_with_timeout(
+                "/slow",
+                Method::GET,
+                move |ctx: RequestContext| async move {
+                    let timer = ctx
+                        .request()
+                        .extensions()
+                        .get::<SleepHandle>()
+                        .cloned()
+                        .expect("timer");
+                    timer.sleep(handler_time).await;
+                    Ok::<_, EdgeError>("finished")
+                },
+                Duration::from_millis(20),
+            )
+            .build();
+        let mut request = request_builder()
+            .method(Method::GET)
+            .uri("/slow")
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(SleepHandle::with_sleep(ThreadSleep));
+
+        let started = Instant::now();
+        let response = block_on(router.oneshot(request)).expect("response");
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert!(started.elapsed() < handler_time, "{:?}", started.elapsed());
+    }
+}
@@ -8,7 +8,7 @@ use crate::body::Body;
 use crate::config_store::ConfigStoreError;
 use crate::http::{
     HeaderValue, Method, Response, StatusCode,
-    header::{CONTENT_TYPE, RETRY_AFTER},
+    header::{ALLOW, CONTENT_TYPE, RETRY_AFTER},
 };
 use crate::response::{IntoResponse, response_with_body};
 
@@ -16,6 +16,11 @@ use crate::response::{IntoResponse, response_with_body};
 #[derive(Debug, Error)]
 #[non_exhaustive]
 pub enum EdgeError {
+    /// An upstream service could not be reached or sent back an unusable
+    /// response, e.g. a refused connection from a proxied request. HTTP
+    /// 502, kind `"bad_gateway"`.
+    #[error("bad gateway: {message}")]
+    BadGateway { message: String },
     #[error("{message}")]
     BadRequest { message: String },
     /// The blob's `data` shape disagrees with the deployed `C`
@@ -24,24 +29,105 @@ pub enum EdgeError {
     /// `"config_out_of_date"`, carries `Retry-After: 60`.
     #[error("config out of date: {message}")]
     ConfigOutOfDate { message: String, field_path: String },
+    /// The request conflicts with the current state of the resource, e.g.
+    /// a duplicate create. HTTP 409.
+    #[error("conflict: {message}")]
+    Conflict { message: String },
+    /// The caller is known but may not do this. HTTP 403.
+    #[error("forbidden: {message}")]
+    Forbidden { message: String },
+    /// The route's timeout elapsed before its handler produced a response.
+    /// HTTP 504, kind `"gateway_timeout"`.
+    #[error("gateway timeout: {message}")]
+    GatewayTimeout { message: String },
     #[error("internal error: {source}")]
     Internal {
         #[from]
         source: AnyError,
     },
+    /// The path matched a route, but not for this method. `allowed` is the
+    /// sorted, comma-separated method list (see
+    /// [`EdgeError::allowed_methods`]); `route` is the matched template when
+    /// the router knows it.
     #[error("method {method} not allowed; allowed: {allowed}")]
-    MethodNotAllowed { method: Method, allowed: String },
+    MethodNotAllowed {
+        method: Method,
+        allowed: String,
+        route: Option<String>,
+    },
+    /// A body extractor ran on a request that has no body, as opposed to
+    /// a body that failed to parse. HTTP 400, kind `"missing_body"`.
+    #[error("missing body: {message}")]
+    MissingBody { message: String },
     #[error("no route matched path: {path}")]
     NotFound { path: String },
     #[error("not implemented: {message}")]
     NotImplemented { message: String },
+    /// The request body is larger than the configured
+    /// [`BodyLimit`](crate::body_limit::BodyLimit). HTTP 413.
+    #[error("payload too large: {message}")]
+    PayloadTooLarge { message: String },
+    /// A conditional request header (e.g. `If-Match`) did not hold for
+    /// the current resource state. HTTP 412.
+    #[error("precondition failed: {message}")]
+    PreconditionFailed { message: String },
     #[error("service unavailable: {message}")]
     ServiceUnavailable { message: String },
+    /// The caller has sent too many requests, e.g. past a rate limit.
+    /// HTTP 429.
+    #[error("too many requests: {message}")]
+    TooManyRequests { message: String },
+    /// The request lacks valid credentials. HTTP 401.
+    #[error("unauthorized: {message}")]
+    Unauthorized { message: String },
+    /// The request target is longer than the router accepts, e.g. a query
+    /// string over the [`QueryLimit`](crate::extractor::QueryLimit). HTTP 414.
+    #[error("uri too long: {message}")]
+    UriTooLong { message: String },
     #[error("validation error: {message}")]
     Validation { message: String },
 }
 
 impl EdgeError {
+    /// Methods the matched path does accept, for a `MethodNotAllowed` error,
+    /// sorted by name.
+    #[must_use]
+    #[inline]
+    pub fn allowed_methods(&self) -> Option<Vec<Method>> {
+        match self {
+            EdgeError::MethodNotAllowed { allowed, .. } => Some(
+                allowed
+                    .split(", ")
+                    .filter_map(|name| Method::from_bytes(name.as_bytes()).ok())
+                    .collect(),
+            ),
+            EdgeError::BadGateway { .. }
+            | EdgeError::BadRequest { .. }
+            | EdgeError::ConfigOutOfDate { .. }
+            | EdgeError::Conflict { .. }
+            | EdgeError::Forbidden { .. }
+            | EdgeError::GatewayTimeout { .. }
+            | EdgeError::Internal { .. }
+            | EdgeError::MissingBody { .. }
+            | EdgeError::NotFound { .. }
+            | EdgeError::NotImplemented { .. }
+            | EdgeError::PayloadTooLarge { .. }
+            | EdgeError::PreconditionFailed { .. }
+            | EdgeError::ServiceUnavailable { .. }
+            | EdgeError::TooManyRequests { .. }
+            | EdgeError::Unauthorized { .. }
+            | EdgeError::UriTooLong { .. }
+            | EdgeError::Validation { .. } => None,
+        }
+    }
+
+    #[inline]
+    pub fn bad_gateway<S: Into<String>>(message: S) -> Self {
+        EdgeError::BadGateway {
+            message: message.into(),
+        }
+    }
+
     #[inline]
     pub fn bad_request<S: Into<String>>(message: S) -> Self {
         EdgeError::BadRequest {
@@ -76,6 +162,27 @@ impl EdgeError {
         }
     }
 
+    #[inline]
+    pub fn conflict<S: Into<String>>(message: S) -> Self {
+        EdgeError::Conflict {
+            message: message.into(),
+        }
+    }
+
+    #[inline]
+    pub fn forbidden<S: Into<String>>(message: S) -> Self {
+        EdgeError::Forbidden {
+            message: message.into(),
+        }
+    }
+
+    #[inline]
+    pub fn gateway_timeout<S: Into<String>>(message: S) -> Self {
+        EdgeError::GatewayTimeout {
+            message: message.into(),
+        }
+    }
+
     /// Typed access to the wrapped [`AnyError`] for `EdgeError::Internal`.
     ///
     /// Renamed away from `source` to avoid shadowing
@@ -87,13 +194,23 @@ impl EdgeError {
     pub fn inner(&self) -> Option<&AnyError> {
         match self {
             EdgeError::Internal { source } => Some(source),
-            EdgeError::BadRequest { .. }
+            EdgeError::BadGateway { .. }
+            | EdgeError::BadRequest { .. }
             | EdgeError::ConfigOutOfDate { .. }
+            | EdgeError::Conflict { .. }
+            | EdgeError::Forbidden { .. }
+            | EdgeError::GatewayTimeout { .. }
             | EdgeError::NotFound { .. }
             | EdgeError::NotImplemented { .. }
             | EdgeError::MethodNotAllowed { .. }
+            | EdgeError::MissingBody { .. }
+            | EdgeError::PayloadTooLarge { .. }
+            | EdgeError::PreconditionFailed { .. }
             | EdgeError::Validation { .. }
-            | EdgeError::ServiceUnavailable { .. } => None,
+            | EdgeError::ServiceUnavailable { .. }
+            | EdgeError::TooManyRequests { .. }
+            | EdgeError::Unauthorized { .. }
+            | EdgeError::UriTooLong { .. } => None,
         }
     }
 
@@ -109,13 +226,23 @@ impl EdgeError {
 
     fn kind_str(&self) -> &'static str {
         match self {
+            EdgeError::BadGateway { .. } => "bad_gateway",
             EdgeError::BadRequest { .. } => "bad_request",
             EdgeError::ConfigOutOfDate { .. } => "config_out_of_date",
+            EdgeError::Conflict { .. } => "conflict",
+            EdgeError::Forbidden { .. } => "forbidden",
+            EdgeError::GatewayTimeout { .. } => "gateway_timeout",
             EdgeError::Internal { .. } => "internal",
             EdgeError::MethodNotAllowed { .. } => "method_not_allowed",
+            EdgeError::MissingBody { .. } => "missing_body",
             EdgeError::NotFound { .. } => "not_found",
             EdgeError::NotImplemented { .. } => "not_implemented",
+            EdgeError::PayloadTooLarge { .. } => "payload_too_large",
+            EdgeError::PreconditionFailed { .. } => "precondition_failed",
             EdgeError::ServiceUnavailable { .. } => "service_unavailable",
+            EdgeError::TooManyRequests { .. } => "too_many_requests",
+            EdgeError::Unauthorized { .. } => "unauthorized",
+            EdgeError::UriTooLong { .. } => "uri_too_long",
             EdgeError::Validation { .. } => "validation",
         }
     }
@@ -124,13 +251,25 @@ impl EdgeError {
     #[inline]
     pub fn message(&self) -> String {
         match self {
-            EdgeError::BadRequest { message }
+            EdgeError::BadGateway { message }
+            | EdgeError::BadRequest { message }
             | EdgeError::ConfigOutOfDate { message, .. }
+            | EdgeError::Conflict { message }
+            | EdgeError::Forbidden { message }
+            | EdgeError::GatewayTimeout { message }
+            | EdgeError::MissingBody { message }
             | EdgeError::Validation { message }
             | EdgeError::NotImplemented { message }
-            | EdgeError::ServiceUnavailable { message } => message.clone(),
+            | EdgeError::PayloadTooLarge { message }
+            | EdgeError::PreconditionFailed { message }
+            | EdgeError::ServiceUnavailable { message }
+            | EdgeError::TooManyRequests { message }
+            | EdgeError::Unauthorized { message }
+            | EdgeError::UriTooLong { message } => message.clone(),
             EdgeError::NotFound { path } => format!("no route matched path: {path}"),
-            EdgeError::MethodNotAllowed { method, allowed } => {
+            EdgeError::MethodNotAllowed {
+                method, allowed, ..
+            } => {
                 format!("method {method} not allowed; allowed: {allowed}")
             }
             EdgeError::Internal { source } => format!("internal error: {source}"),
@@ -153,6 +292,30 @@ impl EdgeError {
         EdgeError::MethodNotAllowed {
             method: method.clone(),
             allowed: allowed_list,
+            route: None,
+        }
+    }
+
+    /// [`Self::method_not_allowed`] for a path that matched the route
+    /// template `route`.
+    #[must_use]
+    #[inline]
+    pub fn method_not_allowed_for(method: &Method, allowed: &[Method], route: &str) -> Self {
+        let mut err = Self::method_not_allowed(method, allowed);
+        if let EdgeError::MethodNotAllowed {
+            route: ref mut slot,
+            ..
+        } = err
+        {
+            *slot = Some(route.to_owned());
+        }
+        err
+    }
+
+    #[inline]
+    pub fn missing_body<S: Into<String>>(message: S) -> Self {
+        EdgeError::MissingBody {
+            message: message.into(),
         }
     }
 
@@ -168,6 +331,47 @@ impl EdgeError {
         }
     }
 
+    #[inline]
+    pub fn payload_too_large<S: Into<String>>(message: S) -> Self {
+        EdgeError::PayloadTooLarge {
+            message: message.into(),
+        }
+    }
+
+    #[inline]
+    pub fn precondition_failed<S: Into<String>>(message: S) -> Self {
+        EdgeError::PreconditionFailed {
+            message: message.into(),
+        }
+    }
+
+    /// Template of the route whose path matched, for a `MethodNotAllowed`
+    /// error raised by the router (e.g. `/users/{id}`).
+    #[must_use]
+    #[inline]
+    pub fn route_template(&self) -> Option<&str> {
+        match self {
+            EdgeError::MethodNotAllowed { route, .. } => route.as_deref(),
+            EdgeError::BadGateway { .. }
+            | EdgeError::BadRequest { .. }
+            | EdgeError::ConfigOutOfDate { .. }
+            | EdgeError::Conflict { .. }
+            | EdgeError::Forbidden { .. }
+            | EdgeError::GatewayTimeout { .. }
+            | EdgeError::Internal { .. }
+            | EdgeError::MissingBody { .. }
+            | EdgeError::NotFound { .. }
+            | EdgeError::NotImplemented { .. }
+            | EdgeError::PayloadTooLarge { .. }
+            | EdgeError::PreconditionFailed { .. }
+            | EdgeError::ServiceUnavailable { .. }
+            | EdgeError::TooManyRequests { .. }
+            | EdgeError::Unauthorized { .. }
+            | EdgeError::UriTooLong { .. }
+            | EdgeError::Validation { .. } => None,
+        }
+    }
+
     #[inline]
     pub fn service_unavailable<S: Into<String>>(message: S) -> Self {
         EdgeError::ServiceUnavailable {
@@ -179,18 +383,48 @@ impl EdgeError {
     #[inline]
     pub fn status(&self) -> StatusCode {
         match self {
-            EdgeError::BadRequest { .. } => StatusCode::BAD_REQUEST,
+            EdgeError::BadRequest { .. } | EdgeError::MissingBody { .. } => StatusCode::BAD_REQUEST,
             EdgeError::ConfigOutOfDate { .. } | EdgeError::ServiceUnavailable { .. } => {
                 StatusCode::SERVICE_UNAVAILABLE
             }
+            EdgeError::Unauthorized { .. } => StatusCode::UNAUTHORIZED,
+            EdgeError::Forbidden { .. } => StatusCode::FORBIDDEN,
+            EdgeError::Conflict { .. } => StatusCode::CONFLICT,
+            EdgeError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
             EdgeError::Validation { .. } => StatusCode::UNPROCESSABLE_ENTITY,
             EdgeError::NotFound { .. } => StatusCode::NOT_FOUND,
             EdgeError::MethodNotAllowed { .. } => StatusCode::METHOD_NOT_ALLOWED,
             EdgeError::NotImplemented { .. } => StatusCode::NOT_IMPLEMENTED,
+            EdgeError::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            EdgeError::UriTooLong { .. } => StatusCode::URI_TOO_LONG,
+            EdgeError::PreconditionFailed { .. } => StatusCode::PRECONDITION_FAILED,
+            EdgeError::BadGateway { .. } => StatusCode::BAD_GATEWAY,
+            EdgeError::GatewayTimeout { .. } => StatusCode::GATEWAY_TIMEOUT,
             EdgeError::Internal { .. } => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
+    #[inline]
+    pub fn too_many_requests<S: Into<String>>(message: S) -> Self {
+        EdgeError::TooManyRequests {
+            message: message.into(),
+        }
+    }
+
+    #[inline]
+    pub fn unauthorized<S: Into<String>>(message: S) -> Self {
+        EdgeError::Unauthorized {
+            message: message.into(),
+        }
+    }
+
+    #[inline]
+    pub fn uri_too_long<S: Into<String>>(message: S) -> Self {
+        EdgeError::UriTooLong {
+            message: message.into(),
+        }
+    }
+
     #[inline]
     pub fn validation<S: Into<String>>(message: S) -> Self {
         EdgeError::Validation {
@@ -222,13 +456,23 @@ impl IntoResponse for EdgeError {
             EdgeError::ConfigOutOfDate { field_path, .. } if !field_path.is_empty() => {
                 Some(field_path.as_str())
             }
-            EdgeError::BadRequest { .. }
+            EdgeError::BadGateway { .. }
+            | EdgeError::BadRequest { .. }
             | EdgeError::ConfigOutOfDate { .. }
+            | EdgeError::Conflict { .. }
+            | EdgeError::Forbidden { .. }
+            | EdgeError::GatewayTimeout { .. }
             | EdgeError::Internal { .. }
             | EdgeError::MethodNotAllowed { .. }
+            | EdgeError::MissingBody { .. }
             | EdgeError::NotFound { .. }
             | EdgeError::NotImplemented { .. }
+            | EdgeError::PayloadTooLarge { .. }
+            | EdgeError::PreconditionFailed { .. }
             | EdgeError::ServiceUnavailable { .. }
+            | EdgeError::TooManyRequests { .. }
+            | EdgeError::Unauthorized { .. }
+            | EdgeError::UriTooLong { .. }
             | EdgeError::Validation { .. } => None,
         };
         let status = self.status();
@@ -253,10 +497,25 @@ impl IntoResponse for EdgeError {
                 .headers_mut()
                 .insert(RETRY_AFTER, HeaderValue::from_static("60"));
         }
+        if let Some(allowed) = allow_header(&self) {
+            response.headers_mut().insert(ALLOW, allowed);
+        }
         Ok(response)
     }
 }
 
+/// The `Allow` header a `405` must carry, listing every accepted method,
+/// extension methods included. Empty when none is.
+fn allow_header(err: &EdgeError) -> Option<HeaderValue> {
+    let allowed = err.allowed_methods()?;
+    let list = allowed
+        .iter()
+        .map(Method::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+    HeaderValue::try_from(list).ok()
+}
+
 fn json_or_text<T: Serialize>(payload: &T) -> Body {
     Body::json(payload).unwrap_or_else(|_| Body::text("internal error"))
 }
@@ -286,12 +545,22 @@ mod tests {
                 assert_eq!(message, "missing field");
                 assert_eq!(field_path, "feature.new_checkout");
             }
-            EdgeError::BadRequest { .. }
+            EdgeError::BadGateway { .. }
+            | EdgeError::BadRequest { .. }
+            | EdgeError::Conflict { .. }
+            | EdgeError::Forbidden { .. }
+            | EdgeError::GatewayTimeout { .. }
             | EdgeError::Internal { .. }
             | EdgeError::MethodNotAllowed { .. }
+            | EdgeError::MissingBody { .. }
             | EdgeError::NotFound { .. }
             | EdgeError::NotImplemented { .. }
+            | EdgeError::PayloadTooLarge { .. }
+            | EdgeError::PreconditionFailed { .. }
             | EdgeError::ServiceUnavailable { .. }
+            | EdgeError::TooManyRequests { .. }
+            | EdgeError::Unauthorized { .. }
+            | EdgeError::UriTooLong { .. }
             | EdgeError::Validation { .. } => panic!("expected ConfigOutOfDate"),
         }
     }
@@ -336,12 +605,22 @@ mod tests {
             EdgeError::ConfigOutOfDate { field_path, .. } => {
                 assert_eq!(field_path, expected_path);
             }
-            EdgeError::BadRequest { .. }
+            EdgeError::BadGateway { .. }
+            | EdgeError::BadRequest { .. }
+            | EdgeError::Conflict { .. }
+            | EdgeError::Forbidden { .. }
+            | EdgeError::GatewayTimeout { .. }
             | EdgeError::Internal { .. }
             | EdgeError::MethodNotAllowed { .. }
+            | EdgeError::MissingBody { .. }
             | EdgeError::NotFound { .. }
             | EdgeError::NotImplemented { .. }
+            | EdgeError::PayloadTooLarge { .. }
+            | EdgeError::PreconditionFailed { .. }
             | EdgeError::ServiceUnavailable { .. }
+            | EdgeError::TooManyRequests { .. }
+            | EdgeError::Unauthorized { .. }
+            | EdgeError::UriTooLong { .. }
             | EdgeError::Validation { .. } => panic!("expected ConfigOutOfDate"),
         }
     }
@@ -375,12 +654,22 @@ mod tests {
                     "field_path should match serde_path_to_error sentinel"
                 );
             }
-            EdgeError::BadRequest { .. }
+            EdgeError::BadGateway { .. }
+            | EdgeError::BadRequest { .. }
+            | EdgeError::Conflict { .. }
+            | EdgeError::Forbidden { .. }
+            | EdgeError::GatewayTimeout { .. }
             | EdgeError::Internal { .. }
             | EdgeError::MethodNotAllowed { .. }
+            | EdgeError::MissingBody { .. }
             | EdgeError::NotFound { .. }
             | EdgeError::NotImplemented { .. }
+            | EdgeError::PayloadTooLarge { .. }
+            | EdgeError::PreconditionFailed { .. }
             | EdgeError::ServiceUnavailable { .. }
+            | EdgeError::TooManyRequests { .. }
+            | EdgeError::Unauthorized { .. }
+            | EdgeError::UriTooLong { .. }
             | EdgeError::Validation { .. } => panic!("expected ConfigOutOfDate"),
         }
     }
@@ -468,6 +757,38 @@ mod tests {
         let err = EdgeError::method_not_allowed(&Method::POST, &[Method::GET, Method::DELETE]);
         assert_eq!(err.status(), StatusCode::METHOD_NOT_ALLOWED);
         assert!(err.message().contains("allowed: DELETE, GET"));
+        assert_eq!(
+            err.allowed_methods(),
+            Some(vec![Method::DELETE, Method::GET])
+        );
+        assert_eq!(
+            EdgeError::method_not_allowed(&Method::GET, &[]).allowed_methods(),
+            Some(Vec::new())
+        );
+    }
+
+    #[test]
+    fn bad_gateway_sets_status_and_message() {
+        let err = EdgeError::bad_gateway("upstream refused the connection");
+        assert_eq!(err.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(err.message(), "upstream refused the connection");
+        assert!(err.inner().is_none());
+    }
+
+    #[test]
+    fn gateway_timeout_sets_status_and_message() {
+        let err = EdgeError::gateway_timeout("route timed out after 50ms");
+        assert_eq!(err.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(err.message(), "route timed out after 50ms");
+        assert!(err.inner().is_none());
+    }
+
+    #[test]
+    fn missing_body_sets_status_and_message() {
+        let err = EdgeError::missing_body("request body is required");
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(err.message(), "request body is required");
+        assert!(err.inner().is_none());
     }
 
     #[test]
@@ -477,6 +798,20 @@ mod tests {
         assert!(err.message().contains("/missing"));
     }
 
+    #[test]
+    fn payload_too_large_sets_status_and_message() {
+        let err = EdgeError::payload_too_large("request body exceeds 10 bytes");
+        assert_eq!(err.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(err.message(), "request body exceeds 10 bytes");
+    }
+
+    #[test]
+    fn precondition_failed_sets_status_and_message() {
+        let err = EdgeError::precondition_failed("etag mismatch");
+        assert_eq!(err.status(), StatusCode::PRECONDITION_FAILED);
+        assert_eq!(err.message(), "etag mismatch");
+    }
+
     #[test]
     fn service_unavailable_sets_status_and_message() {
         let err = EdgeError::service_unavailable("config store unavailable");
@@ -484,6 +819,44 @@ mod tests {
         assert_eq!(err.message(), "config store unavailable");
     }
 
+    #[test]
+    fn client_error_constructors_map_to_their_statuses() {
+        let cases = [
+            (
+                EdgeError::unauthorized("x"),
+                StatusCode::UNAUTHORIZED,
+                "unauthorized",
+            ),
+            (
+                EdgeError::forbidden("x"),
+                StatusCode::FORBIDDEN,
+                "forbidden",
+            ),
+            (EdgeError::conflict("x"), StatusCode::CONFLICT, "conflict"),
+            (
+                EdgeError::too_many_requests("x"),
+                StatusCode::TOO_MANY_REQUESTS,
+                "too_many_requests",
+            ),
+            (
+                EdgeError::uri_too_long("x"),
+                StatusCode::URI_TOO_LONG,
+                "uri_too_long",
+            ),
+        ];
+        for (err, status, kind) in cases {
+            assert_eq!(err.status(), status, "{err}");
+            assert_eq!(err.message(), "x");
+            assert!(err.inner().is_none());
+            let body = parse_body(err.into_response().expect("response"));
+            assert_eq!(body["error"]["kind"], kind);
+        }
+        assert_eq!(
+            EdgeError::unauthorized("missing token").to_string(),
+            "unauthorized: missing token"
+        );
+    }
+
     #[test]
     fn validation_sets_status_and_message() {
         let err = EdgeError::validation("invalid input");
@@ -535,8 +908,16 @@ mod tests {
             "method_not_allowed",
             405_u16
         );
+        assert_kind!(EdgeError::bad_gateway("x"), "bad_gateway", 502_u16);
+        assert_kind!(EdgeError::gateway_timeout("x"), "gateway_timeout", 504_u16);
+        assert_kind!(EdgeError::missing_body("x"), "missing_body", 400_u16);
         assert_kind!(EdgeError::not_found("/x"), "not_found", 404_u16);
         assert_kind!(EdgeError::not_implemented("x"), "not_implemented", 501_u16);
+        assert_kind!(
+            EdgeError::precondition_failed("x"),
+            "precondition_failed",
+            412_u16
+        );
         assert_kind!(
             EdgeError::service_unavailable("x"),
             "service_unavailable",
@@ -1,7 +1,10 @@
 use std::any;
 use std::future::Future;
+use std::io::Read;
+use std::net::IpAddr;
 use std::ops::{Deref, DerefMut};
 use std::pin::Pin;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use http::header;
@@ -10,22 +13,52 @@ use validator::Validate;
 
 use crate::app_config::{AppConfigMeta, SecretField, SecretKind, SecretPathSegment};
 use crate::blob_envelope::BlobEnvelope;
+use crate::body_spool;
 use crate::config_store::ConfigStoreHandle;
+use crate::connection::ConnectionInfo;
 use crate::context::RequestContext;
 use crate::error::EdgeError;
-use crate::http::HeaderMap;
+use crate::forwarded::{TrustedProxies, X_FORWARDED_HOST, X_FORWARDED_PROTO};
+use crate::http::{HeaderMap, Method, Uri, Version};
 use crate::secret_store::SecretError;
 use crate::store_registry::{
     BoundConfigStore, BoundKvStore, BoundSecretStore, ConfigRegistry, ConfigStoreBinding,
     KvRegistry, SecretRegistry,
 };
+use crate::unknown_fields;
 use serde::de::IntoDeserializer as _;
 
+/// Implements [`FromRequest`] for a tuple of extractors, which runs them in
+/// order and returns the first error.
+macro_rules! impl_from_request_for_tuple {
+    ($($extractor:ident),+) => {
+        #[async_trait(?Send)]
+        impl<$($extractor),+> FromRequest for ($($extractor,)+)
+        where
+            $($extractor: FromRequest,)+
+        {
+            #[inline]
+            async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+                Ok(($($extractor::from_request(ctx).await?,)+))
+            }
+        }
+    };
+}
+
 #[async_trait(?Send)]
 pub trait FromRequest: Sized {
     async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError>;
 }
 
+impl_from_request_for_tuple!(T1);
+impl_from_request_for_tuple!(T1, T2);
+impl_from_request_for_tuple!(T1, T2, T3);
+impl_from_request_for_tuple!(T1, T2, T3, T4);
+impl_from_request_for_tuple!(T1, T2, T3, T4, T5);
+impl_from_request_for_tuple!(T1, T2, T3, T4, T5, T6);
+impl_from_request_for_tuple!(T1, T2, T3, T4, T5, T6, T7);
+impl_from_request_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8);
+
 pub struct Json<T>(pub T);
 
 #[async_trait(?Send)]
@@ -35,6 +68,7 @@ where
 {
     #[inline]
     async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+        ctx.buffer_body().await?;
         ctx.json().map(Json)
     }
 }
@@ -64,6 +98,189 @@ impl<T> Json<T> {
 
 pub struct ValidatedJson<T>(pub T);
 
+/// Size and nesting limits enforced by the [`Json`] and [`ValidatedJson`]
+/// extractors before deserializing.
+///
+/// Register once with [`crate::router::RouterBuilder::with_json_limits`] or
+/// [`crate::app::App::with_json_limits`]; requests whose JSON body exceeds
+/// either limit are rejected with `400 Bad Request`. Without a registered
+/// value only `serde_json`'s own recursion limit applies.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct JsonLimits {
+    max_depth: usize,
+    max_length: usize,
+}
+
+impl JsonLimits {
+    /// Check `bytes` against both limits without parsing them.
+    ///
+    /// # Errors
+    /// Returns [`EdgeError::bad_request`] when the payload is longer than
+    /// `max_length` bytes or nests arrays/objects deeper than `max_depth`.
+    #[inline]
+    pub fn check(&self, bytes: &[u8]) -> Result<(), EdgeError> {
+        self.check_length(bytes.len())?;
+        JsonNesting::default().scan(bytes, self.max_depth)
+    }
+
+    fn check_length(&self, length: usize) -> Result<(), EdgeError> {
+        if length > self.max_length {
+            return Err(EdgeError::bad_request(format!(
+                "JSON payload exceeds {} bytes",
+                self.max_length
+            )));
+        }
+        Ok(())
+    }
+
+    /// [`Self::check`] for a `length`-byte payload read from `reader`, a
+    /// chunk at a time.
+    pub(crate) fn check_reader<R: Read>(
+        &self,
+        length: usize,
+        mut reader: R,
+    ) -> Result<(), EdgeError> {
+        self.check_length(length)?;
+        let mut nesting = JsonNesting::default();
+        let mut chunk = [0_u8; 8 * 1024];
+        loop {
+            let read = reader
+                .read(&mut chunk)
+                .map_err(|err| body_spool::read_error(&err))?;
+            if read == 0 {
+                return Ok(());
+            }
+            nesting.scan(chunk.get(..read).unwrap_or_default(), self.max_depth)?;
+        }
+    }
+
+    /// Maximum nesting of arrays and objects.
+    #[must_use]
+    #[inline]
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Maximum payload length in bytes.
+    #[must_use]
+    #[inline]
+    pub fn max_length(mut self, length: usize) -> Self {
+        self.max_length = length;
+        self
+    }
+
+    /// Limits of 128 levels of nesting (matching `serde_json`'s recursion
+    /// limit) and 1 MiB of payload.
+    #[must_use]
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            max_depth: 128,
+            max_length: 1024 * 1024,
+        }
+    }
+}
+
+impl Default for JsonLimits {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How deeply the JSON scanned so far nests arrays and objects, carried from
+/// one chunk to the next.
+#[derive(Default)]
+struct JsonNesting {
+    depth: usize,
+    escaped: bool,
+    in_string: bool,
+}
+
+impl JsonNesting {
+    fn scan(&mut self, bytes: &[u8], max_depth: usize) -> Result<(), EdgeError> {
+        for byte in bytes {
+            if self.in_string {
+                match (self.escaped, byte) {
+                    (true, _) => self.escaped = false,
+                    (false, b'\\') => self.escaped = true,
+                    (false, b'"') => self.in_string = false,
+                    (false, _) => {}
+                }
+                continue;
+            }
+            match byte {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => {
+                    self.depth = self.depth.saturating_add(1);
+                    if self.depth > max_depth {
+                        return Err(EdgeError::bad_request(format!(
+                            "JSON payload nests deeper than {max_depth} levels"
+                        )));
+                    }
+                }
+                b'}' | b']' => self.depth = self.depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Longest query string the [`Query`] and [`ValidatedQuery`] extractors
+/// (and [`RequestContext::query`]) will parse, in bytes.
+///
+/// Checked before the query is decoded, so a pathological query costs no
+/// parsing time or memory; longer ones are rejected with
+/// `414 URI Too Long`. Register with
+/// [`crate::router::RouterBuilder::with_query_limit`] or
+/// [`crate::app::App::with_query_limit`]; without one, the 16 KiB
+/// [`QueryLimit::default`] applies.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct QueryLimit {
+    max_length: usize,
+}
+
+impl QueryLimit {
+    /// Check `query` against the limit without decoding it.
+    ///
+    /// # Errors
+    /// Returns [`EdgeError::uri_too_long`] when `query` is longer than the
+    /// limit.
+    #[inline]
+    pub fn check(self, query: &str) -> Result<(), EdgeError> {
+        if query.len() > self.max_length {
+            return Err(EdgeError::uri_too_long(format!(
+                "query string exceeds {} bytes",
+                self.max_length
+            )));
+        }
+        Ok(())
+    }
+
+    /// The limit in bytes.
+    #[must_use]
+    #[inline]
+    pub const fn max_length(self) -> usize {
+        self.max_length
+    }
+
+    /// A limit of `max_length` bytes.
+    #[must_use]
+    #[inline]
+    pub const fn new(max_length: usize) -> Self {
+        Self { max_length }
+    }
+}
+
+impl Default for QueryLimit {
+    #[inline]
+    fn default() -> Self {
+        Self::new(16 * 1024)
+    }
+}
+
 #[async_trait(?Send)]
 impl<T> FromRequest for ValidatedJson<T>
 where
@@ -102,6 +319,57 @@ impl<T> ValidatedJson<T> {
     }
 }
 
+/// Like [`Json`], but rejects payloads carrying object keys that `T` does
+/// not declare, without `#[serde(deny_unknown_fields)]` on `T`.
+///
+/// Unknown keys are reported together with `422 Unprocessable Entity`, as
+/// dotted paths (`unknown fields: coupon, items[1].qty`). See
+/// [`crate::unknown_fields`] for which types are checked.
+pub struct StrictJson<T>(pub T);
+
+#[async_trait(?Send)]
+impl<T> FromRequest for StrictJson<T>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    #[inline]
+    async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+        let Json(value) = Json::<serde_json::Value>::from_request(ctx).await?;
+        let (parsed, unknown) = unknown_fields::from_value::<T>(value)
+            .map_err(|err| EdgeError::bad_request(format!("invalid JSON payload: {err}")))?;
+        if !unknown.is_empty() {
+            return Err(EdgeError::validation(format!(
+                "unknown fields: {}",
+                unknown.join(", ")
+            )));
+        }
+        Ok(StrictJson(parsed))
+    }
+}
+
+impl<T> Deref for StrictJson<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for StrictJson<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> StrictJson<T> {
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
 pub struct Headers(pub HeaderMap);
 
 #[async_trait(?Send)]
@@ -136,9 +404,58 @@ impl Headers {
     }
 }
 
+/// The request headers, for handlers that take a bare [`HeaderMap`] rather
+/// than the [`Headers`] wrapper.
+#[async_trait(?Send)]
+impl FromRequest for HeaderMap {
+    #[inline]
+    async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+        Ok(ctx.request().headers().clone())
+    }
+}
+
+/// The request method.
+///
+/// # Example
+/// ```ignore
+/// #[action]
+/// pub async fn handler(method: Method) -> Text<String> {
+///     Text::new(format!("called with {method}"))
+/// }
+/// ```
+#[async_trait(?Send)]
+impl FromRequest for Method {
+    #[inline]
+    async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+        Ok(ctx.request().method().clone())
+    }
+}
+
+/// The request URI, as the adapter received it.
+#[async_trait(?Send)]
+impl FromRequest for Uri {
+    #[inline]
+    async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+        Ok(ctx.request().uri().clone())
+    }
+}
+
+/// The request's HTTP version.
+#[async_trait(?Send)]
+impl FromRequest for Version {
+    #[inline]
+    async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+        Ok(ctx.request().version())
+    }
+}
+
 /// Extracts the host from the standard `Host` header.
 ///
-/// Falls back to "localhost" if the header is not present.
+/// Falls back to "localhost" if the header is not present. A request with
+/// more than one `Host` header is rejected with `400 Bad Request`, since
+/// servers and proxies that pick different ones can be used to smuggle
+/// requests past host-based routing. Header names are case-insensitive, so
+/// `Host` and `HOST` count as duplicates.
 ///
 /// # Example
 /// ```ignore
@@ -153,10 +470,7 @@ pub struct Host(pub String);
 impl FromRequest for Host {
     #[inline]
     async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
-        let headers = ctx.request().headers();
-        let host = headers
-            .get(header::HOST)
-            .and_then(|value| value.to_str().ok())
+        let host = unique_host(ctx.request().headers())?
             .unwrap_or("localhost")
             .to_owned();
         Ok(Host(host))
@@ -187,6 +501,12 @@ impl Host {
 /// 2. `Host` - standard HTTP host header
 /// 3. Falls back to "localhost" if neither is present
 ///
+/// When the router registers [`TrustedProxies`], `X-Forwarded-Host` is read
+/// the same way [`ClientIp`] reads `X-Forwarded-For`: only the entry added by
+/// the outermost trusted proxy is used, and none when no hop is trusted.
+/// Without a registered setting the first `X-Forwarded-Host` header is used,
+/// as set. Duplicate `Host` headers are rejected as they are by [`Host`].
+///
 /// Use this extractor when your application is behind a reverse proxy or load balancer.
 ///
 /// # Example
@@ -196,6 +516,8 @@ impl Host {
 ///     // host contains the effective hostname (X-Forwarded-Host or Host)
 /// }
 /// ```
+///
+/// [`TrustedProxies`]: crate::forwarded::TrustedProxies
 pub struct ForwardedHost(pub String);
 
 #[async_trait(?Send)]
@@ -203,12 +525,14 @@ impl FromRequest for ForwardedHost {
     #[inline]
     async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
         let headers = ctx.request().headers();
-        let host = headers
-            .get("x-forwarded-host")
-            .or_else(|| headers.get(header::HOST))
-            .and_then(|value| value.to_str().ok())
-            .unwrap_or("localhost")
-            .to_owned();
+        let host_header = unique_host(headers)?;
+        let forwarded = match ctx.request().extensions().get::<TrustedProxies>() {
+            Some(trust) => trust.forwarded(ctx, X_FORWARDED_HOST),
+            None => headers
+                .get(X_FORWARDED_HOST)
+                .and_then(|value| value.to_str().ok()),
+        };
+        let host = forwarded.or(host_header).unwrap_or("localhost").to_owned();
         Ok(ForwardedHost(host))
     }
 }
@@ -230,6 +554,114 @@ impl ForwardedHost {
     }
 }
 
+/// Extracts the IP address of the connecting client.
+///
+/// Reads `peer_addr` from the [`ConnectionInfo`] the adapter records, so it
+/// works the same on every platform: Axum's connect info, Fastly's client IP,
+/// Cloudflare's `CF-Connecting-IP` and Spin's `spin-client-addr`. Behind a
+/// reverse proxy this is the proxy's address, unless the router registers
+/// [`TrustedProxies`]; then it is the `X-Forwarded-For` entry added by the
+/// outermost trusted proxy.
+///
+/// Fails with `500 Internal Server Error` when the adapter reported no peer
+/// address and no trusted proxy named the client.
+///
+/// # Example
+/// ```ignore
+/// #[action]
+/// pub async fn handler(ClientIp(ip): ClientIp) -> Response {
+///     // ip is the client's IpAddr
+/// }
+/// ```
+///
+/// [`ConnectionInfo`]: crate::connection::ConnectionInfo
+/// [`TrustedProxies`]: crate::forwarded::TrustedProxies
+pub struct ClientIp(pub IpAddr);
+
+#[async_trait(?Send)]
+impl FromRequest for ClientIp {
+    #[inline]
+    async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+        TrustedProxies::of(ctx)
+            .client_ip(ctx)
+            .map(ClientIp)
+            .ok_or_else(|| {
+                EdgeError::internal(anyhow::anyhow!(
+                    "the adapter did not report a client address"
+                ))
+            })
+    }
+}
+
+impl Deref for ClientIp {
+    type Target = IpAddr;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl ClientIp {
+    #[must_use]
+    #[inline]
+    pub const fn into_inner(self) -> IpAddr {
+        self.0
+    }
+}
+
+/// Extracts the scheme the client used, such as `https`.
+///
+/// Reads `X-Forwarded-Proto` as the outermost trusted proxy set it when the
+/// router registers [`TrustedProxies`]. Otherwise it is the request URI's
+/// scheme, or `https` for a TLS connection and `http` for any other.
+///
+/// # Example
+/// ```ignore
+/// #[action]
+/// pub async fn handler(Scheme(scheme): Scheme) -> Response {
+///     // scheme is "https" or "http"
+/// }
+/// ```
+///
+/// [`TrustedProxies`]: crate::forwarded::TrustedProxies
+pub struct Scheme(pub String);
+
+#[async_trait(?Send)]
+impl FromRequest for Scheme {
+    #[inline]
+    async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+        let scheme = TrustedProxies::of(ctx)
+            .forwarded(ctx, X_FORWARDED_PROTO)
+            .or_else(|| ctx.request().uri().scheme_str())
+            .map_or_else(
+                || {
+                    let tls = ctx.connection_info().is_some_and(ConnectionInfo::is_tls);
+                    if tls { "https" } else { "http" }.to_owned()
+                },
+                str::to_ascii_lowercase,
+            );
+        Ok(Scheme(scheme))
+    }
+}
+
+impl Deref for Scheme {
+    type Target = String;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Scheme {
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
 pub struct Query<T>(pub T);
 
 #[async_trait(?Send)]
@@ -391,6 +823,7 @@ where
 {
     #[inline]
     async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+        ctx.buffer_body().await?;
         ctx.form().map(Form)
     }
 }
@@ -591,6 +1024,67 @@ impl<T> State<T> {
     }
 }
 
+/// Extractor for the app's typed settings, registered with
+/// [`RouterBuilder::with_settings`] (typically loaded from the manifest's
+/// `[settings]` table via [`ManifestLoader::settings`]).
+///
+/// ```ignore
+/// #[edgezero_core::action]
+/// async fn handle(Settings(settings): Settings<AppSettings>) -> Result<String, EdgeError> {
+///     Ok(settings.greeting.clone())
+/// }
+/// ```
+///
+/// [`RouterBuilder::with_settings`]: crate::router::RouterBuilder::with_settings
+/// [`ManifestLoader::settings`]: crate::manifest::ManifestLoader::settings
+pub struct Settings<T>(pub Arc<T>);
+
+impl<T> Clone for Settings<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+
+    #[inline]
+    fn clone_from(&mut self, source: &Self) {
+        self.0 = Arc::clone(&source.0);
+    }
+}
+
+#[async_trait(?Send)]
+impl<T> FromRequest for Settings<T>
+where
+    T: Send + Sync + 'static,
+{
+    #[inline]
+    async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+        ctx.extension::<Settings<T>>().ok_or_else(|| {
+            EdgeError::internal(anyhow::anyhow!(
+                "no `Settings<{}>` registered -- call RouterBuilder::with_settings(..) before build()",
+                any::type_name::<T>()
+            ))
+        })
+    }
+}
+
+impl<T> Deref for Settings<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> Settings<T> {
+    /// Consume the extractor and return the shared settings.
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> Arc<T> {
+        self.0
+    }
+}
+
 /// Extractor that yields the per-request [`SecretRegistry`].
 ///
 /// The returned [`BoundSecretStore`] is pre-bound to a platform store name
@@ -964,6 +1458,21 @@ fn resolve_secret_field<'walk>(
     })
 }
 
+/// The request's `Host` header, or `None` when it is absent or not visible
+/// ASCII.
+///
+/// # Errors
+/// Returns [`EdgeError::bad_request`] when the request carries more than one
+/// `Host` header.
+fn unique_host(headers: &HeaderMap) -> Result<Option<&str>, EdgeError> {
+    let mut values = headers.get_all(header::HOST).iter();
+    let first = values.next();
+    if values.next().is_some() {
+        return Err(EdgeError::bad_request("multiple Host headers"));
+    }
+    Ok(first.and_then(|value| value.to_str().ok()))
+}
+
 fn join_field(prefix: &str, name: &str) -> String {
     if prefix.is_empty() {
         name.to_owned()
@@ -1142,15 +1651,19 @@ fn first_violating_field(errors: &validator::ValidationErrors) -> Option<String>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::action;
     use crate::app_config::{AppConfigMeta, SecretField, SecretKind, SecretPathSegment};
     use crate::blob_envelope::BlobEnvelope;
     use crate::body::Body;
     use crate::config_store::{ConfigStore, ConfigStoreError, ConfigStoreHandle};
+    use crate::connection::ConnectionInfo;
     use crate::context::RequestContext;
     use crate::http::{HeaderValue, Method, StatusCode, request_builder};
     use crate::params::PathParams;
+    use crate::router::RouterService;
     use crate::secret_store::{InMemorySecretStore, NoopSecretStore, SecretHandle, SecretStore};
     use crate::store_registry::StoreRegistry;
+    use crate::test_client::TestClient;
     use futures::executor::block_on;
     use serde::{Deserialize, Serialize};
     use std::borrow::Cow;
@@ -1378,6 +1891,24 @@ mod tests {
         assert_eq!(err.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[test]
+    fn strict_json_accepts_an_exact_payload() {
+        let ctx = ctx(Body::from(r#"{"name":"demo"}"#), PathParams::default());
+        let payload = block_on(StrictJson::<Payload>::from_request(&ctx)).expect("strict json");
+        assert_eq!(payload.name, "demo");
+    }
+
+    #[test]
+    fn strict_json_rejects_unknown_fields_by_name() {
+        let body = Body::from(r#"{"admin":true,"name":"demo","role":"owner"}"#);
+        let ctx = ctx(body, PathParams::default());
+        let err = block_on(StrictJson::<Payload>::from_request(&ctx))
+            .err()
+            .expect("expected unknown fields");
+        assert_eq!(err.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(err.message(), "unknown fields: admin, role");
+    }
+
     #[test]
     fn validated_json_rejects_invalid_payloads() {
         let body = Body::json(&ValidatedPayload {
@@ -1391,6 +1922,47 @@ mod tests {
         assert_eq!(err.status(), StatusCode::UNPROCESSABLE_ENTITY);
     }
 
+    fn ctx_with_json_limits(body: &str, limits: JsonLimits) -> RequestContext {
+        let mut request = request_builder()
+            .method(Method::POST)
+            .uri("/test")
+            .body(Body::from(body.to_owned()))
+            .expect("request");
+        request.extensions_mut().insert(limits);
+        RequestContext::new(request, PathParams::default())
+    }
+
+    #[test]
+    fn json_limits_accept_payload_within_limits() {
+        let ctx = ctx_with_json_limits(
+            r#"{"name":"[[[{{{"}"#,
+            JsonLimits::new().max_depth(1).max_length(64),
+        );
+        let payload = block_on(Json::<Payload>::from_request(&ctx)).expect("json");
+        assert_eq!(payload.0.name, "[[[{{{");
+    }
+
+    #[test]
+    fn json_limits_reject_overly_deep_payload() {
+        let deep = format!("{}{}", "[".repeat(40), "]".repeat(40));
+        let ctx = ctx_with_json_limits(&deep, JsonLimits::new().max_depth(32));
+        let err = block_on(Json::<serde_json::Value>::from_request(&ctx))
+            .err()
+            .expect("expected depth error");
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+        assert!(err.message().contains("deeper than 32"));
+    }
+
+    #[test]
+    fn json_limits_reject_overly_long_payload() {
+        let ctx = ctx_with_json_limits(r#"{"name":"demo"}"#, JsonLimits::new().max_length(8));
+        let err = block_on(ValidatedJson::<ValidatedPayload>::from_request(&ctx))
+            .err()
+            .expect("expected length error");
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+        assert!(err.message().contains("exceeds 8 bytes"));
+    }
+
     #[test]
     fn path_extractor_reads_params() {
         let ctx = ctx(Body::empty(), params(&[("id", "7")]));
@@ -1398,6 +1970,49 @@ mod tests {
         assert_eq!(payload.0.id, "7");
     }
 
+    fn ctx_with_json_and_query(query: &str) -> RequestContext {
+        let request = request_builder()
+            .method(Method::POST)
+            .uri(format!("/test?{query}"))
+            .body(Body::from(r#"{"name":"demo"}"#))
+            .expect("request");
+        RequestContext::new(request, PathParams::default())
+    }
+
+    #[test]
+    fn tuple_extractor_runs_each_extractor() {
+        let ctx = ctx_with_json_and_query("page=2");
+        let (Json(payload), Query(query)) =
+            block_on(<(Json<Payload>, Query<QueryParams>)>::from_request(&ctx)).expect("tuple");
+        assert_eq!(payload.name, "demo");
+        assert_eq!(query.page, Some(2));
+    }
+
+    #[test]
+    fn tuple_extractor_stops_at_the_first_error() {
+        // The query fails validation (422) before the missing path param (400)
+        // is looked at.
+        let ctx = ctx_with_json_and_query("page=200");
+        let invalid_query = block_on(<(
+            Json<Payload>,
+            ValidatedQuery<ValidatedQueryParams>,
+            Path<PathPayload>,
+        )>::from_request(&ctx))
+        .err()
+        .expect("expected error");
+        assert_eq!(invalid_query.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let valid = ctx_with_json_and_query("page=20");
+        let missing_path = block_on(<(
+            Json<Payload>,
+            ValidatedQuery<ValidatedQueryParams>,
+            Path<PathPayload>,
+        )>::from_request(&valid))
+        .err()
+        .expect("expected error");
+        assert_eq!(missing_path.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[test]
     fn headers_extractor_clones_request_headers() {
         let mut ctx = ctx(Body::empty(), PathParams::default());
@@ -1414,6 +2029,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn header_map_and_version_extract_from_the_request() {
+        let mut ctx = ctx(Body::empty(), PathParams::default());
+        ctx.request_mut()
+            .headers_mut()
+            .insert("x-test", HeaderValue::from_static("value"));
+        *ctx.request_mut().version_mut() = Version::HTTP_2;
+
+        let headers = block_on(HeaderMap::from_request(&ctx)).expect("headers");
+        assert_eq!(headers.get("x-test").unwrap(), "value");
+        let version = block_on(Version::from_request(&ctx)).expect("version");
+        assert_eq!(version, Version::HTTP_2);
+    }
+
+    #[test]
+    fn handlers_can_take_the_method_directly() {
+        #[action]
+        async fn echo_method(method: Method) -> Result<String, EdgeError> {
+            Ok(method.to_string())
+        }
+
+        let router = RouterService::builder()
+            .get("/echo", echo_method)
+            .delete("/echo", echo_method)
+            .build();
+        let client = TestClient::new(router);
+        assert_eq!(block_on(client.get("/echo")).text(), "GET");
+        assert_eq!(block_on(client.delete("/echo")).text(), "DELETE");
+    }
+
+    #[test]
+    fn handlers_can_take_the_uri_directly() {
+        #[action]
+        async fn echo_uri(uri: Uri) -> Result<String, EdgeError> {
+            Ok(format!(
+                "{} {}",
+                uri.path(),
+                uri.query().unwrap_or_default()
+            ))
+        }
+
+        let router = RouterService::builder()
+            .get("/items/{id}", echo_uri)
+            .build();
+        let response = block_on(TestClient::new(router).get("/items/7?sort=asc"));
+        assert_eq!(response.text(), "/items/7 sort=asc");
+    }
+
     #[test]
     fn query_extractor_parses_params() {
         let ctx = ctx_with_query("page=5&q=hello");
@@ -1713,6 +2376,48 @@ mod tests {
         assert_eq!(host.0, "localhost");
     }
 
+    #[test]
+    fn duplicate_host_headers_are_rejected_whatever_their_case() {
+        let mut request = request_builder()
+            .method(Method::GET)
+            .uri("/test")
+            .header("Host", "example.com")
+            .header("HOST", "internal.local")
+            .header("x-forwarded-host", "example.com")
+            .body(Body::empty())
+            .expect("request");
+        request.extensions_mut().insert(TrustedProxies::Hops(1));
+        let ctx = RequestContext::new(request, PathParams::default());
+
+        let err = block_on(Host::from_request(&ctx))
+            .err()
+            .expect("ambiguous host");
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+        let forwarded = block_on(ForwardedHost::from_request(&ctx))
+            .err()
+            .expect("ambiguous host");
+        assert_eq!(forwarded.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn duplicate_forwarded_for_headers_are_read_as_one_list() {
+        let mut request = request_builder()
+            .method(Method::GET)
+            .uri("/test")
+            .header("X-Forwarded-For", "198.51.100.7, 203.0.113.9")
+            .header("x-forwarded-for", "10.0.0.2")
+            .body(Body::empty())
+            .expect("request");
+        request.extensions_mut().insert(ConnectionInfo {
+            peer_addr: Some("10.0.0.1:443".parse().expect("peer addr")),
+            ..ConnectionInfo::default()
+        });
+        request.extensions_mut().insert(TrustedProxies::Hops(2));
+        let ctx = RequestContext::new(request, PathParams::default());
+        let ip = block_on(ClientIp::from_request(&ctx)).expect("client ip");
+        assert_eq!(ip.to_string(), "203.0.113.9");
+    }
+
     #[test]
     fn host_deref_and_into_inner() {
         let host = Host("example.com".to_owned());
@@ -1775,6 +2480,36 @@ mod tests {
         assert_eq!(inner, "example.com");
     }
 
+    #[test]
+    fn client_ip_extractor_reads_the_peer_address() {
+        let mut request = request_builder()
+            .uri("/test")
+            .body(Body::empty())
+            .expect("request");
+        request.extensions_mut().insert(ConnectionInfo {
+            peer_addr: Some("203.0.113.7:0".parse().expect("peer addr")),
+            ..ConnectionInfo::default()
+        });
+        let ctx = RequestContext::new(request, PathParams::default());
+        let ip = block_on(ClientIp::from_request(&ctx)).expect("client ip");
+        assert_eq!(ip.into_inner(), IpAddr::from([203, 0, 113, 7]));
+    }
+
+    #[test]
+    fn client_ip_extractor_fails_without_a_peer_address() {
+        let ctx = RequestContext::new(
+            request_builder()
+                .uri("/test")
+                .body(Body::empty())
+                .expect("request"),
+            PathParams::default(),
+        );
+        let err = block_on(ClientIp::from_request(&ctx))
+            .err()
+            .expect("missing peer address");
+        assert_eq!(err.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
     // -- Kv / Secrets / Config extractors (registry-aware) -----------------
 
     #[test]
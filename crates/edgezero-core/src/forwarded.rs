@@ -0,0 +1,238 @@
+//! Which proxies in front of the app may speak for the client.
+//!
+//! Load balancers and CDNs report what they saw in `X-Forwarded-For`,
+//! `X-Forwarded-Host` and `X-Forwarded-Proto`, each appending its own entry,
+//! so the nearest proxy's entry is the last one. Anything further left may
+//! have been sent by the client itself. [`TrustedProxies`] says how many of
+//! those proxies to believe, and the [`ClientIp`], [`ForwardedHost`] and
+//! [`Scheme`] extractors all apply the same setting: with `n` trusted hops,
+//! each reads the `n`th entry from the right of its header.
+//!
+//! Register it with [`RouterBuilder::with_trusted_proxies`]:
+//!
+//! ```rust,ignore
+//! let router = RouterService::builder()
+//!     .with_trusted_proxies(TrustedProxies::Hops(1))
+//!     .get("/whoami", whoami)
+//!     .build();
+//! ```
+//!
+//! Without one, forwarded headers are not trusted, except that
+//! [`ForwardedHost`] keeps reading `X-Forwarded-Host` as it always has.
+//!
+//! [`ClientIp`]: crate::extractor::ClientIp
+//! [`ForwardedHost`]: crate::extractor::ForwardedHost
+//! [`Scheme`]: crate::extractor::Scheme
+//! [`RouterBuilder::with_trusted_proxies`]: crate::router::RouterBuilder::with_trusted_proxies
+
+use std::net::{IpAddr, SocketAddr};
+
+use ipnet::IpNet;
+
+use crate::context::RequestContext;
+use crate::http::{HeaderMap, HeaderValue};
+
+/// Header listing the client and each proxy that forwarded the request.
+pub const X_FORWARDED_FOR: &str = "x-forwarded-for";
+/// Header carrying the `Host` the client originally requested.
+pub const X_FORWARDED_HOST: &str = "x-forwarded-host";
+/// Header carrying the scheme the client originally used.
+pub const X_FORWARDED_PROTO: &str = "x-forwarded-proto";
+
+/// Applied when no [`TrustedProxies`] is registered.
+static UNTRUSTED: TrustedProxies = TrustedProxies::Hops(0);
+
+/// The proxies whose forwarded headers are believed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TrustedProxies {
+    /// Trust proxies whose address falls in one of these networks, starting
+    /// with the connecting peer and moving left through `X-Forwarded-For`
+    /// until an address outside them.
+    Cidrs(Vec<IpNet>),
+    /// Trust this many proxies nearest the app. `Hops(0)` trusts none and
+    /// `Hops(1)` a single load balancer.
+    Hops(usize),
+}
+
+impl Default for TrustedProxies {
+    #[inline]
+    fn default() -> Self {
+        Self::Hops(0)
+    }
+}
+
+impl TrustedProxies {
+    /// The client's address: the peer when no hop is trusted, otherwise the
+    /// `X-Forwarded-For` entry the outermost trusted proxy added. Falls back
+    /// to the peer when that entry is missing or not an address.
+    #[must_use]
+    #[inline]
+    pub fn client_ip(&self, ctx: &RequestContext) -> Option<IpAddr> {
+        let peer = peer_ip(ctx);
+        let hops = self.trusted_hops(peer, ctx.request().headers());
+        forwarded_entry(ctx.request().headers(), X_FORWARDED_FOR, hops)
+            .and_then(parse_ip)
+            .or(peer)
+    }
+
+    /// The entry of forwarded header `name` added by the outermost trusted
+    /// proxy, or `None` when no hop is trusted or the header is absent.
+    #[must_use]
+    #[inline]
+    pub fn forwarded<'req>(&self, ctx: &'req RequestContext, name: &str) -> Option<&'req str> {
+        let headers = ctx.request().headers();
+        forwarded_entry(headers, name, self.trusted_hops(peer_ip(ctx), headers))
+    }
+
+    /// The setting registered for `ctx`'s router, or [`Self::Hops`]`(0)`.
+    pub(crate) fn of(ctx: &RequestContext) -> &Self {
+        ctx.request()
+            .extensions()
+            .get::<Self>()
+            .unwrap_or(&UNTRUSTED)
+    }
+
+    /// How many proxies, nearest first, are believed for this request.
+    fn trusted_hops(&self, peer: Option<IpAddr>, headers: &HeaderMap) -> usize {
+        match self {
+            Self::Cidrs(networks) => {
+                let trusted = |ip: IpAddr| networks.iter().any(|network| network.contains(&ip));
+                if !peer.is_some_and(trusted) {
+                    return 0;
+                }
+                let forwarders = entries(headers, X_FORWARDED_FOR)
+                    .into_iter()
+                    .rev()
+                    .take_while(|entry| parse_ip(entry).is_some_and(trusted))
+                    .count();
+                forwarders.saturating_add(1)
+            }
+            Self::Hops(count) => *count,
+        }
+    }
+}
+
+/// Add `ip` as the last `X-Forwarded-For` entry, folding repeated headers
+/// into one, as a proxy passing the request on does.
+pub(crate) fn append_forwarded_for(headers: &mut HeaderMap, ip: IpAddr) {
+    let mut all = entries(headers, X_FORWARDED_FOR);
+    let own = ip.to_string();
+    all.push(&own);
+    if let Ok(value) = HeaderValue::try_from(all.join(", ")) {
+        headers.insert(X_FORWARDED_FOR, value);
+    }
+}
+
+/// Every comma-separated entry of header `name`, across repeated headers.
+fn entries<'req>(headers: &'req HeaderMap, name: &str) -> Vec<&'req str> {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+/// The entry `hops` from the right of header `name`, or the leftmost when
+/// more hops are trusted than the header has entries.
+fn forwarded_entry<'req>(headers: &'req HeaderMap, name: &str, hops: usize) -> Option<&'req str> {
+    if hops == 0 {
+        return None;
+    }
+    let all = entries(headers, name);
+    all.get(all.len().saturating_sub(hops)).copied()
+}
+
+/// An `X-Forwarded-For` entry as an address, with or without a port.
+fn parse_ip(entry: &str) -> Option<IpAddr> {
+    entry
+        .parse::<IpAddr>()
+        .or_else(|_| entry.parse::<SocketAddr>().map(|addr| addr.ip()))
+        .ok()
+}
+
+fn peer_ip(ctx: &RequestContext) -> Option<IpAddr> {
+    ctx.connection_info()
+        .and_then(|info| info.peer_addr)
+        .map(|addr| addr.ip())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::Body;
+    use crate::connection::ConnectionInfo;
+    use crate::extractor::{ClientIp, ForwardedHost, FromRequest as _, Scheme};
+    use crate::http::request_builder;
+    use crate::params::PathParams;
+    use futures::executor::block_on;
+
+    /// A request from `203.0.113.9` through `10.0.0.2` and then `10.0.0.1`,
+    /// the app's peer, each proxy having appended to the forwarded headers.
+    fn proxied(trust: Option<TrustedProxies>) -> RequestContext {
+        let mut request = request_builder()
+            .uri("/whoami")
+            .header("host", "10.0.0.1")
+            .header(X_FORWARDED_FOR, "198.51.100.7, 203.0.113.9, 10.0.0.2")
+            .header(
+                X_FORWARDED_HOST,
+                "evil.example, shop.example, edge.internal",
+            )
+            .header(X_FORWARDED_PROTO, "http, https, http")
+            .body(Body::empty())
+            .expect("request");
+        request.extensions_mut().insert(ConnectionInfo {
+            peer_addr: Some("10.0.0.1:443".parse().expect("peer")),
+            ..ConnectionInfo::default()
+        });
+        if let Some(setting) = trust {
+            request.extensions_mut().insert(setting);
+        }
+        RequestContext::new(request, PathParams::default())
+    }
+
+    fn resolve(trust: Option<TrustedProxies>) -> (String, String, String) {
+        let ctx = proxied(trust);
+        let ip = block_on(ClientIp::from_request(&ctx)).expect("client ip");
+        let host = block_on(ForwardedHost::from_request(&ctx)).expect("host");
+        let scheme = block_on(Scheme::from_request(&ctx)).expect("scheme");
+        (ip.to_string(), host.into_inner(), scheme.into_inner())
+    }
+
+    #[test]
+    fn hop_count_picks_the_same_entry_for_every_header() {
+        let one = resolve(Some(TrustedProxies::Hops(1)));
+        assert_eq!(
+            one,
+            ("10.0.0.2".into(), "edge.internal".into(), "http".into())
+        );
+        let two = resolve(Some(TrustedProxies::Hops(2)));
+        assert_eq!(
+            two,
+            ("203.0.113.9".into(), "shop.example".into(), "https".into())
+        );
+    }
+
+    #[test]
+    fn cidrs_trust_hops_until_an_outside_address() {
+        let internal = TrustedProxies::Cidrs(vec!["10.0.0.0/8".parse().expect("cidr")]);
+        assert_eq!(
+            resolve(Some(internal)),
+            ("203.0.113.9".into(), "shop.example".into(), "https".into())
+        );
+
+        let elsewhere = TrustedProxies::Cidrs(vec!["192.168.0.0/16".parse().expect("cidr")]);
+        assert_eq!(
+            resolve(Some(elsewhere)),
+            ("10.0.0.1".into(), "10.0.0.1".into(), "http".into())
+        );
+    }
+
+    #[test]
+    fn untrusted_requests_use_the_connection() {
+        let (ip, _host, scheme) = resolve(Some(TrustedProxies::Hops(0)));
+        assert_eq!((ip.as_str(), scheme.as_str()), ("10.0.0.1", "http"));
+    }
+}
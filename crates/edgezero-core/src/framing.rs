@@ -0,0 +1,273 @@
+//! Length-delimited framing for streamed bodies.
+//!
+//! Some protocols carry a sequence of binary messages in one request body,
+//! each prefixed with its length. [`LengthDelimitedCodec`] describes the
+//! prefix and [`crate::body::Body::framed`] turns the body's chunks into a
+//! stream of whole frames, buffering across chunk boundaries.
+//!
+//! ```rust,ignore
+//! let mut frames = ctx.into_request().into_body().framed(LengthDelimitedCodec::new());
+//! while let Some(frame) = frames.next().await {
+//!     let frame = frame.map_err(EdgeError::bad_request)?;
+//!     // ...
+//! }
+//! ```
+
+use async_stream::try_stream;
+use bytes::{Buf as _, Bytes, BytesMut};
+use futures_util::stream::{LocalBoxStream, StreamExt as _};
+
+/// Default upper bound on a single frame's payload (8 MiB).
+const DEFAULT_MAX_FRAME_LENGTH: usize = 8 * 1024 * 1024;
+
+/// Width in bytes of the largest supported length prefix (`u64`).
+const MAX_LENGTH_FIELD_LENGTH: usize = 8;
+
+/// Errors surfaced while splitting a body into frames.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum FrameError {
+    /// A length prefix announced a payload above the configured maximum.
+    #[error("frame of {length} bytes exceeds the {max} byte limit")]
+    FrameTooLarge { length: u64, max: usize },
+
+    /// The body ended part-way through a length prefix or payload.
+    #[error("body ended with {remaining} bytes of an incomplete frame")]
+    Incomplete { remaining: usize },
+
+    /// The underlying body stream failed.
+    #[error("body stream error: {0}")]
+    Stream(#[source] anyhow::Error),
+}
+
+/// Describes how frames are delimited: a fixed-width unsigned length
+/// prefix followed by that many payload bytes.
+///
+/// The default is a 4-byte big-endian (`u32`) prefix with an 8 MiB
+/// payload cap.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LengthDelimitedCodec {
+    big_endian: bool,
+    length_field_length: usize,
+    max_frame_length: usize,
+}
+
+impl LengthDelimitedCodec {
+    /// Try to split one frame off the front of `buf`, returning `Ok(None)`
+    /// when more bytes are needed.
+    fn decode(&self, buf: &mut BytesMut) -> Result<Option<Bytes>, FrameError> {
+        let Some(header) = buf.get(..self.length_field_length) else {
+            return Ok(None);
+        };
+        let accumulate = |acc: u64, byte: &u8| acc.wrapping_shl(8) | u64::from(*byte);
+        let length = if self.big_endian {
+            header.iter().fold(0, accumulate)
+        } else {
+            header.iter().rev().fold(0, accumulate)
+        };
+
+        let payload_len = usize::try_from(length)
+            .ok()
+            .filter(|len| *len <= self.max_frame_length)
+            .ok_or(FrameError::FrameTooLarge {
+                length,
+                max: self.max_frame_length,
+            })?;
+        let Some(frame_len) = self.length_field_length.checked_add(payload_len) else {
+            return Err(FrameError::FrameTooLarge {
+                length,
+                max: self.max_frame_length,
+            });
+        };
+        if buf.len() < frame_len {
+            buf.reserve(frame_len.saturating_sub(buf.len()));
+            return Ok(None);
+        }
+
+        buf.advance(self.length_field_length);
+        Ok(Some(buf.split_to(payload_len).freeze()))
+    }
+
+    /// Decode `chunks` into a stream of frame payloads.
+    pub(crate) fn decode_stream(
+        self,
+        mut chunks: LocalBoxStream<'static, Result<Bytes, anyhow::Error>>,
+    ) -> LocalBoxStream<'static, Result<Bytes, FrameError>> {
+        try_stream! {
+            let mut buf = BytesMut::new();
+            while let Some(chunk) = chunks.next().await {
+                buf.extend_from_slice(&chunk.map_err(FrameError::Stream)?);
+                while let Some(frame) = self.decode(&mut buf)? {
+                    yield frame;
+                }
+            }
+            Self::finish(&buf)?;
+        }
+        .boxed_local()
+    }
+
+    /// Leftover bytes at end of stream mean the final frame was cut short.
+    fn finish(buf: &BytesMut) -> Result<(), FrameError> {
+        if buf.is_empty() {
+            Ok(())
+        } else {
+            Err(FrameError::Incomplete {
+                remaining: buf.len(),
+            })
+        }
+    }
+
+    /// Set the width of the length prefix in bytes, clamped to `1..=8`.
+    #[must_use]
+    #[inline]
+    pub fn length_field_length(mut self, bytes: usize) -> Self {
+        self.length_field_length = bytes.clamp(1, MAX_LENGTH_FIELD_LENGTH);
+        self
+    }
+
+    /// Use a little-endian length prefix instead of big-endian.
+    #[must_use]
+    #[inline]
+    pub fn little_endian(mut self) -> Self {
+        self.big_endian = false;
+        self
+    }
+
+    /// Reject frames whose payload is longer than `max` bytes.
+    #[must_use]
+    #[inline]
+    pub fn max_frame_length(mut self, max: usize) -> Self {
+        self.max_frame_length = max;
+        self
+    }
+
+    /// A codec reading a 4-byte big-endian length prefix.
+    #[must_use]
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            big_endian: true,
+            length_field_length: 4,
+            max_frame_length: DEFAULT_MAX_FRAME_LENGTH,
+        }
+    }
+}
+
+impl Default for LengthDelimitedCodec {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::Body;
+    use futures::executor::block_on;
+    use futures_util::stream;
+
+    fn collect(body: Body, codec: LengthDelimitedCodec) -> Vec<Result<Bytes, FrameError>> {
+        block_on(body.framed(codec).collect::<Vec<_>>())
+    }
+
+    #[expect(
+        clippy::big_endian_bytes,
+        reason = "test fixture encodes the default big-endian wire format"
+    )]
+    fn frame(payload: &[u8]) -> Vec<u8> {
+        let mut out = u32::try_from(payload.len()).unwrap().to_be_bytes().to_vec();
+        out.extend_from_slice(payload);
+        out
+    }
+
+    fn ok_frames(body: Body, codec: LengthDelimitedCodec) -> Vec<Bytes> {
+        collect(body, codec)
+            .into_iter()
+            .map(Result::unwrap)
+            .collect()
+    }
+
+    fn split_stream(bytes: &[u8], cuts: &[usize]) -> Body {
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        for &cut in cuts {
+            chunks.push(Bytes::copy_from_slice(&bytes[start..cut]));
+            start = cut;
+        }
+        chunks.push(Bytes::copy_from_slice(&bytes[start..]));
+        Body::stream(stream::iter(chunks))
+    }
+
+    #[test]
+    fn buffered_body_yields_frames() {
+        let mut wire = frame(b"one");
+        wire.extend(frame(b""));
+        let frames = ok_frames(Body::from_bytes(wire), LengthDelimitedCodec::new());
+        assert_eq!(frames, vec![Bytes::from_static(b"one"), Bytes::new()]);
+    }
+
+    #[test]
+    fn frames_split_across_chunks_are_reassembled() {
+        let mut wire = frame(b"hello");
+        wire.extend(frame(b"world!"));
+        // Every split point, including mid-prefix and mid-payload.
+        for cut in 1..wire.len() {
+            for second in cut..wire.len() {
+                let frames = ok_frames(
+                    split_stream(&wire, &[cut, second]),
+                    LengthDelimitedCodec::new(),
+                );
+                assert_eq!(
+                    frames,
+                    vec![Bytes::from_static(b"hello"), Bytes::from_static(b"world!")],
+                    "split at {cut}/{second}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn little_endian_short_prefix_is_supported() {
+        let codec = LengthDelimitedCodec::new()
+            .length_field_length(2)
+            .little_endian();
+        let frames = collect(Body::from_bytes(vec![3, 0, b'a', b'b', b'c']), codec);
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].as_ref().unwrap().as_ref(), b"abc");
+    }
+
+    #[test]
+    fn oversized_frame_is_rejected() {
+        let codec = LengthDelimitedCodec::new().max_frame_length(4);
+        let frames = collect(Body::from_bytes(frame(b"too long")), codec);
+        assert!(matches!(
+            frames.as_slice(),
+            [Err(FrameError::FrameTooLarge { length: 8, max: 4 })]
+        ));
+    }
+
+    #[test]
+    fn stream_errors_are_surfaced() {
+        let body = Body::from_stream(stream::iter(vec![
+            Ok(Bytes::from(frame(b"ok"))),
+            Err(anyhow::anyhow!("connection reset")),
+        ]));
+        let frames = collect(body, LengthDelimitedCodec::new());
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].as_ref().unwrap().as_ref(), b"ok");
+        assert!(matches!(frames[1], Err(FrameError::Stream(_))));
+    }
+
+    #[test]
+    fn truncated_frame_is_an_error() {
+        let mut wire = frame(b"whole");
+        wire.extend_from_slice(&frame(b"partial")[..6]);
+        let frames = collect(split_stream(&wire, &[3]), LengthDelimitedCodec::new());
+        assert_eq!(frames.len(), 2);
+        assert!(matches!(
+            frames[1],
+            Err(FrameError::Incomplete { remaining: 6 })
+        ));
+    }
+}
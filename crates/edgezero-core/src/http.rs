@@ -18,6 +18,212 @@ use http::response::Builder as HttpResponseBuilder;
 use crate::body::Body;
 use crate::error::EdgeError;
 
+// ---------------------------------------------------------------------------
+// Contract test macro
+// ---------------------------------------------------------------------------
+
+/// Generate a suite of contract tests for an adapter's conversions between
+/// platform requests and responses and the core [`Request`] and
+/// [`Response`].
+///
+/// The macro takes the module name, an optional test attribute, and four
+/// closures:
+///
+/// - `request`: builds a platform request from a [`Method`], a URI string,
+///   a [`HeaderMap`] and the body bytes;
+/// - `into_core`: converts it, returning a future of
+///   `Result<Request, E>` with `E: Debug`;
+/// - `from_core`: converts a core [`Response`] into a platform response;
+/// - `read_response`: returns a future of the platform response's status,
+///   headers and body bytes.
+///
+/// The suite checks that the method, path, query, headers and body survive
+/// the request conversion, and the status, headers and body (buffered or
+/// streamed) the response conversion. A header sent several times may come
+/// back as one comma-separated value, as RFC 9110 permits. The body mode an
+/// adapter picks and platform metadata such as the client address are left
+/// to the adapter's own tests.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// edgezero_core::conversion_contract_tests!(axum_conversion_contract,
+///     request: |method: Method, uri: &str, headers: HeaderMap, body: Bytes| { ... },
+///     into_core: into_core_request,
+///     from_core: into_axum_response,
+///     read_response: |response: Response<AxumBody>| async move { ... },
+/// );
+/// ```
+#[macro_export]
+macro_rules! conversion_contract_tests {
+    (
+        $mod_name:ident,
+        #[$test_attr:meta],
+        request: $request:expr,
+        into_core: $into_core:expr,
+        from_core: $from_core:expr,
+        read_response: $read_response:expr $(,)?
+    ) => {
+        mod $mod_name {
+            use super::*;
+            use $crate::body::Body as ContractBody;
+            use $crate::http::{
+                HeaderMap as ContractHeaderMap, Method as ContractMethod,
+                StatusCode as ContractStatus,
+            };
+
+            fn run<Fut: ::std::future::Future>(future: Fut) -> Fut::Output {
+                ::futures::executor::block_on(future)
+            }
+
+            /// Every value of `name`, joined as one comma-separated line.
+            fn joined(headers: &ContractHeaderMap, name: &str) -> String {
+                headers
+                    .get_all(name)
+                    .iter()
+                    .map(|value| value.to_str().expect("header value"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+
+            fn convert(
+                method: ContractMethod,
+                uri: &str,
+                headers: ContractHeaderMap,
+                body: &'static [u8],
+            ) -> ($crate::http::Request, ::bytes::Bytes) {
+                let platform = ($request)(method, uri, headers, ::bytes::Bytes::from_static(body));
+                run(async {
+                    let request = ($into_core)(platform).await.expect("request conversion");
+                    let (parts, core_body) = request.into_parts();
+                    let bytes = core_body
+                        .into_bytes_bounded(usize::MAX)
+                        .await
+                        .expect("request body");
+                    ($crate::http::Request::from_parts(parts, ContractBody::empty()), bytes)
+                })
+            }
+
+            fn round_trip(
+                response: $crate::http::Response,
+            ) -> (ContractStatus, ContractHeaderMap, Vec<u8>) {
+                let platform = ($from_core)(response);
+                let (status, headers, body) = run(($read_response)(platform));
+                (status, headers, body.into())
+            }
+
+            #[$test_attr]
+            fn contract_request_method_path_and_query_survive() {
+                let (request, _body) = convert(
+                    ContractMethod::PATCH,
+                    "http://example.com/items/7?color=red&size=m%20l",
+                    ContractHeaderMap::new(),
+                    b"",
+                );
+                assert_eq!(request.method(), ContractMethod::PATCH);
+                assert_eq!(request.uri().path(), "/items/7");
+                assert_eq!(request.uri().query(), Some("color=red&size=m%20l"));
+            }
+
+            #[$test_attr]
+            fn contract_request_headers_survive() {
+                let mut headers = ContractHeaderMap::new();
+                headers.insert("x-contract", "one".parse().expect("header"));
+                headers.append("x-repeated", "a".parse().expect("header"));
+                headers.append("x-repeated", "b".parse().expect("header"));
+                let (request, _body) =
+                    convert(ContractMethod::GET, "http://example.com/", headers, b"");
+                assert_eq!(joined(request.headers(), "x-contract"), "one");
+                assert_eq!(joined(request.headers(), "X-Contract"), "one");
+                assert_eq!(joined(request.headers(), "x-repeated"), "a, b");
+            }
+
+            #[$test_attr]
+            fn contract_request_bodies_survive() {
+                let mut json = ContractHeaderMap::new();
+                json.insert("content-type", "application/json".parse().expect("header"));
+                let (_json, json_body) = convert(
+                    ContractMethod::POST,
+                    "http://example.com/json",
+                    json,
+                    br#"{"name":"edge"}"#,
+                );
+                assert_eq!(json_body.as_ref(), br#"{"name":"edge"}"#);
+
+                let mut binary = ContractHeaderMap::new();
+                binary.insert(
+                    "content-type",
+                    "application/octet-stream".parse().expect("header"),
+                );
+                let (_binary, binary_body) = convert(
+                    ContractMethod::PUT,
+                    "http://example.com/upload",
+                    binary,
+                    b"\x00\x01\xfe\xff",
+                );
+                assert_eq!(binary_body.as_ref(), b"\x00\x01\xfe\xff");
+
+                let (_empty, empty_body) = convert(
+                    ContractMethod::GET,
+                    "http://example.com/",
+                    ContractHeaderMap::new(),
+                    b"",
+                );
+                assert!(empty_body.is_empty());
+            }
+
+            #[$test_attr]
+            fn contract_response_status_headers_and_body_survive() {
+                let response = $crate::http::response_builder()
+                    .status(ContractStatus::CREATED)
+                    .header("content-type", "text/plain")
+                    .header("x-contract", "one")
+                    .header("x-repeated", "a")
+                    .header("x-repeated", "b")
+                    .body(ContractBody::from("created"))
+                    .expect("response");
+                let (status, headers, body) = round_trip(response);
+                assert_eq!(status, ContractStatus::CREATED);
+                assert_eq!(joined(&headers, "content-type"), "text/plain");
+                assert_eq!(joined(&headers, "x-contract"), "one");
+                assert_eq!(joined(&headers, "x-repeated"), "a, b");
+                assert_eq!(body, b"created");
+            }
+
+            #[$test_attr]
+            fn contract_streamed_response_body_survives() {
+                let chunks = ::futures::stream::iter([
+                    Ok::<_, ::std::io::Error>(::bytes::Bytes::from_static(b"hello, ")),
+                    Ok(::bytes::Bytes::from_static(b"stream")),
+                ]);
+                let response = $crate::http::response_builder()
+                    .status(ContractStatus::OK)
+                    .body(ContractBody::from_stream(chunks))
+                    .expect("response");
+                let (status, _headers, body) = round_trip(response);
+                assert_eq!(status, ContractStatus::OK);
+                assert_eq!(body, b"hello, stream");
+            }
+        }
+    };
+    (
+        $mod_name:ident,
+        request: $request:expr,
+        into_core: $into_core:expr,
+        from_core: $from_core:expr,
+        read_response: $read_response:expr $(,)?
+    ) => {
+        $crate::conversion_contract_tests!(
+            $mod_name,
+            #[test],
+            request: $request,
+            into_core: $into_core,
+            from_core: $from_core,
+            read_response: $read_response,
+        );
+    };
+}
+
 // CLAUDE.md mandates that application code never imports from the `http`
 // crate directly — every HTTP type must come through `edgezero_core::http`.
 // `Builder` types are exposed via `pub type` aliases (not `pub use`) so
@@ -47,3 +253,34 @@ pub fn request_builder() -> RequestBuilder {
 pub fn response_builder() -> ResponseBuilder {
     http::Response::builder()
 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    // Run the shared conversion contract against the identity conversion, so
+    // the suite itself is exercised without an adapter.
+    crate::conversion_contract_tests!(
+        identity_conversion_contract,
+        request: |method: Method, uri: &str, headers: HeaderMap, body: bytes::Bytes| {
+            let mut request = request_builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::from_bytes(body))
+                .expect("request");
+            *request.headers_mut() = headers;
+            request
+        },
+        into_core: |request: Request| async move { Ok::<_, EdgeError>(request) },
+        from_core: |response: Response| response,
+        read_response: |response: Response| async move {
+            let (parts, body) = response.into_parts();
+            let bytes = body.into_bytes_bounded(usize::MAX).await.expect("body");
+            (parts.status, parts.headers, bytes)
+        },
+    );
+
+    use super::*;
+}
@@ -1,17 +1,30 @@
 //! Framework-supplied introspection handlers. Bind via `[[triggers.http]]`:
 //! `handler = "edgezero_core::introspection::manifest"` etc.
+//!
+//! The route listing ([`routes`]) goes through the app's middleware like any
+//! other route, so an app-wide `Cors` answers its preflights too. To limit
+//! which browser origins may read it, independently of the app's policy,
+//! register a [`RouteListingAccess`] with
+//! [`RouterBuilder::route_listing_access`].
+//!
+//! [`RouterBuilder::route_listing_access`]: crate::router::RouterBuilder::route_listing_access
 
 use crate::blob_envelope::BlobEnvelope;
 use crate::body::Body;
 use crate::context::RequestContext;
+use crate::cors::Cors;
 use crate::error::EdgeError;
 use crate::extractor::FromRequest;
+use crate::http::header::{HOST, ORIGIN};
 // NOTE: `Response` is an HTTP alias exported from `crate::http`, NOT
 // `crate::response` (response.rs itself imports it from crate::http).
-use crate::http::{Response, StatusCode, response_builder};
+use crate::http::{HeaderValue, Request, Response, StatusCode, response_builder};
+use crate::middleware::{Middleware, Next};
+use crate::response::JsonFormat;
 use crate::router::RouteInfo;
 use async_trait::async_trait;
 use edgezero_core::action;
+use http::uri::Authority;
 use serde::Serialize;
 use std::sync::Arc;
 
@@ -57,6 +70,53 @@ impl FromRequest for RouteTable {
     }
 }
 
+/// Which browser origins may call the route listing. Requests without an
+/// `Origin` header, such as those from command-line tooling, are always
+/// let through.
+#[derive(Clone, Debug)]
+pub enum RouteListingAccess {
+    /// Origins `cors` allows get its preflight answers and CORS headers;
+    /// any other origin is rejected with `403 Forbidden`, even if the app's
+    /// own CORS policy allows it.
+    Origins(Cors),
+    /// Only an `Origin` naming the request's own host is let through;
+    /// anything else is rejected with `403 Forbidden`.
+    SameOrigin,
+}
+
+#[async_trait(?Send)]
+impl Middleware for RouteListingAccess {
+    #[inline]
+    async fn handle(&self, ctx: RequestContext, next: Next<'_>) -> Result<Response, EdgeError> {
+        let Some(origin) = ctx.request().headers().get(ORIGIN) else {
+            return next.run(ctx).await;
+        };
+        match self {
+            Self::Origins(cors) if cors.allows(origin) => cors.handle(ctx, next).await,
+            Self::SameOrigin if is_same_origin(ctx.request(), origin) => next.run(ctx).await,
+            Self::Origins(_) | Self::SameOrigin => Err(EdgeError::forbidden(
+                "the route listing is not available to this origin",
+            )),
+        }
+    }
+}
+
+/// Whether `origin` names the host `request` was sent to.
+fn is_same_origin(request: &Request, origin: &HeaderValue) -> bool {
+    let host = request
+        .headers()
+        .get(HOST)
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| request.uri().authority().map(Authority::as_str));
+    let origin_host = origin
+        .to_str()
+        .ok()
+        .and_then(|value| value.split_once("://"))
+        .map(|(_, authority)| authority);
+    host.zip(origin_host)
+        .is_some_and(|(expected, actual)| expected.eq_ignore_ascii_case(actual))
+}
+
 fn json_response(status: StatusCode, body: Body) -> Result<Response, EdgeError> {
     response_builder()
         .status(status)
@@ -71,9 +131,13 @@ pub async fn manifest(ManifestJson(json): ManifestJson) -> Result<Response, Edge
     json_response(StatusCode::OK, Body::text(json.to_string()))
 }
 
-/// GET — `[{ "method", "path" }]` for every registered route.
+/// GET — `[{ "method", "path" }]` for every registered route, laid out as
+/// the router's [`JsonFormat`].
 #[action(routes)]
-pub async fn routes(RouteTable(table): RouteTable) -> Result<Response, EdgeError> {
+pub async fn routes(
+    RouteTable(table): RouteTable,
+    format: JsonFormat,
+) -> Result<Response, EdgeError> {
     let views: Vec<RouteView> = table
         .iter()
         .map(|route| RouteView {
@@ -81,7 +145,7 @@ pub async fn routes(RouteTable(table): RouteTable) -> Result<Response, EdgeError
             path: route.path().to_owned(),
         })
         .collect();
-    let body = Body::json(&views).map_err(EdgeError::internal)?;
+    let body = format.body(&views).map_err(EdgeError::internal)?;
     json_response(StatusCode::OK, body)
 }
 
@@ -112,7 +176,7 @@ pub async fn config(ctx: RequestContext) -> Result<Response, EdgeError> {
 mod tests {
     use super::*;
     use crate::config_store::{ConfigStore, ConfigStoreError, ConfigStoreHandle};
-    use crate::http::{Method, Response, request_builder};
+    use crate::http::{Method, Request, Response, request_builder};
     use crate::router::RouterService;
     use crate::store_registry::{ConfigRegistry, ConfigStoreBinding, StoreRegistry};
     use async_trait::async_trait;
@@ -140,6 +204,10 @@ mod tests {
         }
     }
 
+    async fn other_route(_ctx: RequestContext) -> Result<Response, EdgeError> {
+        json_response(StatusCode::OK, Body::empty())
+    }
+
     // Collect a buffered response body into JSON (introspection responses are
     // always `Body::Once`). `Body::to_json` works on the buffered variant.
     fn body_json(resp: Response) -> serde_json::Value {
@@ -240,6 +308,113 @@ mod tests {
         );
     }
 
+    #[test]
+    fn routes_listing_is_compact_unless_pretty_is_registered() {
+        let fetch = |format: Option<JsonFormat>| {
+            let mut builder = RouterService::builder();
+            if let Some(chosen) = format {
+                builder = builder.with_json_format(chosen);
+            }
+            let router = builder.get("/r", routes).build();
+            let req = request_builder().uri("/r").body(Body::empty()).unwrap();
+            let resp = block_on(router.oneshot(req)).unwrap();
+            String::from_utf8(resp.body().as_bytes().unwrap().to_vec()).unwrap()
+        };
+        assert_eq!(fetch(None), r#"[{"method":"GET","path":"/r"}]"#);
+        assert_eq!(
+            fetch(Some(JsonFormat::Pretty)),
+            "[\n  {\n    \"method\": \"GET\",\n    \"path\": \"/r\"\n  }\n]"
+        );
+    }
+
+    fn listing_request(method: Method, origin: Option<&str>) -> Request {
+        let mut builder = request_builder()
+            .method(method)
+            .uri("/_app/routes")
+            .header("host", "app.example.com")
+            .header("access-control-request-method", "GET");
+        if let Some(value) = origin {
+            builder = builder.header("origin", value);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn route_listing_preflight_is_answered_for_allowlisted_origins() {
+        let tooling = Cors::builder()
+            .allow_origin("https://tools.example.com")
+            .build()
+            .unwrap();
+        let app_cors = Cors::builder().allow_any_origin().build().unwrap();
+        let router = RouterService::builder()
+            .middleware(app_cors)
+            .route_listing_access(RouteListingAccess::Origins(tooling))
+            .get("/_app/routes", routes)
+            .build();
+        let send = |request| block_on(router.oneshot(request)).unwrap();
+
+        let preflight = send(listing_request(
+            Method::OPTIONS,
+            Some("https://tools.example.com"),
+        ));
+        assert_eq!(preflight.status(), StatusCode::NO_CONTENT);
+        assert_eq!(
+            preflight
+                .headers()
+                .get("access-control-allow-origin")
+                .unwrap(),
+            "https://tools.example.com"
+        );
+
+        let listing = send(listing_request(
+            Method::GET,
+            Some("https://tools.example.com"),
+        ));
+        assert_eq!(listing.status(), StatusCode::OK);
+        assert_eq!(
+            listing
+                .headers()
+                .get("access-control-allow-origin")
+                .unwrap(),
+            "https://tools.example.com"
+        );
+
+        // The app's own policy allows any origin, but not for the listing.
+        let other = send(listing_request(
+            Method::OPTIONS,
+            Some("https://evil.example"),
+        ));
+        assert_eq!(other.status(), StatusCode::FORBIDDEN);
+        let tooling_without_origin = send(listing_request(Method::GET, None));
+        assert_eq!(tooling_without_origin.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn same_origin_route_listing_rejects_other_origins() {
+        let router = RouterService::builder()
+            .route_listing_access(RouteListingAccess::SameOrigin)
+            .get("/_app/routes", routes)
+            .get("/_app/other", other_route)
+            .build();
+        let send = |request| block_on(router.oneshot(request)).unwrap();
+
+        let same = send(listing_request(
+            Method::GET,
+            Some("https://app.example.com"),
+        ));
+        assert_eq!(same.status(), StatusCode::OK);
+        let cross = send(listing_request(Method::GET, Some("https://evil.example")));
+        assert_eq!(cross.status(), StatusCode::FORBIDDEN);
+
+        // Other routes are not affected by the listing policy.
+        let other = request_builder()
+            .uri("/_app/other")
+            .header("origin", "https://evil.example")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(send(other).status(), StatusCode::OK);
+    }
+
     #[test]
     fn config_without_store_is_not_found() {
         let router = RouterService::builder().get("/c", config).build();
@@ -0,0 +1,302 @@
+//! Incremental parsing of a top-level JSON array body.
+//!
+//! [`JsonArrayStream`] yields the elements of a `[...]` document one at a
+//! time as the body streams in, buffering only the element currently being
+//! read rather than the whole document:
+//!
+//! ```rust,ignore
+//! let mut items = JsonArrayStream::<Item>::new(upstream.into_body());
+//! while let Some(item) = items.next().await {
+//!     process(item?);
+//! }
+//! ```
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use async_stream::try_stream;
+use bytes::{Buf as _, BytesMut};
+use futures_util::stream::{self, LocalBoxStream, Stream, StreamExt as _};
+use serde::de::DeserializeOwned;
+
+use crate::body::Body;
+use crate::error::EdgeError;
+
+/// Stream of `T` decoded from the elements of a top-level JSON array body.
+///
+/// Elements split across body chunks are reassembled. A malformed document,
+/// an element that fails to deserialize, or a body that ends before the
+/// closing `]` yields [`EdgeError::BadRequest`]; a failing body stream
+/// yields [`EdgeError::Internal`]. The stream ends after the first error.
+pub struct JsonArrayStream<T> {
+    inner: LocalBoxStream<'static, Result<T, EdgeError>>,
+}
+
+impl<T> JsonArrayStream<T>
+where
+    T: DeserializeOwned + 'static,
+{
+    /// Parse `body`, buffered or streaming, as a JSON array of `T`.
+    #[must_use]
+    #[inline]
+    pub fn new(body: Body) -> Self {
+        let mut chunks = match body {
+            Body::Once(bytes) => stream::once(async move { Ok(bytes) }).boxed_local(),
+            Body::Stream(stream) | Body::StreamWithTrailers(stream, _) => stream,
+        };
+        let inner = try_stream! {
+            let mut scanner = ArrayScanner::default();
+            while let Some(chunk) = chunks.next().await {
+                scanner.buf.extend_from_slice(&chunk.map_err(EdgeError::internal)?);
+                while let Some(element) = scanner.next_element()? {
+                    yield element;
+                }
+            }
+            scanner.finish()?;
+        }
+        .boxed_local();
+        Self { inner }
+    }
+}
+
+impl<T> Stream for JsonArrayStream<T> {
+    type Item = Result<T, EdgeError>;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Where the scanner is in the array grammar.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum Position {
+    /// After the closing `]`; only whitespace may follow.
+    Done,
+    /// After `,`; an element must follow.
+    ElementRequired,
+    /// After `[`; an element or `]` may follow.
+    FirstElement,
+    /// Inside an element.
+    InElement,
+    /// After an element; `,` or `]` must follow.
+    Separator,
+    /// Before the opening `[`.
+    #[default]
+    Start,
+}
+
+/// Byte-level scanner that finds element boundaries without parsing the
+/// elements themselves; each complete element is handed to `serde_json`.
+#[derive(Default)]
+struct ArrayScanner {
+    buf: BytesMut,
+    /// Nesting depth of `[`/`{` inside the current element.
+    depth: usize,
+    /// Previous byte inside a string was an unescaped backslash.
+    escaped: bool,
+    in_string: bool,
+    position: Position,
+    /// Bytes of `buf` already scanned for the current element.
+    scanned: usize,
+}
+
+impl ArrayScanner {
+    /// Consume the element at the front of `buf`: `len` bytes of element
+    /// followed by its `,` or `]` terminator.
+    fn emit<T: DeserializeOwned>(&mut self, len: usize) -> Result<T, EdgeError> {
+        let element = self.buf.split_to(len);
+        self.scanned = 0;
+        serde_json::from_slice(&element)
+            .map_err(|err| EdgeError::bad_request(format!("invalid JSON array element: {err}")))
+    }
+
+    /// The body ended; anything but a completed array is an error.
+    fn finish(&self) -> Result<(), EdgeError> {
+        if self.position == Position::Done {
+            Ok(())
+        } else {
+            Err(EdgeError::bad_request("JSON array body ended early"))
+        }
+    }
+
+    /// Return the next complete element, or `None` when more bytes are
+    /// needed.
+    fn next_element<T: DeserializeOwned>(&mut self) -> Result<Option<T>, EdgeError> {
+        loop {
+            if self.position != Position::InElement {
+                let Some(&byte) = self.buf.first() else {
+                    return Ok(None);
+                };
+                if !byte.is_ascii_whitespace() {
+                    self.position = self.structural(byte)?;
+                }
+                // An element's first byte stays in `buf` for the element scan.
+                if self.position != Position::InElement {
+                    self.buf.advance(1);
+                }
+                continue;
+            }
+
+            let Some(&byte) = self.buf.get(self.scanned) else {
+                return Ok(None);
+            };
+            if self.in_string {
+                match byte {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => {}
+                }
+            } else {
+                match byte {
+                    b'"' => self.in_string = true,
+                    b'[' | b'{' => self.depth = self.depth.saturating_add(1),
+                    b']' | b'}' if self.depth > 0 => self.depth = self.depth.saturating_sub(1),
+                    b',' | b']' if self.depth == 0 => {
+                        let element = self.emit(self.scanned)?;
+                        self.position = Position::Separator;
+                        return Ok(Some(element));
+                    }
+                    _ => {}
+                }
+            }
+            self.scanned = self.scanned.saturating_add(1);
+        }
+    }
+
+    /// The position after a non-whitespace byte outside an element.
+    fn structural(&self, byte: u8) -> Result<Position, EdgeError> {
+        match (self.position, byte) {
+            (Position::Start, b'[') => Ok(Position::FirstElement),
+            (Position::FirstElement | Position::Separator, b']') => Ok(Position::Done),
+            (Position::Separator, b',') => Ok(Position::ElementRequired),
+            (Position::FirstElement | Position::ElementRequired, b',' | b']')
+            | (Position::Done | Position::Start | Position::Separator, _) => {
+                Err(EdgeError::bad_request(format!(
+                    "unexpected `{}` in JSON array body",
+                    char::from(byte)
+                )))
+            }
+            (Position::FirstElement | Position::ElementRequired | Position::InElement, _) => {
+                Ok(Position::InElement)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+    use bytes::Bytes;
+    use futures::executor::block_on;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item {
+        id: u32,
+        name: String,
+    }
+
+    fn chunked(text: &str, cuts: &[usize]) -> Body {
+        let bytes = text.as_bytes();
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        for &cut in cuts {
+            chunks.push(Bytes::copy_from_slice(&bytes[start..cut]));
+            start = cut;
+        }
+        chunks.push(Bytes::copy_from_slice(&bytes[start..]));
+        Body::stream(stream::iter(chunks))
+    }
+
+    fn collect<T: DeserializeOwned + 'static>(body: Body) -> Vec<Result<T, EdgeError>> {
+        block_on(JsonArrayStream::<T>::new(body).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn buffered_array_yields_elements() {
+        let items = collect::<u32>(Body::from("[1, 2,3]"));
+        let values: Vec<u32> = items.into_iter().map(Result::unwrap).collect();
+        assert_eq!(values, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn elements_split_across_awkward_chunks_are_reassembled() {
+        let text = r#" [ {"id":1,"name":"a,]"}, {"id":2,"name":"b\"}"} ,{"id":3,"name":"[c]"} ] "#;
+        let expected = vec![
+            Item {
+                id: 1,
+                name: "a,]".to_owned(),
+            },
+            Item {
+                id: 2,
+                name: "b\"}".to_owned(),
+            },
+            Item {
+                id: 3,
+                name: "[c]".to_owned(),
+            },
+        ];
+        // Every pair of split points, including mid-string and mid-escape.
+        for first in 1..text.len() {
+            for second in first..text.len() {
+                let items: Vec<Item> = collect::<Item>(chunked(text, &[first, second]))
+                    .into_iter()
+                    .map(Result::unwrap)
+                    .collect();
+                assert_eq!(items, expected, "split at {first}/{second}");
+            }
+        }
+    }
+
+    #[test]
+    fn empty_array_yields_nothing() {
+        assert!(collect::<u32>(chunked(" [ ] ", &[2])).is_empty());
+    }
+
+    #[test]
+    fn nested_values_are_single_elements() {
+        let items = collect::<serde_json::Value>(Body::from(r#"[[1,[2]],{"a":{"b":[]}}]"#));
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            items[0].as_ref().unwrap(),
+            &serde_json::json!([1_u8, [2_u8]])
+        );
+    }
+
+    #[test]
+    fn malformed_documents_are_bad_requests() {
+        for text in ["{}", "[1,,2]", "[1] x", "[,1]", "[1,]"] {
+            let items = collect::<u32>(Body::from(text));
+            let err = items
+                .into_iter()
+                .find_map(Result::err)
+                .unwrap_or_else(|| panic!("expected error for {text}"));
+            assert_eq!(err.status(), StatusCode::BAD_REQUEST, "{text}");
+        }
+    }
+
+    #[test]
+    fn truncated_array_is_an_error() {
+        let items = collect::<u32>(chunked("[1, 2", &[3]));
+        assert_eq!(items.len(), 2);
+        assert_eq!(*items[0].as_ref().unwrap(), 1);
+        assert!(matches!(items[1], Err(EdgeError::BadRequest { .. })));
+    }
+
+    #[test]
+    fn invalid_element_type_is_an_error() {
+        let items = collect::<u32>(Body::from(r#"["nope"]"#));
+        assert!(matches!(
+            items.as_slice(),
+            [Err(EdgeError::BadRequest { .. })]
+        ));
+    }
+}
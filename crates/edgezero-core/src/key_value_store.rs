@@ -21,6 +21,15 @@
 //! at another. Design handlers accordingly — do not assume
 //! read-after-write consistency across locations.
 //!
+//! On Cloudflare, `DurableObjectKvStore` routes a namespace through a
+//! single Durable Object for strongly consistent reads and writes, at the
+//! cost of a round trip to the object's location on every operation.
+//!
+//! Counters and other values updated concurrently need an atomic update:
+//! [`KvHandle::increment`] and [`KvHandle::compare_and_swap`] check and
+//! write in one step on backends that can (`PersistentKvStore` and
+//! `DurableObjectKvStore`), and return [`KvError::Unsupported`] elsewhere.
+//!
 //! # Usage
 //!
 //! Use the [`crate::extractor::Kv`] extractor with the `#[action]`
@@ -286,6 +295,50 @@ macro_rules! key_value_store_contract_tests {
                     );
                 });
             }
+
+            #[test]
+            fn contract_compare_and_swap_checks_the_current_value() {
+                let store = $factory;
+                run(async {
+                    let created = store
+                        .compare_and_swap("cas", None, Bytes::from("v1"))
+                        .await
+                        .unwrap();
+                    assert!(created);
+                    let stale = store
+                        .compare_and_swap("cas", None, Bytes::from("v2"))
+                        .await
+                        .unwrap();
+                    assert!(!stale);
+                    let swapped = store
+                        .compare_and_swap("cas", Some(Bytes::from("v1")), Bytes::from("v2"))
+                        .await
+                        .unwrap();
+                    assert!(swapped);
+                    assert_eq!(
+                        store.get_bytes("cas").await.unwrap(),
+                        Some(Bytes::from("v2"))
+                    );
+                });
+            }
+
+            #[test]
+            fn contract_increment_counts_from_zero() {
+                let store = $factory;
+                run(async {
+                    assert_eq!(store.increment("hits", 2).await.unwrap(), 2);
+                    assert_eq!(store.increment("hits", -5).await.unwrap(), -3);
+                    assert_eq!(
+                        store.get_bytes("hits").await.unwrap(),
+                        Some(Bytes::from("-3"))
+                    );
+                    store.put_bytes("text", Bytes::from("\"a\"")).await.unwrap();
+                    assert!(matches!(
+                        store.increment("text", 1).await,
+                        Err($crate::key_value_store::KvError::Validation(_))
+                    ));
+                });
+            }
         }
     };
 }
@@ -355,6 +408,7 @@ pub enum KvError {
 /// ```
 #[derive(Clone)]
 pub struct KvHandle {
+    log_keys: bool,
     store: Arc<dyn KvStore>,
 }
 
@@ -389,6 +443,74 @@ impl KvHandle {
     )]
     pub const MIN_TTL: Duration = Duration::from_secs(60);
 
+    /// Start a batch of writes, committed together by [`KvBatch::commit`].
+    ///
+    /// ```rust,ignore
+    /// store
+    ///     .batch()
+    ///     .put("order:42", &order)
+    ///     .delete("cart:7")
+    ///     .commit()
+    ///     .await?;
+    /// ```
+    ///
+    /// Whether the batch is atomic depends on the backend; see
+    /// [`KvStore::apply_batch`].
+    #[must_use]
+    #[inline]
+    pub fn batch(&self) -> KvBatch {
+        KvBatch {
+            error: None,
+            handle: self.clone(),
+            ops: Vec::new(),
+        }
+    }
+
+    /// Replace the value of `key` with `new` if it currently holds
+    /// `expected` (`None`: the key is absent), checking and writing in one
+    /// atomic step. Values are compared by their JSON encoding, so pass what
+    /// [`Self::get`] returned. Returns whether the value was replaced; the
+    /// new value has no TTL.
+    ///
+    /// ```rust,ignore
+    /// let current: Option<Lease> = store.get("lease").await?;
+    /// let claimed = store.compare_and_swap("lease", current.as_ref(), &mine).await?;
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`KvError::Unsupported`] on backends without an atomic
+    /// check-and-write (see [`KvStore::compare_and_swap`]), and [`KvError`]
+    /// if a value cannot be serialized or the backend rejects the write.
+    #[inline]
+    pub async fn compare_and_swap<T: Serialize>(
+        &self,
+        key: &str,
+        expected: Option<&T>,
+        new: &T,
+    ) -> Result<bool, KvError> {
+        Self::validate_key(key)?;
+        let expected_bytes = expected
+            .map(serde_json::to_vec)
+            .transpose()?
+            .map(Bytes::from);
+        let bytes = serde_json::to_vec(new)?;
+        Self::validate_value(&bytes)?;
+        let bytes_len = bytes.len();
+        let started_at = Self::kv_timing_start();
+        let result = self
+            .store
+            .compare_and_swap(key, expected_bytes, Bytes::from(bytes))
+            .await;
+        self.kv_timing_log(
+            started_at,
+            "compare_and_swap",
+            ("key", key),
+            &result,
+            || Self::kv_write_metadata(key.len(), bytes_len, None),
+        );
+        result
+    }
+
     fn decode_list_cursor(prefix: &str, cursor: Option<&str>) -> Result<Option<String>, KvError> {
         let Some(encoded) = cursor else {
             return Ok(None);
@@ -420,7 +542,7 @@ impl KvHandle {
         Self::validate_key(key)?;
         let started_at = Self::kv_timing_start();
         let result = self.store.delete(key).await;
-        Self::kv_timing_log(started_at, "delete", &result, || {
+        self.kv_timing_log(started_at, "delete", ("key", key), &result, || {
             format!("key_len={}", key.len())
         });
         result
@@ -447,7 +569,7 @@ impl KvHandle {
         Self::validate_key(key)?;
         let started_at = Self::kv_timing_start();
         let result = self.store.exists(key).await;
-        Self::kv_timing_log(started_at, "exists", &result, || {
+        self.kv_timing_log(started_at, "exists", ("key", key), &result, || {
             Self::kv_exists_metadata(key.len(), &result)
         });
         result
@@ -464,7 +586,7 @@ impl KvHandle {
         Self::validate_key(key)?;
         let started_at = Self::kv_timing_start();
         let result = self.store.get_bytes(key).await;
-        Self::kv_timing_log(started_at, "get", &result, || {
+        self.kv_timing_log(started_at, "get", ("key", key), &result, || {
             Self::kv_read_metadata(key.len(), &result)
         });
 
@@ -486,7 +608,7 @@ impl KvHandle {
         Self::validate_key(key)?;
         let started_at = Self::kv_timing_start();
         let result = self.store.get_bytes(key).await;
-        Self::kv_timing_log(started_at, "get_bytes", &result, || {
+        self.kv_timing_log(started_at, "get_bytes", ("key", key), &result, || {
             Self::kv_read_metadata(key.len(), &result)
         });
         result
@@ -501,6 +623,30 @@ impl KvHandle {
         Ok(self.get(key).await?.unwrap_or(default))
     }
 
+    /// Add `delta` (which may be negative) to the integer counter stored
+    /// under `key`, starting from zero when it is absent, in one atomic step.
+    /// Returns the new value. The counter is stored as a JSON number, so
+    /// [`Self::get`] reads it as an `i64`; a TTL set on the key is kept.
+    ///
+    /// Unlike [`Self::read_modify_write`], concurrent increments of the same
+    /// key are never lost.
+    ///
+    /// # Errors
+    /// Returns [`KvError::Unsupported`] on backends without an atomic
+    /// update (see [`KvStore::increment`]), [`KvError::Validation`] if the
+    /// key holds something other than an integer or the sum overflows, and
+    /// [`KvError`] if the backend rejects the write.
+    #[inline]
+    pub async fn increment(&self, key: &str, delta: i64) -> Result<i64, KvError> {
+        Self::validate_key(key)?;
+        let started_at = Self::kv_timing_start();
+        let result = self.store.increment(key, delta).await;
+        self.kv_timing_log(started_at, "increment", ("key", key), &result, || {
+            format!("key_len={} delta={delta}", key.len())
+        });
+        result
+    }
+
     fn kv_exists_metadata(key_len: usize, result: &Result<bool, KvError>) -> String {
         match result.as_ref() {
             Ok(exists) => format!("key_len={key_len} exists={exists}"),
@@ -541,9 +687,14 @@ impl KvHandle {
         }
     }
 
+    /// Log a finished operation at debug level. `subject` is the key (or
+    /// prefix) it touched, as `(field, value)`; it is only logged when
+    /// [`Self::with_key_logging`] is on, since keys may carry user data.
     fn kv_timing_log<ResultValue, Metadata>(
+        &self,
         started_at: Option<Instant>,
         operation: &str,
+        subject: (&str, &str),
         result: &Result<ResultValue, KvError>,
         metadata: Metadata,
     ) where
@@ -551,11 +702,20 @@ impl KvHandle {
     {
         if let Some(start) = started_at {
             let status = if result.is_ok() { "ok" } else { "error" };
-            log::debug!(
-                "kv operation={operation} elapsed_ms={} status={status} {}",
-                start.elapsed().as_millis(),
-                metadata()
-            );
+            if self.log_keys {
+                let (field, value) = subject;
+                log::debug!(
+                    "kv operation={operation} elapsed_ms={} status={status} {field}={value:?} {}",
+                    start.elapsed().as_millis(),
+                    metadata()
+                );
+            } else {
+                log::debug!(
+                    "kv operation={operation} elapsed_ms={} status={status} {}",
+                    start.elapsed().as_millis(),
+                    metadata()
+                );
+            }
         }
     }
 
@@ -597,9 +757,13 @@ impl KvHandle {
             .store
             .list_keys_page(prefix, decoded_cursor.as_deref(), limit)
             .await;
-        Self::kv_timing_log(started_at, "list_keys_page", &result, || {
-            Self::kv_list_metadata(prefix.len(), cursor.is_some(), limit, &result)
-        });
+        self.kv_timing_log(
+            started_at,
+            "list_keys_page",
+            ("prefix", prefix),
+            &result,
+            || Self::kv_list_metadata(prefix.len(), cursor.is_some(), limit, &result),
+        );
         let page = result?;
 
         Ok(KvPage {
@@ -611,7 +775,10 @@ impl KvHandle {
     /// Create a new handle wrapping a KV store implementation.
     #[inline]
     pub fn new(store: Arc<dyn KvStore>) -> Self {
-        Self { store }
+        Self {
+            log_keys: false,
+            store,
+        }
     }
 
     /// Put a value, serializing it to JSON.
@@ -626,7 +793,7 @@ impl KvHandle {
         let bytes_len = bytes.len();
         let started_at = Self::kv_timing_start();
         let result = self.store.put_bytes(key, Bytes::from(bytes)).await;
-        Self::kv_timing_log(started_at, "put", &result, || {
+        self.kv_timing_log(started_at, "put", ("key", key), &result, || {
             Self::kv_write_metadata(key.len(), bytes_len, None)
         });
         result
@@ -643,7 +810,7 @@ impl KvHandle {
         let bytes_len = value.len();
         let started_at = Self::kv_timing_start();
         let result = self.store.put_bytes(key, value).await;
-        Self::kv_timing_log(started_at, "put_bytes", &result, || {
+        self.kv_timing_log(started_at, "put_bytes", ("key", key), &result, || {
             Self::kv_write_metadata(key.len(), bytes_len, None)
         });
         result
@@ -666,9 +833,13 @@ impl KvHandle {
         let bytes_len = value.len();
         let started_at = Self::kv_timing_start();
         let result = self.store.put_bytes_with_ttl(key, value, ttl).await;
-        Self::kv_timing_log(started_at, "put_bytes_with_ttl", &result, || {
-            Self::kv_write_metadata(key.len(), bytes_len, Some(ttl))
-        });
+        self.kv_timing_log(
+            started_at,
+            "put_bytes_with_ttl",
+            ("key", key),
+            &result,
+            || Self::kv_write_metadata(key.len(), bytes_len, Some(ttl)),
+        );
         result
     }
 
@@ -693,7 +864,7 @@ impl KvHandle {
             .store
             .put_bytes_with_ttl(key, Bytes::from(bytes), ttl)
             .await;
-        Self::kv_timing_log(started_at, "put_with_ttl", &result, || {
+        self.kv_timing_log(started_at, "put_with_ttl", ("key", key), &result, || {
             Self::kv_write_metadata(key.len(), bytes_len, Some(ttl))
         });
         result
@@ -710,7 +881,8 @@ impl KvHandle {
     /// This operation is **not atomic**. The read and write are separate
     /// calls to the backend. Concurrent calls on the same key may cause
     /// lost writes. Use this only when eventual consistency is acceptable
-    /// (e.g., approximate counters).
+    /// (e.g., approximate counters); [`Self::increment`] and
+    /// [`Self::compare_and_swap`] are atomic where the backend allows.
     ///
     /// # Errors
     /// Returns [`KvError`] if any of the read, mutate, or write steps fail.
@@ -812,6 +984,17 @@ impl KvHandle {
         }
         Ok(())
     }
+
+    /// Include the key (or list prefix) and outcome of every operation in
+    /// this handle's debug-level logs, to see which keys a request touched.
+    /// Off by default because keys may carry user data; the axum dev server
+    /// turns it on for its local stores. Behaviour is otherwise unchanged.
+    #[must_use]
+    #[inline]
+    pub fn with_key_logging(mut self, enabled: bool) -> Self {
+        self.log_keys = enabled;
+        self
+    }
 }
 
 impl From<KvError> for EdgeError {
@@ -837,6 +1020,166 @@ impl From<KvError> for EdgeError {
     }
 }
 
+/// Writes queued by [`KvHandle::batch`].
+///
+/// Keys, values, and TTLs are validated when the batch is committed, before
+/// anything is written, so an invalid op rejects the whole batch.
+pub struct KvBatch {
+    /// First error from queueing, such as a value that failed to serialize.
+    error: Option<KvError>,
+    handle: KvHandle,
+    ops: Vec<KvOp>,
+}
+
+impl fmt::Debug for KvBatch {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KvBatch")
+            .field("ops", &self.ops.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl KvBatch {
+    /// Validate the queued ops and apply them through
+    /// [`KvStore::apply_batch`].
+    ///
+    /// # Errors
+    /// Returns the first queueing or validation error without writing
+    /// anything, or the backend's error if applying the batch fails.
+    #[inline]
+    pub async fn commit(self) -> Result<(), KvError> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        for op in &self.ops {
+            match op {
+                KvOp::Delete { key } => KvHandle::validate_key(key)?,
+                KvOp::Put { key, ttl, value } => {
+                    KvHandle::validate_key(key)?;
+                    KvHandle::validate_value(value)?;
+                    if let Some(duration) = ttl {
+                        KvHandle::validate_ttl(*duration)?;
+                    }
+                }
+            }
+        }
+        if self.ops.is_empty() {
+            return Ok(());
+        }
+        let op_count = self.ops.len();
+        let keys = if self.handle.log_keys {
+            self.ops
+                .iter()
+                .map(|op| match op {
+                    KvOp::Delete { key } | KvOp::Put { key, .. } => key.as_str(),
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        } else {
+            String::new()
+        };
+        let started_at = KvHandle::kv_timing_start();
+        let result = self.handle.store.apply_batch(self.ops).await;
+        self.handle
+            .kv_timing_log(started_at, "apply_batch", ("keys", &keys), &result, || {
+                format!("ops={op_count}")
+            });
+        result
+    }
+
+    /// Queue a delete of `key`.
+    #[must_use]
+    #[inline]
+    pub fn delete(mut self, key: &str) -> Self {
+        self.ops.push(KvOp::Delete {
+            key: key.to_owned(),
+        });
+        self
+    }
+
+    /// Whether no ops are queued.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Number of queued ops.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Queue a write of `value`, serialized to JSON. A serialization error is
+    /// reported by [`Self::commit`].
+    #[must_use]
+    #[inline]
+    pub fn put<T: Serialize>(self, key: &str, value: &T) -> Self {
+        self.put_serialized(key, value, None)
+    }
+
+    /// Queue a write of raw bytes.
+    #[must_use]
+    #[inline]
+    pub fn put_bytes(mut self, key: &str, value: Bytes) -> Self {
+        self.ops.push(KvOp::Put {
+            key: key.to_owned(),
+            ttl: None,
+            value,
+        });
+        self
+    }
+
+    /// Queue a write of raw bytes that expires after `ttl`.
+    #[must_use]
+    #[inline]
+    pub fn put_bytes_with_ttl(mut self, key: &str, value: Bytes, ttl: Duration) -> Self {
+        self.ops.push(KvOp::Put {
+            key: key.to_owned(),
+            ttl: Some(ttl),
+            value,
+        });
+        self
+    }
+
+    fn put_serialized<T: Serialize>(mut self, key: &str, value: &T, ttl: Option<Duration>) -> Self {
+        match serde_json::to_vec(value) {
+            Ok(bytes) => self.ops.push(KvOp::Put {
+                key: key.to_owned(),
+                ttl,
+                value: Bytes::from(bytes),
+            }),
+            Err(err) => {
+                self.error.get_or_insert(KvError::from(err));
+            }
+        }
+        self
+    }
+
+    /// Queue a write of `value`, serialized to JSON, that expires after
+    /// `ttl`.
+    #[must_use]
+    #[inline]
+    pub fn put_with_ttl<T: Serialize>(self, key: &str, value: &T, ttl: Duration) -> Self {
+        self.put_serialized(key, value, Some(ttl))
+    }
+}
+
+/// One write in a batch passed to [`KvStore::apply_batch`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum KvOp {
+    /// Delete `key`; deleting a missing key is not an error.
+    Delete { key: String },
+    /// Store `value` under `key`, expiring after `ttl` when set.
+    Put {
+        key: String,
+        ttl: Option<Duration>,
+        value: Bytes,
+    },
+}
+
 /// A single page of keys from a KV listing operation.
 ///
 /// **Termination**: callers must use `cursor.is_none()` to determine
@@ -876,8 +1219,41 @@ pub struct KvPage {
 /// - `PersistentKvStore` (axum adapter) — local dev / tests with persistent storage
 /// - `FastlyKvStore` (fastly adapter) — Fastly KV Store
 /// - `CloudflareKvStore` (cloudflare adapter) — Cloudflare Workers KV
+/// - `DurableObjectKvStore` (cloudflare adapter) — a Cloudflare Durable Object
 #[async_trait(?Send)]
 pub trait KvStore: Send + Sync {
+    /// Apply `ops` in order as one batch.
+    ///
+    /// The default implementation applies them one at a time with
+    /// [`apply_sequentially`], so a failure part-way through leaves the
+    /// earlier writes in place. Backends that can commit several writes in
+    /// one transaction should override this; `PersistentKvStore` does, and
+    /// applies the whole batch or none of it. Edge backends (Fastly,
+    /// Cloudflare, Spin) have no multi-key transactions and are not atomic.
+    #[inline]
+    async fn apply_batch(&self, ops: Vec<KvOp>) -> Result<(), KvError> {
+        apply_sequentially(self, ops).await
+    }
+
+    /// Replace the value of `key` with `new` if it currently holds
+    /// `expected` byte for byte (`None`: the key is absent or expired), as
+    /// one atomic step. Returns whether the value was replaced.
+    ///
+    /// The default returns [`KvError::Unsupported`]. `PersistentKvStore` and
+    /// `DurableObjectKvStore` override it; Workers KV, Fastly and Spin have no
+    /// atomic check-and-write.
+    #[inline]
+    async fn compare_and_swap(
+        &self,
+        _key: &str,
+        _expected: Option<Bytes>,
+        _new: Bytes,
+    ) -> Result<bool, KvError> {
+        Err(KvError::Unsupported {
+            operation: "compare_and_swap".to_owned(),
+        })
+    }
+
     /// Delete a key. Returns `Ok(())` even if the key did not exist.
     async fn delete(&self, key: &str) -> Result<(), KvError>;
 
@@ -893,6 +1269,19 @@ pub trait KvStore: Send + Sync {
     /// Retrieve raw bytes for a key. Returns `Ok(None)` if the key does not exist.
     async fn get_bytes(&self, key: &str) -> Result<Option<Bytes>, KvError>;
 
+    /// Add `delta` to the counter stored under `key` as one atomic step and
+    /// return the new value. Use [`incremented`] to decode the current value
+    /// and encode the new one, so every backend stores counters alike.
+    ///
+    /// The default returns [`KvError::Unsupported`]; backends override it
+    /// as they do [`Self::compare_and_swap`].
+    #[inline]
+    async fn increment(&self, _key: &str, _delta: i64) -> Result<i64, KvError> {
+        Err(KvError::Unsupported {
+            operation: "increment".to_owned(),
+        })
+    }
+
     /// List keys in lexicographic order, returning at most `limit` keys.
     ///
     /// The `cursor` is opaque. Pass the cursor from a previous page back to
@@ -946,6 +1335,19 @@ pub struct NoopKvStore;
 #[cfg(any(test, feature = "test-utils"))]
 #[async_trait(?Send)]
 impl KvStore for NoopKvStore {
+    #[inline]
+    async fn apply_batch(&self, _ops: Vec<KvOp>) -> Result<(), KvError> {
+        Ok(())
+    }
+    #[inline]
+    async fn compare_and_swap(
+        &self,
+        _key: &str,
+        expected: Option<Bytes>,
+        _new: Bytes,
+    ) -> Result<bool, KvError> {
+        Ok(expected.is_none())
+    }
     #[inline]
     async fn delete(&self, _key: &str) -> Result<(), KvError> {
         Ok(())
@@ -959,6 +1361,10 @@ impl KvStore for NoopKvStore {
         Ok(None)
     }
     #[inline]
+    async fn increment(&self, _key: &str, delta: i64) -> Result<i64, KvError> {
+        Ok(delta)
+    }
+    #[inline]
     async fn list_keys_page(
         &self,
         _prefix: &str,
@@ -982,6 +1388,63 @@ impl KvStore for NoopKvStore {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Batch helpers
+// ---------------------------------------------------------------------------
+
+/// Apply `ops` one at a time, in order, stopping at the first error. Writes
+/// made before the error are kept.
+///
+/// # Errors
+/// Returns the first error reported by `store`.
+#[inline]
+pub async fn apply_sequentially<S>(store: &S, ops: Vec<KvOp>) -> Result<(), KvError>
+where
+    S: KvStore + ?Sized,
+{
+    for op in ops {
+        match op {
+            KvOp::Delete { key } => store.delete(&key).await?,
+            KvOp::Put {
+                key,
+                ttl: Some(ttl),
+                value,
+            } => store.put_bytes_with_ttl(&key, value, ttl).await?,
+            KvOp::Put {
+                key,
+                ttl: None,
+                value,
+            } => store.put_bytes(&key, value).await?,
+        }
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Counter helpers
+// ---------------------------------------------------------------------------
+
+/// The counter after adding `delta` to the stored `current` value (zero when
+/// absent), with its stored encoding: a JSON number, as [`KvHandle::put`]
+/// would write it. For [`KvStore::increment`] implementations.
+///
+/// # Errors
+/// Returns [`KvError::Validation`] if `current` is not a JSON integer or the
+/// sum overflows an `i64`.
+#[inline]
+pub fn incremented(current: Option<&[u8]>, delta: i64) -> Result<(i64, Bytes), KvError> {
+    let value = match current {
+        Some(bytes) => serde_json::from_slice::<i64>(bytes).map_err(|_err| {
+            KvError::Validation("increment target is not an integer counter".to_owned())
+        })?,
+        None => 0,
+    };
+    let next = value
+        .checked_add(delta)
+        .ok_or_else(|| KvError::Validation("increment overflows the counter".to_owned()))?;
+    Ok((next, Bytes::from(next.to_string())))
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -994,10 +1457,33 @@ mod tests {
     use super::*;
     use crate::http::StatusCode;
     use futures::executor::block_on;
+    use std::cell::RefCell;
     use std::collections::HashMap;
+    use std::mem;
     use std::sync::Mutex;
     use std::time::SystemTime;
 
+    static CAPTURE_LOGGER: CaptureLogger = CaptureLogger;
+
+    thread_local! {
+        static CAPTURED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Records log lines per thread, so parallel tests only see their own.
+    struct CaptureLogger;
+
+    impl log::Log for CaptureLogger {
+        fn enabled(&self, _metadata: &log::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn flush(&self) {}
+
+        fn log(&self, record: &log::Record<'_>) {
+            CAPTURED.with_borrow_mut(|lines| lines.push(record.args().to_string()));
+        }
+    }
+
     #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
     struct Counter {
         count: i32,
@@ -1011,6 +1497,29 @@ mod tests {
 
     #[async_trait(?Send)]
     impl KvStore for MockStore {
+        async fn apply_batch(&self, ops: Vec<KvOp>) -> Result<(), KvError> {
+            apply_sequentially(self, ops).await
+        }
+
+        async fn compare_and_swap(
+            &self,
+            key: &str,
+            expected: Option<Bytes>,
+            new: Bytes,
+        ) -> Result<bool, KvError> {
+            let mut data = self.data.lock().unwrap();
+            let now = SystemTime::now();
+            let current = data
+                .get(key)
+                .filter(|(_, expires_at)| expires_at.is_none_or(|exp| now < exp))
+                .map(|(value, _)| value);
+            if current != expected.as_ref() {
+                return Ok(false);
+            }
+            data.insert(key.to_owned(), (new, None));
+            Ok(true)
+        }
+
         async fn delete(&self, key: &str) -> Result<(), KvError> {
             let mut data = self.data.lock().unwrap();
             data.remove(key);
@@ -1032,6 +1541,18 @@ mod tests {
             Ok(data.get(key).map(|(value, _)| value.clone()))
         }
 
+        async fn increment(&self, key: &str, delta: i64) -> Result<i64, KvError> {
+            let mut data = self.data.lock().unwrap();
+            let now = SystemTime::now();
+            let live = data
+                .get(key)
+                .filter(|(_, expires_at)| expires_at.is_none_or(|exp| now < exp));
+            let expires_at = live.and_then(|(_, expires_at)| *expires_at);
+            let (next, bytes) = incremented(live.map(|(value, _)| value.as_ref()), delta)?;
+            data.insert(key.to_owned(), (bytes, expires_at));
+            Ok(next)
+        }
+
         async fn list_keys_page(
             &self,
             prefix: &str,
@@ -1093,6 +1614,91 @@ mod tests {
         KvHandle::new(Arc::new(MockStore::new()))
     }
 
+    #[test]
+    fn compare_and_swap_compares_json_encodings() {
+        let kv = handle();
+        block_on(async {
+            let first = Counter { count: 1 };
+            assert!(kv.compare_and_swap("c", None, &first).await.unwrap());
+            let second = Counter { count: 2 };
+            assert!(!kv.compare_and_swap("c", None, &second).await.unwrap());
+            assert!(
+                kv.compare_and_swap("c", Some(&first), &second)
+                    .await
+                    .unwrap()
+            );
+            assert_eq!(kv.get::<Counter>("c").await.unwrap(), Some(second));
+        });
+    }
+
+    #[test]
+    fn counters_read_back_as_integers() {
+        let kv = handle();
+        block_on(async {
+            assert_eq!(kv.increment("visits", 1).await.unwrap(), 1);
+            assert_eq!(kv.increment("visits", 41).await.unwrap(), 42);
+            assert_eq!(kv.get::<i64>("visits").await.unwrap(), Some(42));
+        });
+        assert!(matches!(
+            incremented(Some(i64::MAX.to_string().as_bytes()), 1),
+            Err(KvError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn default_atomic_operations_are_unsupported() {
+        struct PlainStore;
+
+        #[async_trait(?Send)]
+        #[expect(
+            clippy::missing_trait_methods,
+            reason = "checks the compare_and_swap and increment defaults"
+        )]
+        impl KvStore for PlainStore {
+            async fn delete(&self, _key: &str) -> Result<(), KvError> {
+                Ok(())
+            }
+
+            async fn get_bytes(&self, _key: &str) -> Result<Option<Bytes>, KvError> {
+                Ok(None)
+            }
+
+            async fn list_keys_page(
+                &self,
+                _prefix: &str,
+                _cursor: Option<&str>,
+                _limit: usize,
+            ) -> Result<KvPage, KvError> {
+                Ok(KvPage::default())
+            }
+
+            async fn put_bytes(&self, _key: &str, _value: Bytes) -> Result<(), KvError> {
+                Ok(())
+            }
+
+            async fn put_bytes_with_ttl(
+                &self,
+                _key: &str,
+                _value: Bytes,
+                _ttl: Duration,
+            ) -> Result<(), KvError> {
+                Ok(())
+            }
+        }
+
+        let kv = KvHandle::new(Arc::new(PlainStore));
+        block_on(async {
+            assert!(matches!(
+                kv.increment("n", 1).await,
+                Err(KvError::Unsupported { operation }) if operation == "increment"
+            ));
+            assert!(matches!(
+                kv.compare_and_swap("n", None, &1_i64).await,
+                Err(KvError::Unsupported { operation }) if operation == "compare_and_swap"
+            ));
+        });
+    }
+
     #[test]
     fn delete_missing_key_is_ok() {
         let kv = handle();
@@ -1361,6 +1967,49 @@ mod tests {
         assert!(!metadata.contains("super-secret-value"));
     }
 
+    fn kv_log_lines(handle: &KvHandle) -> Vec<String> {
+        // Fails harmlessly if another test installed the logger first.
+        let _installed = log::set_logger(&CAPTURE_LOGGER);
+        log::set_max_level(log::LevelFilter::Debug);
+        CAPTURED.with_borrow_mut(Vec::clear);
+        block_on(async {
+            handle.put("user:1", &1_i32).await.unwrap();
+            handle.get::<i32>("user:1").await.unwrap();
+            handle.delete("user:1").await.unwrap();
+            handle.list_keys_page("user:", None, 10).await.unwrap();
+        });
+        CAPTURED
+            .with_borrow_mut(mem::take)
+            .into_iter()
+            .filter(|line| line.starts_with("kv operation="))
+            .collect()
+    }
+
+    #[test]
+    fn key_logging_adds_keys_to_debug_logs_only_when_enabled() {
+        let traced = kv_log_lines(&handle().with_key_logging(true));
+        assert_eq!(traced.len(), 4, "{traced:?}");
+        for (line, expected) in traced.iter().zip([
+            "operation=put ",
+            "operation=get ",
+            "operation=delete ",
+            "operation=list_keys_page ",
+        ]) {
+            assert!(line.contains(expected), "{line}");
+            assert!(line.contains("status=ok"), "{line}");
+        }
+        assert!(traced[0].contains("key=\"user:1\""), "{}", traced[0]);
+        assert!(traced[1].contains("hit=true"), "{}", traced[1]);
+        assert!(traced[3].contains("prefix=\"user:\""), "{}", traced[3]);
+
+        let quiet = kv_log_lines(&handle());
+        assert_eq!(quiet.len(), 4, "timing logs are unchanged: {quiet:?}");
+        assert!(
+            quiet.iter().all(|line| !line.contains("user:")),
+            "{quiet:?}"
+        );
+    }
+
     #[test]
     fn success_metadata_keeps_stable_field_types() {
         let read_result = Ok(Some(Bytes::from_static(b"abc")));
@@ -1649,4 +2298,41 @@ mod tests {
             assert!(format!("{err}").contains("greater than zero"));
         });
     }
+
+    #[test]
+    fn batch_applies_ops_in_order() {
+        let kv = handle();
+        block_on(async {
+            kv.put("gone", &"old").await.unwrap();
+            let batch = kv
+                .batch()
+                .put("a", &1_i32)
+                .put("a", &2_i32)
+                .put_bytes("b", Bytes::from("raw"))
+                .delete("gone");
+            assert_eq!(batch.len(), 4);
+            batch.commit().await.unwrap();
+
+            assert_eq!(kv.get::<i32>("a").await.unwrap(), Some(2_i32));
+            assert_eq!(kv.get_bytes("b").await.unwrap(), Some(Bytes::from("raw")));
+            assert!(!kv.exists("gone").await.unwrap());
+            assert!(kv.batch().is_empty());
+        });
+    }
+
+    #[test]
+    fn batch_validation_failure_writes_nothing() {
+        let kv = handle();
+        block_on(async {
+            let err = kv
+                .batch()
+                .put("first", &"ok")
+                .put_with_ttl("second", &"ok", Duration::from_secs(1))
+                .commit()
+                .await
+                .unwrap_err();
+            assert!(matches!(err, KvError::Validation(_)));
+            assert!(!kv.exists("first").await.unwrap());
+        });
+    }
 }
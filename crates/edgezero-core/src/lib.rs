@@ -18,29 +18,62 @@ pub mod app;
 pub mod app_config;
 pub mod blob_envelope;
 pub mod body;
+pub mod body_limit;
+pub mod body_spool;
+pub mod cache_control;
 pub mod canonical_form;
+/// CBOR extractor and responder. Enable via the `cbor` feature.
+#[cfg(any(test, feature = "cbor"))]
+pub mod cbor;
+pub mod clock;
 pub mod compression;
+pub mod conditional;
 pub mod config_store;
+pub mod connection;
 pub mod context;
+pub mod cookies;
+pub mod cors;
+pub mod deadline;
 pub mod env_config;
 pub mod error;
 pub mod extractor;
+pub mod forwarded;
+pub mod framing;
 pub mod handler;
 pub mod http;
 pub mod introspection;
+pub mod json_stream;
 pub mod key_value_store;
 pub mod manifest;
+/// Prometheus-style request metrics. Enable via the `metrics` feature.
+#[cfg(any(test, feature = "metrics"))]
+pub mod metrics;
 pub mod middleware;
+pub mod object_store;
+pub mod observability;
 pub mod params;
+pub mod patch;
 pub mod proxy;
+/// Development-only request echo endpoint. Enable via the `request-debug`
+/// feature; never in production.
+#[cfg(any(test, feature = "request-debug"))]
+pub mod request_debug;
+pub mod request_id;
 pub mod responder;
 pub mod response;
 pub mod router;
 pub mod secret_store;
+pub mod sse;
 pub mod store_registry;
-/// Test-only env-var guards. The workspace's only `unsafe` lives here; see the
+/// In-process `TestClient` for handler tests. Enable via the `test-utils`
+/// feature in `[dev-dependencies]`.
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_client;
+/// Test-only env-var guards. All test-time `unsafe` env mutation lives here; see the
 /// module docs. Enable via the `test-utils` feature in `[dev-dependencies]`.
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_env;
+pub mod trace_context;
+pub mod unknown_fields;
 
 pub use edgezero_macros::{AppConfig, action, app};
@@ -1,5 +1,5 @@
 use log::LevelFilter;
-use serde::de::Error as DeError;
+use serde::de::{DeserializeOwned, Error as DeError};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
@@ -67,6 +67,30 @@ impl ManifestLoader {
         &self.manifest
     }
 
+    /// Deserialize the manifest's `[settings]` table into the app's own
+    /// settings type. A manifest without `[settings]` deserializes from an
+    /// empty table, so `T` must default its fields to load without one.
+    ///
+    /// Register the result with
+    /// [`RouterBuilder::with_settings`](crate::router::RouterBuilder::with_settings)
+    /// to read it through the [`Settings`](crate::extractor::Settings)
+    /// extractor.
+    ///
+    /// # Errors
+    /// Returns an [`io::Error`] of kind `InvalidData` naming the offending
+    /// field if `[settings]` does not match `T`.
+    #[inline]
+    pub fn settings<T: DeserializeOwned>(&self) -> Result<T, io::Error> {
+        toml::Value::Table(self.manifest.settings.clone())
+            .try_into()
+            .map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid [settings] in manifest: {err}"),
+                )
+            })
+    }
+
     /// # Errors
     /// Returns an [`io::Error`] if `contents` is not valid TOML or fails manifest validation.
     #[inline]
@@ -96,6 +120,10 @@ pub struct Manifest {
     #[serde(default)]
     #[validate(nested)]
     pub app: ManifestApp,
+    /// `[cors]` policy the `app!` macro installs, if declared.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(nested)]
+    pub cors: Option<ManifestCors>,
     #[serde(default)]
     #[validate(nested)]
     pub environment: ManifestEnvironment,
@@ -106,6 +134,10 @@ pub struct Manifest {
     logging_resolved: BTreeMap<String, ResolvedLoggingConfig>,
     #[serde(skip)]
     root: Option<PathBuf>,
+    /// App-defined `[settings]` table, read through
+    /// [`ManifestLoader::settings`].
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    pub settings: toml::Table,
     #[serde(default)]
     #[validate(nested)]
     pub stores: ManifestStores,
@@ -217,12 +249,31 @@ impl Manifest {
 #[derive(Debug, Default, Deserialize, Serialize, Validate)]
 #[non_exhaustive]
 pub struct ManifestApp {
+    /// Size in bytes past which buffered request bodies spill out of
+    /// memory, or are rejected where the adapter cannot spill. See
+    /// [`crate::body_spool`].
+    #[serde(
+        default,
+        rename = "body-spill-bytes",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[validate(range(min = 1_u64))]
+    pub body_spill_bytes: Option<u64>,
     #[serde(default)]
     #[validate(length(min = 1_u64))]
     pub entry: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[validate(length(min = 1_u64))]
     pub kind: Option<String>,
+    /// Request body cap in bytes, overriding the adapter's default. See
+    /// [`crate::body_limit`].
+    #[serde(
+        default,
+        rename = "max-body-bytes",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[validate(range(min = 1_u64))]
+    pub max_body_bytes: Option<u64>,
     #[serde(default)]
     pub middleware: Vec<String>,
     #[serde(default)]
@@ -233,6 +284,51 @@ pub struct ManifestApp {
     pub version: Option<String>,
 }
 
+/// The `[cors]` section: a CORS policy that the `app!` macro installs as
+/// [`Cors`](crate::cors::Cors) middleware, ahead of `[app] middleware`. A
+/// malformed origin, or the `*` origin combined with credentials, is rejected
+/// at load time.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Validate)]
+#[validate(schema(function = "validate_manifest_cors"))]
+#[non_exhaustive]
+pub struct ManifestCors {
+    #[serde(default, rename = "allow-credentials")]
+    pub allow_credentials: bool,
+    #[serde(
+        default,
+        rename = "allow-headers",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub allow_headers: Vec<String>,
+    /// Methods preflight requests may ask for; `GET`, `HEAD` and `POST`
+    /// when empty.
+    #[serde(
+        default,
+        rename = "allow-methods",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub allow_methods: Vec<HttpMethod>,
+    /// Origins in any form
+    /// [`CorsBuilder::allow_origin`](crate::cors::CorsBuilder::allow_origin)
+    /// accepts.
+    #[serde(rename = "allow-origins")]
+    #[validate(length(min = 1_u64))]
+    pub allow_origins: Vec<String>,
+    #[serde(
+        default,
+        rename = "expose-headers",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub expose_headers: Vec<String>,
+    /// How long browsers may cache a preflight answer, in seconds.
+    #[serde(
+        default,
+        rename = "max-age-secs",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_age_secs: Option<u64>,
+}
+
 #[derive(Debug, Default, Deserialize, Serialize, Validate)]
 #[non_exhaustive]
 pub struct ManifestTriggers {
@@ -262,6 +358,14 @@ pub struct ManifestHttpTrigger {
     pub methods: Vec<HttpMethod>,
     #[validate(length(min = 1_u64))]
     pub path: String,
+    /// Per-route timeout in milliseconds; expiry yields `504 Gateway Timeout`.
+    #[serde(
+        default,
+        rename = "timeout-ms",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[validate(range(min = 1_u64))]
+    pub timeout_ms: Option<u64>,
 }
 
 impl ManifestHttpTrigger {
@@ -888,6 +992,83 @@ fn validate_manifest_adapter_keys_case_unique(manifest: &Manifest) -> Result<(),
     Ok(())
 }
 
+/// Rejects a `[cors]` section with an origin or header `CorsBuilder` would
+/// refuse, or allowing any origin with credentials, which browsers refuse.
+fn validate_manifest_cors(cors: &ManifestCors) -> Result<(), ValidationError> {
+    if let Some(origin) = cors
+        .allow_origins
+        .iter()
+        .find(|origin| *origin != "*" && !is_cors_origin(origin))
+    {
+        let mut error = ValidationError::new("cors_invalid_origin");
+        error.message = Some(
+            format!(
+                "`[cors]` origin {origin:?} is invalid; use `scheme://host[:port]`, with `*` only as a whole host label"
+            )
+            .into(),
+        );
+        return Err(error);
+    }
+    if let Some(header) = cors
+        .allow_headers
+        .iter()
+        .chain(&cors.expose_headers)
+        .find(|header| !is_header_name(header))
+    {
+        let mut error = ValidationError::new("cors_invalid_header");
+        error.message =
+            Some(format!("`[cors]` header {header:?} is not a valid header name").into());
+        return Err(error);
+    }
+    if cors.allow_credentials && cors.allow_origins.iter().any(|origin| origin == "*") {
+        let mut error = ValidationError::new("cors_credentials_with_wildcard");
+        error.message = Some(
+            "`[cors]` cannot combine the `*` origin with `allow-credentials = true`; list the origins or origin patterns instead"
+                .into(),
+        );
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// Whether `origin` is `scheme://host[:port]` with `*` only as a whole host
+/// label. Mirrors `OriginPattern::parse` and `split_origin` in `cors.rs`,
+/// which this file cannot reach when the macros crate includes it.
+fn is_cors_origin(origin: &str) -> bool {
+    let Some((scheme, authority)) = origin.split_once("://") else {
+        return false;
+    };
+    if scheme.is_empty() || scheme.contains('*') || authority.is_empty() || authority.contains('/')
+    {
+        return false;
+    }
+    // A bracketed IPv6 host keeps its colons.
+    let port_colon = authority
+        .rfind(':')
+        .filter(|colon| authority.rfind(']').is_none_or(|bracket| colon > &bracket));
+    let (host, port) = match port_colon {
+        Some(colon) => (
+            authority.get(..colon).unwrap_or_default(),
+            authority.get(colon.saturating_add(1)..),
+        ),
+        None => (authority, None),
+    };
+    !host.is_empty()
+        && port.is_none_or(|digits| !digits.is_empty() && !digits.contains('*'))
+        && host
+            .split('.')
+            .all(|label| label == "*" || !(label.is_empty() || label.contains('*')))
+}
+
+/// Whether `name` is an HTTP header name (an RFC 9110 token), as
+/// `HeaderName::from_bytes` in `cors.rs` checks.
+fn is_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte))
+}
+
 /// Validates a single `[stores.<kind>]` declaration against the portable
 /// schema.
 ///
@@ -1051,6 +1232,13 @@ name = "API_TOKEN"
 env = "APP_TOKEN"
 "#;
 
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct DemoSettings {
+        greeting: String,
+        #[serde(default)]
+        retries: u32,
+    }
+
     #[test]
     fn parse_manifest_sample() {
         let loader = ManifestLoader::load_from_str(SAMPLE);
@@ -1125,6 +1313,48 @@ level = "info"
         assert_eq!(json["logging"]["axum"]["level"], "info");
     }
 
+    #[test]
+    fn settings_deserialize_into_custom_type() {
+        let loader = ManifestLoader::load_from_str(
+            r#"
+[app]
+name = "demo"
+
+[settings]
+greeting = "hello"
+retries = 3
+"#,
+        );
+        let settings: DemoSettings = loader.settings().expect("settings");
+        assert_eq!(
+            settings,
+            DemoSettings {
+                greeting: "hello".to_owned(),
+                retries: 3,
+            }
+        );
+        let json = serde_json::to_value(loader.manifest()).unwrap();
+        assert_eq!(json["settings"]["greeting"], "hello");
+    }
+
+    #[test]
+    fn settings_missing_or_invalid_are_load_errors() {
+        let missing = ManifestLoader::load_from_str("[app]\nname = \"demo\"\n");
+        let err = missing
+            .settings::<DemoSettings>()
+            .expect_err("greeting is required");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("greeting"), "{err}");
+
+        let invalid = ManifestLoader::load_from_str(
+            "[app]\nname = \"demo\"\n\n[settings]\ngreeting = \"hi\"\nretries = \"many\"\n",
+        );
+        let type_err = invalid
+            .settings::<DemoSettings>()
+            .expect_err("retries must be an integer");
+        assert!(type_err.to_string().contains("[settings]"), "{type_err}");
+    }
+
     #[test]
     fn try_load_from_str_rejects_invalid_toml() {
         let err = ManifestLoader::try_load_from_str("not a [valid manifest\n")
@@ -1407,6 +1637,125 @@ body-mode = "stream"
         assert!(err.to_string().contains("invalid type"));
     }
 
+    #[test]
+    fn timeout_ms_parses_and_defaults_to_none() {
+        let manifest = r#"
+[[triggers.http]]
+path = "/slow"
+timeout-ms = 250
+
+[[triggers.http]]
+path = "/fast"
+"#;
+        let loader = ManifestLoader::load_from_str(manifest);
+        let triggers = &loader.manifest().triggers.http;
+        assert_eq!(triggers[0].timeout_ms, Some(250));
+        assert_eq!(triggers[1].timeout_ms, None);
+    }
+
+    #[test]
+    fn timeout_ms_zero_fails_validation() {
+        let manifest: Manifest =
+            toml::from_str("[[triggers.http]]\npath = \"/\"\ntimeout-ms = 0\n")
+                .expect("should parse");
+        assert!(
+            manifest.validate().is_err(),
+            "zero `timeout-ms` should fail validation"
+        );
+    }
+
+    #[test]
+    fn cors_section_parses_and_is_absent_by_default() {
+        let manifest = r#"
+[cors]
+allow-origins = ["https://app.example.com", "https://*.example.com"]
+allow-methods = ["get", "PUT"]
+allow-headers = ["content-type"]
+expose-headers = ["x-request-id"]
+allow-credentials = true
+max-age-secs = 600
+"#;
+        let loader = ManifestLoader::load_from_str(manifest);
+        let cors = loader.manifest().cors.as_ref().expect("[cors] section");
+        assert_eq!(
+            cors.allow_origins,
+            ["https://app.example.com", "https://*.example.com"]
+        );
+        assert_eq!(cors.allow_methods, [HttpMethod::Get, HttpMethod::Put]);
+        assert_eq!(cors.allow_headers, ["content-type"]);
+        assert_eq!(cors.expose_headers, ["x-request-id"]);
+        assert!(cors.allow_credentials);
+        assert_eq!(cors.max_age_secs, Some(600));
+
+        let bare = ManifestLoader::load_from_str("[app]\nname = \"no-cors\"\n");
+        assert!(bare.manifest().cors.is_none());
+    }
+
+    #[test]
+    fn cors_section_rejects_wildcard_with_credentials_and_no_origins() {
+        let wildcard = "[cors]\nallow-origins = [\"*\"]\nallow-credentials = true\n";
+        let Err(err) = ManifestLoader::try_load_from_str(wildcard) else {
+            panic!("`*` with credentials should fail validation");
+        };
+        assert!(err.to_string().contains("allow-credentials"), "{err}");
+
+        ManifestLoader::try_load_from_str("[cors]\nallow-origins = [\"*\"]\n")
+            .map(drop)
+            .expect("`*` alone is fine");
+        ManifestLoader::try_load_from_str("[cors]\nallow-origins = []\n")
+            .map(drop)
+            .expect_err("at least one origin");
+        ManifestLoader::try_load_from_str("[cors]\nallow-credentials = true\n")
+            .map(drop)
+            .expect_err("`allow-origins` is required");
+    }
+
+    #[test]
+    fn cors_section_rejects_origins_cors_builder_refuses() {
+        for origin in [
+            "app.example.com",
+            "https://",
+            "https://app.example.com/",
+            "https://*app.example.com",
+            "https://app..example.com",
+            "*://app.example.com",
+            "https://app.example.com:*",
+            "https://app.example.com:",
+            "http://[::1]:",
+        ] {
+            let manifest = format!("[cors]\nallow-origins = [{origin:?}]\n");
+            let Err(err) = ManifestLoader::try_load_from_str(&manifest) else {
+                panic!("origin {origin:?} should fail validation");
+            };
+            assert!(err.to_string().contains("is invalid"), "{origin}: {err}");
+        }
+
+        ManifestLoader::try_load_from_str(
+            "[cors]\nallow-origins = [\"https://*.example.com\", \"http://localhost:8787\", \"http://[::1]:8080\"]\n",
+        )
+        .map(drop)
+        .expect("host-label wildcards, ports and IPv6 hosts are fine");
+    }
+
+    #[test]
+    fn cors_section_rejects_invalid_header_names() {
+        for section in [
+            "allow-headers = [\"x bad\"]",
+            "expose-headers = [\"\"]",
+            "expose-headers = [\"x-id:\"]",
+        ] {
+            let manifest =
+                format!("[cors]\nallow-origins = [\"https://app.example.com\"]\n{section}\n");
+            let Err(err) = ManifestLoader::try_load_from_str(&manifest) else {
+                panic!("{section} should fail validation");
+            };
+            assert!(
+                err.to_string().contains("not a valid header name"),
+                "{section}: {err}"
+            );
+        }
+    }
+
     // LogLevel parsing tests
     #[test]
     fn log_level_parses_all_variants() {
@@ -0,0 +1,385 @@
+//! Request metrics in the Prometheus text exposition format.
+//!
+//! Enabled by the `metrics` feature. [`RouterBuilder::enable_metrics_at`]
+//! records every routed request and serves the exposition at a path of your
+//! choosing:
+//!
+//! ```rust,ignore
+//! let router = RouterService::builder()
+//!     .enable_metrics_at("/metrics")
+//!     .get("/users/{id}", get_user)
+//!     .build();
+//! ```
+//!
+//! Requests are labelled by method, matched route template (see
+//! [`RequestContext::matched_route`]) and status class, so label
+//! cardinality stays bounded by the route table. Requests that match no
+//! route are not recorded. Metrics live in the instance's memory: each edge
+//! isolate reports only the requests it served.
+//!
+//! [`RouterBuilder::enable_metrics_at`]: crate::router::RouterBuilder::enable_metrics_at
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use web_time::Instant;
+
+use crate::body::Body;
+use crate::context::RequestContext;
+use crate::error::EdgeError;
+use crate::http::header::CONTENT_TYPE;
+use crate::http::{Method, Response, StatusCode, response_builder};
+use crate::middleware::{Middleware, Next};
+
+/// Content type of the Prometheus text exposition format.
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Latency histogram upper bounds in microseconds, with their rendered
+/// `le` label in seconds.
+const LATENCY_BUCKETS: [(u64, &str); 11] = [
+    (5_000, "0.005"),
+    (10_000, "0.01"),
+    (25_000, "0.025"),
+    (50_000, "0.05"),
+    (100_000, "0.1"),
+    (250_000, "0.25"),
+    (500_000, "0.5"),
+    (1_000_000, "1"),
+    (2_500_000, "2.5"),
+    (5_000_000, "5"),
+    (10_000_000, "10"),
+];
+
+/// Shared, cheaply cloneable store of request counters and latencies.
+#[derive(Clone, Default)]
+pub struct MetricsRegistry {
+    series: Arc<Mutex<BTreeMap<SeriesKey, RouteSeries>>>,
+}
+
+impl MetricsRegistry {
+    #[must_use]
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one completed request.
+    #[inline]
+    pub fn record(&self, method: &Method, route: &str, status: StatusCode, elapsed: Duration) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        let mut series = self.series.lock().unwrap_or_else(PoisonError::into_inner);
+        let entry = series
+            .entry(SeriesKey {
+                method: method.as_str().to_owned(),
+                route: route.to_owned(),
+            })
+            .or_default();
+
+        let class = status_class(status);
+        let count = entry.requests.entry(class).or_insert(0);
+        *count = count.saturating_add(1);
+
+        for (bucket, (bound, _)) in entry.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if micros <= bound {
+                *bucket = bucket.saturating_add(1);
+            }
+        }
+        entry.latency_count = entry.latency_count.saturating_add(1);
+        entry.latency_sum = entry.latency_sum.saturating_add(elapsed);
+    }
+
+    /// Render every series in the Prometheus text exposition format.
+    #[must_use]
+    #[inline]
+    pub fn render(&self) -> String {
+        let series = self.series.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut out = String::new();
+        // Writing to a String never fails.
+
+        out.push_str(
+            "# HELP edgezero_requests_total Requests handled, by route and status class.\n",
+        );
+        out.push_str("# TYPE edgezero_requests_total counter\n");
+        for (key, entry) in series.iter() {
+            for (class, count) in &entry.requests {
+                writeln!(
+                    out,
+                    "edgezero_requests_total{{{},status=\"{class}\"}} {count}",
+                    key.labels()
+                )
+                .unwrap_or_default();
+            }
+        }
+
+        out.push_str("# HELP edgezero_request_duration_seconds Request latency, by route.\n");
+        out.push_str("# TYPE edgezero_request_duration_seconds histogram\n");
+        for (key, entry) in series.iter() {
+            let labels = key.labels();
+            for (bucket, (_, le)) in entry.buckets.iter().zip(LATENCY_BUCKETS) {
+                writeln!(
+                    out,
+                    "edgezero_request_duration_seconds_bucket{{{labels},le=\"{le}\"}} {bucket}"
+                )
+                .unwrap_or_default();
+            }
+            writeln!(
+                out,
+                "edgezero_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+                entry.latency_count
+            )
+            .unwrap_or_default();
+            writeln!(
+                out,
+                "edgezero_request_duration_seconds_sum{{{labels}}} {}.{:06}",
+                entry.latency_sum.as_secs(),
+                entry.latency_sum.subsec_micros()
+            )
+            .unwrap_or_default();
+            writeln!(
+                out,
+                "edgezero_request_duration_seconds_count{{{labels}}} {}",
+                entry.latency_count
+            )
+            .unwrap_or_default();
+        }
+        out
+    }
+
+    /// The rendered exposition as a `200 OK` response.
+    ///
+    /// # Errors
+    ///
+    /// Returns an internal error if the response cannot be assembled.
+    #[inline]
+    pub fn response(&self) -> Result<Response, EdgeError> {
+        response_builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)
+            .body(Body::text(self.render()))
+            .map_err(EdgeError::internal)
+    }
+}
+
+/// Middleware recording each routed request into a [`MetricsRegistry`].
+pub struct MetricsMiddleware {
+    registry: MetricsRegistry,
+}
+
+impl MetricsMiddleware {
+    #[must_use]
+    #[inline]
+    pub fn new(registry: MetricsRegistry) -> Self {
+        Self { registry }
+    }
+}
+
+#[async_trait(?Send)]
+impl Middleware for MetricsMiddleware {
+    #[inline]
+    async fn handle(&self, ctx: RequestContext, next: Next<'_>) -> Result<Response, EdgeError> {
+        let method = ctx.request().method().clone();
+        let route = ctx.matched_route().unwrap_or_default().to_owned();
+        let start = Instant::now();
+
+        let result = next.run(ctx).await;
+        let status = match &result {
+            Ok(response) => response.status(),
+            Err(err) => err.status(),
+        };
+        self.registry
+            .record(&method, &route, status, start.elapsed());
+        result
+    }
+}
+
+#[derive(Default)]
+struct RouteSeries {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    latency_count: u64,
+    latency_sum: Duration,
+    requests: BTreeMap<&'static str, u64>,
+}
+
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+struct SeriesKey {
+    method: String,
+    route: String,
+}
+
+impl SeriesKey {
+    fn labels(&self) -> String {
+        format!(
+            "method=\"{}\",route=\"{}\"",
+            escape_label(&self.method),
+            escape_label(&self.route)
+        )
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn status_class(status: StatusCode) -> &'static str {
+    match status.as_u16() {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::request_builder;
+    use crate::router::RouterService;
+    use futures::executor::block_on;
+    use std::str;
+
+    fn send(router: &RouterService, method: Method, uri: &str) -> Response {
+        let request = request_builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .expect("request");
+        block_on(router.oneshot(request)).expect("response")
+    }
+
+    /// Minimal exposition parser: every sample line is `name{labels} value`.
+    fn parse_samples(text: &str) -> Vec<(String, f64)> {
+        text.lines()
+            .filter(|line| !line.starts_with('#') && !line.is_empty())
+            .map(|line| {
+                let (series, value) = line.rsplit_once(' ').expect("sample line");
+                assert!(
+                    series.ends_with('}') && series.contains('{'),
+                    "bad series {series}"
+                );
+                (
+                    series.to_owned(),
+                    value.parse::<f64>().expect("numeric value"),
+                )
+            })
+            .collect()
+    }
+
+    fn sample(samples: &[(String, f64)], series: &str) -> Option<f64> {
+        samples
+            .iter()
+            .find(|(name, _)| name == series)
+            .map(|(_, value)| *value)
+    }
+
+    #[test]
+    fn metrics_endpoint_counts_requests_by_route_and_status_class() {
+        async fn user(ctx: RequestContext) -> Result<String, EdgeError> {
+            let id = ctx.path_params().get("id").unwrap_or_default().to_owned();
+            if id == "missing" {
+                return Err(EdgeError::not_found(id));
+            }
+            Ok(id)
+        }
+
+        let router = RouterService::builder()
+            .enable_metrics_at("/metrics")
+            .get("/users/{id}", user)
+            .build();
+
+        send(&router, Method::GET, "/users/1");
+        send(&router, Method::GET, "/users/2");
+        send(&router, Method::GET, "/users/missing");
+        send(&router, Method::GET, "/unrouted");
+
+        let response = send(&router, Method::GET, "/metrics");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response
+                .headers()
+                .get("content-type")
+                .expect("content type"),
+            PROMETHEUS_CONTENT_TYPE
+        );
+        let text = str::from_utf8(response.body().as_bytes().expect("buffered"))
+            .expect("utf8")
+            .to_owned();
+        let samples = parse_samples(&text);
+
+        let labels = "method=\"GET\",route=\"/users/{id}\"";
+        assert_eq!(
+            sample(
+                &samples,
+                &format!("edgezero_requests_total{{{labels},status=\"2xx\"}}")
+            ),
+            Some(2.0_f64)
+        );
+        assert_eq!(
+            sample(
+                &samples,
+                &format!("edgezero_requests_total{{{labels},status=\"4xx\"}}")
+            ),
+            Some(1.0_f64)
+        );
+        assert_eq!(
+            sample(
+                &samples,
+                &format!("edgezero_request_duration_seconds_count{{{labels}}}")
+            ),
+            Some(3.0_f64)
+        );
+        assert_eq!(
+            sample(
+                &samples,
+                &format!("edgezero_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}}")
+            ),
+            Some(3.0_f64)
+        );
+        assert!(!text.contains("/unrouted"));
+    }
+
+    #[test]
+    fn record_fills_cumulative_latency_buckets() {
+        let registry = MetricsRegistry::new();
+        registry.record(
+            &Method::POST,
+            "/a",
+            StatusCode::CREATED,
+            Duration::from_millis(30),
+        );
+        let samples = parse_samples(&registry.render());
+        let bucket = |le: &str| {
+            sample(
+                &samples,
+                &format!(
+                    "edgezero_request_duration_seconds_bucket{{method=\"POST\",route=\"/a\",le=\"{le}\"}}"
+                ),
+            )
+        };
+        assert_eq!(bucket("0.025"), Some(0.0_f64));
+        assert_eq!(bucket("0.05"), Some(1.0_f64));
+        assert_eq!(bucket("10"), Some(1.0_f64));
+        assert_eq!(
+            sample(
+                &samples,
+                "edgezero_request_duration_seconds_sum{method=\"POST\",route=\"/a\"}"
+            ),
+            Some(0.03_f64)
+        );
+    }
+
+    #[test]
+    fn status_classes_cover_all_ranges() {
+        assert_eq!(status_class(StatusCode::CONTINUE), "1xx");
+        assert_eq!(status_class(StatusCode::NO_CONTENT), "2xx");
+        assert_eq!(status_class(StatusCode::FOUND), "3xx");
+        assert_eq!(status_class(StatusCode::TOO_MANY_REQUESTS), "4xx");
+        assert_eq!(status_class(StatusCode::BAD_GATEWAY), "5xx");
+    }
+}
@@ -1,16 +1,78 @@
+use std::collections::HashMap;
 use std::future::Future;
+use std::mem;
 use std::sync::Arc;
 use web_time::Instant;
 
 use async_trait::async_trait;
+use bytes::Bytes;
+use thiserror::Error;
 
+use crate::body::Body;
 use crate::context::RequestContext;
 use crate::error::EdgeError;
 use crate::handler::DynHandler;
-use crate::http::Response;
+use crate::http::header::{CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE};
+use crate::http::{HeaderValue, Response};
+use crate::request_id::RequestId;
+use crate::response::IntoResponse as _;
+
+pub type BoxAfterMiddleware = Arc<dyn AfterMiddleware>;
+
+pub type BoxErrorHook = Arc<dyn ErrorHook>;
 
 pub type BoxMiddleware = Arc<dyn Middleware>;
 
+/// Response-only hook registered with
+/// [`RouterBuilder::after`](crate::router::RouterBuilder::after).
+///
+/// Runs on every response leaving [`RouterService::oneshot`] — after the
+/// whole [`Middleware`] chain and the handler, and after an [`EdgeError`]
+/// has been rendered with `into_response`, so error, 404, and 405 responses
+/// are covered too. Hooks cannot see the request or fail; middleware that
+/// needs either should wrap [`Next::run`] instead.
+///
+/// Closures `Fn(&mut Response)` implement this trait.
+///
+/// [`RouterService::oneshot`]: crate::router::RouterService::oneshot
+pub trait AfterMiddleware: Send + Sync + 'static {
+    fn after(&self, response: &mut Response);
+}
+
+impl<F> AfterMiddleware for F
+where
+    F: Fn(&mut Response) + Send + Sync + 'static,
+{
+    #[inline]
+    fn after(&self, response: &mut Response) {
+        self(response);
+    }
+}
+
+/// Error observer registered with
+/// [`RouterBuilder::on_error`](crate::router::RouterBuilder::on_error).
+///
+/// Called with every [`EdgeError`] the middleware chain or handler returns,
+/// before it is rendered as a response, together with the request it failed
+/// (without its body, which the handler has consumed). Use it to log the
+/// full `source()` chain while the client gets only the rendered message.
+/// Hooks cannot change the error; wrap [`Next::run`] in middleware for that.
+///
+/// Closures `Fn(&EdgeError, &RequestContext)` implement this trait.
+pub trait ErrorHook: Send + Sync + 'static {
+    fn on_error(&self, error: &EdgeError, ctx: &RequestContext);
+}
+
+impl<F> ErrorHook for F
+where
+    F: Fn(&EdgeError, &RequestContext) + Send + Sync + 'static,
+{
+    #[inline]
+    fn on_error(&self, error: &EdgeError, ctx: &RequestContext) {
+        self(error, ctx);
+    }
+}
+
 pub struct FnMiddleware<F>
 where
     F: Send + Sync + 'static,
@@ -46,6 +108,7 @@ pub trait Middleware: Send + Sync + 'static {
 }
 
 pub struct Next<'mw> {
+    error_hooks: &'mw [BoxErrorHook],
     handler: &'mw dyn DynHandler,
     middlewares: &'mw [BoxMiddleware],
 }
@@ -54,6 +117,7 @@ impl<'mw> Next<'mw> {
     #[inline]
     pub fn new(middlewares: &'mw [BoxMiddleware], handler: &'mw dyn DynHandler) -> Self {
         Self {
+            error_hooks: &[],
             handler,
             middlewares,
         }
@@ -62,13 +126,55 @@ impl<'mw> Next<'mw> {
     /// # Errors
     /// Returns whatever error the next middleware or the final handler produces.
     #[inline]
-    pub async fn run(self, ctx: RequestContext) -> Result<Response, EdgeError> {
+    pub async fn run(self, mut ctx: RequestContext) -> Result<Response, EdgeError> {
         if let Some((head, tail)) = self.middlewares.split_first() {
-            head.handle(ctx, Next::new(tail, self.handler)).await
+            // The previous middleware may have settled the body with `&mut`
+            // access; attach it again so the extractors can buffer it.
+            ctx.defer_body();
+            let rest = Next::new(tail, self.handler).with_error_hooks(self.error_hooks);
+            head.handle(ctx, rest).await
         } else {
-            self.handler.call(ctx).await
+            // A mounted router reuses its parent's parts, which `apply`
+            // drains, so each change is applied once.
+            let parts = ctx.response_parts();
+            ctx.request_mut().extensions_mut().insert(parts.clone());
+            ctx.defer_body();
+            let mut response = self.handler.call(ctx).await?;
+            parts.apply(&mut response);
+            Ok(response)
         }
     }
+
+    /// Run the rest of the chain like [`Self::run`], but render an error as
+    /// its response, for middleware that has to decorate every response.
+    /// The error is passed to the
+    /// [`RouterBuilder::on_error`](crate::router::RouterBuilder::on_error)
+    /// hooks first, since the router only reports errors that reach it.
+    ///
+    /// # Errors
+    /// Returns an error only if rendering the error fails.
+    #[inline]
+    pub async fn run_rendered(self, ctx: RequestContext) -> Result<Response, EdgeError> {
+        let hooks = self.error_hooks;
+        let reported = (!hooks.is_empty()).then(|| ctx.without_body());
+        match self.run(ctx).await {
+            Ok(response) => Ok(response),
+            Err(err) => {
+                if let Some(failed) = &reported {
+                    for hook in hooks {
+                        hook.on_error(&err, failed);
+                    }
+                }
+                err.into_response()
+            }
+        }
+    }
+
+    /// Pass errors rendered by [`Self::run_rendered`] to `hooks`.
+    pub(crate) const fn with_error_hooks(mut self, hooks: &'mw [BoxErrorHook]) -> Self {
+        self.error_hooks = hooks;
+        self
+    }
 }
 
 pub struct RequestLogger;
@@ -112,6 +218,273 @@ impl Middleware for RequestLogger {
     }
 }
 
+/// Appends a `Server-Timing` entry measuring the rest of the chain, such as
+/// `app;dur=12.503`, which browser developer tools show with the request.
+///
+/// Entries set by handlers are kept. Errors pass through untimed, so
+/// register it ahead of middleware that renders them, as
+/// [`RouterBuilder::with_observability`] does.
+///
+/// [`RouterBuilder::with_observability`]: crate::router::RouterBuilder::with_observability
+#[derive(Clone, Debug)]
+pub struct ServerTiming {
+    metric: String,
+}
+
+impl Default for ServerTiming {
+    #[inline]
+    fn default() -> Self {
+        Self::new("app")
+    }
+}
+
+#[async_trait(?Send)]
+impl Middleware for ServerTiming {
+    #[inline]
+    async fn handle(&self, ctx: RequestContext, next: Next<'_>) -> Result<Response, EdgeError> {
+        let start = Instant::now();
+        let mut response = next.run(ctx).await?;
+        let micros = start.elapsed().as_micros();
+        let entry = format!(
+            "{};dur={}.{:03}",
+            self.metric,
+            micros.checked_div(1000).unwrap_or_default(),
+            micros.checked_rem(1000).unwrap_or_default()
+        );
+        if let Ok(value) = HeaderValue::try_from(entry) {
+            response.headers_mut().append("server-timing", value);
+        }
+        Ok(response)
+    }
+}
+
+impl ServerTiming {
+    /// Report the duration under `metric` instead of `app`.
+    #[must_use]
+    #[inline]
+    pub fn new<S: Into<String>>(metric: S) -> Self {
+        Self {
+            metric: metric.into(),
+        }
+    }
+}
+
+/// Logs one `tracing` event per request with `method`, `path`, `status` and
+/// `elapsed_ms` fields, plus `request_id` when [`RequestIdMiddleware`] runs
+/// before it. Responses are logged at `INFO` and errors at `ERROR`, with
+/// the error message.
+///
+/// Unlike [`RequestLogger`], which writes the values into the message,
+/// the fields can be indexed by a JSON or OpenTelemetry subscriber.
+///
+/// [`RequestIdMiddleware`]: crate::request_id::RequestIdMiddleware
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StructuredLogger;
+
+#[async_trait(?Send)]
+impl Middleware for StructuredLogger {
+    #[inline]
+    async fn handle(&self, ctx: RequestContext, next: Next<'_>) -> Result<Response, EdgeError> {
+        let method = ctx.request().method().clone();
+        let path = ctx.request().uri().path().to_owned();
+        let request_id = ctx
+            .request()
+            .extensions()
+            .get::<RequestId>()
+            .map_or_else(String::new, |id| id.0.clone());
+        let start = Instant::now();
+
+        let result = next.run(ctx).await;
+        let elapsed_ms = start.elapsed().as_millis();
+        match &result {
+            Ok(response) => tracing::info!(
+                method = %method,
+                path = %path,
+                status = response.status().as_u16(),
+                elapsed_ms,
+                request_id = %request_id,
+                "request"
+            ),
+            Err(err) => tracing::error!(
+                method = %method,
+                path = %path,
+                status = err.status().as_u16(),
+                elapsed_ms,
+                request_id = %request_id,
+                error = %err.message(),
+                "request failed"
+            ),
+        }
+        result
+    }
+}
+
+type MiddlewareConstructor = Arc<dyn Fn() -> BoxMiddleware + Send + Sync>;
+
+/// Middleware constructors registered under names, so that `[app]
+/// middleware` in `edgezero.toml` can list middleware by name and decide
+/// their order. Names resolve when the router is built; see
+/// [`RouterBuilder::named_middleware`](crate::router::RouterBuilder::named_middleware).
+///
+/// ```rust,ignore
+/// let registry = MiddlewareRegistry::new()
+///     .register("logger", || RequestLogger)
+///     .register("cors", || Cors::permissive());
+/// ```
+#[derive(Clone, Default)]
+pub struct MiddlewareRegistry {
+    constructors: HashMap<String, MiddlewareConstructor>,
+}
+
+impl MiddlewareRegistry {
+    /// A new instance of the middleware registered as `name`.
+    ///
+    /// # Errors
+    /// Returns [`UnknownMiddleware`] if nothing is registered as `name`.
+    #[inline]
+    pub fn build(&self, name: &str) -> Result<BoxMiddleware, UnknownMiddleware> {
+        self.constructors
+            .get(name)
+            .map(|constructor| constructor())
+            .ok_or_else(|| UnknownMiddleware {
+                name: name.to_owned(),
+            })
+    }
+
+    /// Whether something is registered as `name`.
+    #[must_use]
+    #[inline]
+    pub fn contains(&self, name: &str) -> bool {
+        self.constructors.contains_key(name)
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `constructor` as `name`, replacing any earlier registration
+    /// under that name.
+    #[must_use]
+    #[inline]
+    pub fn register<F, M>(mut self, name: &str, constructor: F) -> Self
+    where
+        F: Fn() -> M + Send + Sync + 'static,
+        M: Middleware,
+    {
+        let boxed: MiddlewareConstructor = Arc::new(move || Arc::new(constructor()));
+        self.constructors.insert(name.to_owned(), boxed);
+        self
+    }
+}
+
+/// Middleware that rewrites buffered response bodies, e.g. to inject an
+/// analytics snippet into HTML.
+///
+/// A response whose `Content-Type` satisfies the predicate and whose body is
+/// a [`Body::Once`] has its bytes passed through the transform, and any
+/// `Content-Length` updated to match. Streaming bodies, encoded bodies
+/// (`Content-Encoding` other than `identity`), other content types, and
+/// errors pass through untouched.
+///
+/// ```rust,ignore
+/// let snippet = TransformBody::html(|html: Bytes| {
+///     let page = String::from_utf8_lossy(&html);
+///     Bytes::from(page.replacen("</body>", "<script src=\"/a.js\"></script></body>", 1))
+/// });
+/// ```
+pub struct TransformBody<P, F> {
+    predicate: P,
+    transform: F,
+}
+
+impl<F> TransformBody<fn(&str) -> bool, F>
+where
+    F: Fn(Bytes) -> Bytes + Send + Sync + 'static,
+{
+    /// Transform `text/html` responses.
+    #[inline]
+    pub fn html(transform: F) -> Self {
+        Self::new(is_html, transform)
+    }
+}
+
+impl<P, F> TransformBody<P, F>
+where
+    P: Fn(&str) -> bool + Send + Sync + 'static,
+    F: Fn(Bytes) -> Bytes + Send + Sync + 'static,
+{
+    /// Transform responses whose `Content-Type` value (e.g.
+    /// `text/html; charset=utf-8`) satisfies `predicate`.
+    #[inline]
+    pub fn new(predicate: P, transform: F) -> Self {
+        Self {
+            predicate,
+            transform,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<P, F> Middleware for TransformBody<P, F>
+where
+    P: Fn(&str) -> bool + Send + Sync + 'static,
+    F: Fn(Bytes) -> Bytes + Send + Sync + 'static,
+{
+    #[inline]
+    async fn handle(&self, ctx: RequestContext, next: Next<'_>) -> Result<Response, EdgeError> {
+        let mut response = next.run(ctx).await?;
+        let headers = response.headers();
+        let matches = headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|content_type| (self.predicate)(content_type));
+        let encoded = headers
+            .get(CONTENT_ENCODING)
+            .is_some_and(|value| value != "identity");
+        if !matches || encoded {
+            return Ok(response);
+        }
+        let Body::Once(bytes) = response.body_mut() else {
+            return Ok(response);
+        };
+        let transformed = (self.transform)(mem::take(bytes));
+        let length = transformed.len();
+        *bytes = transformed;
+        if response.headers().contains_key(CONTENT_LENGTH) {
+            response
+                .headers_mut()
+                .insert(CONTENT_LENGTH, HeaderValue::from(length));
+        }
+        Ok(response)
+    }
+}
+
+/// A middleware name with nothing registered under it in the
+/// [`MiddlewareRegistry`].
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("middleware `{name}` is not registered")]
+pub struct UnknownMiddleware {
+    name: String,
+}
+
+impl UnknownMiddleware {
+    /// The unregistered name.
+    #[must_use]
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+fn is_html(content_type: &str) -> bool {
+    content_type
+        .split(';')
+        .next()
+        .is_some_and(|essence| essence.trim().eq_ignore_ascii_case("text/html"))
+}
+
 #[inline]
 pub fn middleware_fn<F, Fut>(func: F) -> FnMiddleware<F>
 where
@@ -127,9 +500,12 @@ mod tests {
     use crate::body::Body;
     use crate::handler::IntoHandler as _;
     use crate::http::{Method, Response, StatusCode, request_builder};
+    use crate::manifest::ManifestLoader;
     use crate::params::PathParams;
     use crate::response::response_with_body;
+    use crate::router::RouterService;
     use futures::executor::block_on;
+    use futures::stream;
     use std::sync::atomic::{AtomicBool, Ordering};
     use std::sync::{Arc, Mutex};
 
@@ -182,6 +558,111 @@ mod tests {
         assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
     }
 
+    fn transform_router(content_type: &'static str, body: fn() -> Body) -> RouterService {
+        RouterService::builder()
+            .middleware(TransformBody::html(|html: Bytes| {
+                let page = String::from_utf8_lossy(&html).replacen(
+                    "</body>",
+                    "<script src=\"/a.js\"></script></body>",
+                    1,
+                );
+                Bytes::from(page)
+            }))
+            .get("/page", move |_ctx: RequestContext| async move {
+                let mut response = response_with_body(StatusCode::OK, body())?;
+                let buffered_len = response.body().as_bytes().map(<[u8]>::len);
+                let headers = response.headers_mut();
+                headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+                if let Some(length) = buffered_len {
+                    headers.insert(CONTENT_LENGTH, HeaderValue::from(length));
+                }
+                Ok::<_, EdgeError>(response)
+            })
+            .build()
+    }
+
+    fn get_page(router: &RouterService) -> Response {
+        let request = request_builder()
+            .uri("/page")
+            .body(Body::empty())
+            .expect("request");
+        block_on(router.oneshot(request)).expect("response")
+    }
+
+    #[test]
+    fn transform_body_rewrites_html_and_its_content_length() {
+        let router = transform_router("text/html; charset=utf-8", || {
+            Body::from("<html><body>hi</body></html>")
+        });
+        let response = get_page(&router);
+        let expected = "<html><body>hi<script src=\"/a.js\"></script></body></html>";
+        assert_eq!(response.body().as_bytes(), Some(expected.as_bytes()));
+        assert_eq!(
+            response.headers()[CONTENT_LENGTH],
+            expected.len().to_string()
+        );
+    }
+
+    #[test]
+    fn transform_body_leaves_json_and_streams_alone() {
+        let json = transform_router("application/json", || Body::from(r#"{"body":"</body>"}"#));
+        let response = get_page(&json);
+        assert_eq!(
+            response.body().as_bytes(),
+            Some(&br#"{"body":"</body>"}"#[..])
+        );
+        assert_eq!(response.headers()[CONTENT_LENGTH], "18");
+
+        let streamed = transform_router("text/html", || {
+            Body::stream(stream::iter([Bytes::from_static(b"</body>")]))
+        });
+        assert!(get_page(&streamed).body().is_stream());
+    }
+
+    #[test]
+    fn named_middleware_runs_in_manifest_order() {
+        let log: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+        let recording = |name: &'static str| {
+            let shared = Arc::clone(&log);
+            move || RecordingMiddleware {
+                log: Arc::clone(&shared),
+                name,
+            }
+        };
+        let registry = MiddlewareRegistry::new()
+            .register("second", recording("second"))
+            .register("first", recording("first"));
+        let loader = ManifestLoader::load_from_str(
+            "[app]\nmiddleware = [\"second\", \"first\", \"second\"]\n",
+        );
+
+        let router = RouterService::builder()
+            .named_middleware(&loader.manifest().app.middleware, &registry)
+            .expect("all names registered")
+            .get("/test", ok_handler)
+            .build();
+        let request = request_builder()
+            .uri("/test")
+            .body(Body::empty())
+            .expect("request");
+        let response = block_on(router.oneshot(request)).expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(*log.lock().unwrap(), ["second", "first", "second"]);
+    }
+
+    #[test]
+    fn named_middleware_rejects_unregistered_names() {
+        let registry = MiddlewareRegistry::new().register("logger", || RequestLogger);
+        assert!(registry.contains("logger"));
+
+        let err = RouterService::builder()
+            .named_middleware(["logger", "auth"], &registry)
+            .err()
+            .expect("`auth` is not registered");
+        assert_eq!(err.name(), "auth");
+        assert_eq!(err.to_string(), "middleware `auth` is not registered");
+    }
+
     #[test]
     fn middleware_chain_runs_in_order() {
         let log: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
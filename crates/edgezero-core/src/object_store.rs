@@ -0,0 +1,506 @@
+//! Provider-neutral object store abstraction for large blobs.
+//!
+//! Where [`crate::key_value_store`] holds small values read whole,
+//! [`ObjectStore`] serves large assets: object bodies travel as
+//! [`ObjectStream`]s, so neither reads nor writes need to hold a whole object
+//! in memory.
+//!
+//! Backends:
+//! - `FsObjectStore` (axum adapter) — a directory on disk, for local dev
+//! - `R2ObjectStore` (cloudflare adapter) — a Cloudflare R2 bucket binding
+//!
+//! Register an [`ObjectHandle`] as app state and take it in handlers with the
+//! [`crate::extractor::State`] extractor:
+//!
+//! ```rust,ignore
+//! #[action]
+//! async fn asset(
+//!     State(assets): State<ObjectHandle>,
+//!     Path(key): Path<String>,
+//! ) -> Result<Response, EdgeError> {
+//!     let object = assets
+//!         .get(&key)
+//!         .await?
+//!         .ok_or_else(|| EdgeError::not_found(key))?;
+//!     response_with_body(StatusCode::OK, object.into_body())
+//! }
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use futures_util::stream::{self, LocalBoxStream, Stream, StreamExt as _};
+
+use crate::body::Body;
+use crate::error::EdgeError;
+
+// ---------------------------------------------------------------------------
+// Contract test macro
+// ---------------------------------------------------------------------------
+
+/// Generate a suite of contract tests for any [`ObjectStore`] implementation.
+///
+/// The macro takes the module name and a factory expression that produces a
+/// fresh, empty store instance.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// edgezero_core::object_store_contract_tests!(fs_object_store_contract, {
+///     let dir = Box::leak(Box::new(tempfile::tempdir().unwrap()));
+///     FsObjectStore::new(dir.path())
+/// });
+/// ```
+#[macro_export]
+macro_rules! object_store_contract_tests {
+    ($mod_name:ident, $factory:expr) => {
+        mod $mod_name {
+            use super::*;
+            use bytes::Bytes;
+            use $crate::object_store::{ObjectStore, ObjectStream};
+
+            fn run<Fut: std::future::Future>(future: Fut) -> Fut::Output {
+                ::futures::executor::block_on(future)
+            }
+
+            fn chunked(chunks: &[&'static [u8]]) -> ObjectStream {
+                let owned: Vec<Result<Bytes, std::io::Error>> = chunks
+                    .iter()
+                    .map(|chunk| Ok(Bytes::from_static(chunk)))
+                    .collect();
+                ObjectStream::from_stream(::futures::stream::iter(owned))
+            }
+
+            #[test]
+            fn contract_put_and_get_round_trips_streamed_chunks() {
+                let store = $factory;
+                run(async {
+                    store
+                        .put(
+                            "docs/readme.txt",
+                            chunked(&[b"hello ", b"object ", b"store"]),
+                        )
+                        .await
+                        .unwrap();
+                    let object = store.get("docs/readme.txt").await.unwrap().unwrap();
+                    assert_eq!(object.size(), Some(18));
+                    assert_eq!(
+                        object.into_bytes().await.unwrap(),
+                        Bytes::from_static(b"hello object store")
+                    );
+                });
+            }
+
+            #[test]
+            fn contract_large_object_round_trips() {
+                let store = $factory;
+                run(async {
+                    // 300,000 bytes, spanning several read chunks.
+                    let payload: Vec<u8> = (0..75_000_u32).flat_map(u32::to_le_bytes).collect();
+                    store
+                        .put("large.bin", ObjectStream::from_bytes(payload.clone()))
+                        .await
+                        .unwrap();
+                    let object = store.get("large.bin").await.unwrap().unwrap();
+                    assert_eq!(object.into_bytes().await.unwrap(), Bytes::from(payload));
+                });
+            }
+
+            #[test]
+            fn contract_get_missing_returns_none() {
+                let store = $factory;
+                run(async {
+                    assert!(store.get("missing").await.unwrap().is_none());
+                });
+            }
+
+            #[test]
+            fn contract_head_reports_size_and_etag() {
+                let store = $factory;
+                run(async {
+                    assert!(store.head("asset.css").await.unwrap().is_none());
+                    let written = store
+                        .put("asset.css", ObjectStream::from_bytes("body{}"))
+                        .await
+                        .unwrap();
+                    let meta = store.head("asset.css").await.unwrap().unwrap();
+                    assert_eq!(meta.size, 6);
+                    assert!(!meta.etag.is_empty());
+                    assert_eq!(meta, written);
+                });
+            }
+
+            #[test]
+            fn contract_put_overwrites() {
+                let store = $factory;
+                run(async {
+                    store
+                        .put("k", ObjectStream::from_bytes("first"))
+                        .await
+                        .unwrap();
+                    store
+                        .put("k", ObjectStream::from_bytes("second!"))
+                        .await
+                        .unwrap();
+                    let object = store.get("k").await.unwrap().unwrap();
+                    assert_eq!(
+                        object.into_bytes().await.unwrap(),
+                        Bytes::from_static(b"second!")
+                    );
+                    assert_eq!(store.head("k").await.unwrap().unwrap().size, 7);
+                });
+            }
+
+            #[test]
+            fn contract_delete_removes_object() {
+                let store = $factory;
+                run(async {
+                    store.put("k", ObjectStream::from_bytes("v")).await.unwrap();
+                    store.delete("k").await.unwrap();
+                    assert!(store.get("k").await.unwrap().is_none());
+                    assert!(store.head("k").await.unwrap().is_none());
+                });
+            }
+
+            #[test]
+            fn contract_delete_nonexistent_ok() {
+                let store = $factory;
+                run(async {
+                    store.delete("nope").await.unwrap();
+                });
+            }
+        }
+    };
+}
+
+// ---------------------------------------------------------------------------
+// Error
+// ---------------------------------------------------------------------------
+
+/// Errors returned by object store operations.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ObjectError {
+    /// A general internal error, including a failed body stream.
+    #[error("object store error: {0}")]
+    Internal(#[from] anyhow::Error),
+
+    /// The object store backend is temporarily unavailable.
+    #[error("object store unavailable")]
+    Unavailable,
+
+    /// The key is not valid for the store.
+    #[error("validation error: {0}")]
+    Validation(String),
+}
+
+impl From<ObjectError> for EdgeError {
+    #[inline]
+    fn from(err: ObjectError) -> Self {
+        match err {
+            ObjectError::Internal(source) => EdgeError::internal(source),
+            ObjectError::Unavailable => EdgeError::service_unavailable("object store unavailable"),
+            ObjectError::Validation(msg) => {
+                EdgeError::bad_request(format!("object validation error: {msg}"))
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A cloneable handle to an object store that validates keys before
+/// delegating to the object-safe [`ObjectStore`] trait.
+#[derive(Clone)]
+pub struct ObjectHandle {
+    store: Arc<dyn ObjectStore>,
+}
+
+impl fmt::Debug for ObjectHandle {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObjectHandle").finish_non_exhaustive()
+    }
+}
+
+impl ObjectHandle {
+    /// Maximum key size in bytes (R2 limit).
+    pub const MAX_KEY_SIZE: usize = 1_024;
+
+    /// Delete an object. Deleting a missing object is not an error.
+    ///
+    /// # Errors
+    /// Returns [`ObjectError::Validation`] for an invalid key, or the
+    /// backend's error.
+    #[inline]
+    pub async fn delete(&self, key: &str) -> Result<(), ObjectError> {
+        Self::validate_key(key)?;
+        self.store.delete(key).await
+    }
+
+    /// Stream an object's body, or `None` if there is no such object.
+    ///
+    /// # Errors
+    /// Returns [`ObjectError::Validation`] for an invalid key, or the
+    /// backend's error.
+    #[inline]
+    pub async fn get(&self, key: &str) -> Result<Option<ObjectStream>, ObjectError> {
+        Self::validate_key(key)?;
+        self.store.get(key).await
+    }
+
+    /// An object's size and `ETag`, without its body.
+    ///
+    /// # Errors
+    /// Returns [`ObjectError::Validation`] for an invalid key, or the
+    /// backend's error.
+    #[inline]
+    pub async fn head(&self, key: &str) -> Result<Option<ObjectMeta>, ObjectError> {
+        Self::validate_key(key)?;
+        self.store.head(key).await
+    }
+
+    /// Create a new handle wrapping an object store implementation.
+    #[inline]
+    pub fn new(store: Arc<dyn ObjectStore>) -> Self {
+        Self { store }
+    }
+
+    /// Write `body` under `key`, replacing any existing object.
+    ///
+    /// # Errors
+    /// Returns [`ObjectError::Validation`] for an invalid key, or the
+    /// backend's error; a failing `body` stream aborts the write.
+    #[inline]
+    pub async fn put(&self, key: &str, body: ObjectStream) -> Result<ObjectMeta, ObjectError> {
+        Self::validate_key(key)?;
+        self.store.put(key, body).await
+    }
+
+    fn validate_key(key: &str) -> Result<(), ObjectError> {
+        if key.is_empty() {
+            return Err(ObjectError::Validation("key must not be empty".to_owned()));
+        }
+        if key.len() > Self::MAX_KEY_SIZE {
+            return Err(ObjectError::Validation(format!(
+                "key length {} exceeds limit of {} bytes",
+                key.len(),
+                Self::MAX_KEY_SIZE
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Size and `ETag` of a stored object, as returned by [`ObjectStore::head`]
+/// and [`ObjectStore::put`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ObjectMeta {
+    /// Quoted entity tag, usable as an HTTP `ETag` header value.
+    pub etag: String,
+    /// Body size in bytes.
+    pub size: u64,
+}
+
+/// An object body as a stream of chunks, with its size when known.
+///
+/// Backends that need the length up front (R2) use [`Self::size`] to stream
+/// the upload; set it with [`Self::with_size`] when the source knows it,
+/// e.g. from a request's `Content-Length`.
+pub struct ObjectStream {
+    size: Option<u64>,
+    stream: LocalBoxStream<'static, Result<Bytes, anyhow::Error>>,
+}
+
+impl fmt::Debug for ObjectStream {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObjectStream")
+            .field("size", &self.size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ObjectStream {
+    /// Stream a [`Body`]; a buffered body's size is known.
+    #[must_use]
+    #[inline]
+    pub fn from_body(body: Body) -> Self {
+        match body {
+            Body::Once(bytes) => Self::from_bytes(bytes),
+            Body::Stream(stream) | Body::StreamWithTrailers(stream, _) => {
+                Self { size: None, stream }
+            }
+        }
+    }
+
+    /// A single-chunk stream of `bytes`.
+    #[must_use]
+    #[inline]
+    pub fn from_bytes<B>(bytes: B) -> Self
+    where
+        B: Into<Bytes>,
+    {
+        let chunk: Bytes = bytes.into();
+        Self {
+            size: u64::try_from(chunk.len()).ok(),
+            stream: stream::once(async move { Ok(chunk) }).boxed_local(),
+        }
+    }
+
+    /// Stream chunks of unknown total size.
+    #[must_use]
+    #[inline]
+    pub fn from_stream<S, E>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<Bytes, E>> + 'static,
+        anyhow::Error: From<E>,
+    {
+        Self {
+            size: None,
+            stream: stream
+                .map(|res| res.map_err(anyhow::Error::from))
+                .boxed_local(),
+        }
+    }
+
+    /// A streaming response [`Body`].
+    #[must_use]
+    #[inline]
+    pub fn into_body(self) -> Body {
+        Body::Stream(self.stream)
+    }
+
+    /// Collect the whole object into memory. Prefer [`Self::into_body`] or
+    /// [`Self::into_stream`] for large objects.
+    ///
+    /// # Errors
+    /// Returns [`ObjectError::Internal`] if the stream fails.
+    #[inline]
+    pub async fn into_bytes(mut self) -> Result<Bytes, ObjectError> {
+        let mut buf = BytesMut::new();
+        while let Some(chunk) = self.stream.next().await {
+            buf.extend_from_slice(&chunk?);
+        }
+        Ok(buf.freeze())
+    }
+
+    /// The underlying chunk stream.
+    #[must_use]
+    #[inline]
+    pub fn into_stream(self) -> LocalBoxStream<'static, Result<Bytes, anyhow::Error>> {
+        self.stream
+    }
+
+    /// Total body size in bytes, when known.
+    #[must_use]
+    #[inline]
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    /// Declare the total body size, e.g. from a `Content-Length` header.
+    /// A stream that yields a different number of bytes fails the write on
+    /// backends that rely on the size.
+    #[must_use]
+    #[inline]
+    pub fn with_size(mut self, size: u64) -> Self {
+        self.size = Some(size);
+        self
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Trait
+// ---------------------------------------------------------------------------
+
+/// Object-safe interface implemented by each object store backend. Callers
+/// normally go through [`ObjectHandle`], which validates keys first.
+#[async_trait(?Send)]
+pub trait ObjectStore: Send + Sync {
+    /// Delete `key`; a missing object is not an error.
+    async fn delete(&self, key: &str) -> Result<(), ObjectError>;
+
+    /// Stream the object at `key`, or `None` if there is none.
+    async fn get(&self, key: &str) -> Result<Option<ObjectStream>, ObjectError>;
+
+    /// Size and `ETag` of the object at `key`, or `None` if there is none.
+    async fn head(&self, key: &str) -> Result<Option<ObjectMeta>, ObjectError>;
+
+    /// Write `body` to `key`, replacing any existing object, and return the
+    /// new object's metadata.
+    async fn put(&self, key: &str, body: ObjectStream) -> Result<ObjectMeta, ObjectError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use std::io;
+
+    struct Unreachable;
+
+    #[async_trait(?Send)]
+    impl ObjectStore for Unreachable {
+        async fn delete(&self, _key: &str) -> Result<(), ObjectError> {
+            Err(ObjectError::Unavailable)
+        }
+
+        async fn get(&self, _key: &str) -> Result<Option<ObjectStream>, ObjectError> {
+            Err(ObjectError::Unavailable)
+        }
+
+        async fn head(&self, _key: &str) -> Result<Option<ObjectMeta>, ObjectError> {
+            Err(ObjectError::Unavailable)
+        }
+
+        async fn put(&self, _key: &str, _body: ObjectStream) -> Result<ObjectMeta, ObjectError> {
+            Err(ObjectError::Unavailable)
+        }
+    }
+
+    #[test]
+    fn handle_rejects_empty_and_oversized_keys() {
+        let handle = ObjectHandle::new(Arc::new(Unreachable));
+        let long_key = "k".repeat(ObjectHandle::MAX_KEY_SIZE + 1);
+        for key in ["", long_key.as_str()] {
+            let err = block_on(handle.head(key)).expect_err("invalid key");
+            assert!(matches!(err, ObjectError::Validation(_)), "{err}");
+        }
+        let err = block_on(handle.head("ok")).expect_err("backend error");
+        assert!(matches!(err, ObjectError::Unavailable));
+    }
+
+    #[test]
+    fn object_stream_tracks_size_and_collects_chunks() {
+        let buffered = ObjectStream::from_body(Body::from("abc"));
+        assert_eq!(buffered.size(), Some(3));
+
+        let chunks = vec![
+            Ok::<_, io::Error>(Bytes::from_static(b"ab")),
+            Ok(Bytes::from_static(b"cd")),
+        ];
+        let streamed = ObjectStream::from_stream(stream::iter(chunks));
+        assert_eq!(streamed.size(), None);
+        let sized = streamed.with_size(4);
+        assert_eq!(sized.size(), Some(4));
+        assert_eq!(
+            block_on(sized.into_bytes()).expect("bytes"),
+            Bytes::from_static(b"abcd")
+        );
+    }
+
+    #[test]
+    fn object_errors_map_to_edge_errors() {
+        use crate::http::StatusCode;
+
+        let bad: EdgeError = ObjectError::Validation("nope".to_owned()).into();
+        assert_eq!(bad.status(), StatusCode::BAD_REQUEST);
+        let down: EdgeError = ObjectError::Unavailable.into();
+        assert_eq!(down.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -0,0 +1,247 @@
+//! The standard observability middleware, installed together.
+//!
+//! [`RouterBuilder::with_observability`] registers, outermost first:
+//!
+//! 1. [`ServerTiming`], which times everything below it;
+//! 2. [`RequestIdMiddleware`], which gives the request its [`RequestId`] and
+//!    renders errors so that error responses carry the id and a timing too;
+//! 3. [`StructuredLogger`], which logs each request with its id.
+//!
+//! Middleware registered afterwards, and the handler, run inside all three.
+//! To change one part or leave it out, pass an [`Observability`] to
+//! [`RouterBuilder::observability`] instead:
+//!
+//! ```rust,ignore
+//! let router = RouterService::builder()
+//!     .observability(
+//!         Observability::default()
+//!             .request_id(RequestIdMiddleware::default().trust_incoming(false))
+//!             .without_server_timing(),
+//!     )
+//!     .get("/", index)
+//!     .build();
+//! ```
+//!
+//! [`RequestId`]: crate::request_id::RequestId
+//! [`RouterBuilder::observability`]: crate::router::RouterBuilder::observability
+//! [`RouterBuilder::with_observability`]: crate::router::RouterBuilder::with_observability
+
+use std::sync::Arc;
+
+use crate::middleware::{BoxMiddleware, ServerTiming, StructuredLogger};
+use crate::request_id::RequestIdMiddleware;
+
+/// Which observability middleware to install, and how each is configured.
+///
+/// The default installs all three with their defaults.
+#[derive(Clone, Debug)]
+pub struct Observability {
+    logger: Option<StructuredLogger>,
+    request_id: Option<RequestIdMiddleware>,
+    server_timing: Option<ServerTiming>,
+}
+
+impl Default for Observability {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            logger: Some(StructuredLogger),
+            request_id: Some(RequestIdMiddleware::default()),
+            server_timing: Some(ServerTiming::default()),
+        }
+    }
+}
+
+impl Observability {
+    /// The enabled middleware, outermost first.
+    pub(crate) fn into_middlewares(self) -> Vec<BoxMiddleware> {
+        let mut middlewares: Vec<BoxMiddleware> = Vec::new();
+        if let Some(timing) = self.server_timing {
+            middlewares.push(Arc::new(timing));
+        }
+        if let Some(request_id) = self.request_id {
+            middlewares.push(Arc::new(request_id));
+        }
+        if let Some(logger) = self.logger {
+            middlewares.push(Arc::new(logger));
+        }
+        middlewares
+    }
+
+    /// Log requests with `logger`.
+    #[must_use]
+    #[inline]
+    pub const fn logger(mut self, logger: StructuredLogger) -> Self {
+        self.logger = Some(logger);
+        self
+    }
+
+    /// Assign request ids with `request_id`.
+    #[must_use]
+    #[inline]
+    pub fn request_id(mut self, request_id: RequestIdMiddleware) -> Self {
+        self.request_id = Some(request_id);
+        self
+    }
+
+    /// Time requests with `timing`.
+    #[must_use]
+    #[inline]
+    pub fn server_timing(mut self, timing: ServerTiming) -> Self {
+        self.server_timing = Some(timing);
+        self
+    }
+
+    /// Leave out the [`StructuredLogger`].
+    #[must_use]
+    #[inline]
+    pub const fn without_logger(mut self) -> Self {
+        self.logger = None;
+        self
+    }
+
+    /// Leave out the [`RequestIdMiddleware`]. Errors then reach the logger
+    /// and [`ServerTiming`] unrendered, so error responses are not timed.
+    #[must_use]
+    #[inline]
+    pub fn without_request_id(mut self) -> Self {
+        self.request_id = None;
+        self
+    }
+
+    /// Leave out [`ServerTiming`].
+    #[must_use]
+    #[inline]
+    pub fn without_server_timing(mut self) -> Self {
+        self.server_timing = None;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::Body;
+    use crate::context::RequestContext;
+    use crate::error::EdgeError;
+    use crate::extractor::FromRequest as _;
+    use crate::http::request_builder;
+    use crate::request_id::{FixedRequestId, RequestId, X_REQUEST_ID};
+    use crate::router::RouterService;
+    use crate::test_client::TestClient;
+    use futures::executor::block_on;
+    use std::fmt::Debug;
+    use std::sync::Mutex;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::subscriber::with_default;
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Records the fields of every event as `name=value` lines.
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<String>>>);
+
+    struct Fields(Vec<String>);
+
+    #[expect(
+        clippy::missing_trait_methods,
+        reason = "the test only needs events, not spans"
+    )]
+    impl Subscriber for Capture {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields(Vec::new());
+            event.record(&mut fields);
+            self.0.lock().expect("capture").push(fields.0.join(" "));
+        }
+
+        fn exit(&self, _span: &Id) {}
+
+        fn new_span(&self, _span: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+    }
+
+    #[expect(
+        clippy::missing_trait_methods,
+        reason = "every field type is formatted through record_debug"
+    )]
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.push(format!("{}={value:?}", field.name()));
+        }
+    }
+
+    async fn reject(_ctx: RequestContext) -> Result<String, EdgeError> {
+        Err(EdgeError::bad_request("no"))
+    }
+
+    async fn whoami(ctx: RequestContext) -> Result<String, EdgeError> {
+        Ok(RequestId::from_request(&ctx).await?.into_inner())
+    }
+
+    #[test]
+    fn the_default_stack_sets_an_id_a_log_record_and_a_timing() {
+        let router = RouterService::builder()
+            .with_request_id_generator(FixedRequestId::new("req-42"))
+            .with_observability()
+            .get("/whoami", whoami)
+            .build();
+        let capture = Capture::default();
+
+        let response = with_default(capture.clone(), || {
+            block_on(TestClient::new(router).get("/whoami"))
+        });
+
+        assert_eq!(response.text(), "req-42");
+        assert_eq!(response.header(X_REQUEST_ID), Some("req-42"));
+        let timing = response.header("server-timing").expect("server-timing");
+        assert!(timing.starts_with("app;dur="), "{timing}");
+        let records = capture.0.lock().expect("capture");
+        assert!(
+            records.iter().any(|record| record.contains("path=/whoami")
+                && record.contains("status=200")
+                && record.contains("request_id=req-42")),
+            "{records:?}"
+        );
+    }
+
+    #[test]
+    fn parts_can_be_replaced_or_left_out() {
+        let router = RouterService::builder()
+            .observability(
+                Observability::default()
+                    .request_id(RequestIdMiddleware::default().trust_incoming(false))
+                    .server_timing(ServerTiming::new("edge"))
+                    .without_logger(),
+            )
+            .get("/whoami", whoami)
+            .get("/reject", reject)
+            .build();
+        let client = TestClient::new(router);
+        let request = request_builder()
+            .uri("/whoami")
+            .header(X_REQUEST_ID, "from-caller")
+            .body(Body::empty())
+            .expect("request");
+
+        let response = block_on(client.send(request));
+        assert_ne!(response.header(X_REQUEST_ID), Some("from-caller"));
+        let timing = response.header("server-timing").expect("server-timing");
+        assert!(timing.starts_with("edge;dur="), "{timing}");
+
+        let rejected = block_on(client.get("/reject"));
+        assert_eq!(rejected.status().as_u16(), 400);
+        assert!(rejected.header(X_REQUEST_ID).is_some());
+        assert!(rejected.header("server-timing").is_some());
+    }
+}
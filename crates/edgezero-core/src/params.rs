@@ -1,8 +1,12 @@
 use std::collections::HashMap;
+use std::str;
 
 use serde::de::DeserializeOwned;
 
 /// Normalised view of path parameters captured by the router.
+///
+/// The router percent-decodes each captured value (see
+/// [`decode_path_param`]), so `/files/a%20b` gives `"a b"` for `{name}`.
 #[derive(Clone, Debug, Default)]
 pub struct PathParams {
     inner: HashMap<String, String>,
@@ -25,6 +29,29 @@ impl PathParams {
         self.inner.get(key).map(String::as_str)
     }
 
+    /// Whether the route captured no params.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Iterate over `(name, value)` pairs, in no particular order.
+    #[must_use]
+    #[inline]
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (&str, &str)> {
+        self.inner
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Number of captured params.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
     #[must_use]
     #[inline]
     pub fn new(inner: HashMap<String, String>) -> Self {
@@ -32,6 +59,41 @@ impl PathParams {
     }
 }
 
+/// Percent-decode a captured path param. `%2F` stays encoded: routes match
+/// on the raw path, so a decoded slash would make one segment look like
+/// two. `%25` stays encoded as well, so a literal `%2F` (sent as `%252F`)
+/// is not confused with an encoded slash. Malformed escapes (`%`, `%zz`)
+/// are kept literally. Returns `None` if the decoded bytes are not UTF-8.
+pub(crate) fn decode_path_param(raw: &str) -> Option<String> {
+    if !raw.contains('%') {
+        return Some(raw.to_owned());
+    }
+    let mut decoded = Vec::with_capacity(raw.len());
+    let mut rest = raw.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%'
+            && let Some(value) = tail.get(..2).and_then(decode_hex_pair)
+            && value != b'/'
+            && value != b'%'
+        {
+            decoded.push(value);
+            rest = tail.get(2..).unwrap_or_default();
+            continue;
+        }
+        decoded.push(byte);
+        rest = tail;
+    }
+    String::from_utf8(decoded).ok()
+}
+
+fn decode_hex_pair(pair: &[u8]) -> Option<u8> {
+    if !pair.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    let digits = str::from_utf8(pair).ok()?;
+    u8::from_str_radix(digits, 16).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,6 +112,19 @@ mod tests {
         PathParams::new(inner)
     }
 
+    #[test]
+    fn decode_path_param_decodes_escapes_but_keeps_encoded_slashes_and_percents() {
+        assert_eq!(decode_path_param("plain").as_deref(), Some("plain"));
+        assert_eq!(decode_path_param("a%20b").as_deref(), Some("a b"));
+        assert_eq!(decode_path_param("caf%C3%A9").as_deref(), Some("caf\u{e9}"));
+        assert_eq!(decode_path_param("a%2Fb%2fc").as_deref(), Some("a%2Fb%2fc"));
+        assert_eq!(decode_path_param("a%252Fb").as_deref(), Some("a%252Fb"));
+        assert_eq!(decode_path_param("100%25").as_deref(), Some("100%25"));
+        assert_eq!(decode_path_param("100%").as_deref(), Some("100%"));
+        assert_eq!(decode_path_param("%zz%+f").as_deref(), Some("%zz%+f"));
+        assert_eq!(decode_path_param("%FF"), None);
+    }
+
     #[test]
     fn deserialize_converts_to_target_type() {
         let params = params(&[("id", "42")]);
@@ -77,4 +152,16 @@ mod tests {
         assert_eq!(params.get("id"), Some("7"));
         assert_eq!(params.get("missing"), None);
     }
+
+    #[test]
+    fn iter_yields_every_captured_pair() {
+        let params = params(&[("org", "stackpop"), ("repo", "edgezero")]);
+        assert_eq!(params.len(), 2);
+        assert!(!params.is_empty());
+        let mut pairs: Vec<(&str, &str)> = params.iter().collect();
+        pairs.sort_unstable();
+        assert_eq!(pairs, [("org", "stackpop"), ("repo", "edgezero")]);
+        assert_eq!(params.iter().len(), 2);
+        assert!(PathParams::default().is_empty());
+    }
 }
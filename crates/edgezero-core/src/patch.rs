@@ -0,0 +1,180 @@
+//! Telling a JSON field set to `null` apart from one left out.
+//!
+//! Serde reads both `{"nickname": null}` and `{}` into `None` for an
+//! `Option<T>` field, so a `PATCH` handler cannot tell "clear the nickname"
+//! from "leave it alone". A [`Patch<T>`] field keeps the three cases apart:
+//!
+//! ```rust,ignore
+//! #[derive(serde::Deserialize)]
+//! struct ProfileUpdate {
+//!     #[serde(default)]
+//!     nickname: Patch<String>,
+//! }
+//!
+//! #[action]
+//! async fn update(Json(update): Json<ProfileUpdate>) -> Result<(), EdgeError> {
+//!     match update.nickname {
+//!         Patch::Missing => {}                        // {}
+//!         Patch::Null => clear_nickname(),            // {"nickname": null}
+//!         Patch::Value(name) => set_nickname(name),   // {"nickname": "edge"}
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+//! The field needs `#[serde(default)]`: serde fills in a missing field from
+//! [`Default`], which is [`Patch::Missing`]. Without it, a missing field
+//! reads as [`Patch::Null`]. It works with every JSON extractor and with
+//! [`Form`](crate::extractor::Form) and [`Query`](crate::extractor::Query),
+//! where there is no `null`, so a field is only ever missing or set.
+
+use serde::{Deserialize, Deserializer};
+
+/// A field that may be left out, set to `null`, or set to a value.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Patch<T> {
+    /// The field was not in the body.
+    #[default]
+    Missing,
+    /// The field was `null`.
+    Null,
+    /// The field was set to a value.
+    Value(T),
+}
+
+#[expect(
+    clippy::missing_trait_methods,
+    reason = "default deserialize_in_place is identical to what we would write manually"
+)]
+impl<'de, T> Deserialize<'de> for Patch<T>
+where
+    T: Deserialize<'de>,
+{
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<T>::deserialize(deserializer).map(|value| value.map_or(Self::Null, Self::Value))
+    }
+}
+
+impl<T> From<Patch<T>> for Option<Option<T>> {
+    /// `None` for [`Patch::Missing`], `Some(None)` for [`Patch::Null`].
+    #[inline]
+    fn from(patch: Patch<T>) -> Self {
+        patch.into_option()
+    }
+}
+
+impl<T> Patch<T> {
+    /// `None` for [`Patch::Missing`], `Some(None)` for [`Patch::Null`], and
+    /// `Some(Some(value))` for [`Patch::Value`].
+    #[must_use]
+    #[inline]
+    pub fn into_option(self) -> Option<Option<T>> {
+        match self {
+            Self::Missing => None,
+            Self::Null => Some(None),
+            Self::Value(value) => Some(Some(value)),
+        }
+    }
+
+    /// Whether the field was left out.
+    #[must_use]
+    #[inline]
+    pub const fn is_missing(&self) -> bool {
+        matches!(self, Self::Missing)
+    }
+
+    /// Whether the field was `null`.
+    #[must_use]
+    #[inline]
+    pub const fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// The value the field was set to, if any.
+    #[must_use]
+    #[inline]
+    pub const fn value(&self) -> Option<&T> {
+        match self {
+            Self::Value(value) => Some(value),
+            Self::Missing | Self::Null => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::RequestContext;
+    use crate::error::EdgeError;
+    use crate::extractor::{FromRequest as _, Json, StrictJson};
+    use crate::http::header::CONTENT_TYPE;
+    use crate::http::{Method, request_builder};
+    use crate::router::RouterService;
+    use crate::test_client::TestClient;
+    use futures::executor::block_on;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct ProfileUpdate {
+        #[serde(default)]
+        nickname: Patch<String>,
+    }
+
+    #[test]
+    fn null_value_and_absent_fields_deserialize_differently() {
+        let cases = [
+            (r#"{"nickname": null}"#, Patch::Null),
+            (r#"{"nickname": "edge"}"#, Patch::Value("edge".to_owned())),
+            ("{}", Patch::Missing),
+        ];
+        for (body, nickname) in cases {
+            let update: ProfileUpdate = serde_json::from_str(body).expect("valid body");
+            assert_eq!(update, ProfileUpdate { nickname });
+        }
+    }
+
+    #[test]
+    fn patch_converts_to_a_double_option() {
+        assert_eq!(Patch::<u8>::Missing.into_option(), None);
+        assert_eq!(Option::from(Patch::<u8>::Null), Some(None));
+        assert_eq!(Patch::Value(3_u8).into_option(), Some(Some(3_u8)));
+        assert_eq!(Patch::Value(3_u8).value(), Some(&3_u8));
+        assert!(Patch::<u8>::Null.is_null() && Patch::<u8>::Missing.is_missing());
+    }
+
+    #[test]
+    fn json_extractors_keep_the_distinction() {
+        async fn update(ctx: RequestContext) -> Result<String, EdgeError> {
+            let Json(update) = Json::<ProfileUpdate>::from_request(&ctx).await?;
+            let StrictJson(strict) = StrictJson::<ProfileUpdate>::from_request(&ctx).await?;
+            assert_eq!(update, strict);
+            Ok(match update.nickname {
+                Patch::Missing => "unchanged".to_owned(),
+                Patch::Null => "cleared".to_owned(),
+                Patch::Value(name) => format!("set to {name}"),
+            })
+        }
+
+        let client = TestClient::new(
+            RouterService::builder()
+                .route("/profile", Method::PATCH, update)
+                .build(),
+        );
+        for (body, expected) in [
+            (r#"{"nickname":null}"#, "cleared"),
+            (r#"{"nickname":"edge"}"#, "set to edge"),
+            ("{}", "unchanged"),
+        ] {
+            let request = request_builder()
+                .method(Method::PATCH)
+                .uri("/profile")
+                .header(CONTENT_TYPE, "application/json")
+                .body(body.into())
+                .expect("request");
+            assert_eq!(block_on(client.send(request)).text(), expected);
+        }
+    }
+}
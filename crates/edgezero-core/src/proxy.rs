@@ -1,13 +1,43 @@
 use std::fmt;
+use std::mem;
+use std::ops::Deref;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
 
 use async_trait::async_trait;
+use futures_util::FutureExt as _;
+use futures_util::future::LocalBoxFuture;
 
 use crate::body::Body;
+use crate::compression::{self, AcceptEncoding, Coding};
+use crate::context::RequestContext;
+use crate::deadline::Deadline;
 use crate::error::EdgeError;
+use crate::extractor::FromRequest;
+use crate::http::header::{
+    CONNECTION, CONTENT_ENCODING, CONTENT_LENGTH, HeaderName, PROXY_AUTHENTICATE,
+    PROXY_AUTHORIZATION, TE, TRAILER, TRANSFER_ENCODING, UPGRADE,
+};
 use crate::http::{
-    Extensions, HeaderMap, Method, Request, Response, StatusCode, Uri, response_builder,
+    Extensions, HeaderMap, HeaderValue, Method, Request, Response, StatusCode, Uri,
+    response_builder,
 };
+use crate::response;
+use crate::trace_context::TraceParent;
+
+/// Headers that describe a single connection rather than the message, and
+/// so must not be forwarded (RFC 9110, section 7.6.1).
+const HOP_BY_HOP: [HeaderName; 8] = [
+    CONNECTION,
+    HeaderName::from_static("keep-alive"),
+    PROXY_AUTHENTICATE,
+    PROXY_AUTHORIZATION,
+    TE,
+    TRAILER,
+    TRANSFER_ENCODING,
+    UPGRADE,
+];
 
 /// Header name attached to proxied responses to identify which adapter
 /// forwarded the request (e.g. "fastly", "cloudflare", "spin").
@@ -18,16 +48,63 @@ pub trait ProxyClient: Send + Sync {
     async fn send(&self, request: ProxyRequest) -> Result<ProxyResponse, EdgeError>;
 }
 
+/// Adjusts every outbound request of a [`ProxyHandle`] before it is sent,
+/// e.g. to add credentials. Implemented for `Fn(&mut ProxyRequest)`
+/// closures; implement it directly when the adjustment needs to await.
+#[async_trait(?Send)]
+pub trait ProxyRequestInterceptor: Send + Sync {
+    async fn intercept(&self, request: &mut ProxyRequest);
+}
+
+#[async_trait(?Send)]
+impl<F> ProxyRequestInterceptor for F
+where
+    F: Fn(&mut ProxyRequest) + Send + Sync,
+{
+    #[inline]
+    async fn intercept(&self, request: &mut ProxyRequest) {
+        self(request);
+    }
+}
+
+/// Inspects or adjusts every response a [`ProxyHandle`] receives, e.g. to
+/// record metrics. Implemented for `Fn(&mut ProxyResponse)` closures;
+/// implement it directly when the work needs to await.
+#[async_trait(?Send)]
+pub trait ProxyResponseInterceptor: Send + Sync {
+    async fn intercept(&self, response: &mut ProxyResponse);
+}
+
+#[async_trait(?Send)]
+impl<F> ProxyResponseInterceptor for F
+where
+    F: Fn(&mut ProxyResponse) + Send + Sync,
+{
+    #[inline]
+    async fn intercept(&self, response: &mut ProxyResponse) {
+        self(response);
+    }
+}
+
 #[derive(Clone)]
 pub struct ProxyHandle {
     client: Arc<dyn ProxyClient>,
+    request_interceptors: Vec<Arc<dyn ProxyRequestInterceptor>>,
+    response_interceptors: Vec<Arc<dyn ProxyResponseInterceptor>>,
 }
 
 impl ProxyHandle {
+    /// The client requests are sent with. With interceptors registered, a
+    /// client that runs them around the underlying one.
     #[must_use]
     #[inline]
     pub fn client(&self) -> Arc<dyn ProxyClient> {
-        Arc::clone(&self.client)
+        if self.request_interceptors.is_empty() && self.response_interceptors.is_empty() {
+            return Arc::clone(&self.client);
+        }
+        Arc::new(InterceptedClient {
+            handle: self.clone(),
+        })
     }
 
     /// # Errors
@@ -35,13 +112,41 @@ impl ProxyHandle {
     /// response cannot be assembled.
     #[inline]
     pub async fn forward(&self, request: ProxyRequest) -> Result<Response, EdgeError> {
-        let response = self.client.send(request).await?;
+        let response = self.send(request).await?;
         response.into_response()
     }
 
+    /// This handle with `interceptors` run after its own, as the router
+    /// layers its app-wide ones onto the adapter's handle.
+    #[must_use]
+    pub(crate) fn layered(mut self, interceptors: &ProxyInterceptors) -> Self {
+        self.request_interceptors
+            .extend(interceptors.request.iter().cloned());
+        self.response_interceptors
+            .extend(interceptors.response.iter().cloned());
+        self
+    }
+
     #[inline]
     pub fn new(client: Arc<dyn ProxyClient>) -> Self {
-        Self { client }
+        Self {
+            client,
+            request_interceptors: Vec::new(),
+            response_interceptors: Vec::new(),
+        }
+    }
+
+    /// Run the request interceptors, send, then run the response
+    /// interceptors. A failed send skips the response interceptors.
+    async fn send(&self, mut request: ProxyRequest) -> Result<ProxyResponse, EdgeError> {
+        for interceptor in &self.request_interceptors {
+            interceptor.intercept(&mut request).await;
+        }
+        let mut response = self.client.send(request).await?;
+        for interceptor in &self.response_interceptors {
+            interceptor.intercept(&mut response).await;
+        }
+        Ok(response)
     }
 
     #[inline]
@@ -49,10 +154,254 @@ impl ProxyHandle {
     where
         C: ProxyClient + 'static,
     {
+        Self::new(Arc::new(client))
+    }
+
+    /// Run `interceptor` on every request before it is sent, after the
+    /// interceptors registered earlier.
+    ///
+    /// ```rust,ignore
+    /// let proxy = proxy.with_request_interceptor(move |request: &mut ProxyRequest| {
+    ///     request.headers_mut().insert(AUTHORIZATION, token.clone());
+    /// });
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn with_request_interceptor<I>(mut self, interceptor: I) -> Self
+    where
+        I: ProxyRequestInterceptor + 'static,
+    {
+        self.request_interceptors.push(Arc::new(interceptor));
+        self
+    }
+
+    /// Run `interceptor` on every response received, after the
+    /// interceptors registered earlier. Not run when the send fails.
+    #[must_use]
+    #[inline]
+    pub fn with_response_interceptor<I>(mut self, interceptor: I) -> Self
+    where
+        I: ProxyResponseInterceptor + 'static,
+    {
+        self.response_interceptors.push(Arc::new(interceptor));
+        self
+    }
+}
+
+/// App-wide interceptors registered with
+/// [`RouterBuilder::with_proxy_request_interceptor`] and its response
+/// counterpart. Stored as router state and layered onto the adapter's
+/// [`ProxyHandle`] by [`RequestContext::proxy_handle`].
+///
+/// [`RouterBuilder::with_proxy_request_interceptor`]: crate::router::RouterBuilder::with_proxy_request_interceptor
+#[derive(Clone, Default)]
+pub(crate) struct ProxyInterceptors {
+    request: Vec<Arc<dyn ProxyRequestInterceptor>>,
+    response: Vec<Arc<dyn ProxyResponseInterceptor>>,
+}
+
+impl ProxyInterceptors {
+    /// Append `interceptor` to the request interceptors stored in
+    /// `extensions`.
+    pub(crate) fn add_request(
+        extensions: &mut Extensions,
+        interceptor: Arc<dyn ProxyRequestInterceptor>,
+    ) {
+        extensions
+            .get_or_insert_default::<Self>()
+            .request
+            .push(interceptor);
+    }
+
+    /// Append `interceptor` to the response interceptors stored in
+    /// `extensions`.
+    pub(crate) fn add_response(
+        extensions: &mut Extensions,
+        interceptor: Arc<dyn ProxyResponseInterceptor>,
+    ) {
+        extensions
+            .get_or_insert_default::<Self>()
+            .response
+            .push(interceptor);
+    }
+}
+
+/// What [`ProxyHandle::client`] hands out when interceptors are registered.
+struct InterceptedClient {
+    handle: ProxyHandle,
+}
+
+#[async_trait(?Send)]
+impl ProxyClient for InterceptedClient {
+    #[inline]
+    async fn send(&self, request: ProxyRequest) -> Result<ProxyResponse, EdgeError> {
+        self.handle.send(request).await
+    }
+}
+
+/// Spawns a mirror request's future onto the platform's local executor.
+pub type MirrorSpawner = Arc<dyn Fn(LocalBoxFuture<'static, ()>) + Send + Sync>;
+
+/// Shadows a sample of traffic to a second backend for safe rollouts.
+///
+/// Every request goes to the primary client and its response is returned.
+/// A configured percentage of requests with buffered bodies is also copied
+/// to the mirror client; the mirror's response and errors are discarded.
+/// Sampling is deterministic: at 25%, every fourth request is mirrored.
+/// Streaming bodies cannot be copied and are never mirrored.
+///
+/// Mirror requests run fire-and-forget on a [`MirrorSpawner`] (e.g. wrapping
+/// `wasm_bindgen_futures::spawn_local`), carrying the original's extensions
+/// (deadline, trace context) and timeout. Without a spawner nothing is
+/// mirrored, so the primary response never waits on the mirror.
+pub struct MirrorProxyClient {
+    mirror: Arc<dyn ProxyClient>,
+    primary: Arc<dyn ProxyClient>,
+    /// Accumulated sample percentage; a request is mirrored each time it
+    /// reaches 100.
+    sample_credit: AtomicU32,
+    sample_percent: u32,
+    spawner: Option<MirrorSpawner>,
+}
+
+impl MirrorProxyClient {
+    /// Copy of `request` for the mirror, or `None` for streaming bodies.
+    fn mirror_request(request: &ProxyRequest) -> Option<ProxyRequest> {
+        let Body::Once(bytes) = request.body() else {
+            return None;
+        };
+        Some(ProxyRequest {
+            body: Body::Once(bytes.clone()),
+            extensions: request.extensions().clone(),
+            headers: request.headers().clone(),
+            method: request.method().clone(),
+            timeout: request.timeout,
+            uri: request.uri().clone(),
+        })
+    }
+
+    /// Mirror every request to `mirror` once [`Self::with_spawner`] is set;
+    /// narrow with [`Self::sample_percent`].
+    #[inline]
+    pub fn new(primary: Arc<dyn ProxyClient>, mirror: Arc<dyn ProxyClient>) -> Self {
         Self {
-            client: Arc::new(client),
+            mirror,
+            primary,
+            sample_credit: AtomicU32::new(0),
+            sample_percent: 100,
+            spawner: None,
         }
     }
+
+    /// Mirror `percent` (clamped to `0..=100`) of requests.
+    #[must_use]
+    #[inline]
+    pub fn sample_percent(mut self, percent: u8) -> Self {
+        self.sample_percent = u32::from(percent.min(100));
+        self
+    }
+
+    /// Whether the next request should be mirrored.
+    fn take_sample(&self) -> bool {
+        let step = |credit: u32| {
+            let next = credit.saturating_add(self.sample_percent);
+            if next >= 100 {
+                next.saturating_sub(100)
+            } else {
+                next
+            }
+        };
+        // The closure never returns `None`, so `fetch_update` cannot fail.
+        let previous = self
+            .sample_credit
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |credit| {
+                Some(step(credit))
+            })
+            .unwrap_or_else(|credit| credit);
+        previous.saturating_add(self.sample_percent) >= 100
+    }
+
+    /// Run mirror requests fire-and-forget on `spawner`. Without one,
+    /// nothing is mirrored.
+    #[must_use]
+    #[inline]
+    pub fn with_spawner<F>(mut self, spawner: F) -> Self
+    where
+        F: Fn(LocalBoxFuture<'static, ()>) + Send + Sync + 'static,
+    {
+        self.spawner = Some(Arc::new(spawner));
+        self
+    }
+}
+
+#[async_trait(?Send)]
+impl ProxyClient for MirrorProxyClient {
+    #[inline]
+    async fn send(&self, request: ProxyRequest) -> Result<ProxyResponse, EdgeError> {
+        let Some(spawn) = &self.spawner else {
+            return self.primary.send(request).await;
+        };
+        let Some(copy) = self
+            .take_sample()
+            .then(|| Self::mirror_request(&request))
+            .flatten()
+        else {
+            return self.primary.send(request).await;
+        };
+
+        let mirror = Arc::clone(&self.mirror);
+        spawn(
+            async move {
+                if let Err(err) = mirror.send(copy).await {
+                    tracing::debug!("mirror request failed: {err}");
+                }
+            }
+            .boxed_local(),
+        );
+        self.primary.send(request).await
+    }
+}
+
+/// Extractor for the [`ProxyHandle`] the adapter installed, so handlers can
+/// make outbound requests without naming a platform client:
+///
+/// ```rust,ignore
+/// async fn weather(Proxy(proxy): Proxy) -> Result<Response, EdgeError> {
+///     let uri = Uri::from_static("https://api.example.com/weather");
+///     proxy.forward(ProxyRequest::new(Method::GET, uri)).await
+/// }
+/// ```
+///
+/// Fails with `500 Internal Server Error` when the request carries no
+/// handle, as in a router driven without an adapter.
+#[derive(Clone)]
+pub struct Proxy(pub ProxyHandle);
+
+#[async_trait(?Send)]
+impl FromRequest for Proxy {
+    #[inline]
+    async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+        ctx.proxy_handle()
+            .map(Proxy)
+            .ok_or_else(|| EdgeError::internal(anyhow::anyhow!("no proxy client is configured")))
+    }
+}
+
+impl Deref for Proxy {
+    type Target = ProxyHandle;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Proxy {
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> ProxyHandle {
+        self.0
+    }
 }
 
 /// Outbound request description for a proxy operation.
@@ -61,6 +410,7 @@ pub struct ProxyRequest {
     extensions: Extensions,
     headers: HeaderMap,
     method: Method,
+    timeout: Option<Duration>,
     uri: Uri,
 }
 
@@ -71,11 +421,22 @@ impl fmt::Debug for ProxyRequest {
             .field("method", &self.method)
             .field("uri", &self.uri)
             .field("headers", &self.headers)
+            .field("timeout", &self.timeout)
             .finish_non_exhaustive()
     }
 }
 
 impl ProxyRequest {
+    /// Carry the request's [`Deadline`] onto this outbound request so
+    /// [`Self::timeout`] is capped by it. Requests built with
+    /// [`Self::from_request`] already inherit it with the extensions.
+    #[inline]
+    pub fn apply_deadline(&mut self, ctx: &RequestContext) {
+        if let Some(deadline) = ctx.deadline() {
+            self.extensions.insert(deadline);
+        }
+    }
+
     #[inline]
     pub fn body(&self) -> &Body {
         &self.body
@@ -104,6 +465,7 @@ impl ProxyRequest {
             extensions: parts.extensions,
             headers: parts.headers,
             method: parts.method,
+            timeout: None,
             uri,
         }
     }
@@ -118,6 +480,19 @@ impl ProxyRequest {
         &mut self.headers
     }
 
+    /// Propagate the request's trace onto this outbound request.
+    ///
+    /// Writes a child of the [`TraceParent`] stored by the
+    /// [`TraceContext`](crate::trace_context::TraceContext) middleware, so
+    /// the upstream call is recorded as a span under this request. Does
+    /// nothing when the middleware did not run.
+    #[inline]
+    pub fn inject_trace_context(&mut self, ctx: &RequestContext) {
+        if let Some(trace) = ctx.request().extensions().get::<TraceParent>() {
+            trace.child().inject(&mut self.headers);
+        }
+    }
+
     #[inline]
     pub fn into_parts(self) -> (Method, Uri, HeaderMap, Body, Extensions) {
         (
@@ -129,6 +504,15 @@ impl ProxyRequest {
         )
     }
 
+    /// Whether the body is a stream rather than buffered bytes. A streaming
+    /// body is consumed by the first send, so such a request cannot be
+    /// replayed, e.g. to retry it.
+    #[must_use]
+    #[inline]
+    pub fn is_streaming(&self) -> bool {
+        self.body.is_stream()
+    }
+
     #[inline]
     pub fn method(&self) -> &Method {
         &self.method
@@ -141,10 +525,35 @@ impl ProxyRequest {
             extensions: Extensions::new(),
             headers: HeaderMap::new(),
             method,
+            timeout: None,
             uri,
         }
     }
 
+    /// Limit how long the proxy client may wait for the upstream.
+    #[inline]
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    /// Effective timeout for this request: the one set with
+    /// [`Self::set_timeout`], capped by the time remaining before any
+    /// attached [`Deadline`]. `None` leaves the client's own default.
+    ///
+    /// Fastly backends only take whole seconds, so the Fastly client rounds
+    /// this up to at least one second and the upstream may be given up to a
+    /// second past the deadline. A deadline that has already passed is
+    /// answered with `504 Gateway Timeout` without sending.
+    #[must_use]
+    #[inline]
+    pub fn timeout(&self) -> Option<Duration> {
+        match (self.timeout, self.extensions.get::<Deadline>()) {
+            (Some(timeout), Some(deadline)) => Some(deadline.cap(timeout)),
+            (None, Some(deadline)) => Some(deadline.remaining()),
+            (timeout, None) => timeout,
+        }
+    }
+
     #[inline]
     pub fn uri(&self) -> &Uri {
         &self.uri
@@ -212,6 +621,13 @@ impl ProxyResponse {
         builder.body(self.body).map_err(EdgeError::internal)
     }
 
+    /// Whether the body is a stream rather than buffered bytes.
+    #[must_use]
+    #[inline]
+    pub fn is_streaming(&self) -> bool {
+        self.body.is_stream()
+    }
+
     #[inline]
     pub fn new(status: StatusCode, body: Body) -> Self {
         Self {
@@ -226,6 +642,67 @@ impl ProxyResponse {
     pub fn status(&self) -> StatusCode {
         self.status
     }
+
+    /// Re-encode a gzip, brotli, or deflate body, as it streams, into a
+    /// coding the client accepts, given the `Accept-Encoding` the client
+    /// sent (`None` when it sent none).
+    ///
+    /// The client's highest-rated coding is used, or identity if it takes
+    /// none of them. `Content-Encoding` is updated, `Content-Length`
+    /// removed, a strong `ETag` weakened, and `Accept-Encoding` added to
+    /// `Vary`. The response is left as it is when the client accepts the
+    /// upstream coding (or sent no `Accept-Encoding`), the upstream used
+    /// another coding or several, or there is no body.
+    ///
+    /// ```rust,ignore
+    /// let accept_encoding = ctx.request().headers().get(ACCEPT_ENCODING).cloned();
+    /// let mut response = proxy.client().send(request).await?;
+    /// response.transcode_for(accept_encoding.as_ref());
+    /// response.into_response()
+    /// ```
+    #[inline]
+    pub fn transcode_for(&mut self, accept_encoding: Option<&HeaderValue>) {
+        let Some(accept) = accept_encoding
+            .and_then(|value| value.to_str().ok())
+            .map(AcceptEncoding::new)
+        else {
+            return;
+        };
+        let Some(from) = self
+            .headers
+            .get(CONTENT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Coding::from_header)
+        else {
+            return;
+        };
+        let no_body = matches!(
+            self.status,
+            StatusCode::NO_CONTENT | StatusCode::NOT_MODIFIED
+        ) || matches!(&self.body, Body::Once(bytes) if bytes.is_empty());
+        if no_body || accept.accepts(from.name()) {
+            return;
+        }
+        let to = match accept.preferred() {
+            Some(coding) => Some(coding),
+            None if accept.accepts("identity") => None,
+            None => return,
+        };
+
+        self.body = compression::transcode(mem::take(&mut self.body), from, to);
+        match to {
+            Some(coding) => {
+                self.headers
+                    .insert(CONTENT_ENCODING, HeaderValue::from_static(coding.name()));
+            }
+            None => {
+                self.headers.remove(CONTENT_ENCODING);
+            }
+        }
+        self.headers.remove(CONTENT_LENGTH);
+        compression::weaken_etag(&mut self.headers);
+        response::append_vary(&mut self.headers, "Accept-Encoding");
+    }
 }
 
 pub struct ProxyService<C> {
@@ -253,15 +730,48 @@ where
     }
 }
 
+/// Remove the hop-by-hop headers from `headers`: the standard ones and any
+/// the `Connection` header names.
+#[inline]
+pub fn strip_hop_by_hop_headers(headers: &mut HeaderMap) {
+    let named: Vec<HeaderName> = headers
+        .get_all(CONNECTION)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|name| HeaderName::try_from(name.trim()).ok())
+        .collect();
+    for name in HOP_BY_HOP.iter().chain(&named) {
+        headers.remove(name);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::body::Body;
-    use crate::http::header::HeaderName;
+    use crate::http::header::{ETAG, HeaderName, VARY};
     use crate::http::{HeaderValue, Method, StatusCode, Uri, request_builder};
+    use crate::params::PathParams;
+    use brotli::CompressorWriter;
     use bytes::Bytes;
+    use flate2::read::GzDecoder;
     use futures::executor::block_on;
+    use futures::future::ready;
     use futures_util::{StreamExt as _, stream};
+    use std::cell::RefCell;
+    use std::io::{Read as _, Write as _};
+    use std::sync::Mutex;
+    use std::sync::atomic::AtomicUsize;
+
+    type Recorded = (Option<&'static str>, Option<Duration>);
+
+    /// Counts calls and answers with a fixed body, or fails when `body` is
+    /// `None`.
+    struct CountingClient {
+        body: Option<&'static str>,
+        calls: Arc<AtomicUsize>,
+    }
 
     struct EchoBodyClient;
 
@@ -271,10 +781,26 @@ mod tests {
 
     struct ErrorClient;
 
+    /// Records each request's `&'static str` extension and timeout.
+    struct RecordingClient {
+        seen: Arc<Mutex<Vec<Recorded>>>,
+    }
+
     struct StreamingClient;
 
     struct TestClient;
 
+    #[async_trait(?Send)]
+    impl ProxyClient for CountingClient {
+        async fn send(&self, _request: ProxyRequest) -> Result<ProxyResponse, EdgeError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let body = self
+                .body
+                .ok_or_else(|| EdgeError::service_unavailable("canary down"))?;
+            Ok(ProxyResponse::new(StatusCode::OK, Body::from(body)))
+        }
+    }
+
     #[async_trait(?Send)]
     impl ProxyClient for EchoBodyClient {
         async fn send(&self, request: ProxyRequest) -> Result<ProxyResponse, EdgeError> {
@@ -316,6 +842,15 @@ mod tests {
         }
     }
 
+    #[async_trait(?Send)]
+    impl ProxyClient for RecordingClient {
+        async fn send(&self, request: ProxyRequest) -> Result<ProxyResponse, EdgeError> {
+            let marker = request.extensions().get::<&'static str>().copied();
+            self.seen.lock().unwrap().push((marker, request.timeout()));
+            Ok(ProxyResponse::new(StatusCode::OK, Body::empty()))
+        }
+    }
+
     #[async_trait(?Send)]
     impl ProxyClient for StreamingClient {
         async fn send(&self, request: ProxyRequest) -> Result<ProxyResponse, EdgeError> {
@@ -347,10 +882,34 @@ mod tests {
         }
     }
 
+    fn counting(body: Option<&'static str>) -> (Arc<dyn ProxyClient>, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = CountingClient {
+            body,
+            calls: Arc::clone(&calls),
+        };
+        (Arc::new(client), calls)
+    }
+
+    thread_local! {
+        static SPAWNED: RefCell<Vec<LocalBoxFuture<'static, ()>>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// A [`MirrorSpawner`] that queues mirror requests for [`run_spawned`].
+    fn queue_spawn(shadow: LocalBoxFuture<'static, ()>) {
+        SPAWNED.with(|spawned| spawned.borrow_mut().push(shadow));
+    }
+
+    fn run_spawned() {
+        for shadow in SPAWNED.with(RefCell::take) {
+            block_on(shadow);
+        }
+    }
+
     fn collect_body(body: Body) -> Vec<u8> {
         match body {
             Body::Once(bytes) => bytes.to_vec(),
-            Body::Stream(mut stream) => block_on(async {
+            Body::Stream(mut stream) | Body::StreamWithTrailers(mut stream, _) => block_on(async {
                 let mut data = Vec::new();
                 while let Some(result) = stream.next().await {
                     let chunk = result.expect("chunk");
@@ -361,6 +920,92 @@ mod tests {
         }
     }
 
+    #[test]
+    fn mirror_client_returns_primary_response_at_configured_rate() {
+        let (primary, primary_calls) = counting(Some("primary"));
+        let (mirror, mirror_calls) = counting(None);
+        let client = MirrorProxyClient::new(primary, mirror)
+            .sample_percent(30)
+            .with_spawner(queue_spawn);
+
+        for _ in 0_u8..10 {
+            let mut request = ProxyRequest::new(Method::POST, Uri::from_static("/orders"));
+            *request.body_mut() = Body::from("payload");
+            let response = block_on(client.send(request)).expect("primary response");
+            assert_eq!(
+                collect_body(response.into_response().unwrap().into_body()),
+                b"primary"
+            );
+        }
+        run_spawned();
+
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 10);
+        assert_eq!(mirror_calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn mirror_client_skips_streaming_bodies() {
+        let (primary, _) = counting(Some("primary"));
+        let (mirror, mirror_calls) = counting(Some("mirror"));
+        let client = MirrorProxyClient::new(primary, mirror).with_spawner(queue_spawn);
+
+        let mut request = ProxyRequest::new(Method::POST, Uri::from_static("/upload"));
+        *request.body_mut() = Body::stream(stream::iter(vec![Bytes::from_static(b"chunk")]));
+        block_on(client.send(request)).expect("primary response");
+        run_spawned();
+
+        assert_eq!(mirror_calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn mirror_client_spawns_fire_and_forget() {
+        let (primary, _) = counting(Some("primary"));
+        let (mirror, mirror_calls) = counting(Some("mirror"));
+        let client = MirrorProxyClient::new(primary, mirror).with_spawner(queue_spawn);
+
+        let request = ProxyRequest::new(Method::GET, Uri::from_static("/"));
+        block_on(client.send(request)).expect("primary response");
+        // The primary answered without waiting on the mirror.
+        assert_eq!(mirror_calls.load(Ordering::SeqCst), 0);
+
+        run_spawned();
+        assert_eq!(mirror_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn mirror_client_without_spawner_skips_mirroring() {
+        let (primary, primary_calls) = counting(Some("primary"));
+        let (mirror, mirror_calls) = counting(Some("mirror"));
+        let client = MirrorProxyClient::new(primary, mirror);
+
+        let request = ProxyRequest::new(Method::GET, Uri::from_static("/"));
+        block_on(client.send(request)).expect("primary response");
+
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(mirror_calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn mirror_copy_keeps_extensions_and_timeout() {
+        let (primary, _) = counting(Some("primary"));
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mirror = Arc::new(RecordingClient {
+            seen: Arc::clone(&seen),
+        });
+        let client = MirrorProxyClient::new(primary, mirror).with_spawner(queue_spawn);
+
+        let mut request = ProxyRequest::new(Method::GET, Uri::from_static("/"));
+        request.extensions_mut().insert("trace-marker");
+        request.set_timeout(Duration::from_secs(3));
+        block_on(client.send(request)).expect("primary response");
+        run_spawned();
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [(Some("trace-marker"), Some(Duration::from_secs(3)))]
+        );
+    }
+
     #[test]
     fn proxy_forward_preserves_streaming_body() {
         let request = request_builder()
@@ -479,6 +1124,65 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[test]
+    fn proxy_handle_interceptors_run_in_order_around_send() {
+        /// Awaits before tagging, standing in for e.g. a token fetch.
+        struct AsyncTag;
+
+        #[async_trait(?Send)]
+        impl ProxyRequestInterceptor for AsyncTag {
+            async fn intercept(&self, request: &mut ProxyRequest) {
+                ready(()).await;
+                let order = match request.headers().get("x-order") {
+                    Some(value) if value == "first" => "first,second",
+                    Some(_) | None => "second",
+                };
+                request
+                    .headers_mut()
+                    .insert("x-order", HeaderValue::from_static(order));
+            }
+        }
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let (first_seen, second_seen) = (Arc::clone(&seen), Arc::clone(&seen));
+        let handle = ProxyHandle::with_client(EchoHeadersClient)
+            .with_request_interceptor(|request: &mut ProxyRequest| {
+                let headers = request.headers_mut();
+                headers.insert("authorization", HeaderValue::from_static("Bearer t0k3n"));
+                headers.insert("x-order", HeaderValue::from_static("first"));
+            })
+            .with_request_interceptor(AsyncTag)
+            .with_response_interceptor(move |response: &mut ProxyResponse| {
+                first_seen.lock().unwrap().push(response.status());
+                response
+                    .headers_mut()
+                    .insert("x-observed", HeaderValue::from_static("1"));
+            })
+            .with_response_interceptor(move |response: &mut ProxyResponse| {
+                let observed = response.headers().contains_key("x-observed");
+                assert!(observed, "response interceptors compose in order");
+                second_seen.lock().unwrap().push(response.status());
+            });
+        let upstream_request =
+            || ProxyRequest::new(Method::GET, Uri::from_static("https://example.com"));
+
+        let response = block_on(handle.forward(upstream_request())).expect("response");
+        assert_eq!(
+            response.headers().get("x-echo-authorization").unwrap(),
+            "Bearer t0k3n"
+        );
+        assert_eq!(
+            response.headers().get("x-echo-x-order").unwrap(),
+            "first,second"
+        );
+        assert_eq!(*seen.lock().unwrap(), [StatusCode::OK, StatusCode::OK]);
+
+        // The client handed out to handlers runs the interceptors as well.
+        let sent = block_on(handle.client().send(upstream_request())).expect("response");
+        assert!(sent.headers().contains_key("x-echo-authorization"));
+        assert_eq!(seen.lock().unwrap().len(), 4);
+    }
+
     #[test]
     fn proxy_handle_new_wraps_client() {
         let client = Arc::new(TestClient);
@@ -509,6 +1213,26 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn proxy_request_is_streaming_follows_the_body() {
+        let mut req = ProxyRequest::new(Method::POST, Uri::from_static("https://example.com"));
+        *req.body_mut() = Body::from("buffered");
+        assert!(!req.is_streaming());
+        *req.body_mut() = Body::stream(stream::iter([Bytes::from_static(b"chunk")]));
+        assert!(req.is_streaming());
+    }
+
+    #[test]
+    fn proxy_response_is_streaming_follows_the_body() {
+        let buffered = ProxyResponse::new(StatusCode::OK, Body::from("buffered"));
+        assert!(!buffered.is_streaming());
+        let streamed = ProxyResponse::new(
+            StatusCode::OK,
+            Body::stream(stream::iter([Bytes::from_static(b"chunk")])),
+        );
+        assert!(streamed.is_streaming());
+    }
+
     #[test]
     fn proxy_request_debug_format() {
         let mut req = ProxyRequest::new(Method::GET, Uri::from_static("https://example.com"));
@@ -634,6 +1358,18 @@ mod tests {
         assert!(http_resp.headers().get("x-custom").is_some());
     }
 
+    #[test]
+    fn proxy_response_into_response_keeps_opaque_upstream_header_bytes() {
+        // Upstreams may send obs-text (non-UTF-8) header bytes; conversion
+        // must carry them through rather than fail or panic.
+        let mut resp = ProxyResponse::new(StatusCode::OK, Body::empty());
+        let opaque = HeaderValue::from_bytes(b"caf\xe9").expect("obs-text is a valid value");
+        resp.headers_mut().insert("x-upstream", opaque.clone());
+
+        let http_resp = resp.into_response().expect("response");
+        assert_eq!(http_resp.headers().get("x-upstream"), Some(&opaque));
+    }
+
     #[test]
     fn proxy_response_new_creates_response() {
         let resp = ProxyResponse::new(StatusCode::OK, Body::from("response body"));
@@ -644,6 +1380,77 @@ mod tests {
         ));
     }
 
+    fn brotli_response(payload: &[u8]) -> ProxyResponse {
+        let mut compressed = Vec::new();
+        let mut compressor = CompressorWriter::new(&mut compressed, 4096, 5, 21);
+        compressor.write_all(payload).unwrap();
+        drop(compressor);
+        let chunks: Vec<Bytes> = compressed.chunks(7).map(Bytes::copy_from_slice).collect();
+        let mut resp = ProxyResponse::new(StatusCode::OK, Body::stream(stream::iter(chunks)));
+        let headers = resp.headers_mut();
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static("br"));
+        headers.insert(CONTENT_LENGTH, HeaderValue::from(compressed.len()));
+        headers.insert(ETAG, HeaderValue::from_static("\"v1\""));
+        resp
+    }
+
+    #[test]
+    fn proxy_response_transcode_for_turns_brotli_into_gzip() {
+        let payload = b"hello from a brotli upstream ".repeat(64);
+        let mut resp = brotli_response(&payload);
+        resp.transcode_for(Some(&HeaderValue::from_static("gzip, deflate;q=0.5")));
+
+        assert_eq!(resp.headers()[CONTENT_ENCODING], "gzip");
+        assert!(!resp.headers().contains_key(CONTENT_LENGTH));
+        assert_eq!(resp.headers()[ETAG], "W/\"v1\"");
+        assert_eq!(resp.headers()[VARY], "Accept-Encoding");
+        assert!(resp.body().is_stream());
+
+        let gzipped = collect_body(mem::take(resp.body_mut()));
+        let mut decoded = Vec::new();
+        GzDecoder::new(gzipped.as_slice())
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, payload);
+    }
+
+    #[test]
+    fn proxy_response_transcode_for_decodes_for_identity_only_clients() {
+        let mut resp = brotli_response(b"plain please");
+        resp.transcode_for(Some(&HeaderValue::from_static("identity, br;q=0")));
+
+        assert!(!resp.headers().contains_key(CONTENT_ENCODING));
+        assert_eq!(collect_body(mem::take(resp.body_mut())), b"plain please");
+    }
+
+    #[test]
+    fn proxy_response_transcode_for_passes_through_when_accepted() {
+        for accept_encoding in [
+            None,
+            Some(HeaderValue::from_static("gzip, br")),
+            Some(HeaderValue::from_static("*")),
+            Some(HeaderValue::from_static("identity;q=0")),
+        ] {
+            let mut resp = brotli_response(b"as is");
+            resp.transcode_for(accept_encoding.as_ref());
+            assert_eq!(
+                resp.headers()[CONTENT_ENCODING],
+                "br",
+                "{accept_encoding:?}"
+            );
+            assert!(resp.headers().contains_key(CONTENT_LENGTH));
+            assert!(!resp.headers().contains_key(VARY));
+        }
+
+        let mut unknown = ProxyResponse::new(StatusCode::OK, Body::from("zstd bytes"));
+        unknown
+            .headers_mut()
+            .insert(CONTENT_ENCODING, HeaderValue::from_static("zstd"));
+        unknown.transcode_for(Some(&HeaderValue::from_static("gzip")));
+        assert_eq!(unknown.headers()[CONTENT_ENCODING], "zstd");
+        assert_eq!(collect_body(mem::take(unknown.body_mut())), b"zstd bytes");
+    }
+
     #[test]
     fn proxy_service_propagates_client_errors() {
         let service = ProxyService::new(ErrorClient);
@@ -653,4 +1460,40 @@ mod tests {
         let err = result.unwrap_err();
         assert_eq!(err.status(), StatusCode::BAD_REQUEST);
     }
+
+    #[test]
+    fn proxy_extractor_yields_the_installed_handle() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut request = request_builder()
+            .uri("/weather")
+            .body(Body::empty())
+            .expect("request");
+        request
+            .extensions_mut()
+            .insert(ProxyHandle::with_client(CountingClient {
+                body: Some("sunny"),
+                calls: Arc::clone(&calls),
+            }));
+        let ctx = RequestContext::new(request, PathParams::default());
+
+        let Proxy(proxy) = block_on(Proxy::from_request(&ctx)).expect("proxy");
+        let outbound = ProxyRequest::new(Method::GET, Uri::from_static("https://example.com"));
+        let response = block_on(proxy.forward(outbound)).expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn proxy_extractor_fails_without_a_handle() {
+        let request = request_builder()
+            .uri("/weather")
+            .body(Body::empty())
+            .expect("request");
+        let ctx = RequestContext::new(request, PathParams::default());
+
+        let err = block_on(Proxy::from_request(&ctx))
+            .err()
+            .expect("missing handle");
+        assert_eq!(err.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
 }
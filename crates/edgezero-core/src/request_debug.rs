@@ -0,0 +1,203 @@
+//! Development-only endpoint that echoes the incoming request back as JSON.
+//!
+//! **Do not enable in production.** The echo reflects every request header
+//! — including `Authorization` and `Cookie` — into a response body that
+//! page scripts can read, which defeats `HttpOnly` cookies and hands
+//! credentials to anyone who can make a victim's browser call the endpoint.
+//!
+//! Enabled by the `request-debug` feature; nothing is registered unless
+//! [`RouterBuilder::enable_request_debug_at`] is called:
+//!
+//! ```rust,ignore
+//! let router = RouterService::builder()
+//!     .enable_request_debug_at("/_debug/echo")
+//!     .build();
+//! ```
+//!
+//! The response reports the method, URI, path, query (raw and decoded),
+//! headers (repeated names keep every value), and up to
+//! [`MAX_ECHO_BODY_BYTES`] of the body. A body that is not UTF-8 is
+//! returned base64-encoded.
+//!
+//! [`RouterBuilder::enable_request_debug_at`]: crate::router::RouterBuilder::enable_request_debug_at
+
+use std::collections::BTreeMap;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use bytes::BytesMut;
+use futures_util::StreamExt as _;
+use serde_json::{Value, json};
+
+use crate::body::Body;
+use crate::context::RequestContext;
+use crate::error::EdgeError;
+use crate::http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use crate::http::{Response, StatusCode, response_builder};
+
+/// Bytes of the request body included in the echo; the rest is dropped and
+/// `body_truncated` is set.
+pub const MAX_ECHO_BODY_BYTES: usize = 64 * 1024;
+
+/// Handler registered by [`RouterBuilder::enable_request_debug_at`].
+///
+/// [`RouterBuilder::enable_request_debug_at`]: crate::router::RouterBuilder::enable_request_debug_at
+pub(crate) async fn echo(ctx: RequestContext) -> Result<Response, EdgeError> {
+    let (parts, body) = ctx.into_request().into_parts();
+
+    let mut headers: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (name, value) in &parts.headers {
+        headers
+            .entry(name.as_str())
+            .or_default()
+            .push(String::from_utf8_lossy(value.as_bytes()).into_owned());
+    }
+    let raw_query = parts.uri.query().unwrap_or_default();
+    let query: Vec<(String, String)> = serde_urlencoded::from_str(raw_query).unwrap_or_default();
+
+    let (bytes, truncated) = read_capped(body, MAX_ECHO_BODY_BYTES).await?;
+    let (body_text, encoding) = match str::from_utf8(&bytes) {
+        Ok(text) => (text.to_owned(), "utf-8"),
+        Err(_) => (STANDARD.encode(&bytes), "base64"),
+    };
+
+    let echo = json!({
+        "body": body_text,
+        "body_bytes": bytes.len(),
+        "body_encoding": encoding,
+        "body_truncated": truncated,
+        "headers": headers,
+        "method": parts.method.as_str(),
+        "path": parts.uri.path(),
+        "query": query
+            .into_iter()
+            .map(|(key, value)| json!([key, value]))
+            .collect::<Vec<Value>>(),
+        "raw_query": raw_query,
+        "uri": parts.uri.to_string(),
+    });
+    response_builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "application/json")
+        .header(CACHE_CONTROL, "no-store")
+        .body(Body::json(&echo).map_err(EdgeError::internal)?)
+        .map_err(EdgeError::internal)
+}
+
+/// Read at most `cap` bytes of `body`, reporting whether more was dropped.
+/// A streaming body is not drained past the cap.
+async fn read_capped(body: Body, cap: usize) -> Result<(BytesMut, bool), EdgeError> {
+    let mut buf = BytesMut::new();
+    let mut stream = match body {
+        Body::Once(bytes) => {
+            let kept = bytes.get(..cap).unwrap_or(&bytes);
+            buf.extend_from_slice(kept);
+            return Ok((buf, bytes.len() > cap));
+        }
+        Body::Stream(stream) | Body::StreamWithTrailers(stream, _) => stream,
+    };
+    while let Some(next) = stream.next().await {
+        let chunk = next.map_err(EdgeError::internal)?;
+        let room = cap.saturating_sub(buf.len());
+        if chunk.len() > room {
+            buf.extend_from_slice(chunk.get(..room).unwrap_or_default());
+            return Ok((buf, true));
+        }
+        buf.extend_from_slice(&chunk);
+    }
+    Ok((buf, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{Method, Request, request_builder};
+    use crate::router::RouterService;
+    use bytes::Bytes;
+    use futures::executor::block_on;
+    use futures_util::stream;
+
+    fn echo_json(router: &RouterService, request: Request) -> Value {
+        let response = block_on(router.oneshot(request)).expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response
+                .headers()
+                .get(CACHE_CONTROL)
+                .expect("cache-control"),
+            "no-store"
+        );
+        response.into_body().to_json().expect("json echo")
+    }
+
+    #[test]
+    fn echoes_post_with_json_body() {
+        let router = RouterService::builder()
+            .enable_request_debug_at("/_debug/echo")
+            .build();
+        let request = request_builder()
+            .method(Method::POST)
+            .uri("/_debug/echo?tag=a&tag=b%20c")
+            .header("content-type", "application/json")
+            .header("x-trace", "one")
+            .header("x-trace", "two")
+            .body(Body::from(r#"{"name":"widget","count":2}"#))
+            .expect("request");
+
+        let echo = echo_json(&router, request);
+        assert_eq!(echo["method"], "POST");
+        assert_eq!(echo["path"], "/_debug/echo");
+        assert_eq!(echo["raw_query"], "tag=a&tag=b%20c");
+        assert_eq!(echo["query"], json!([["tag", "a"], ["tag", "b c"]]));
+        assert_eq!(echo["headers"]["content-type"], json!(["application/json"]));
+        assert_eq!(echo["headers"]["x-trace"], json!(["one", "two"]));
+        assert_eq!(echo["body_encoding"], "utf-8");
+        assert_eq!(echo["body_truncated"], false);
+        let body: Value =
+            serde_json::from_str(echo["body"].as_str().expect("body text")).expect("body json");
+        assert_eq!(body, json!({"name": "widget", "count": 2_u8}));
+    }
+
+    #[test]
+    fn not_registered_unless_enabled() {
+        let router = RouterService::builder().build();
+        let request = request_builder()
+            .method(Method::GET)
+            .uri("/_debug/echo")
+            .body(Body::empty())
+            .expect("request");
+        let response = block_on(router.oneshot(request)).expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn oversized_and_binary_bodies_are_capped_and_encoded() {
+        let big = vec![b'x'; MAX_ECHO_BODY_BYTES.saturating_add(10)];
+        let (bytes, truncated) =
+            block_on(read_capped(Body::from(big), MAX_ECHO_BODY_BYTES)).expect("read");
+        assert_eq!(bytes.len(), MAX_ECHO_BODY_BYTES);
+        assert!(truncated);
+
+        let chunks = stream::iter(vec![
+            Bytes::from_static(b"abc"),
+            Bytes::from_static(b"defg"),
+            Bytes::from_static(b"never read"),
+        ]);
+        let (streamed, streamed_truncated) =
+            block_on(read_capped(Body::stream(chunks), 5)).expect("read");
+        assert_eq!(&*streamed, b"abcde");
+        assert!(streamed_truncated);
+
+        let router = RouterService::builder()
+            .enable_request_debug_at("/echo")
+            .build();
+        let request = request_builder()
+            .method(Method::PUT)
+            .uri("/echo")
+            .body(Body::from(vec![0xff_u8, 0x00]))
+            .expect("request");
+        let echo = echo_json(&router, request);
+        assert_eq!(echo["body_encoding"], "base64");
+        assert_eq!(echo["body"], "/wA=");
+    }
+}
@@ -0,0 +1,320 @@
+//! Request ids.
+//!
+//! [`gen_request_id`] returns 22 base62 characters encoding 128 random bits
+//! from the platform's secure random source (`crypto.getRandomValues` on
+//! Cloudflare, WASI on Fastly and Spin), so ids are safe to put in URLs and
+//! log lines without pulling in a UUID crate.
+//!
+//! Code that needs a fresh id calls [`RequestContext::generate_request_id`],
+//! which uses the [`RequestIdGenerator`] registered with
+//! [`RouterBuilder::with_request_id_generator`], or [`gen_request_id`] when
+//! none is. Tests register a [`FixedRequestId`] to make ids predictable:
+//!
+//! ```rust,ignore
+//! let router = RouterService::builder()
+//!     .with_request_id_generator(FixedRequestId::new("req-1"))
+//!     .get("/", handler)
+//!     .build();
+//! ```
+//!
+//! [`RequestIdMiddleware`] gives every request an id, reusing the one a
+//! caller sent in `X-Request-Id` when it looks safe to log, stores it as a
+//! [`RequestId`] for handlers and echoes it in the response.
+//!
+//! [`RequestContext::generate_request_id`]: crate::context::RequestContext::generate_request_id
+//! [`RouterBuilder::with_request_id_generator`]: crate::router::RouterBuilder::with_request_id_generator
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use sha2::{Digest as _, Sha256};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::context::RequestContext;
+use crate::error::EdgeError;
+use crate::extractor::FromRequest;
+use crate::http::{HeaderName, HeaderValue, Response};
+use crate::middleware::{Middleware, Next};
+
+const BASE62: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Characters needed to write any `u128` in base62.
+const ID_LEN: usize = 22;
+
+/// Longest caller-supplied id [`RequestIdMiddleware`] reuses.
+const MAX_INCOMING_LEN: usize = 128;
+
+/// Header carrying the request id, on requests and responses.
+pub const X_REQUEST_ID: &str = "x-request-id";
+
+/// Keeps fallback ids generated in the same nanosecond apart.
+static FALLBACK_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A source of request ids.
+pub trait RequestIdGenerator: Send + Sync {
+    /// A new id.
+    fn generate(&self) -> String;
+}
+
+/// A generator that always returns the same id.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FixedRequestId {
+    id: String,
+}
+
+/// The id [`RequestIdMiddleware`] gave the current request.
+///
+/// As an extractor it fails with `500 Internal Server Error` when the
+/// middleware is not installed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RequestId(pub String);
+
+/// Middleware that gives each request a [`RequestId`] and sends it back in
+/// the response.
+///
+/// The id comes from the request's `X-Request-Id` when it is at most 128
+/// characters of `[A-Za-z0-9._:-]`, so ids from a load balancer carry
+/// through, and from [`RequestContext::generate_request_id`] otherwise.
+/// Errors are rendered here, as [`Cors`] does, so error responses carry the
+/// id too.
+///
+/// [`Cors`]: crate::cors::Cors
+#[derive(Clone, Debug)]
+pub struct RequestIdMiddleware {
+    header: HeaderName,
+    trust_incoming: bool,
+}
+
+/// The default generator, backed by [`gen_request_id`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RandomRequestId;
+
+/// The generator registered for a router, stored in each request's
+/// extensions.
+#[derive(Clone)]
+pub(crate) struct SharedRequestIdGenerator(Arc<dyn RequestIdGenerator>);
+
+impl FixedRequestId {
+    /// A generator returning `id` every time.
+    #[must_use]
+    #[inline]
+    pub fn new<S: Into<String>>(id: S) -> Self {
+        Self { id: id.into() }
+    }
+}
+
+#[async_trait(?Send)]
+impl FromRequest for RequestId {
+    #[inline]
+    async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+        ctx.request()
+            .extensions()
+            .get::<Self>()
+            .cloned()
+            .ok_or_else(|| {
+                EdgeError::internal(anyhow::anyhow!("RequestIdMiddleware is not installed"))
+            })
+    }
+}
+
+impl RequestId {
+    #[must_use]
+    #[inline]
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl RequestIdGenerator for FixedRequestId {
+    #[inline]
+    fn generate(&self) -> String {
+        self.id.clone()
+    }
+}
+
+impl RequestIdGenerator for RandomRequestId {
+    #[inline]
+    fn generate(&self) -> String {
+        gen_request_id()
+    }
+}
+
+impl RequestIdGenerator for SharedRequestIdGenerator {
+    #[inline]
+    fn generate(&self) -> String {
+        self.0.generate()
+    }
+}
+
+impl Default for RequestIdMiddleware {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            header: HeaderName::from_static(X_REQUEST_ID),
+            trust_incoming: true,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Middleware for RequestIdMiddleware {
+    #[inline]
+    async fn handle(&self, mut ctx: RequestContext, next: Next<'_>) -> Result<Response, EdgeError> {
+        let incoming = self
+            .trust_incoming
+            .then(|| ctx.request().headers().get(&self.header))
+            .flatten()
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| is_loggable(id))
+            .map(str::to_owned);
+        let id = incoming.unwrap_or_else(|| ctx.generate_request_id());
+        let value = HeaderValue::try_from(id.as_str()).map_err(EdgeError::internal)?;
+        ctx.request_mut().extensions_mut().insert(RequestId(id));
+
+        let mut response = next.run_rendered(ctx).await?;
+        response.headers_mut().insert(self.header.clone(), value);
+        Ok(response)
+    }
+}
+
+impl RequestIdMiddleware {
+    /// Read and write the id in `name` instead of `X-Request-Id`.
+    #[must_use]
+    #[inline]
+    pub fn header(mut self, name: HeaderName) -> Self {
+        self.header = name;
+        self
+    }
+
+    /// Whether to reuse an id the request already carries (the default), or
+    /// always generate one.
+    #[must_use]
+    #[inline]
+    pub const fn trust_incoming(mut self, trust: bool) -> Self {
+        self.trust_incoming = trust;
+        self
+    }
+}
+
+impl SharedRequestIdGenerator {
+    pub(crate) fn new<G: RequestIdGenerator + 'static>(generator: G) -> Self {
+        Self(Arc::new(generator))
+    }
+}
+
+fn encode_base62(mut value: u128) -> String {
+    let mut digits = [b'0'; ID_LEN];
+    for digit in digits.iter_mut().rev() {
+        let index = usize::try_from(value.checked_rem(62).unwrap_or_default()).unwrap_or_default();
+        *digit = BASE62.get(index).copied().unwrap_or(b'0');
+        value = value.checked_div(62).unwrap_or_default();
+    }
+    digits.iter().map(|&byte| char::from(byte)).collect()
+}
+
+/// Whether a caller-supplied id is short and plain enough to log as is.
+fn is_loggable(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_INCOMING_LEN
+        && id
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b':'))
+}
+
+/// `bytes` read as one big-endian number.
+fn pack<I: IntoIterator<Item = u8>>(bytes: I) -> u128 {
+    bytes
+        .into_iter()
+        .fold(0, |bits, byte| (bits << 8_u32) | u128::from(byte))
+}
+
+/// 128 bits from a hash of the time and a process-wide counter, for when
+/// the platform's random source fails.
+fn fallback_bits() -> u128 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    let count = FALLBACK_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let digest = Sha256::digest(format!("request-id:{nanos}:{count}"));
+    pack(digest.iter().take(16).copied())
+}
+
+/// A new random request id: 22 characters from `[0-9A-Za-z]`.
+///
+/// Should the platform's random source fail, the id is derived from the
+/// current time and a counter instead, so it stays unique but is guessable.
+#[must_use]
+#[inline]
+pub fn gen_request_id() -> String {
+    encode_base62(random_bits())
+}
+
+/// 128 random bits from the platform's random source, shared by every
+/// generated id (request ids, trace and span ids, multipart boundaries).
+///
+/// Should the random source fail, the bits are derived from the current time
+/// and a counter instead, so they stay unique but are guessable.
+pub(crate) fn random_bits() -> u128 {
+    let mut bytes = [0_u8; 16];
+    match getrandom::fill(&mut bytes) {
+        Ok(()) => pack(bytes),
+        Err(err) => {
+            log::warn!("random source unavailable for generated ids: {err}");
+            fallback_bits()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::RequestContext;
+    use crate::error::EdgeError;
+    use crate::router::RouterService;
+    use crate::test_client::TestClient;
+    use futures::executor::block_on;
+    use std::collections::BTreeSet;
+    use std::iter;
+
+    async fn echo_id(ctx: RequestContext) -> Result<String, EdgeError> {
+        Ok(ctx.generate_request_id())
+    }
+
+    #[test]
+    fn generated_ids_are_base62_and_unique() {
+        let ids: BTreeSet<String> = iter::repeat_with(gen_request_id).take(1000).collect();
+        assert_eq!(ids.len(), 1000);
+        for id in &ids {
+            assert_eq!(id.len(), ID_LEN);
+            assert!(id.bytes().all(|byte| byte.is_ascii_alphanumeric()), "{id}");
+        }
+    }
+
+    #[test]
+    fn base62_covers_the_whole_u128_range() {
+        assert_eq!(encode_base62(0), "0".repeat(ID_LEN));
+        assert_eq!(encode_base62(61), format!("{}z", "0".repeat(ID_LEN - 1)));
+        assert_eq!(encode_base62(u128::MAX), "7n42DGM5Tflk9n8mt7Fhc7");
+    }
+
+    #[test]
+    fn handlers_use_the_registered_generator() {
+        let router = RouterService::builder()
+            .with_request_id_generator(FixedRequestId::new("req-fixed"))
+            .get("/id", echo_id)
+            .build();
+        let response = block_on(TestClient::new(router).get("/id"));
+        assert_eq!(response.text(), "req-fixed");
+    }
+
+    #[test]
+    fn random_ids_are_the_default() {
+        let router = RouterService::builder().get("/id", echo_id).build();
+        let client = TestClient::new(router);
+        let first = block_on(client.get("/id")).text().to_owned();
+        let second = block_on(client.get("/id")).text().to_owned();
+        assert_eq!(first.len(), ID_LEN);
+        assert_ne!(first, second);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::cache_control::{CacheControl, WithCacheControl};
 use crate::error::EdgeError;
 use crate::http::Response;
 use crate::response::IntoResponse;
@@ -6,8 +7,20 @@ pub trait Responder: Sized {
     /// # Errors
     /// Returns [`EdgeError`] if the value cannot be turned into a response (e.g., a `Result`'s `Err` variant).
     fn respond(self) -> Result<Response, EdgeError>;
+
+    /// Respond as `self` would, with `cache_control` as the
+    /// `Cache-Control` header. Errors are passed through unchanged.
+    #[must_use]
+    #[inline]
+    fn with_cache_control(self, cache_control: CacheControl) -> WithCacheControl<Self> {
+        WithCacheControl::new(self, cache_control)
+    }
 }
 
+#[expect(
+    clippy::missing_trait_methods,
+    reason = "with_cache_control is the same for every responder"
+)]
 impl<T> Responder for T
 where
     T: IntoResponse,
@@ -18,6 +31,10 @@ where
     }
 }
 
+#[expect(
+    clippy::missing_trait_methods,
+    reason = "with_cache_control is the same for every responder"
+)]
 impl<T> Responder for Result<T, EdgeError>
 where
     T: IntoResponse,
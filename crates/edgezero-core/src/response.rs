@@ -1,9 +1,20 @@
+use std::mem;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use async_trait::async_trait;
+use bytes::{BufMut as _, Bytes, BytesMut};
+use futures_util::stream::{self, LocalBoxStream, StreamExt as _};
+use serde::Serialize;
+
 use crate::body::Body;
+use crate::context::RequestContext;
 use crate::error::EdgeError;
+use crate::extractor::FromRequest;
 use crate::http::{
-    HeaderValue, Response, StatusCode,
-    header::{CONTENT_LENGTH, CONTENT_TYPE},
+    HeaderMap, HeaderName, HeaderValue, Response, StatusCode,
+    header::{CONTENT_LENGTH, CONTENT_TYPE, IntoHeaderName, LOCATION, VARY},
 };
+use crate::request_id::random_bits;
 
 /// Convert common return types into `Response`.
 ///
@@ -11,6 +22,10 @@ use crate::http::{
 /// EdgeError>`. Callers must propagate response-building failures (typically
 /// invalid headers) instead of letting them panic at the `http::Builder`
 /// boundary.
+///
+/// Handlers returning `Result<T, EdgeError>` for any `T: IntoResponse` go
+/// through [`Responder`](crate::responder::Responder), which keeps the error
+/// as an `Err` so middleware can still observe it.
 pub trait IntoResponse {
     /// # Errors
     /// Returns [`EdgeError::internal`] if the underlying HTTP response cannot
@@ -19,6 +34,99 @@ pub trait IntoResponse {
     fn into_response(self) -> Result<Response, EdgeError>;
 }
 
+/// A header change recorded in [`ResponseParts`].
+#[derive(Debug)]
+enum HeaderEdit {
+    Append(HeaderName, HeaderValue),
+    Insert(HeaderName, HeaderValue),
+}
+
+/// Layout of JSON response bodies. Compact unless a router registers
+/// [`JsonFormat::Pretty`] with [`RouterBuilder::with_json_format`], which
+/// handlers pick up by taking a `JsonFormat` argument.
+///
+/// [`RouterBuilder::with_json_format`]: crate::router::RouterBuilder::with_json_format
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum JsonFormat {
+    /// No whitespace between tokens.
+    #[default]
+    Compact,
+    /// Indented, one member per line, for people reading the output.
+    Pretty,
+}
+
+/// Marks a response whose status and headers should be sent before the
+/// first body chunk is ready. Set with [`ResponseExt::flush_headers`].
+///
+/// Only the Axum adapter acts on it, since it otherwise buffers streamed
+/// bodies; see the streaming guide for the other platforms.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FlushHeaders;
+
+/// Status and headers for the current response, set out of band.
+///
+/// Code deep inside a handler can change the response without having it
+/// passed back up, by taking `ResponseParts` as an extractor or calling
+/// [`RequestContext::response_parts`]:
+///
+/// ```rust,ignore
+/// fn record_creation(ctx: &RequestContext, id: u64) -> Result<(), EdgeError> {
+///     let parts = ctx.response_parts();
+///     parts.set_status(StatusCode::CREATED);
+///     parts.insert_header(LOCATION, HeaderValue::try_from(format!("/orders/{id}"))?);
+///     Ok(())
+/// }
+/// ```
+///
+/// The router applies the changes to the handler's response as soon as the
+/// handler returns, so middleware sees the result; they take precedence
+/// over the status and headers the handler returned. Changes are dropped
+/// when the handler returns an error. Clones share the same parts.
+#[derive(Clone, Debug, Default)]
+pub struct ResponseParts {
+    pending: Arc<Mutex<PendingParts>>,
+}
+
+/// The changes a [`ResponseParts`] has collected.
+#[derive(Debug, Default)]
+struct PendingParts {
+    headers: Vec<HeaderEdit>,
+    status: Option<StatusCode>,
+}
+
+/// Constructors for bodiless responses, so handlers can write
+/// `Response::no_content()` with the trait in scope, and the
+/// [`FlushHeaders`] marker.
+pub trait ResponseExt: Sized {
+    /// Send the status and headers as soon as the response is returned,
+    /// before its streamed body yields a chunk.
+    #[must_use]
+    fn flush_headers(self) -> Self;
+
+    /// Whether [`Self::flush_headers`] was called.
+    fn flushes_headers(&self) -> bool;
+
+    /// An empty `204 No Content` response.
+    #[must_use]
+    fn no_content() -> Self;
+
+    /// An empty `304 Not Modified` response.
+    #[must_use]
+    fn not_modified() -> Self;
+
+    /// An empty redirect to `location` with `status`, such as
+    /// [`StatusCode::FOUND`] or [`StatusCode::PERMANENT_REDIRECT`].
+    ///
+    /// # Errors
+    /// Returns [`EdgeError::internal`] if `status` is not a redirect status
+    /// (`304 Not Modified` included) or `location` is not a valid header value.
+    fn redirect(status: StatusCode, location: &str) -> Result<Self, EdgeError>;
+
+    /// Add `field` to the `Vary` header; see [`append_vary`].
+    #[must_use]
+    fn vary(self, field: &str) -> Self;
+}
+
 impl IntoResponse for Response {
     #[inline]
     fn into_response(self) -> Result<Response, EdgeError> {
@@ -47,6 +155,182 @@ impl IntoResponse for String {
     }
 }
 
+impl IntoResponse for Bytes {
+    #[inline]
+    fn into_response(self) -> Result<Response, EdgeError> {
+        let mut response = response_with_body(StatusCode::OK, Body::from_bytes(self))?;
+        response.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/octet-stream"),
+        );
+        Ok(response)
+    }
+}
+
+impl IntoResponse for Vec<u8> {
+    #[inline]
+    fn into_response(self) -> Result<Response, EdgeError> {
+        Bytes::from(self).into_response()
+    }
+}
+
+impl IntoResponse for StatusCode {
+    #[inline]
+    fn into_response(self) -> Result<Response, EdgeError> {
+        response_with_body(self, Body::empty())
+    }
+}
+
+impl IntoResponse for serde_json::Value {
+    /// A `200 OK` `application/json` response in the compact layout.
+    #[inline]
+    fn into_response(self) -> Result<Response, EdgeError> {
+        // A `Value` always serializes; any error still surfaces as a 500.
+        JsonFormat::Compact.response(&self)
+    }
+}
+
+/// One part of a [`MultipartResponse`]: its own headers and a body, which
+/// may stream.
+#[derive(Debug)]
+pub struct MultipartPart {
+    body: Body,
+    headers: HeaderMap,
+}
+
+impl MultipartPart {
+    /// Add a header to the part, e.g. `content-type` or `content-id`.
+    #[must_use]
+    #[inline]
+    pub fn header<K: IntoHeaderName>(mut self, name: K, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// A part with `body` and no headers.
+    #[must_use]
+    #[inline]
+    pub fn new<B: Into<Body>>(body: B) -> Self {
+        Self {
+            body: body.into(),
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// Replace the part's headers.
+    #[must_use]
+    #[inline]
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+}
+
+/// A `multipart/mixed` response (RFC 2046), e.g. for batched API results.
+///
+/// Parts are written in order, each framed by a generated boundary, and the
+/// response streams: a part's body is not buffered.
+///
+/// ```rust,ignore
+/// MultipartResponse::new(vec![
+///     MultipartPart::new(Body::json(&first)?)
+///         .header(CONTENT_TYPE, HeaderValue::from_static("application/json")),
+///     MultipartPart::new("done"),
+/// ])
+/// ```
+#[derive(Debug)]
+pub struct MultipartResponse {
+    boundary: String,
+    parts: Vec<MultipartPart>,
+}
+
+impl MultipartResponse {
+    /// The boundary separating parts, also sent in the `content-type`.
+    #[must_use]
+    #[inline]
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// A response of `parts`, in order, with a freshly generated boundary.
+    #[must_use]
+    #[inline]
+    pub fn new(parts: Vec<MultipartPart>) -> Self {
+        Self {
+            boundary: generate_boundary(),
+            parts,
+        }
+    }
+}
+
+impl IntoResponse for MultipartResponse {
+    #[inline]
+    fn into_response(self) -> Result<Response, EdgeError> {
+        let content_type =
+            HeaderValue::from_str(&format!("multipart/mixed; boundary={}", self.boundary))
+                .map_err(EdgeError::internal)?;
+        let delimiter = format!("--{}", self.boundary);
+        let mut segments: Vec<LocalBoxStream<'static, Result<Bytes, anyhow::Error>>> =
+            Vec::with_capacity(self.parts.len().saturating_mul(2).saturating_add(1));
+        for (index, part) in self.parts.into_iter().enumerate() {
+            let mut head = BytesMut::new();
+            if index > 0 {
+                head.put_slice(b"\r\n");
+            }
+            head.put_slice(delimiter.as_bytes());
+            head.put_slice(b"\r\n");
+            for (name, value) in &part.headers {
+                head.put_slice(name.as_str().as_bytes());
+                head.put_slice(b": ");
+                head.put_slice(value.as_bytes());
+                head.put_slice(b"\r\n");
+            }
+            head.put_slice(b"\r\n");
+            segments.push(once_chunk(head.freeze()));
+            segments.push(match part.body {
+                Body::Once(bytes) => once_chunk(bytes),
+                Body::Stream(chunks) | Body::StreamWithTrailers(chunks, _) => chunks,
+            });
+        }
+        let closing = if segments.is_empty() {
+            format!("{delimiter}--\r\n")
+        } else {
+            format!("\r\n{delimiter}--\r\n")
+        };
+        segments.push(once_chunk(Bytes::from(closing)));
+
+        let body = Body::from_stream(stream::iter(segments).flatten());
+        let mut response = response_with_body(StatusCode::OK, body)?;
+        response.headers_mut().insert(CONTENT_TYPE, content_type);
+        Ok(response)
+    }
+}
+
+/// Binary data, sent as `application/octet-stream`. Use it for bytes that
+/// are not text, e.g. `Raw::new(png)` for an image.
+pub struct Raw<T>(T);
+
+impl<T> Raw<T> {
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> IntoResponse for Raw<T>
+where
+    T: Into<Bytes>,
+{
+    #[inline]
+    fn into_response(self) -> Result<Response, EdgeError> {
+        self.0.into().into_response()
+    }
+}
+
+/// Text, sent as `text/plain; charset=utf-8`. Contents that turn out not to
+/// be UTF-8 (e.g. a `Vec<u8>` read from elsewhere) are labelled
+/// `application/octet-stream` instead; use [`Raw`] for data known to be
+/// binary.
 pub struct Text<T>(T);
 
 impl<T> Text<T> {
@@ -58,11 +342,15 @@ impl<T> Text<T> {
 
 impl<T> IntoResponse for Text<T>
 where
-    T: Into<String>,
+    T: Into<Bytes>,
 {
     #[inline]
     fn into_response(self) -> Result<Response, EdgeError> {
-        response_with_body(StatusCode::OK, Body::text(self.0.into()))
+        let bytes = self.0.into();
+        if str::from_utf8(&bytes).is_err() {
+            return bytes.into_response();
+        }
+        response_with_body(StatusCode::OK, Body::from_bytes(bytes))
     }
 }
 
@@ -86,6 +374,208 @@ where
     }
 }
 
+#[async_trait(?Send)]
+impl FromRequest for JsonFormat {
+    #[inline]
+    async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+        Ok(ctx.extension::<Self>().unwrap_or_default())
+    }
+}
+
+impl JsonFormat {
+    /// `value` serialized in this layout.
+    ///
+    /// # Errors
+    /// Returns the serialization error when `value` cannot be written as JSON.
+    #[inline]
+    pub fn body<T: Serialize + ?Sized>(self, value: &T) -> Result<Body, serde_json::Error> {
+        let bytes = match self {
+            Self::Compact => serde_json::to_vec(value)?,
+            Self::Pretty => serde_json::to_vec_pretty(value)?,
+        };
+        Ok(Body::from_bytes(bytes))
+    }
+
+    /// A `200 OK` `application/json` response carrying `value` in this layout.
+    ///
+    /// # Errors
+    /// Returns [`EdgeError::internal`] when `value` cannot be serialized.
+    #[inline]
+    pub fn response<T: Serialize + ?Sized>(self, value: &T) -> Result<Response, EdgeError> {
+        let body = self.body(value).map_err(EdgeError::internal)?;
+        let mut response = response_with_body(StatusCode::OK, body)?;
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        Ok(response)
+    }
+}
+
+#[async_trait(?Send)]
+impl FromRequest for ResponseParts {
+    #[inline]
+    async fn from_request(ctx: &RequestContext) -> Result<Self, EdgeError> {
+        Ok(ctx.response_parts())
+    }
+}
+
+impl ResponseParts {
+    /// Add `value` to the response's `name` header, keeping existing values.
+    #[inline]
+    pub fn append_header(&self, name: HeaderName, value: HeaderValue) {
+        self.pending().headers.push(HeaderEdit::Append(name, value));
+    }
+
+    /// Apply the collected changes to `response` and clear them.
+    pub(crate) fn apply(&self, response: &mut Response) {
+        let PendingParts { headers, status } = mem::take(&mut *self.pending());
+        if let Some(code) = status {
+            *response.status_mut() = code;
+        }
+        for edit in headers {
+            match edit {
+                HeaderEdit::Append(name, value) => {
+                    response.headers_mut().append(name, value);
+                }
+                HeaderEdit::Insert(name, value) => {
+                    response.headers_mut().insert(name, value);
+                }
+            }
+        }
+    }
+
+    /// Set the response's `name` header to `value`, replacing its values.
+    #[inline]
+    pub fn insert_header(&self, name: HeaderName, value: HeaderValue) {
+        self.pending().headers.push(HeaderEdit::Insert(name, value));
+    }
+
+    fn pending(&self) -> MutexGuard<'_, PendingParts> {
+        self.pending.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Replace the response's status.
+    #[inline]
+    pub fn set_status(&self, status: StatusCode) {
+        self.pending().status = Some(status);
+    }
+
+    /// The status set with [`Self::set_status`], if any.
+    #[must_use]
+    #[inline]
+    pub fn status(&self) -> Option<StatusCode> {
+        self.pending().status
+    }
+}
+
+impl ResponseExt for Response {
+    #[inline]
+    fn flush_headers(mut self) -> Self {
+        self.extensions_mut().insert(FlushHeaders);
+        self
+    }
+
+    #[inline]
+    fn flushes_headers(&self) -> bool {
+        self.extensions().get::<FlushHeaders>().is_some()
+    }
+
+    #[inline]
+    fn no_content() -> Self {
+        empty_response(StatusCode::NO_CONTENT)
+    }
+
+    #[inline]
+    fn not_modified() -> Self {
+        empty_response(StatusCode::NOT_MODIFIED)
+    }
+
+    #[inline]
+    fn redirect(status: StatusCode, location: &str) -> Result<Self, EdgeError> {
+        if !status.is_redirection() || status == StatusCode::NOT_MODIFIED {
+            return Err(EdgeError::internal(anyhow::anyhow!(
+                "{status} is not a redirect status"
+            )));
+        }
+        let value = HeaderValue::try_from(location).map_err(EdgeError::internal)?;
+        let mut response = empty_response(status);
+        response.headers_mut().insert(LOCATION, value);
+        Ok(response)
+    }
+
+    #[inline]
+    fn vary(mut self, field: &str) -> Self {
+        append_vary(self.headers_mut(), field);
+        self
+    }
+}
+
+/// Add `field` to the `Vary` header in `headers`, unless it is already
+/// listed.
+///
+/// Field names compare case-insensitively, and the existing `Vary` lines are
+/// folded into a single comma-separated value, so middleware and handlers
+/// can each add the fields they depend on without duplicating any. A `Vary:
+/// *` already covers every field and is left as it is. A `field` that is
+/// not a valid header value is ignored.
+#[inline]
+pub fn append_vary(headers: &mut HeaderMap, field: &str) {
+    let name = field.trim();
+    if name.is_empty() {
+        return;
+    }
+    let values: Option<Vec<String>> = headers
+        .get_all(VARY)
+        .iter()
+        .map(|value| value.to_str().ok().map(str::to_owned))
+        .collect();
+    let Some(listed) = values else {
+        // A value that is not text cannot be merged; keep it and add a line.
+        if let Ok(value) = HeaderValue::try_from(name) {
+            headers.append(VARY, value);
+        }
+        return;
+    };
+    let mut fields: Vec<&str> = Vec::new();
+    for listed_name in listed
+        .iter()
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+    {
+        if !listed_name.is_empty()
+            && !fields
+                .iter()
+                .any(|seen| seen.eq_ignore_ascii_case(listed_name))
+        {
+            fields.push(listed_name);
+        }
+    }
+    if fields.contains(&"*") {
+        return;
+    }
+    if !fields.iter().any(|seen| seen.eq_ignore_ascii_case(name)) {
+        fields.push(name);
+    }
+    if let Ok(value) = HeaderValue::try_from(fields.join(", ")) {
+        headers.insert(VARY, value);
+    }
+}
+
+fn empty_response(status: StatusCode) -> Response {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+    response
+}
+
+/// A random boundary, unlikely to occur in any part body.
+fn generate_boundary() -> String {
+    format!("edgezero-{:032x}", random_bits())
+}
+
+fn once_chunk(bytes: Bytes) -> LocalBoxStream<'static, Result<Bytes, anyhow::Error>> {
+    stream::once(async move { Ok(bytes) }).boxed_local()
+}
+
 /// # Errors
 /// Returns [`EdgeError::internal`] if the underlying [`http::response::Builder`]
 /// rejects the supplied status, headers, or body.
@@ -112,6 +602,110 @@ pub fn response_with_body(status: StatusCode, body: Body) -> Result<Response, Ed
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::router::RouterService;
+    use crate::test_client::TestClient;
+    use futures::executor::block_on;
+
+    async fn create_order(ctx: RequestContext) -> Result<&'static str, EdgeError> {
+        record_creation(&ctx, 42)?;
+        Ok("created")
+    }
+
+    async fn failing_order(ctx: RequestContext) -> Result<&'static str, EdgeError> {
+        record_creation(&ctx, 7)?;
+        Err(EdgeError::bad_request("out of stock"))
+    }
+
+    /// A helper that only sees the context, as deeply nested code would.
+    fn record_creation(ctx: &RequestContext, id: u64) -> Result<(), EdgeError> {
+        let parts = ctx.response_parts();
+        parts.set_status(StatusCode::CREATED);
+        let location =
+            HeaderValue::try_from(format!("/orders/{id}")).map_err(EdgeError::internal)?;
+        parts.insert_header(LOCATION, location);
+        Ok(())
+    }
+
+    async fn tagged(ctx: RequestContext) -> Result<Response, EdgeError> {
+        let parts = ResponseParts::from_request(&ctx).await?;
+        parts.append_header(
+            HeaderName::from_static("x-tag"),
+            HeaderValue::from_static("b"),
+        );
+        let mut response = "tagged".into_response()?;
+        response
+            .headers_mut()
+            .insert("x-tag", HeaderValue::from_static("a"));
+        Ok(response)
+    }
+
+    fn assert_empty(response: Response) {
+        let body = block_on(response.into_body().into_bytes_bounded(usize::MAX)).expect("body");
+        assert!(body.is_empty());
+    }
+
+    #[test]
+    fn helpers_set_the_status_and_headers_of_the_final_response() {
+        let router = RouterService::builder()
+            .post("/orders", create_order)
+            .post("/orders/full", failing_order)
+            .get("/tagged", tagged)
+            .build();
+        let client = TestClient::new(router);
+
+        let created = block_on(client.post_json("/orders", &serde_json::json!({})));
+        assert_eq!(created.status(), StatusCode::CREATED);
+        assert_eq!(created.header("location"), Some("/orders/42"));
+        assert_eq!(created.text(), "created");
+
+        let failed = block_on(client.post_json("/orders/full", &serde_json::json!({})));
+        assert_eq!(failed.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(failed.header("location"), None);
+
+        let tagged = block_on(client.get("/tagged"));
+        let tags: Vec<_> = tagged.headers().get_all("x-tag").iter().collect();
+        assert_eq!(tags, ["a", "b"]);
+    }
+
+    #[test]
+    fn mounted_routers_apply_parts_once() {
+        let api = RouterService::builder().get("/tagged", tagged).build();
+        let router = RouterService::builder().mount("/api", api).build();
+        let response = block_on(TestClient::new(router).get("/api/tagged"));
+        assert_eq!(response.headers().get_all("x-tag").iter().count(), 2);
+    }
+
+    #[test]
+    fn no_content_and_not_modified_are_empty() {
+        let no_content = Response::no_content();
+        assert_eq!(no_content.status(), StatusCode::NO_CONTENT);
+        assert_empty(no_content);
+
+        let not_modified = Response::not_modified();
+        assert_eq!(not_modified.status(), StatusCode::NOT_MODIFIED);
+        assert!(not_modified.headers().get(CONTENT_LENGTH).is_none());
+        assert_empty(not_modified);
+    }
+
+    #[test]
+    fn redirect_sets_status_and_location() {
+        let response = Response::redirect(StatusCode::SEE_OTHER, "/orders/42").expect("redirect");
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            response.headers().get(LOCATION).expect("location"),
+            "/orders/42"
+        );
+        assert_empty(response);
+    }
+
+    #[test]
+    fn redirect_rejects_other_statuses_and_bad_locations() {
+        for status in [StatusCode::OK, StatusCode::NOT_MODIFIED] {
+            let err = Response::redirect(status, "/").expect_err("not a redirect");
+            assert_eq!(err.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        Response::redirect(StatusCode::FOUND, "/a\nb").expect_err("invalid header value");
+    }
 
     #[test]
     fn response_with_body_sets_length_and_type() {
@@ -162,4 +756,217 @@ mod tests {
         assert_eq!(response.status(), StatusCode::CREATED);
         assert_eq!(response.body().as_bytes().expect("buffered"), b"created");
     }
+
+    #[test]
+    fn status_code_builds_empty_response() {
+        let response = StatusCode::ACCEPTED.into_response().expect("response");
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert!(response.body().as_bytes().expect("buffered").is_empty());
+        assert!(response.headers().get(CONTENT_TYPE).is_none());
+    }
+
+    #[test]
+    fn status_code_tuple_wraps_any_responder() {
+        let response = (StatusCode::NOT_FOUND, String::from("missing"))
+            .into_response()
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.body().as_bytes().expect("buffered"), b"missing");
+
+        let empty = (StatusCode::ACCEPTED, ())
+            .into_response()
+            .expect("response");
+        assert_eq!(empty.status(), StatusCode::ACCEPTED);
+    }
+
+    #[test]
+    fn bytes_and_vec_are_octet_streams() {
+        for rendered in [
+            Bytes::from_static(b"\x00\x01").into_response(),
+            vec![0_u8, 1].into_response(),
+        ] {
+            let response = rendered.expect("response");
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(
+                response.headers().get(CONTENT_TYPE).expect("content type"),
+                "application/octet-stream"
+            );
+            assert_eq!(response.headers().get(CONTENT_LENGTH).expect("length"), "2");
+            assert_eq!(response.body().as_bytes().expect("buffered"), [0, 1]);
+        }
+    }
+
+    #[test]
+    fn multipart_response_frames_parts_with_boundary() {
+        let parts = vec![
+            MultipartPart::new(Body::from("{\"ok\":true}"))
+                .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+                .header("content-id", HeaderValue::from_static("<first>")),
+            MultipartPart::new(Body::stream(stream::iter([
+                Bytes::from_static(b"chunk one, "),
+                Bytes::from_static(b"chunk two"),
+            ]))),
+        ];
+        let multipart = MultipartResponse::new(parts);
+        let boundary = multipart.boundary().to_owned();
+        assert!(boundary.starts_with("edgezero-"));
+        assert_ne!(boundary, MultipartResponse::new(Vec::new()).boundary());
+
+        let response = multipart.into_response().expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).expect("content type"),
+            format!("multipart/mixed; boundary={boundary}").as_str()
+        );
+        assert!(response.headers().get(CONTENT_LENGTH).is_none());
+        assert!(response.body().is_stream());
+
+        let body = block_on(response.into_body().into_bytes_bounded(1024)).expect("body");
+        let expected = format!(
+            "--{boundary}\r\n\
+             content-type: application/json\r\n\
+             content-id: <first>\r\n\
+             \r\n\
+             {{\"ok\":true}}\r\n\
+             --{boundary}\r\n\
+             \r\n\
+             chunk one, chunk two\r\n\
+             --{boundary}--\r\n"
+        );
+        assert_eq!(body, expected.as_bytes());
+    }
+
+    #[test]
+    fn text_labels_utf8_as_text_and_other_bytes_as_binary() {
+        let text = Text::new(String::from("h\u{e9}llo"))
+            .into_response()
+            .expect("response");
+        assert_eq!(
+            text.headers().get(CONTENT_TYPE).expect("content type"),
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(
+            text.body().as_bytes().expect("buffered"),
+            "h\u{e9}llo".as_bytes()
+        );
+
+        let invalid = Text::new(vec![0xff_u8, 0xfe])
+            .into_response()
+            .expect("response");
+        assert_eq!(
+            invalid.headers().get(CONTENT_TYPE).expect("content type"),
+            "application/octet-stream"
+        );
+    }
+
+    #[test]
+    fn raw_is_binary_even_when_it_looks_like_text() {
+        let response = Raw::new(&b"GIF89a"[..]).into_response().expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).expect("content type"),
+            "application/octet-stream"
+        );
+        assert_eq!(response.headers().get(CONTENT_LENGTH).expect("length"), "6");
+        assert_eq!(response.body().as_bytes().expect("buffered"), b"GIF89a");
+    }
+
+    #[test]
+    fn append_vary_folds_fields_into_one_deduplicated_value() {
+        let mut headers = HeaderMap::new();
+        headers.append(VARY, HeaderValue::from_static("Origin"));
+        headers.append(VARY, HeaderValue::from_static("accept-encoding, ORIGIN"));
+
+        append_vary(&mut headers, "Accept");
+        append_vary(&mut headers, "Accept-Encoding");
+        append_vary(&mut headers, " accept ");
+        assert_eq!(headers.get_all(VARY).iter().count(), 1);
+        assert_eq!(headers[VARY], "Origin, accept-encoding, Accept");
+
+        let mut any = HeaderMap::new();
+        any.insert(VARY, HeaderValue::from_static("*"));
+        append_vary(&mut any, "Origin");
+        assert_eq!(any[VARY], "*");
+
+        let vary = Response::no_content().vary("Origin").vary("origin");
+        assert_eq!(vary.headers()[VARY], "Origin");
+    }
+
+    #[test]
+    fn middlewares_share_a_single_vary_header() {
+        use crate::compression::CompressResponse;
+        use crate::cors::Cors;
+        use crate::http::header::{ACCEPT_ENCODING, CONTENT_TYPE, ORIGIN};
+        use crate::http::request_builder;
+        use crate::middleware::{Middleware, Next};
+
+        /// Picks a representation by `Accept`, and so varies on it.
+        struct Negotiate;
+
+        #[async_trait(?Send)]
+        impl Middleware for Negotiate {
+            async fn handle(
+                &self,
+                ctx: RequestContext,
+                next: Next<'_>,
+            ) -> Result<Response, EdgeError> {
+                Ok(next.run(ctx).await?.vary("Accept").vary("origin"))
+            }
+        }
+
+        async fn page(_ctx: RequestContext) -> Result<Response, EdgeError> {
+            let mut response = "<p>hello</p>".repeat(64).into_response()?;
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static("text/html"));
+            Ok(response)
+        }
+
+        let cors = Cors::builder()
+            .allow_origin("https://app.example.com")
+            .build()
+            .expect("policy");
+        let router = RouterService::builder()
+            .middleware(cors)
+            .middleware(CompressResponse)
+            .middleware(Negotiate)
+            .get("/page", page)
+            .build();
+        let request = request_builder()
+            .uri("/page")
+            .header(ORIGIN, "https://app.example.com")
+            .header(ACCEPT_ENCODING, "gzip")
+            .body(Body::empty())
+            .expect("request");
+        let response = block_on(TestClient::new(router).send(request));
+
+        assert_eq!(response.headers().get_all(VARY).iter().count(), 1);
+        assert_eq!(
+            response.header("vary"),
+            Some("Accept, origin, Accept-Encoding")
+        );
+    }
+
+    #[test]
+    fn json_values_are_application_json_responses() {
+        async fn status(_ctx: RequestContext) -> Result<serde_json::Value, EdgeError> {
+            Ok(serde_json::json!({ "ok": true, "items": [1_u8, 2_u8] }))
+        }
+
+        let router = RouterService::builder().get("/status", status).build();
+        let response = block_on(TestClient::new(router).get("/status"));
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.header("content-type"), Some("application/json"));
+        let body: serde_json::Value = serde_json::from_str(response.text()).expect("json body");
+        assert_eq!(
+            body,
+            serde_json::json!({ "ok": true, "items": [1_u8, 2_u8] })
+        );
+
+        let created = (StatusCode::CREATED, serde_json::json!({ "id": 7_u8 }))
+            .into_response()
+            .expect("response");
+        assert_eq!(created.status(), StatusCode::CREATED);
+        assert_eq!(created.headers()[CONTENT_TYPE], "application/json");
+    }
 }
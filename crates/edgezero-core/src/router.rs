@@ -1,22 +1,94 @@
-use std::collections::{HashMap, HashSet};
+use std::any::type_name;
+use std::collections::HashMap;
+use std::future::Future;
+use std::mem;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
-use matchit::Router as PathRouter;
+use futures::future::{FutureExt as _, LocalBoxFuture, poll_fn};
+use futures::stream;
+use matchit::{InsertError, Router as PathRouter};
+use thiserror::Error;
 use tower_service::Service;
 
+use crate::body::Body;
+use crate::body_limit::BodyLimit;
+use crate::body_spool::BodyBuffering;
+use crate::clock::{Clock, SharedClock};
 use crate::context::RequestContext;
+use crate::deadline::RouteTimeout;
 use crate::error::EdgeError;
-use crate::handler::{BoxHandler, IntoHandler, IntrospectionNeeds};
-use crate::http::{Extensions, HandlerFuture, Method, Request, Response};
-use crate::introspection::{ManifestJson, RouteTable};
-use crate::middleware::{BoxMiddleware, Middleware, Next};
-use crate::params::PathParams;
-use crate::response::IntoResponse as _;
+use crate::extractor::{JsonLimits, QueryLimit, Settings};
+use crate::forwarded::TrustedProxies;
+use crate::handler::{BoxHandler, DynHandler, IntoHandler, IntrospectionNeeds};
+use crate::http::header::CONTENT_LENGTH;
+use crate::http::{
+    Extensions, HandlerFuture, HeaderMap, HeaderValue, Method, Request, Response, StatusCode,
+};
+use crate::introspection::{ManifestJson, RouteListingAccess, RouteTable};
+#[cfg(any(test, feature = "metrics"))]
+use crate::metrics::{MetricsMiddleware, MetricsRegistry};
+use crate::middleware::{
+    AfterMiddleware, BoxAfterMiddleware, BoxErrorHook, BoxMiddleware, ErrorHook, Middleware,
+    MiddlewareRegistry, Next, UnknownMiddleware,
+};
+use crate::observability::Observability;
+use crate::params::{PathParams, decode_path_param};
+use crate::proxy::{ProxyInterceptors, ProxyRequestInterceptor, ProxyResponseInterceptor};
+#[cfg(any(test, feature = "request-debug"))]
+use crate::request_debug;
+use crate::request_id::{RequestIdGenerator, SharedRequestIdGenerator};
+use crate::response::{IntoResponse as _, JsonFormat};
+
+/// The methods [`RouterBuilder::any`] registers.
+const STANDARD_METHODS: [Method; 9] = [
+    Method::GET,
+    Method::HEAD,
+    Method::POST,
+    Method::PUT,
+    Method::DELETE,
+    Method::CONNECT,
+    Method::OPTIONS,
+    Method::TRACE,
+    Method::PATCH,
+];
+
+/// Route template (e.g. `/users/{id}`) of the route that matched the
+/// request, injected into every dispatched request's extensions. Read via
+/// [`RequestContext::matched_route`].
+#[derive(Clone, Debug)]
+pub(crate) struct MatchedRoute(Arc<str>);
+
+impl MatchedRoute {
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Prefix of the [`RouterBuilder::mount`] points the request was dispatched
+/// through, outermost first (e.g. `/api/v1`). Read via
+/// [`RequestContext::route_path`].
+#[derive(Clone, Debug)]
+pub(crate) struct MountPrefix(Arc<str>);
+
+impl MountPrefix {
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A router mounted under `prefix` by [`RouterBuilder::mount`].
+#[derive(Clone)]
+struct Mount {
+    prefix: Arc<str>,
+    router: RouterService,
+}
 
 struct RouteEntry {
     handler: BoxHandler,
     introspection_needs: IntrospectionNeeds,
+    template: Arc<str>,
 }
 
 impl Clone for RouteEntry {
@@ -24,12 +96,14 @@ impl Clone for RouteEntry {
         Self {
             handler: Arc::clone(&self.handler),
             introspection_needs: self.introspection_needs,
+            template: Arc::clone(&self.template),
         }
     }
 
     fn clone_from(&mut self, source: &Self) {
         self.handler = Arc::clone(&source.handler);
         self.introspection_needs = source.introspection_needs;
+        self.template = Arc::clone(&source.template);
     }
 }
 
@@ -61,65 +135,252 @@ impl RouteInfo {
     }
 }
 
+/// Caps on the route table, checked by [`RouterBuilder::build`] and
+/// [`RouterBuilder::try_build`] as a guard against pathological tables,
+/// e.g. ones generated from manifest triggers.
+///
+/// Register with [`RouterBuilder::with_route_limits`]; without one the
+/// table is not capped. Each method of a path counts as one route, and
+/// mounted routers are checked when they are built.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RouteLimits {
+    params: usize,
+    path_length: usize,
+    routes: usize,
+}
+
+impl RouteLimits {
+    /// Check `routes` against every limit.
+    fn check(&self, routes: &[RouteInfo]) -> Result<(), RouterBuildError> {
+        if routes.len() > self.routes {
+            return Err(RouterBuildError::TooManyRoutes {
+                count: routes.len(),
+                max: self.routes,
+            });
+        }
+        for route in routes {
+            if route.path.len() > self.path_length {
+                return Err(RouterBuildError::PathTooLong {
+                    length: route.path.len(),
+                    max: self.path_length,
+                    path: route.path.clone(),
+                });
+            }
+            let params = param_count(&route.path);
+            if params > self.params {
+                return Err(RouterBuildError::TooManyParams {
+                    count: params,
+                    max: self.params,
+                    path: route.path.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Maximum number of parameters and catch-alls in one pattern.
+    #[must_use]
+    #[inline]
+    pub const fn max_params(mut self, params: usize) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Maximum length of a pattern in bytes.
+    #[must_use]
+    #[inline]
+    pub const fn max_path_length(mut self, length: usize) -> Self {
+        self.path_length = length;
+        self
+    }
+
+    /// Maximum number of routes.
+    #[must_use]
+    #[inline]
+    pub const fn max_routes(mut self, routes: usize) -> Self {
+        self.routes = routes;
+        self
+    }
+
+    /// Limits of 1024 routes, 2048-byte patterns and 16 parameters per
+    /// pattern, well past what a hand-written app needs.
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            params: 16,
+            path_length: 2048,
+            routes: 1024,
+        }
+    }
+}
+
+impl Default for RouteLimits {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Deferred setup registered by [`RouterBuilder::middleware_async`].
+pub(crate) type MiddlewareSetup =
+    Arc<dyn Fn() -> LocalBoxFuture<'static, Result<BoxMiddleware, RouterBuildError>> + Send + Sync>;
+
 enum RouteMatch<'route> {
     Found(&'route RouteEntry, PathParams),
-    MethodNotAllowed(Vec<Method>),
+    /// A captured param is not UTF-8 once percent-decoded.
+    InvalidParam(String),
+    MethodNotAllowed(Vec<Method>, Arc<str>),
     NotFound,
 }
 
 #[derive(Default)]
 pub struct RouterBuilder {
+    after: Vec<BoxAfterMiddleware>,
+    body_buffering: BodyBuffering,
+    body_limit: Option<BodyLimit>,
+    /// Paths of endpoints the router serves itself, such as
+    /// [`Self::enable_metrics_at`].
+    builtin_paths: Vec<Arc<str>>,
+    error_hooks: Vec<BoxErrorHook>,
     manifest_json: Option<Arc<str>>,
+    /// Names [`Self::named_middleware`] and
+    /// [`Self::named_middleware_or_report`] resolved, in order.
+    middleware_names: Vec<String>,
     middlewares: Vec<BoxMiddleware>,
+    mounts: Vec<Mount>,
+    /// Async middleware setups with their position among `middlewares`,
+    /// resolved by [`RouterService::ready`].
+    pending_middlewares: Vec<(usize, MiddlewareSetup)>,
+    /// Routes the panicking registration methods could not add, reported
+    /// by [`RouterBuilder::build`] or [`RouterBuilder::try_build`].
+    route_errors: Vec<RouteError>,
     route_info: Vec<RouteInfo>,
+    route_limits: Option<RouteLimits>,
+    route_listing_access: Option<RouteListingAccess>,
+    route_names: HashMap<String, Arc<str>>,
     routes: HashMap<Method, PathRouter<RouteEntry>>,
     /// App state registered via [`RouterBuilder::with_state`], keyed by type.
     /// Cloned into every request's extensions at dispatch.
     state_extensions: Extensions,
+    /// Names [`RouterBuilder::named_middleware_or_report`] found nothing
+    /// registered under, reported by [`App::validate`](crate::app::App::validate).
+    unknown_middleware: Vec<String>,
 }
 
 impl RouterBuilder {
-    #[expect(
-        clippy::panic,
-        reason = "duplicate route is a build-time programmer error, not a runtime condition"
-    )]
     fn add_route<H>(&mut self, path: &str, method: Method, handler: H)
     where
         H: IntoHandler,
     {
-        let router = self.routes.entry(method.clone()).or_default();
-
-        // The handler reports which introspection payloads its route needs; the
-        // flag is read once here and consulted per request in `dispatch`.
-        let boxed = handler.into_handler();
-        let introspection_needs = boxed.introspection_needs();
+        if let Err(err) = self.try_add_route(path, method, handler) {
+            self.route_errors.push(err);
+        }
+    }
 
-        router
-            .insert(
-                path,
-                RouteEntry {
-                    handler: boxed,
-                    introspection_needs,
-                },
-            )
-            .unwrap_or_else(|err| panic!("duplicate route definition for {path}: {err}"));
+    /// Run `hook` on every response on its way out, including rendered
+    /// errors and 404/405 responses. Hooks run after all [`Self::middleware`]
+    /// (and any middleware the app layers on), in registration order. See
+    /// [`AfterMiddleware`].
+    #[must_use]
+    #[inline]
+    pub fn after<A>(mut self, hook: A) -> Self
+    where
+        A: AfterMiddleware,
+    {
+        self.after.push(Arc::new(hook));
+        self
+    }
 
-        self.route_info
-            .push(RouteInfo::new(method, path.to_owned()));
+    /// [`Self::route_methods`] for every standard method: `GET`, `HEAD`,
+    /// `POST`, `PUT`, `DELETE`, `CONNECT`, `OPTIONS`, `TRACE` and `PATCH`.
+    ///
+    /// # Panics
+    /// [`Self::build`] panics if `path` is already registered for any of
+    /// them.
+    #[must_use]
+    #[inline]
+    pub fn any<H>(self, path: &str, handler: H) -> Self
+    where
+        H: IntoHandler,
+    {
+        self.route_methods(&STANDARD_METHODS, path, handler)
     }
 
+    /// Middleware registered with [`Self::middleware_async`] is not set up
+    /// yet: the router answers every request with an error until
+    /// [`RouterService::ready`] has run, as adapters' `run_app` does.
+    ///
+    /// # Panics
+    /// Panics if a route could not be registered, e.g. because its pattern
+    /// conflicts with an earlier one, or if the table breaks the
+    /// [`Self::with_route_limits`]. [`Self::try_build`] returns both as
+    /// errors.
+    #[expect(
+        clippy::panic,
+        reason = "a bad route table is a build-time programmer error, not a runtime condition"
+    )]
     #[must_use]
     #[inline]
-    pub fn build(self) -> RouterService {
+    pub fn build(mut self) -> RouterService {
+        match self.check_routes() {
+            Ok(()) => {}
+            Err(RouterBuildError::Route(err)) => panic!(
+                "duplicate route definition: {err}; use `try_build` to handle it as an error"
+            ),
+            Err(err) => panic!("{err}; use `try_build` to handle it as an error"),
+        }
         let route_index: Arc<[RouteInfo]> = Arc::from(self.route_info);
 
-        RouterService::new(
+        let mut service = RouterService::new(
+            self.after,
+            self.route_names,
             self.routes,
             self.middlewares,
             route_index,
             self.manifest_json,
             self.state_extensions,
-        )
+        );
+        let inner = Arc::make_mut(&mut service.inner);
+        inner.body_buffering = self.body_buffering;
+        inner.body_limit = self.body_limit;
+        inner.builtin_paths = self.builtin_paths;
+        inner.error_hooks = self.error_hooks;
+        inner.middleware_names = self.middleware_names;
+        inner.mounts = self.mounts;
+        inner.pending_middlewares = self.pending_middlewares;
+        inner.route_listing_access = self.route_listing_access;
+        inner.unknown_middleware = self.unknown_middleware;
+        service
+    }
+
+    /// The first route that could not be registered, then the
+    /// [`RouteLimits`], if any.
+    fn check_routes(&mut self) -> Result<(), RouterBuildError> {
+        if let Some(err) = mem::take(&mut self.route_errors).into_iter().next() {
+            return Err(RouterBuildError::Route(err));
+        }
+        self.route_limits
+            .map_or(Ok(()), |limits| limits.check(&self.route_info))
+    }
+
+    /// Add `headers` to every response that lacks them, including rendered
+    /// errors and 404/405 responses, e.g. a `Server` or version header. A
+    /// header the handler (or middleware) set is left alone. Runs as an
+    /// [`Self::after`] hook, in registration order with the others.
+    #[must_use]
+    #[inline]
+    pub fn default_response_headers(self, headers: HeaderMap) -> Self {
+        self.after(move |response: &mut Response| {
+            for name in headers.keys() {
+                if response.headers().contains_key(name) {
+                    continue;
+                }
+                for value in headers.get_all(name) {
+                    response.headers_mut().append(name.clone(), value.clone());
+                }
+            }
+        })
     }
 
     #[must_use]
@@ -131,6 +392,61 @@ impl RouterBuilder {
         self.route(path, Method::DELETE, handler)
     }
 
+    /// [`Self::delete`], registering the route as `name` for
+    /// [`RouterService::url_for`].
+    #[must_use]
+    #[inline]
+    pub fn delete_named<H>(self, name: &str, path: &str, handler: H) -> Self
+    where
+        H: IntoHandler,
+    {
+        self.route_named(name, path, Method::DELETE, handler)
+    }
+
+    /// Record request counts and latencies for every route and serve them
+    /// in the Prometheus text format at `GET path`. See [`crate::metrics`].
+    #[cfg(any(test, feature = "metrics"))]
+    #[must_use]
+    #[inline]
+    pub fn enable_metrics_at(mut self, path: &str) -> Self {
+        self.builtin_paths.push(Arc::from(path));
+        let registry = MetricsRegistry::new();
+        let endpoint = registry.clone();
+        self.middleware(MetricsMiddleware::new(registry))
+            .get(path, move |_ctx: RequestContext| {
+                let response = endpoint.response();
+                async move { response }
+            })
+    }
+
+    /// Register a development-only endpoint at `path` that echoes each
+    /// request's method, URI, query, headers, and (capped) body back as
+    /// JSON, for any of `GET`, `POST`, `PUT`, `PATCH`, and `DELETE`. See
+    /// [`crate::request_debug`].
+    ///
+    /// **Unsafe for production**: the echo reflects credentials such as
+    /// `Authorization` and `HttpOnly` cookies back to the caller.
+    #[cfg(any(test, feature = "request-debug"))]
+    #[must_use]
+    #[inline]
+    pub fn enable_request_debug_at(mut self, path: &str) -> Self {
+        self.builtin_paths.push(Arc::from(path));
+        tracing::warn!(
+            "request debug endpoint enabled at {path}; it echoes credentials and must not be used in production"
+        );
+        [
+            Method::DELETE,
+            Method::GET,
+            Method::PATCH,
+            Method::POST,
+            Method::PUT,
+        ]
+        .into_iter()
+        .fold(self, |builder, method| {
+            builder.route(path, method, request_debug::echo)
+        })
+    }
+
     #[must_use]
     #[inline]
     pub fn get<H>(self, path: &str, handler: H) -> Self
@@ -140,6 +456,46 @@ impl RouterBuilder {
         self.route(path, Method::GET, handler)
     }
 
+    /// [`Self::get`], registering the route as `name` for
+    /// [`RouterService::url_for`].
+    #[must_use]
+    #[inline]
+    pub fn get_named<H>(self, name: &str, path: &str, handler: H) -> Self
+    where
+        H: IntoHandler,
+    {
+        self.route_named(name, path, Method::GET, handler)
+    }
+
+    fn insert_route(
+        &mut self,
+        route: &str,
+        method: Method,
+        handler: BoxHandler,
+    ) -> Result<(), RouteError> {
+        let path = root_if_empty(route);
+        let router = self.routes.entry(method.clone()).or_default();
+
+        // The handler reports which introspection payloads its route needs; the
+        // flag is read once here and consulted per request in `dispatch`.
+        let introspection_needs = handler.introspection_needs();
+
+        router
+            .insert(
+                path,
+                RouteEntry {
+                    handler,
+                    introspection_needs,
+                    template: Arc::from(path),
+                },
+            )
+            .map_err(|err| RouteError::from_insert(&method, path, err))?;
+
+        self.route_info
+            .push(RouteInfo::new(method, path.to_owned()));
+        Ok(())
+    }
+
     #[must_use]
     #[inline]
     pub fn middleware<M>(mut self, middleware: M) -> Self
@@ -157,12 +513,185 @@ impl RouterBuilder {
         self
     }
 
+    /// Register middleware whose construction needs async, fallible setup,
+    /// e.g. opening a connection or fetching a signing key. `setup` runs when
+    /// the router is made [`RouterService::ready`], which [`Self::try_build`]
+    /// and adapters' `run_app` do and which fails if it does; the middleware
+    /// keeps its place in registration order relative to [`Self::middleware`].
+    ///
+    /// ```rust,ignore
+    /// let router = RouterService::builder()
+    ///     .middleware_async(|| async { Ok(Auth::new(fetch_jwks().await?)) })
+    ///     .get("/", index)
+    ///     .try_build()
+    ///     .await?;
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn middleware_async<F, Fut, M>(mut self, setup: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<M, EdgeError>> + 'static,
+        M: Middleware,
+    {
+        let position = self
+            .middlewares
+            .len()
+            .saturating_add(self.pending_middlewares.len());
+        self.pending_middlewares
+            .push((position, middleware_setup(setup)));
+        self
+    }
+
+    /// Serve every path at or under `prefix` (e.g. `/api/v1`) from `router`,
+    /// which matches its routes against the rest of the path: a request for
+    /// `/api/v1/users/7` reaches its `/users/{id}` route, and a handler can
+    /// read `/users/7` back from [`RequestContext::route_path`].
+    ///
+    /// This router's own routes win over a mount; among mounts the longest
+    /// matching prefix wins. Mounted requests run through this router's
+    /// middleware, then `router` answers them as it would on its own, with
+    /// its middleware, error rendering and after hooks. Its routes are listed
+    /// in [`RouterService::routes`] with the prefix added.
+    ///
+    /// # Panics
+    /// Panics if `prefix` does not start with `/` or is already mounted.
+    #[must_use]
+    #[inline]
+    pub fn mount(mut self, prefix: &str, router: RouterService) -> Self {
+        assert!(
+            prefix.starts_with('/'),
+            "mount prefix `{prefix}` must start with `/`"
+        );
+        let trimmed = prefix.trim_end_matches('/');
+        assert!(
+            self.mounts.iter().all(|mount| &*mount.prefix != trimmed),
+            "duplicate mount for `{trimmed}/`"
+        );
+        self.route_info
+            .extend(router.routes().into_iter().map(|info| {
+                let path = format!("{trimmed}{}", info.path());
+                RouteInfo::new(info.method().clone(), path)
+            }));
+        self.mounts.push(Mount {
+            prefix: Arc::from(trimmed),
+            router,
+        });
+        self
+    }
+
+    fn name_route(&mut self, name: &str, route: &str) {
+        let path = root_if_empty(route);
+        let template = self
+            .route_names
+            .entry(name.to_owned())
+            .or_insert_with(|| Arc::from(path));
+        assert!(
+            &**template == path,
+            "route name `{name}` is already used for {template}"
+        );
+    }
+
+    /// Register the middleware named by `names`, in order, from `registry`,
+    /// as [`Self::middleware`] would. This is how `[app] middleware` entries
+    /// that are names rather than paths are applied.
+    ///
+    /// ```rust,ignore
+    /// let router = RouterService::builder()
+    ///     .named_middleware(&manifest.app.middleware, &registry)?
+    ///     .get("/", index)
+    ///     .build();
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`UnknownMiddleware`] for the first name with nothing
+    /// registered under it.
+    #[inline]
+    pub fn named_middleware<I, S>(
+        mut self,
+        names: I,
+        registry: &MiddlewareRegistry,
+    ) -> Result<Self, UnknownMiddleware>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for name in names {
+            self.middlewares.push(registry.build(name.as_ref())?);
+            self.middleware_names.push(name.as_ref().to_owned());
+        }
+        Ok(self)
+    }
+
+    /// [`Self::named_middleware`], except that a name with nothing registered
+    /// under it is skipped and reported by
+    /// [`App::validate`](crate::app::App::validate) as
+    /// [`AppValidationError::UnknownMiddleware`](crate::app::AppValidationError::UnknownMiddleware),
+    /// so adapters' `run_app` fails with that error instead of panicking.
+    /// The `app!` macro applies `[app] middleware` names this way.
+    #[must_use]
+    #[inline]
+    pub fn named_middleware_or_report<I, S>(
+        mut self,
+        names: I,
+        registry: &MiddlewareRegistry,
+    ) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for name in names {
+            match registry.build(name.as_ref()) {
+                Ok(middleware) => {
+                    self.middlewares.push(middleware);
+                    self.middleware_names.push(name.as_ref().to_owned());
+                }
+                Err(err) => self.unknown_middleware.push(err.name().to_owned()),
+            }
+        }
+        self
+    }
+
     #[must_use]
     #[inline]
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Register the observability middleware `stack` enables, outermost
+    /// first, at this point in the middleware order. See
+    /// [`Self::with_observability`].
+    #[must_use]
+    #[inline]
+    pub fn observability(mut self, stack: Observability) -> Self {
+        self.middlewares.extend(stack.into_middlewares());
+        self
+    }
+
+    /// Call `hook` with every error a handler or middleware returns, before
+    /// it is rendered as the response, e.g. to log its source chain. The hook
+    /// also sees the request, without its body. Hooks run in registration
+    /// order; a mounted router reports its errors to its own hooks. See
+    /// [`ErrorHook`].
+    ///
+    /// ```rust,ignore
+    /// let router = RouterService::builder()
+    ///     .on_error(|err: &EdgeError, ctx: &RequestContext| {
+    ///         tracing::error!(path = %ctx.request().uri().path(), "{err:#}");
+    ///     })
+    ///     .get("/reports/{id}", report)
+    ///     .build();
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn on_error<E>(mut self, hook: E) -> Self
+    where
+        E: ErrorHook,
+    {
+        self.error_hooks.push(Arc::new(hook));
+        self
+    }
+
     #[must_use]
     #[inline]
     pub fn post<H>(self, path: &str, handler: H) -> Self
@@ -172,6 +701,17 @@ impl RouterBuilder {
         self.route(path, Method::POST, handler)
     }
 
+    /// [`Self::post`], registering the route as `name` for
+    /// [`RouterService::url_for`].
+    #[must_use]
+    #[inline]
+    pub fn post_named<H>(self, name: &str, path: &str, handler: H) -> Self
+    where
+        H: IntoHandler,
+    {
+        self.route_named(name, path, Method::POST, handler)
+    }
+
     #[must_use]
     #[inline]
     pub fn put<H>(self, path: &str, handler: H) -> Self
@@ -181,6 +721,32 @@ impl RouterBuilder {
         self.route(path, Method::PUT, handler)
     }
 
+    /// [`Self::put`], registering the route as `name` for
+    /// [`RouterService::url_for`].
+    #[must_use]
+    #[inline]
+    pub fn put_named<H>(self, name: &str, path: &str, handler: H) -> Self
+    where
+        H: IntoHandler,
+    {
+        self.route_named(name, path, Method::PUT, handler)
+    }
+
+    /// Register `handler` for `method` requests to `path`. Any method works,
+    /// including extension methods the typed helpers do not cover:
+    ///
+    /// ```rust,ignore
+    /// let purge = Method::from_bytes(b"PURGE")?;
+    /// let router = RouterService::builder().route("/cache/{*key}", purge, purge_key).build();
+    /// ```
+    ///
+    /// Such methods take part in `405 Method Not Allowed` answers and their
+    /// `Allow` header like any other.
+    ///
+    /// # Panics
+    /// [`Self::build`] panics if `path` conflicts with a route already
+    /// registered for `method`, or is not a valid route; see
+    /// [`Self::try_route`] and [`Self::try_build`].
     #[must_use]
     #[inline]
     pub fn route<H>(mut self, path: &str, method: Method, handler: H) -> Self
@@ -191,47 +757,367 @@ impl RouterBuilder {
         self
     }
 
+    /// Restrict which browser origins may call the route listing, i.e. any
+    /// route whose handler is [`introspection::routes`]. The policy runs
+    /// outside the app's middleware for those routes, preflights included.
+    ///
+    /// [`introspection::routes`]: crate::introspection::routes
     #[must_use]
     #[inline]
-    pub fn with_manifest_json<S: Into<Arc<str>>>(mut self, json: S) -> Self {
-        self.manifest_json = Some(json.into());
+    pub fn route_listing_access(mut self, access: RouteListingAccess) -> Self {
+        self.route_listing_access = Some(access);
         self
     }
 
-    /// Register a value cloned into every request's extensions before
-    /// dispatch, making it available to the [`State<T>`] extractor and to
-    /// `RequestContext`-based handlers.
+    /// [`Self::route`] for each of `methods`, sharing one handler.
     ///
-    /// Typically `T = Arc<AppState>`. Registering the same `T` twice is
-    /// last-write-wins. Cost is one `T::clone` (an `Arc` bump for
-    /// `Arc<AppState>`) per registered state per request.
+    /// # Panics
+    /// [`Self::build`] panics if `path` is already registered for any of
+    /// `methods`.
+    #[must_use]
+    #[inline]
+    pub fn route_methods<H>(mut self, methods: &[Method], path: &str, handler: H) -> Self
+    where
+        H: IntoHandler,
+    {
+        let shared = handler.into_handler();
+        for method in methods {
+            if let Err(err) = self.insert_route(path, method.clone(), Arc::clone(&shared)) {
+                self.route_errors.push(err);
+            }
+        }
+        self
+    }
+
+    /// [`Self::route`], registering the route as `name` so
+    /// [`RouterService::url_for`] can build links to it. One name may cover
+    /// several methods on the same path.
     ///
-    /// [`State<T>`]: crate::extractor::State
+    /// # Panics
+    /// Panics if `name` is already registered for a different path.
     #[must_use]
     #[inline]
-    pub fn with_state<T>(mut self, value: T) -> Self
+    pub fn route_named<H>(mut self, name: &str, path: &str, method: Method, handler: H) -> Self
     where
-        T: Clone + Send + Sync + 'static,
+        H: IntoHandler,
     {
-        self.state_extensions.insert(value);
+        self.name_route(name, path);
+        self.add_route(path, method, handler);
         self
     }
-}
 
-struct RouterInner {
-    manifest_json: Option<Arc<str>>,
-    middlewares: Vec<BoxMiddleware>,
-    route_index: Arc<[RouteInfo]>,
-    routes: HashMap<Method, PathRouter<RouteEntry>>,
-    state_extensions: Extensions,
-}
+    /// [`Self::route`], answering `504 Gateway Timeout` if the handler has
+    /// not responded within `timeout`. The timeout also caps the request's
+    /// [`Deadline`](crate::deadline::Deadline), so outbound proxy calls made
+    /// by the handler give up in time. See [`crate::deadline`].
+    #[must_use]
+    #[inline]
+    pub fn route_with_timeout<H>(
+        mut self,
+        path: &str,
+        method: Method,
+        handler: H,
+        timeout: Duration,
+    ) -> Self
+    where
+        H: IntoHandler,
+    {
+        let timed = RouteTimeout::new(handler.into_handler(), timeout);
+        self.add_route(path, method, timed);
+        self
+    }
 
-impl RouterInner {
-    async fn dispatch(&self, mut request: Request) -> Result<Response, EdgeError> {
-        let method = request.method().clone();
-        let path = request.uri().path().to_owned();
+    fn try_add_route<H>(&mut self, path: &str, method: Method, handler: H) -> Result<(), RouteError>
+    where
+        H: IntoHandler,
+    {
+        self.insert_route(path, method, handler.into_handler())
+    }
 
-        match self.find_route(&method, &path) {
+    /// Check that every route was registered, [`Self::build`] the router,
+    /// then make it [`RouterService::ready`] by running the
+    /// [`Self::middleware_async`] setups.
+    ///
+    /// # Errors
+    /// Returns [`RouterBuildError::Route`] for the first route that could
+    /// not be registered, e.g. `/users/{name}` after `/users/{id}`, and
+    /// [`RouterBuildError::TooManyRoutes`], [`RouterBuildError::PathTooLong`]
+    /// or [`RouterBuildError::TooManyParams`] if the table breaks the
+    /// [`Self::with_route_limits`], before any setup runs. Returns
+    /// [`RouterBuildError::Middleware`] if a setup fails.
+    #[inline]
+    pub async fn try_build(mut self) -> Result<RouterService, RouterBuildError> {
+        self.check_routes()?;
+        self.build().ready().await
+    }
+
+    /// [`Self::route`] for code that assembles routes at runtime, e.g. by
+    /// merging route sets: a clash is returned instead of panicking.
+    ///
+    /// # Errors
+    /// Returns [`RouteError::Conflict`] if `path` clashes with a route
+    /// already registered for `method`, and [`RouteError::InvalidPath`] if
+    /// `path` is not a valid route template.
+    #[inline]
+    pub fn try_route<H>(
+        mut self,
+        path: &str,
+        method: Method,
+        handler: H,
+    ) -> Result<Self, RouteError>
+    where
+        H: IntoHandler,
+    {
+        self.try_add_route(path, method, handler)?;
+        Ok(self)
+    }
+
+    /// Buffer bodies for the extractors with `buffering`, as
+    /// `body-spill-bytes` under `[app]` in `edgezero.toml` does. See
+    /// [`crate::body_spool`].
+    #[must_use]
+    #[inline]
+    pub const fn with_body_buffering(mut self, buffering: BodyBuffering) -> Self {
+        self.body_buffering = buffering;
+        self
+    }
+
+    /// Cap request bodies at `limit` instead of the adapter's default, as
+    /// `max-body-bytes` under `[app]` in `edgezero.toml` does. See
+    /// [`crate::body_limit`].
+    #[must_use]
+    #[inline]
+    pub fn with_body_limit(mut self, limit: BodyLimit) -> Self {
+        self.body_limit = Some(limit);
+        self
+    }
+
+    /// Serve [`RequestContext::now`] from `clock` instead of the system
+    /// clock, e.g. a [`FixedClock`] in tests. Stored like
+    /// [`Self::with_state`] state.
+    ///
+    /// [`FixedClock`]: crate::clock::FixedClock
+    #[must_use]
+    #[inline]
+    pub fn with_clock<C>(self, clock: C) -> Self
+    where
+        C: Clock + 'static,
+    {
+        self.with_state(SharedClock::new(clock))
+    }
+
+    /// Lay out JSON written through [`JsonFormat`] for every route, including
+    /// the route listing. Stored like [`Self::with_state`] state.
+    #[must_use]
+    #[inline]
+    pub fn with_json_format(self, format: JsonFormat) -> Self {
+        self.with_state(format)
+    }
+
+    /// Enforce `limits` in the [`Json`] and [`ValidatedJson`] extractors for
+    /// every route. Stored like [`Self::with_state`] state.
+    ///
+    /// [`Json`]: crate::extractor::Json
+    /// [`ValidatedJson`]: crate::extractor::ValidatedJson
+    #[must_use]
+    #[inline]
+    pub fn with_json_limits(self, limits: JsonLimits) -> Self {
+        self.with_state(limits)
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn with_manifest_json<S: Into<Arc<str>>>(mut self, json: S) -> Self {
+        self.manifest_json = Some(json.into());
+        self
+    }
+
+    /// Register the standard observability middleware: a `Server-Timing`
+    /// header, an `X-Request-Id` for every request and a structured log
+    /// record per request, in that order. Call it before other middleware
+    /// so that they run inside it. See [`crate::observability`].
+    #[must_use]
+    #[inline]
+    pub fn with_observability(self) -> Self {
+        self.observability(Observability::default())
+    }
+
+    /// Run `interceptor` on every outbound request of the adapter's
+    /// [`ProxyHandle`], after the handle's own interceptors and those
+    /// registered earlier. Stored like [`Self::with_state`] state and applied
+    /// wherever handlers reach the handle: [`RequestContext::proxy_handle`],
+    /// the [`Proxy`] extractor and [`RequestContext::proxy_to`].
+    ///
+    /// [`ProxyHandle`]: crate::proxy::ProxyHandle
+    /// [`Proxy`]: crate::proxy::Proxy
+    #[must_use]
+    #[inline]
+    pub fn with_proxy_request_interceptor<I>(mut self, interceptor: I) -> Self
+    where
+        I: ProxyRequestInterceptor + 'static,
+    {
+        ProxyInterceptors::add_request(&mut self.state_extensions, Arc::new(interceptor));
+        self
+    }
+
+    /// Run `interceptor` on every response the adapter's [`ProxyHandle`]
+    /// receives, like [`Self::with_proxy_request_interceptor`]. Not run when
+    /// the send fails.
+    ///
+    /// [`ProxyHandle`]: crate::proxy::ProxyHandle
+    #[must_use]
+    #[inline]
+    pub fn with_proxy_response_interceptor<I>(mut self, interceptor: I) -> Self
+    where
+        I: ProxyResponseInterceptor + 'static,
+    {
+        ProxyInterceptors::add_response(&mut self.state_extensions, Arc::new(interceptor));
+        self
+    }
+
+    /// Cap the query strings the [`Query`] and [`ValidatedQuery`] extractors
+    /// parse for every route, replacing the default [`QueryLimit`]. Stored
+    /// like [`Self::with_state`] state.
+    ///
+    /// [`Query`]: crate::extractor::Query
+    /// [`ValidatedQuery`]: crate::extractor::ValidatedQuery
+    #[must_use]
+    #[inline]
+    pub fn with_query_limit(self, limit: QueryLimit) -> Self {
+        self.with_state(limit)
+    }
+
+    /// Generate the ids [`RequestContext::generate_request_id`] returns with
+    /// `generator` instead of [`gen_request_id`], e.g. a [`FixedRequestId`]
+    /// in tests. Stored like [`Self::with_state`] state.
+    ///
+    /// [`gen_request_id`]: crate::request_id::gen_request_id
+    /// [`FixedRequestId`]: crate::request_id::FixedRequestId
+    #[must_use]
+    #[inline]
+    pub fn with_request_id_generator<G>(self, generator: G) -> Self
+    where
+        G: RequestIdGenerator + 'static,
+    {
+        self.with_state(SharedRequestIdGenerator::new(generator))
+    }
+
+    /// Cap the number of routes, the length of their patterns and their
+    /// parameters, so [`Self::build`] panics and [`Self::try_build`] fails
+    /// past `limits`. See [`RouteLimits`].
+    #[must_use]
+    #[inline]
+    pub const fn with_route_limits(mut self, limits: RouteLimits) -> Self {
+        self.route_limits = Some(limits);
+        self
+    }
+
+    /// Register typed app settings for the [`Settings<T>`] extractor.
+    /// Shared behind an `Arc`, so `T` need not be `Clone`.
+    ///
+    /// [`Settings<T>`]: crate::extractor::Settings
+    #[must_use]
+    #[inline]
+    pub fn with_settings<T>(self, settings: T) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        self.with_state(Settings(Arc::new(settings)))
+    }
+
+    /// Register a value cloned into every request's extensions before
+    /// dispatch, making it available to the [`State<T>`] extractor and to
+    /// `RequestContext`-based handlers.
+    ///
+    /// Typically `T = Arc<AppState>`. Registering the same `T` twice is
+    /// last-write-wins. Cost is one `T::clone` (an `Arc` bump for
+    /// `Arc<AppState>`) per registered state per request.
+    ///
+    /// [`State<T>`]: crate::extractor::State
+    #[must_use]
+    #[inline]
+    pub fn with_state<T>(mut self, value: T) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.state_extensions.insert(value);
+        self
+    }
+
+    /// Trust forwarded headers from `proxies` in the [`ClientIp`],
+    /// [`ForwardedHost`] and [`Scheme`] extractors for every route. Stored
+    /// like [`Self::with_state`] state.
+    ///
+    /// [`ClientIp`]: crate::extractor::ClientIp
+    /// [`ForwardedHost`]: crate::extractor::ForwardedHost
+    /// [`Scheme`]: crate::extractor::Scheme
+    #[must_use]
+    #[inline]
+    pub fn with_trusted_proxies(self, proxies: TrustedProxies) -> Self {
+        self.with_state(proxies)
+    }
+}
+
+/// Service a [`RouterService`] hands unmatched requests to.
+#[derive(Clone)]
+struct Fallback {
+    call: Arc<dyn Fn(Request) -> HandlerFuture + Send + Sync>,
+    on: Fallthrough,
+}
+
+/// Which unmatched requests [`RouterService::or_else_when`] delegates to its
+/// fallback.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Fallthrough {
+    /// Only requests whose path matches no route.
+    NotFound,
+    /// Also requests whose path matches a route registered for other
+    /// methods, which would otherwise be answered with a 405.
+    NotFoundOrMethodNotAllowed,
+}
+
+#[derive(Clone)]
+struct RouterInner {
+    after: Vec<BoxAfterMiddleware>,
+    body_buffering: BodyBuffering,
+    /// Overrides the adapter's [`BodyLimit`] when set.
+    body_limit: Option<BodyLimit>,
+    builtin_paths: Vec<Arc<str>>,
+    error_hooks: Vec<BoxErrorHook>,
+    fallback: Option<Fallback>,
+    manifest_json: Option<Arc<str>>,
+    middleware_names: Vec<String>,
+    middlewares: Vec<BoxMiddleware>,
+    mounts: Vec<Mount>,
+    pending_middlewares: Vec<(usize, MiddlewareSetup)>,
+    route_index: Arc<[RouteInfo]>,
+    route_listing_access: Option<RouteListingAccess>,
+    route_names: HashMap<String, Arc<str>>,
+    routes: HashMap<Method, PathRouter<RouteEntry>>,
+    state_extensions: Extensions,
+    unknown_middleware: Vec<String>,
+}
+
+impl RouterInner {
+    async fn dispatch(&self, mut request: Request) -> Result<Response, EdgeError> {
+        self.ensure_ready()?;
+        let body_limit = self
+            .body_limit
+            .or_else(|| request.extensions().get::<BodyLimit>().copied());
+        if let Some(limit) = body_limit {
+            limit.enforce(&mut request)?;
+        }
+        let method = request.method().clone();
+        let mounted_at = request.extensions().get::<MountPrefix>().cloned();
+        let path = match &mounted_at {
+            Some(prefix) => strip_mount_prefix(request.uri().path(), prefix.as_str()),
+            None => Some(request.uri().path()),
+        }
+        .filter(|rest| !rest.is_empty())
+        .unwrap_or("/")
+        .to_owned();
+
+        match self.find_route(&method, &path) {
             RouteMatch::Found(entry, params) => {
                 // Inject only the introspection payloads this route asked for —
                 // nothing for the vast majority of routes that need none.
@@ -248,6 +1134,12 @@ impl RouterInner {
                         .extensions_mut()
                         .insert(RouteTable(Arc::clone(&self.route_index)));
                 }
+                request
+                    .extensions_mut()
+                    .insert(MatchedRoute(Arc::clone(&entry.template)));
+                // Read by `RequestContext::buffer_body` if the route extracts
+                // the body; nothing is read before then.
+                request.extensions_mut().insert(self.body_buffering);
                 // App-owned state registered via RouterBuilder::with_state.
                 // Runs after introspection inserts; `extend` overwrites by
                 // TypeId, so app state wins last-write on any collision.
@@ -255,43 +1147,226 @@ impl RouterInner {
                     .extensions_mut()
                     .extend(self.state_extensions.clone());
                 let ctx = RequestContext::new(request, params);
-                let next = Next::new(&self.middlewares, entry.handler.as_ref());
-                next.run(ctx).await
+                let result = self.run_chain(ctx, &path, entry.handler.as_ref()).await;
+                if method == Method::HEAD {
+                    // The handler (possibly a `GET` one) built the full
+                    // response; a lazy body is dropped without being polled.
+                    return result.map(head_response);
+                }
+                result
+            }
+            RouteMatch::MethodNotAllowed(allowed, template) => {
+                self.method_not_allowed(request, &path, &allowed, template)
+                    .await
             }
-            RouteMatch::MethodNotAllowed(mut allowed) => {
-                allowed.sort_by(|left, right| left.as_str().cmp(right.as_str()));
-                Err(EdgeError::method_not_allowed(&method, &allowed))
+            RouteMatch::InvalidParam(name) => self.invalid_param(request, &path, name).await,
+            RouteMatch::NotFound => {
+                if let Some(mount) = self.find_mount(&path) {
+                    let prefix = match &mounted_at {
+                        Some(outer) => format!("{}{}", outer.as_str(), mount.prefix),
+                        None => mount.prefix.to_string(),
+                    };
+                    request
+                        .extensions_mut()
+                        .insert(MountPrefix(Arc::from(prefix)));
+                    request
+                        .extensions_mut()
+                        .extend(self.state_extensions.clone());
+                    let mounted = mount.router.clone();
+                    let forward = move |ctx: RequestContext| {
+                        let router = mounted.clone();
+                        async move { router.oneshot(ctx.into_request()).await }
+                    };
+                    let ctx = RequestContext::new(request, PathParams::default());
+                    return self.run_chain(ctx, &path, &forward).await;
+                }
+                match &self.fallback {
+                    Some(fallback) => (fallback.call)(request).await,
+                    None => Err(EdgeError::not_found(request.uri().path())),
+                }
             }
-            RouteMatch::NotFound => Err(EdgeError::not_found(path)),
         }
     }
 
+    /// Refuse to serve while [`RouterBuilder::middleware_async`] setups
+    /// have not run.
+    fn ensure_ready(&self) -> Result<(), EdgeError> {
+        if self.pending_middlewares.is_empty() {
+            return Ok(());
+        }
+        Err(EdgeError::internal(anyhow::anyhow!(
+            "middleware registered with `middleware_async` is not set up; make the router ready with `RouterService::ready` before serving"
+        )))
+    }
+
+    /// The mount with the longest prefix covering `path`, if any.
+    fn find_mount(&self, path: &str) -> Option<&Mount> {
+        self.mounts
+            .iter()
+            .filter(|mount| strip_mount_prefix(path, &mount.prefix).is_some())
+            .max_by_key(|mount| mount.prefix.len())
+    }
+
+    /// The route for `method` and `path`. A `HEAD` request for a path with
+    /// only a `GET` route is matched to that route; dispatch drops the body.
     fn find_route(&self, method: &Method, path: &str) -> RouteMatch<'_> {
-        if let Some(router) = self.routes.get(method)
-            && let Ok(matched) = router.at(path)
-        {
-            let params = PathParams::new(
-                matched
-                    .params
-                    .iter()
-                    .map(|(key, value)| (key.to_owned(), value.to_owned()))
-                    .collect(),
-            );
-            return RouteMatch::Found(matched.value, params);
+        let lookup = |candidate: &Method| self.routes.get(candidate)?.at(path).ok();
+        let direct = lookup(method).or_else(|| {
+            if *method == Method::HEAD {
+                lookup(&Method::GET)
+            } else {
+                None
+            }
+        });
+        if let Some(matched) = direct {
+            let params = matched
+                .params
+                .iter()
+                .map(|(key, value)| {
+                    decode_path_param(value)
+                        .map(|decoded| (key.to_owned(), decoded))
+                        .ok_or_else(|| key.to_owned())
+                })
+                .collect();
+            return match params {
+                Ok(decoded) => RouteMatch::Found(matched.value, PathParams::new(decoded)),
+                Err(name) => RouteMatch::InvalidParam(name),
+            };
         }
 
-        let allowed: HashSet<Method> = self
+        let mut candidates: Vec<(&Method, &RouteEntry)> = self
             .routes
             .iter()
-            .filter(|(_, router)| router.at(path).is_ok())
-            .map(|(candidate_method, _)| candidate_method.clone())
+            .filter_map(|(candidate_method, router)| {
+                router
+                    .at(path)
+                    .ok()
+                    .map(|matched| (candidate_method, matched.value))
+            })
+            .collect();
+        candidates.sort_by(|left, right| left.0.as_str().cmp(right.0.as_str()));
+
+        let Some(&(_, entry)) = candidates.first() else {
+            return RouteMatch::NotFound;
+        };
+        let mut allowed: Vec<Method> = candidates
+            .iter()
+            .map(|&(candidate_method, _)| candidate_method.clone())
             .collect();
+        // `HEAD` is answered by the `GET` route, so it is allowed too.
+        if allowed.contains(&Method::GET) && !allowed.contains(&Method::HEAD) {
+            allowed.push(Method::HEAD);
+            allowed.sort_by(|left, right| left.as_str().cmp(right.as_str()));
+        }
+        RouteMatch::MethodNotAllowed(allowed, Arc::clone(&entry.template))
+    }
+
+    /// Reject a request whose path param `name` does not decode with
+    /// `400 Bad Request`, behind the middleware chain and error hooks like
+    /// a handler's error.
+    async fn invalid_param(
+        &self,
+        mut request: Request,
+        path: &str,
+        name: String,
+    ) -> Result<Response, EdgeError> {
+        request
+            .extensions_mut()
+            .extend(self.state_extensions.clone());
+        let reject = move |_ctx: RequestContext| {
+            let err = EdgeError::bad_request(format!(
+                "path parameter `{name}` is not valid UTF-8 once percent-decoded"
+            ));
+            async move { Err::<Response, _>(err) }
+        };
+        let ctx = RequestContext::new(request, PathParams::default());
+        self.run_chain(ctx, path, &reject).await
+    }
+
+    /// Whether the `method` route registered as `template` is an endpoint the
+    /// framework provides: an introspection handler, or one registered by
+    /// [`RouterBuilder::enable_metrics_at`] and the like.
+    fn is_builtin(&self, method: &Method, template: &str) -> bool {
+        self.builtin_paths.iter().any(|path| **path == *template)
+            || self
+                .routes
+                .get(method)
+                .and_then(|router| router.at(template).ok())
+                .is_some_and(|matched| {
+                    *matched.value.template == *template && matched.value.introspection_needs.any()
+                })
+    }
+
+    /// Whether `path` is served by the route listing for some method.
+    fn is_route_listing(&self, path: &str) -> bool {
+        self.routes.values().any(|router| {
+            router
+                .at(path)
+                .is_ok_and(|matched| matched.value.introspection_needs.routes)
+        })
+    }
 
-        if allowed.is_empty() {
-            RouteMatch::NotFound
-        } else {
-            RouteMatch::MethodNotAllowed(allowed.into_iter().collect())
+    /// Answer a request whose path matched a route registered only for
+    /// other methods: the [`Fallthrough::NotFoundOrMethodNotAllowed`]
+    /// fallback, or a 405 returned without running the middleware chain.
+    /// `OPTIONS` still runs the chain so middleware such as `Cors` can
+    /// answer preflights; the 405 stands if none does.
+    async fn method_not_allowed(
+        &self,
+        mut request: Request,
+        path: &str,
+        allowed: &[Method],
+        template: Arc<str>,
+    ) -> Result<Response, EdgeError> {
+        if let Some(fallback) = self
+            .fallback
+            .as_ref()
+            .filter(|fallback| fallback.on == Fallthrough::NotFoundOrMethodNotAllowed)
+        {
+            return (fallback.call)(request).await;
+        }
+        let method = request.method().clone();
+        if method != Method::OPTIONS {
+            return Err(EdgeError::method_not_allowed_for(
+                &method, allowed, &template,
+            ));
+        }
+        request
+            .extensions_mut()
+            .insert(MatchedRoute(Arc::clone(&template)));
+        request
+            .extensions_mut()
+            .extend(self.state_extensions.clone());
+        let methods = allowed.to_vec();
+        let reject = move |_ctx: RequestContext| {
+            let err = EdgeError::method_not_allowed_for(&method, &methods, &template);
+            async move { Err::<Response, _>(err) }
+        };
+        let ctx = RequestContext::new(request, PathParams::default());
+        self.run_chain(ctx, path, &reject).await
+    }
+
+    /// Run `handler` behind the middleware chain, and behind the
+    /// [`RouteListingAccess`] policy as well when `path` is the route listing.
+    /// An error is reported to the [`RouterBuilder::on_error`] hooks.
+    async fn run_chain(
+        &self,
+        ctx: RequestContext,
+        path: &str,
+        handler: &dyn DynHandler,
+    ) -> Result<Response, EdgeError> {
+        let dispatched = (!self.error_hooks.is_empty()).then(|| ctx.without_body());
+        let next = Next::new(&self.middlewares, handler).with_error_hooks(&self.error_hooks);
+        let result = match &self.route_listing_access {
+            Some(access) if self.is_route_listing(path) => access.handle(ctx, next).await,
+            Some(_) | None => next.run(ctx).await,
+        };
+        if let (Err(err), Some(failed)) = (&result, &dispatched) {
+            for hook in &self.error_hooks {
+                hook.on_error(err, failed);
+            }
         }
+        result
     }
 }
 
@@ -324,7 +1399,74 @@ impl RouterService {
         RouterBuilder::new()
     }
 
+    /// Whether a `method` request for the concrete `path` would reach a
+    /// handler, matched the same way as on dispatch (mounted routers
+    /// included) but without running anything. A path registered only for
+    /// other methods, or one whose parameters fail to decode, does not; `HEAD`
+    /// also matches `GET` routes.
+    ///
+    /// ```rust,ignore
+    /// assert!(router.has_route(&Method::GET, "/users/42"));
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn has_route(&self, method: &Method, requested: &str) -> bool {
+        let path = root_if_empty(requested);
+        match self.inner.find_route(method, path) {
+            RouteMatch::Found(..) => true,
+            RouteMatch::NotFound => self.inner.find_mount(path).is_some_and(|mount| {
+                let rest = strip_mount_prefix(path, &mount.prefix)
+                    .filter(|rest| !rest.is_empty())
+                    .unwrap_or("/");
+                mount.router.has_route(method, rest)
+            }),
+            RouteMatch::InvalidParam(_) | RouteMatch::MethodNotAllowed(..) => false,
+        }
+    }
+
+    /// Add `hook` after any registered on the builder. Used by
+    /// [`crate::app::App::on_error`].
+    pub(crate) fn layer_error_hook(mut self, hook: BoxErrorHook) -> Self {
+        Arc::make_mut(&mut self.inner).error_hooks.push(hook);
+        self
+    }
+
+    /// Wrap every route in `middleware`, outside any middleware registered
+    /// on the builder. Used by [`crate::app::App::with_middleware`].
+    pub(crate) fn layer_middleware(mut self, middleware: BoxMiddleware) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.middlewares.insert(0, middleware);
+        for (position, _) in &mut inner.pending_middlewares {
+            *position = position.saturating_add(1);
+        }
+        self
+    }
+
+    /// [`Self::layer_middleware`] for a [`RouterBuilder::middleware_async`]
+    /// setup, run by [`Self::ready`].
+    pub(crate) fn layer_middleware_async(mut self, setup: MiddlewareSetup) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        for (position, _) in &mut inner.pending_middlewares {
+            *position = position.saturating_add(1);
+        }
+        inner.pending_middlewares.push((0, setup));
+        self
+    }
+
+    /// The manifest JSON registered with [`RouterBuilder::with_manifest_json`].
+    pub(crate) fn manifest_json(&self) -> Option<&str> {
+        self.inner.manifest_json.as_deref()
+    }
+
+    /// Names of the middleware [`RouterBuilder::named_middleware`] and
+    /// [`RouterBuilder::named_middleware_or_report`] applied.
+    pub(crate) fn middleware_names(&self) -> &[String] {
+        &self.inner.middleware_names
+    }
+
     fn new(
+        after: Vec<BoxAfterMiddleware>,
+        route_names: HashMap<String, Arc<str>>,
         routes: HashMap<Method, PathRouter<RouteEntry>>,
         middlewares: Vec<BoxMiddleware>,
         route_index: Arc<[RouteInfo]>,
@@ -333,25 +1475,117 @@ impl RouterService {
     ) -> Self {
         Self {
             inner: Arc::new(RouterInner {
+                after,
+                body_buffering: BodyBuffering::Memory,
+                body_limit: None,
+                builtin_paths: Vec::new(),
+                error_hooks: Vec::new(),
+                fallback: None,
                 manifest_json,
+                middleware_names: Vec::new(),
                 middlewares,
+                mounts: Vec::new(),
+                pending_middlewares: Vec::new(),
                 route_index,
+                route_listing_access: None,
+                route_names,
                 routes,
                 state_extensions,
+                unknown_middleware: Vec::new(),
             }),
         }
     }
 
+    /// Dispatch `request`, render an error as its response, and run the
+    /// [`RouterBuilder::after`] hooks on the result. Unlike calling the
+    /// [`Service`] directly, this always yields the response a client sees.
+    ///
     /// # Errors
     /// Returns [`EdgeError`] if the dispatched handler errors AND the error
     /// itself fails to render as a response.
     #[inline]
     pub async fn oneshot(&self, request: Request) -> Result<Response, EdgeError> {
         let mut service = self.clone();
-        match service.call(request).await {
-            Ok(response) => Ok(response),
-            Err(err) => err.into_response(),
+        let mut response = match service.call(request).await {
+            Ok(response) => response,
+            Err(err) => err.into_response()?,
+        };
+        for hook in &self.inner.after {
+            hook.after(&mut response);
+        }
+        Ok(response)
+    }
+
+    /// Hand requests whose path matches no route to `fallback` instead of
+    /// answering 404, e.g. to put a proxy behind the app's own routes. A
+    /// path matched for other methods still gets its 405, and handler
+    /// errors are returned as-is; see [`Self::or_else_when`] to delegate
+    /// 405s too. Replaces any fallback set earlier.
+    ///
+    /// ```rust,ignore
+    /// let gateway = app_router.or_else(proxy_router);
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn or_else<S>(self, fallback: S) -> Self
+    where
+        S: Service<Request, Response = Response, Error = EdgeError> + Clone + Send + Sync + 'static,
+        S::Future: 'static,
+    {
+        self.or_else_when(fallback, Fallthrough::NotFound)
+    }
+
+    /// [`Self::or_else`], delegating the unmatched requests selected by `on`.
+    #[must_use]
+    #[inline]
+    pub fn or_else_when<S>(mut self, fallback: S, on: Fallthrough) -> Self
+    where
+        S: Service<Request, Response = Response, Error = EdgeError> + Clone + Send + Sync + 'static,
+        S::Future: 'static,
+    {
+        let call = move |request: Request| -> HandlerFuture {
+            let mut service = fallback.clone();
+            Box::pin(async move {
+                poll_fn(|cx| service.poll_ready(cx)).await?;
+                service.call(request).await
+            })
+        };
+        Arc::make_mut(&mut self.inner).fallback = Some(Fallback {
+            call: Arc::new(call),
+            on,
+        });
+        self
+    }
+
+    /// Run the [`RouterBuilder::middleware_async`] setups, in registration
+    /// order, and add the middleware they return in its place. Adapters'
+    /// `run_app` calls this, through [`App::ready`], before serving; until
+    /// then a router with pending setups answers every request with an
+    /// error.
+    ///
+    /// # Errors
+    /// Returns [`RouterBuildError::Middleware`] for the first setup that
+    /// fails; later setups do not run.
+    ///
+    /// [`App::ready`]: crate::app::App::ready
+    #[inline]
+    pub async fn ready(mut self) -> Result<Self, RouterBuildError> {
+        if self.inner.pending_middlewares.is_empty() {
+            return Ok(self);
+        }
+        let mut resolved = Vec::with_capacity(self.inner.pending_middlewares.len());
+        for (position, setup) in &self.inner.pending_middlewares {
+            resolved.push((*position, setup().await?));
+        }
+        // Setups run in registration order; inserting by final position
+        // keeps every earlier slot filled.
+        resolved.sort_by_key(|(position, _)| *position);
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.pending_middlewares.clear();
+        for (position, middleware) in resolved {
+            inner.middlewares.insert(position, middleware);
         }
+        Ok(self)
     }
 
     #[must_use]
@@ -359,21 +1593,360 @@ impl RouterService {
     pub fn routes(&self) -> Vec<RouteInfo> {
         self.inner.route_index.to_vec()
     }
-}
 
-#[cfg(test)]
-mod tests {
-    /// Per-capability introspection injection: a route receives exactly the
-    /// payloads its handler opted into via `#[action(manifest|routes)]`.
-    mod introspection_gating {
-        use super::*;
-        use crate::handler::DynHandler;
+    /// Whether the router can answer anything but 404: it has a route, a
+    /// mount, or a fallback.
+    pub(crate) fn serves_anything(&self) -> bool {
+        !self.inner.route_index.is_empty()
+            || !self.inner.mounts.is_empty()
+            || self.inner.fallback.is_some()
+    }
 
-        /// A handler that records which introspection payloads its request
-        /// carried, as `(manifest_present, routes_present)`, and reports `needs`.
-        struct CapProbe {
-            needs: IntrospectionNeeds,
-            seen: Arc<Mutex<Option<(bool, bool)>>>,
+    /// Pairs of a built-in endpoint (the route listing, manifest, metrics, or
+    /// request debug endpoint) and another route for the same method whose
+    /// template also matches the endpoint's path. The endpoint wins, so that
+    /// route never sees requests for it.
+    pub(crate) fn shadowed_routes(&self) -> Vec<(RouteInfo, RouteInfo)> {
+        let inner = &self.inner;
+        let mut shadowed = Vec::new();
+        for endpoint in inner
+            .route_index
+            .iter()
+            .filter(|route| inner.is_builtin(&route.method, &route.path))
+        {
+            for route in inner
+                .route_index
+                .iter()
+                .filter(|route| route.method == endpoint.method && route.path != endpoint.path)
+            {
+                let mut probe = PathRouter::new();
+                if probe.insert(route.path.as_str(), ()).is_ok() && probe.at(&endpoint.path).is_ok()
+                {
+                    shadowed.push((endpoint.clone(), route.clone()));
+                }
+            }
+        }
+        shadowed
+    }
+
+    /// Middleware names [`RouterBuilder::named_middleware_or_report`] found
+    /// nothing registered under.
+    pub(crate) fn unknown_middleware(&self) -> &[String] {
+        &self.inner.unknown_middleware
+    }
+
+    /// Build the path of the route registered as `name`, substituting
+    /// `params` for its `{param}` and `{*catch_all}` segments. Values are
+    /// percent-encoded; a catch-all keeps its `/` separators. Params the
+    /// template does not use are ignored.
+    ///
+    /// ```rust,ignore
+    /// let router = RouterService::builder()
+    ///     .get_named("user", "/users/{id}", show_user)
+    ///     .build();
+    /// assert_eq!(router.url_for("user", &[("id", "42")])?, "/users/42");
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`UrlForError::UnknownRoute`] if no route is named `name`,
+    /// and [`UrlForError::MissingParam`] if `params` lacks a value the
+    /// template needs.
+    #[inline]
+    pub fn url_for(&self, name: &str, params: &[(&str, &str)]) -> Result<String, UrlForError> {
+        let template =
+            self.inner
+                .route_names
+                .get(name)
+                .ok_or_else(|| UrlForError::UnknownRoute {
+                    name: name.to_owned(),
+                })?;
+        fill_template(name, template, params)
+    }
+
+    /// Add an app-wide proxy request interceptor to an already built router,
+    /// as [`RouterBuilder::with_proxy_request_interceptor`] does.
+    pub(crate) fn with_proxy_request_interceptor(
+        mut self,
+        interceptor: Arc<dyn ProxyRequestInterceptor>,
+    ) -> Self {
+        ProxyInterceptors::add_request(
+            &mut Arc::make_mut(&mut self.inner).state_extensions,
+            interceptor,
+        );
+        self
+    }
+
+    /// Add an app-wide proxy response interceptor to an already built
+    /// router, as [`RouterBuilder::with_proxy_response_interceptor`] does.
+    pub(crate) fn with_proxy_response_interceptor(
+        mut self,
+        interceptor: Arc<dyn ProxyResponseInterceptor>,
+    ) -> Self {
+        ProxyInterceptors::add_response(
+            &mut Arc::make_mut(&mut self.inner).state_extensions,
+            interceptor,
+        );
+        self
+    }
+
+    /// Add app state to an already built router, with the same semantics as
+    /// [`RouterBuilder::with_state`].
+    pub(crate) fn with_state<T>(mut self, value: T) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.inner)
+            .state_extensions
+            .insert(value);
+        self
+    }
+}
+
+/// Why [`RouterBuilder::try_route`] could not register a route.
+#[derive(Debug, Eq, Error, PartialEq)]
+#[non_exhaustive]
+pub enum RouteError {
+    /// The path overlaps a route already registered for the same method:
+    /// it is the same pattern, or it puts a parameter or catch-all where
+    /// the existing route has a differently named one.
+    #[error(
+        "{method} {path} conflicts with existing route {existing}: {}",
+        conflict_reason(path, existing)
+    )]
+    Conflict {
+        existing: String,
+        method: Method,
+        path: String,
+    },
+    /// The path is not a valid route template.
+    #[error("invalid route path {path}: {message}")]
+    InvalidPath { message: String, path: String },
+}
+
+impl RouteError {
+    fn from_insert(method: &Method, path: &str, err: InsertError) -> Self {
+        if let InsertError::Conflict { with } = err {
+            return RouteError::Conflict {
+                existing: with,
+                method: method.clone(),
+                path: path.to_owned(),
+            };
+        }
+        RouteError::InvalidPath {
+            message: err.to_string(),
+            path: path.to_owned(),
+        }
+    }
+}
+
+/// Why [`RouterBuilder::try_build`] or [`RouterService::ready`] could not
+/// build the router.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum RouterBuildError {
+    /// A [`RouterBuilder::middleware_async`] setup failed with `source`.
+    #[error("setup of middleware `{middleware}` failed: {source}")]
+    Middleware {
+        middleware: &'static str,
+        source: EdgeError,
+    },
+    /// A pattern is longer than [`RouteLimits::max_path_length`].
+    #[error("route {path} is {length} bytes long, over the limit of {max}")]
+    PathTooLong {
+        length: usize,
+        max: usize,
+        path: String,
+    },
+    /// A route registered through one of the panicking methods, such as
+    /// [`RouterBuilder::get`], could not be added.
+    #[error(transparent)]
+    Route(#[from] RouteError),
+    /// A pattern has more parameters than [`RouteLimits::max_params`].
+    #[error("route {path} has {count} parameters, over the limit of {max}")]
+    TooManyParams {
+        count: usize,
+        max: usize,
+        path: String,
+    },
+    /// The table has more routes than [`RouteLimits::max_routes`].
+    #[error("the router has {count} routes, over the limit of {max}")]
+    TooManyRoutes { count: usize, max: usize },
+}
+
+impl From<RouterBuildError> for EdgeError {
+    #[inline]
+    fn from(err: RouterBuildError) -> Self {
+        match err {
+            RouterBuildError::Middleware { source, .. } => source,
+            other @ (RouterBuildError::PathTooLong { .. }
+            | RouterBuildError::Route(_)
+            | RouterBuildError::TooManyParams { .. }
+            | RouterBuildError::TooManyRoutes { .. }) => EdgeError::internal(other),
+        }
+    }
+}
+
+/// Why [`RouterService::url_for`] could not build a URL.
+#[derive(Debug, Eq, Error, PartialEq)]
+#[non_exhaustive]
+pub enum UrlForError {
+    /// The route template has a parameter with no value in `params`.
+    #[error("route `{route}` needs a value for `{param}`")]
+    MissingParam { param: String, route: String },
+    /// No route was registered under the name.
+    #[error("no route named `{name}`")]
+    UnknownRoute { name: String },
+}
+
+/// Explains a matchit conflict between `path` and the `existing` route.
+fn conflict_reason(path: &str, existing: &str) -> &'static str {
+    if path == existing {
+        "the pattern is registered twice"
+    } else {
+        "both capture the same segment under different names, so a request could match either"
+    }
+}
+
+/// `response` without its body, as sent for a `HEAD` request. A buffered
+/// body's length is kept as the `Content-Length` a `GET` would have sent; a
+/// stream of unknown length becomes an empty stream, so adapters do not
+/// declare a length of zero.
+fn head_response(mut response: Response) -> Response {
+    let (lower, upper) = response.body().size_hint();
+    if upper != Some(lower) {
+        *response.body_mut() = Body::stream(stream::empty());
+        return response;
+    }
+    let status = response.status();
+    if !status.is_informational()
+        && status != StatusCode::NO_CONTENT
+        && status != StatusCode::NOT_MODIFIED
+    {
+        response
+            .headers_mut()
+            .entry(CONTENT_LENGTH)
+            .or_insert_with(|| HeaderValue::from(lower));
+    }
+    *response.body_mut() = Body::empty();
+    response
+}
+
+/// Box a [`RouterBuilder::middleware_async`] setup, naming the middleware
+/// type in its error.
+pub(crate) fn middleware_setup<F, Fut, M>(setup: F) -> MiddlewareSetup
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<M, EdgeError>> + 'static,
+    M: Middleware,
+{
+    Arc::new(move || {
+        let pending = setup();
+        async move {
+            let middleware = pending
+                .await
+                .map_err(|source| RouterBuildError::Middleware {
+                    middleware: type_name::<M>(),
+                    source,
+                })?;
+            Ok::<BoxMiddleware, RouterBuildError>(Arc::new(middleware))
+        }
+        .boxed_local()
+    })
+}
+
+/// Parameters and catch-alls in a matchit route template, skipping the
+/// `{{` escape for a literal brace.
+fn param_count(template: &str) -> usize {
+    let mut count = 0_usize;
+    let mut chars = template.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '{' && chars.next_if_eq(&'{').is_none() {
+            count = count.saturating_add(1);
+        }
+    }
+    count
+}
+
+/// `path`, or `/` when it is empty: requests never have an empty path, so
+/// an empty route means the root.
+pub(crate) fn root_if_empty(path: &str) -> &str {
+    if path.is_empty() { "/" } else { path }
+}
+
+/// Substitute `params` into a matchit route template. `{{` and `}}` are
+/// matchit's escapes for literal braces.
+fn fill_template(
+    route: &str,
+    template: &str,
+    params: &[(&str, &str)],
+) -> Result<String, UrlForError> {
+    let mut url = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' if chars.next_if_eq(&'{').is_some() => url.push('{'),
+            '}' if chars.next_if_eq(&'}').is_some() => url.push('}'),
+            '{' => {
+                let segment: String = chars.by_ref().take_while(|next| *next != '}').collect();
+                let (param, catch_all) = segment
+                    .strip_prefix('*')
+                    .map_or((segment.as_str(), false), |rest| (rest, true));
+                let value = params
+                    .iter()
+                    .find_map(|(key, value)| (*key == param).then_some(*value))
+                    .ok_or_else(|| UrlForError::MissingParam {
+                        param: param.to_owned(),
+                        route: route.to_owned(),
+                    })?;
+                push_encoded(&mut url, value, catch_all);
+            }
+            other => url.push(other),
+        }
+    }
+    Ok(url)
+}
+
+/// Append `value` percent-encoded for a path segment: everything but
+/// RFC 3986 unreserved characters (and `/` when `keep_slash`) is escaped.
+fn push_encoded(url: &mut String, value: &str, keep_slash: bool) {
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric()
+            || matches!(byte, b'-' | b'.' | b'_' | b'~')
+            || (keep_slash && byte == b'/')
+        {
+            url.push(char::from(byte));
+            continue;
+        }
+        url.push('%');
+        for nibble in [byte >> 4_u8, byte & 0x0f] {
+            if let Some(digit) = char::from_digit(u32::from(nibble), 16) {
+                url.push(digit.to_ascii_uppercase());
+            }
+        }
+    }
+}
+
+/// `path` relative to the mount point `prefix`, or `None` if `prefix` does
+/// not cover it. `prefix` itself maps to an empty path, which callers treat
+/// as `/`.
+pub(crate) fn strip_mount_prefix<'path>(path: &'path str, prefix: &str) -> Option<&'path str> {
+    let rest = path.strip_prefix(prefix)?;
+    (rest.is_empty() || rest.starts_with('/')).then_some(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    /// Per-capability introspection injection: a route receives exactly the
+    /// payloads its handler opted into via `#[action(manifest|routes)]`.
+    mod introspection_gating {
+        use super::*;
+        use crate::handler::DynHandler;
+
+        /// A handler that records which introspection payloads its request
+        /// carried, as `(manifest_present, routes_present)`, and reports `needs`.
+        struct CapProbe {
+            needs: IntrospectionNeeds,
+            seen: Arc<Mutex<Option<(bool, bool)>>>,
         }
 
         impl DynHandler for CapProbe {
@@ -506,22 +2079,124 @@ mod tests {
     }
 
     use super::*;
+    use crate::app::App;
     use crate::body::Body;
     use crate::context::RequestContext;
     use crate::error::EdgeError;
-    use crate::http::{Method, Request, Response, StatusCode, request_builder};
+    use crate::extractor::{FromRequest as _, Json};
+    use crate::http::{
+        HeaderValue, Method, Request, Response, StatusCode, request_builder, response_builder,
+    };
+    use crate::introspection;
     use crate::params::PathParams;
     use crate::response::response_with_body;
     use futures::executor::block_on;
     use futures::task::noop_waker_ref;
     use serde::Deserialize;
+    use std::io;
     use std::sync::{Arc, Mutex};
     use std::task::{Context, Poll};
 
+    struct Tagging(&'static str);
+
+    #[async_trait::async_trait(?Send)]
+    impl Middleware for Tagging {
+        async fn handle(&self, ctx: RequestContext, next: Next<'_>) -> Result<Response, EdgeError> {
+            let mut response = next.run(ctx).await?;
+            response
+                .headers_mut()
+                .append("x-tag", HeaderValue::from_static(self.0));
+            Ok(response)
+        }
+    }
+
     async fn ok_handler(_ctx: RequestContext) -> Result<Response, EdgeError> {
         response_with_body(StatusCode::OK, Body::empty())
     }
 
+    #[test]
+    fn after_hooks_mutate_success_and_error_responses() {
+        use crate::http::HeaderValue;
+        use async_trait::async_trait;
+
+        struct TagResponse;
+
+        #[async_trait(?Send)]
+        impl Middleware for TagResponse {
+            async fn handle(
+                &self,
+                ctx: RequestContext,
+                next: Next<'_>,
+            ) -> Result<Response, EdgeError> {
+                let mut response = next.run(ctx).await?;
+                response
+                    .headers_mut()
+                    .insert("x-order", HeaderValue::from_static("middleware"));
+                Ok(response)
+            }
+        }
+
+        async fn failing(_ctx: RequestContext) -> Result<Response, EdgeError> {
+            Err(EdgeError::bad_request("nope"))
+        }
+
+        let service = RouterService::builder()
+            .get("/ok", ok_handler)
+            .get("/fail", failing)
+            .middleware(TagResponse)
+            .after(|response: &mut Response| {
+                response
+                    .headers_mut()
+                    .insert("x-frame-options", HeaderValue::from_static("DENY"));
+            })
+            .after(|response: &mut Response| {
+                response
+                    .headers_mut()
+                    .append("x-order", HeaderValue::from_static("after"));
+            })
+            .build();
+        let send = |path: &str| {
+            let request = request_builder()
+                .method(Method::GET)
+                .uri(path)
+                .body(Body::empty())
+                .expect("request");
+            block_on(service.oneshot(request)).expect("response")
+        };
+        let order = |response: &Response| {
+            response
+                .headers()
+                .get_all("x-order")
+                .iter()
+                .map(|value| value.to_str().expect("ascii").to_owned())
+                .collect::<Vec<_>>()
+        };
+
+        let ok = send("/ok");
+        assert_eq!(ok.status(), StatusCode::OK);
+        assert_eq!(ok.headers().get("x-frame-options").expect("hook"), "DENY");
+        assert_eq!(order(&ok), ["middleware", "after"]);
+
+        // The error is rendered first, so hooks see the 400 response and
+        // can touch the headers of the JSON error body.
+        let failed = send("/fail");
+        assert_eq!(failed.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            failed.headers().get("content-type").expect("json error"),
+            "application/json"
+        );
+        assert_eq!(
+            failed.headers().get("x-frame-options").expect("hook"),
+            "DENY"
+        );
+        assert_eq!(order(&failed), ["after"]);
+
+        // Unmatched paths never reach the middleware chain but still get hooks.
+        let missing = send("/missing");
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        assert_eq!(order(&missing), ["after"]);
+    }
+
     #[test]
     fn builder_accepts_middleware_and_middleware_arc() {
         struct RecordingMiddleware {
@@ -596,6 +2271,56 @@ mod tests {
         assert_eq!(delete_response.status(), StatusCode::OK);
     }
 
+    #[test]
+    fn default_response_headers_fill_gaps_without_overriding() {
+        use crate::http::{HeaderMap, HeaderValue};
+
+        async fn custom_server(_ctx: RequestContext) -> Result<Response, EdgeError> {
+            let mut response = response_with_body(StatusCode::OK, Body::empty())?;
+            response
+                .headers_mut()
+                .insert("server", HeaderValue::from_static("handler"));
+            Ok(response)
+        }
+
+        let mut defaults = HeaderMap::new();
+        defaults.insert("server", HeaderValue::from_static("edgezero"));
+        defaults.insert("x-app-version", HeaderValue::from_static("1.2.3"));
+        let service = RouterService::builder()
+            .get("/ok", ok_handler)
+            .get("/custom", custom_server)
+            .default_response_headers(defaults)
+            .build();
+        let send = |path: &str| {
+            let request = request_builder()
+                .method(Method::GET)
+                .uri(path)
+                .body(Body::empty())
+                .expect("request");
+            block_on(service.oneshot(request)).expect("response")
+        };
+
+        let ok = send("/ok");
+        assert_eq!(ok.headers().get("server").expect("server"), "edgezero");
+        assert_eq!(ok.headers().get("x-app-version").expect("version"), "1.2.3");
+
+        let custom = send("/custom");
+        assert_eq!(custom.headers().get_all("server").iter().count(), 1);
+        assert_eq!(custom.headers().get("server").expect("server"), "handler");
+        assert_eq!(
+            custom.headers().get("x-app-version").expect("version"),
+            "1.2.3"
+        );
+
+        let missing = send("/missing");
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        assert_eq!(missing.headers().get("server").expect("server"), "edgezero");
+        assert_eq!(
+            missing.headers().get("x-app-version").expect("version"),
+            "1.2.3"
+        );
+    }
+
     #[test]
     #[should_panic(expected = "duplicate route definition")]
     fn duplicate_route_definition_panics() {
@@ -605,6 +2330,130 @@ mod tests {
             .build();
     }
 
+    #[test]
+    #[should_panic(expected = "duplicate route definition")]
+    fn route_methods_panics_when_a_method_is_taken() {
+        let _service = RouterService::builder()
+            .put("/doc", ok_handler)
+            .route_methods(&[Method::GET, Method::PUT], "/doc", ok_handler)
+            .build();
+    }
+
+    #[test]
+    fn route_methods_and_any_register_every_method() {
+        let service = RouterService::builder()
+            .route_methods(&[Method::GET, Method::HEAD], "/page", ok_handler)
+            .any("/echo", ok_handler)
+            .build();
+
+        assert!(service.has_route(&Method::GET, "/page"));
+        assert!(service.has_route(&Method::HEAD, "/page"));
+        assert!(!service.has_route(&Method::POST, "/page"));
+        for method in STANDARD_METHODS {
+            assert!(service.has_route(&method, "/echo"), "{method}");
+        }
+        let listed = service
+            .routes()
+            .iter()
+            .filter(|route| route.path() == "/echo")
+            .count();
+        assert_eq!(listed, STANDARD_METHODS.len());
+    }
+
+    #[test]
+    fn error_hooks_see_handler_errors_with_the_request() {
+        async fn report(_ctx: RequestContext) -> Result<Response, EdgeError> {
+            Err(EdgeError::internal(anyhow::anyhow!("database unavailable")))
+        }
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&seen);
+        let router = RouterService::builder()
+            .on_error(move |err: &EdgeError, ctx: &RequestContext| {
+                recorded.lock().expect("hook log").push(format!(
+                    "{} {} {} id={}",
+                    err.status().as_u16(),
+                    ctx.request().method(),
+                    ctx.request().uri().path(),
+                    ctx.path_params().get("id").unwrap_or_default()
+                ));
+            })
+            .get("/reports/{id}", report)
+            .get("/ok", ok_handler)
+            .build();
+        let send = |path: &str| {
+            let request = request_builder()
+                .method(Method::GET)
+                .uri(path)
+                .body(Body::empty())
+                .expect("request");
+            block_on(router.oneshot(request)).expect("response")
+        };
+
+        assert_eq!(
+            send("/reports/7").status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(send("/ok").status(), StatusCode::OK);
+        assert_eq!(*seen.lock().expect("hook log"), ["500 GET /reports/7 id=7"]);
+    }
+
+    #[test]
+    fn error_hooks_see_errors_rendered_by_observability_and_cors() {
+        use crate::cors::Cors;
+        use crate::request_id::RequestId;
+
+        async fn fail(_ctx: RequestContext) -> Result<Response, EdgeError> {
+            Err(EdgeError::bad_request("missing field"))
+        }
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&seen);
+        let cors = Cors::builder()
+            .allow_origin("https://app.example")
+            .build()
+            .expect("cors");
+        let router = RouterService::builder()
+            .with_observability()
+            .middleware(cors)
+            .on_error(move |err: &EdgeError, ctx: &RequestContext| {
+                let id = ctx.request().extensions().get::<RequestId>();
+                recorded.lock().expect("hook log").push(format!(
+                    "{} id={}",
+                    err.status().as_u16(),
+                    id.is_some()
+                ));
+            })
+            .get("/fail", fail)
+            .build();
+        let send = |origin: Option<&str>| {
+            let mut builder = request_builder().method(Method::GET).uri("/fail");
+            if let Some(value) = origin {
+                builder = builder.header("origin", value);
+            }
+            block_on(router.oneshot(builder.body(Body::empty()).expect("request")))
+                .expect("response")
+        };
+
+        // Cors renders the error for the allowed origin, RequestIdMiddleware
+        // for the request without one; the hook sees each error once.
+        let cross_origin = send(Some("https://app.example"));
+        assert_eq!(cross_origin.status(), StatusCode::BAD_REQUEST);
+        assert!(
+            cross_origin
+                .headers()
+                .contains_key("access-control-allow-origin")
+        );
+        assert!(cross_origin.headers().contains_key("x-request-id"));
+        let same_origin = send(None);
+        assert_eq!(same_origin.status(), StatusCode::BAD_REQUEST);
+        assert!(same_origin.headers().contains_key("x-request-id"));
+        assert_eq!(
+            *seen.lock().expect("hook log"),
+            ["400 id=true", "400 id=true"]
+        );
+    }
+
     #[test]
     fn handler_returns_bad_request_for_invalid_path_params() {
         #[derive(Deserialize)]
@@ -644,6 +2493,72 @@ mod tests {
         assert_eq!(error.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[test]
+    fn proxy_interceptors_layer_onto_the_adapter_handle() {
+        use crate::http::{HeaderValue, Uri};
+        use crate::proxy::{Proxy, ProxyClient, ProxyHandle, ProxyRequest, ProxyResponse};
+        use async_trait::async_trait;
+
+        /// Answers with the `x-order` the request arrived with.
+        struct EchoClient;
+
+        #[async_trait(?Send)]
+        impl ProxyClient for EchoClient {
+            async fn send(&self, request: ProxyRequest) -> Result<ProxyResponse, EdgeError> {
+                let mut response = ProxyResponse::new(StatusCode::OK, Body::empty());
+                if let Some(order) = request.headers().get("x-order") {
+                    response.headers_mut().insert("x-order", order.clone());
+                }
+                Ok(response)
+            }
+        }
+
+        fn append_order(request: &mut ProxyRequest, step: &str) {
+            let order = request
+                .headers()
+                .get("x-order")
+                .and_then(|value| value.to_str().ok())
+                .map_or_else(|| step.to_owned(), |order| format!("{order},{step}"));
+            request
+                .headers_mut()
+                .insert("x-order", HeaderValue::from_str(&order).expect("header"));
+        }
+
+        let router = RouterService::builder()
+            .get("/upstream", |ctx: RequestContext| async move {
+                let Proxy(proxy) = Proxy::from_request(&ctx).await?;
+                let uri = Uri::from_static("https://origin.example/");
+                proxy.forward(ProxyRequest::new(Method::GET, uri)).await
+            })
+            .with_proxy_request_interceptor(|request: &mut ProxyRequest| {
+                append_order(request, "router");
+            })
+            .with_proxy_response_interceptor(|response: &mut ProxyResponse| {
+                response
+                    .headers_mut()
+                    .insert("x-seen", HeaderValue::from_static("1"));
+            })
+            .build();
+        let app = App::new(router).with_proxy_request_interceptor(|request: &mut ProxyRequest| {
+            append_order(request, "app");
+        });
+
+        let mut request = request_builder()
+            .method(Method::GET)
+            .uri("/upstream")
+            .body(Body::empty())
+            .expect("request");
+        request.extensions_mut().insert(
+            ProxyHandle::with_client(EchoClient).with_request_interceptor(
+                |outbound: &mut ProxyRequest| append_order(outbound, "adapter"),
+            ),
+        );
+        let response = block_on(app.router().oneshot(request)).expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-order"], "adapter,router,app");
+        assert_eq!(response.headers()["x-seen"], "1");
+    }
+
     #[test]
     fn oneshot_returns_error_response() {
         let service = RouterService::builder().build();
@@ -670,6 +2585,379 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[test]
+    fn middleware_async_is_set_up_by_try_build_in_registration_order() {
+        let service = block_on(
+            RouterService::builder()
+                .middleware(Tagging("outer"))
+                .middleware_async(|| async { Ok(Tagging("async")) })
+                .middleware(Tagging("inner"))
+                .get("/", ok_handler)
+                .try_build(),
+        )
+        .expect("setup succeeds");
+        let request = request_builder()
+            .method(Method::GET)
+            .uri("/")
+            .body(Body::empty())
+            .expect("request");
+        let response = block_on(service.oneshot(request)).expect("response");
+        let tags: Vec<_> = response.headers().get_all("x-tag").iter().collect();
+        // Innermost middleware appends first on the way out.
+        assert_eq!(tags, ["inner", "async", "outer"]);
+    }
+
+    #[test]
+    fn middleware_async_setup_failure_fails_the_build() {
+        let later_ran = Arc::new(Mutex::new(false));
+        let flag = Arc::clone(&later_ran);
+        let build_err = block_on(
+            RouterService::builder()
+                .middleware_async(|| async {
+                    Err::<Tagging, _>(EdgeError::service_unavailable("key server down"))
+                })
+                .middleware_async(move || {
+                    *flag.lock().unwrap() = true;
+                    async { Ok(Tagging("later")) }
+                })
+                .get("/", ok_handler)
+                .try_build(),
+        )
+        .err()
+        .expect("setup fails");
+        let message = build_err.to_string();
+        assert!(message.contains("Tagging"), "{message}");
+        assert!(message.contains("key server down"), "{message}");
+        assert!(
+            matches!(build_err, RouterBuildError::Middleware { .. }),
+            "{build_err:?}"
+        );
+        // The setup's own status survives the conversion.
+        let err = EdgeError::from(build_err);
+        assert_eq!(err.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(
+            !*later_ran.lock().unwrap(),
+            "setup stops at the first failure"
+        );
+    }
+
+    #[test]
+    fn build_defers_async_middleware_until_ready() {
+        let router = RouterService::builder()
+            .middleware_async(|| async { Ok(Tagging("async")) })
+            .get("/", ok_handler)
+            .build();
+        let request = || {
+            request_builder()
+                .method(Method::GET)
+                .uri("/")
+                .body(Body::empty())
+                .expect("request")
+        };
+        let pending = block_on(router.oneshot(request())).expect("response");
+        assert_eq!(pending.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let ready = block_on(router.ready()).expect("setup succeeds");
+        let response = block_on(ready.oneshot(request())).expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-tag"], "async");
+    }
+
+    #[test]
+    fn mounted_handlers_read_path_relative_to_mount_point() {
+        async fn relative(ctx: RequestContext) -> Result<Response, EdgeError> {
+            let body = format!(
+                "{} {}",
+                ctx.route_path(),
+                ctx.matched_route().unwrap_or("-")
+            );
+            response_with_body(StatusCode::OK, Body::text(body))
+        }
+
+        let api = RouterService::builder()
+            .get("/", relative)
+            .get("/users/{id}", relative)
+            .build();
+        let service = RouterService::builder()
+            .get("/api/status", relative)
+            .mount("/api/v1/", api)
+            .build();
+        let send = |path: &str| {
+            let request = request_builder()
+                .method(Method::GET)
+                .uri(path)
+                .body(Body::empty())
+                .expect("request");
+            block_on(service.oneshot(request)).expect("response")
+        };
+
+        let user = send("/api/v1/users/7?expand=1");
+        assert_eq!(
+            user.body().as_bytes().expect("buffered"),
+            b"/users/7 /users/{id}"
+        );
+        let root = send("/api/v1");
+        assert_eq!(root.body().as_bytes().expect("buffered"), b"/ /");
+        let unmounted = send("/api/status");
+        assert_eq!(
+            unmounted.body().as_bytes().expect("buffered"),
+            b"/api/status /api/status"
+        );
+        assert_eq!(send("/api/v1x").status(), StatusCode::NOT_FOUND);
+        assert_eq!(send("/api/v1/missing").status(), StatusCode::NOT_FOUND);
+
+        let paths: Vec<String> = service
+            .routes()
+            .iter()
+            .map(|info| info.path().to_owned())
+            .collect();
+        assert!(
+            paths.contains(&"/api/v1/users/{id}".to_owned()),
+            "{paths:?}"
+        );
+    }
+
+    #[test]
+    fn has_route_matches_concrete_paths_without_dispatching() {
+        let service = RouterService::builder()
+            .get("/users/{id}", ok_handler)
+            .mount(
+                "/api",
+                RouterService::builder().post("/items", ok_handler).build(),
+            )
+            .build();
+
+        assert!(service.has_route(&Method::GET, "/users/42"));
+        assert!(service.has_route(&Method::POST, "/api/items"));
+    }
+
+    #[test]
+    fn root_route_listing_and_health_routes_coexist() {
+        let service = RouterService::builder()
+            .get("/", |_ctx: RequestContext| async move {
+                Ok::<_, EdgeError>("home")
+            })
+            .get("/__edgezero/routes", introspection::routes)
+            .get("/healthz", |_ctx: RequestContext| async move {
+                Ok::<_, EdgeError>("ok")
+            })
+            .build();
+        assert!(service.shadowed_routes().is_empty());
+        App::new(service.clone())
+            .validate()
+            .expect("no collisions among the three routes");
+        let body_of = |uri: &str| {
+            let request = request_builder()
+                .method(Method::GET)
+                .uri(uri)
+                .body(Body::empty())
+                .expect("request");
+            let response = block_on(service.clone().call(request)).expect("response");
+            assert_eq!(response.status(), StatusCode::OK, "{uri}");
+            String::from_utf8(response.body().as_bytes().expect("buffered").to_vec())
+                .expect("utf-8")
+        };
+
+        assert_eq!(body_of("/"), "home");
+        assert_eq!(body_of("/healthz"), "ok");
+        let listing = body_of("/__edgezero/routes");
+        for path in ["\"/\"", "\"/__edgezero/routes\"", "\"/healthz\""] {
+            assert!(listing.contains(path), "{listing}");
+        }
+    }
+
+    #[test]
+    fn empty_route_path_registers_the_root() {
+        let service = RouterService::builder()
+            .get_named("home", "", |_ctx: RequestContext| async move {
+                Ok::<_, EdgeError>("home")
+            })
+            .build();
+        let request = request_builder()
+            .method(Method::GET)
+            .uri("/")
+            .body(Body::empty())
+            .expect("request");
+
+        let response = block_on(service.clone().call(request)).expect("response");
+        assert_eq!(response.body().as_bytes().expect("buffered"), b"home");
+        assert!(service.has_route(&Method::GET, ""));
+        assert_eq!(service.routes()[0].path(), "/");
+        assert_eq!(service.url_for("home", &[]).expect("url"), "/");
+    }
+
+    #[test]
+    fn a_router_without_routes_answers_not_found_for_the_root() {
+        let service = RouterService::builder().build();
+        let request = request_builder()
+            .method(Method::GET)
+            .uri("/")
+            .body(Body::empty())
+            .expect("request");
+
+        let err = block_on(service.clone().call(request)).expect_err("no routes");
+        assert_eq!(err.status(), StatusCode::NOT_FOUND);
+        assert!(!service.has_route(&Method::GET, "/"));
+    }
+
+    #[test]
+    fn has_route_is_false_for_a_method_mismatch() {
+        let service = RouterService::builder()
+            .get("/users/{id}", ok_handler)
+            .build();
+
+        assert!(!service.has_route(&Method::DELETE, "/users/42"));
+    }
+
+    #[test]
+    fn has_route_is_false_for_unknown_paths() {
+        let service = RouterService::builder()
+            .get("/users/{id}", ok_handler)
+            .build();
+
+        assert!(!service.has_route(&Method::GET, "/users"));
+        assert!(!service.has_route(&Method::GET, "/users/42/posts"));
+        assert!(!service.has_route(&Method::GET, "/api/items"));
+    }
+
+    #[test]
+    fn or_else_serves_unknown_paths_from_fallback() {
+        async fn primary(_ctx: RequestContext) -> Result<Response, EdgeError> {
+            response_with_body(StatusCode::OK, Body::text("primary"))
+        }
+        async fn proxied(_ctx: RequestContext) -> Result<Response, EdgeError> {
+            response_with_body(StatusCode::OK, Body::text("fallback"))
+        }
+
+        let fallback = RouterService::builder()
+            .get("/{*rest}", proxied)
+            .post("/{*rest}", proxied)
+            .build();
+        let service = RouterService::builder()
+            .get("/app", primary)
+            .build()
+            .or_else(fallback);
+        let send = |method: Method, path: &str| {
+            let request = request_builder()
+                .method(method)
+                .uri(path)
+                .body(Body::empty())
+                .expect("request");
+            block_on(service.clone().call(request))
+        };
+
+        let delegated = send(Method::GET, "/elsewhere").expect("fallback response");
+        assert_eq!(delegated.body().as_bytes().expect("buffered"), b"fallback");
+
+        let known = send(Method::GET, "/app").expect("primary response");
+        assert_eq!(known.body().as_bytes().expect("buffered"), b"primary");
+
+        let wrong_method = send(Method::POST, "/app").expect_err("405 is not delegated");
+        assert_eq!(wrong_method.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[test]
+    fn or_else_when_delegates_method_not_allowed_on_opt_in() {
+        async fn proxied(_ctx: RequestContext) -> Result<Response, EdgeError> {
+            response_with_body(StatusCode::ACCEPTED, Body::empty())
+        }
+
+        let fallback = RouterService::builder().post("/app", proxied).build();
+        let service = RouterService::builder()
+            .get("/app", ok_handler)
+            .build()
+            .or_else_when(fallback, Fallthrough::NotFoundOrMethodNotAllowed);
+        let request = request_builder()
+            .method(Method::POST)
+            .uri("/app")
+            .body(Body::empty())
+            .expect("request");
+
+        let response = block_on(service.clone().call(request)).expect("fallback response");
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+
+    #[test]
+    fn path_params_are_percent_decoded_except_slashes() {
+        #[derive(Deserialize)]
+        struct Params {
+            name: String,
+        }
+
+        async fn handler(ctx: RequestContext) -> Result<String, EdgeError> {
+            let params: Params = ctx.path()?;
+            Ok(params.name)
+        }
+
+        let service = RouterService::builder()
+            .get("/files/{name}", handler)
+            .build();
+        let send = |uri: &str| {
+            let request = request_builder()
+                .method(Method::GET)
+                .uri(uri)
+                .body(Body::empty())
+                .expect("request");
+            block_on(service.clone().call(request))
+        };
+        let name_of = |uri: &str| {
+            let response = send(uri).expect("response");
+            String::from_utf8(response.body().as_bytes().expect("buffered").to_vec())
+                .expect("utf-8")
+        };
+
+        assert_eq!(name_of("/files/my%20doc"), "my doc");
+        assert_eq!(name_of("/files/a%3Ab"), "a:b");
+        assert_eq!(name_of("/files/caf%C3%A9"), "caf\u{e9}");
+        assert_eq!(name_of("/files/a%2Fb"), "a%2Fb");
+        // `%25` stays encoded too, so a literal `%2F` is told apart from an
+        // encoded slash.
+        assert_eq!(name_of("/files/a%252Fb"), "a%252Fb");
+        assert_eq!(name_of("/files/100%25"), "100%25");
+
+        let error = send("/files/%FF").expect_err("not utf-8");
+        assert_eq!(error.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn undecodable_params_are_rejected_through_middleware_and_error_hooks() {
+        struct Logged(Arc<Mutex<Vec<String>>>);
+
+        #[async_trait::async_trait(?Send)]
+        impl Middleware for Logged {
+            async fn handle(
+                &self,
+                ctx: RequestContext,
+                next: Next<'_>,
+            ) -> Result<Response, EdgeError> {
+                self.0.lock().expect("log").push("middleware".to_owned());
+                next.run(ctx).await
+            }
+        }
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let in_hook = Arc::clone(&seen);
+        let router = RouterService::builder()
+            .middleware(Logged(Arc::clone(&seen)))
+            .on_error(move |err: &EdgeError, _ctx: &RequestContext| {
+                in_hook
+                    .lock()
+                    .expect("log")
+                    .push(format!("hook {}", err.status().as_u16()));
+            })
+            .get("/files/{name}", ok_handler)
+            .build();
+        let request = request_builder()
+            .method(Method::GET)
+            .uri("/files/%FF")
+            .body(Body::empty())
+            .expect("request");
+
+        let response = block_on(router.oneshot(request)).expect("response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(*seen.lock().expect("log"), ["middleware", "hook 400"]);
+    }
+
     #[test]
     fn returns_method_not_allowed() {
         let service = RouterService::builder().post("/submit", ok_handler).build();
@@ -691,14 +2979,104 @@ mod tests {
             .post("/submit", ok_handler)
             .build();
 
-        let request = request_builder()
-            .method(Method::PUT)
-            .uri("/submit")
-            .body(Body::empty())
-            .expect("request");
+        let request = request_builder()
+            .method(Method::PUT)
+            .uri("/submit")
+            .body(Body::empty())
+            .expect("request");
+
+        let error = block_on(service.clone().call(request)).expect_err("error");
+        assert_eq!(error.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[test]
+    fn extension_methods_are_routed_and_listed_in_allow() {
+        let purge = Method::from_bytes(b"PURGE").expect("method");
+        let service = RouterService::builder()
+            .get("/cache/{*key}", ok_handler)
+            .route("/cache/{*key}", purge.clone(), ok_handler)
+            .build();
+        let send = |method: Method| {
+            let request = request_builder()
+                .method(method)
+                .uri("/cache/pages/home")
+                .body(Body::empty())
+                .expect("request");
+            block_on(service.oneshot(request)).expect("response")
+        };
+
+        assert_eq!(send(purge).status(), StatusCode::OK);
+
+        let rejected = send(Method::DELETE);
+        assert_eq!(rejected.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(rejected.headers()["allow"], "GET, HEAD, PURGE");
+        assert!(service.has_route(&Method::from_bytes(b"PURGE").expect("method"), "/cache/a"));
+    }
+
+    #[test]
+    fn custom_method_not_allowed_handler_reads_template_and_allowed_methods() {
+        use async_trait::async_trait;
+
+        struct Custom405;
+
+        #[async_trait(?Send)]
+        impl Middleware for Custom405 {
+            async fn handle(
+                &self,
+                ctx: RequestContext,
+                next: Next<'_>,
+            ) -> Result<Response, EdgeError> {
+                let matched = ctx.matched_route().map(str::to_owned);
+                match next.run(ctx).await {
+                    Err(err) if err.status() == StatusCode::METHOD_NOT_ALLOWED => {
+                        let allowed = err
+                            .allowed_methods()
+                            .expect("allowed methods")
+                            .iter()
+                            .map(Method::as_str)
+                            .collect::<Vec<_>>()
+                            .join(",");
+                        let template = err.route_template().expect("route template");
+                        assert_eq!(matched.as_deref(), Some(template));
+                        response_builder()
+                            .status(StatusCode::METHOD_NOT_ALLOWED)
+                            .body(Body::text(format!("{template} accepts {allowed}")))
+                            .map_err(EdgeError::internal)
+                    }
+                    other => other,
+                }
+            }
+        }
+
+        let service = RouterService::builder()
+            .get("/users/{id}", ok_handler)
+            .put("/users/{id}", ok_handler)
+            .middleware(Custom405)
+            .build();
+        let request = |method: Method| {
+            request_builder()
+                .method(method)
+                .uri("/users/7")
+                .body(Body::empty())
+                .expect("request")
+        };
+
+        // A 405 skips the middleware chain; whoever renders the error can
+        // still read the template and the allowed methods.
+        let err = block_on(service.clone().call(request(Method::POST))).expect_err("405");
+        assert_eq!(err.route_template(), Some("/users/{id}"));
+        assert_eq!(
+            err.allowed_methods(),
+            Some(vec![Method::GET, Method::HEAD, Method::PUT])
+        );
 
-        let error = block_on(service.clone().call(request)).expect_err("error");
-        assert_eq!(error.status(), StatusCode::METHOD_NOT_ALLOWED);
+        // OPTIONS runs the chain, so middleware can answer it.
+        let response = block_on(service.oneshot(request(Method::OPTIONS))).expect("response");
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            response.body().as_bytes().expect("buffered"),
+            b"/users/{id} accepts GET,HEAD,PUT"
+        );
     }
 
     #[test]
@@ -719,6 +3097,7 @@ mod tests {
         let entry = RouteEntry {
             handler: ok_handler.into_handler(),
             introspection_needs: IntrospectionNeeds::default(),
+            template: Arc::from("/test"),
         };
         let cloned = entry.clone();
 
@@ -760,6 +3139,15 @@ mod tests {
         );
     }
 
+    #[test]
+    #[should_panic(expected = "route name `user` is already used for /users/{id}")]
+    fn reusing_route_name_for_another_path_panics() {
+        let _service = RouterService::builder()
+            .get_named("user", "/users/{id}", ok_handler)
+            .get_named("user", "/people/{id}", ok_handler)
+            .build();
+    }
+
     #[test]
     fn service_poll_ready_reports_ready() {
         let mut service = RouterService::builder().build();
@@ -769,6 +3157,77 @@ mod tests {
         assert!(matches!(ready, Poll::Ready(Ok(()))));
     }
 
+    #[test]
+    fn head_requests_reach_get_routes_without_computing_lazy_bodies() {
+        use bytes::Bytes;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static COMPUTED: AtomicUsize = AtomicUsize::new(0);
+
+        async fn report(_ctx: RequestContext) -> Result<Response, EdgeError> {
+            let body = Body::lazy(|| async {
+                COMPUTED.fetch_add(1, Ordering::SeqCst);
+                Ok(Bytes::from_static(b"expensive report"))
+            });
+            response_with_body(StatusCode::OK, body)
+        }
+
+        let service = RouterService::builder().get("/report", report).build();
+        let send = |method: Method| {
+            let request = request_builder()
+                .method(method)
+                .uri("/report")
+                .body(Body::empty())
+                .expect("request");
+            block_on(service.oneshot(request)).expect("response")
+        };
+
+        let head = send(Method::HEAD);
+        assert_eq!(head.status(), StatusCode::OK);
+        assert!(!head.headers().contains_key("content-length"));
+        let head_body = block_on(head.into_body().into_bytes_bounded(usize::MAX)).expect("body");
+        assert!(head_body.is_empty());
+        assert_eq!(COMPUTED.load(Ordering::SeqCst), 0);
+
+        let get = send(Method::GET);
+        assert_eq!(
+            COMPUTED.load(Ordering::SeqCst),
+            0,
+            "not computed until polled"
+        );
+        let body = block_on(get.into_body().into_bytes_bounded(usize::MAX)).expect("body");
+        assert_eq!(body, "expensive report");
+        assert_eq!(COMPUTED.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn head_requests_keep_the_get_content_length() {
+        let service = RouterService::builder()
+            .get("/page", |_ctx: RequestContext| async move {
+                Ok::<_, EdgeError>("twelve bytes")
+            })
+            .build();
+        let request = request_builder()
+            .method(Method::HEAD)
+            .uri("/page")
+            .body(Body::empty())
+            .expect("request");
+
+        let head = block_on(service.oneshot(request)).expect("response");
+        assert_eq!(head.status(), StatusCode::OK);
+        assert_eq!(head.headers()["content-length"], "12");
+        assert_eq!(head.into_body().as_bytes(), Some(&b""[..]));
+
+        let post = request_builder()
+            .method(Method::POST)
+            .uri("/page")
+            .body(Body::empty())
+            .expect("request");
+        let rejected = block_on(service.oneshot(post)).expect("response");
+        assert_eq!(rejected.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(rejected.headers()["allow"], "GET, HEAD");
+    }
+
     #[test]
     fn streams_body_through_router() {
         use bytes::Bytes;
@@ -805,6 +3264,211 @@ mod tests {
         assert_eq!(collected, b"chunk-one\nchunk-two\n");
     }
 
+    fn chunked_json_post(parts: Vec<Result<&'static [u8], io::Error>>) -> Request {
+        use bytes::Bytes;
+        use futures_util::stream;
+
+        let chunks = parts.into_iter().map(|part| part.map(Bytes::from_static));
+        request_builder()
+            .method(Method::POST)
+            .uri("/echo")
+            .header("content-type", "application/json")
+            .header("transfer-encoding", "chunked")
+            .body(Body::from_stream(stream::iter(chunks)))
+            .expect("request")
+    }
+
+    #[test]
+    fn chunked_json_without_content_length_is_extracted_whole() {
+        async fn handler(ctx: RequestContext) -> Result<String, EdgeError> {
+            let Json(value) = Json::<serde_json::Value>::from_request(&ctx).await?;
+            Ok(value["name"].to_string())
+        }
+
+        let service = RouterService::builder()
+            .with_body_limit(BodyLimit::new(1024))
+            .post("/echo", handler)
+            .build();
+        let request = chunked_json_post(vec![Ok(b"{\"na"), Ok(b"me\":\"ed"), Ok(b"ge\"}")]);
+        let response = block_on(service.oneshot(request)).expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body().as_bytes(), Some(&b"\"edge\""[..]));
+    }
+
+    #[test]
+    fn chunked_json_over_the_body_limit_is_cut_off_mid_stream() {
+        async fn handler(ctx: RequestContext) -> Result<String, EdgeError> {
+            let Json(value) = Json::<serde_json::Value>::from_request(&ctx).await?;
+            Ok(value.to_string())
+        }
+
+        let service = RouterService::builder()
+            .with_body_limit(BodyLimit::new(10))
+            .post("/echo", handler)
+            .build();
+        // Reading past the limit would reach the failing chunk and answer 500.
+        let request = chunked_json_post(vec![
+            Ok(b"{\"name\""),
+            Ok(b":\"edge\"}"),
+            Err(io::Error::other("read past the limit")),
+        ]);
+        let response = block_on(service.oneshot(request)).expect("response");
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn json_bodies_are_only_read_by_routes_that_extract_them() {
+        use crate::middleware::{Middleware, Next};
+        use async_trait::async_trait;
+        use bytes::Bytes;
+        use futures_util::stream::{self, StreamExt as _};
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        /// Touches the request with `request_mut`, which settles the body.
+        struct MarkRequest;
+
+        #[async_trait(?Send)]
+        impl Middleware for MarkRequest {
+            async fn handle(
+                &self,
+                mut ctx: RequestContext,
+                next: Next<'_>,
+            ) -> Result<Response, EdgeError> {
+                ctx.request_mut()
+                    .headers_mut()
+                    .insert("x-seen", HeaderValue::from_static("1"));
+                next.run(ctx).await
+            }
+        }
+
+        async fn extract(ctx: RequestContext) -> Result<String, EdgeError> {
+            let Json(value) = Json::<serde_json::Value>::from_request(&ctx).await?;
+            Ok(value.to_string())
+        }
+
+        async fn stream_through(mut ctx: RequestContext) -> Result<Response, EdgeError> {
+            let body = ctx.take_body();
+            assert!(body.is_stream(), "the body was buffered before the handler");
+            response_with_body(StatusCode::OK, body)
+        }
+
+        let service = RouterService::builder()
+            .middleware(MarkRequest)
+            .post("/echo", extract)
+            .post("/stream", stream_through)
+            .build();
+        let send = |uri: &str| {
+            let polled = Arc::new(AtomicBool::new(false));
+            let flag = Arc::clone(&polled);
+            let chunks = stream::iter(["{\"a\":", "1}"]).map(move |chunk| {
+                flag.store(true, Ordering::SeqCst);
+                Ok::<_, io::Error>(Bytes::from_static(chunk.as_bytes()))
+            });
+            let request = request_builder()
+                .method(Method::POST)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from_stream(chunks))
+                .expect("request");
+            let response = block_on(service.oneshot(request)).expect("response");
+            (response, polled.load(Ordering::SeqCst))
+        };
+
+        let (missing, missing_read) = send("/missing");
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        assert!(!missing_read, "a 404 read the body");
+
+        let (streamed, streamed_read) = send("/stream");
+        assert!(
+            !streamed_read,
+            "the body was read before the handler streamed it"
+        );
+        let body = block_on(streamed.into_body().into_bytes_bounded(usize::MAX)).expect("body");
+        assert_eq!(body, "{\"a\":1}");
+
+        let (extracted, _) = send("/echo");
+        assert_eq!(extracted.status(), StatusCode::OK);
+        assert_eq!(extracted.body().as_bytes(), Some(&b"{\"a\":1}"[..]));
+    }
+
+    #[test]
+    fn buffering_without_a_body_limit_stops_at_the_default() {
+        use bytes::Bytes;
+        use futures_util::stream;
+
+        let chunk = Bytes::from(vec![b' '; 1024 * 1024]);
+        // One chunk more than the 32 MiB default.
+        assert_eq!(BodyLimit::default().max_bytes(), 32 * chunk.len());
+        let chunks = stream::iter(vec![chunk; 33].into_iter().map(Ok::<_, io::Error>));
+        let request = request_builder()
+            .method(Method::POST)
+            .uri("/")
+            .header("content-type", "application/json")
+            .body(Body::from_stream(chunks))
+            .expect("request");
+        let ctx = RequestContext::new(request, PathParams::default());
+        let err = block_on(ctx.buffer_body()).expect_err("past the default limit");
+        assert_eq!(err.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn with_json_limits_rejects_payloads_over_the_limit() {
+        async fn handler(ctx: RequestContext) -> Result<String, EdgeError> {
+            let value: serde_json::Value = ctx.json()?;
+            Ok(value.to_string())
+        }
+
+        let service = RouterService::builder()
+            .with_json_limits(JsonLimits::new().max_depth(2))
+            .post("/echo", handler)
+            .build();
+        let send = |body: &'static str| {
+            let request = request_builder()
+                .method(Method::POST)
+                .uri("/echo")
+                .body(Body::from(body))
+                .expect("request");
+            block_on(service.oneshot(request)).expect("response")
+        };
+
+        assert_eq!(send(r#"{"a":[1]}"#).status(), StatusCode::OK);
+        assert_eq!(send(r#"{"a":[[1]]}"#).status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn with_settings_exposes_settings_to_extractor() {
+        use crate::extractor::{FromRequest as _, Settings};
+        use crate::manifest::ManifestLoader;
+
+        #[derive(serde::Deserialize)]
+        struct AppSettings {
+            greeting: String,
+        }
+
+        async fn handler(ctx: RequestContext) -> Result<String, EdgeError> {
+            let settings = Settings::<AppSettings>::from_request(&ctx).await?;
+            Ok(settings.greeting.clone())
+        }
+
+        let loader = ManifestLoader::load_from_str(
+            "[app]\nname = \"demo\"\n\n[settings]\ngreeting = \"hi there\"\n",
+        );
+        let settings: AppSettings = loader.settings().expect("settings");
+        let service = RouterService::builder()
+            .with_settings(settings)
+            .get("/greet", handler)
+            .build();
+
+        let request = request_builder()
+            .method(Method::GET)
+            .uri("/greet")
+            .body(Body::empty())
+            .expect("request");
+        let response = block_on(service.oneshot(request)).expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body().as_bytes().expect("buffered"), b"hi there");
+    }
+
     #[test]
     fn with_state_exposes_value_to_handler() {
         use crate::extractor::{FromRequest as _, State};
@@ -946,4 +3610,311 @@ mod tests {
         let response = block_on(service.oneshot(request)).expect("response");
         assert_eq!(response.body().as_bytes().expect("buffered"), b"7-hi");
     }
+
+    #[test]
+    fn try_route_registers_non_conflicting_routes() {
+        let service = RouterService::builder()
+            .try_route("/items", Method::GET, ok_handler)
+            .and_then(|builder| builder.try_route("/items", Method::POST, ok_handler))
+            .expect("no conflict")
+            .build();
+        let request = request_builder()
+            .method(Method::POST)
+            .uri("/items")
+            .body(Body::empty())
+            .expect("request");
+
+        let response = block_on(service.clone().call(request)).expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(service.routes().len(), 2);
+    }
+
+    #[test]
+    fn try_route_reports_conflicts_and_invalid_paths() {
+        let builder = RouterService::builder().get("/items/{id}", ok_handler);
+        let err = builder
+            .try_route("/items/{id}", Method::GET, ok_handler)
+            .err()
+            .expect("conflict");
+        assert_eq!(
+            err,
+            RouteError::Conflict {
+                existing: "/items/{id}".to_owned(),
+                method: Method::GET,
+                path: "/items/{id}".to_owned(),
+            }
+        );
+
+        let invalid = RouterService::builder()
+            .try_route("/files/{*rest}/tail", Method::GET, ok_handler)
+            .err()
+            .expect("invalid path");
+        assert!(matches!(invalid, RouteError::InvalidPath { .. }));
+    }
+
+    #[test]
+    fn try_build_reports_colliding_params() {
+        let err = block_on(
+            RouterService::builder()
+                .get("/users/{id}", ok_handler)
+                .get("/users/{name}", ok_handler)
+                .try_build(),
+        )
+        .err()
+        .expect("conflict");
+
+        assert!(
+            matches!(
+                &err,
+                RouterBuildError::Route(RouteError::Conflict { existing, path, .. })
+                    if existing == "/users/{id}" && path == "/users/{name}"
+            ),
+            "{err:?}"
+        );
+        let message = err.to_string();
+        assert!(
+            message.contains("/users/{name} conflicts with existing route /users/{id}"),
+            "{message}"
+        );
+        assert!(message.contains("different names"), "{message}");
+    }
+
+    #[test]
+    fn try_build_fails_past_the_route_cap() {
+        let generated = (0_u8..4).fold(
+            RouterService::builder().with_route_limits(RouteLimits::new().max_routes(3)),
+            |builder, index| builder.get(&format!("/generated/{index}"), ok_handler),
+        );
+
+        let err = block_on(generated.try_build()).err().expect("over the cap");
+        assert!(
+            matches!(err, RouterBuildError::TooManyRoutes { count: 4, max: 3 }),
+            "{err:?}"
+        );
+
+        let within = block_on(
+            RouterService::builder()
+                .with_route_limits(RouteLimits::new().max_routes(3))
+                .route_methods(&[Method::GET, Method::POST, Method::PUT], "/", ok_handler)
+                .try_build(),
+        );
+        within.expect("three routes fit");
+    }
+
+    #[test]
+    fn try_build_checks_pattern_length_and_params() {
+        let limits = RouteLimits::new().max_path_length(16).max_params(2);
+
+        let long = block_on(
+            RouterService::builder()
+                .with_route_limits(limits)
+                .get("/a/very/long/generated/path", ok_handler)
+                .try_build(),
+        )
+        .err()
+        .expect("too long");
+        assert!(
+            matches!(
+                &long,
+                RouterBuildError::PathTooLong {
+                    length: 27,
+                    max: 16,
+                    ..
+                }
+            ),
+            "{long:?}"
+        );
+
+        let params = block_on(
+            RouterService::builder()
+                .with_route_limits(limits)
+                .get("/{a}/{b}/{*c}", ok_handler)
+                .try_build(),
+        )
+        .err()
+        .expect("too many params");
+        assert!(
+            matches!(
+                &params,
+                RouterBuildError::TooManyParams {
+                    count: 3,
+                    max: 2,
+                    ..
+                }
+            ),
+            "{params:?}"
+        );
+
+        let escaped = block_on(
+            RouterService::builder()
+                .with_route_limits(limits)
+                .get("/{{x}}/{a}/{b}", ok_handler)
+                .try_build(),
+        );
+        escaped.expect("literal braces are not params");
+    }
+
+    #[test]
+    #[should_panic(expected = "the router has 2 routes, over the limit of 1")]
+    fn build_panics_past_the_route_cap() {
+        let _service = RouterService::builder()
+            .with_route_limits(RouteLimits::new().max_routes(1))
+            .get("/one", ok_handler)
+            .get("/two", ok_handler)
+            .build();
+    }
+
+    #[test]
+    #[should_panic(expected = "GET /users/{name} conflicts with existing route /users/{id}")]
+    fn build_panics_naming_both_colliding_patterns() {
+        let _service = RouterService::builder()
+            .get("/users/{id}", ok_handler)
+            .get("/users/{name}", ok_handler)
+            .build();
+    }
+
+    #[test]
+    fn static_segments_take_precedence_over_params() {
+        let service = block_on(
+            RouterService::builder()
+                .get("/users/{id}", |ctx: RequestContext| async move {
+                    let id = ctx.path_params().get("id").unwrap_or_default().to_owned();
+                    Ok::<_, EdgeError>(format!("user {id}"))
+                })
+                .get("/users/me", |_ctx: RequestContext| async move {
+                    Ok::<_, EdgeError>("current user")
+                })
+                .try_build(),
+        )
+        .expect("static and param segments do not collide");
+        let body_of = |uri: &str| {
+            let request = request_builder()
+                .method(Method::GET)
+                .uri(uri)
+                .body(Body::empty())
+                .expect("request");
+            let response = block_on(service.clone().call(request)).expect("response");
+            String::from_utf8(response.body().as_bytes().expect("buffered").to_vec())
+                .expect("utf-8")
+        };
+
+        assert_eq!(body_of("/users/me"), "current user");
+        assert_eq!(body_of("/users/7"), "user 7");
+    }
+
+    #[test]
+    fn url_for_fills_named_route_templates() {
+        let router = RouterService::builder()
+            .get_named("user", "/users/{id}", ok_handler)
+            .put_named("user", "/users/{id}", ok_handler)
+            .get_named("file", "/files/{owner}/{*path}", ok_handler)
+            .get_named("literal", "/raw/{{braces}}/{id}", ok_handler)
+            .build();
+
+        assert_eq!(
+            router.url_for("user", &[("id", "42")]).expect("url"),
+            "/users/42"
+        );
+        assert_eq!(
+            router
+                .url_for("user", &[("id", "a b/\u{fc}"), ("unused", "x")])
+                .expect("url"),
+            "/users/a%20b%2F%C3%BC"
+        );
+        assert_eq!(
+            router
+                .url_for("file", &[("owner", "ann"), ("path", "docs/q 1.txt")])
+                .expect("url"),
+            "/files/ann/docs/q%201.txt"
+        );
+        assert_eq!(
+            router.url_for("literal", &[("id", "7")]).expect("url"),
+            "/raw/{braces}/7"
+        );
+    }
+
+    #[test]
+    fn url_for_reports_missing_params_and_unknown_routes() {
+        let router = RouterService::builder()
+            .get_named("user", "/users/{id}", ok_handler)
+            .build();
+
+        let missing = router.url_for("user", &[("name", "42")]);
+        assert_eq!(
+            missing,
+            Err(UrlForError::MissingParam {
+                param: "id".to_owned(),
+                route: "user".to_owned(),
+            })
+        );
+        assert_eq!(
+            missing.expect_err("missing").to_string(),
+            "route `user` needs a value for `id`"
+        );
+        assert_eq!(
+            router.url_for("nope", &[]),
+            Err(UrlForError::UnknownRoute {
+                name: "nope".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn route_with_timeout_answers_504_for_slow_handlers() {
+        use std::thread;
+        use std::time::Duration;
+
+        async fn slow_handler(_ctx: RequestContext) -> Result<Response, EdgeError> {
+            thread::sleep(Duration::from_millis(20));
+            response_with_body(StatusCode::OK, Body::empty())
+        }
+
+        let router = RouterService::builder()
+            .route_with_timeout("/slow", Method::GET, slow_handler, Duration::from_millis(1))
+            .route_with_timeout("/fast", Method::GET, ok_handler, Duration::from_secs(30))
+            .build();
+
+        let slow = request_builder()
+            .method(Method::GET)
+            .uri("/slow")
+            .body(Body::empty())
+            .expect("request");
+        let slow_response = block_on(router.oneshot(slow)).expect("response");
+        assert_eq!(slow_response.status(), StatusCode::GATEWAY_TIMEOUT);
+
+        let fast = request_builder()
+            .method(Method::GET)
+            .uri("/fast")
+            .body(Body::empty())
+            .expect("request");
+        let fast_response = block_on(router.oneshot(fast)).expect("response");
+        assert_eq!(fast_response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn handlers_iterate_path_params_of_multi_segment_routes() {
+        async fn echo_params(ctx: RequestContext) -> Result<Response, EdgeError> {
+            let mut pairs: Vec<String> = ctx
+                .path_params()
+                .iter()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect();
+            pairs.sort_unstable();
+            response_with_body(StatusCode::OK, Body::from(pairs.join("&")))
+        }
+
+        let router = RouterService::builder()
+            .get("/{org}/{repo}", echo_params)
+            .build();
+        let request = request_builder()
+            .method(Method::GET)
+            .uri("/stackpop/edgezero")
+            .body(Body::empty())
+            .expect("request");
+        let response = block_on(router.oneshot(request)).expect("response");
+        assert_eq!(
+            response.body().as_bytes().expect("buffered"),
+            b"org=stackpop&repo=edgezero"
+        );
+    }
 }
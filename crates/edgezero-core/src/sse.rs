@@ -0,0 +1,221 @@
+//! Server-sent events.
+//!
+//! [`Sse`] sends a stream of [`Event`]s as a `text/event-stream` response.
+//! When the connection drops, a browser's `EventSource` reconnects and sends
+//! the id of the last event it received in `Last-Event-ID`.
+//! [`Sse::resume`] passes that id to the closure producing the events, so the
+//! stream can pick up after it instead of starting over:
+//!
+//! ```rust,ignore
+//! async fn feed(ctx: RequestContext) -> Sse {
+//!     Sse::resume(&ctx, |last_event_id| updates_after(last_event_id))
+//!         .retry(Duration::from_secs(5))
+//! }
+//! ```
+
+use std::time::Duration;
+
+use bytes::{BufMut as _, Bytes, BytesMut};
+use futures_util::stream::{self, LocalBoxStream, Stream, StreamExt as _};
+
+use crate::body::Body;
+use crate::context::RequestContext;
+use crate::error::EdgeError;
+use crate::http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use crate::http::{HeaderValue, Response, StatusCode};
+use crate::response::{IntoResponse, response_with_body};
+
+/// Request header carrying the id of the last event the client received.
+pub const LAST_EVENT_ID: &str = "last-event-id";
+
+/// One server-sent event.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Event {
+    data: String,
+    id: Option<String>,
+    /// Event type, sent as the `event:` field.
+    kind: Option<String>,
+}
+
+/// A `text/event-stream` response of [`Event`]s.
+pub struct Sse {
+    events: LocalBoxStream<'static, Event>,
+    retry: Option<Duration>,
+}
+
+impl Event {
+    /// The event in wire format, ending with the blank line that dispatches
+    /// it.
+    fn encode(&self) -> Bytes {
+        let mut buf = BytesMut::new();
+        if let Some(id) = &self.id {
+            put_field(&mut buf, "id", id);
+        }
+        if let Some(kind) = &self.kind {
+            put_field(&mut buf, "event", kind);
+        }
+        for line in self.data.replace("\r\n", "\n").split(['\r', '\n']) {
+            put_field(&mut buf, "data", line);
+        }
+        buf.put_slice(b"\n");
+        buf.freeze()
+    }
+
+    /// Set the event type, dispatched to `addEventListener(name, ...)`
+    /// instead of `onmessage`. Line breaks are removed.
+    #[must_use]
+    #[inline]
+    pub fn event<S: Into<String>>(mut self, name: S) -> Self {
+        self.kind = Some(single_line(name.into()));
+        self
+    }
+
+    /// Set the id a reconnecting client sends back in `Last-Event-ID`. Line
+    /// breaks are removed.
+    #[must_use]
+    #[inline]
+    pub fn id<S: Into<String>>(mut self, id: S) -> Self {
+        self.id = Some(single_line(id.into()));
+        self
+    }
+
+    /// An event carrying `data`, which may span several lines.
+    #[must_use]
+    #[inline]
+    pub fn new<S: Into<String>>(data: S) -> Self {
+        Self {
+            data: data.into(),
+            id: None,
+            kind: None,
+        }
+    }
+}
+
+impl Sse {
+    /// Send `events` in order, as they are produced.
+    #[must_use]
+    #[inline]
+    pub fn new<S>(events: S) -> Self
+    where
+        S: Stream<Item = Event> + 'static,
+    {
+        Self {
+            events: events.boxed_local(),
+            retry: None,
+        }
+    }
+
+    /// Send the events `produce` returns for the request's `Last-Event-ID`,
+    /// or `None` when the client is not reconnecting.
+    #[must_use]
+    #[inline]
+    pub fn resume<F, S>(ctx: &RequestContext, produce: F) -> Self
+    where
+        F: FnOnce(Option<String>) -> S,
+        S: Stream<Item = Event> + 'static,
+    {
+        let last_event_id = ctx
+            .request()
+            .headers()
+            .get(LAST_EVENT_ID)
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| !id.is_empty())
+            .map(str::to_owned);
+        Self::new(produce(last_event_id))
+    }
+
+    /// Ask the client to wait `delay` before reconnecting, with a `retry:`
+    /// line sent ahead of the events.
+    #[must_use]
+    #[inline]
+    pub fn retry(mut self, delay: Duration) -> Self {
+        self.retry = Some(delay);
+        self
+    }
+}
+
+impl IntoResponse for Sse {
+    #[inline]
+    fn into_response(self) -> Result<Response, EdgeError> {
+        let retry = self
+            .retry
+            .map(|delay| Bytes::from(format!("retry: {}\n\n", delay.as_millis())));
+        let events = self.events.map(|event| event.encode());
+        let body = Body::stream(stream::iter(retry).chain(events));
+        let mut response = response_with_body(StatusCode::OK, body)?;
+        let headers = response.headers_mut();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+        headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+        Ok(response)
+    }
+}
+
+fn put_field(buf: &mut BytesMut, name: &str, value: &str) {
+    buf.put_slice(name.as_bytes());
+    buf.put_slice(b": ");
+    buf.put_slice(value.as_bytes());
+    buf.put_slice(b"\n");
+}
+
+/// `value` without line breaks, which would end the field early.
+fn single_line(value: String) -> String {
+    if value.contains(['\r', '\n']) {
+        value.replace(['\r', '\n'], "")
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::request_builder;
+    use crate::params::PathParams;
+    use futures::executor::block_on;
+    use std::sync::{Arc, Mutex};
+
+    fn body_text(response: Response) -> String {
+        let bytes =
+            block_on(response.into_body().into_bytes_bounded(usize::MAX)).expect("event stream");
+        String::from_utf8(bytes.to_vec()).expect("utf-8")
+    }
+
+    #[test]
+    fn events_are_framed_with_multi_line_data() {
+        let events = stream::iter([
+            Event::new("first\nsecond").id("1"),
+            Event::new("{}").event("update").id("2\nevent: spoofed"),
+        ]);
+        let response = Sse::new(events).into_response().expect("response");
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).expect("content type"),
+            "text/event-stream"
+        );
+        assert_eq!(
+            body_text(response),
+            "id: 1\ndata: first\ndata: second\n\n\
+             id: 2event: spoofed\nevent: update\ndata: {}\n\n"
+        );
+    }
+
+    #[test]
+    fn resume_passes_last_event_id_and_sends_retry_first() {
+        let request = request_builder()
+            .uri("/feed")
+            .header(LAST_EVENT_ID, "41")
+            .body(Body::empty())
+            .expect("request");
+        let ctx = RequestContext::new(request, PathParams::default());
+        let seen = Arc::new(Mutex::new(None));
+        let recorded = Arc::clone(&seen);
+        let sse = Sse::resume(&ctx, move |last_event_id| {
+            *recorded.lock().expect("producer log") = last_event_id;
+            stream::iter([Event::new("next").id("42")])
+        })
+        .retry(Duration::from_millis(2500));
+
+        assert_eq!(seen.lock().expect("producer log").as_deref(), Some("41"));
+        let response = sse.into_response().expect("response");
+        assert_eq!(body_text(response), "retry: 2500\n\nid: 42\ndata: next\n\n");
+    }
+}
@@ -0,0 +1,284 @@
+//! In-process client for handler tests.
+//!
+//! [`TestClient`] sends requests through a [`RouterService`] exactly as an
+//! adapter would (via [`RouterService::oneshot`], so errors are rendered and
+//! after hooks run) and drains the response body, streaming or not, into a
+//! [`TestResponse`]:
+//!
+//! ```rust,ignore
+//! let client = TestClient::new(router);
+//! let response = block_on(client.post_json("/users", &json!({ "name": "ada" })));
+//! assert_eq!(response.status(), StatusCode::CREATED);
+//! assert_eq!(response.json::<User>().name, "ada");
+//! ```
+//!
+//! Like a failed assertion, a request that cannot be built or a body that
+//! does not decode panics with a message naming the request or the body.
+//!
+//! Available in `#[cfg(test)]` builds within this crate, and in any
+//! downstream crate that enables the `test-utils` feature on `edgezero-core`.
+
+#![expect(
+    clippy::panic,
+    reason = "a test harness reports failures by panicking, like `assert!`"
+)]
+
+use bytes::{Bytes, BytesMut};
+use futures_util::StreamExt as _;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::app::App;
+use crate::body::Body;
+use crate::http::header::CONTENT_TYPE;
+use crate::http::{HeaderMap, HeaderValue, Method, Request, StatusCode, request_builder};
+use crate::router::RouterService;
+
+/// Sends requests to a router in process. See the module docs.
+#[derive(Clone)]
+pub struct TestClient {
+    router: RouterService,
+}
+
+/// A response with its body drained, as returned by [`TestClient`].
+#[derive(Debug)]
+pub struct TestResponse {
+    body: Bytes,
+    headers: HeaderMap,
+    status: StatusCode,
+}
+
+impl TestClient {
+    /// `DELETE path`.
+    #[inline]
+    pub async fn delete(&self, path: &str) -> TestResponse {
+        self.send(empty_request(Method::DELETE, path)).await
+    }
+
+    /// A client for the router of `app`, including its app-level middleware
+    /// and state.
+    #[must_use]
+    #[inline]
+    pub fn from_app(app: App) -> Self {
+        Self::new(app.into_router())
+    }
+
+    /// `GET path`.
+    #[inline]
+    pub async fn get(&self, path: &str) -> TestResponse {
+        self.send(empty_request(Method::GET, path)).await
+    }
+
+    /// A client for `router`.
+    #[must_use]
+    #[inline]
+    pub fn new(router: RouterService) -> Self {
+        Self { router }
+    }
+
+    /// `POST path` with `value` as a JSON body.
+    #[inline]
+    pub async fn post_json<T: Serialize + ?Sized>(&self, path: &str, value: &T) -> TestResponse {
+        self.send(json_request(Method::POST, path, value)).await
+    }
+
+    /// `PUT path` with `value` as a JSON body.
+    #[inline]
+    pub async fn put_json<T: Serialize + ?Sized>(&self, path: &str, value: &T) -> TestResponse {
+        self.send(json_request(Method::PUT, path, value)).await
+    }
+
+    /// Send a request built by the caller, e.g. with custom headers.
+    ///
+    /// # Panics
+    /// Panics if an error response cannot be rendered or the response body
+    /// stream fails.
+    #[inline]
+    pub async fn send(&self, request: Request) -> TestResponse {
+        let target = format!("{} {}", request.method(), request.uri());
+        let response = self
+            .router
+            .oneshot(request)
+            .await
+            .unwrap_or_else(|err| panic!("{target}: error could not be rendered: {err}"));
+        let (parts, streamed) = response.into_parts();
+        let body = match streamed {
+            Body::Once(bytes) => bytes,
+            Body::Stream(mut chunks) | Body::StreamWithTrailers(mut chunks, _) => {
+                let mut buffer = BytesMut::new();
+                while let Some(chunk) = chunks.next().await {
+                    let bytes =
+                        chunk.unwrap_or_else(|err| panic!("{target}: response body failed: {err}"));
+                    buffer.extend_from_slice(&bytes);
+                }
+                buffer.freeze()
+            }
+        };
+        TestResponse {
+            body,
+            headers: parts.headers,
+            status: parts.status,
+        }
+    }
+}
+
+impl TestResponse {
+    /// The drained body.
+    #[must_use]
+    #[inline]
+    pub fn bytes(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// The value of header `name`, if present and visible ASCII. For
+    /// repeated headers, the first value.
+    #[must_use]
+    #[inline]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)?.to_str().ok()
+    }
+
+    /// All response headers.
+    #[must_use]
+    #[inline]
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// The body parsed as JSON.
+    ///
+    /// # Panics
+    /// Panics if the body is not JSON of type `T`.
+    #[must_use]
+    #[inline]
+    pub fn json<T: DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(&self.body).unwrap_or_else(|err| {
+            panic!(
+                "response body is not the expected JSON ({err}): {}",
+                String::from_utf8_lossy(&self.body)
+            )
+        })
+    }
+
+    /// The response status.
+    #[must_use]
+    #[inline]
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The body as text.
+    ///
+    /// # Panics
+    /// Panics if the body is not UTF-8.
+    #[must_use]
+    #[inline]
+    pub fn text(&self) -> &str {
+        str::from_utf8(&self.body)
+            .unwrap_or_else(|err| panic!("response body is not UTF-8 ({err}): {:?}", self.body))
+    }
+}
+
+fn empty_request(method: Method, path: &str) -> Request {
+    request_builder()
+        .method(method)
+        .uri(path)
+        .body(Body::empty())
+        .unwrap_or_else(|err| panic!("invalid test request for {path}: {err}"))
+}
+
+fn json_request<T: Serialize + ?Sized>(method: Method, path: &str, value: &T) -> Request {
+    let body = serde_json::to_vec(value)
+        .unwrap_or_else(|err| panic!("test request body for {path} is not serializable: {err}"));
+    request_builder()
+        .method(method)
+        .uri(path)
+        .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+        .body(Body::from(body))
+        .unwrap_or_else(|err| panic!("invalid test request for {path}: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::RequestContext;
+    use crate::error::EdgeError;
+    use crate::http::Response;
+    use crate::response::response_with_body;
+    use futures::executor::block_on;
+    use futures::stream;
+    use serde::Deserialize;
+    use serde_json::{Value, json};
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct User {
+        name: String,
+    }
+
+    async fn create(ctx: RequestContext) -> Result<Response, EdgeError> {
+        let user: User = ctx.json()?;
+        let mut response = response_with_body(
+            StatusCode::CREATED,
+            Body::json(&user).map_err(EdgeError::internal)?,
+        )?;
+        response
+            .headers_mut()
+            .insert("location", HeaderValue::from_static("/users/1"));
+        Ok(response)
+    }
+
+    async fn streamed(_ctx: RequestContext) -> Result<Response, EdgeError> {
+        let chunks = stream::iter([Bytes::from_static(b"hello, "), Bytes::from_static(b"world")]);
+        response_with_body(StatusCode::OK, Body::stream(chunks))
+    }
+
+    fn client() -> TestClient {
+        TestClient::new(
+            RouterService::builder()
+                .get("/stream", streamed)
+                .post("/users", create)
+                .build(),
+        )
+    }
+
+    #[test]
+    fn post_json_round_trips_through_the_handler() {
+        let response = block_on(client().post_json("/users", &json!({ "name": "ada" })));
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.header("location"), Some("/users/1"));
+        assert_eq!(
+            response.json::<User>(),
+            User {
+                name: "ada".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn streaming_bodies_are_drained() {
+        let response = block_on(client().get("/stream"));
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.text(), "hello, world");
+    }
+
+    #[test]
+    fn errors_are_rendered_like_an_adapter_would() {
+        let client = client();
+        let missing = block_on(client.get("/nope"));
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+
+        let wrong_method = block_on(client.delete("/users"));
+        assert_eq!(wrong_method.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+        let malformed = block_on(client.post_json("/users", &json!({ "nickname": "ada" })));
+        assert_eq!(malformed.status(), StatusCode::BAD_REQUEST);
+        assert!(malformed.json::<Value>().is_object());
+    }
+
+    #[test]
+    #[should_panic(expected = "not the expected JSON")]
+    fn json_panics_with_the_body_on_mismatch() {
+        let response = block_on(client().get("/stream"));
+        let _user: User = response.json();
+    }
+}
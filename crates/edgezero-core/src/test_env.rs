@@ -7,9 +7,10 @@
 //!
 //! Rather than spread `unsafe` — and an `#[expect(unsafe_code)]` to opt out of
 //! the workspace's `unsafe_code = "deny"` lint — across every crate that
-//! overrides an env var in its tests, all mutation funnels through this module.
-//! It is the only place in the workspace that writes the environment, so the
-//! `unsafe` blocks and their safety argument live in exactly one file.
+//! overrides an env var in its tests, all test-time mutation funnels through
+//! this module, so the `unsafe` blocks and their safety argument live in
+//! exactly one file. (The only other write is the axum dev server's `.env`
+//! loading, which runs at startup before any other thread exists.)
 //!
 //! # Safety contract
 //!
@@ -21,7 +22,7 @@
 //! The `unsafe` here is sound only under that discipline, which is why this
 //! module is test-only and never compiled into a production build.
 
-// The workspace's only `unsafe`. See the module docs for the safety argument.
+// The workspace's only test-time `unsafe`. See the module docs for the safety argument.
 #![expect(
     unsafe_code,
     reason = "std::env::{set_var, remove_var} are unsafe in edition 2024; centralised here so no other crate has to opt out of the unsafe_code deny"
@@ -0,0 +1,354 @@
+//! W3C Trace Context (`traceparent` / `tracestate`) propagation.
+//!
+//! [`TraceContext`] middleware parses the incoming `traceparent` header,
+//! opens a span for this hop (a child of the caller's span, or a new root
+//! when the header is absent or malformed) and stores it in the request
+//! extensions as a [`TraceParent`]. Proxy calls forward it with
+//! [`ProxyRequest::inject_trace_context`], which writes a child span so the
+//! upstream's work nests under this request.
+//!
+//! [`ProxyRequest::inject_trace_context`]: crate::proxy::ProxyRequest::inject_trace_context
+//!
+//! ```rust,ignore
+//! let router = RouterService::builder()
+//!     .middleware(TraceContext)
+//!     .get("/api/{*rest}", forward)
+//!     .build();
+//!
+//! async fn forward(ctx: RequestContext) -> Result<Response, EdgeError> {
+//!     let mut outbound = ProxyRequest::new(Method::GET, upstream_uri()?);
+//!     outbound.inject_trace_context(&ctx);
+//!     // send via ctx.proxy_handle()
+//! }
+//! ```
+//!
+//! Span and trace ids come from the platform's random source, like request
+//! ids (see [`gen_request_id`](crate::request_id::gen_request_id)).
+
+use async_trait::async_trait;
+
+use crate::context::RequestContext;
+use crate::error::EdgeError;
+use crate::http::{HeaderMap, HeaderValue, Response};
+use crate::middleware::{Middleware, Next};
+use crate::request_id::random_bits;
+
+/// Header carrying the trace id, parent span id and flags.
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+/// Header carrying vendor-specific trace state, forwarded verbatim.
+pub const TRACESTATE_HEADER: &str = "tracestate";
+
+/// Sampled bit of the `trace-flags` field.
+const FLAG_SAMPLED: u8 = 0x01;
+const SPAN_ID_LEN: usize = 16;
+const TRACE_ID_LEN: usize = 32;
+
+/// Middleware that establishes a [`TraceParent`] for every request.
+pub struct TraceContext;
+
+#[async_trait(?Send)]
+impl Middleware for TraceContext {
+    #[inline]
+    async fn handle(&self, mut ctx: RequestContext, next: Next<'_>) -> Result<Response, EdgeError> {
+        let trace = TraceParent::from_headers(ctx.request().headers())
+            .map_or_else(TraceParent::new_root, |incoming| incoming.child());
+        ctx.request_mut().extensions_mut().insert(trace);
+        next.run(ctx).await
+    }
+}
+
+/// One span's position in a distributed trace, as carried by the
+/// `traceparent` header, plus any `tracestate` to forward.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TraceParent {
+    flags: u8,
+    span_id: String,
+    trace_id: String,
+    tracestate: Option<String>,
+}
+
+impl TraceParent {
+    /// A new span in the same trace, parented to this one. Flags and
+    /// `tracestate` carry over.
+    #[must_use]
+    #[inline]
+    pub fn child(&self) -> Self {
+        Self {
+            flags: self.flags,
+            span_id: generate_id(SPAN_ID_LEN),
+            trace_id: self.trace_id.clone(),
+            tracestate: self.tracestate.clone(),
+        }
+    }
+
+    /// Read `traceparent` (and `tracestate`, if present) from `headers`.
+    /// Returns `None` when `traceparent` is missing or invalid.
+    #[must_use]
+    #[inline]
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let mut parsed = Self::parse(headers.get(TRACEPARENT_HEADER)?.to_str().ok()?)?;
+        parsed.tracestate = headers
+            .get(TRACESTATE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_owned);
+        Some(parsed)
+    }
+
+    /// Write this span as `traceparent` (and `tracestate`, if any) onto
+    /// `headers`, replacing existing values.
+    #[inline]
+    pub fn inject(&self, headers: &mut HeaderMap) {
+        if let Ok(value) = HeaderValue::from_str(&self.to_header()) {
+            headers.insert(TRACEPARENT_HEADER, value);
+        }
+        if let Some(state) = self
+            .tracestate
+            .as_deref()
+            .and_then(|state| HeaderValue::from_str(state).ok())
+        {
+            headers.insert(TRACESTATE_HEADER, state);
+        }
+    }
+
+    /// Whether the caller asked for this trace to be recorded.
+    #[must_use]
+    #[inline]
+    pub fn is_sampled(&self) -> bool {
+        self.flags & FLAG_SAMPLED != 0
+    }
+
+    /// Start a new trace with a fresh trace id and span id, sampled.
+    #[must_use]
+    #[inline]
+    pub fn new_root() -> Self {
+        let trace_id = generate_id(TRACE_ID_LEN);
+        Self {
+            flags: FLAG_SAMPLED,
+            span_id: generate_id(SPAN_ID_LEN),
+            trace_id,
+            tracestate: None,
+        }
+    }
+
+    /// Parse a `traceparent` header value.
+    ///
+    /// Accepts version `00` in its exact four-field form, and later
+    /// versions with extra trailing fields, per the W3C spec. Rejects the
+    /// invalid `ff` version, non-lowercase-hex fields and all-zero ids.
+    #[must_use]
+    #[inline]
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut fields = value.trim().split('-');
+        let version = fields.next()?;
+        let trace_id = fields.next()?;
+        let span_id = fields.next()?;
+        let flags = fields.next()?;
+
+        let well_formed = is_lower_hex(version, 2)
+            && version != "ff"
+            && is_lower_hex(trace_id, TRACE_ID_LEN)
+            && is_lower_hex(span_id, SPAN_ID_LEN)
+            && is_lower_hex(flags, 2)
+            && !is_all_zero(trace_id)
+            && !is_all_zero(span_id);
+        if !well_formed || (version == "00" && fields.next().is_some()) {
+            return None;
+        }
+
+        Some(Self {
+            flags: u8::from_str_radix(flags, 16).ok()?,
+            span_id: span_id.to_owned(),
+            trace_id: trace_id.to_owned(),
+            tracestate: None,
+        })
+    }
+
+    /// This span's 16-hex-digit id.
+    #[must_use]
+    #[inline]
+    pub fn span_id(&self) -> &str {
+        &self.span_id
+    }
+
+    /// Render as a version-`00` `traceparent` header value.
+    #[must_use]
+    #[inline]
+    pub fn to_header(&self) -> String {
+        format!("00-{}-{}-{:02x}", self.trace_id, self.span_id, self.flags)
+    }
+
+    /// The 32-hex-digit id shared by every span in the trace.
+    #[must_use]
+    #[inline]
+    pub fn trace_id(&self) -> &str {
+        &self.trace_id
+    }
+
+    /// Vendor trace state forwarded alongside the `traceparent`.
+    #[must_use]
+    #[inline]
+    pub fn tracestate(&self) -> Option<&str> {
+        self.tracestate.as_deref()
+    }
+}
+
+/// A new random non-zero lowercase-hex id of `len` (at most 32) digits.
+fn generate_id(len: usize) -> String {
+    let mut id = format!("{:032x}", random_bits());
+    id.truncate(len);
+    if is_all_zero(&id) {
+        // Astronomically unlikely; all-zero ids are invalid on the wire.
+        id.replace_range(..1, "1");
+    }
+    id
+}
+
+fn is_all_zero(value: &str) -> bool {
+    value.bytes().all(|byte| byte == b'0')
+}
+
+fn is_lower_hex(value: &str, len: usize) -> bool {
+    value.len() == len
+        && value
+            .bytes()
+            .all(|byte| byte.is_ascii_digit() || (b'a'..=b'f').contains(&byte))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::Body;
+    use crate::http::{Method, StatusCode, Uri, request_builder};
+    use crate::params::PathParams;
+    use crate::proxy::ProxyRequest;
+    use crate::router::RouterService;
+    use futures::executor::block_on;
+    use std::str;
+
+    const SAMPLE: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    fn run_with_header(traceparent: Option<&str>) -> TraceParent {
+        async fn handler(ctx: RequestContext) -> Result<String, EdgeError> {
+            let trace = ctx
+                .request()
+                .extensions()
+                .get::<TraceParent>()
+                .expect("trace context");
+            Ok(trace.to_header())
+        }
+
+        let router = RouterService::builder()
+            .middleware(TraceContext)
+            .get("/", handler)
+            .build();
+        let mut builder = request_builder().method(Method::GET).uri("/");
+        if let Some(value) = traceparent {
+            builder = builder.header(TRACEPARENT_HEADER, value);
+        }
+        let request = builder.body(Body::empty()).expect("request");
+        let response = block_on(router.oneshot(request)).expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let header = str::from_utf8(response.body().as_bytes().expect("buffered"))
+            .expect("utf8")
+            .to_owned();
+        TraceParent::parse(&header).expect("valid traceparent")
+    }
+
+    #[test]
+    fn child_keeps_trace_id_and_flags_with_new_span() {
+        let parent = TraceParent::parse(SAMPLE).expect("parse");
+        let child = parent.child();
+        assert_eq!(child.trace_id(), parent.trace_id());
+        assert_ne!(child.span_id(), parent.span_id());
+        assert_eq!(child.span_id().len(), 16);
+        assert!(child.is_sampled());
+        assert_ne!(child.span_id(), parent.child().span_id());
+    }
+
+    #[test]
+    fn inject_trace_context_writes_child_onto_proxy_request() {
+        let mut request = request_builder()
+            .method(Method::GET)
+            .uri("/")
+            .body(Body::empty())
+            .expect("request");
+        let mut trace = TraceParent::parse(SAMPLE).expect("parse");
+        trace.tracestate = Some("vendor=abc".to_owned());
+        request.extensions_mut().insert(trace);
+        let ctx = RequestContext::new(request, PathParams::default());
+
+        let mut outbound = ProxyRequest::new(Method::GET, Uri::from_static("https://up.example"));
+        outbound.inject_trace_context(&ctx);
+
+        let injected = TraceParent::from_headers(outbound.headers()).expect("injected");
+        assert_eq!(injected.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_ne!(injected.span_id(), "00f067aa0ba902b7");
+        assert_eq!(injected.tracestate(), Some("vendor=abc"));
+    }
+
+    #[test]
+    fn inject_trace_context_without_middleware_is_noop() {
+        let request = request_builder()
+            .method(Method::GET)
+            .uri("/")
+            .body(Body::empty())
+            .expect("request");
+        let ctx = RequestContext::new(request, PathParams::default());
+        let mut outbound = ProxyRequest::new(Method::GET, Uri::from_static("https://up.example"));
+        outbound.inject_trace_context(&ctx);
+        assert!(outbound.headers().get(TRACEPARENT_HEADER).is_none());
+    }
+
+    #[test]
+    fn middleware_continues_incoming_trace() {
+        let trace = run_with_header(Some(SAMPLE));
+        assert_eq!(trace.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_ne!(trace.span_id(), "00f067aa0ba902b7");
+    }
+
+    #[test]
+    fn middleware_starts_root_when_header_absent_or_invalid() {
+        for header in [None, Some("garbage")] {
+            let trace = run_with_header(header);
+            assert_eq!(trace.trace_id().len(), 32);
+            assert_ne!(trace.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+            assert!(trace.is_sampled());
+        }
+    }
+
+    #[test]
+    fn parse_accepts_valid_header() {
+        let trace = TraceParent::parse(SAMPLE).expect("parse");
+        assert_eq!(trace.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(trace.span_id(), "00f067aa0ba902b7");
+        assert!(trace.is_sampled());
+        assert_eq!(trace.to_header(), SAMPLE);
+    }
+
+    #[test]
+    fn parse_accepts_future_version_with_extra_fields() {
+        let trace =
+            TraceParent::parse("cc-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00-extra")
+                .expect("parse");
+        assert!(!trace.is_sampled());
+        assert!(trace.to_header().starts_with("00-"));
+    }
+
+    #[test]
+    fn parse_rejects_malformed_headers() {
+        for value in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e473-00f067aa0ba902b7-01",
+        ] {
+            assert!(TraceParent::parse(value).is_none(), "accepted {value:?}");
+        }
+    }
+}
@@ -0,0 +1,282 @@
+//! Deserialize JSON while recording the object keys the target type ignores.
+//!
+//! Serde's `deny_unknown_fields` has to be set on every type. [`from_value`]
+//! gives the same check for any `DeserializeOwned` type: it deserializes from
+//! a [`Value`] through a wrapper that compares each object's keys with the
+//! fields the struct asks for, and returns the extra keys as dotted paths
+//! (`user.nickname`, `items[2].colour`) next to the value.
+//!
+//! Only structs are checked. Maps accept any key, and the contents of enums,
+//! `#[serde(flatten)]` fields and untagged enums are not tracked, since serde
+//! does not expose their field lists to the deserializer.
+
+use serde::de::value::StringDeserializer;
+use serde::de::{
+    DeserializeOwned, DeserializeSeed, Deserializer, Error as _, IntoDeserializer as _, MapAccess,
+    SeqAccess, Visitor,
+};
+use serde::forward_to_deserialize_any;
+use serde_json::{Error, Value, map};
+use std::iter::Enumerate;
+use std::vec;
+
+/// Visits the items of an array, tracking each one.
+struct ArrayAccess<'track> {
+    path: &'track str,
+    remaining: Enumerate<vec::IntoIter<Value>>,
+    unknown: &'track mut Vec<String>,
+}
+
+/// Visits the entries of an object, tracking each value.
+struct ObjectAccess<'track> {
+    path: &'track str,
+    pending: Option<(String, Value)>,
+    remaining: map::IntoIter,
+    unknown: &'track mut Vec<String>,
+}
+
+/// A [`Value`] at `path` whose unknown keys are pushed onto `unknown`.
+struct Tracked<'track> {
+    path: String,
+    unknown: &'track mut Vec<String>,
+    value: Value,
+}
+
+#[expect(
+    clippy::missing_trait_methods,
+    reason = "the provided `next_key`/`next_value`/`next_entry` variants call the seeded ones"
+)]
+impl<'de> MapAccess<'de> for ObjectAccess<'_> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let Some((key, entry)) = self.remaining.next() else {
+            return Ok(None);
+        };
+        let deserializer: StringDeserializer<Error> = key.clone().into_deserializer();
+        self.pending = Some((key, entry));
+        seed.deserialize(deserializer).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let (key, entry) = self
+            .pending
+            .take()
+            .ok_or_else(|| Error::custom("map value requested before its key"))?;
+        seed.deserialize(Tracked {
+            path: join_key(self.path, &key),
+            unknown: self.unknown,
+            value: entry,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining.len())
+    }
+}
+
+#[expect(
+    clippy::missing_trait_methods,
+    reason = "the provided `next_element` calls `next_element_seed`"
+)]
+impl<'de> SeqAccess<'de> for ArrayAccess<'_> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        let Some((index, item)) = self.remaining.next() else {
+            return Ok(None);
+        };
+        seed.deserialize(Tracked {
+            path: format!("{}[{index}]", self.path),
+            unknown: self.unknown,
+            value: item,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining.len())
+    }
+}
+
+#[expect(
+    clippy::missing_trait_methods,
+    reason = "the remaining defaults (`is_human_readable`, the 128-bit integers) match `Value`'s own"
+)]
+impl<'de> Deserializer<'de> for Tracked<'_> {
+    type Error = Error;
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf unit
+        unit_struct identifier
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let Tracked {
+            path,
+            unknown,
+            value,
+        } = self;
+        match value {
+            Value::Array(items) => visitor.visit_seq(ArrayAccess {
+                remaining: items.into_iter().enumerate(),
+                path: &path,
+                unknown,
+            }),
+            Value::Object(entries) => visitor.visit_map(ObjectAccess {
+                remaining: entries.into_iter(),
+                path: &path,
+                pending: None,
+                unknown,
+            }),
+            scalar @ (Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_)) => {
+                scalar.deserialize_any(visitor)
+            }
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.value.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.value.deserialize_ignored_any(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.value.is_null() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        if let Value::Object(entries) = &self.value {
+            let extra = entries
+                .keys()
+                .filter(|key| !fields.contains(&key.as_str()))
+                .map(|key| join_key(&self.path, key));
+            self.unknown.extend(extra);
+        }
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+}
+
+/// Deserialize `value` as `T`, returning it with the paths of the object keys
+/// that no struct in `T` declares, in document order.
+///
+/// # Errors
+/// Returns the deserialization error when `value` does not fit `T`.
+#[inline]
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<(T, Vec<String>), Error> {
+    let mut unknown = Vec::new();
+    let parsed = T::deserialize(Tracked {
+        path: String::new(),
+        unknown: &mut unknown,
+        value,
+    })?;
+    Ok((parsed, unknown))
+}
+
+fn join_key(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_owned()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item {
+        sku: String,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Order {
+        items: Vec<Item>,
+        note: Option<String>,
+        tags: HashMap<String, String>,
+    }
+
+    #[test]
+    fn exact_payload_has_no_unknown_fields() {
+        let payload =
+            json!({ "items": [{ "sku": "a-1" }], "note": null, "tags": { "any": "key" } });
+        let (order, unknown) = from_value::<Order>(payload).expect("order");
+        assert_eq!(
+            order.items,
+            vec![Item {
+                sku: "a-1".to_owned()
+            }]
+        );
+        assert!(unknown.is_empty(), "{unknown:?}");
+    }
+
+    #[test]
+    fn unknown_fields_are_reported_by_path() {
+        let payload = json!({
+            "coupon": "SAVE10",
+            "items": [{ "sku": "a-1" }, { "qty": 2_i32, "sku": "b-2" }],
+            "tags": { "gift": "yes" }
+        });
+        let (_order, unknown) = from_value::<Order>(payload).expect("order");
+        assert_eq!(unknown, ["coupon", "items[1].qty"]);
+    }
+}
@@ -1,7 +1,7 @@
-use crate::manifest_definitions::{Manifest, StoreDeclaration};
+use crate::manifest_definitions::{Manifest, ManifestCors, StoreDeclaration};
 use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
-use quote::quote;
+use quote::{format_ident, quote};
 use std::env;
 use std::fs;
 use std::path::PathBuf;
@@ -12,8 +12,10 @@ use validator::Validate as _;
 #[derive(Debug)]
 struct AppArgs {
     app_ident: Option<Ident>,
+    middleware: Option<syn::Expr>,
     owns_logging: Option<bool>,
     path: LitStr,
+    settings: Option<syn::Type>,
     state: Option<syn::Expr>,
 }
 
@@ -21,7 +23,9 @@ impl Parse for AppArgs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let path: LitStr = input.parse()?;
         let mut app_ident: Option<Ident> = None;
+        let mut middleware: Option<syn::Expr> = None;
         let mut owns_logging: Option<bool> = None;
+        let mut settings: Option<syn::Type> = None;
         let mut state: Option<syn::Expr> = None;
         let mut seen_keyword = false;
 
@@ -34,6 +38,15 @@ impl Parse for AppArgs {
                 input.parse::<Token![=]>()?;
                 seen_keyword = true;
                 match key.to_string().as_str() {
+                    "middleware" => {
+                        if middleware.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "duplicate `middleware` argument",
+                            ));
+                        }
+                        middleware = Some(input.parse::<syn::Expr>()?);
+                    }
                     "owns_logging" => {
                         if owns_logging.is_some() {
                             return Err(syn::Error::new(
@@ -44,6 +57,15 @@ impl Parse for AppArgs {
                         let value: syn::LitBool = input.parse()?;
                         owns_logging = Some(value.value);
                     }
+                    "settings" => {
+                        if settings.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "duplicate `settings` argument",
+                            ));
+                        }
+                        settings = Some(input.parse::<syn::Type>()?);
+                    }
                     "state" => {
                         if state.is_some() {
                             return Err(syn::Error::new(key.span(), "duplicate `state` argument"));
@@ -54,7 +76,7 @@ impl Parse for AppArgs {
                         return Err(syn::Error::new(
                             key.span(),
                             format!(
-                                "unknown `app!` argument `{other}`; expected `state` or `owns_logging`"
+                                "unknown `app!` argument `{other}`; expected `state`, `settings`, `middleware`, or `owns_logging`"
                             ),
                         ));
                     }
@@ -81,8 +103,10 @@ impl Parse for AppArgs {
         }
         Ok(Self {
             app_ident,
+            middleware,
             owns_logging,
             path,
+            settings,
             state,
         })
     }
@@ -123,18 +147,154 @@ fn build_stores_tokens(manifest: &Manifest) -> TokenStream2 {
     }
 }
 
-fn build_middleware_tokens(manifest: &Manifest) -> Result<Vec<TokenStream2>, String> {
-    manifest
+/// Codegen the `Hooks::install_settings()` impl. `settings = <Type>` loads
+/// the manifest's `[settings]` once per process; every app built after the
+/// first reuses the result.
+fn build_install_settings_tokens(
+    settings: Option<&syn::Type>,
+    manifest_path_lit: &LitStr,
+) -> TokenStream2 {
+    let body = settings.map_or_else(
+        || quote! { Ok(app) },
+        |settings_ty| {
+            quote! {
+                static SETTINGS: ::std::sync::OnceLock<
+                    Result<
+                        edgezero_core::extractor::Settings<#settings_ty>,
+                        edgezero_core::app::AppValidationError,
+                    >,
+                > = ::std::sync::OnceLock::new();
+                let settings = SETTINGS
+                    .get_or_init(|| {
+                        edgezero_core::app::settings_from_manifest(include_str!(#manifest_path_lit))
+                    })
+                    .clone()?;
+                Ok(app.with_state(settings))
+            }
+        },
+    );
+    quote! {
+        fn install_settings(
+            app: edgezero_core::app::App,
+        ) -> Result<edgezero_core::app::App, edgezero_core::app::AppValidationError> {
+            #body
+        }
+    }
+}
+
+/// Render the `with_body_limit` call for `max-body-bytes`, if set. The value
+/// is validated as >= 1 but must also fit the host's `usize`.
+fn build_body_limit_call(manifest: &Manifest) -> Result<Option<TokenStream2>, String> {
+    let limit = manifest
         .app
-        .middleware
-        .iter()
-        .map(|middleware| {
+        .max_body_bytes
+        .map(usize::try_from)
+        .transpose()
+        .map_err(|err| format!("`max-body-bytes` is too large: {err}"))?;
+    Ok(limit.map(|max_bytes| {
+        quote! {
+            builder = builder.with_body_limit(
+                edgezero_core::body_limit::BodyLimit::new(#max_bytes),
+            );
+        }
+    }))
+}
+
+/// Render the `with_body_buffering` call for `body-spill-bytes`, if set.
+/// Like `max-body-bytes`, the value must fit the host's `usize`.
+fn build_body_buffering_call(manifest: &Manifest) -> Result<Option<TokenStream2>, String> {
+    let threshold = manifest
+        .app
+        .body_spill_bytes
+        .map(usize::try_from)
+        .transpose()
+        .map_err(|err| format!("`body-spill-bytes` is too large: {err}"))?;
+    Ok(threshold.map(|max_in_memory| {
+        quote! {
+            builder = builder.with_body_buffering(
+                edgezero_core::body_spool::BodyBuffering::Spill { threshold: #max_in_memory },
+            );
+        }
+    }))
+}
+
+/// Render the body limit and buffering calls together.
+fn build_body_calls(manifest: &Manifest) -> Result<TokenStream2, String> {
+    let limit_call = build_body_limit_call(manifest)?;
+    let buffering_call = build_body_buffering_call(manifest)?;
+    Ok(quote! {
+        #limit_call
+        #buffering_call
+    })
+}
+
+/// Render the `Cors` middleware for a `[cors]` section. The manifest was
+/// validated, origins included, so `build` does not fail.
+fn build_cors_call(cors: &ManifestCors) -> TokenStream2 {
+    let origins = &cors.allow_origins;
+    let credentials = cors.allow_credentials;
+    let headers = (!cors.allow_headers.is_empty()).then(|| {
+        let names = &cors.allow_headers;
+        quote! { .allow_headers([#(#names),*]) }
+    });
+    let methods = (!cors.allow_methods.is_empty()).then(|| {
+        let idents = cors
+            .allow_methods
+            .iter()
+            .map(|method| format_ident!("{}", method.as_str()));
+        quote! { .allow_methods([#(edgezero_core::http::Method::#idents),*]) }
+    });
+    let expose = (!cors.expose_headers.is_empty()).then(|| {
+        let names = &cors.expose_headers;
+        quote! { .expose_headers([#(#names),*]) }
+    });
+    let max_age = cors.max_age_secs.map(|secs| {
+        quote! { .max_age(::std::time::Duration::from_secs(#secs)) }
+    });
+    quote! {
+        builder = builder.middleware(
+            edgezero_core::cors::Cors::builder()
+                #(.allow_origin(#origins))*
+                .allow_credentials(#credentials)
+                #headers
+                #methods
+                #expose
+                #max_age
+                .build()
+                .unwrap_or_else(|err| panic!("`[cors]` in edgezero.toml: {err}")),
+        );
+    }
+}
+
+/// Render the `[cors]` middleware, outermost, then `[app] middleware` in
+/// order. An entry containing `::` is a path to a middleware value; any
+/// other entry is a name looked up in the `MiddlewareRegistry` passed as
+/// `middleware = <expr>`, bound as `middleware_registry` in the generated
+/// `build_router`. An unregistered name fails `App::validate` with
+/// `AppValidationError::UnknownMiddleware` rather than panicking.
+fn build_middleware_tokens(
+    manifest: &Manifest,
+    has_registry: bool,
+) -> Result<Vec<TokenStream2>, String> {
+    let cors = manifest.cors.as_ref().map(build_cors_call).map(Ok);
+    let listed = manifest.app.middleware.iter().map(|middleware| {
+        if middleware.contains("::") {
             let path = parse_handler_path(middleware)?;
-            Ok(quote! {
+            return Ok(quote! {
                 builder = builder.middleware(#path);
-            })
+            });
+        }
+        if !has_registry {
+            return Err(format!(
+                "`[app] middleware` entry `{middleware}` is a name, not a path; pass its registry as `app!(..., middleware = <MiddlewareRegistry>)`"
+            ));
+        }
+        let name_lit = LitStr::new(middleware.trim(), Span::call_site());
+        Ok(quote! {
+            builder = builder.named_middleware_or_report([#name_lit], &middleware_registry);
         })
-        .collect()
+    });
+    cors.into_iter().chain(listed).collect()
 }
 
 fn build_route_tokens(manifest: &Manifest) -> Result<Vec<TokenStream2>, String> {
@@ -147,7 +307,12 @@ fn build_route_tokens(manifest: &Manifest) -> Result<Vec<TokenStream2>, String>
         let path_lit = LitStr::new(&trigger.path, Span::call_site());
 
         for method in trigger.methods() {
-            tokens.push(route_for_method(method, &path_lit, &handler_path));
+            tokens.push(match trigger.timeout_ms {
+                Some(timeout_ms) => {
+                    route_with_timeout(method, &path_lit, &handler_path, timeout_ms)
+                }
+                None => route_for_method(method, &path_lit, &handler_path),
+            });
         }
     }
     Ok(tokens)
@@ -197,7 +362,7 @@ pub fn expand_app(input: TokenStream) -> TokenStream {
         .unwrap_or_else(|| "EdgeZero App".to_owned());
     let app_name_lit = LitStr::new(&app_name, Span::call_site());
 
-    let middleware_tokens = match build_middleware_tokens(&manifest) {
+    let middleware_tokens = match build_middleware_tokens(&manifest, args.middleware.is_some()) {
         Ok(tokens) => tokens,
         Err(msg) => return quote!(compile_error!(#msg);).into(),
     };
@@ -206,6 +371,13 @@ pub fn expand_app(input: TokenStream) -> TokenStream {
         Err(msg) => return quote!(compile_error!(#msg);).into(),
     };
     let stores_tokens = build_stores_tokens(&manifest);
+    let body_calls = match build_body_calls(&manifest) {
+        Ok(calls) => calls,
+        Err(reason) => {
+            let msg = format!("{reason} in {}", manifest_path.display());
+            return quote!(compile_error!(#msg);).into();
+        }
+    };
 
     let manifest_path_lit = LitStr::new(&manifest_path.to_string_lossy(), Span::call_site());
     let owns_logging_lit = args.owns_logging.unwrap_or(false);
@@ -214,9 +386,16 @@ pub fn expand_app(input: TokenStream) -> TokenStream {
     let state_call = args.state.as_ref().map(|state_expr| {
         quote! { builder = builder.with_state(#state_expr); }
     });
+    let install_settings =
+        build_install_settings_tokens(args.settings.as_ref(), &manifest_path_lit);
+    let middleware_registry = args.middleware.as_ref().map(|registry_expr| {
+        quote! {
+            let middleware_registry: edgezero_core::middleware::MiddlewareRegistry = #registry_expr;
+        }
+    });
 
     // The emitted `Hooks` impl below explicitly defines `configure`,
-    // `owns_logging`, and `build_app` even though their bodies mirror the trait
+    // `owns_logging`, `build_app`, and `install_settings` even though their bodies mirror the trait
     // defaults. This is required because `missing_trait_methods` (restriction =
     // deny) forbids relying on trait defaults in the impl. If those `Hooks`
     // defaults change, update these emitted bodies to match.
@@ -243,6 +422,8 @@ pub fn expand_app(input: TokenStream) -> TokenStream {
 
             #stores_tokens
 
+            #install_settings
+
             fn build_app() -> edgezero_core::app::App {
                 let mut app = edgezero_core::app::App::with_name(Self::routes(), Self::name());
                 Self::configure(&mut app);
@@ -254,6 +435,8 @@ pub fn expand_app(input: TokenStream) -> TokenStream {
             let mut builder = edgezero_core::router::RouterService::builder();
             builder = builder.with_manifest_json(#manifest_json_lit);
             #state_call
+            #body_calls
+            #middleware_registry
             #(#middleware_tokens)*
             #(#route_tokens)*
             builder.build()
@@ -333,6 +516,24 @@ fn route_for_method(method: &str, path: &LitStr, handler: &syn::ExprPath) -> Tok
     }
 }
 
+fn route_with_timeout(
+    method: &str,
+    path: &LitStr,
+    handler: &syn::ExprPath,
+    timeout_ms: u64,
+) -> TokenStream2 {
+    let method_bytes = syn::LitByteStr::new(method.as_bytes(), Span::call_site());
+    quote! {
+        builder = builder.route_with_timeout(
+            #path,
+            edgezero_core::http::Method::from_bytes(#method_bytes)
+                .expect("invalid HTTP method in manifest"),
+            #handler,
+            ::std::time::Duration::from_millis(#timeout_ms),
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{AppArgs, Manifest, build_route_tokens, parse_handler_path};
@@ -503,6 +704,37 @@ handler = "crate::handlers::root"
         assert_eq!(tokens.len(), 3);
     }
 
+    #[test]
+    fn build_route_tokens_wraps_routes_with_manifest_timeout() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+[app]
+name = "demo"
+entry = "crates/demo-core"
+
+[[triggers.http]]
+path = "/slow"
+methods = ["GET", "POST"]
+handler = "crate::handlers::slow"
+timeout-ms = 1500
+"#,
+        )
+        .expect("manifest TOML should parse");
+        let tokens = build_route_tokens(&manifest).expect("valid manifest builds routes");
+        assert_eq!(tokens.len(), 2);
+        for token in &tokens {
+            let rendered = token.to_string();
+            assert!(
+                rendered.contains("route_with_timeout"),
+                "timed route should use route_with_timeout, got: {rendered}"
+            );
+            assert!(
+                rendered.contains("from_millis (1500u64)"),
+                "timeout should be threaded through, got: {rendered}"
+            );
+        }
+    }
+
     #[test]
     fn build_route_tokens_skips_trigger_without_handler() {
         let manifest: Manifest = toml::from_str(
@@ -0,0 +1,38 @@
+//! Integration coverage: `max-body-bytes` under `[app]` makes the generated
+//! router cap request bodies, overriding whatever limit the adapter set.
+
+use edgezero_core::context::RequestContext;
+use edgezero_core::error::EdgeError;
+
+edgezero_core::app!("tests/fixtures/body_limit.toml", BodyLimitApp);
+
+async fn upload(_ctx: RequestContext) -> Result<&'static str, EdgeError> {
+    Ok("stored")
+}
+
+#[cfg(test)]
+mod tests {
+    use edgezero_core::body::Body;
+    use edgezero_core::body_limit::BodyLimit;
+    use edgezero_core::http::{Method, StatusCode, request_builder};
+    use futures::executor::block_on;
+
+    fn post(body: &'static str) -> StatusCode {
+        let mut request = request_builder()
+            .method(Method::POST)
+            .uri("/upload")
+            .body(Body::from(body))
+            .expect("request");
+        // The adapter default the manifest value overrides.
+        request.extensions_mut().insert(BodyLimit::new(1024));
+        block_on(super::build_router().oneshot(request))
+            .expect("response")
+            .status()
+    }
+
+    #[test]
+    fn manifest_max_body_bytes_overrides_the_adapter_default() {
+        assert_eq!(post("12345678"), StatusCode::OK);
+        assert_eq!(post("123456789"), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
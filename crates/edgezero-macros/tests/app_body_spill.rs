@@ -0,0 +1,40 @@
+//! Integration coverage: `body-spill-bytes` under `[app]` makes the
+//! generated router reject buffered bodies past the threshold when the
+//! adapter installed no spool to write them to.
+
+use edgezero_core::context::RequestContext;
+use edgezero_core::error::EdgeError;
+use edgezero_core::extractor::{FromRequest as _, Json};
+
+edgezero_core::app!("tests/fixtures/body_spill.toml", BodySpillApp);
+
+async fn upload(ctx: RequestContext) -> Result<String, EdgeError> {
+    let Json(value) = Json::<serde_json::Value>::from_request(&ctx).await?;
+    Ok(value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use edgezero_core::body::Body;
+    use edgezero_core::http::header::CONTENT_TYPE;
+    use edgezero_core::http::{Method, StatusCode, request_builder};
+    use futures::executor::block_on;
+
+    fn post(body: &'static str) -> StatusCode {
+        let request = request_builder()
+            .method(Method::POST)
+            .uri("/upload")
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .expect("request");
+        block_on(super::build_router().oneshot(request))
+            .expect("response")
+            .status()
+    }
+
+    #[test]
+    fn manifest_body_spill_bytes_rejects_larger_bodies_without_a_spool() {
+        assert_eq!(post("[1,2,3]"), StatusCode::OK);
+        assert_eq!(post("[1,2,3,4,5]"), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
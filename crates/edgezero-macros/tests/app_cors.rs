@@ -0,0 +1,67 @@
+//! Integration coverage: a `[cors]` section makes the generated router
+//! answer preflights and add the `Access-Control-*` headers per the
+//! manifest's policy.
+
+use edgezero_core::context::RequestContext;
+use edgezero_core::error::EdgeError;
+
+edgezero_core::app!("tests/fixtures/cors.toml", CorsApp);
+
+async fn items(_ctx: RequestContext) -> Result<&'static str, EdgeError> {
+    Ok("items")
+}
+
+#[cfg(test)]
+mod tests {
+    use edgezero_core::body::Body;
+    use edgezero_core::http::header::{
+        ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
+        ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS,
+        ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_METHOD, ORIGIN, VARY,
+    };
+    use edgezero_core::http::{Method, Response, StatusCode, request_builder};
+    use futures::executor::block_on;
+
+    fn send(method: Method, origin: &str) -> Response {
+        let request = request_builder()
+            .method(method)
+            .uri("/items")
+            .header(ORIGIN, origin)
+            .header(ACCESS_CONTROL_REQUEST_METHOD, "PUT")
+            .body(Body::empty())
+            .expect("request");
+        block_on(super::build_router().oneshot(request)).expect("response")
+    }
+
+    #[test]
+    fn manifest_cors_answers_preflights_per_the_policy() {
+        let preflight = send(Method::OPTIONS, "https://eu.example.org");
+        assert_eq!(preflight.status(), StatusCode::NO_CONTENT);
+        let headers = preflight.headers();
+        assert_eq!(
+            headers[ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://eu.example.org"
+        );
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_METHODS], "GET, PUT");
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_HEADERS], "content-type");
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert_eq!(headers[ACCESS_CONTROL_MAX_AGE], "600");
+    }
+
+    #[test]
+    fn manifest_cors_decorates_allowed_origins_only() {
+        let allowed = send(Method::GET, "https://app.example.com");
+        assert_eq!(allowed.status(), StatusCode::OK);
+        let headers = allowed.headers();
+        assert_eq!(
+            headers[ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://app.example.com"
+        );
+        assert_eq!(headers[ACCESS_CONTROL_EXPOSE_HEADERS], "x-request-id");
+        assert_eq!(headers[VARY], "Origin");
+
+        let other = send(Method::GET, "https://evil.example.net");
+        assert_eq!(other.status(), StatusCode::OK);
+        assert!(!other.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+}
@@ -0,0 +1,64 @@
+//! Integration coverage: `[app] middleware` entries that are names resolve
+//! through the registry passed as `app!(..., middleware = ...)` and run in
+//! manifest order, interleaved with entries that are paths.
+
+use async_trait::async_trait;
+use edgezero_core::context::RequestContext;
+use edgezero_core::error::EdgeError;
+use edgezero_core::http::{HeaderValue, Response};
+use edgezero_core::middleware::{Middleware, MiddlewareRegistry, Next};
+
+edgezero_core::app!(
+    "tests/fixtures/named_middleware.toml",
+    NamedMiddlewareApp,
+    middleware = registry()
+);
+
+/// Appends its name to the request's `x-order` header.
+struct Mark(&'static str);
+
+#[async_trait(?Send)]
+impl Middleware for Mark {
+    async fn handle(&self, mut ctx: RequestContext, next: Next<'_>) -> Result<Response, EdgeError> {
+        ctx.request_mut()
+            .headers_mut()
+            .append("x-order", HeaderValue::from_static(self.0));
+        next.run(ctx).await
+    }
+}
+
+async fn order(ctx: RequestContext) -> Result<String, EdgeError> {
+    let marks: Vec<&str> = ctx
+        .request()
+        .headers()
+        .get_all("x-order")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+    Ok(marks.join(","))
+}
+
+fn registry() -> MiddlewareRegistry {
+    MiddlewareRegistry::new()
+        .register("second", || Mark("second"))
+        .register("first", || Mark("first"))
+}
+
+#[cfg(test)]
+mod tests {
+    use edgezero_core::body::Body;
+    use edgezero_core::http::{Method, StatusCode, request_builder};
+    use futures::executor::block_on;
+
+    #[test]
+    fn named_middleware_runs_in_manifest_order() {
+        let request = request_builder()
+            .method(Method::GET)
+            .uri("/order")
+            .body(Body::empty())
+            .expect("request");
+        let response = block_on(super::build_router().oneshot(request)).expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body().as_bytes(), Some(&b"first,second"[..]));
+    }
+}
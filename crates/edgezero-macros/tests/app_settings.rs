@@ -0,0 +1,48 @@
+//! Integration coverage: `settings = <Type>` makes the generated hooks load
+//! the manifest's `[settings]` table when the app is built and validated, so
+//! handlers read it through the `Settings` extractor.
+
+use edgezero_core::context::RequestContext;
+use edgezero_core::error::EdgeError;
+use edgezero_core::extractor::{FromRequest as _, Settings};
+
+#[derive(serde::Deserialize)]
+struct GreetSettings {
+    greeting: String,
+}
+
+edgezero_core::app!(
+    "tests/fixtures/settings.toml",
+    SettingsApp,
+    settings = GreetSettings
+);
+
+async fn greet(ctx: RequestContext) -> Result<String, EdgeError> {
+    let settings = Settings::<GreetSettings>::from_request(&ctx).await?;
+    Ok(settings.greeting.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SettingsApp;
+    use edgezero_core::app::Hooks as _;
+    use edgezero_core::body::Body;
+    use edgezero_core::http::{Method, StatusCode, request_builder};
+    use futures::executor::block_on;
+
+    #[test]
+    fn build_validated_app_registers_manifest_settings() {
+        let app = SettingsApp::build_validated_app().expect("valid settings");
+        let request = request_builder()
+            .method(Method::GET)
+            .uri("/greet")
+            .body(Body::empty())
+            .expect("request");
+        let response = block_on(app.router().oneshot(request)).expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.body().as_bytes().expect("buffered"),
+            b"hello from the manifest"
+        );
+    }
+}
@@ -0,0 +1,44 @@
+//! Integration coverage: an `[app] middleware` name missing from the
+//! registry fails `App::validate` with `UnknownMiddleware`, so adapters'
+//! `run_app` reports it instead of the router panicking.
+
+use edgezero_core::context::RequestContext;
+use edgezero_core::error::EdgeError;
+use edgezero_core::middleware::{MiddlewareRegistry, RequestLogger};
+
+edgezero_core::app!(
+    "tests/fixtures/unknown_middleware.toml",
+    UnknownMiddlewareApp,
+    middleware = registry()
+);
+
+async fn ping(_ctx: RequestContext) -> Result<&'static str, EdgeError> {
+    Ok("pong")
+}
+
+fn registry() -> MiddlewareRegistry {
+    MiddlewareRegistry::new().register("registered", || RequestLogger)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UnknownMiddlewareApp;
+    use edgezero_core::app::{AppValidationError, Hooks as _};
+
+    #[test]
+    fn unregistered_middleware_name_fails_validation() {
+        let Err(err) = UnknownMiddlewareApp::build_validated_app() else {
+            panic!("an unregistered middleware name should fail validation");
+        };
+        assert_eq!(
+            err,
+            AppValidationError::UnknownMiddleware {
+                name: "missing".to_owned()
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "`[app] middleware` entry `missing` is not registered"
+        );
+    }
+}